@@ -0,0 +1,84 @@
+//! Progress of a search split across workers, totalled on the main thread.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{AggregateProgress, ProgressSnapshot, VanityError};
+use wasm_bindgen::prelude::*;
+
+use crate::clock::now_ms;
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// Totals the `progress()` snapshots workers post into one view of the
+/// search: attempts, a smoothed combined rate, the ETA and the chance of a
+/// match by now. Workers that restart never make the totals go backwards,
+/// and workers that go quiet stop counting towards the rate.
+#[wasm_bindgen]
+pub struct ProgressAggregator {
+    inner: vanity_core::ProgressAggregator,
+}
+
+#[wasm_bindgen]
+impl ProgressAggregator {
+    /// `expected_attempts` is the pattern's mean attempts per match, e.g.
+    /// from `estimate_attempts`. Workers silent for
+    /// `stale_after_ms` (10 s by default) drop out of the rate.
+    #[wasm_bindgen(constructor)]
+    pub fn new(expected_attempts: f64, stale_after_ms: Option<f64>) -> ProgressAggregator {
+        ProgressAggregator {
+            inner: vanity_core::ProgressAggregator::with_windows(
+                1.0 / expected_attempts,
+                vanity_core::DEFAULT_RATE_WINDOW_MS,
+                stale_after_ms.unwrap_or(vanity_core::DEFAULT_STALE_AFTER_MS),
+            ),
+        }
+    }
+
+    /// Takes a snapshot worker `worker_id` posted, as received now.
+    #[wasm_bindgen]
+    pub fn update(
+        &mut self,
+        worker_id: u32,
+        progress: Ts<ProgressSnapshot>,
+    ) -> Result<(), VanityJsError> {
+        let progress = progress
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid progress: {e}")))?;
+        self.inner.update(worker_id, &progress, now_ms());
+        Ok(())
+    }
+
+    /// Attempts of every worker so far, across restarts.
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.inner.attempts()
+    }
+
+    /// Combined attempts per second of the workers still reporting.
+    #[wasm_bindgen(getter)]
+    pub fn rate(&self) -> Option<f64> {
+        self.inner.rate(now_ms())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn eta_ms(&self) -> Option<f64> {
+        self.inner.snapshot(now_ms()).eta_ms
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn probability_found(&self) -> f64 {
+        self.inner.snapshot(now_ms()).probability_found
+    }
+
+    /// Milliseconds since `worker_id` last reported; `undefined` for a
+    /// worker never heard from.
+    #[wasm_bindgen]
+    pub fn stale_ms(&self, worker_id: u32) -> Option<f64> {
+        self.inner.stale_ms(worker_id, now_ms())
+    }
+
+    /// Everything above, with a row per worker, ready to render.
+    #[wasm_bindgen]
+    pub fn snapshot(&self) -> Result<Ts<AggregateProgress>, VanityJsError> {
+        Ok(self.inner.snapshot(now_ms()).into_ts().map_err(internal)?)
+    }
+}
@@ -0,0 +1,103 @@
+//! Approximate prefix or suffix search.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    ApproxMatch, ApproxPattern, ApproxSearcher, ApproxSide, KeyRole, Pubkey, VanityError,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// Searches for a prefix or suffix that may be up to `max_edits`
+/// characters off, e.g. "M0on" for "Moon", for users who would rather wait
+/// less than get the exact spelling. Each match says how many edits it is
+/// from the pattern, so exact hits can be listed first.
+#[wasm_bindgen]
+pub struct VanityApproxSearcher {
+    searcher: ApproxSearcher,
+}
+
+#[wasm_bindgen]
+impl VanityApproxSearcher {
+    /// Exactly one of `prefix` and `suffix`. `max_edits` is at most 2 and
+    /// less than the pattern's length; edits are substitutions, plus
+    /// insertions and deletions with `allow_indels`.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        prefix: Option<String>,
+        suffix: Option<String>,
+        max_edits: u8,
+        allow_indels: Option<bool>,
+        case_insensitive: bool,
+        count_offset: Option<u64>,
+    ) -> Result<VanityApproxSearcher, VanityJsError> {
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let (side, pattern) = match (prefix, suffix) {
+            (Some(prefix), None) => (ApproxSide::Prefix, prefix),
+            (None, Some(suffix)) => (ApproxSide::Suffix, suffix),
+            (Some(_), Some(_)) => {
+                return Err(VanityError::ConflictingOptions(
+                    "approximate matching takes a prefix or a suffix, not both".into(),
+                )
+                .into())
+            }
+            (None, None) => return Err(VanityError::EmptyPattern.into()),
+        };
+        let pattern = ApproxPattern::new(
+            side,
+            &pattern,
+            max_edits,
+            allow_indels.unwrap_or(false),
+            case_insensitive,
+        )?;
+        Ok(VanityApproxSearcher {
+            searcher: ApproxSearcher::new(
+                base_pubkey.as_bytes(),
+                owner_pubkey.as_bytes(),
+                pattern,
+                count_offset.unwrap_or(0),
+            ),
+        })
+    }
+
+    /// Tries up to `batch_size` more counters and returns the first match,
+    /// if any.
+    #[wasm_bindgen]
+    pub fn search_batch(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Option<Ts<ApproxMatch>>, VanityJsError> {
+        let Some(found) = self.searcher.search_batch(batch_size) else {
+            return Ok(None);
+        };
+        log_info!(
+            "match {} ({} edits) after {} attempts",
+            found.address,
+            found.edits,
+            found.attempts
+        );
+        Ok(Some(found.into_ts().map_err(internal)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+
+    /// Counting substitutions only, so with `allow_indels` matches come
+    /// somewhat sooner.
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.searcher.pattern().probability()
+    }
+}
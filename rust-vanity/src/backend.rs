@@ -0,0 +1,208 @@
+//! What this build and host support, and which search implementation runs.
+
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+use tsify::{Ts, Tsify};
+use vanity_core::VanityError;
+use wasm_bindgen::prelude::*;
+
+use crate::device;
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// A search implementation. Only `scalar` is built so far; the others are
+/// named so callers can already ask for them and fall back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    Scalar,
+    Simd,
+    Threads,
+    Webgpu,
+}
+
+impl Backend {
+    /// Fastest first.
+    const BY_SPEED: [Backend; 4] = [
+        Backend::Webgpu,
+        Backend::Threads,
+        Backend::Simd,
+        Backend::Scalar,
+    ];
+
+    /// Whether this build has an implementation the host can run.
+    fn is_available(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            Backend::Simd | Backend::Threads | Backend::Webgpu => false,
+        }
+    }
+}
+
+thread_local! {
+    static ACTIVE: Cell<Backend> = const { Cell::new(Backend::Scalar) };
+}
+
+/// Returned by `capabilities()`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct Capabilities {
+    /// Crate version.
+    pub version: String,
+    /// Cargo features the build was compiled with.
+    pub features: Vec<String>,
+    /// Whether the module was compiled with wasm SIMD.
+    pub simd_compiled: bool,
+    /// Whether the engine can run wasm SIMD at all.
+    pub simd_supported: bool,
+    /// Whether the module was compiled with shared-memory atomics.
+    pub threads_compiled: bool,
+    pub shared_array_buffer: bool,
+    /// Whether the page is cross-origin isolated, which browsers require
+    /// before sharing memory between workers.
+    pub cross_origin_isolated: bool,
+    /// Whether `navigator.gpu` exists; no adapter is requested.
+    pub webgpu: bool,
+    /// `navigator.hardwareConcurrency`, or 0 if unknown.
+    pub hardware_concurrency: u32,
+    /// Backends this build can run here, fastest first.
+    pub backends: Vec<Backend>,
+    pub active_backend: Backend,
+}
+
+/// Returned by `select_backend`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct BackendSelection {
+    pub active: Backend,
+    #[tsify(optional)]
+    pub requested: Option<Backend>,
+    /// Why the requested backend isn't the active one.
+    #[tsify(optional)]
+    pub fallback_reason: Option<String>,
+}
+
+/// Cargo features this build was compiled with, e.g. to hide the UI of
+/// search modes left out: "keypair" (`VanityKeypairSearcher` and
+/// `VanityIncrementalKeypairSearcher`), "metadata"
+/// (`VanityMetadataSearcher`), "squads" (`VanitySquadsSearcher`) and
+/// "passphrase" (`VanityPassphraseSearcher`, `seed_from_passphrase`).
+/// `createWithSeed` search is always built and has no feature.
+#[wasm_bindgen]
+pub fn features() -> Vec<String> {
+    [
+        (
+            "console_error_panic_hook",
+            cfg!(feature = "console_error_panic_hook"),
+        ),
+        ("wee_alloc", cfg!(feature = "wee_alloc")),
+        ("keypair", cfg!(feature = "keypair")),
+        ("metadata", cfg!(feature = "metadata")),
+        ("squads", cfg!(feature = "squads")),
+        ("passphrase", cfg!(feature = "passphrase")),
+        ("token2022", cfg!(feature = "token2022")),
+    ]
+    .into_iter()
+    .filter(|&(_, enabled)| enabled)
+    .map(|(feature, _)| feature.to_string())
+    .collect()
+}
+
+fn detect() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: features(),
+        simd_compiled: cfg!(target_feature = "simd128"),
+        simd_supported: device::simd_supported(),
+        threads_compiled: cfg!(target_feature = "atomics"),
+        shared_array_buffer: device::shared_array_buffer(),
+        cross_origin_isolated: device::cross_origin_isolated(),
+        webgpu: device::webgpu(),
+        hardware_concurrency: device::hardware_concurrency(),
+        backends: Backend::BY_SPEED
+            .into_iter()
+            .filter(|backend| backend.is_available())
+            .collect(),
+        active_backend: ACTIVE.get(),
+    }
+}
+
+fn select(requested: Option<Backend>) -> BackendSelection {
+    let active = match requested {
+        Some(backend) if backend.is_available() => backend,
+        _ => Backend::BY_SPEED
+            .into_iter()
+            .find(|backend| backend.is_available())
+            .unwrap_or(Backend::Scalar),
+    };
+    ACTIVE.set(active);
+    let fallback_reason = requested
+        .filter(|&backend| backend != active)
+        .map(|backend| {
+            format!("{backend:?} is not implemented in this build; using {active:?}").to_lowercase()
+        });
+    if let Some(reason) = &fallback_reason {
+        log_info!("{reason}");
+    }
+    BackendSelection {
+        active,
+        requested,
+        fallback_reason,
+    }
+}
+
+/// What this build and environment support: SIMD (compiled in, and usable
+/// by the engine), shared memory for threads, WebGPU, the core count, the
+/// crate version and features, and the backends that can run. Never
+/// throws for missing globals, e.g. in Node or a worker.
+#[wasm_bindgen]
+pub fn capabilities() -> Result<Ts<Capabilities>, VanityJsError> {
+    Ok(detect().into_ts().map_err(internal)?)
+}
+
+/// Makes `preference` the active backend if it can run here, and otherwise
+/// the fastest one that can; without a preference, picks the fastest.
+/// Applies to the calling thread.
+#[wasm_bindgen]
+pub fn select_backend(
+    preference: Option<Ts<Backend>>,
+) -> Result<Ts<BackendSelection>, VanityJsError> {
+    let requested = preference
+        .map(|preference| preference.to_rust())
+        .transpose()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid backend: {e}")))?;
+    Ok(select(requested).into_ts().map_err(internal)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection_without_a_js_host() {
+        let capabilities = detect();
+        assert_eq!(capabilities.version, env!("CARGO_PKG_VERSION"));
+        assert!(!capabilities.simd_supported);
+        assert!(!capabilities.shared_array_buffer);
+        assert!(!capabilities.cross_origin_isolated);
+        assert!(!capabilities.webgpu);
+        assert_eq!(capabilities.backends, [Backend::Scalar]);
+        assert_eq!(capabilities.features, features());
+        assert_eq!(
+            features().contains(&"passphrase".to_string()),
+            cfg!(feature = "passphrase")
+        );
+    }
+
+    #[test]
+    fn test_select_falls_back_to_scalar() {
+        let selection = select(Some(Backend::Webgpu));
+        assert_eq!(selection.active, Backend::Scalar);
+        assert_eq!(
+            selection.fallback_reason.as_deref(),
+            Some("webgpu is not implemented in this build; using scalar")
+        );
+        assert_eq!(select(None).fallback_reason, None);
+        assert_eq!(select(Some(Backend::Scalar)).fallback_reason, None);
+        assert_eq!(detect().active_backend, Backend::Scalar);
+    }
+}
@@ -0,0 +1,28 @@
+//! Base58 pubkey helpers, so the UI needn't bundle its own bs58 library.
+
+use vanity_core::Pubkey;
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+
+/// Base58-encodes a 32-byte pubkey; fails with `BadPubkeyLength` for any
+/// other length.
+#[wasm_bindgen]
+pub fn encode_pubkey(bytes: &[u8]) -> Result<String, VanityJsError> {
+    Ok(Pubkey::from_bytes(bytes)?.to_string())
+}
+
+/// Decodes a base58 pubkey. Fails with `InvalidBase58` (with `position`)
+/// at the first character outside the alphabet, such as 0, O, I or l, and
+/// with `BadPubkeyLength` if it doesn't decode to exactly 32 bytes.
+#[wasm_bindgen]
+pub fn decode_pubkey(encoded: &str) -> Result<Vec<u8>, VanityJsError> {
+    Ok(Pubkey::from_base58(encoded)?.as_bytes().to_vec())
+}
+
+/// Whether `s` is non-empty and uses only base58 characters, whatever it
+/// decodes to.
+#[wasm_bindgen]
+pub fn is_valid_base58(s: &str) -> bool {
+    !s.is_empty() && vanity_core::decode_base58(s).is_ok()
+}
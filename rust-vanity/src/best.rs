@@ -0,0 +1,215 @@
+//! Best-effort search, against a deadline or batch by batch across
+//! workers.
+
+use std::sync::atomic::AtomicI32;
+
+use tsify::Ts;
+use vanity_core::{
+    seed_to_string, BestMatch, BestSearcher, KeyRole, Pubkey, Scoring, SharedBest, VanityError,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::clock::now_ms;
+use crate::error::VanityJsError;
+
+/// Attempts between deadline checks.
+const BATCH_SIZE: u32 = 1_000;
+
+/// The best address `search_best` found.
+#[wasm_bindgen]
+pub struct BestResult {
+    address: String,
+    seed: String,
+    attempts: u64,
+    score: u32,
+}
+
+#[wasm_bindgen]
+impl BestResult {
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> String {
+        self.seed.clone()
+    }
+
+    /// Attempts made when this address was found.
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.attempts
+    }
+
+    /// The address's score under the scoring used; higher is better.
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+}
+
+impl From<BestMatch> for BestResult {
+    fn from(best: BestMatch) -> BestResult {
+        BestResult {
+            address: best.address,
+            seed: seed_to_string(&best.seed),
+            attempts: best.attempts,
+            score: best.score,
+        }
+    }
+}
+
+/// Searches for about `duration_ms` with no hard pattern and returns the
+/// highest-scoring address seen, by one of the built-in heuristics:
+/// `{ kind: "leading_match", pattern, case_insensitive? }` (characters of
+/// `pattern` the address starts with), `{ kind: "trailing_run" }` (the run
+/// of one character it ends with) or `{ kind: "dictionary_prefix" }` (the
+/// longest word from a built-in list it starts with). Always makes at least
+/// one batch of attempts, so a result is always returned.
+#[wasm_bindgen]
+pub fn search_best(
+    base_pubkey: &[u8],
+    owner_pubkey: &[u8],
+    duration_ms: f64,
+    scoring: Ts<Scoring>,
+    count_offset: Option<u64>,
+) -> Result<BestResult, VanityJsError> {
+    let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+    let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+    let scoring = scoring
+        .to_rust()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid scoring: {e}")))?;
+    let mut searcher = BestSearcher::new(
+        base_pubkey.as_bytes(),
+        owner_pubkey.as_bytes(),
+        scoring,
+        count_offset.unwrap_or(0),
+    )?;
+
+    let start_ms = now_ms();
+    loop {
+        searcher.search_batch(BATCH_SIZE);
+        if now_ms() - start_ms >= duration_ms {
+            break;
+        }
+    }
+    let best = searcher
+        .best()
+        .cloned()
+        .ok_or_else(|| VanityError::Internal("no address scored".into()))?;
+    log_info!(
+        "best address {} scored {} after {} attempts",
+        best.address,
+        best.score,
+        searcher.attempts()
+    );
+    Ok(BestResult::from(best))
+}
+
+/// A best score held in an `Int32Array` over a `SharedArrayBuffer`, read
+/// and raised with `Atomics` so every worker sees the others' reports.
+struct SharedScore(js_sys::Int32Array);
+
+impl SharedBest for SharedScore {
+    fn load(&self) -> i32 {
+        // Can't fail on an array `set_shared_best` accepted; if it somehow
+        // did, reporting too much beats hiding a result.
+        js_sys::Atomics::load(&self.0, 0).unwrap_or(i32::MIN)
+    }
+
+    fn compare_exchange(&self, current: i32, new: i32) -> Result<i32, i32> {
+        let found = js_sys::Atomics::compare_exchange(&self.0, 0, current, new).unwrap_or(current);
+        if found == current {
+            Ok(found)
+        } else {
+            Err(found)
+        }
+    }
+}
+
+/// `search_best` for one of several workers, batch by batch: each batch
+/// returns the best address so far only if it beats every address reported
+/// before it, by this worker or, with `set_shared_best`, by any worker.
+#[wasm_bindgen]
+pub struct VanityBestSearcher {
+    searcher: BestSearcher,
+    shared: Option<SharedScore>,
+    /// The best score reported without a shared array, or after a rejected
+    /// one: only this worker's own reports count.
+    local: AtomicI32,
+}
+
+#[wasm_bindgen]
+impl VanityBestSearcher {
+    /// Takes the scorings `search_best` does. Workers splitting a search
+    /// give each a different `count_offset`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        scoring: Ts<Scoring>,
+        count_offset: Option<u64>,
+    ) -> Result<VanityBestSearcher, VanityJsError> {
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let scoring = scoring
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid scoring: {e}")))?;
+        Ok(VanityBestSearcher {
+            searcher: BestSearcher::new(
+                base_pubkey.as_bytes(),
+                owner_pubkey.as_bytes(),
+                scoring,
+                count_offset.unwrap_or(0),
+            )?,
+            shared: None,
+            local: AtomicI32::new(-1),
+        })
+    }
+
+    /// Shares the best reported score with other workers through
+    /// `score[0]`, which the caller sets to -1 before the search starts.
+    /// Returns `false` and keeps reporting this worker's own records if
+    /// `score` isn't a non-empty `Int32Array` over a `SharedArrayBuffer`,
+    /// as happens on pages that aren't cross-origin isolated.
+    #[wasm_bindgen]
+    pub fn set_shared_best(&mut self, score: &js_sys::Int32Array) -> bool {
+        if !score.buffer().is_instance_of::<js_sys::SharedArrayBuffer>() || score.length() == 0 {
+            log_warn!("no shared best score: reporting this worker's records only");
+            self.shared = None;
+            return false;
+        }
+        self.shared = Some(SharedScore(score.clone()));
+        true
+    }
+
+    /// Scores up to `batch_size` more addresses and returns the best so far
+    /// if it beats every report before it, and `undefined` otherwise.
+    #[wasm_bindgen]
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<BestResult> {
+        let found = match &self.shared {
+            Some(shared) => self.searcher.search_batch_shared(batch_size, shared),
+            None => self.searcher.search_batch_shared(batch_size, &self.local),
+        }?;
+        log_info!("best score now {} at {}", found.score, found.address);
+        Some(BestResult::from(found))
+    }
+
+    /// This worker's best address, reported or not; `undefined` before the
+    /// first batch.
+    #[wasm_bindgen]
+    pub fn best(&self) -> Option<BestResult> {
+        self.searcher.best().cloned().map(BestResult::from)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+}
@@ -0,0 +1,518 @@
+//! Fluent construction of a `VanitySearcher` from JS.
+
+use vanity_core::{
+    fix_confusables, AddressMatcher, CreateWithSeedDerivation, KeyRole, MatchType, PatternRewrite,
+    PatternSide, Pubkey, Searcher, VanityError,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::control::CancelToken;
+use crate::error::VanityJsError;
+use crate::{check_stop_flag, clean_prefix, VanitySearcher};
+
+#[derive(Clone, Debug)]
+enum PubkeyInput {
+    Bytes(Vec<u8>),
+    Base58(String),
+}
+
+impl PubkeyInput {
+    fn resolve(&self, role: KeyRole) -> Result<Pubkey, VanityError> {
+        match self {
+            PubkeyInput::Bytes(bytes) => Pubkey::for_role(bytes, role),
+            PubkeyInput::Base58(encoded) => Pubkey::from_base58_for_role(encoded, role),
+        }
+    }
+}
+
+/// Builder for `VanitySearcher`. Setters return the builder so calls can be
+/// chained; nothing is checked until `build()`, which reports every problem
+/// at once. Defaults match the `VanitySearcher` constructor.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct VanitySearcherBuilder {
+    base: Option<PubkeyInput>,
+    owner: Option<PubkeyInput>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    contains: Option<String>,
+    case_insensitive: bool,
+    count_offset: u64,
+    max_attempts: Option<u64>,
+    /// `Some(None)` asks for a random key.
+    permute_counters: Option<Option<u64>>,
+    allow_empty_pattern: bool,
+    auto_fix: bool,
+    clean_prefix: Option<(u8, Option<String>, Option<Vec<String>>)>,
+    stop_flag: Option<js_sys::Int32Array>,
+    cancel_token: Option<CancelToken>,
+    partial_tracking: bool,
+    progress_callback: Option<(js_sys::Function, u32)>,
+    checkpoint_callback: Option<(js_sys::Function, u32)>,
+    heartbeat: Option<(js_sys::Function, f64)>,
+}
+
+#[wasm_bindgen]
+impl VanitySearcherBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> VanitySearcherBuilder {
+        VanitySearcherBuilder::default()
+    }
+
+    #[wasm_bindgen(js_name = base)]
+    pub fn base(mut self, base_pubkey: Vec<u8>) -> VanitySearcherBuilder {
+        self.base = Some(PubkeyInput::Bytes(base_pubkey));
+        self
+    }
+
+    #[wasm_bindgen(js_name = base58Base)]
+    pub fn base58_base(mut self, base_pubkey: String) -> VanitySearcherBuilder {
+        self.base = Some(PubkeyInput::Base58(base_pubkey));
+        self
+    }
+
+    #[wasm_bindgen(js_name = owner)]
+    pub fn owner(mut self, owner_pubkey: Vec<u8>) -> VanitySearcherBuilder {
+        self.owner = Some(PubkeyInput::Bytes(owner_pubkey));
+        self
+    }
+
+    #[wasm_bindgen(js_name = base58Owner)]
+    pub fn base58_owner(mut self, owner_pubkey: String) -> VanitySearcherBuilder {
+        self.owner = Some(PubkeyInput::Base58(owner_pubkey));
+        self
+    }
+
+    pub fn prefix(mut self, prefix: String) -> VanitySearcherBuilder {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    pub fn suffix(mut self, suffix: String) -> VanitySearcherBuilder {
+        self.suffix = Some(suffix);
+        self
+    }
+
+    /// Matches the pattern anywhere in the address; can't be combined with
+    /// `prefix` or `suffix`.
+    pub fn contains(mut self, pattern: String) -> VanitySearcherBuilder {
+        self.contains = Some(pattern);
+        self
+    }
+
+    #[wasm_bindgen(js_name = caseInsensitive)]
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> VanitySearcherBuilder {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    #[wasm_bindgen(js_name = countOffset)]
+    pub fn count_offset(mut self, count_offset: u64) -> VanitySearcherBuilder {
+        self.count_offset = count_offset;
+        self
+    }
+
+    /// See `VanitySearcher.set_max_attempts`.
+    #[wasm_bindgen(js_name = maxAttempts)]
+    pub fn max_attempts(mut self, max_attempts: u64) -> VanitySearcherBuilder {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// See `VanitySearcher.permute_counters`; without a key, a random one is
+    /// drawn by `build()`.
+    #[wasm_bindgen(js_name = permuteCounters)]
+    pub fn permute_counters(mut self, key: Option<u64>) -> VanitySearcherBuilder {
+        self.permute_counters = Some(key);
+        self
+    }
+
+    #[wasm_bindgen(js_name = allowEmptyPattern)]
+    pub fn allow_empty_pattern(mut self, allow_empty_pattern: bool) -> VanitySearcherBuilder {
+        self.allow_empty_pattern = allow_empty_pattern;
+        self
+    }
+
+    /// Replaces characters no address can contain with their look-alikes
+    /// (0 and O become o, I and l become 1) instead of failing; see
+    /// `VanitySearcher.pattern_rewrites` for what was changed. Other invalid
+    /// characters still fail.
+    #[wasm_bindgen(js_name = autoFix)]
+    pub fn auto_fix(mut self, auto_fix: bool) -> VanitySearcherBuilder {
+        self.auto_fix = auto_fix;
+        self
+    }
+
+    /// See `VanitySearcher.set_clean_prefix`.
+    #[wasm_bindgen(js_name = cleanPrefix)]
+    pub fn clean_prefix(
+        mut self,
+        len: u8,
+        chars: Option<String>,
+        pairs: Option<Vec<String>>,
+    ) -> VanitySearcherBuilder {
+        self.clean_prefix = Some((len, chars, pairs));
+        self
+    }
+
+    /// See `VanitySearcher.set_stop_flag`.
+    #[wasm_bindgen(js_name = stopFlag)]
+    pub fn stop_flag(mut self, flag: js_sys::Int32Array) -> VanitySearcherBuilder {
+        self.stop_flag = Some(flag);
+        self
+    }
+
+    /// See `VanitySearcher.set_cancel_token`.
+    #[wasm_bindgen(js_name = cancelToken)]
+    pub fn cancel_token(mut self, token: &CancelToken) -> VanitySearcherBuilder {
+        self.cancel_token = Some(token.clone());
+        self
+    }
+
+    /// See `VanitySearcher.set_partial_tracking`.
+    #[wasm_bindgen(js_name = partialTracking)]
+    pub fn partial_tracking(mut self, track: bool) -> VanitySearcherBuilder {
+        self.partial_tracking = track;
+        self
+    }
+
+    /// See `VanitySearcher.set_progress_callback`.
+    #[wasm_bindgen(js_name = progressCallback)]
+    pub fn progress_callback(
+        mut self,
+        #[wasm_bindgen(unchecked_param_type = "(progress: ProgressSnapshot) => boolean | void")]
+        callback: js_sys::Function,
+        every_n_attempts: u32,
+    ) -> VanitySearcherBuilder {
+        self.progress_callback = Some((callback, every_n_attempts));
+        self
+    }
+
+    /// See `VanitySearcher.set_checkpoint_callback`.
+    #[wasm_bindgen(js_name = checkpointCallback)]
+    pub fn checkpoint_callback(
+        mut self,
+        #[wasm_bindgen(unchecked_param_type = "(token: string) => boolean | void")]
+        callback: js_sys::Function,
+        every_n_attempts: u32,
+    ) -> VanitySearcherBuilder {
+        self.checkpoint_callback = Some((callback, every_n_attempts));
+        self
+    }
+
+    /// See `VanitySearcher.set_heartbeat`.
+    pub fn heartbeat(
+        mut self,
+        #[wasm_bindgen(unchecked_param_type = "(sequence: number, attempts: number) => void")]
+        callback: js_sys::Function,
+        interval_ms: f64,
+    ) -> VanitySearcherBuilder {
+        self.heartbeat = Some((callback, interval_ms));
+        self
+    }
+
+    /// Fails with a `VanityError`; when several options are wrong, its kind
+    /// is "Multiple" and `errors` holds one error per problem.
+    pub fn build(self) -> Result<VanitySearcher, VanityJsError> {
+        let mut problems = Vec::new();
+        let searcher = self.validate(&mut problems);
+        if let Some(flag) = &self.stop_flag {
+            if let Err(problem) = check_stop_flag(flag) {
+                problems.push(problem);
+            }
+        }
+        if let Some(error) = VanityError::from_problems(problems) {
+            return Err(error.into());
+        }
+        let searcher = searcher.ok_or_else(|| VanityError::Internal("no searcher".into()))?;
+
+        let mut vanity_searcher = VanitySearcher::from_searcher(searcher);
+        let (patterns, rewrites) = self.fixed_patterns();
+        let originals = [&self.prefix, &self.suffix, &self.contains];
+        for (original, fixed) in originals.into_iter().zip(&patterns) {
+            if let (Some(original), Some(fixed)) = (original, fixed) {
+                if original.trim() != fixed {
+                    log_info!("searching for {original:?} as {fixed:?}");
+                }
+            }
+        }
+        vanity_searcher.pattern_rewrites = rewrites;
+        vanity_searcher.set_max_attempts(self.max_attempts);
+        if let Some(key) = self.permute_counters {
+            vanity_searcher.permute_counters(key)?;
+        }
+        vanity_searcher.stop_flag = self.stop_flag;
+        if let Some(token) = &self.cancel_token {
+            vanity_searcher.set_cancel_token(token);
+        }
+        vanity_searcher.set_partial_tracking(self.partial_tracking);
+        if let Some((callback, every_n_attempts)) = self.progress_callback {
+            vanity_searcher.set_progress_callback(callback, every_n_attempts);
+        }
+        if let Some((callback, every_n_attempts)) = self.checkpoint_callback {
+            vanity_searcher.set_checkpoint_callback(callback, every_n_attempts);
+        }
+        if let Some((callback, interval_ms)) = self.heartbeat {
+            vanity_searcher.set_heartbeat(callback, interval_ms);
+        }
+        Ok(vanity_searcher)
+    }
+}
+
+impl VanitySearcherBuilder {
+    /// The prefix, suffix and contains patterns, with look-alikes replaced
+    /// if `auto_fix` is set, and the replacements made.
+    fn fixed_patterns(&self) -> ([Option<String>; 3], Vec<PatternRewrite>) {
+        let mut rewrites = Vec::new();
+        let mut fix = |pattern: &Option<String>, side| {
+            let pattern = pattern.as_ref()?;
+            if !self.auto_fix {
+                return Some(pattern.clone());
+            }
+            let (fixed, changes) = fix_confusables(pattern, side, self.case_insensitive);
+            rewrites.extend(changes);
+            Some(fixed)
+        };
+        let patterns = [
+            fix(&self.prefix, PatternSide::Prefix),
+            fix(&self.suffix, PatternSide::Suffix),
+            fix(&self.contains, PatternSide::Contains),
+        ];
+        (patterns, rewrites)
+    }
+
+    /// Everything `build` checks that doesn't need JS. Problems are appended
+    /// to `problems`; the searcher is only returned if there were none.
+    fn validate(&self, problems: &mut Vec<VanityError>) -> Option<Searcher> {
+        let mut resolve = |role: KeyRole, input: &Option<PubkeyInput>| {
+            let missing = match role {
+                KeyRole::Base => VanityError::MissingBase,
+                KeyRole::Owner => VanityError::MissingOwner,
+            };
+            input
+                .as_ref()
+                .ok_or(missing)
+                .and_then(|input| input.resolve(role))
+                .map_err(|error| problems.push(error))
+                .ok()
+        };
+        let base = resolve(KeyRole::Base, &self.base);
+        let owner = resolve(KeyRole::Owner, &self.owner);
+        let matcher = self.compile_matcher(problems);
+
+        match (base, owner, matcher) {
+            (Some(base), Some(owner), Some(matcher)) if problems.is_empty() => {
+                Some(Searcher::with_matcher(
+                    CreateWithSeedDerivation::new(base.as_bytes(), owner.as_bytes()),
+                    matcher,
+                    self.count_offset,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// The pattern, case flag and clean prefix as the searcher will test
+    /// them, also behind `Matcher`. Problems are appended to `problems`.
+    pub(crate) fn compile_matcher(
+        &self,
+        problems: &mut Vec<VanityError>,
+    ) -> Option<AddressMatcher> {
+        let ([prefix, suffix, contains], _) = self.fixed_patterns();
+        let match_type = match contains {
+            Some(_) if prefix.is_some() || suffix.is_some() => {
+                problems.push(VanityError::ConflictingOptions(
+                    "contains cannot be combined with prefix or suffix".into(),
+                ));
+                None
+            }
+            Some(pattern) => Some((
+                MatchType::contains(pattern.trim().to_string(), self.case_insensitive),
+                MatchType::contains(pattern.trim().to_string(), false),
+            )),
+            None => Some((
+                MatchType::new(prefix.clone(), suffix.clone(), self.case_insensitive),
+                MatchType::new(prefix, suffix, false),
+            )),
+        };
+        if let Some((match_type, _)) = &match_type {
+            if match_type.is_empty() && !self.allow_empty_pattern {
+                problems.push(VanityError::EmptyPattern);
+            }
+            if let Err(error) = match_type.validate(self.case_insensitive) {
+                problems.push(error);
+            }
+        }
+
+        let clean = self.clean_prefix.clone().and_then(|(len, chars, pairs)| {
+            clean_prefix(len, chars, pairs)
+                .map_err(|error| problems.push(error))
+                .ok()
+        });
+
+        let (match_type, typed) = match_type?;
+        let mut matcher = AddressMatcher::with_match_type(match_type, self.case_insensitive);
+        matcher.set_typed_pattern(typed);
+        // Only a prefix that breaks the clean prefix fails here.
+        if let Err(error) = matcher.set_clean_prefix(clean) {
+            problems.push(error);
+            return None;
+        }
+        Some(matcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vanity_core::ErrorKind;
+
+    use super::*;
+
+    fn problems(builder: &VanitySearcherBuilder) -> Vec<VanityError> {
+        let mut problems = Vec::new();
+        let searcher = builder.validate(&mut problems);
+        assert_eq!(searcher.is_some(), problems.is_empty());
+        problems
+    }
+
+    #[test]
+    fn test_missing_fields_are_all_reported() {
+        assert_eq!(
+            problems(&VanitySearcherBuilder::new()),
+            [
+                VanityError::MissingBase,
+                VanityError::MissingOwner,
+                VanityError::EmptyPattern,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_and_conflicting_options() {
+        let builder = VanitySearcherBuilder::new()
+            .base(vec![1; 31])
+            .base58_owner("not-base58!".into())
+            .prefix("A".into())
+            .contains("moon".into());
+        let problems = problems(&builder);
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert_eq!(problems[0], VanityError::BadBaseLength(31));
+        assert_eq!(problems[1].kind(), ErrorKind::InvalidOwner);
+        assert_eq!(problems[2].kind(), ErrorKind::ConflictingOptions);
+    }
+
+    #[test]
+    fn test_clean_prefix_problems() {
+        let clean = |prefix: &str, len| {
+            let builder = VanitySearcherBuilder::new()
+                .base(vec![1; 32])
+                .owner(vec![4; 32])
+                .prefix(prefix.into())
+                .clean_prefix(len, None, Some(vec!["abc".into()]));
+            problems(&builder)
+        };
+        assert_eq!(clean("A", 0).len(), 1);
+        assert_eq!(clean("A", 0)[0].kind(), ErrorKind::InvalidOptions);
+        let builder = VanitySearcherBuilder::new()
+            .base(vec![1; 32])
+            .owner(vec![4; 32])
+            .prefix("AS".into())
+            .clean_prefix(4, None, None);
+        assert_eq!(problems(&builder)[0].kind(), ErrorKind::ConflictingOptions);
+        assert!(problems(&builder.clone().prefix("Ab".into())).is_empty());
+    }
+
+    #[test]
+    fn test_key_and_pattern_errors() {
+        let builder = VanitySearcherBuilder::new()
+            .base58_base("0OIl".into())
+            .owner(vec![4; 33])
+            .suffix("x0".into());
+        let kinds: Vec<_> = problems(&builder).iter().map(VanityError::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ErrorKind::InvalidBase,
+                ErrorKind::BadOwnerLength,
+                ErrorKind::InvalidPatternChar
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fully_specified_build_matches_constructor() {
+        let builder = VanitySearcherBuilder::new()
+            .base(vec![1; 32])
+            .base58_owner(vanity_core::encode_pubkey(&[4; 32], &mut [0; 44]).into())
+            .prefix("AAA".into())
+            .case_insensitive(false)
+            .count_offset(0);
+        let mut built = VanitySearcher::from_searcher(builder.validate(&mut Vec::new()).unwrap());
+        let mut constructed = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("AAA".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let found = loop {
+            if let Some(found) = built.search_batch(10_000) {
+                break found;
+            }
+        };
+        let expected = loop {
+            if let Some(found) = constructed.search_batch(10_000) {
+                break found;
+            }
+        };
+        assert_eq!(found.address(), expected.address());
+        assert_eq!(found.attempts(), expected.attempts());
+    }
+
+    #[test]
+    fn test_auto_fix_rewrites_look_alikes() {
+        let builder = VanitySearcherBuilder::new()
+            .base(vec![1; 32])
+            .owner(vec![4; 32])
+            .prefix("S0L".into())
+            .suffix("Il".into());
+        let kinds: Vec<_> = problems(&builder).iter().map(VanityError::kind).collect();
+        assert_eq!(kinds, [ErrorKind::InvalidPatternChar]);
+
+        let builder = builder.auto_fix(true);
+        assert!(problems(&builder).is_empty());
+        let (patterns, rewrites) = builder.fixed_patterns();
+        assert_eq!(patterns, [Some("SoL".into()), Some("11".into()), None]);
+        let summary: Vec<_> = rewrites
+            .iter()
+            .map(|r| (r.pattern, r.position, r.original, r.replacement))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (PatternSide::Prefix, 1, '0', 'o'),
+                (PatternSide::Suffix, 0, 'I', '1'),
+                (PatternSide::Suffix, 1, 'l', '1'),
+            ]
+        );
+
+        let unfixable = builder.prefix("S_L".into());
+        let kinds: Vec<_> = problems(&unfixable).iter().map(VanityError::kind).collect();
+        assert_eq!(kinds, [ErrorKind::InvalidPatternChar]);
+    }
+
+    #[test]
+    fn test_empty_pattern_opt_in_and_contains() {
+        let builder = VanitySearcherBuilder::new()
+            .base(vec![1; 32])
+            .owner(vec![4; 32]);
+        assert_eq!(problems(&builder.clone()), [VanityError::EmptyPattern]);
+        assert!(problems(&builder.clone().allow_empty_pattern(true)).is_empty());
+        assert!(problems(&builder.contains(" moon ".into()).case_insensitive(true)).is_empty());
+    }
+}
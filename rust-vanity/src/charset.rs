@@ -0,0 +1,159 @@
+//! Seeds over a custom charset and length.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    seed_space_warning, CharsetSeedDerivation, KeyRole, ProgressSnapshot, Pubkey, SearchMatch,
+    SearchStats, Searcher, SeedCharset,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::clock::now_ms;
+use crate::control::CancelToken;
+use crate::error::VanityJsError;
+use crate::{internal, progress_snapshot, BatchOutcome, VanityResult};
+
+/// `VanitySearcher` with seeds of `seed_len` characters from `charset`,
+/// e.g. 4 lowercase letters to type by hand. Such a seed space can be far
+/// smaller than the counter space: once every seed has been tried the
+/// search ends with status "exhausted" instead of trying them again.
+#[wasm_bindgen]
+pub struct VanityCharsetSeedSearcher {
+    searcher: Searcher<CharsetSeedDerivation>,
+    stats: SearchStats,
+}
+
+#[wasm_bindgen]
+impl VanityCharsetSeedSearcher {
+    /// `charset` is at least 2 distinct printable ASCII characters and
+    /// `seed_len` between 1 and 32. Logs a warning if the pattern is
+    /// expected to take more attempts than there are seeds.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        charset: String,
+        seed_len: u32,
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: Option<u64>,
+    ) -> Result<VanityCharsetSeedSearcher, VanityJsError> {
+        let charset = SeedCharset::new(&charset, seed_len)?;
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let searcher = Searcher::with_pattern(
+            CharsetSeedDerivation::new(base_pubkey.as_bytes(), owner_pubkey.as_bytes(), charset),
+            prefix,
+            suffix,
+            case_insensitive,
+            count_offset.unwrap_or(0),
+        )?;
+        let searcher = VanityCharsetSeedSearcher {
+            searcher,
+            stats: SearchStats::default(),
+        };
+        if let Some(warning) = searcher.seed_space_warning() {
+            log_warn!("{warning}");
+        }
+        Ok(searcher)
+    }
+
+    fn run_batch(&mut self, batch_size: u32) -> Option<SearchMatch<Vec<u8>>> {
+        let start_ms = now_ms();
+        let start_count = self.searcher.attempts();
+        let found = self.searcher.search_batch(batch_size);
+        self.stats
+            .record_batch(self.searcher.attempts() - start_count, now_ms() - start_ms);
+        if let Some(found) = &found {
+            log_info!("match {} after {} attempts", found.address, found.attempts);
+        } else if self.searcher.seed_space().is_some() && self.searcher.is_exhausted() {
+            log_info!(
+                "every seed tried after {} attempts",
+                self.searcher.attempts()
+            );
+        }
+        found
+    }
+
+    /// Tries up to `batch_size` more seeds and returns the first match, if
+    /// any.
+    #[wasm_bindgen]
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<VanityResult> {
+        self.run_batch(batch_size).map(VanityResult::from)
+    }
+
+    /// As `VanitySearcher.search_batch_outcome`; the status is "exhausted"
+    /// once every seed has been tried.
+    #[wasm_bindgen]
+    pub fn search_batch_outcome(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Ts<BatchOutcome>, VanityJsError> {
+        let start_ms = now_ms();
+        let start = (self.searcher.attempts(), self.searcher.skipped());
+        let found = self.run_batch(batch_size);
+        let outcome = BatchOutcome::new(
+            &self.searcher,
+            self.searcher.batch_status(found.is_some()),
+            found.map(|found| VanityResult::from(found).to_plain()),
+            start,
+            now_ms() - start_ms,
+        );
+        Ok(outcome.into_ts().map_err(internal)?)
+    }
+
+    /// As `VanitySearcher.progress`, with `seed_space_coverage` set when
+    /// there are fewer seeds than counters.
+    #[wasm_bindgen]
+    pub fn progress(&self) -> Result<Ts<ProgressSnapshot>, VanityJsError> {
+        let snapshot = progress_snapshot(&self.stats, &self.searcher, self.searcher.attempts());
+        Ok(snapshot.into_ts().map_err(internal)?)
+    }
+
+    /// Number of distinct seeds, or `undefined` if there are more than
+    /// counters.
+    #[wasm_bindgen(getter)]
+    pub fn seed_space(&self) -> Option<u64> {
+        self.searcher.seed_space()
+    }
+
+    /// Set if a match is expected to take more attempts than there are
+    /// seeds, i.e. may well not exist.
+    #[wasm_bindgen]
+    pub fn seed_space_warning(&self) -> Option<String> {
+        let space = self.searcher.seed_space()?;
+        seed_space_warning(self.searcher.match_probability(), space)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.searcher.stop();
+    }
+
+    /// See `VanitySearcher.set_cancel_token`.
+    #[wasm_bindgen]
+    pub fn set_cancel_token(&mut self, token: &CancelToken) {
+        self.searcher.set_cancel_token(Some(token.shared()));
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_cancel_token(&mut self) {
+        self.searcher.set_cancel_token(None);
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.searcher.match_probability()
+    }
+}
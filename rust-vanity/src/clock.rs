@@ -0,0 +1,114 @@
+//! Monotonic millisecond clock for timing batches, with a fake stepping
+//! clock for test mode, and the wall clock deadlines are set against.
+
+use std::cell::Cell;
+
+#[cfg(target_arch = "wasm32")]
+mod imp {
+    use std::cell::OnceCell;
+
+    use wasm_bindgen::JsCast;
+
+    thread_local! {
+        static PERFORMANCE: OnceCell<Option<web_sys::Performance>> = const { OnceCell::new() };
+    }
+
+    // `performance` exists on both windows and workers, but not everywhere,
+    // so it is looked up once and `Date.now()` is used in its absence.
+    fn with_performance<R>(f: impl FnOnce(Option<&web_sys::Performance>) -> R) -> R {
+        PERFORMANCE.with(|cell| {
+            let performance = cell.get_or_init(|| {
+                js_sys::Reflect::get(&js_sys::global(), &"performance".into())
+                    .ok()
+                    .and_then(|performance| performance.dyn_into().ok())
+            });
+            f(performance.as_ref())
+        })
+    }
+
+    pub fn init() {
+        with_performance(|_| ());
+    }
+
+    pub fn now_ms() -> f64 {
+        with_performance(|performance| performance.map_or_else(js_sys::Date::now, |p| p.now()))
+    }
+
+    /// Milliseconds since the Unix epoch; the stepping clock doesn't touch
+    /// it, so deadlines hold in test mode too.
+    pub fn epoch_ms() -> f64 {
+        js_sys::Date::now()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod imp {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static START: OnceLock<Instant> = OnceLock::new();
+
+    pub fn init() {
+        START.get_or_init(Instant::now);
+    }
+
+    pub fn now_ms() -> f64 {
+        START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+
+    pub fn epoch_ms() -> f64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |since| since.as_secs_f64() * 1000.0)
+    }
+}
+
+pub use imp::{epoch_ms, init};
+
+/// How far the stepping clock advances per reading.
+const STEP_MS: f64 = 1.0;
+
+thread_local! {
+    /// The stepping clock's next reading, while it is enabled.
+    static STEPPING: Cell<Option<f64>> = const { Cell::new(None) };
+}
+
+/// Switches to a fake clock that starts at 0 and advances exactly
+/// [`STEP_MS`] per reading, so measured times and rates are reproducible;
+/// `false` switches back to real time.
+pub fn set_stepping(enabled: bool) {
+    STEPPING.set(enabled.then_some(0.0));
+}
+
+pub fn now_ms() -> f64 {
+    match STEPPING.get() {
+        Some(now) => {
+            STEPPING.set(Some(now + STEP_MS));
+            now
+        }
+        None => imp::now_ms(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_now_ms_is_monotonic() {
+        init();
+        let first = now_ms();
+        let second = now_ms();
+        assert!(second >= first);
+    }
+
+    #[test]
+    fn test_stepping_clock() {
+        set_stepping(true);
+        assert_eq!((now_ms(), now_ms(), now_ms()), (0.0, 1.0, 2.0));
+        set_stepping(true);
+        assert_eq!(now_ms(), 0.0);
+        set_stepping(false);
+        assert!(now_ms() > 0.0);
+    }
+}
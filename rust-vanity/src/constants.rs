@@ -0,0 +1,104 @@
+//! Alphabets and limits the crate validates against, so the UI can mirror
+//! them instead of hard-coding its own copies.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{SeedScheme, SeedSchemeDescriptor};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// The base58 alphabet addresses are encoded with.
+#[wasm_bindgen]
+pub fn base58_alphabet() -> String {
+    String::from_utf8_lossy(vanity_core::BASE58_ALPHABET).into_owned()
+}
+
+/// Characters generated seeds are drawn from.
+#[wasm_bindgen]
+pub fn seed_charset() -> String {
+    String::from_utf8_lossy(vanity_core::ALPHANUMERIC_CHARS).into_owned()
+}
+
+/// Length of the seeds this crate generates.
+#[wasm_bindgen]
+pub fn seed_len() -> usize {
+    vanity_core::SEED_LEN
+}
+
+/// Longest seed `createWithSeed` accepts.
+#[wasm_bindgen]
+pub fn max_seed_len() -> usize {
+    vanity_core::MAX_SEED_LEN
+}
+
+/// Shortest base58 encoding of a 32-byte key.
+#[wasm_bindgen]
+pub fn min_encoded_len() -> usize {
+    vanity_core::MIN_ENCODED_LEN
+}
+
+/// Longest base58 encoding of a 32-byte key.
+#[wasm_bindgen]
+pub fn max_encoded_len() -> usize {
+    vanity_core::MAX_ENCODED_LEN
+}
+
+/// Version of the counter → seed mapping.
+#[wasm_bindgen]
+pub fn seed_scheme_version() -> u32 {
+    vanity_core::SEED_SCHEME_VERSION
+}
+
+/// How counters become seeds under seed scheme `version` (the latest if
+/// omitted), with test vectors, for reproducing seeds outside this crate.
+/// Fails with `InvalidOptions` for a version this build doesn't know.
+#[wasm_bindgen]
+pub fn seed_scheme_descriptor(
+    version: Option<u32>,
+) -> Result<Ts<SeedSchemeDescriptor>, VanityJsError> {
+    let scheme = match version {
+        Some(version) => SeedScheme::from_version(version)?,
+        None => SeedScheme::LATEST,
+    };
+    Ok(scheme.descriptor().into_ts().map_err(internal)?)
+}
+
+/// Most buckets `VanitySearcher.set_rate_history` keeps.
+#[wasm_bindgen]
+pub fn max_rate_history_buckets() -> u32 {
+    vanity_core::MAX_RATE_HISTORY_BUCKETS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabets() {
+        assert_eq!(
+            base58_alphabet(),
+            "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+        );
+        let seed_charset = seed_charset();
+        assert_eq!(seed_charset.as_bytes(), vanity_core::ALPHANUMERIC_CHARS);
+        let seed = vanity_core::generate_seed_from_counter(987_654_321);
+        assert_eq!(seed.len(), seed_len());
+        assert!(seed.iter().all(|&c| seed_charset.contains(c as char)));
+    }
+
+    #[test]
+    fn test_encoded_lengths_cover_searched_addresses() {
+        let mut searcher =
+            vanity_core::Searcher::new(&[1; 32], &[4; 32], None, Some("z".into()), false, 0)
+                .unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                break found;
+            }
+        };
+        assert!((min_encoded_len()..=max_encoded_len()).contains(&found.address.len()));
+        assert!(found.address.chars().all(|c| base58_alphabet().contains(c)));
+        assert!(seed_len() <= max_seed_len());
+    }
+}
@@ -0,0 +1,101 @@
+//! Steering a searcher from inside its own callbacks.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use vanity_core::VanityError;
+use wasm_bindgen::prelude::*;
+
+/// Passed to every `VanitySearcher` callback as its last argument. While a
+/// callback runs, the searcher is in the middle of a call and any method
+/// called on it throws, so a callback stops the search through this
+/// instead. A stop requested here is applied as soon as the callback
+/// returns, or at the start of the next batch if none is running; the
+/// searcher keeps its position, and `restart()` continues from it.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct SearchControl {
+    stop_requested: Rc<Cell<bool>>,
+}
+
+#[wasm_bindgen]
+impl SearchControl {
+    #[wasm_bindgen]
+    pub fn stop(&self) {
+        self.stop_requested.set(true);
+    }
+
+    /// Whether a stop was requested and not yet applied.
+    #[wasm_bindgen(getter)]
+    pub fn stop_requested(&self) -> bool {
+        self.stop_requested.get()
+    }
+}
+
+impl SearchControl {
+    pub(crate) fn new() -> SearchControl {
+        SearchControl {
+            stop_requested: Rc::new(Cell::new(false)),
+        }
+    }
+
+    /// Clears a pending stop, returning whether there was one.
+    pub(crate) fn take_stop(&self) -> bool {
+        self.stop_requested.replace(false)
+    }
+}
+
+/// A stop shared by several searchers in this module: attach it with each
+/// searcher's `set_cancel_token` (or the builder's `cancelToken`), and one
+/// `cancel()` stops them all within a few thousand attempts, mid-batch or
+/// at the start of the next, with status "stopped". Unlike `stop()` it can
+/// be called while the searchers run, e.g. from one of their callbacks.
+/// Cancelling is for good; attach a fresh token to search again.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    token: vanity_core::CancelToken,
+}
+
+#[wasm_bindgen]
+impl CancelToken {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    #[wasm_bindgen]
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl CancelToken {
+    /// The token itself, to hand to a core searcher.
+    pub(crate) fn shared(&self) -> vanity_core::CancelToken {
+        self.token.clone()
+    }
+}
+
+/// wasm-bindgen's message for a method called on an object that is
+/// already in use, i.e. from inside one of its callbacks.
+const RECURSIVE_USE: &str = "recursive use of an object";
+
+/// What a callback named `callback` threw, as kept in `callback_error`: a
+/// typed `ReentrantCall` error if it threw because it called into the
+/// searcher running it, the exception itself otherwise.
+pub(crate) fn callback_error(callback: &str, error: JsValue) -> JsValue {
+    let reentrant = error
+        .dyn_ref::<js_sys::Error>()
+        .is_some_and(|error| String::from(error.message()).contains(RECURSIVE_USE));
+    if !reentrant {
+        return error;
+    }
+    log_error!("{callback} callback called into its own searcher");
+    crate::error::VanityJsError::from(VanityError::ReentrantCall(callback.into())).into()
+}
@@ -0,0 +1,80 @@
+//! What the host tells us about its hardware and environment. None of this
+//! throws: a missing global reads as "not available".
+
+#[cfg(target_arch = "wasm32")]
+mod imp {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    // `navigator` lives on windows and workers under different types, and
+    // is missing entirely in some hosts (older Node), where 0 is returned.
+    pub fn hardware_concurrency() -> u32 {
+        let global = js_sys::global();
+        let concurrency = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+            window.navigator().hardware_concurrency()
+        } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+            worker.navigator().hardware_concurrency()
+        } else {
+            0.0
+        };
+        concurrency as u32
+    }
+
+    fn global_property(target: &JsValue, name: &str) -> JsValue {
+        js_sys::Reflect::get(target, &name.into()).unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// A module using one SIMD instruction (`i8x16.popcnt` of a splat),
+    /// which only validates where the engine supports wasm SIMD.
+    const SIMD_PROBE: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7b,
+        0x03, 0x02, 0x01, 0x00, 0x0a, 0x0a, 0x01, 0x08, 0x00, 0x41, 0x00, 0xfd, 0x0f, 0xfd, 0x62,
+        0x0b,
+    ];
+
+    pub fn simd_supported() -> bool {
+        js_sys::WebAssembly::validate(&js_sys::Uint8Array::from(SIMD_PROBE).into()).unwrap_or(false)
+    }
+
+    pub fn shared_array_buffer() -> bool {
+        !global_property(&js_sys::global(), "SharedArrayBuffer").is_undefined()
+    }
+
+    pub fn cross_origin_isolated() -> bool {
+        global_property(&js_sys::global(), "crossOriginIsolated").is_truthy()
+    }
+
+    /// Whether `navigator.gpu` exists. Getting an adapter is asynchronous
+    /// and not attempted here.
+    pub fn webgpu() -> bool {
+        let navigator = global_property(&js_sys::global(), "navigator");
+        !navigator.is_undefined() && !global_property(&navigator, "gpu").is_undefined()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod imp {
+    pub fn hardware_concurrency() -> u32 {
+        std::thread::available_parallelism().map_or(0, |n| n.get() as u32)
+    }
+
+    // Native builds have no JS host to offer any of these.
+    pub fn simd_supported() -> bool {
+        false
+    }
+
+    pub fn shared_array_buffer() -> bool {
+        false
+    }
+
+    pub fn cross_origin_isolated() -> bool {
+        false
+    }
+
+    pub fn webgpu() -> bool {
+        false
+    }
+}
+
+pub use imp::{
+    cross_origin_isolated, hardware_concurrency, shared_array_buffer, simd_supported, webgpu,
+};
@@ -0,0 +1,88 @@
+//! `VanityError` as seen from JS: an `Error` named "VanityError" whose
+//! `code` and `kind` let callers branch without parsing the message.
+
+use vanity_core::VanityError;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const VANITY_ERROR_TS: &str = r#"
+export interface VanityError extends Error {
+    name: "VanityError";
+    code: number;
+    kind: ErrorKind;
+    /** The individual problems, when `kind` is "Multiple". */
+    errors?: VanityError[];
+    /** Index of the offending character, when `kind` is "InvalidPatternChar" or "InvalidBase58". */
+    position?: number;
+    /** Alphabet the pattern was checked against, e.g. "base58", when `kind` is "InvalidPatternChar". */
+    alphabet?: string;
+    /** Expected attempts to a match, when `kind` is "Infeasible". */
+    expected_attempts?: number;
+    /** How long `expected_attempts` take at 1M attempts/s, e.g. "240 years", when `kind` is "Infeasible". */
+    duration?: string;
+    /** 1-based line of the pattern list, when `kind` is "InvalidPatternList". */
+    line?: number;
+    /** Attempts made, when `kind` is "SearchExhausted" or "SearchStopped". */
+    attempts?: number;
+}
+"#;
+
+/// Error type of the exported functions; converts into a JS `Error`.
+#[derive(Debug)]
+pub struct VanityJsError(pub(crate) VanityError);
+
+impl From<VanityError> for VanityJsError {
+    /// Every error bound for JS passes here, so this is where internal ones
+    /// reach the error callback.
+    fn from(error: VanityError) -> VanityJsError {
+        crate::report::report_error(&error);
+        VanityJsError(error)
+    }
+}
+
+impl From<VanityJsError> for JsValue {
+    fn from(error: VanityJsError) -> JsValue {
+        to_js_error(&error.0).into()
+    }
+}
+
+fn to_js_error(error: &VanityError) -> js_sys::Error {
+    let js_error = js_sys::Error::new(&error.to_string());
+    js_error.set_name("VanityError");
+    let set = |key: &str, value: &JsValue| {
+        js_sys::Reflect::set(&js_error, &key.into(), value).unwrap_throw();
+    };
+    set("code", &error.code().into());
+    let kind = serde_wasm_bindgen::to_value(&error.kind()).unwrap_throw();
+    set("kind", &kind);
+    match error {
+        VanityError::Multiple(errors) => {
+            let errors: js_sys::Array = errors.iter().map(to_js_error).collect();
+            set("errors", &errors);
+        }
+        VanityError::InvalidPatternChar {
+            position, alphabet, ..
+        } => {
+            set("position", &(*position as u32).into());
+            set("alphabet", &(*alphabet).into());
+        }
+        VanityError::InvalidBase58 { position, .. } => {
+            set("position", &(*position as u32).into());
+        }
+        VanityError::Infeasible {
+            expected_attempts,
+            duration,
+        } => {
+            set("expected_attempts", &(*expected_attempts).into());
+            set("duration", &duration.as_str().into());
+        }
+        VanityError::InvalidPatternList { line, .. } => {
+            set("line", &(*line as u32).into());
+        }
+        VanityError::SearchExhausted { attempts } | VanityError::SearchStopped { attempts } => {
+            set("attempts", &(*attempts as f64).into());
+        }
+        _ => {}
+    }
+    js_error
+}
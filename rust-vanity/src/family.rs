@@ -0,0 +1,122 @@
+//! Families of seeds `stem + salt + index` whose addresses all match.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    family_estimate, FamilyEstimate, FamilySearcher, FamilyStrategy, KeyRole, MatchType, Pubkey,
+    VanityError,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::internal;
+
+fn strategy(strategy: Ts<FamilyStrategy>) -> Result<FamilyStrategy, VanityError> {
+    strategy
+        .to_rust()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid family strategy: {e}")))
+}
+
+/// Grinds a family of `size` seeds `stem + salt + index` (index from 0, salt
+/// letters only) whose addresses all match the pattern: with `"all_match"`
+/// one salt shared by the whole family, exponentially harder in `size`;
+/// with `"independent"` a salt per index. Use `estimate_family` first.
+#[wasm_bindgen]
+pub struct VanityFamilySearcher {
+    family: FamilySearcher,
+}
+
+#[wasm_bindgen]
+impl VanityFamilySearcher {
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        stem: String,
+        size: u32,
+        family_strategy: Ts<FamilyStrategy>,
+    ) -> Result<VanityFamilySearcher, VanityJsError> {
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let family = FamilySearcher::new(
+            base_pubkey.as_bytes(),
+            owner_pubkey.as_bytes(),
+            MatchType::new(prefix, suffix, case_insensitive),
+            case_insensitive,
+            stem,
+            size,
+            strategy(family_strategy)?,
+        )?;
+        if let Some(warning) = family.estimate().warning {
+            log_warn!("{warning}");
+        }
+        Ok(VanityFamilySearcher { family })
+    }
+
+    /// Runs up to `batch_size` derivations and returns whether the family is
+    /// complete.
+    #[wasm_bindgen]
+    pub fn search_batch(&mut self, batch_size: u32) -> bool {
+        let complete = self.family.search_batch(batch_size);
+        if complete {
+            log_info!(
+                "family of {} found after {} attempts",
+                self.family.members().len(),
+                self.family.attempts()
+            );
+        }
+        complete
+    }
+
+    #[wasm_bindgen]
+    pub fn is_complete(&self) -> bool {
+        self.family.is_complete()
+    }
+
+    /// Whether the salts grew too long to fit in a seed first.
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.family.is_exhausted()
+    }
+
+    #[wasm_bindgen]
+    pub fn attempts(&self) -> u64 {
+        self.family.attempts()
+    }
+
+    /// The family, in index order, once complete; before that, the members
+    /// found so far.
+    #[wasm_bindgen(unchecked_return_type = "FamilyMember[]")]
+    pub fn members(&self) -> Result<js_sys::Array, VanityJsError> {
+        self.family
+            .members()
+            .iter()
+            .map(|member| Ok(JsValue::from(member.into_ts().map_err(internal)?)))
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn estimate(&self) -> Result<Ts<FamilyEstimate>, VanityJsError> {
+        Ok(self.family.estimate().into_ts().map_err(internal)?)
+    }
+}
+
+/// Expected attempts to find a family of `size` addresses matching the
+/// pattern with the given strategy, with a warning for `"all_match"`
+/// families, whose cost grows exponentially with `size`.
+#[wasm_bindgen]
+pub fn estimate_family(
+    prefix: Option<String>,
+    suffix: Option<String>,
+    case_insensitive: bool,
+    size: u32,
+    family_strategy: Ts<FamilyStrategy>,
+) -> Result<Ts<FamilyEstimate>, VanityJsError> {
+    let probability =
+        vanity_core::match_probability(prefix.as_deref(), suffix.as_deref(), case_insensitive);
+    let estimate = family_estimate(probability, size, strategy(family_strategy)?);
+    Ok(estimate.into_ts().map_err(internal)?)
+}
@@ -0,0 +1,186 @@
+//! The whole createWithSeed flow in one call: search, verify, and hand back
+//! everything needed to create the account.
+
+use serde::{Deserialize, Serialize};
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    check_feasible, rent_exempt_lamports, LuckStats, Pubkey, VanityError,
+    DEFAULT_MAX_EXPECTED_ATTEMPTS,
+};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::clock::now_ms;
+use crate::error::VanityJsError;
+use crate::pool::next_tick;
+use crate::{internal, SearchOptions, VanityResult, VanityResultJson, VanitySearcher};
+
+const DEFAULT_BATCH_SIZE: u32 = 100_000;
+
+/// Options for `find_one`, as a plain JS object.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct FindOptions {
+    pub base_pubkey: Vec<u8>,
+    pub owner_pubkey: Vec<u8>,
+    #[serde(flatten)]
+    pub pattern: SearchOptions,
+    #[tsify(optional)]
+    pub count_offset: u64,
+    /// Base58 key that pays for the new account; the base key if omitted.
+    #[tsify(optional)]
+    pub payer: Option<String>,
+    /// Data size of the new account, for its rent.
+    #[tsify(optional)]
+    pub space: u64,
+}
+
+/// When `find_one` gives up, as a plain JS object; with neither limit it
+/// searches until a match.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct FindLimits {
+    /// Rejects with `SearchExhausted` after this many attempts.
+    #[tsify(optional)]
+    pub max_attempts: Option<u64>,
+    /// Rejects with `SearchStopped` once this much time has passed,
+    /// checked between batches.
+    #[tsify(optional)]
+    pub max_ms: Option<f64>,
+    /// Attempts between yields to the event loop.
+    #[tsify(optional)]
+    pub batch_size: Option<u32>,
+}
+
+/// `SystemProgram.createAccountWithSeed` parameters, named as web3.js
+/// names them.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAccountWithSeedParams {
+    pub from_pubkey: String,
+    pub new_account_pubkey: String,
+    pub base_pubkey: String,
+    pub seed: String,
+    pub lamports: u64,
+    pub space: u64,
+    pub program_id: String,
+}
+
+/// What `find_one` resolves with: the match, already verified.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct FoundAccount {
+    pub result: VanityResultJson,
+    pub create_account_with_seed: CreateAccountWithSeedParams,
+    pub stats: LuckStats,
+    pub elapsed_ms: f64,
+}
+
+/// Searches for a createWithSeed address matching `options`, yielding to
+/// the event loop between batches, and resolves with the match once it
+/// re-derives, packaged with the parameters to create it. Rejects with a
+/// `VanityError`: the constructor's for bad options or an infeasible
+/// pattern, `SearchExhausted` when `max_attempts` or the counters run out,
+/// `SearchStopped` when `max_ms` passes.
+#[wasm_bindgen]
+pub async fn find_one(
+    options: Ts<FindOptions>,
+    limits: Option<Ts<FindLimits>>,
+) -> Result<Ts<FoundAccount>, VanityJsError> {
+    let options = options
+        .to_rust()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid find options: {e}")))?;
+    let limits = match limits {
+        Some(limits) => limits
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid find limits: {e}")))?,
+        None => FindLimits::default(),
+    };
+    Ok(run(options, limits).await?.into_ts().map_err(internal)?)
+}
+
+async fn run(options: FindOptions, limits: FindLimits) -> Result<FoundAccount, VanityError> {
+    if let Some(max_ms) = limits.max_ms {
+        if max_ms.is_nan() || max_ms < 0.0 {
+            return Err(VanityError::InvalidOptions(format!(
+                "max_ms must not be negative, got {max_ms}"
+            )));
+        }
+    }
+    let batch_size = limits.batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
+    if batch_size == 0 {
+        return Err(VanityError::InvalidOptions(
+            "batch_size must be positive".into(),
+        ));
+    }
+    let payer = options
+        .payer
+        .as_deref()
+        .map(|payer| Pubkey::from_base58(payer).map(|_| payer.to_string()))
+        .transpose()?;
+    let space = usize::try_from(options.space).map_err(|_| {
+        VanityError::InvalidOptions(format!("space {} is too large", options.space))
+    })?;
+    let mut searcher = VanitySearcher::build(
+        &options.base_pubkey,
+        &options.owner_pubkey,
+        options.pattern.prefix,
+        options.pattern.suffix,
+        options.pattern.case_insensitive,
+        options.count_offset,
+        false,
+    )?;
+    check_feasible(searcher.match_probability(), DEFAULT_MAX_EXPECTED_ATTEMPTS)?;
+    searcher.set_max_attempts(limits.max_attempts);
+
+    let start_ms = now_ms();
+    let found = loop {
+        if let Some(found) = searcher.search_batch(batch_size) {
+            break found;
+        }
+        let attempts = searcher.attempts();
+        if searcher.is_exhausted() {
+            return Err(VanityError::SearchExhausted { attempts });
+        }
+        if limits
+            .max_ms
+            .is_some_and(|max_ms| now_ms() - start_ms >= max_ms)
+        {
+            return Err(VanityError::SearchStopped { attempts });
+        }
+        if let Err(error) = JsFuture::from(next_tick()).await {
+            log_warn!("find_one: yield failed: {error:?}");
+        }
+    };
+    let elapsed_ms = now_ms() - start_ms;
+    let stats = searcher.luck_stats();
+    let result = VanityResult::from(found).with_keys(searcher.derivation());
+    drop(searcher);
+
+    if !result.verify().map_err(|error| error.0)? {
+        return Err(VanityError::Internal(format!(
+            "match {} doesn't re-derive",
+            result.address()
+        )));
+    }
+    let result = result.to_plain();
+    let (Some(base_pubkey), Some(owner_pubkey)) =
+        (result.base_pubkey.clone(), result.owner_pubkey.clone())
+    else {
+        return Err(VanityError::Internal("result without its keys".into()));
+    };
+    let create_account_with_seed = CreateAccountWithSeedParams {
+        from_pubkey: payer.unwrap_or_else(|| base_pubkey.clone()),
+        new_account_pubkey: result.address.clone(),
+        base_pubkey,
+        seed: result.seed.clone(),
+        lamports: rent_exempt_lamports(space),
+        space: options.space,
+        program_id: owner_pubkey,
+    };
+    Ok(FoundAccount {
+        result,
+        create_account_with_seed,
+        stats,
+        elapsed_ms,
+    })
+}
@@ -0,0 +1,443 @@
+//! Plain keypair grinding, on the same search loop as `VanitySearcher`.
+
+use serde::{Deserialize, Serialize};
+use tsify::{Ts, Tsify};
+use vanity_core::keys::{
+    encode_keypair, pubkey_from_secret, verify_expanded_secret, verify_keypair, KeyVerification,
+};
+use vanity_core::{
+    check_pattern_list, AddressMatcher, IncrementalKeyDerivation, PatternEntry, Pubkey,
+    SearchMatch, Searcher, SignerKeyDerivation, VanityError,
+};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroize;
+
+use crate::error::VanityJsError;
+use crate::{internal, master_secret_or_random};
+
+/// The pattern a keypair searcher was given, kept in its results so they
+/// can be checked again wherever they end up.
+fn searched_pattern(
+    prefix: &Option<String>,
+    suffix: &Option<String>,
+    case_insensitive: bool,
+) -> PatternEntry {
+    let mut pattern = PatternEntry {
+        prefix: prefix.clone(),
+        suffix: suffix.clone(),
+        case_insensitive,
+        ..PatternEntry::default()
+    };
+    pattern.label = pattern.directives();
+    pattern
+}
+
+/// The matcher a result's pattern was matched with; fails as
+/// `check_pattern_list` does for a pattern no searcher would have taken.
+fn result_matcher(pattern: &PatternEntry) -> Result<AddressMatcher, VanityError> {
+    check_pattern_list(std::slice::from_ref(pattern))?;
+    Ok(AddressMatcher::with_match_type(
+        pattern.match_type(),
+        pattern.case_insensitive,
+    ))
+}
+
+/// Returned by `VanityKeypairSearcher.search_batch`. The secret is wiped
+/// from wasm memory once the result is dropped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct KeypairResult {
+    pub address: String,
+    /// Base58 of the 64-byte keypair, as wallets import it.
+    pub secret_key: String,
+    pub attempts: u64,
+    pub counter: u64,
+    /// What the searcher was looking for, for `verify_keypair_result`.
+    pub pattern: PatternEntry,
+}
+
+impl KeypairResult {
+    fn new(found: SearchMatch<[u8; 32]>, pattern: &PatternEntry) -> KeypairResult {
+        KeypairResult {
+            secret_key: encode_keypair(&found.seed, &pubkey_from_secret(&found.seed)),
+            address: found.address,
+            attempts: found.attempts,
+            counter: found.counter,
+            pattern: pattern.clone(),
+        }
+    }
+}
+
+impl Drop for KeypairResult {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// Checks a `VanityKeypairSearcher` result from scratch before the address
+/// is funded, e.g. after it came back from a worker: the public key is
+/// derived again from the secret, encoded, compared with the address, and
+/// the address matched against the result's pattern again. The report
+/// names the first step that failed, if any. Throws only for a pattern no
+/// searcher would have taken.
+#[wasm_bindgen]
+pub fn verify_keypair_result(
+    result: Ts<KeypairResult>,
+) -> Result<Ts<KeyVerification>, VanityJsError> {
+    let result = result
+        .to_rust()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid keypair result: {e}")))?;
+    let matcher = result_matcher(&result.pattern)?;
+    let report = verify_keypair(&result.secret_key, &result.address, &matcher);
+    if let Some(step) = report.failed {
+        log_warn!("keypair {} failed verification at {step:?}", result.address);
+    }
+    Ok(report.into_ts().map_err(internal)?)
+}
+
+/// Grinds keypairs whose public key matches the pattern, e.g. for a mint
+/// or a wallet. The result's `secret_key` is a live secret key.
+#[wasm_bindgen]
+pub struct VanityKeypairSearcher {
+    searcher: Searcher<SignerKeyDerivation>,
+    master_secret: [u8; 32],
+    pattern: PatternEntry,
+}
+
+#[wasm_bindgen]
+impl VanityKeypairSearcher {
+    /// `master_secret` (32 bytes) seeds every key and is random if omitted;
+    /// workers splitting a search by `count_offset` must share it.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: Option<u64>,
+        master_secret: Option<Vec<u8>>,
+    ) -> Result<VanityKeypairSearcher, VanityJsError> {
+        let master_secret = master_secret_or_random(master_secret.as_deref())?;
+        let pattern = searched_pattern(&prefix, &suffix, case_insensitive);
+        let searcher = Searcher::with_pattern(
+            SignerKeyDerivation::new(&master_secret),
+            prefix,
+            suffix,
+            case_insensitive,
+            count_offset.unwrap_or(0),
+        )?;
+        Ok(VanityKeypairSearcher {
+            searcher,
+            master_secret,
+            pattern,
+        })
+    }
+
+    /// Tries up to `batch_size` more keys and returns the first match, if
+    /// any.
+    #[wasm_bindgen]
+    pub fn search_batch(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Option<Ts<KeypairResult>>, VanityJsError> {
+        let Some(found) = self.searcher.search_batch(batch_size) else {
+            return Ok(None);
+        };
+        log_info!(
+            "keypair {} after {} attempts",
+            found.address,
+            found.attempts
+        );
+        Ok(Some(
+            KeypairResult::new(found, &self.pattern)
+                .into_ts()
+                .map_err(internal)?,
+        ))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    /// The secret every key is derived from, to hand to other workers.
+    #[wasm_bindgen(getter)]
+    pub fn master_secret(&self) -> Vec<u8> {
+        self.master_secret.to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.searcher.stop();
+    }
+
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.searcher.match_probability()
+    }
+
+    /// As `VanitySearcher.resume_token`; tokens only apply to searchers with
+    /// the same master secret and pattern.
+    #[wasm_bindgen]
+    pub fn resume_token(&self) -> String {
+        self.searcher.resume_token()
+    }
+
+    #[wasm_bindgen]
+    pub fn resume_from(&mut self, token: &str) -> Result<(), VanityJsError> {
+        Ok(self.searcher.resume_from(token)?)
+    }
+}
+
+/// Returned by `VanityIncrementalKeypairSearcher.search_batch`. The secret
+/// is wiped from wasm memory once the result is dropped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct ExpandedKeypairResult {
+    pub address: String,
+    /// Base58 of the 64-byte expanded secret key: the clamped scalar, then
+    /// the nonce prefix. Not a wallet keypair; sign with an Ed25519 library
+    /// that takes expanded keys.
+    pub expanded_secret_key: String,
+    pub attempts: u64,
+    pub counter: u64,
+    /// What the searcher was looking for, for
+    /// `verify_expanded_keypair_result`.
+    pub pattern: PatternEntry,
+}
+
+impl Drop for ExpandedKeypairResult {
+    fn drop(&mut self) {
+        self.expanded_secret_key.zeroize();
+    }
+}
+
+/// `verify_keypair_result` for a `VanityIncrementalKeypairSearcher`
+/// result. An expanded key has no public half to compare with, so the
+/// derive step checks instead that it signs what verifies under its public
+/// key.
+#[wasm_bindgen]
+pub fn verify_expanded_keypair_result(
+    result: Ts<ExpandedKeypairResult>,
+) -> Result<Ts<KeyVerification>, VanityJsError> {
+    let result = result.to_rust().map_err(|e| {
+        VanityError::InvalidOptions(format!("invalid expanded keypair result: {e}"))
+    })?;
+    let matcher = result_matcher(&result.pattern)?;
+    let report = verify_expanded_secret(&result.expanded_secret_key, &result.address, &matcher);
+    if let Some(step) = report.failed {
+        log_warn!("keypair {} failed verification at {step:?}", result.address);
+    }
+    Ok(report.into_ts().map_err(internal)?)
+}
+
+/// Grinds keypairs several times faster than `VanityKeypairSearcher` by
+/// stepping each public key from the last with one point addition. The
+/// catch: the keys have no seed, so results carry an expanded secret key
+/// that seed-based wallets can't import. Every result's key is checked by
+/// signing a test message before it is returned.
+#[wasm_bindgen]
+pub struct VanityIncrementalKeypairSearcher {
+    searcher: Searcher<IncrementalKeyDerivation>,
+    master_secret: [u8; 32],
+    pattern: PatternEntry,
+}
+
+#[wasm_bindgen]
+impl VanityIncrementalKeypairSearcher {
+    /// As `VanityKeypairSearcher`'s constructor.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: Option<u64>,
+        master_secret: Option<Vec<u8>>,
+    ) -> Result<VanityIncrementalKeypairSearcher, VanityJsError> {
+        let master_secret = master_secret_or_random(master_secret.as_deref())?;
+        let pattern = searched_pattern(&prefix, &suffix, case_insensitive);
+        let searcher = Searcher::with_pattern(
+            IncrementalKeyDerivation::new(&master_secret),
+            prefix,
+            suffix,
+            case_insensitive,
+            count_offset.unwrap_or(0),
+        )?;
+        Ok(VanityIncrementalKeypairSearcher {
+            searcher,
+            master_secret,
+            pattern,
+        })
+    }
+
+    /// Tries up to `batch_size` more keys and returns the first match, if
+    /// any. Fails with `Internal` if the matched key doesn't check out.
+    #[wasm_bindgen]
+    pub fn search_batch(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Option<Ts<ExpandedKeypairResult>>, VanityJsError> {
+        let Some(found) = self.searcher.search_batch(batch_size) else {
+            return Ok(None);
+        };
+        log_info!(
+            "keypair {} after {} attempts",
+            found.address,
+            found.attempts
+        );
+        Ok(Some(self.result(found)?.into_ts().map_err(internal)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn master_secret(&self) -> Vec<u8> {
+        self.master_secret.to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.searcher.stop();
+    }
+
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.searcher.match_probability()
+    }
+
+    #[wasm_bindgen]
+    pub fn resume_token(&self) -> String {
+        self.searcher.resume_token()
+    }
+
+    #[wasm_bindgen]
+    pub fn resume_from(&mut self, token: &str) -> Result<(), VanityJsError> {
+        Ok(self.searcher.resume_from(token)?)
+    }
+}
+
+impl VanityIncrementalKeypairSearcher {
+    fn result(&self, found: SearchMatch<u64>) -> Result<ExpandedKeypairResult, VanityError> {
+        let pubkey = Pubkey::from_base58(&found.address)?;
+        let secret = self
+            .searcher
+            .derivation()
+            .verified_secret(found.seed, pubkey.as_bytes())?;
+        Ok(ExpandedKeypairResult {
+            address: found.address,
+            expanded_secret_key: secret.to_base58(),
+            attempts: found.attempts,
+            counter: found.counter,
+            pattern: self.pattern.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_secrets_stay_random_in_test_mode() {
+        crate::test_mode(true);
+        let secrets: Vec<_> = (0..2)
+            .flat_map(|_| {
+                let pattern = || Some("A".into());
+                [
+                    VanityKeypairSearcher::new(pattern(), None, false, None, None)
+                        .unwrap()
+                        .master_secret,
+                    VanityIncrementalKeypairSearcher::new(pattern(), None, false, None, None)
+                        .unwrap()
+                        .master_secret,
+                ]
+            })
+            .collect();
+        crate::test_mode(false);
+        for (i, secret) in secrets.iter().enumerate() {
+            assert!(!secrets[..i].contains(secret), "{i}");
+        }
+    }
+
+    #[test]
+    fn test_results_carry_a_working_keypair() {
+        let mut searcher =
+            VanityKeypairSearcher::new(Some("A".into()), None, false, None, Some(vec![2; 32]))
+                .unwrap();
+        let found = loop {
+            if let Some(found) = searcher.searcher.search_batch(100) {
+                break KeypairResult::new(found, &searcher.pattern);
+            }
+        };
+        let keypair = vanity_core::decode_base58(&found.secret_key).unwrap();
+        // The public half of the keypair is the address.
+        let pubkey: [u8; 32] = keypair[32..].try_into().unwrap();
+        assert_eq!(
+            vanity_core::encode_pubkey(&pubkey, &mut [0; 44]),
+            found.address
+        );
+        assert_eq!(
+            pubkey_from_secret(&keypair[..32].try_into().unwrap()),
+            pubkey
+        );
+
+        let matcher = result_matcher(&found.pattern).unwrap();
+        assert_eq!(found.pattern.label, "prefix:A");
+        assert!(verify_keypair(&found.secret_key, &found.address, &matcher).ok);
+        let mut tampered = found.address.clone();
+        tampered.replace_range(1..2, if &tampered[1..2] == "B" { "C" } else { "B" });
+        let report = verify_keypair(&found.secret_key, &tampered, &matcher);
+        assert_eq!(report.failed, Some(vanity_core::keys::VerifyStep::Address));
+
+        let mut resumed =
+            VanityKeypairSearcher::new(Some("A".into()), None, false, None, Some(vec![2; 32]))
+                .unwrap();
+        resumed.resume_from(&searcher.resume_token()).unwrap();
+        assert_eq!(
+            resumed.searcher.absolute_position(),
+            Some(found.counter + 1)
+        );
+    }
+
+    #[test]
+    fn test_incremental_results_carry_a_signing_key() {
+        use vanity_core::keys::{verify_signature, ExpandedSecret};
+
+        let mut searcher = VanityIncrementalKeypairSearcher::new(
+            Some("A".into()),
+            None,
+            false,
+            None,
+            Some(vec![2; 32]),
+        )
+        .unwrap();
+        let found = loop {
+            if let Some(found) = searcher.searcher.search_batch(100) {
+                break searcher.result(found).unwrap();
+            }
+        };
+        let bytes = vanity_core::decode_base58(&found.expanded_secret_key).unwrap();
+        let secret = ExpandedSecret::new(
+            bytes[..32].try_into().unwrap(),
+            bytes[32..].try_into().unwrap(),
+        );
+        let pubkey = Pubkey::from_base58(&found.address).unwrap();
+        assert_eq!(secret.pubkey(), pubkey.to_bytes());
+        assert!(verify_signature(
+            pubkey.as_bytes(),
+            b"transfer",
+            &secret.sign(b"transfer")
+        ));
+        assert_eq!(searcher.attempts(), found.counter + 1);
+        let matcher = result_matcher(&found.pattern).unwrap();
+        assert!(verify_expanded_secret(&found.expanded_secret_key, &found.address, &matcher).ok);
+        let elsewhere = result_matcher(&searched_pattern(&None, &Some("zzzz".into()), false));
+        let report = verify_expanded_secret(
+            &found.expanded_secret_key,
+            &found.address,
+            &elsewhere.unwrap(),
+        );
+        assert_eq!(report.failed, Some(vanity_core::keys::VerifyStep::Pattern));
+        assert!(result_matcher(&searched_pattern(&None, &None, false)).is_err());
+    }
+}
@@ -1,87 +1,367 @@
-use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clock::{epoch_ms, now_ms};
+use results::ResultQueue;
+use serde::{Deserialize, Serialize};
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    check_feasible, AttemptQuantiles, BatchStatus, ByteMask, CleanPrefix, ConfusableSet,
+    CounterPermutation, CreateWithSeedDerivation, Derivation, DerivationTrace, DerivedAddress,
+    Heartbeat, KeyRole, LuckStats, MatchType, PartialStats, PatternRewrite, ProgressSnapshot,
+    Pubkey, RateHistory, SearchMatch, SearchPlan, SearchStats, Searcher, SeedNamespace, SeedScheme,
+    VanityError, DEFAULT_MAX_EXPECTED_ATTEMPTS, DEFAULT_RATE_HISTORY_BUCKETS,
+    INTERRUPT_POLL_INTERVAL, MAX_SAMPLES,
+};
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
-extern "C" {
-    fn alert(s: &str);
+#[macro_use]
+mod logger;
+
+mod aggregator;
+mod approx;
+mod backend;
+mod base58;
+mod best;
+mod builder;
+mod charset;
+mod clock;
+mod constants;
+mod control;
+mod device;
+mod error;
+mod family;
+mod find;
+#[cfg(feature = "keypair")]
+mod keypair;
+mod matcher;
+mod matches;
+#[cfg(feature = "metadata")]
+mod metadata;
+mod multi;
+#[cfg(feature = "passphrase")]
+mod passphrase;
+mod patterns;
+mod pool;
+mod queue;
+mod ranges;
+mod report;
+mod results;
+mod seen;
+mod short;
+#[cfg(feature = "squads")]
+mod squads;
+mod test_mode;
+mod tiers;
+#[cfg(feature = "token2022")]
+mod token2022;
+mod workunit;
+
+pub use aggregator::ProgressAggregator;
+pub use approx::VanityApproxSearcher;
+pub use backend::{
+    capabilities, features, select_backend, Backend, BackendSelection, Capabilities,
+};
+pub use base58::{decode_pubkey, encode_pubkey, is_valid_base58};
+pub use best::{search_best, BestResult, VanityBestSearcher};
+pub use builder::VanitySearcherBuilder;
+pub use charset::VanityCharsetSeedSearcher;
+pub use constants::{
+    base58_alphabet, max_encoded_len, max_rate_history_buckets, max_seed_len, min_encoded_len,
+    seed_charset, seed_len, seed_scheme_descriptor, seed_scheme_version,
+};
+pub use control::{CancelToken, SearchControl};
+pub use error::VanityJsError;
+pub use family::{estimate_family, VanityFamilySearcher};
+pub use find::{find_one, CreateAccountWithSeedParams, FindLimits, FindOptions, FoundAccount};
+#[cfg(feature = "keypair")]
+pub use keypair::{
+    verify_expanded_keypair_result, verify_keypair_result, ExpandedKeypairResult, KeypairResult,
+    VanityIncrementalKeypairSearcher, VanityKeypairSearcher,
+};
+pub use logger::{set_log_callback, set_log_level};
+pub use matcher::{Matcher, MatcherOptions};
+pub use matches::MatchesOptions;
+#[cfg(feature = "metadata")]
+pub use metadata::{MetadataOptions, PatternOptions, VanityMetadataSearcher};
+pub use multi::{MultiResult, VanityMultiSearcher};
+#[cfg(feature = "passphrase")]
+pub use passphrase::{seed_from_passphrase, VanityPassphraseSearcher};
+pub use patterns::{format_pattern_list, parse_pattern_list};
+pub use pool::{pool_worker_main, PoolOptions, PoolProgress, WorkerPool};
+pub use queue::{JobOptions, JobResult, QueueProgress, VanityJobQueue};
+pub use ranges::{audit_ranges, merge_ranges};
+pub use report::{set_error_callback, ErrorReport, ErrorSource};
+pub use results::QueueOverflow;
+pub use seen::SeenFilter;
+pub use short::{estimate_short_seed, VanityShortSeedSearcher};
+#[cfg(feature = "squads")]
+pub use squads::VanitySquadsSearcher;
+pub use test_mode::test_mode;
+pub use tiers::VanityTieredSearcher;
+#[cfg(feature = "token2022")]
+pub use token2022::{
+    token2022_extensions, Token2022MintResult, Token2022Options, TokenMetadataOptions,
+    VanityToken2022Searcher,
+};
+pub use workunit::{collate_results, create_work_units, run_work_unit, WorkUnitOptions};
 
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
+/// Set once the module's start function has run.
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Runs automatically when the module is instantiated.
+#[wasm_bindgen(start)]
+pub fn start() {
+    init_panic_hook();
+    clock::init();
+    INITIALIZED.store(true, Ordering::Release);
 }
 
-macro_rules! console_log {
-    ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
+/// Whether the module's start function has completed.
+#[wasm_bindgen]
+pub fn is_initialized() -> bool {
+    INITIALIZED.load(Ordering::Acquire)
 }
 
+/// Installs the panic hook, which logs to the console (when built with
+/// `console_error_panic_hook`) and calls the `set_error_callback` callback.
+/// This already happens on module start; calling it again is harmless.
 #[wasm_bindgen]
 pub fn init_panic_hook() {
-    #[cfg(feature = "console_error_panic_hook")]
-    console_error_panic_hook::set_once();
+    report::install_panic_hook();
+}
+
+#[wasm_bindgen]
+pub struct VanitySearcher {
+    searcher: Searcher,
+    stats: SearchStats,
+    stop_flag: Option<js_sys::Int32Array>,
+    /// From `stop_at`, in epoch milliseconds.
+    deadline: Option<f64>,
+    /// Whether `deadline` is what stopped the searcher, until `restart()`.
+    deadline_reached: bool,
+    progress_slot: Option<ProgressSlot>,
+    progress_callback: Option<PeriodicCallback>,
+    checkpoint_callback: Option<PeriodicCallback>,
+    heartbeat: Option<HeartbeatCallback>,
+    callback_error: Option<JsValue>,
+    control: SearchControl,
+    /// Look-alike replacements the builder's `autoFix` made.
+    pattern_rewrites: Vec<PatternRewrite>,
+    results: ResultQueue,
 }
 
-const ALPHANUMERIC_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz0123456789";
+fn check_stop_flag(flag: &js_sys::Int32Array) -> Result<(), VanityError> {
+    if !flag.buffer().is_instance_of::<js_sys::SharedArrayBuffer>() {
+        return Err(VanityError::InvalidStopFlag(
+            "stop flag must be an Int32Array over a SharedArrayBuffer \
+             (is the page cross-origin isolated?)"
+                .into(),
+        ));
+    }
+    if flag.length() == 0 {
+        return Err(VanityError::InvalidStopFlag(
+            "stop flag must have at least one element".into(),
+        ));
+    }
+    Ok(())
+}
 
-fn generate_seed_from_counter(counter: u64) -> [u8; 16] {
-    let mut seed = [0u8; 16];
+/// A key given as 32 bytes or in base58, with errors naming its `role`.
+fn pubkey_from_js(key: &JsValue, role: KeyRole) -> Result<Pubkey, VanityError> {
+    if let Some(encoded) = key.as_string() {
+        return Pubkey::from_base58_for_role(&encoded, role);
+    }
+    match key.dyn_ref::<js_sys::Uint8Array>() {
+        Some(bytes) => Pubkey::for_role(&bytes.to_vec(), role),
+        None => {
+            let message = "expected a Uint8Array or a base58 string".to_string();
+            Err(match role {
+                KeyRole::Base => VanityError::InvalidBase(message),
+                KeyRole::Owner => VanityError::InvalidOwner(message),
+            })
+        }
+    }
+}
 
-    // Use counter and hash to generate indices into valid chars - much faster than base conversion
-    let mut state1 = counter;
-    let mut state2 = counter.wrapping_mul(0x9E3779B97F4A7C15); // Golden ratio hash
+/// One element of a `BigUint64Array` over a `SharedArrayBuffer` that a
+/// searcher keeps its attempt count in.
+struct ProgressSlot {
+    counters: js_sys::BigUint64Array,
+    slot: u32,
+}
 
-    for i in 0..8 {
-        seed[i] = ALPHANUMERIC_CHARS[state1 as usize % ALPHANUMERIC_CHARS.len()];
-        seed[i + 8] = ALPHANUMERIC_CHARS[state2 as usize % ALPHANUMERIC_CHARS.len()];
-        state1 >>= 8;
-        state2 >>= 8;
+impl ProgressSlot {
+    fn new(counters: &js_sys::BigUint64Array, slot: u32) -> Result<ProgressSlot, VanityError> {
+        if !counters
+            .buffer()
+            .is_instance_of::<js_sys::SharedArrayBuffer>()
+        {
+            return Err(VanityError::InvalidProgressBuffer(
+                "progress buffer must be a BigUint64Array over a SharedArrayBuffer \
+                 (is the page cross-origin isolated?)"
+                    .into(),
+            ));
+        }
+        if slot >= counters.length() {
+            return Err(VanityError::InvalidProgressBuffer(format!(
+                "slot {slot} is outside a progress buffer of {} counters",
+                counters.length()
+            )));
+        }
+        Ok(ProgressSlot {
+            counters: counters.clone(),
+            slot,
+        })
     }
 
-    seed
+    fn publish(&self, attempts: u64) {
+        // Can't fail on a slot `new` checked; a missed update is harmless,
+        // the next one overwrites it.
+        let _ = js_sys::Atomics::store_bigint(&self.counters, self.slot, attempts as i64);
+    }
 }
 
-fn maybe_bs58_aware_lowercase(pubkey: &str, case_insensitive: bool) -> String {
-    if case_insensitive {
-        pubkey.to_lowercase()
-    } else {
-        pubkey.to_string()
+/// The built-in confusable set unless `chars` or `pairs` override it; an
+/// omitted half of an override is empty.
+fn clean_prefix(
+    len: u8,
+    chars: Option<String>,
+    pairs: Option<Vec<String>>,
+) -> Result<CleanPrefix, VanityError> {
+    match (chars, pairs) {
+        (None, None) => CleanPrefix::with_default_set(len),
+        (chars, pairs) => CleanPrefix::new(
+            len,
+            chars.as_deref().unwrap_or(""),
+            &pairs.unwrap_or_default(),
+        ),
     }
 }
 
-fn check_prefix_match(encoded: &str, prefix_bytes: &[u8]) -> bool {
-    let encoded_bytes = encoded.as_bytes();
-    if encoded_bytes.len() < prefix_bytes.len() {
-        return false;
+fn internal(error: impl std::fmt::Display) -> VanityError {
+    VanityError::Internal(error.to_string())
+}
+
+/// Largest integer a JS number holds exactly, 2^53 - 1.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// A random u64, or [`test_mode::PINNED_COUNTER`] in test mode.
+fn random_u64() -> Result<u64, VanityError> {
+    if test_mode::enabled() {
+        return Ok(test_mode::PINNED_COUNTER);
     }
+    Ok(u64::from_le_bytes(random_bytes()?))
+}
 
-    for i in 0..prefix_bytes.len() {
-        if encoded_bytes[i] != prefix_bytes[i] {
-            return false;
-        }
+/// Random bytes, in test mode too: master secrets come from here, and
+/// pinned ones would let anyone rebuild the keys found with them.
+fn random_bytes<const N: usize>() -> Result<[u8; N], VanityError> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(internal)?;
+    Ok(bytes)
+}
+
+/// An 11-character base58 tag from a random u64.
+fn random_namespace() -> Result<String, VanityError> {
+    let mut value = random_u64()?;
+    Ok((0..11)
+        .map(|_| {
+            let digit = vanity_core::BASE58_ALPHABET[(value % 58) as usize];
+            value /= 58;
+            digit as char
+        })
+        .collect())
+}
+
+/// The 32-byte secret signer keys are derived from, random if not given.
+#[cfg(feature = "keypair")]
+fn master_secret_or_random(given: Option<&[u8]>) -> Result<[u8; 32], VanityError> {
+    match given {
+        Some(bytes) => bytes.try_into().map_err(|_| {
+            VanityError::InvalidOptions(format!(
+                "master secret must be 32 bytes, got {}",
+                bytes.len()
+            ))
+        }),
+        None => random_bytes(),
+    }
+}
+
+fn progress_snapshot<D: Derivation>(
+    stats: &SearchStats,
+    searcher: &Searcher<D>,
+    attempts: u64,
+) -> ProgressSnapshot {
+    ProgressSnapshot {
+        stop_at_probability: searcher.stop_at_probability(),
+        seed_space_coverage: searcher.seed_space_coverage(),
+        ..stats.snapshot(attempts, searcher.match_probability())
     }
+}
 
-    true
+/// Lower bound on the progress and checkpoint callback intervals.
+const MIN_CALLBACK_INTERVAL: u32 = 10_000;
+
+struct HeartbeatCallback {
+    callback: js_sys::Function,
+    schedule: Heartbeat,
 }
 
-#[derive(Clone)]
-enum MatchType {
-    Prefix(String),
-    Suffix(String),
-    Both(String, String),
+/// A callback run every `every` attempts, between chunks of a batch.
+struct PeriodicCallback {
+    callback: js_sys::Function,
+    every: u32,
+    /// Local attempt count at which the callback next fires; `u64::MAX`
+    /// while paused.
+    next_at: u64,
 }
 
-#[wasm_bindgen]
-pub struct VanitySearcher {
-    base_pubkey: [u8; 32],
-    owner_pubkey: [u8; 32],
-    match_type: MatchType,
-    case_insensitive: bool,
-    count: u64,
-    count_offset: u64,
-    should_exit: bool,
+impl PeriodicCallback {
+    fn new(callback: js_sys::Function, every_n_attempts: u32, attempts: u64) -> PeriodicCallback {
+        if every_n_attempts < MIN_CALLBACK_INTERVAL {
+            log_warn!(
+                "callback interval {every_n_attempts} raised to {MIN_CALLBACK_INTERVAL} attempts"
+            );
+        }
+        let every = every_n_attempts.max(MIN_CALLBACK_INTERVAL);
+        PeriodicCallback {
+            callback,
+            every,
+            next_at: attempts + every as u64,
+        }
+    }
+
+    fn until_next(&self, attempts: u64) -> u32 {
+        self.next_at
+            .saturating_sub(attempts)
+            .clamp(1, u32::MAX as u64) as u32
+    }
+
+    /// Whether the callback is due, scheduling the next call if so.
+    fn due(&mut self, attempts: u64) -> bool {
+        if attempts < self.next_at {
+            return false;
+        }
+        self.reschedule(attempts);
+        true
+    }
+
+    fn reschedule(&mut self, attempts: u64) {
+        self.next_at = attempts + self.every as u64;
+    }
 }
 
 #[wasm_bindgen]
 impl VanitySearcher {
     #[wasm_bindgen(constructor)]
+    /// Fails with a `VanityError` if a key isn't 32 bytes, the pattern can't
+    /// match any address, or neither a prefix nor a suffix is given (or both
+    /// are blank). The last check is skipped when `allow_empty_pattern` is
+    /// set, in which case every attempt matches — useful only for
+    /// benchmarking. Fails with `Infeasible` if a match is expected to take
+    /// more than `DEFAULT_MAX_EXPECTED_ATTEMPTS` (ten years at 1M attempts
+    /// per second) unless `force` is set.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         base_pubkey: &[u8],
         owner_pubkey: &[u8],
@@ -89,156 +369,2611 @@ impl VanitySearcher {
         suffix: Option<String>,
         case_insensitive: bool,
         count_offset: u64,
-    ) -> VanitySearcher {
-        let match_type = match (prefix, suffix) {
-            (Some(p), Some(s)) => {
-                let prefix_str = if case_insensitive {
-                    p.to_lowercase()
-                } else {
-                    p
-                };
-                let suffix_str = if case_insensitive {
-                    s.to_lowercase()
-                } else {
-                    s
-                };
-                MatchType::Both(prefix_str, suffix_str)
-            }
-            (Some(p), None) => {
-                let prefix_str = if case_insensitive {
-                    p.to_lowercase()
-                } else {
-                    p
-                };
-                MatchType::Prefix(prefix_str)
-            }
-            (None, Some(s)) => {
-                let suffix_str = if case_insensitive {
-                    s.to_lowercase()
-                } else {
-                    s
-                };
-                MatchType::Suffix(suffix_str)
-            }
-            (None, None) => MatchType::Prefix(String::new()), // Default to empty prefix
-        };
-
-        VanitySearcher {
-            base_pubkey: base_pubkey.try_into().unwrap(),
-            owner_pubkey: owner_pubkey.try_into().unwrap(),
-            match_type,
+        allow_empty_pattern: Option<bool>,
+        force: Option<bool>,
+    ) -> Result<VanitySearcher, VanityJsError> {
+        let searcher = VanitySearcher::build(
+            base_pubkey,
+            owner_pubkey,
+            prefix,
+            suffix,
             case_insensitive,
-            count: 0,
             count_offset,
-            should_exit: false,
+            allow_empty_pattern.unwrap_or(false),
+        )?;
+        if !force.unwrap_or(false) {
+            check_feasible(searcher.match_probability(), DEFAULT_MAX_EXPECTED_ATTEMPTS)?;
         }
+        Ok(VanitySearcher::from_searcher(searcher))
     }
 
+    /// Like the constructor, with everything but the keys in one object:
+    /// `{ prefix, suffix, case_insensitive, count_offset, allow_empty_pattern,
+    /// force, max_expected_attempts }`. `max_expected_attempts` raises or
+    /// lowers the `Infeasible` threshold; `force` skips it.
     #[wasm_bindgen]
-    pub fn search_batch(&mut self, batch_size: u32) -> Option<VanityResult> {
-        let base_sha = Sha256::new().chain_update(self.base_pubkey);
+    pub fn from_options(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        options: Ts<SearcherOptions>,
+    ) -> Result<VanitySearcher, VanityJsError> {
+        let options = options
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid searcher options: {e}")))?;
+        let max_expected_attempts = options
+            .max_expected_attempts
+            .unwrap_or(DEFAULT_MAX_EXPECTED_ATTEMPTS);
+        if max_expected_attempts.is_nan() || max_expected_attempts <= 0.0 {
+            return Err(VanityError::InvalidOptions(format!(
+                "max_expected_attempts must be positive, got {max_expected_attempts}"
+            ))
+            .into());
+        }
+        let searcher = VanitySearcher::build(
+            base_pubkey,
+            owner_pubkey,
+            options.pattern.prefix,
+            options.pattern.suffix,
+            options.pattern.case_insensitive,
+            options.count_offset,
+            options.allow_empty_pattern,
+        )?;
+        if !options.force {
+            check_feasible(searcher.match_probability(), max_expected_attempts)?;
+        }
+        Ok(VanitySearcher::from_searcher(searcher))
+    }
 
-        for _ in 0..batch_size {
-            if self.should_exit {
-                return None;
-            }
+    /// Searcher for one of the built-in patterns listed by `presets()`.
+    /// Fails with `InvalidOptions` for an unknown id.
+    #[wasm_bindgen]
+    pub fn from_preset(
+        id: &str,
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        count_offset: u64,
+    ) -> Result<VanitySearcher, VanityJsError> {
+        let preset = vanity_core::preset(id)?;
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        log_debug!("new searcher from preset {id}");
+        let searcher = preset.searcher(
+            base_pubkey.as_bytes(),
+            owner_pubkey.as_bytes(),
+            count_offset,
+        )?;
+        Ok(VanitySearcher::from_searcher(searcher))
+    }
+
+    /// Searcher for pubkeys whose raw bytes satisfy `(pubkey & mask) ==
+    /// value`, e.g. `mask = [0xff]`, `value = [0x00]` for a zero first byte.
+    /// `mask` and `value` cover the leading bytes (up to 32, both the same
+    /// length); with a `prefix`, the address must start with it too.
+    /// Without one, non-matching pubkeys are never base58-encoded, so these
+    /// searches run noticeably faster. Fails with "InvalidByteMask" if the
+    /// lengths are wrong or no pubkey could match.
+    #[wasm_bindgen]
+    pub fn with_byte_mask(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        mask: &[u8],
+        value: &[u8],
+        prefix: Option<String>,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Result<VanitySearcher, VanityJsError> {
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let byte_mask = ByteMask::new(mask, value)?;
+        let searcher = Searcher::with_byte_mask(
+            base_pubkey.as_bytes(),
+            owner_pubkey.as_bytes(),
+            byte_mask,
+            prefix,
+            case_insensitive,
+            count_offset,
+        )?;
+        Ok(VanitySearcher::from_searcher(searcher))
+    }
 
-            let seed = generate_seed_from_counter(self.count + self.count_offset);
+    /// Characters the builder's `autoFix` replaced, e.g. to show "searching
+    /// for 'S0L' as 'SoL'". Empty unless the searcher was built with it.
+    #[wasm_bindgen(unchecked_return_type = "PatternRewrite[]")]
+    pub fn pattern_rewrites(&self) -> Result<js_sys::Array, VanityJsError> {
+        self.pattern_rewrites
+            .iter()
+            .map(|rewrite| Ok(JsValue::from(rewrite.into_ts().map_err(internal)?)))
+            .collect()
+    }
 
-            let mut hasher = base_sha.clone();
-            hasher.update(seed);
-            hasher.update(&self.owner_pubkey);
-            let pubkey_bytes: [u8; 32] = hasher.finalize().into();
+    /// Also requires the first `len` characters of a match to avoid easily
+    /// misread characters and pairs: by default S/5, Z/2, B/8, G/6, g/q and
+    /// "rn", "vv", "VV", or `chars` and the two-character `pairs` if given.
+    /// Composes with the prefix and suffix. Fails with `InvalidOptions` on
+    /// a bad length or set, or `ConflictingOptions` if a case-sensitive
+    /// prefix itself has a flagged character or pair in that range.
+    #[wasm_bindgen]
+    pub fn set_clean_prefix(
+        &mut self,
+        len: u8,
+        chars: Option<String>,
+        pairs: Option<Vec<String>>,
+    ) -> Result<(), VanityJsError> {
+        let clean_prefix = clean_prefix(len, chars, pairs)?;
+        self.searcher.set_clean_prefix(Some(clean_prefix))?;
+        Ok(())
+    }
 
-            let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
-            let encoded_len = five8::encode_32(&pubkey_bytes, &mut encoded_buf);
-            let pubkey = std::str::from_utf8(&encoded_buf[..encoded_len as usize]).unwrap();
+    #[wasm_bindgen]
+    pub fn clear_clean_prefix(&mut self) {
+        // Dropping the requirement can't conflict with the pattern.
+        let _ = self.searcher.set_clean_prefix(None);
+    }
 
-            let out_str_target_check = maybe_bs58_aware_lowercase(pubkey, self.case_insensitive);
+    /// The clean-prefix length and the characters and pairs it rules out,
+    /// or `undefined` without `set_clean_prefix`.
+    #[wasm_bindgen]
+    pub fn confusables(&self) -> Result<Option<Ts<ConfusableSet>>, VanityJsError> {
+        self.searcher
+            .clean_prefix()
+            .map(|clean_prefix| Ok(clean_prefix.set().into_ts().map_err(internal)?))
+            .transpose()
+    }
 
-            self.count += 1;
+    /// Runs up to `batch_size` attempts and returns the match, if any. Use
+    /// `search_batch_outcome` to also learn why a batch ended without one.
+    #[wasm_bindgen]
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<VanityResult> {
+        self.run_batch(batch_size)
+            .map(|found| self.to_result(found))
+    }
 
-            // Check prefix/suffix matching using enum
-            let matches = match &self.match_type {
-                MatchType::Prefix(prefix) => out_str_target_check.starts_with(prefix),
-                MatchType::Suffix(suffix) => out_str_target_check.ends_with(suffix),
-                MatchType::Both(prefix, suffix) => {
-                    out_str_target_check.starts_with(prefix)
-                        && out_str_target_check.ends_with(suffix)
-                }
-            };
+    /// Like `search_batch`, but reports a `BatchOutcome`: whether the batch
+    /// found a match, ran to completion, was stopped (including mid-batch),
+    /// hit the attempt cap, or was aborted by a throwing progress callback,
+    /// plus the attempts and time this call took.
+    #[wasm_bindgen]
+    pub fn search_batch_outcome(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Ts<BatchOutcome>, VanityJsError> {
+        Ok(self.batch_outcome(batch_size).into_ts().map_err(internal)?)
+    }
 
-            if matches {
-                return Some(VanityResult::new(
-                    pubkey.to_string(),
-                    String::from_utf8_lossy(&seed).to_string(),
-                    self.count,
-                ));
-            }
+    /// Snapshot of attempts, rates, ETA and the chance a match would have
+    /// been found by now, ready to post to the main thread.
+    #[wasm_bindgen]
+    pub fn progress(&self) -> Result<Ts<ProgressSnapshot>, VanityJsError> {
+        let snapshot = progress_snapshot(&self.stats, &self.searcher, self.searcher.attempts());
+        Ok(snapshot.into_ts().map_err(internal)?)
+    }
+
+    /// Expected versus actual attempts so far, e.g. to show "found in 2.1M
+    /// attempts vs 11.3M expected (5.4× luck)" after a match: `luck` is
+    /// expected over actual, and `percentile` the chance a search succeeds
+    /// within this many attempts.
+    #[wasm_bindgen]
+    pub fn stats(&self) -> Result<Ts<LuckStats>, VanityJsError> {
+        Ok(self.searcher.luck_stats().into_ts().map_err(internal)?)
+    }
+
+    /// Memory in use: the module's whole linear memory in bytes (omitted
+    /// outside wasm) and the bytes held by this searcher itself.
+    #[wasm_bindgen]
+    pub fn memory_stats(&self) -> Result<Ts<MemoryStats>, VanityJsError> {
+        #[cfg(target_arch = "wasm32")]
+        let linear_memory_bytes = Some(core::arch::wasm32::memory_size::<0>() as u64 * 65_536);
+        #[cfg(not(target_arch = "wasm32"))]
+        let linear_memory_bytes = None;
+        let stats = MemoryStats {
+            linear_memory_bytes,
+            searcher_bytes: (std::mem::size_of::<VanitySearcher>()
+                - std::mem::size_of::<Searcher>()
+                + self.searcher.memory_bytes()) as u64,
+        };
+        Ok(stats.into_ts().map_err(internal)?)
+    }
+
+    /// Releases spare capacity held by this searcher. Linear memory itself
+    /// never shrinks; this only makes room for later allocations.
+    #[wasm_bindgen]
+    pub fn shrink(&mut self) {
+        self.searcher.shrink();
+    }
+
+    /// A new searcher with the same keys and pattern, starting from zero
+    /// attempts at `new_offset`, e.g. to hand part of the remaining range to
+    /// another worker. Stats, the stop flag and the progress callback are not
+    /// carried over. This searcher is left untouched.
+    #[wasm_bindgen]
+    pub fn fork(&self, new_offset: u64) -> VanitySearcher {
+        VanitySearcher::from_searcher(self.searcher.fork(new_offset))
+    }
+
+    /// Up to `n` example addresses for this searcher's keys, derived exactly
+    /// as a search would from consecutive counters starting at `start`, or at
+    /// a random counter if omitted (0 in `test_mode`). `n` is capped at
+    /// 1000, and the counters must stay below 2^53 so each entry's `counter`
+    /// is an exact JS number. The searcher's own position is not affected.
+    #[wasm_bindgen(unchecked_return_type = "AddressSample[]")]
+    pub fn sample_addresses(
+        &self,
+        n: u32,
+        start: Option<u64>,
+    ) -> Result<js_sys::Array, VanityJsError> {
+        if n > MAX_SAMPLES {
+            log_warn!("sample size {n} capped at {MAX_SAMPLES}");
+        }
+        let start = match start {
+            Some(start) => start,
+            None => random_u64()? % (MAX_SAFE_INTEGER - MAX_SAMPLES as u64),
+        };
+        if start.saturating_add(n.min(MAX_SAMPLES) as u64) > MAX_SAFE_INTEGER + 1 {
+            return Err(VanityError::InvalidOptions(format!(
+                "sample counters from {start} would pass Number.MAX_SAFE_INTEGER"
+            ))
+            .into());
         }
+        self.searcher
+            .sample_addresses(start, n)
+            .into_iter()
+            .map(|derived| {
+                let sample = AddressSample::new(derived, self.permutation());
+                Ok(JsValue::from(sample.into_ts().map_err(internal)?))
+            })
+            .collect()
+    }
+
+    /// Every intermediate value of the derivation for `counter`, as a
+    /// result reports it (e.g. `VanityResult.counter`): seed, the 80-byte
+    /// SHA-256 input and digest in hex, the address, each pattern check and
+    /// how long each step took. Computed by the search loop's own functions,
+    /// so it always shows what a search does. The searcher's position is not
+    /// affected.
+    #[wasm_bindgen]
+    pub fn trace(&self, counter: u64) -> Result<Ts<DerivationTrace>, VanityJsError> {
+        Ok(self
+            .searcher
+            .trace(counter, now_ms)
+            .into_ts()
+            .map_err(internal)?)
+    }
+
+    /// Keeps a ring of the latest `capacity` (1 to 1000) of every
+    /// `every_n_attempts`th address the search tries, for a UI to animate
+    /// with `drain_samples` at its own frame rate. Recording one copies its
+    /// 44 bytes into a buffer allocated here; with sampling off (the
+    /// default) the search loop doesn't look. Replaces any earlier ring.
+    #[wasm_bindgen]
+    pub fn set_sampling(
+        &mut self,
+        capacity: u32,
+        every_n_attempts: u32,
+    ) -> Result<(), VanityJsError> {
+        self.searcher
+            .set_sampling(Some((capacity, every_n_attempts)))?;
+        log_debug!("sampling every {every_n_attempts} attempts, keeping {capacity}");
+        Ok(())
+    }
+
+    /// Turns sampling off and frees the ring.
+    #[wasm_bindgen]
+    pub fn clear_sampling(&mut self) {
+        // Turning sampling off can't fail.
+        let _ = self.searcher.set_sampling(None);
+    }
+
+    /// The addresses sampled since the last call, oldest first, emptying
+    /// the ring; empty if sampling is off.
+    #[wasm_bindgen(unchecked_return_type = "string[]")]
+    pub fn drain_samples(&mut self) -> js_sys::Array {
+        self.searcher
+            .drain_samples()
+            .into_iter()
+            .map(|sample| JsValue::from(sample.address))
+            .collect()
+    }
+
+    /// Logs an 8-byte SHA-256 digest of the pubkey derived for every counter
+    /// that is a multiple of `every_n_counters`, keeping the latest
+    /// `capacity` (up to 1,048,576), to check this build's derivation
+    /// against another's with `compare_replay`, e.g. after a compiler
+    /// upgrade or on another backend. Off by default; while on, the search
+    /// loop runs as with sampling. Replaces any earlier log.
+    #[wasm_bindgen]
+    pub fn set_replay_log(
+        &mut self,
+        capacity: u32,
+        every_n_counters: u64,
+    ) -> Result<(), VanityJsError> {
+        self.searcher
+            .set_replay_log(Some((capacity, every_n_counters)))?;
+        log_debug!("replay log every {every_n_counters} counters, keeping {capacity}");
+        Ok(())
+    }
 
-        None
+    /// Turns the replay log off and frees it.
+    #[wasm_bindgen]
+    pub fn clear_replay_log(&mut self) {
+        // Turning the log off can't fail.
+        let _ = self.searcher.set_replay_log(None);
+    }
+
+    /// The replay log, 16 bytes an entry: the counter as a little-endian
+    /// u64, then the digest; oldest first, and empty if the log is off.
+    #[wasm_bindgen]
+    pub fn replay_log(&self) -> Vec<u8> {
+        self.searcher
+            .replay_log()
+            .map(vanity_core::ReplayLog::to_bytes)
+            .unwrap_or_default()
+    }
+
+    /// Scans exactly the counters in `[start, end)` and reports every match
+    /// with its counter, for work units handed out by a coordinator. The
+    /// searcher's own attempts, offset and attempt cap are neither used nor
+    /// changed, so a range always gives the same outcome. The stop flag and
+    /// `stop()` end the scan early, with `interrupted` set; the progress,
+    /// checkpoint and heartbeat callbacks don't fire. `end` can be at most
+    /// 2^53, so every counter is an exact JS number. With a counter
+    /// permutation the range is of logical counters, like `count_offset`.
+    #[wasm_bindgen]
+    pub fn search_range(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Ts<RangeOutcome>, VanityJsError> {
+        if end > MAX_SAFE_INTEGER + 1 {
+            return Err(VanityError::InvalidOptions(format!(
+                "range end {end} is past Number.MAX_SAFE_INTEGER + 1"
+            ))
+            .into());
+        }
+        Ok(self.range_outcome(start, end).into_ts().map_err(internal)?)
     }
 
     #[wasm_bindgen]
     pub fn stop(&mut self) {
-        self.should_exit = true;
+        self.searcher.stop();
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn attempts(&self) -> u64 {
-        self.count
+    /// Whether `stop()` was called; `search_batch` on a stopped searcher
+    /// returns `undefined` without doing any work.
+    #[wasm_bindgen]
+    pub fn is_stopped(&self) -> bool {
+        self.searcher.is_stopped()
     }
-}
 
-#[wasm_bindgen]
-pub struct VanityResult {
-    address: String,
-    seed: String,
-    attempts: u64,
-}
+    /// Lets another thread stop a running batch: `flag` is a one-element view
+    /// over a `SharedArrayBuffer`, and storing a non-zero value in it stops
+    /// the searcher within a few thousand attempts, as if `stop()` had been
+    /// called. Reset the flag to 0 before `restart()`, or the next batch
+    /// stops immediately. Fails if `flag` is not backed by shared memory,
+    /// which usually means the page isn't cross-origin isolated.
+    #[wasm_bindgen]
+    pub fn set_stop_flag(&mut self, flag: &js_sys::Int32Array) -> Result<(), VanityJsError> {
+        check_stop_flag(flag)?;
+        self.stop_flag = Some(flag.clone());
+        Ok(())
+    }
 
-#[wasm_bindgen]
-impl VanityResult {
-    #[wasm_bindgen(constructor)]
-    pub fn new(address: String, seed: String, attempts: u64) -> VanityResult {
-        VanityResult {
-            address,
-            seed,
-            attempts,
+    /// Stops polling the flag passed to `set_stop_flag`.
+    #[wasm_bindgen]
+    pub fn clear_stop_flag(&mut self) {
+        self.stop_flag = None;
+    }
+
+    /// Stops the searcher once `Date.now()` reaches `epoch_ms`, checked as
+    /// often as the stop flag, so it halts within a few thousand attempts of
+    /// the deadline even where timers are throttled, as in a background
+    /// tab. A batch ended this way has status "stopped" with
+    /// `deadline_reached` set; a deadline already past stops the next batch
+    /// before it starts. It applies while `collect_batch` is paused too, and
+    /// ends a `matches()` iteration as a stop does. Calling this again moves
+    /// the deadline. Fails with "InvalidOptions" if `epoch_ms` isn't finite.
+    #[wasm_bindgen]
+    pub fn stop_at(&mut self, epoch_ms: f64) -> Result<(), VanityJsError> {
+        if !epoch_ms.is_finite() {
+            return Err(VanityError::InvalidOptions(format!(
+                "deadline must be a finite timestamp, got {epoch_ms}"
+            ))
+            .into());
         }
+        self.deadline = Some(epoch_ms);
+        Ok(())
+    }
+
+    /// Drops the deadline set by `stop_at`. A searcher it already stopped
+    /// stays stopped until `restart()`.
+    #[wasm_bindgen]
+    pub fn clear_stop_at(&mut self) {
+        self.deadline = None;
     }
 
+    /// The deadline set by `stop_at`, if any.
     #[wasm_bindgen(getter)]
-    pub fn address(&self) -> String {
-        self.address.clone()
+    pub fn deadline(&self) -> Option<f64> {
+        self.deadline
+    }
+
+    /// Whether the deadline stopped the searcher, since the last `restart()`.
+    #[wasm_bindgen]
+    pub fn deadline_reached(&self) -> bool {
+        self.deadline_reached
+    }
+
+    /// Stops this searcher once `token` is cancelled; see `CancelToken`.
+    /// Replaces any token set before.
+    #[wasm_bindgen]
+    pub fn set_cancel_token(&mut self, token: &CancelToken) {
+        self.searcher.set_cancel_token(Some(token.shared()));
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_cancel_token(&mut self) {
+        self.searcher.set_cancel_token(None);
+    }
+
+    /// Keeps `attempts` in `counters[slot]`, so a dashboard can read every
+    /// worker's count from shared memory without messages: the searcher
+    /// stores its total attempts there every few thousand attempts while a
+    /// batch runs and exactly at the end of each chunk. Read the counters
+    /// with `Atomics.load`. Each store is an `Atomics.store`, so a reader
+    /// never sees a torn value and one slot's values never go backwards
+    /// short of `resume_from` an earlier token, but a count says nothing
+    /// about other memory: don't use it to tell whether a result is ready.
+    /// Fails with "InvalidProgressBuffer" if `counters` isn't over a
+    /// `SharedArrayBuffer` or `slot` is out of range.
+    #[wasm_bindgen]
+    pub fn attach_progress_buffer(
+        &mut self,
+        counters: &js_sys::BigUint64Array,
+        slot: u32,
+    ) -> Result<(), VanityJsError> {
+        let progress_slot = ProgressSlot::new(counters, slot)?;
+        progress_slot.publish(self.searcher.attempts());
+        self.progress_slot = Some(progress_slot);
+        Ok(())
+    }
+
+    /// Stops updating the counter passed to `attach_progress_buffer`; its
+    /// last value stays.
+    #[wasm_bindgen]
+    pub fn detach_progress_buffer(&mut self) {
+        self.progress_slot = None;
+    }
+
+    /// Calls `callback` with a progress snapshot (see `progress()`) every
+    /// `every_n_attempts` attempts, from inside `search_batch`. N is raised
+    /// to at least 10 000 so the callback can't dominate the hashing.
+    /// Returning `false` stops the searcher; if the callback throws, the
+    /// searcher stops and the exception is kept in `callback_error`. The
+    /// callback must not call methods of this searcher, which is busy:
+    /// those throw, and left uncaught that stops the search with a
+    /// `ReentrantCall` error. It can stop the search through the
+    /// `SearchControl` it is passed.
+    #[wasm_bindgen]
+    pub fn set_progress_callback(
+        &mut self,
+        #[wasm_bindgen(
+            unchecked_param_type = "(progress: ProgressSnapshot, control: SearchControl) => boolean | void"
+        )]
+        callback: js_sys::Function,
+        every_n_attempts: u32,
+    ) {
+        self.progress_callback = Some(PeriodicCallback::new(
+            callback,
+            every_n_attempts,
+            self.searcher.attempts(),
+        ));
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_progress_callback(&mut self) {
+        self.progress_callback = None;
+    }
+
+    /// Calls `callback` with a fresh `resume_token` every `every_n_attempts`
+    /// attempts (at least 10 000), so the app can persist progress without
+    /// polling. Checkpoints are taken between chunks of a batch, so the token
+    /// covers exactly the attempts completed so far. Returning `false`
+    /// pauses checkpointing until `resume_checkpoints()`; the search itself
+    /// goes on. If the callback throws, the searcher stops and the exception
+    /// is kept in `callback_error`. As with the progress callback, calling
+    /// into this searcher fails with `ReentrantCall`; stop it through the
+    /// `SearchControl` passed.
+    #[wasm_bindgen]
+    pub fn set_checkpoint_callback(
+        &mut self,
+        #[wasm_bindgen(
+            unchecked_param_type = "(token: string, control: SearchControl) => boolean | void"
+        )]
+        callback: js_sys::Function,
+        every_n_attempts: u32,
+    ) {
+        self.checkpoint_callback = Some(PeriodicCallback::new(
+            callback,
+            every_n_attempts,
+            self.searcher.attempts(),
+        ));
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_checkpoint_callback(&mut self) {
+        self.checkpoint_callback = None;
+    }
+
+    /// Restarts checkpoints paused by the callback returning `false`; the
+    /// next one comes a full interval from now.
+    #[wasm_bindgen]
+    pub fn resume_checkpoints(&mut self) {
+        let attempts = self.searcher.attempts();
+        if let Some(checkpoint) = self.checkpoint_callback.as_mut() {
+            checkpoint.reschedule(attempts);
+        }
+    }
+
+    /// Calls `callback(sequence, attempts)` from inside `search_batch`
+    /// whenever at least `interval_ms` of active search time has passed
+    /// since the previous beat, with `sequence` counting up from 0. A worker
+    /// whose beats stop while it should be searching has stalled. The clock
+    /// is only read every few thousand attempts, so beats can come a few
+    /// milliseconds late. If the callback throws, the searcher stops and the
+    /// exception is kept in `callback_error`. As with the progress callback,
+    /// calling into this searcher fails with `ReentrantCall`; a stop through
+    /// the `SearchControl` passed takes effect within the batch.
+    #[wasm_bindgen]
+    pub fn set_heartbeat(
+        &mut self,
+        #[wasm_bindgen(
+            unchecked_param_type = "(sequence: number, attempts: number, control: SearchControl) => void"
+        )]
+        callback: js_sys::Function,
+        interval_ms: f64,
+    ) {
+        self.heartbeat = Some(HeartbeatCallback {
+            callback,
+            schedule: Heartbeat::new(interval_ms, self.stats.active_ms()),
+        });
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_heartbeat(&mut self) {
+        self.heartbeat = None;
     }
 
+    /// Handle to stop this searcher from inside its callbacks, where its own
+    /// methods can't be called; the same one they are passed.
+    #[wasm_bindgen]
+    pub fn control(&self) -> SearchControl {
+        self.control.clone()
+    }
+
+    /// What the progress, checkpoint, heartbeat or match callback threw
+    /// when it stopped the search, or `undefined`: a `ReentrantCall` error
+    /// if it called into this searcher. Cleared by `restart()`.
     #[wasm_bindgen(getter)]
-    pub fn seed(&self) -> String {
-        self.seed.clone()
+    pub fn callback_error(&self) -> JsValue {
+        self.callback_error.clone().unwrap_or(JsValue::UNDEFINED)
     }
 
+    /// The "Internal" `VanityError` that stopped the search if the searcher
+    /// itself broke, e.g. its base58 encoder produced something that isn't
+    /// an address, or `undefined`. Batches then report status "error"
+    /// instead of the module trapping. Cleared by `restart()`.
     #[wasm_bindgen(getter)]
-    pub fn attempts(&self) -> u64 {
-        self.attempts
+    pub fn fault(&self) -> JsValue {
+        self.searcher.fault().map_or(JsValue::UNDEFINED, |fault| {
+            VanityJsError(fault.clone()).into()
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Caps the attempts this instance makes, not counting `count_offset`;
+    /// `undefined` removes the cap. Once it is reached, `is_exhausted()` is
+    /// true and `search_batch` returns `undefined` without doing any work
+    /// until the cap is raised or removed. A match found on the last allowed
+    /// attempt is still returned.
+    #[wasm_bindgen]
+    pub fn set_max_attempts(&mut self, max_attempts: Option<u64>) {
+        self.searcher.set_max_attempts(max_attempts);
+    }
 
-    #[test]
-    fn test_search_batch() {
-        let mut vanity_searcher =
-            VanitySearcher::new(&[1, 2, 3], &[4, 5, 6], Some("AAA".into()), None, false, 0);
+    /// Tracks the address that came closest to matching and how often each
+    /// part of the pattern nearly matched, so a `search_batch_outcome` that
+    /// ends without a match reports them in `partial`. Costs a full encode
+    /// per attempt; `false` stops and drops what was tracked. New keys or a
+    /// new seed namespace start it over.
+    #[wasm_bindgen]
+    pub fn set_partial_tracking(&mut self, track: bool) {
+        self.searcher.set_partial_tracking(track);
+    }
 
-        loop {
-            if let Some(vanity_result) = vanity_searcher.search_batch(1000) {
-                println!("{}", vanity_result.address);
-                break;
-            }
+    /// What partial tracking has gathered so far; `undefined` unless it is
+    /// on.
+    #[wasm_bindgen(unchecked_return_type = "PartialOutcome | undefined")]
+    pub fn partial(&self) -> Result<JsValue, VanityJsError> {
+        match self.partial_outcome() {
+            Some(partial) => Ok(partial.into_ts().map_err(internal)?.into()),
+            None => Ok(JsValue::UNDEFINED),
         }
     }
+
+    /// Skips the counter blocks `filter` holds as if they had been searched,
+    /// using a copy of it as it is now: later inserts into `filter` need
+    /// another call. Skipped counters count towards `attempts`, the
+    /// attempt cap and coverage, but not the rate, and are tallied in
+    /// `skipped`. Blocks are of logical counters, as in `count_offset`.
+    #[wasm_bindgen]
+    pub fn set_seen_filter(&mut self, filter: &SeenFilter) {
+        self.searcher.set_seen_filter(Some(filter.inner().clone()));
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_seen_filter(&mut self) {
+        self.searcher.set_seen_filter(None);
+    }
+
+    /// Counters skipped as already covered by the seen filter, out of
+    /// `attempts`; not restored by `resume_from`.
+    #[wasm_bindgen(getter)]
+    pub fn skipped(&self) -> u64 {
+        self.searcher.skipped()
+    }
+
+    /// Gives up without a match once the attempts made (not counting
+    /// `count_offset`) would have found one with chance `probability` at the
+    /// pattern's estimated odds, e.g. 0.95 for about three times the
+    /// expected attempts; `undefined` removes the limit. Composes with
+    /// `set_max_attempts`: whichever is reached first ends the search, and
+    /// makes `is_exhausted()` true. Reaching this limit gives the status
+    /// "coverage_reached" and `coverage` the chance achieved. Fails with
+    /// "InvalidOptions" unless `0 < probability < 1`.
+    #[wasm_bindgen]
+    pub fn set_stop_at_probability(
+        &mut self,
+        probability: Option<f64>,
+    ) -> Result<(), VanityJsError> {
+        Ok(self.searcher.set_stop_at_probability(probability)?)
+    }
+
+    /// Chance the attempts made so far would have found a match at the
+    /// pattern's estimated odds, as `probability_found` in `progress()`.
+    #[wasm_bindgen(getter)]
+    pub fn coverage(&self) -> f64 {
+        self.searcher.coverage()
+    }
+
+    /// Whether the attempt cap, the stop probability or the whole counter
+    /// space is used up. `restart()` doesn't clear this.
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+
+    /// Walks the counter space in a pseudo-random order chosen by `key`, or
+    /// by a fresh random key if omitted (0 in `test_mode`), and returns the
+    /// key. Positions (`count_offset`, `absolute_position`, ranges) stay
+    /// logical, so workers given disjoint ranges still never overlap, but a
+    /// new session with a new key no longer re-grinds the counters an
+    /// earlier one covered. The key is saved in `resume_token`, and results
+    /// carry the physical counter their seed came from.
+    #[wasm_bindgen]
+    pub fn permute_counters(&mut self, key: Option<u64>) -> Result<u64, VanityJsError> {
+        let key = match key {
+            Some(key) => key,
+            None => random_u64()?,
+        };
+        self.searcher.set_counter_permutation(Some(key));
+        log_debug!("permuting counters with key {key:016x}");
+        Ok(key)
+    }
+
+    /// Walks counters in order again.
+    #[wasm_bindgen]
+    pub fn clear_counter_permutation(&mut self) {
+        self.searcher.set_counter_permutation(None);
+    }
+
+    /// Key set by `permute_counters`, or `undefined` if counters are walked
+    /// in order.
+    #[wasm_bindgen(getter)]
+    pub fn counter_permutation_key(&self) -> Option<u64> {
+        self.searcher.counter_permutation_key()
+    }
+
+    /// Folds a session tag into every seed, so a new session restarting
+    /// from counter 0 tries different seeds than the last one without any
+    /// record of what it covered. The tag is 1 to 32 letters, digits, `-`
+    /// or `_`; a random 11-character one is used if omitted (all "1"s in
+    /// `test_mode`). Returns the tag, which is saved in `resume_token` and
+    /// carried by every result: keep it, as counters only reproduce seeds
+    /// together with it. Fails with `InvalidOptions` for a bad tag.
+    #[wasm_bindgen]
+    pub fn namespace_seeds(&mut self, namespace: Option<String>) -> Result<String, VanityJsError> {
+        let tag = match namespace {
+            Some(tag) => tag,
+            None => random_namespace()?,
+        };
+        self.searcher
+            .set_seed_namespace(Some(SeedNamespace::new(&tag)?));
+        log_debug!("seed namespace {tag}");
+        Ok(tag)
+    }
+
+    /// Derives seeds from plain counters again.
+    #[wasm_bindgen]
+    pub fn clear_seed_namespace(&mut self) {
+        self.searcher.set_seed_namespace(None);
+    }
+
+    /// Tag set by `namespace_seeds`, or `undefined` without one.
+    #[wasm_bindgen(getter)]
+    pub fn seed_namespace(&self) -> Option<String> {
+        self.searcher
+            .seed_namespace()
+            .map(|namespace| namespace.tag().to_string())
+    }
+
+    /// Version of the seed scheme seeds are generated with: the latest,
+    /// `seed_scheme_version()`, unless set with `set_seed_scheme` or
+    /// restored by `resume_from` from a token saved under an older one.
+    #[wasm_bindgen(getter)]
+    pub fn seed_scheme(&self) -> u32 {
+        self.searcher.seed_scheme().unwrap_or_default().version()
+    }
+
+    /// Generates seeds with scheme `version` from now on, keeping the
+    /// position, e.g. to carry on a search saved before a newer scheme
+    /// became the default. Resume tokens saved under the old scheme no
+    /// longer apply. Fails with `InvalidOptions` for an unknown version.
+    #[wasm_bindgen]
+    pub fn set_seed_scheme(&mut self, version: u32) -> Result<(), VanityJsError> {
+        self.searcher
+            .set_seed_scheme(SeedScheme::from_version(version)?)?;
+        Ok(())
+    }
+
+    /// Derives from `base_pubkey` (32 bytes or base58) from now on, e.g.
+    /// after the user switched wallets, keeping the pattern, options, rate
+    /// history and attempts. Results already found keep the keys they were
+    /// derived from. With `reset_counter`, the search starts over at its
+    /// `count_offset` with no attempts; counters covered under the old key
+    /// say nothing about the new one, which is also why a `set_seen_filter`
+    /// filter is dropped. Resume tokens saved under the old key no longer
+    /// apply. Fails with `BadBaseLength` or `InvalidBase`, changing
+    /// nothing.
+    #[wasm_bindgen]
+    pub fn set_base_pubkey(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Uint8Array | string")] base_pubkey: JsValue,
+        reset_counter: Option<bool>,
+    ) -> Result<(), VanityJsError> {
+        let base_pubkey = pubkey_from_js(&base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = *self.searcher.derivation().owner_pubkey();
+        self.set_keys(base_pubkey.as_bytes(), &owner_pubkey, reset_counter);
+        Ok(())
+    }
+
+    /// `set_base_pubkey` for the owner program; fails with `BadOwnerLength`
+    /// or `InvalidOwner`.
+    #[wasm_bindgen]
+    pub fn set_owner(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Uint8Array | string")] owner_pubkey: JsValue,
+        reset_counter: Option<bool>,
+    ) -> Result<(), VanityJsError> {
+        let owner_pubkey = pubkey_from_js(&owner_pubkey, KeyRole::Owner)?;
+        let base_pubkey = *self.searcher.derivation().base_pubkey();
+        self.set_keys(&base_pubkey, owner_pubkey.as_bytes(), reset_counter);
+        Ok(())
+    }
+
+    /// Makes a stopped searcher usable again, continuing from where it left
+    /// off. Move or clear a deadline that has passed first, or the next
+    /// batch stops again at once.
+    #[wasm_bindgen]
+    pub fn restart(&mut self) {
+        self.callback_error = None;
+        self.deadline_reached = false;
+        self.searcher.restart();
+    }
+
+    /// Attempts per second, an exponential moving average over recent
+    /// batches measured inside the searcher; `undefined` before the first
+    /// batch. Time between batches, e.g. while stopped, doesn't count.
+    #[wasm_bindgen(getter)]
+    pub fn rate(&self) -> Option<f64> {
+        self.stats.rate()
+    }
+
+    /// Time constant of `rate`, in milliseconds of active searching.
+    #[wasm_bindgen(getter)]
+    pub fn rate_window(&self) -> f64 {
+        self.stats.rate_window_ms()
+    }
+
+    /// Attempts per second in each completed bucket of active time, oldest
+    /// first, e.g. for a sparkline: by default one-second buckets over the
+    /// last five minutes (see `set_rate_history`). Recorded at batch
+    /// boundaries inside the searcher, so delayed messages can't skew it,
+    /// and time between batches adds no buckets.
+    #[wasm_bindgen]
+    pub fn rate_history(&self) -> js_sys::Float64Array {
+        js_sys::Float64Array::from(&self.stats.rate_history().rates()[..])
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_rate_history(&mut self) {
+        self.stats.rate_history_mut().clear();
+    }
+
+    /// Buckets `rate_history` by `resolution_ms` of active time, keeping
+    /// the last `buckets` (300 if omitted), and clears it. Fails with
+    /// `InvalidOptions` unless `resolution_ms` is positive and `buckets` is
+    /// 1 to `max_rate_history_buckets()`.
+    #[wasm_bindgen]
+    pub fn set_rate_history(
+        &mut self,
+        resolution_ms: f64,
+        buckets: Option<u32>,
+    ) -> Result<(), VanityJsError> {
+        let history = RateHistory::new(
+            resolution_ms,
+            buckets.unwrap_or(DEFAULT_RATE_HISTORY_BUCKETS),
+        )?;
+        self.stats.set_rate_history(history);
+        Ok(())
+    }
+
+    /// Alias of `local_attempts`, kept for existing callers.
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.local_attempts()
+    }
+
+    /// Attempts made by this instance, excluding `count_offset`.
+    #[wasm_bindgen(getter)]
+    pub fn local_attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    /// This instance's absolute position in the shared counter space
+    /// (`count_offset + local_attempts`), i.e. the next counter it will try.
+    /// `undefined` once the counter space is exhausted.
+    #[wasm_bindgen(getter)]
+    pub fn absolute_position(&self) -> Option<u64> {
+        self.searcher.absolute_position()
+    }
+
+    /// Compact string to persist instead of a bare counter: it records
+    /// `count_offset`, `local_attempts`, any counter permutation key and
+    /// any seed namespace along with a hash of the keys, pattern, case flag
+    /// and seed scheme, so `resume_from` can refuse it for a different
+    /// search.
+    #[wasm_bindgen(getter)]
+    pub fn resume_token(&self) -> String {
+        self.searcher.resume_token()
+    }
+
+    /// Continues from a position saved with `resume_token`. Throws a
+    /// `VanityError` with kind "ResumeTokenMismatch" if the token belongs to
+    /// a searcher with different keys, pattern, case flag or seed scheme, or
+    /// "InvalidResumeToken" if it can't be parsed; the searcher is unchanged
+    /// in either case. A token that doesn't name its seed scheme is taken
+    /// to be version 1's, or `unnamed_scheme`'s if given: pass 0 for a token
+    /// saved by this web build before seed schemes were versioned, whose
+    /// seeds came out differently (see `reproduce`).
+    #[wasm_bindgen]
+    pub fn resume_from(
+        &mut self,
+        token: &str,
+        unnamed_scheme: Option<u32>,
+    ) -> Result<(), VanityJsError> {
+        let unnamed = unnamed_scheme.map_or(Ok(SeedScheme::V1), SeedScheme::from_version)?;
+        self.searcher.resume_from_as(token, unnamed)?;
+        let attempts = self.searcher.attempts();
+        if let Some(progress) = self.progress_callback.as_mut() {
+            progress.reschedule(attempts);
+        }
+        if let Some(checkpoint) = self.checkpoint_callback.as_mut() {
+            if checkpoint.next_at != u64::MAX {
+                checkpoint.reschedule(attempts);
+            }
+        }
+        if let Some(progress_slot) = &self.progress_slot {
+            progress_slot.publish(attempts);
+        }
+        log_debug!("resumed at attempt {attempts}");
+        Ok(())
+    }
+}
+
+impl VanitySearcher {
+    fn run_batch(&mut self, batch_size: u32) -> Option<SearchMatch> {
+        self.apply_stop_request();
+        self.apply_deadline();
+        if self.searcher.is_stopped() {
+            log_debug!("search_batch called on a stopped searcher");
+        }
+
+        // Split the batch at progress and checkpoint boundaries so the
+        // callbacks run between chunks, never inside the core loop.
+        let mut remaining = batch_size;
+        let found = loop {
+            let attempts = self.searcher.attempts();
+            let chunk = [&self.progress_callback, &self.checkpoint_callback]
+                .into_iter()
+                .flatten()
+                .map(|callback| callback.until_next(attempts))
+                .fold(remaining, u32::min);
+            let start_count = self.searcher.attempts();
+            let found = self.search_chunk(chunk);
+            if found.is_some() {
+                break found;
+            }
+            self.report_progress();
+            self.report_checkpoint();
+
+            remaining -= chunk;
+            let exhausted = self.searcher.attempts() - start_count < chunk as u64;
+            if remaining == 0 || exhausted || self.searcher.is_stopped() {
+                break None;
+            }
+        };
+
+        if let Some(found) = &found {
+            log_info!("match {} after {} attempts", found.address, found.attempts);
+        }
+        found
+    }
+
+    fn permutation(&self) -> Option<CounterPermutation> {
+        self.searcher
+            .counter_permutation_key()
+            .map(CounterPermutation::new)
+    }
+
+    fn range_outcome(&mut self, start: u64, end: u64) -> RangeOutcome {
+        let stop_flag = self.stop_flag.as_ref();
+        let outcome = self.searcher.search_range_until(start, end, || {
+            stop_flag.is_some_and(|flag| js_sys::Atomics::load(flag, 0).unwrap_or(0) != 0)
+        });
+        log_debug!(
+            "range {start}..{end}: {} matches in {} attempts",
+            outcome.matches.len(),
+            outcome.attempts
+        );
+        RangeOutcome {
+            matches: outcome
+                .matches
+                .into_iter()
+                .map(|derived| AddressSample::new(derived, self.permutation()))
+                .collect(),
+            attempts: outcome.attempts,
+            interrupted: outcome.interrupted,
+        }
+    }
+
+    fn batch_outcome(&mut self, batch_size: u32) -> BatchOutcome {
+        let start_ms = now_ms();
+        let start = (self.searcher.attempts(), self.searcher.skipped());
+        let found = self.run_batch(batch_size);
+
+        let status = match self.searcher.batch_status(found.is_some()) {
+            BatchStatus::Stopped if self.callback_error.is_some() => BatchStatus::Error,
+            status => status,
+        };
+        let result = found.map(|found| self.to_result(found).to_plain());
+        let mut outcome =
+            BatchOutcome::new(&self.searcher, status, result, start, now_ms() - start_ms);
+        if status != BatchStatus::Found {
+            outcome.partial = self.partial_outcome();
+        }
+        outcome.deadline_reached = status == BatchStatus::Stopped && self.deadline_reached;
+        outcome
+    }
+
+    fn partial_outcome(&self) -> Option<PartialOutcome> {
+        let stats = self.searcher.partial_stats()?.clone();
+        let closest = self.searcher.closest_match();
+        Some(PartialOutcome {
+            closest: closest.map(|found| self.to_result(found).to_plain()),
+            stats,
+        })
+    }
+
+    fn set_keys(
+        &mut self,
+        base_pubkey: &[u8; 32],
+        owner_pubkey: &[u8; 32],
+        reset_counter: Option<bool>,
+    ) {
+        let reset_counter = reset_counter.unwrap_or(false);
+        log_debug!(
+            "new keys: base={} reset_counter={reset_counter}",
+            Pubkey::new(*base_pubkey)
+        );
+        self.searcher
+            .set_keys(base_pubkey, owner_pubkey, reset_counter);
+    }
+
+    /// `found` as a result recording the keys it was derived from.
+    fn to_result(&self, found: SearchMatch) -> VanityResult {
+        VanityResult::from(found).with_keys(self.searcher.derivation())
+    }
+
+    /// The searcher behind the constructor, before the feasibility check.
+    fn build(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: u64,
+        allow_empty_pattern: bool,
+    ) -> Result<Searcher, VanityError> {
+        log_debug!(
+            "new searcher: prefix={prefix:?} suffix={suffix:?} case_insensitive={case_insensitive} count_offset={count_offset}"
+        );
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let searcher = if allow_empty_pattern {
+            let typed = MatchType::new(prefix.clone(), suffix.clone(), false);
+            let match_type = MatchType::new(prefix, suffix, case_insensitive);
+            match_type.validate(case_insensitive)?;
+            let mut searcher = Searcher::with_match_type(
+                base_pubkey.as_bytes(),
+                owner_pubkey.as_bytes(),
+                match_type,
+                case_insensitive,
+                count_offset,
+            );
+            searcher.set_typed_pattern(typed);
+            searcher
+        } else {
+            Searcher::new(
+                base_pubkey.as_bytes(),
+                owner_pubkey.as_bytes(),
+                prefix,
+                suffix,
+                case_insensitive,
+                count_offset,
+            )?
+        };
+        Ok(searcher)
+    }
+
+    fn from_searcher(searcher: Searcher) -> VanitySearcher {
+        VanitySearcher {
+            searcher,
+            stats: SearchStats::default(),
+            stop_flag: None,
+            deadline: None,
+            deadline_reached: false,
+            progress_slot: None,
+            progress_callback: None,
+            checkpoint_callback: None,
+            heartbeat: None,
+            callback_error: None,
+            control: SearchControl::new(),
+            pattern_rewrites: Vec::new(),
+            results: ResultQueue::default(),
+        }
+    }
+
+    fn search_chunk(&mut self, chunk: u32) -> Option<vanity_core::SearchMatch> {
+        let start_ms = now_ms();
+        let start_count = self.searcher.attempts();
+        let start_skipped = self.searcher.skipped();
+        let had_fault = self.searcher.fault().is_some();
+
+        let stop_flag = self.stop_flag.as_ref();
+        let progress_slot = self.progress_slot.as_ref();
+        let mut heartbeat = self.heartbeat.as_mut();
+        let callback_error = &mut self.callback_error;
+        let control = &self.control;
+        let deadline = self.deadline;
+        let mut deadline_reached = false;
+        let active_ms = self.stats.active_ms();
+        // Polls come every INTERRUPT_POLL_INTERVAL attempts, starting with
+        // the first, so the poll count gives the attempts made so far; after
+        // a skipped seen block it falls short until the chunk ends.
+        let mut polls = 0;
+        let found = self.searcher.search_batch_until(chunk, || {
+            let attempts = start_count + polls * INTERRUPT_POLL_INTERVAL as u64;
+            polls += 1;
+            if let Some(progress_slot) = progress_slot {
+                progress_slot.publish(attempts);
+            }
+            if let Some(heartbeat) = heartbeat.as_mut() {
+                let now = active_ms + now_ms() - start_ms;
+                if let Some(sequence) = heartbeat.schedule.beat(now) {
+                    let result = heartbeat.callback.call3(
+                        &JsValue::NULL,
+                        &(sequence as f64).into(),
+                        &(attempts as f64).into(),
+                        &control.clone().into(),
+                    );
+                    if let Err(error) = result {
+                        log_error!("heartbeat callback threw; stopping search");
+                        *callback_error = Some(control::callback_error("heartbeat", error));
+                        return true;
+                    }
+                }
+            }
+            deadline_reached = deadline.is_some_and(|deadline| epoch_ms() >= deadline);
+            deadline_reached
+                || control.stop_requested()
+                || stop_flag.is_some_and(|flag| js_sys::Atomics::load(flag, 0).unwrap_or(0) != 0)
+        });
+        if found.is_none() && self.searcher.is_stopped() {
+            if deadline_reached {
+                log_debug!("batch stopped at its deadline");
+                self.deadline_reached = true;
+            } else if stop_flag.is_some() {
+                log_debug!("batch interrupted by stop flag");
+            }
+        }
+        if let Some(fault) = self.searcher.fault().filter(|_| !had_fault) {
+            log_error!("search stopped: {fault}");
+            report::report_error(fault);
+        }
+
+        if let Some(progress_slot) = progress_slot {
+            progress_slot.publish(self.searcher.attempts());
+        }
+        // Skipped counters took no time; counting them would inflate the rate.
+        let searched =
+            self.searcher.attempts() - start_count - (self.searcher.skipped() - start_skipped);
+        self.stats.record_batch(searched, now_ms() - start_ms);
+        self.apply_stop_request();
+        found
+    }
+
+    fn report_progress(&mut self) {
+        let attempts = self.searcher.attempts();
+        let Some(progress) = self.progress_callback.as_mut() else {
+            return;
+        };
+        if !progress.due(attempts) {
+            return;
+        }
+
+        let snapshot = progress_snapshot(&self.stats, &self.searcher, attempts);
+        let result = snapshot
+            .into_ts()
+            .map(JsValue::from)
+            .map_err(|error| JsError::from(error).into())
+            .and_then(|snapshot| {
+                progress
+                    .callback
+                    .call2(&JsValue::NULL, &snapshot, &self.control.clone().into())
+            });
+        match result {
+            Ok(keep_going) if keep_going.as_bool() == Some(false) => {
+                log_debug!("progress callback requested a stop");
+                self.searcher.stop();
+            }
+            Ok(_) => {}
+            Err(error) => {
+                log_error!("progress callback threw; stopping search");
+                self.callback_error = Some(control::callback_error("progress", error));
+                self.searcher.stop();
+            }
+        }
+        self.apply_stop_request();
+    }
+
+    fn report_checkpoint(&mut self) {
+        let attempts = self.searcher.attempts();
+        let Some(checkpoint) = self.checkpoint_callback.as_mut() else {
+            return;
+        };
+        if !checkpoint.due(attempts) {
+            return;
+        }
+
+        let token = JsValue::from(self.searcher.resume_token());
+        match checkpoint
+            .callback
+            .call2(&JsValue::NULL, &token, &self.control.clone().into())
+        {
+            Ok(keep_going) if keep_going.as_bool() == Some(false) => {
+                log_debug!("checkpoint callback paused checkpoints");
+                checkpoint.next_at = u64::MAX;
+            }
+            Ok(_) => {}
+            Err(error) => {
+                log_error!("checkpoint callback threw; stopping search");
+                self.callback_error = Some(control::callback_error("checkpoint", error));
+                self.searcher.stop();
+            }
+        }
+        self.apply_stop_request();
+    }
+
+    /// Applies a stop requested through the `SearchControl`.
+    fn apply_stop_request(&mut self) {
+        if self.control.take_stop() {
+            log_debug!("stop requested through the search control");
+            self.searcher.stop();
+        }
+    }
+
+    /// Stops the searcher if its deadline has passed, without searching.
+    fn apply_deadline(&mut self) {
+        if self.deadline.is_some_and(|deadline| epoch_ms() >= deadline) {
+            if !self.searcher.is_stopped() {
+                log_debug!("deadline passed; stopping");
+                self.deadline_reached = true;
+            }
+            self.searcher.stop();
+        }
+    }
+}
+
+/// Returned by `memory_stats`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct MemoryStats {
+    #[tsify(optional)]
+    pub linear_memory_bytes: Option<u64>,
+    pub searcher_bytes: u64,
+}
+
+/// What one `search_batch_outcome` call did, ready for postMessage.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct BatchOutcome {
+    pub status: BatchStatus,
+    #[tsify(optional)]
+    pub result: Option<VanityResultJson>,
+    /// Attempts made during this call.
+    pub attempts: u64,
+    pub elapsed_ms: f64,
+    /// The searcher's `coverage` after the call; with status
+    /// "coverage_reached", the chance it gave up at.
+    pub coverage: f64,
+    /// First logical counter this call covered, in decimal since offsets
+    /// span all of u64; unset if it covered none. Right after the previous
+    /// call's `last_counter`.
+    #[tsify(optional)]
+    pub first_counter: Option<String>,
+    /// Last logical counter covered, inclusive: a killed worker's remainder
+    /// starts right after it, whether the call ended on a match, a stop or
+    /// exhaustion.
+    #[tsify(optional)]
+    pub last_counter: Option<String>,
+    /// Candidates actually derived and matched, out of `attempts`; counters
+    /// skipped by a seen filter are covered but not evaluated.
+    pub evaluated: u64,
+    /// With partial tracking on, what it gathered, unless the call found a
+    /// match.
+    #[tsify(optional)]
+    pub partial: Option<PartialOutcome>,
+    /// With status "stopped", whether the `stop_at` deadline was the cause.
+    pub deadline_reached: bool,
+}
+
+/// The closest a search got without matching, from `set_partial_tracking`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct PartialOutcome {
+    /// The closest address with its seed, as if it had matched; `attempts`
+    /// is when it was derived.
+    #[tsify(optional)]
+    pub closest: Option<VanityResultJson>,
+    pub stats: PartialStats,
+}
+
+impl BatchOutcome {
+    /// The outcome of a call that started with `start_count` attempts and
+    /// `start_skipped` skipped.
+    fn new<D: Derivation>(
+        searcher: &Searcher<D>,
+        status: BatchStatus,
+        result: Option<VanityResultJson>,
+        (start_count, start_skipped): (u64, u64),
+        elapsed_ms: f64,
+    ) -> BatchOutcome {
+        let span = searcher.counters_since(start_count);
+        let attempts = searcher.attempts() - start_count;
+        BatchOutcome {
+            status,
+            result,
+            attempts,
+            elapsed_ms,
+            coverage: searcher.coverage(),
+            first_counter: span.map(|(first, _)| first.to_string()),
+            last_counter: span.map(|(_, last)| last.to_string()),
+            evaluated: attempts - (searcher.skipped() - start_skipped),
+            partial: None,
+            deadline_reached: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VanityResult {
+    address: String,
+    seed: Vec<u8>,
+    attempts: u64,
+    counter: Option<u64>,
+    case_mismatch_positions: Vec<u32>,
+    seed_namespace: Option<String>,
+    seed_scheme: Option<u32>,
+    base_pubkey: Option<String>,
+    owner_pubkey: Option<String>,
+}
+
+#[wasm_bindgen]
+impl VanityResult {
+    /// `seed` as the `seed` getter shows it: the seed itself, or "hex:"
+    /// and its bytes in hex. Fails with `InvalidOptions` for malformed hex.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        address: String,
+        seed: String,
+        attempts: u64,
+        counter: Option<u64>,
+    ) -> Result<VanityResult, VanityJsError> {
+        let seed = vanity_core::seed_from_string(&seed)?;
+        Ok(VanityResult::from_seed_bytes(
+            address, seed, attempts, counter,
+        ))
+    }
+
+    #[wasm_bindgen]
+    pub fn from_seed_bytes(
+        address: String,
+        seed: Vec<u8>,
+        attempts: u64,
+        counter: Option<u64>,
+    ) -> VanityResult {
+        VanityResult {
+            address,
+            seed,
+            attempts,
+            counter,
+            case_mismatch_positions: Vec::new(),
+            seed_namespace: None,
+            seed_scheme: None,
+            base_pubkey: None,
+            owner_pubkey: None,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    /// The seed as text, or "hex:" and its bytes in hex if they aren't
+    /// UTF-8 (or the text would start with "hex:"). `seed_bytes` is what the
+    /// address was derived from.
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> String {
+        vanity_core::seed_to_string(&self.seed)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed_bytes(&self) -> Vec<u8> {
+        self.seed.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.attempts
+    }
+
+    /// Physical counter the seed was generated from, which differs from
+    /// the position once counters are permuted; `undefined` if the result
+    /// was constructed without one.
+    #[wasm_bindgen(getter)]
+    pub fn counter(&self) -> Option<u64> {
+        self.counter
+    }
+
+    /// For a case-insensitive match, the positions in `address` whose case
+    /// differs from the pattern as typed, e.g. to render them dimmed.
+    #[wasm_bindgen(getter)]
+    pub fn case_mismatch_positions(&self) -> Vec<u32> {
+        self.case_mismatch_positions.clone()
+    }
+
+    /// Seed namespace the search ran under (see
+    /// `VanitySearcher.namespace_seeds`); pass it to `reproduce` with the
+    /// counter.
+    #[wasm_bindgen(getter)]
+    pub fn seed_namespace(&self) -> Option<String> {
+        self.seed_namespace.clone()
+    }
+
+    /// Version of the seed scheme the seed was generated with; pass it to
+    /// `reproduce` with the counter. `undefined` for a result constructed
+    /// directly or saved before schemes were recorded, which used
+    /// version 1.
+    #[wasm_bindgen(getter)]
+    pub fn seed_scheme(&self) -> Option<u32> {
+        self.seed_scheme
+    }
+
+    /// Base key the address was derived from, in base58, as it was when
+    /// the match was found; `undefined` for a result constructed directly.
+    #[wasm_bindgen(getter)]
+    pub fn base_pubkey(&self) -> Option<String> {
+        self.base_pubkey.clone()
+    }
+
+    /// Owner program the address was derived for; see `base_pubkey`.
+    #[wasm_bindgen(getter)]
+    pub fn owner_pubkey(&self) -> Option<String> {
+        self.owner_pubkey.clone()
+    }
+
+    /// Whether the matched part of `address` has exactly the case typed;
+    /// always true for a case-sensitive search.
+    #[wasm_bindgen(getter)]
+    pub fn exact_case_match(&self) -> bool {
+        self.case_mismatch_positions.is_empty()
+    }
+
+    /// The address's page on `provider` ("explorer.solana.com", "solscan"
+    /// or "solanafm") for `cluster` ("mainnet", "devnet" or "testnet").
+    /// Fails with `UnknownExplorer` or `UnknownCluster` for other names.
+    #[wasm_bindgen]
+    pub fn explorer_url(&self, cluster: &str, provider: &str) -> Result<String, VanityJsError> {
+        Ok(vanity_core::explorer_url(&self.address, cluster, provider)?)
+    }
+
+    /// A Solana Pay link asking for `amount` SOL (or any amount, if
+    /// omitted) to be sent to the address, shown to the payer as `label`.
+    /// Fails with `InvalidOptions` for a negative amount or one finer than
+    /// a lamport.
+    #[wasm_bindgen]
+    pub fn solana_pay_url(
+        &self,
+        amount: Option<f64>,
+        label: Option<String>,
+    ) -> Result<String, VanityJsError> {
+        Ok(vanity_core::solana_pay_url(
+            &self.address,
+            amount,
+            label.as_deref(),
+        )?)
+    }
+
+    /// Plain-object form, also used by `JSON.stringify`.
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json(&self) -> Result<Ts<VanityResultJson>, VanityJsError> {
+        Ok(self.to_plain().into_ts().map_err(internal)?)
+    }
+
+    /// The result back from its `toJSON` form, e.g. after a worker posted
+    /// it or it went through `JSON.stringify` and `JSON.parse`: the counter
+    /// is a decimal string there, so every u64 comes back exact, and a
+    /// binary seed comes back byte for byte. Fails with `InvalidOptions` for
+    /// a malformed object, counter or seed.
+    #[wasm_bindgen]
+    pub fn from_js(
+        #[wasm_bindgen(unchecked_param_type = "VanityResultJson")] value: JsValue,
+    ) -> Result<VanityResult, VanityJsError> {
+        let plain: VanityResultJson = serde_wasm_bindgen::from_value(value)
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid result: {e}")))?;
+        Ok(VanityResult::from_plain(plain)?)
+    }
+
+    /// Whether `seed_bytes` under `base_pubkey` and `owner_pubkey` really
+    /// derives `address`, e.g. for a result rebuilt with `from_js`, before
+    /// funding it. The counter isn't checked; `reproduce` regenerates the
+    /// seed from it. Fails with `InvalidOptions` for a result without its
+    /// keys, or `InvalidBase`/`InvalidOwner` for keys that don't decode.
+    #[wasm_bindgen]
+    pub fn verify(&self) -> Result<bool, VanityJsError> {
+        let (Some(base_pubkey), Some(owner_pubkey)) = (&self.base_pubkey, &self.owner_pubkey)
+        else {
+            return Err(VanityError::InvalidOptions(
+                "the result doesn't record its base and owner keys".into(),
+            )
+            .into());
+        };
+        let base_pubkey = Pubkey::from_base58_for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::from_base58_for_role(owner_pubkey, KeyRole::Owner)?;
+        let pubkey = vanity_core::Deriver::new(base_pubkey.as_bytes(), owner_pubkey.as_bytes())
+            .derive(&self.seed);
+        let verified = Pubkey::new(pubkey).to_string() == self.address;
+        if !verified {
+            log_warn!("result {} doesn't derive from its seed", self.address);
+        }
+        Ok(verified)
+    }
+}
+
+impl VanityResult {
+    fn to_plain(&self) -> VanityResultJson {
+        VanityResultJson {
+            address: self.address.clone(),
+            seed: self.seed(),
+            attempts: self.attempts,
+            counter: self.counter.map(|counter| counter.to_string()),
+            case_mismatch_positions: self.case_mismatch_positions.clone(),
+            seed_namespace: self.seed_namespace.clone(),
+            seed_scheme: self.seed_scheme,
+            base_pubkey: self.base_pubkey.clone(),
+            owner_pubkey: self.owner_pubkey.clone(),
+        }
+    }
+
+    /// Records the keys `derivation` derives from.
+    fn with_keys(self, derivation: &CreateWithSeedDerivation) -> VanityResult {
+        VanityResult {
+            base_pubkey: Some(Pubkey::new(*derivation.base_pubkey()).to_string()),
+            owner_pubkey: Some(Pubkey::new(*derivation.owner_pubkey()).to_string()),
+            ..self
+        }
+    }
+
+    /// Inverse of `to_plain`, for results that crossed a worker boundary.
+    fn from_plain(plain: VanityResultJson) -> Result<VanityResult, VanityError> {
+        let counter = plain
+            .counter
+            .map(|counter| counter.parse())
+            .transpose()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid result counter: {e}")))?;
+        Ok(VanityResult {
+            case_mismatch_positions: plain.case_mismatch_positions,
+            seed_namespace: plain.seed_namespace,
+            seed_scheme: plain.seed_scheme,
+            base_pubkey: plain.base_pubkey,
+            owner_pubkey: plain.owner_pubkey,
+            ..VanityResult::from_seed_bytes(
+                plain.address,
+                vanity_core::seed_from_string(&plain.seed)?,
+                plain.attempts,
+                counter,
+            )
+        })
+    }
+}
+
+impl<A: AsRef<[u8]>> From<SearchMatch<A>> for VanityResult {
+    fn from(found: SearchMatch<A>) -> VanityResult {
+        VanityResult {
+            case_mismatch_positions: found.case_mismatch_positions,
+            seed_namespace: found.seed_namespace,
+            seed_scheme: found.seed_scheme.map(SeedScheme::version),
+            ..VanityResult::from_seed_bytes(
+                found.address,
+                found.seed.as_ref().to_vec(),
+                found.attempts,
+                Some(found.counter),
+            )
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct VanityResultJson {
+    pub address: String,
+    /// `VanityResult.seed`, hex-encoded after "hex:" if it isn't UTF-8.
+    pub seed: String,
+    pub attempts: u64,
+    /// `VanityResult.counter` in decimal: physical counters span all of
+    /// u64, past what a JS number holds exactly.
+    #[tsify(optional)]
+    pub counter: Option<String>,
+    /// `VanityResult.case_mismatch_positions`, left out when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[tsify(optional)]
+    pub case_mismatch_positions: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub seed_namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub seed_scheme: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub base_pubkey: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    pub owner_pubkey: Option<String>,
+}
+
+/// One entry of `sample_addresses`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct AddressSample {
+    pub address: String,
+    pub seed: String,
+    /// Absolute counter the seed was generated from, or with a counter
+    /// permutation the logical counter mapped to it.
+    pub counter: u64,
+    /// With a counter permutation, the counter the seed was generated from,
+    /// in decimal since it can be past 2^53.
+    #[tsify(optional)]
+    pub physical_counter: Option<String>,
+}
+
+impl AddressSample {
+    fn new(derived: DerivedAddress, permutation: Option<CounterPermutation>) -> AddressSample {
+        let seed = vanity_core::seed_to_string(&derived.seed);
+        let (counter, physical_counter) = match permutation {
+            Some(permutation) => (
+                permutation.invert(derived.counter),
+                Some(derived.counter.to_string()),
+            ),
+            None => (derived.counter, None),
+        };
+        AddressSample {
+            address: derived.address,
+            seed,
+            counter,
+            physical_counter,
+        }
+    }
+}
+
+/// One entry of `derive_batch`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct DerivedCounter {
+    /// In decimal, since counters can be past 2^53.
+    pub counter: String,
+    pub seed: String,
+    pub address: String,
+}
+
+/// Returned by `search_range`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct RangeOutcome {
+    /// Every match in the range, in counter order.
+    pub matches: Vec<AddressSample>,
+    /// Counters scanned: the size of the range unless interrupted, in which
+    /// case they are the first `attempts` counters of the range.
+    pub attempts: u64,
+    pub interrupted: bool,
+}
+
+/// Expected number of attempts to find an address matching the pattern.
+/// Returns `Infinity` when the pattern can never match.
+#[wasm_bindgen]
+pub fn estimate_attempts(
+    prefix: Option<String>,
+    suffix: Option<String>,
+    case_insensitive: bool,
+) -> f64 {
+    1.0 / estimate_probability(prefix, suffix, case_insensitive)
+}
+
+/// Probability that a single attempt matches the pattern.
+#[wasm_bindgen]
+pub fn estimate_probability(
+    prefix: Option<String>,
+    suffix: Option<String>,
+    case_insensitive: bool,
+) -> f64 {
+    vanity_core::match_probability(prefix.as_deref(), suffix.as_deref(), case_insensitive)
+}
+
+/// Expected attempts for each prefix of `pattern` (lengths 1 to n), e.g. to
+/// chart difficulty as the user types. Throws a `VanityError` of kind
+/// "InvalidPatternChar" whose `position` is the first bad character.
+#[wasm_bindgen]
+pub fn difficulty_curve(pattern: &str, case_insensitive: bool) -> Result<Vec<f64>, VanityJsError> {
+    Ok(vanity_core::difficulty_curve(pattern, case_insensitive)?)
+}
+
+/// Histogram of the character at `position` over `samples` addresses
+/// derived with throwaway keys, with the analytic probabilities for
+/// position 0 alongside: the first character is far from uniform. Runs on
+/// the calling thread, at search speed. Fails with `InvalidOptions` past
+/// position 43.
+#[wasm_bindgen]
+pub fn char_distribution(
+    position: u8,
+    samples: u32,
+) -> Result<Ts<vanity_core::CharDistribution>, VanityJsError> {
+    let distribution = vanity_core::char_distribution(position, samples)?;
+    Ok(distribution.into_ts().map_err(internal)?)
+}
+
+/// Lines up two `VanitySearcher.replay_log` logs by counter and reports
+/// how many counters they share and the lowest one they disagree on, if
+/// any: from there on one of the builds derives different addresses. Fails
+/// with `InvalidOptions` for a log that isn't whole 16-byte entries.
+#[wasm_bindgen]
+pub fn compare_replay(
+    log_a: &[u8],
+    log_b: &[u8],
+) -> Result<Ts<vanity_core::ReplayComparison>, VanityJsError> {
+    let comparison = vanity_core::compare_replay(log_a, log_b)?;
+    if let Some(counter) = comparison.first_divergent_counter {
+        log_warn!("replay logs diverge at counter {counter}");
+    }
+    Ok(comparison.into_ts().map_err(internal)?)
+}
+
+/// The result a search with these keys would report for `counter`, with
+/// `attempts` 0: regenerates the seed (under `seed_namespace` and
+/// `seed_scheme`, as `VanityResult` records them) and derives its address,
+/// to check a saved result or recover its seed. Without a `seed_scheme`
+/// the seed is regenerated with version 1, which results saved before
+/// schemes were recorded used; those from web builds that old need 0. Fails with `InvalidOptions` for an unknown
+/// scheme or a bad namespace.
+#[wasm_bindgen]
+pub fn reproduce(
+    base_pubkey: &[u8],
+    owner_pubkey: &[u8],
+    counter: u64,
+    seed_namespace: Option<String>,
+    seed_scheme: Option<u32>,
+) -> Result<VanityResult, VanityJsError> {
+    let scheme = seed_scheme.map_or(Ok(SeedScheme::V1), SeedScheme::from_version)?;
+    let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+    let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+    let namespace = seed_namespace
+        .as_deref()
+        .map(SeedNamespace::new)
+        .transpose()?;
+    let derived_from = namespace.map_or(counter, |namespace| namespace.apply(counter));
+    let seed = scheme.seed_from_counter(derived_from);
+    let pubkey =
+        vanity_core::Deriver::new(base_pubkey.as_bytes(), owner_pubkey.as_bytes()).derive(&seed);
+    let address = Pubkey::new(pubkey);
+    Ok(VanityResult {
+        seed_namespace,
+        seed_scheme: Some(scheme.version()),
+        base_pubkey: Some(base_pubkey.to_string()),
+        owner_pubkey: Some(owner_pubkey.to_string()),
+        ..VanityResult::from_seed_bytes(address.to_string(), seed.to_vec(), 0, Some(counter))
+    })
+}
+
+/// `reproduce` for many counters at once, in order, as `{ counter, seed,
+/// address }` records, e.g. to spot-check counters a worker claims; a
+/// BigUint64Array of a hundred thousand counters takes milliseconds.
+/// Fails with `InvalidOptions` for more than `MAX_DERIVE_COUNTERS`
+/// (1,000,000) counters or a bad `seed_namespace`.
+#[wasm_bindgen(unchecked_return_type = "DerivedCounter[]")]
+pub fn derive_batch(
+    base_pubkey: &[u8],
+    owner_pubkey: &[u8],
+    counters: &[u64],
+    seed_namespace: Option<String>,
+) -> Result<js_sys::Array, VanityJsError> {
+    let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+    let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+    let namespace = seed_namespace
+        .as_deref()
+        .map(SeedNamespace::new)
+        .transpose()?;
+    let derivation = CreateWithSeedDerivation::new(base_pubkey.as_bytes(), owner_pubkey.as_bytes());
+    vanity_core::derive_counters(&derivation, namespace.as_ref(), counters)?
+        .into_iter()
+        .map(|derived| {
+            let record = DerivedCounter {
+                counter: derived.counter.to_string(),
+                seed: vanity_core::seed_to_string(&derived.seed),
+                address: derived.address,
+            };
+            Ok(JsValue::from(record.into_ts().map_err(internal)?))
+        })
+        .collect()
+}
+
+/// Runs a few embedded known-answer vectors through the full search
+/// pipeline and throws a `VanityError` with kind "SelfTestFailed" if this
+/// build derives anything differently. Call it once after the module loads
+/// and don't search if it throws; it takes microseconds.
+#[wasm_bindgen]
+pub fn self_test() -> Result<(), VanityJsError> {
+    vanity_core::self_test().map_err(|error| {
+        log_error!("{error}");
+        error.into()
+    })
+}
+
+/// Built-in patterns for popular targets, with their expected attempts and
+/// a difficulty class at a reference rate of one browser worker. Pass an
+/// `id` to `VanitySearcher.from_preset`.
+#[wasm_bindgen(unchecked_return_type = "PresetInfo[]")]
+pub fn presets() -> Result<js_sys::Array, VanityJsError> {
+    vanity_core::presets()
+        .iter()
+        .map(|preset| Ok(JsValue::from(preset.into_ts().map_err(internal)?)))
+        .collect()
+}
+
+/// Attempts per second of a single searcher on this thread, measured by
+/// searching for an unmatchable pattern for about `duration_ms`.
+#[wasm_bindgen]
+pub fn benchmark_hash_rate(duration_ms: f64) -> f64 {
+    const BATCH_SIZE: u32 = 1_000;
+
+    let match_type = MatchType::new(Some("0".into()), None, false);
+    let mut searcher = Searcher::with_match_type(&[1; 32], &[4; 32], match_type, false, 0);
+    let start_ms = now_ms();
+    let mut elapsed_ms = 0.0;
+    while elapsed_ms < duration_ms || searcher.attempts() == 0 {
+        searcher.search_batch(BATCH_SIZE);
+        elapsed_ms = now_ms() - start_ms;
+    }
+    searcher.attempts() as f64 / elapsed_ms.max(f64::EPSILON) * 1000.0
+}
+
+/// The pattern to search for, as a plain JS object.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct SearchOptions {
+    #[tsify(optional)]
+    pub prefix: Option<String>,
+    #[tsify(optional)]
+    pub suffix: Option<String>,
+    #[tsify(optional)]
+    pub case_insensitive: bool,
+}
+
+/// Options of `VanitySearcher.from_options`, as a plain JS object.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct SearcherOptions {
+    #[serde(flatten)]
+    pub pattern: SearchOptions,
+    #[tsify(optional)]
+    pub count_offset: u64,
+    #[tsify(optional)]
+    pub allow_empty_pattern: bool,
+    /// Build the searcher even if the pattern is `Infeasible`.
+    #[tsify(optional)]
+    pub force: bool,
+    /// Expected attempts past which the pattern is `Infeasible`; defaults to
+    /// `DEFAULT_MAX_EXPECTED_ATTEMPTS`.
+    #[tsify(optional)]
+    pub max_expected_attempts: Option<f64>,
+}
+
+const PLAN_BENCHMARK_MS: f64 = 200.0;
+
+/// Benchmarks this device and recommends a batch size that takes about
+/// `target_batch_ms` per call, a worker count, and the expected wall time
+/// for `pattern_options` (`{ prefix, suffix, case_insensitive }`).
+#[wasm_bindgen]
+pub fn plan_search(
+    pattern_options: Ts<SearchOptions>,
+    target_batch_ms: f64,
+) -> Result<Ts<SearchPlan>, VanityJsError> {
+    let options = pattern_options
+        .to_rust()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid search options: {e}")))?;
+    let plan = vanity_core::plan_search(
+        benchmark_hash_rate(PLAN_BENCHMARK_MS),
+        device::hardware_concurrency(),
+        vanity_core::match_probability(
+            options.prefix.as_deref(),
+            options.suffix.as_deref(),
+            options.case_insensitive,
+        ),
+        target_batch_ms,
+    );
+    log_debug!("search plan: {plan:?}");
+    Ok(plan.into_ts().map_err(internal)?)
+}
+
+/// Attempts within which a search for `pattern_options` succeeds with 50%,
+/// 90% and 99% probability, and how long that takes at `rate` attempts per
+/// second if given, e.g. "likely done within 2 minutes, almost certainly
+/// within 7". More honest than `estimate_attempts`: half of all searches
+/// finish well before the mean, and an unlucky few take several times it.
+#[wasm_bindgen]
+pub fn attempt_quantiles(
+    pattern_options: Ts<SearchOptions>,
+    rate: Option<f64>,
+) -> Result<Ts<AttemptQuantiles>, VanityJsError> {
+    let options = pattern_options
+        .to_rust()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid search options: {e}")))?;
+    let probability = vanity_core::match_probability(
+        options.prefix.as_deref(),
+        options.suffix.as_deref(),
+        options.case_insensitive,
+    );
+    Ok(vanity_core::attempt_quantiles(probability, rate)
+        .into_ts()
+        .map_err(internal)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use vanity_core::ErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn test_search_batch() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("AAA".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+
+        loop {
+            if let Some(vanity_result) = vanity_searcher.search_batch(1000) {
+                println!("{}", vanity_result.address);
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_start_is_idempotent() {
+        start();
+        start();
+        init_panic_hook();
+        assert!(is_initialized());
+    }
+
+    #[test]
+    fn test_stop_and_restart() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("A".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        vanity_searcher.stop();
+        assert!(vanity_searcher.is_stopped());
+        assert!(vanity_searcher.search_batch(1000).is_none());
+
+        vanity_searcher.restart();
+        assert!(!vanity_searcher.is_stopped());
+        let vanity_result = loop {
+            if let Some(vanity_result) = vanity_searcher.search_batch(1000) {
+                break vanity_result;
+            }
+        };
+        assert!(vanity_result.address().starts_with('A'));
+    }
+
+    #[test]
+    fn test_deadline_stops_the_search() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(vanity_searcher.stop_at(f64::NAN).is_err());
+        assert_eq!(vanity_searcher.deadline(), None);
+
+        vanity_searcher.stop_at(epoch_ms() + 3_600_000.0).unwrap();
+        let outcome = vanity_searcher.batch_outcome(1_000);
+        assert_eq!(outcome.status, BatchStatus::NotFound);
+        assert!(!outcome.deadline_reached);
+
+        // A deadline already past stops the next batch before any work.
+        vanity_searcher.stop_at(epoch_ms() - 1.0).unwrap();
+        let outcome = vanity_searcher.batch_outcome(1_000);
+        assert_eq!(
+            (outcome.status, outcome.attempts),
+            (BatchStatus::Stopped, 0)
+        );
+        assert!(outcome.deadline_reached && vanity_searcher.deadline_reached());
+        // Still past after a restart, so it stops again.
+        vanity_searcher.restart();
+        assert!(vanity_searcher.batch_outcome(1_000).deadline_reached);
+
+        // Cleared, a restart gets going again, and a plain stop isn't the
+        // deadline's doing.
+        vanity_searcher.clear_stop_at();
+        vanity_searcher.restart();
+        assert!(!vanity_searcher.deadline_reached());
+        assert_eq!(vanity_searcher.batch_outcome(1_000).attempts, 1_000);
+        vanity_searcher.stop();
+        let outcome = vanity_searcher.batch_outcome(1_000);
+        assert_eq!(outcome.status, BatchStatus::Stopped);
+        assert!(!outcome.deadline_reached);
+    }
+
+    #[test]
+    fn test_attempt_getters_with_offset() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            5_000,
+            None,
+            None,
+        )
+        .unwrap();
+        vanity_searcher.search_batch(100);
+
+        assert_eq!(vanity_searcher.local_attempts(), 100);
+        assert_eq!(vanity_searcher.attempts(), 100);
+        assert_eq!(vanity_searcher.absolute_position(), Some(5_100));
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("A".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        vanity_searcher.search_batch(100);
+        let attempts = vanity_searcher.local_attempts();
+        let mut fork = vanity_searcher.fork(1 << 32);
+        assert_eq!(fork.local_attempts(), 0);
+        assert_eq!(fork.absolute_position(), Some(1 << 32));
+        assert_eq!(vanity_searcher.local_attempts(), attempts);
+
+        let next_match = |searcher: &mut VanitySearcher| loop {
+            if let Some(vanity_result) = searcher.search_batch(1000) {
+                break vanity_result;
+            }
+        };
+        let (a, b) = (next_match(&mut vanity_searcher), next_match(&mut fork));
+        assert!(a.address().starts_with('A') && b.address().starts_with('A'));
+        assert_ne!(a.seed(), b.seed());
+    }
+
+    #[test]
+    fn test_constructor_error_codes() {
+        let new = |base: &[u8], owner: &[u8], prefix: &str, allow_empty: bool| {
+            VanitySearcher::new(
+                base,
+                owner,
+                Some(prefix.into()),
+                None,
+                false,
+                0,
+                Some(allow_empty),
+                None,
+            )
+            .err()
+            .map(|error| error.0.kind())
+        };
+        assert_eq!(
+            new(&[1; 31], &[4; 32], "A", false),
+            Some(ErrorKind::BadBaseLength)
+        );
+        assert_eq!(
+            new(&[1; 32], &[4; 0], "A", false),
+            Some(ErrorKind::BadOwnerLength)
+        );
+        assert_eq!(
+            new(&[1; 32], &[4; 32], "A0", false),
+            Some(ErrorKind::InvalidPatternChar)
+        );
+        assert_eq!(
+            new(&[1; 32], &[4; 32], "A0", true),
+            Some(ErrorKind::InvalidPatternChar)
+        );
+        assert_eq!(
+            new(&[1; 32], &[4; 32], &"z".repeat(45), false),
+            Some(ErrorKind::PatternTooLong)
+        );
+        assert_eq!(
+            new(&[1; 32], &[4; 32], " ", false),
+            Some(ErrorKind::EmptyPattern)
+        );
+        assert_eq!(new(&[1; 32], &[4; 32], "A", false), None);
+    }
+
+    #[test]
+    fn test_max_attempts() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("AAA".into()),
+            None,
+            false,
+            1 << 20,
+            None,
+            None,
+        )
+        .unwrap();
+        vanity_searcher.set_max_attempts(Some(1_234));
+        assert!(vanity_searcher.search_batch(u32::MAX).is_none());
+        assert!(vanity_searcher.is_exhausted());
+        assert!(!vanity_searcher.is_stopped());
+        assert_eq!(vanity_searcher.local_attempts(), 1_234);
+        assert_eq!(vanity_searcher.absolute_position(), Some((1 << 20) + 1_234));
+    }
+
+    #[test]
+    fn test_batch_outcome_statuses() {
+        let new = || {
+            VanitySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                Some("AAA".into()),
+                None,
+                false,
+                0,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        let mut vanity_searcher = new();
+        let outcome = vanity_searcher.batch_outcome(1_000);
+        assert_eq!(outcome.status, BatchStatus::NotFound);
+        assert_eq!(outcome.result, None);
+        assert_eq!(outcome.attempts, 1_000);
+        assert!(outcome.elapsed_ms >= 0.0);
+        let span = |outcome: &BatchOutcome| {
+            (
+                outcome
+                    .first_counter
+                    .as_deref()
+                    .map(|c| c.parse::<u64>().unwrap()),
+                outcome
+                    .last_counter
+                    .as_deref()
+                    .map(|c| c.parse::<u64>().unwrap()),
+            )
+        };
+        assert_eq!(span(&outcome), (Some(0), Some(999)));
+        assert_eq!(outcome.evaluated, 1_000);
+
+        let outcome = vanity_searcher.batch_outcome(100_000);
+        assert_eq!(outcome.status, BatchStatus::Found);
+        // The match is the last counter covered; the next call starts after.
+        assert_eq!(span(&outcome), (Some(1_000), Some(51_051)));
+        let result = outcome.result.unwrap();
+        assert_eq!(result.attempts, 51_052);
+        assert_eq!(result.counter.as_deref(), Some("51051"));
+        assert_eq!(outcome.attempts, 50_052);
+        let outcome = vanity_searcher.batch_outcome(10);
+        assert_eq!(span(&outcome), (Some(51_052), Some(51_061)));
+
+        let mut vanity_searcher = new();
+        vanity_searcher.set_max_attempts(Some(2_500));
+        let outcome = vanity_searcher.batch_outcome(1_000_000);
+        assert_eq!(outcome.status, BatchStatus::Exhausted);
+        assert_eq!(outcome.attempts, 2_500);
+        assert_eq!(span(&outcome), (Some(0), Some(2_499)));
+        let outcome = vanity_searcher.batch_outcome(10);
+        assert_eq!((outcome.attempts, span(&outcome)), (0, (None, None)));
+
+        // Giving up at 1% coverage, before the cap.
+        let mut vanity_searcher = new();
+        vanity_searcher.set_max_attempts(Some(2_500));
+        vanity_searcher.set_stop_at_probability(Some(0.01)).unwrap();
+        let outcome = vanity_searcher.batch_outcome(1_000_000);
+        assert_eq!(outcome.status, BatchStatus::CoverageReached);
+        assert_eq!(outcome.attempts, 573);
+        assert!(outcome.coverage >= 0.01 && outcome.coverage < 0.01001);
+        assert!(vanity_searcher.is_exhausted());
+
+        let mut vanity_searcher = new();
+        vanity_searcher.stop();
+        let outcome = vanity_searcher.batch_outcome(1_000);
+        assert_eq!(outcome.status, BatchStatus::Stopped);
+        assert_eq!(outcome.attempts, 0);
+        assert_eq!(span(&outcome), (None, None));
+
+        // Skipped counters are covered but not evaluated: the covered block
+        // goes in one step, then 10 counters are searched.
+        let mut vanity_searcher = new();
+        let mut seen = vanity_core::SeenFilter::new(10, 1e-9).unwrap();
+        seen.insert_range(0, vanity_core::SEEN_BLOCK_SIZE);
+        vanity_searcher.searcher.set_seen_filter(Some(seen));
+        let outcome = vanity_searcher.batch_outcome(vanity_core::SEEN_BLOCK_SIZE as u32 + 10);
+        assert_eq!(
+            span(&outcome),
+            (Some(0), Some(vanity_core::SEEN_BLOCK_SIZE + 9))
+        );
+        assert_eq!(outcome.evaluated, 10);
+
+        // The last 5 counters, then nothing: no wrapping back to 0.
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            u64::MAX - 4,
+            None,
+            None,
+        )
+        .unwrap();
+        let outcome = vanity_searcher.batch_outcome(1_000);
+        assert_eq!(outcome.status, BatchStatus::Exhausted);
+        assert_eq!(outcome.attempts, 5);
+        assert_eq!(span(&outcome), (Some(u64::MAX - 4), Some(u64::MAX)));
+        assert_eq!(vanity_searcher.absolute_position(), None);
+        let outcome = vanity_searcher.batch_outcome(1_000);
+        assert_eq!(
+            (outcome.status, outcome.attempts),
+            (BatchStatus::Exhausted, 0)
+        );
+    }
+
+    #[test]
+    fn test_resume_token() {
+        let new = |owner: &[u8]| {
+            VanitySearcher::new(
+                &[1; 32],
+                owner,
+                Some("AAA".into()),
+                None,
+                false,
+                0,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        let mut vanity_searcher = new(&[4; 32]);
+        vanity_searcher.search_batch(20_000);
+        let token = vanity_searcher.resume_token();
+
+        let mut resumed = new(&[4; 32]);
+        resumed.resume_from(&token, None).unwrap();
+        assert_eq!(resumed.local_attempts(), 20_000);
+        assert_eq!(resumed.search_batch(100_000).unwrap().attempts(), 51_052);
+
+        let error = new(&[5; 32]).resume_from(&token, None).unwrap_err();
+        assert_eq!(error.0.kind(), ErrorKind::ResumeTokenMismatch);
+
+        // The same token from an old web build, with its seeds.
+        let mut legacy = new(&[4; 32]);
+        legacy.resume_from(&token, Some(0)).unwrap();
+        assert_eq!(legacy.seed_scheme(), 0);
+        let result = legacy.search_batch(100_000).unwrap();
+        let counter = result.counter.unwrap();
+        assert_eq!(
+            result.seed,
+            vanity_core::SeedScheme::LegacyWasm32.seed_from_counter(counter)
+        );
+        assert_eq!(result.seed_scheme, Some(0));
+        let error = new(&[4; 32]).resume_from(&token, Some(7)).unwrap_err();
+        assert_eq!(error.0.kind(), ErrorKind::InvalidOptions);
+    }
+
+    #[test]
+    fn test_namespaced_results_reproduce_with_their_namespace() {
+        let new = || {
+            VanitySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                Some("A".into()),
+                None,
+                false,
+                0,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        let mut plain = new();
+        let mut tagged = new();
+        assert_eq!(
+            tagged.namespace_seeds(Some("tab-1".into())).unwrap(),
+            "tab-1"
+        );
+        assert_eq!(tagged.seed_namespace().as_deref(), Some("tab-1"));
+        let found = tagged.search_batch(10_000).unwrap();
+        assert_eq!(found.seed_namespace().as_deref(), Some("tab-1"));
+        assert_eq!(found.to_plain().seed_namespace.as_deref(), Some("tab-1"));
+        assert_ne!(plain.search_batch(10_000).unwrap().seed(), found.seed());
+
+        let counter = found.counter().unwrap();
+        assert_eq!(found.seed_scheme(), Some(seed_scheme_version()));
+        assert_eq!(found.to_plain().seed_scheme, found.seed_scheme());
+        let again = reproduce(
+            &[1; 32],
+            &[4; 32],
+            counter,
+            found.seed_namespace(),
+            found.seed_scheme(),
+        )
+        .unwrap();
+        assert_eq!(
+            (again.address(), again.seed()),
+            (found.address(), found.seed())
+        );
+        assert_eq!(again.seed_namespace(), found.seed_namespace());
+        assert_eq!(again.seed_scheme(), found.seed_scheme());
+        // Results saved before schemes were recorded are V1.
+        let legacy = reproduce(&[1; 32], &[4; 32], counter, found.seed_namespace(), None).unwrap();
+        assert_eq!(
+            (legacy.seed(), legacy.seed_scheme()),
+            (found.seed(), Some(1))
+        );
+        let error = reproduce(&[1; 32], &[4; 32], counter, None, Some(99)).unwrap_err();
+        assert_eq!(error.0.kind(), ErrorKind::InvalidOptions);
+        assert_eq!(tagged.seed_scheme(), seed_scheme_version());
+        tagged.set_seed_scheme(1).unwrap();
+        assert_eq!(
+            tagged.set_seed_scheme(99).unwrap_err().0.kind(),
+            ErrorKind::InvalidOptions
+        );
+        let unmixed = reproduce(&[1; 32], &[4; 32], counter, None, None).unwrap();
+        assert_ne!(unmixed.seed(), found.seed());
+        assert_ne!(unmixed.address(), found.address());
+
+        let mut resumed = new();
+        resumed.resume_from(&tagged.resume_token(), None).unwrap();
+        assert_eq!(resumed.seed_namespace().as_deref(), Some("tab-1"));
+        tagged.clear_seed_namespace();
+        assert_eq!(tagged.seed_namespace(), None);
+        let error = tagged
+            .namespace_seeds(Some("no spaces".into()))
+            .unwrap_err();
+        assert_eq!(error.0.kind(), ErrorKind::InvalidOptions);
+        assert_eq!(random_namespace().unwrap().len(), 11);
+    }
+
+    #[test]
+    fn test_permuted_results_carry_the_physical_counter() {
+        let new = || {
+            VanitySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                Some("A".into()),
+                None,
+                false,
+                0,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        let mut vanity_searcher = new();
+        assert_eq!(vanity_searcher.counter_permutation_key(), None);
+        assert_eq!(vanity_searcher.permute_counters(Some(7)).unwrap(), 7);
+        let found = vanity_searcher.search_batch(10_000).unwrap();
+        let physical = CounterPermutation::new(7).apply(found.attempts() - 1);
+        assert_eq!(found.counter(), Some(physical));
+        assert_eq!(found.to_plain().counter, Some(physical.to_string()));
+
+        let mut resumed = new();
+        resumed
+            .resume_from(&vanity_searcher.resume_token(), None)
+            .unwrap();
+        assert_eq!(resumed.counter_permutation_key(), Some(7));
+        resumed.clear_counter_permutation();
+        assert_eq!(resumed.counter_permutation_key(), None);
+    }
+
+    #[test]
+    fn test_results_report_case_mismatches() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("Ab".into()),
+            None,
+            true,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut cases = std::collections::HashSet::new();
+        while cases.len() < 4 {
+            let found = vanity_searcher.search_batch(100_000).unwrap();
+            let expected: Vec<u32> = [(0, 'A'), (1, 'b')]
+                .into_iter()
+                .filter(|&(i, typed)| found.address().chars().nth(i as usize) != Some(typed))
+                .map(|(i, _)| i)
+                .collect();
+            assert_eq!(found.case_mismatch_positions(), expected);
+            assert_eq!(found.exact_case_match(), expected.is_empty());
+            assert_eq!(found.to_plain().case_mismatch_positions, expected);
+            cases.insert(expected);
+        }
+
+        let built = |case_insensitive| {
+            let builder = VanitySearcherBuilder::new()
+                .base(vec![1; 32])
+                .owner(vec![4; 32])
+                .suffix("X".into())
+                .case_insensitive(case_insensitive);
+            builder.build().unwrap().search_batch(100_000).unwrap()
+        };
+        let found = built(true);
+        let last = found.address().len() as u32 - 1;
+        assert_eq!(found.exact_case_match(), found.address().ends_with('X'));
+        if !found.exact_case_match() {
+            assert_eq!(found.case_mismatch_positions(), [last]);
+        }
+        assert!(built(false).exact_case_match());
+    }
+
+    #[test]
+    fn test_allow_empty_pattern() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            None,
+            Some(" ".into()),
+            false,
+            0,
+            Some(true),
+            None,
+        )
+        .unwrap();
+        assert!(vanity_searcher.search_batch(10).is_some());
+        assert_eq!(vanity_searcher.attempts(), 1);
+    }
+
+    #[test]
+    fn test_estimate_attempts() {
+        assert_eq!(estimate_attempts(None, Some("2".into()), false), 58.0);
+        assert!(estimate_attempts(Some("0".into()), None, false).is_infinite());
+    }
+
+    #[test]
+    fn test_benchmark_hash_rate() {
+        assert!(benchmark_hash_rate(20.0) > 0.0);
+        assert!(benchmark_hash_rate(0.0) > 0.0);
+    }
+
+    #[test]
+    fn test_search_batch_records_stats() {
+        let mut vanity_searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        vanity_searcher.search_batch(2_000);
+
+        let snapshot = vanity_searcher
+            .stats
+            .snapshot(vanity_searcher.attempts(), 1.0);
+        assert_eq!(snapshot.attempts, vanity_searcher.attempts());
+        assert!(snapshot.elapsed_ms > 0.0);
+        assert!(snapshot.instant_rate.unwrap() > 0.0);
+        assert!(vanity_searcher.rate().unwrap() > 0.0);
+        assert_eq!(
+            vanity_searcher.rate_window(),
+            vanity_core::DEFAULT_RATE_WINDOW_MS
+        );
+    }
+
+    #[test]
+    fn test_results_match_pre_split_output() {
+        let cases = [
+            (
+                Some("AAA"),
+                None,
+                false,
+                0,
+                "AAArB3C2J4JtzZDGh9kQBDVU1c3fdDoH1UF1qiyGLzoj",
+                "SYAAAAAAW4UTiYNF",
+                51052,
+            ),
+            (
+                None,
+                Some("xy"),
+                true,
+                1_000_000,
+                "B4sJFEfvKE8Brb9xSLB4ABZCSaLApWQxDtBBjpzjGJXy",
+                "TXRAAAAAuri4JL2J",
+                2369,
+            ),
+        ];
+        for (prefix, suffix, case_insensitive, count_offset, address, seed, attempts) in cases {
+            let mut vanity_searcher = VanitySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                prefix.map(Into::into),
+                suffix.map(Into::into),
+                case_insensitive,
+                count_offset,
+                None,
+                None,
+            )
+            .unwrap();
+            let vanity_result = loop {
+                if let Some(vanity_result) = vanity_searcher.search_batch(1000) {
+                    break vanity_result;
+                }
+            };
+            assert_eq!(vanity_result.address(), address);
+            assert_eq!(vanity_result.seed(), seed);
+            assert_eq!(vanity_result.attempts(), attempts);
+        }
+    }
+
+    #[test]
+    fn test_infeasible_pattern_needs_force() {
+        let new = |suffix: &str, force| {
+            VanitySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                None,
+                Some(suffix.into()),
+                false,
+                0,
+                None,
+                force,
+            )
+        };
+        let error = new("zzzzzzzzzz", None).err().unwrap();
+        assert_eq!(error.0.kind(), ErrorKind::Infeasible);
+        assert!(error.0.to_string().contains("years at 1M attempts/s"));
+        assert!(new("zzzzzzzzzz", Some(true)).is_ok());
+        // Eight characters, about four years at 1M/s, squeak under the limit.
+        assert!(new("zzzzzzzz", None).is_ok());
+    }
+
+    #[test]
+    fn test_binary_seeds_survive_results() {
+        let seed = vec![0xff, 0x00, b'A', 0xc3, 0x28];
+        let derive = |seed: &[u8]| {
+            Pubkey::new(vanity_core::Deriver::new(&[1; 32], &[4; 32]).derive(seed)).to_string()
+        };
+        let result = VanityResult::from_seed_bytes(derive(&seed), seed.clone(), 1, Some(7));
+        assert_eq!(result.seed(), "hex:ff0041c328");
+        assert!(!result.seed().contains('\u{fffd}'));
+
+        let plain = VanityResult::from_plain(result.to_plain()).unwrap();
+        let again = VanityResult::new(plain.address(), plain.seed(), 1, Some(7)).unwrap();
+        for result in [&plain, &again] {
+            assert_eq!(result.seed_bytes(), seed);
+            assert_eq!(derive(&result.seed_bytes()), result.address());
+        }
+        // A lossy round trip would have derived something else.
+        let lossy = String::from_utf8_lossy(&seed).into_owned();
+        assert_ne!(derive(lossy.as_bytes()), result.address());
+    }
+
+    #[test]
+    fn test_results_verify_against_their_keys() {
+        let seed = vec![0xff, 0x00, b'A', 0xc3, 0x28];
+        let address = Pubkey::new(vanity_core::Deriver::new(&[1; 32], &[4; 32]).derive(&seed));
+        let result = VanityResult {
+            base_pubkey: Some(Pubkey::new([1; 32]).to_string()),
+            owner_pubkey: Some(Pubkey::new([4; 32]).to_string()),
+            ..VanityResult::from_seed_bytes(address.to_string(), seed, 3, Some(u64::MAX))
+        };
+        let back = VanityResult::from_plain(result.to_plain()).unwrap();
+        assert_eq!(back, result);
+        assert!(back.verify().unwrap());
+
+        let swapped = VanityResult {
+            owner_pubkey: result.base_pubkey.clone(),
+            ..result.clone()
+        };
+        assert!(!swapped.verify().unwrap());
+        let keyless = VanityResult {
+            base_pubkey: None,
+            ..result
+        };
+        assert_eq!(
+            keyless.verify().err().unwrap().0.kind(),
+            ErrorKind::InvalidOptions
+        );
+    }
 }
@@ -0,0 +1,215 @@
+//! Levelled logging routed to a host-provided callback.
+//!
+//! Without a callback, messages go to `console.log` when the environment has
+//! one and are dropped otherwise.
+
+use std::cell::RefCell;
+use std::str::FromStr;
+
+use vanity_core::VanityError;
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<LogLevel, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            _ => Err(format!("unknown log level: {s}")),
+        }
+    }
+}
+
+type Sink = Box<dyn Fn(LogLevel, &str)>;
+
+struct Logger {
+    level: LogLevel,
+    sink: Option<Sink>,
+}
+
+thread_local! {
+    static LOGGER: RefCell<Logger> = const {
+        RefCell::new(Logger {
+            level: LogLevel::Warn,
+            sink: None,
+        })
+    };
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+    LOGGER.with(|logger| level <= logger.borrow().level)
+}
+
+pub fn log(level: LogLevel, message: &str) {
+    LOGGER.with(|logger| {
+        let logger = logger.borrow();
+        if level > logger.level {
+            return;
+        }
+        match &logger.sink {
+            Some(sink) => sink(level, message),
+            None => console_fallback(level, message),
+        }
+    });
+}
+
+pub fn set_level(level: LogLevel) {
+    LOGGER.with(|logger| logger.borrow_mut().level = level);
+}
+
+pub fn set_sink(sink: Option<Sink>) {
+    LOGGER.with(|logger| logger.borrow_mut().sink = sink);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn console_fallback(level: LogLevel, message: &str) {
+    use wasm_bindgen::JsCast;
+
+    // Looked up dynamically so hosts without a console don't throw.
+    let Ok(console) = js_sys::Reflect::get(&js_sys::global(), &"console".into()) else {
+        return;
+    };
+    if console.is_undefined() || console.is_null() {
+        return;
+    }
+    if let Ok(log) = js_sys::Reflect::get(&console, &"log".into()) {
+        if let Some(log) = log.dyn_ref::<js_sys::Function>() {
+            let line = format!("[rust-vanity {}] {message}", level.as_str());
+            let _ = log.call1(&console, &line.into());
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn console_fallback(_level: LogLevel, _message: &str) {}
+
+macro_rules! log_at {
+    ($level:expr, $($t:tt)*) => {
+        if $crate::logger::enabled($level) {
+            $crate::logger::log($level, &format!($($t)*));
+        }
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! log_error {
+    ($($t:tt)*) => (log_at!($crate::logger::LogLevel::Error, $($t)*))
+}
+
+#[allow(unused_macros)]
+macro_rules! log_warn {
+    ($($t:tt)*) => (log_at!($crate::logger::LogLevel::Warn, $($t)*))
+}
+
+#[allow(unused_macros)]
+macro_rules! log_info {
+    ($($t:tt)*) => (log_at!($crate::logger::LogLevel::Info, $($t)*))
+}
+
+#[allow(unused_macros)]
+macro_rules! log_debug {
+    ($($t:tt)*) => (log_at!($crate::logger::LogLevel::Debug, $($t)*))
+}
+
+/// Routes log messages to `callback(level, message)`; pass `undefined` to
+/// go back to the console fallback.
+#[wasm_bindgen]
+pub fn set_log_callback(callback: Option<js_sys::Function>) {
+    set_sink(callback.map(|callback| -> Sink {
+        Box::new(move |level, message| {
+            let _ = callback.call2(&JsValue::NULL, &level.as_str().into(), &message.into());
+        })
+    }));
+}
+
+/// Sets the most verbose level that is emitted: "error", "warn" (the
+/// default), "info" or "debug".
+#[wasm_bindgen]
+pub fn set_log_level(level: &str) -> Result<(), VanityJsError> {
+    set_level(level.parse().map_err(VanityError::InvalidOptions)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn capture() -> Rc<RefCell<Vec<(LogLevel, String)>>> {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&captured);
+        set_sink(Some(Box::new(move |level, message| {
+            sink.borrow_mut().push((level, message.to_string()))
+        })));
+        captured
+    }
+
+    #[test]
+    fn test_default_level_is_warn() {
+        let captured = capture();
+        log_error!("e");
+        log_warn!("w");
+        log_info!("i");
+        log_debug!("d");
+        assert_eq!(
+            *captured.borrow(),
+            vec![(LogLevel::Error, "e".into()), (LogLevel::Warn, "w".into())]
+        );
+    }
+
+    #[test]
+    fn test_level_filtering() {
+        let captured = capture();
+        set_level(LogLevel::Error);
+        log_warn!("dropped");
+        log_error!("kept {}", 1);
+        set_level(LogLevel::Debug);
+        log_debug!("verbose");
+        assert_eq!(
+            *captured.borrow(),
+            vec![
+                (LogLevel::Error, "kept 1".into()),
+                (LogLevel::Debug, "verbose".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!("WARN".parse(), Ok(LogLevel::Warn));
+        assert_eq!("debug".parse(), Ok(LogLevel::Debug));
+        assert!("loud".parse::<LogLevel>().is_err());
+        let error = set_log_level("loud").unwrap_err();
+        assert_eq!(error.0.kind(), vanity_core::ErrorKind::InvalidOptions);
+    }
+
+    #[test]
+    fn test_no_sink_is_silent() {
+        set_sink(None);
+        log_error!("goes nowhere natively");
+    }
+}
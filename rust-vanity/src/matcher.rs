@@ -0,0 +1,236 @@
+//! Pattern checks outside the search loop, e.g. on addresses a user
+//! already has.
+
+use serde::Deserialize;
+use tsify::{Ts, Tsify};
+use vanity_core::{AddressMatcher, MatchExplanation, VanityError};
+use wasm_bindgen::prelude::*;
+
+use crate::builder::VanitySearcherBuilder;
+use crate::error::VanityJsError;
+use crate::{internal, VanitySearcher};
+
+/// Options for `Matcher`, as a plain JS object; they mean what the
+/// `VanitySearcherBuilder` setters of the same names do.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct MatcherOptions {
+    #[tsify(optional)]
+    pub prefix: Option<String>,
+    #[tsify(optional)]
+    pub suffix: Option<String>,
+    /// Can't be combined with `prefix` or `suffix`.
+    #[tsify(optional)]
+    pub contains: Option<String>,
+    #[tsify(optional)]
+    pub case_insensitive: bool,
+    #[tsify(optional)]
+    pub auto_fix: bool,
+    /// Length of the clean prefix; see `VanitySearcher.set_clean_prefix`.
+    #[tsify(optional)]
+    pub clean_prefix: Option<u8>,
+    #[tsify(optional)]
+    pub clean_prefix_chars: Option<String>,
+    #[tsify(optional)]
+    pub clean_prefix_pairs: Option<Vec<String>>,
+}
+
+/// A pattern as a `VanitySearcher` tests it, without the search: the same
+/// compiled matcher, so the two can't disagree about an address.
+#[wasm_bindgen]
+pub struct Matcher {
+    matcher: AddressMatcher,
+}
+
+#[wasm_bindgen]
+impl Matcher {
+    /// Fails as `VanitySearcherBuilder.build` does on the same pattern
+    /// options, e.g. with `InvalidPatternChar`, `PatternTooLong` or
+    /// `EmptyPattern`, or `Multiple` for several problems.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Ts<MatcherOptions>) -> Result<Matcher, VanityJsError> {
+        let options = options
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid matcher options: {e}")))?;
+        Ok(Matcher::from_options(options)?)
+    }
+
+    /// Whether a `VanitySearcher` with these options would report `address`.
+    #[wasm_bindgen]
+    pub fn matches(&self, address: &str) -> bool {
+        self.matcher.matches(address)
+    }
+
+    /// Each part of the pattern, whether `address` passed it, and how much
+    /// of it is in place.
+    #[wasm_bindgen]
+    pub fn explain(&self, address: &str) -> Result<Ts<MatchExplanation>, VanityJsError> {
+        Ok(self.matcher.explain(address).into_ts().map_err(internal)?)
+    }
+
+    /// Mean attempts per match for a search with this pattern.
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.matcher.probability()
+    }
+}
+
+impl Matcher {
+    fn from_options(options: MatcherOptions) -> Result<Matcher, VanityError> {
+        let mut builder = VanitySearcherBuilder::new()
+            .case_insensitive(options.case_insensitive)
+            .auto_fix(options.auto_fix);
+        if let Some(prefix) = options.prefix {
+            builder = builder.prefix(prefix);
+        }
+        if let Some(suffix) = options.suffix {
+            builder = builder.suffix(suffix);
+        }
+        if let Some(contains) = options.contains {
+            builder = builder.contains(contains);
+        }
+        if let Some(len) = options.clean_prefix {
+            builder =
+                builder.clean_prefix(len, options.clean_prefix_chars, options.clean_prefix_pairs);
+        }
+        let mut problems = Vec::new();
+        let matcher = builder.compile_matcher(&mut problems);
+        match (matcher, VanityError::from_problems(problems)) {
+            (Some(matcher), None) => Ok(Matcher { matcher }),
+            (_, Some(error)) => Err(error),
+            (None, None) => Err(VanityError::Internal("no matcher".into())),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl VanitySearcher {
+    /// This searcher's pattern, case flag and clean prefix, as a `Matcher`.
+    #[wasm_bindgen]
+    pub fn matcher(&self) -> Matcher {
+        Matcher {
+            matcher: self.searcher.matcher().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vanity_core::{ErrorKind, MatchPart};
+
+    use super::*;
+
+    fn matcher(options: MatcherOptions) -> Result<Matcher, VanityError> {
+        Matcher::from_options(options)
+    }
+
+    #[test]
+    fn test_matcher_agrees_with_the_search() {
+        let standalone = matcher(MatcherOptions {
+            prefix: Some("aB".into()),
+            case_insensitive: true,
+            clean_prefix: Some(4),
+            ..MatcherOptions::default()
+        })
+        .unwrap();
+        let mut searcher = VanitySearcherBuilder::new()
+            .base(vec![1; 32])
+            .owner(vec![4; 32])
+            .prefix("aB".into())
+            .case_insensitive(true)
+            .clean_prefix(4, None, None)
+            .build()
+            .unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(10_000) {
+                break found;
+            }
+        };
+        assert!(standalone.matches(&found.address()));
+        assert!(standalone.matcher.explain(&found.address()).matches);
+        assert_eq!(
+            standalone.matcher.case_mismatch_positions(&found.address()),
+            found.case_mismatch_positions()
+        );
+        assert_eq!(standalone.matcher, searcher.matcher().matcher);
+
+        // Every attempt up to the match agrees too.
+        let counter = found.counter().unwrap();
+        let sample = searcher.searcher.sample_addresses(0, counter as u32 + 1);
+        assert_eq!(sample.len() as u64, counter + 1);
+        for derived in &sample {
+            let expected = derived.address == found.address();
+            assert_eq!(standalone.matches(&derived.address), expected);
+        }
+        assert_eq!(
+            standalone.expected_attempts(),
+            1.0 / searcher.searcher.match_probability()
+        );
+    }
+
+    #[test]
+    fn test_explain_reports_each_part() {
+        let both = matcher(MatcherOptions {
+            prefix: Some("So1".into()),
+            suffix: Some("pump".into()),
+            ..MatcherOptions::default()
+        })
+        .unwrap();
+        let explanation = both.matcher.explain("SoXyzpump");
+        assert!(!explanation.matches);
+        let parts: Vec<_> = explanation
+            .checks
+            .iter()
+            .map(|check| (check.part, check.passed, check.matched_chars))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                (MatchPart::Prefix, false, Some(2)),
+                (MatchPart::Suffix, true, Some(4)),
+            ]
+        );
+        assert!(!both.matches("SoXyzpump"));
+        assert!(both.matches("So1xyzpump"));
+    }
+
+    #[test]
+    fn test_invalid_options_are_typed_errors() {
+        let kind = |options: MatcherOptions| matcher(options).err().map(|e| e.kind());
+        assert_eq!(
+            kind(MatcherOptions::default()),
+            Some(ErrorKind::EmptyPattern)
+        );
+        assert_eq!(
+            kind(MatcherOptions {
+                prefix: Some("S0L".into()),
+                ..MatcherOptions::default()
+            }),
+            Some(ErrorKind::InvalidPatternChar)
+        );
+        assert_eq!(
+            kind(MatcherOptions {
+                suffix: Some("z".repeat(45)),
+                ..MatcherOptions::default()
+            }),
+            Some(ErrorKind::PatternTooLong)
+        );
+        assert_eq!(
+            kind(MatcherOptions {
+                prefix: Some("A".into()),
+                contains: Some("B".into()),
+                clean_prefix: Some(0),
+                ..MatcherOptions::default()
+            }),
+            Some(ErrorKind::Multiple)
+        );
+        // Look-alikes are fixed as the builder fixes them.
+        let fixed = matcher(MatcherOptions {
+            prefix: Some("S0L".into()),
+            auto_fix: true,
+            ..MatcherOptions::default()
+        })
+        .unwrap();
+        assert!(fixed.matches("SoLxyz"));
+    }
+}
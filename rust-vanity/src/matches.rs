@@ -0,0 +1,243 @@
+//! `for await` over a searcher's matches.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::Deserialize;
+use tsify::{Ts, Tsify};
+use vanity_core::{BatchStatus, VanityError};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::{internal, PartialOutcome, VanityResult, VanitySearcher};
+
+/// Options for `VanitySearcher.matches`, as a plain JS object.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct MatchesOptions {
+    /// Attempts per batch; defaults to 100 000.
+    #[tsify(optional)]
+    pub batch_size: Option<u32>,
+    /// Ends the iteration after this many matches; unlimited by default.
+    #[tsify(optional)]
+    pub max_results: Option<u32>,
+}
+
+const DEFAULT_BATCH_SIZE: u32 = 100_000;
+
+#[wasm_bindgen(inline_js = "
+export function async_iterator(next, close) {
+    return { next, return: close, [Symbol.asyncIterator]() { return this; } };
+}
+")]
+extern "C" {
+    fn async_iterator(next: &js_sys::Function, close: &js_sys::Function) -> JsValue;
+}
+
+/// What `next()` resolves or rejects with, before it is turned into JS.
+enum Step {
+    Match(VanityResult),
+    Done,
+    /// The `stop_at` deadline ended the iteration; what partial tracking
+    /// gathered, if it was on.
+    Deadline(Option<PartialOutcome>),
+    /// A progress, checkpoint or heartbeat callback threw this.
+    Failed(JsValue),
+}
+
+/// The searcher behind an iterator, until the iteration ends.
+struct MatchStream {
+    searcher: Option<VanitySearcher>,
+    batch_size: u32,
+    remaining: Option<u32>,
+}
+
+impl MatchStream {
+    /// Runs batches until the next match, or until the searcher is stopped
+    /// or exhausted, which ends the iteration and drops it.
+    fn step(&mut self) -> Step {
+        let Some(searcher) = self.searcher.as_mut() else {
+            return Step::Done;
+        };
+        if self.remaining == Some(0) {
+            self.close();
+            return Step::Done;
+        }
+        loop {
+            if let Some(found) = searcher.run_batch(self.batch_size) {
+                if let Some(remaining) = self.remaining.as_mut() {
+                    *remaining -= 1;
+                }
+                return Step::Match(searcher.to_result(found));
+            }
+            match searcher.searcher.batch_status(false) {
+                BatchStatus::NotFound => continue,
+                BatchStatus::Stopped if searcher.deadline_reached => {
+                    let partial = searcher.partial_outcome();
+                    self.close();
+                    return Step::Deadline(partial);
+                }
+                _ => {
+                    let error = searcher.callback_error.clone();
+                    self.close();
+                    return error.map_or(Step::Done, Step::Failed);
+                }
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if self.searcher.take().is_some() {
+            log_debug!("match iterator closed");
+        }
+    }
+}
+
+fn iterator_result(value: JsValue, done: bool) -> JsValue {
+    let result = js_sys::Object::new();
+    // Setting properties on a fresh plain object can't fail.
+    let _ = js_sys::Reflect::set(&result, &"value".into(), &value);
+    let _ = js_sys::Reflect::set(&result, &"done".into(), &done.into());
+    result.into()
+}
+
+#[wasm_bindgen]
+impl VanitySearcher {
+    /// An async iterator over matches, for
+    /// `for await (const result of searcher.matches({ batch_size: 100_000 }))`.
+    /// Each `next()` runs batches until the next match and resolves with a
+    /// `VanityResult`; no work happens between calls. The iteration ends
+    /// after `max_results` matches, or once the searcher is stopped (e.g.
+    /// through its stop flag) or exhausted, and rejects with whatever a
+    /// throwing callback threw. When a `stop_at` deadline ends it, the
+    /// final `next()` resolves with the partial outcome as its `value`
+    /// (`undefined` without partial tracking). A `next()` blocks the thread
+    /// it runs on, so iterate in a worker. The iterator takes over the
+    /// searcher: this handle can't be used afterwards, and the searcher is
+    /// freed when the iteration ends or `return()` is called (as `break`
+    /// does).
+    #[wasm_bindgen(unchecked_return_type = "AsyncIterableIterator<VanityResult>")]
+    pub fn matches(self, options: Option<Ts<MatchesOptions>>) -> Result<JsValue, VanityJsError> {
+        let options = options
+            .map(|options| options.to_rust())
+            .transpose()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid matches options: {e}")))?
+            .unwrap_or_default();
+        let stream = Rc::new(RefCell::new(MatchStream {
+            searcher: Some(self),
+            batch_size: options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1),
+            remaining: options.max_results,
+        }));
+
+        let next = {
+            let stream = stream.clone();
+            Closure::<dyn FnMut() -> js_sys::Promise>::new(move || {
+                match stream.borrow_mut().step() {
+                    Step::Match(result) => {
+                        js_sys::Promise::resolve(&iterator_result(result.into(), false))
+                    }
+                    Step::Done => {
+                        js_sys::Promise::resolve(&iterator_result(JsValue::UNDEFINED, true))
+                    }
+                    Step::Deadline(partial) => {
+                        let value = match partial.map(|partial| partial.into_ts()).transpose() {
+                            Ok(value) => value.map_or(JsValue::UNDEFINED, JsValue::from),
+                            Err(error) => {
+                                let error = VanityJsError::from(internal(error));
+                                return js_sys::Promise::reject(&error.into());
+                            }
+                        };
+                        js_sys::Promise::resolve(&iterator_result(value, true))
+                    }
+                    Step::Failed(error) => js_sys::Promise::reject(&error),
+                }
+            })
+        };
+        let close = Closure::<dyn FnMut(JsValue) -> js_sys::Promise>::new(move |value| {
+            stream.borrow_mut().close();
+            js_sys::Promise::resolve(&iterator_result(value, true))
+        });
+        Ok(async_iterator(
+            next.into_js_value().unchecked_ref(),
+            close.into_js_value().unchecked_ref(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(prefix: &str, max_results: Option<u32>) -> MatchStream {
+        let searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some(prefix.into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        MatchStream {
+            searcher: Some(searcher),
+            batch_size: 100,
+            remaining: max_results,
+        }
+    }
+
+    #[test]
+    fn test_stream_yields_until_max_results() {
+        let mut stream = stream("A", Some(2));
+        let mut attempts = Vec::new();
+        for _ in 0..2 {
+            match stream.step() {
+                Step::Match(result) => {
+                    assert!(result.address().starts_with('A'));
+                    attempts.push(result.attempts());
+                }
+                _ => panic!("expected a match"),
+            }
+        }
+        assert!(attempts[0] < attempts[1]);
+        assert!(matches!(stream.step(), Step::Done));
+        assert!(stream.searcher.is_none());
+    }
+
+    #[test]
+    fn test_stream_ends_when_exhausted_or_closed() {
+        let mut exhausted = stream("zzzz", None);
+        exhausted
+            .searcher
+            .as_mut()
+            .unwrap()
+            .set_max_attempts(Some(250));
+        assert!(matches!(exhausted.step(), Step::Done));
+        assert!(exhausted.searcher.is_none());
+
+        let mut closed = stream("A", None);
+        closed.close();
+        assert!(matches!(closed.step(), Step::Done));
+    }
+
+    #[test]
+    fn test_stream_ends_at_the_deadline_with_the_partial_outcome() {
+        let mut stream = stream("zzzz", None);
+        let searcher = stream.searcher.as_mut().unwrap();
+        searcher.set_partial_tracking(true);
+        searcher.set_max_attempts(Some(100));
+        assert!(matches!(stream.step(), Step::Done));
+
+        let mut stream = self::stream("zzzz", None);
+        let searcher = stream.searcher.as_mut().unwrap();
+        searcher.set_partial_tracking(true);
+        searcher.run_batch(100);
+        searcher.stop_at(crate::clock::epoch_ms() - 1.0).unwrap();
+        match stream.step() {
+            Step::Deadline(Some(partial)) => assert_eq!(partial.stats.tracked, 100),
+            _ => panic!("expected the deadline to end the iteration"),
+        }
+        assert!(stream.searcher.is_none());
+    }
+}
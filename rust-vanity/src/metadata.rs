@@ -0,0 +1,191 @@
+//! Joint mint + metadata PDA grinding.
+
+use serde::Deserialize;
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    JointRequirement, MatchSpec, MetadataMatch, MetadataSearcher, Pubkey, VanityError,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::{internal, master_secret_or_random};
+
+/// One side's pattern in `MetadataOptions`.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct PatternOptions {
+    #[tsify(optional)]
+    pub prefix: Option<String>,
+    #[tsify(optional)]
+    pub suffix: Option<String>,
+    #[tsify(optional)]
+    pub case_insensitive: bool,
+}
+
+impl PatternOptions {
+    fn spec(self) -> MatchSpec {
+        MatchSpec::new(self.prefix, self.suffix, self.case_insensitive)
+    }
+}
+
+/// Options for `VanityMetadataSearcher`, as a plain JS object.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct MetadataOptions {
+    /// Pattern for the mint address.
+    #[tsify(optional)]
+    pub mint: Option<PatternOptions>,
+    /// Pattern for the metadata PDA.
+    #[tsify(optional)]
+    pub metadata: Option<PatternOptions>,
+    /// "both" (the default) or "either".
+    #[tsify(optional)]
+    pub require: JointRequirement,
+    /// Defaults to the Metaplex Token Metadata program.
+    #[tsify(optional)]
+    pub program_id: Option<String>,
+    #[tsify(optional)]
+    pub count_offset: u64,
+    /// 32 bytes every mint key is derived from; random if omitted. Workers
+    /// splitting a search by `count_offset` must share it.
+    #[tsify(optional)]
+    pub master_secret: Option<Vec<u8>>,
+}
+
+/// Grinds mint keypairs until the mint address, its Metaplex metadata PDA,
+/// or (by default) both match their patterns. The result's `mint_secret`
+/// must sign the mint's creation, so treat it like any other secret key.
+#[wasm_bindgen]
+pub struct VanityMetadataSearcher {
+    searcher: MetadataSearcher,
+    master_secret: [u8; 32],
+}
+
+#[wasm_bindgen]
+impl VanityMetadataSearcher {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Ts<MetadataOptions>) -> Result<VanityMetadataSearcher, VanityJsError> {
+        let options = options
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid metadata options: {e}")))?;
+        VanityMetadataSearcher::from_options(options)
+    }
+
+    /// Tries up to `batch_size` more mints and returns the first that
+    /// passes, if any.
+    #[wasm_bindgen]
+    pub fn search_batch(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Option<Ts<MetadataMatch>>, VanityJsError> {
+        let Some(found) = self.searcher.search_batch(batch_size) else {
+            return Ok(None);
+        };
+        log_info!(
+            "mint {} (metadata {}) after {} attempts",
+            found.mint,
+            found.metadata,
+            found.attempts
+        );
+        Ok(Some(found.into_ts().map_err(internal)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+
+    /// The secret every mint key is derived from, to hand to other workers.
+    #[wasm_bindgen(getter)]
+    pub fn master_secret(&self) -> Vec<u8> {
+        self.master_secret.to_vec()
+    }
+
+    /// Expected attempts for the combined patterns.
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        self.searcher.expected_attempts()
+    }
+}
+
+impl VanityMetadataSearcher {
+    fn from_options(options: MetadataOptions) -> Result<VanityMetadataSearcher, VanityJsError> {
+        let program_id = Pubkey::from_base58(
+            options
+                .program_id
+                .as_deref()
+                .unwrap_or(vanity_core::METADATA_PROGRAM_ID),
+        )?;
+        let master_secret = master_secret_or_random(options.master_secret.as_deref())?;
+        let searcher = MetadataSearcher::new(
+            program_id.as_bytes(),
+            &master_secret,
+            options.mint.map(PatternOptions::spec),
+            options.metadata.map(PatternOptions::spec),
+            options.require,
+            options.count_offset,
+        )?;
+        Ok(VanityMetadataSearcher {
+            searcher,
+            master_secret,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefix(prefix: &str) -> Option<PatternOptions> {
+        Some(PatternOptions {
+            prefix: Some(prefix.into()),
+            ..PatternOptions::default()
+        })
+    }
+
+    #[test]
+    fn test_options_reach_the_searcher() {
+        let mut searcher = VanityMetadataSearcher::from_options(MetadataOptions {
+            mint: prefix("A"),
+            metadata: prefix("B"),
+            master_secret: Some(vec![3; 32]),
+            ..MetadataOptions::default()
+        })
+        .unwrap();
+        let odds = |prefix| vanity_core::match_probability(Some(prefix), None, false);
+        assert_eq!(searcher.expected_attempts(), 1.0 / (odds("A") * odds("B")));
+        assert_eq!(searcher.master_secret(), vec![3; 32]);
+        let found = loop {
+            if let Some(found) = searcher.searcher.search_batch(1000) {
+                break found;
+            }
+        };
+        assert!(found.mint.starts_with('A') && found.metadata.starts_with('B'));
+    }
+
+    #[test]
+    fn test_rejects_bad_options() {
+        let kind = |options| {
+            VanityMetadataSearcher::from_options(options)
+                .err()
+                .map(|error| error.0.kind())
+        };
+        assert_eq!(
+            kind(MetadataOptions::default()),
+            Some(vanity_core::ErrorKind::EmptyPattern)
+        );
+        assert_eq!(
+            kind(MetadataOptions {
+                mint: prefix("A"),
+                master_secret: Some(vec![1; 8]),
+                ..MetadataOptions::default()
+            }),
+            Some(vanity_core::ErrorKind::InvalidOptions)
+        );
+    }
+}
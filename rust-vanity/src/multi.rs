@@ -0,0 +1,225 @@
+//! Several unrelated searches sharing one loop.
+
+use serde::Serialize;
+use tsify::Tsify;
+use vanity_core::{
+    seed_to_string, KeyRole, MultiMatch, MultiSearcher, PatternEntry, PatternStats, Pubkey,
+    Searcher, VanityError,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::control::CancelToken;
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// One match from `VanityMultiSearcher.search_batch`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct MultiResult {
+    /// Index returned by `add_job`.
+    pub job: u32,
+    pub address: String,
+    pub seed: String,
+    /// Attempts the job had made, including this one.
+    pub attempts: u64,
+    /// Absolute counter the seed was generated from.
+    pub counter: u64,
+}
+
+impl From<MultiMatch> for MultiResult {
+    fn from(found: MultiMatch) -> MultiResult {
+        MultiResult {
+            job: found.job as u32,
+            address: found.address,
+            seed: seed_to_string(&found.seed),
+            attempts: found.attempts,
+            counter: found.counter,
+        }
+    }
+}
+
+/// Grinds for several targets at once, each with its own base/owner pair
+/// and pattern, in a single loop: every counter's seed is generated once and
+/// checked against each job still running. A job drops out once it has
+/// `max_results` matches; the search is done when every job has.
+#[wasm_bindgen]
+pub struct VanityMultiSearcher {
+    multi: MultiSearcher,
+}
+
+#[wasm_bindgen]
+impl VanityMultiSearcher {
+    #[wasm_bindgen(constructor)]
+    pub fn new(count_offset: u64) -> VanityMultiSearcher {
+        VanityMultiSearcher {
+            multi: MultiSearcher::new(count_offset),
+        }
+    }
+
+    /// Adds a job and returns its index. The keys and pattern are checked as
+    /// the `VanitySearcher` constructor checks them. `max_results` defaults
+    /// to 1. A job added mid-search starts at the current counter.
+    #[wasm_bindgen]
+    pub fn add_job(
+        &mut self,
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        max_results: Option<u32>,
+    ) -> Result<u32, VanityJsError> {
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let searcher = Searcher::new(
+            base_pubkey.as_bytes(),
+            owner_pubkey.as_bytes(),
+            prefix,
+            suffix,
+            case_insensitive,
+            0,
+        )?;
+        let job = self.multi.add_job(searcher, max_results.unwrap_or(1));
+        log_debug!("multi searcher: added job {job}");
+        Ok(job as u32)
+    }
+
+    /// Adds a job for each entry of a pattern list from
+    /// `parse_pattern_list`, all for the same keys, and returns their
+    /// indexes in list order. Fails, adding nothing, if a key is bad or an
+    /// entry is, as `format_pattern_list` would.
+    #[wasm_bindgen]
+    pub fn add_pattern_list(
+        &mut self,
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        #[wasm_bindgen(unchecked_param_type = "PatternEntry[]")] entries: JsValue,
+        max_results: Option<u32>,
+    ) -> Result<Vec<u32>, VanityJsError> {
+        let entries: Vec<PatternEntry> = serde_wasm_bindgen::from_value(entries)
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid pattern list: {e}")))?;
+        vanity_core::check_pattern_list(&entries)?;
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let jobs = entries
+            .iter()
+            .map(|entry| {
+                let searcher = Searcher::with_match_type(
+                    base_pubkey.as_bytes(),
+                    owner_pubkey.as_bytes(),
+                    entry.match_type(),
+                    entry.case_insensitive,
+                    0,
+                );
+                let max_results = max_results.unwrap_or(1);
+                self.multi
+                    .add_labeled_job(searcher, max_results, entry.label.clone())
+                    as u32
+            })
+            .collect();
+        log_debug!(
+            "multi searcher: added {} jobs from a pattern list",
+            entries.len()
+        );
+        Ok(jobs)
+    }
+
+    /// Checks up to `batch_size` counters against every running job and
+    /// returns the matches found, in counter order, each tagged with its job.
+    #[wasm_bindgen(unchecked_return_type = "MultiResult[]")]
+    pub fn search_batch(&mut self, batch_size: u32) -> Result<js_sys::Array, VanityJsError> {
+        self.run_batch(batch_size)
+            .into_iter()
+            .map(|result| Ok(JsValue::from(result.into_ts().map_err(internal)?)))
+            .collect()
+    }
+
+    /// Counters checked so far, across all jobs.
+    #[wasm_bindgen]
+    pub fn attempts(&self) -> u64 {
+        self.multi.attempts()
+    }
+
+    /// Whether the counter space is used up; jobs still running then never
+    /// finish.
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.multi.is_exhausted()
+    }
+
+    /// Counters the job checked while it was running, or `undefined` for an
+    /// unknown job.
+    #[wasm_bindgen]
+    pub fn job_attempts(&self, job: u32) -> Option<u64> {
+        self.multi.job_attempts(job as usize)
+    }
+
+    #[wasm_bindgen]
+    pub fn job_results(&self, job: u32) -> Option<u32> {
+        self.multi.job_results(job as usize)
+    }
+
+    #[wasm_bindgen]
+    pub fn is_job_done(&self, job: u32) -> Option<bool> {
+        self.multi.is_job_done(job as usize)
+    }
+
+    /// Matches found against attempts made for each job, in job order,
+    /// labelled as in the pattern list for jobs from `add_pattern_list` and
+    /// "job N" otherwise; `empirical_vs_expected_ratio` near 1 means the
+    /// estimate is holding up.
+    #[wasm_bindgen(unchecked_return_type = "PatternStats[]")]
+    pub fn pattern_stats(&self) -> Result<js_sys::Array, VanityJsError> {
+        self.multi
+            .pattern_stats()
+            .into_iter()
+            .map(|stats: PatternStats| Ok(JsValue::from(stats.into_ts().map_err(internal)?)))
+            .collect()
+    }
+
+    /// Whether every job has all its results.
+    #[wasm_bindgen]
+    pub fn is_done(&self) -> bool {
+        self.multi.is_done()
+    }
+
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.multi.stop();
+    }
+
+    #[wasm_bindgen]
+    pub fn is_stopped(&self) -> bool {
+        self.multi.is_stopped()
+    }
+
+    #[wasm_bindgen]
+    pub fn restart(&mut self) {
+        self.multi.restart();
+    }
+
+    /// As `VanitySearcher.set_cancel_token`, for every job at once.
+    #[wasm_bindgen]
+    pub fn set_cancel_token(&mut self, token: &CancelToken) {
+        self.multi.set_cancel_token(Some(token.shared()));
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_cancel_token(&mut self) {
+        self.multi.set_cancel_token(None);
+    }
+}
+
+impl VanityMultiSearcher {
+    fn run_batch(&mut self, batch_size: u32) -> Vec<MultiResult> {
+        let found = self.multi.search_batch(batch_size);
+        for found in &found {
+            log_info!(
+                "job {}: match {} after {} attempts",
+                found.job,
+                found.address,
+                found.attempts
+            );
+        }
+        found.into_iter().map(MultiResult::from).collect()
+    }
+}
@@ -0,0 +1,147 @@
+//! Passphrase seeds: the winning seed is regenerated from a remembered
+//! passphrase and the result's counter, so it never has to be stored.
+
+use vanity_core::{KeyRole, PassphraseSeedDerivation, Pubkey, Searcher, VanityError};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::error::VanityJsError;
+use crate::VanityResult;
+
+fn check_passphrase(passphrase: &str) -> Result<(), VanityError> {
+    if passphrase.is_empty() {
+        return Err(VanityError::InvalidOptions(
+            "passphrase must not be empty".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// The seed `VanityPassphraseSearcher` tried at `counter` with
+/// `passphrase`, e.g. to recover a match's seed from its `counter`.
+#[wasm_bindgen]
+pub fn seed_from_passphrase(passphrase: String, counter: u64) -> Result<String, VanityJsError> {
+    let passphrase = Zeroizing::new(passphrase);
+    check_passphrase(&passphrase)?;
+    let seed = vanity_core::seed_from_passphrase(passphrase.as_bytes(), counter);
+    Ok(vanity_core::seed_to_string(&seed))
+}
+
+/// `VanitySearcher` with seeds derived from a passphrase by HKDF, so a
+/// match can be re-derived from the passphrase and its `counter` at any
+/// time with `seed_from_passphrase`. The passphrase is wiped from wasm
+/// memory when the searcher is freed and never logged; the JS string it
+/// came from is out of reach, so drop references to it promptly. Anyone
+/// who learns the passphrase learns every seed.
+#[wasm_bindgen]
+pub struct VanityPassphraseSearcher {
+    searcher: Searcher<PassphraseSeedDerivation>,
+}
+
+#[wasm_bindgen]
+impl VanityPassphraseSearcher {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        passphrase: String,
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: Option<u64>,
+    ) -> Result<VanityPassphraseSearcher, VanityJsError> {
+        let passphrase = Zeroizing::new(passphrase);
+        check_passphrase(&passphrase)?;
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let searcher = Searcher::with_pattern(
+            PassphraseSeedDerivation::new(
+                base_pubkey.as_bytes(),
+                owner_pubkey.as_bytes(),
+                passphrase.as_bytes(),
+            ),
+            prefix,
+            suffix,
+            case_insensitive,
+            count_offset.unwrap_or(0),
+        )?;
+        Ok(VanityPassphraseSearcher { searcher })
+    }
+
+    /// Tries up to `batch_size` more seeds and returns the first match, if
+    /// any.
+    #[wasm_bindgen]
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<VanityResult> {
+        let found = self.searcher.search_batch(batch_size)?;
+        log_info!("match {} after {} attempts", found.address, found.attempts);
+        Some(VanityResult::from(found))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.searcher.stop();
+    }
+
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.searcher.match_probability()
+    }
+
+    /// As `VanitySearcher.resume_token`; tokens only apply to searchers with
+    /// the same passphrase and pattern.
+    #[wasm_bindgen]
+    pub fn resume_token(&self) -> String {
+        self.searcher.resume_token()
+    }
+
+    #[wasm_bindgen]
+    pub fn resume_from(&mut self, token: &str) -> Result<(), VanityJsError> {
+        Ok(self.searcher.resume_from(token)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vanity_core::{encode_pubkey, CreateWithSeedDerivation, ErrorKind, MAX_ENCODED_LEN};
+
+    use super::*;
+
+    #[test]
+    fn test_seed_is_regenerated_from_the_passphrase() {
+        let mut searcher = VanityPassphraseSearcher::new(
+            &[1; 32],
+            &[4; 32],
+            "correct horse battery staple".into(),
+            Some("Ab".into()),
+            None,
+            true,
+            None,
+        )
+        .unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(10_000) {
+                break found;
+            }
+        };
+        // Keep only the counter; the passphrase gives the seed back.
+        let counter = found.counter().unwrap();
+
+        let seed = seed_from_passphrase("correct horse battery staple".into(), counter).unwrap();
+        let pubkey = CreateWithSeedDerivation::new(&[1; 32], &[4; 32]).derive(seed.as_bytes());
+        let address = encode_pubkey(&pubkey, &mut [0; MAX_ENCODED_LEN]).to_string();
+        assert_eq!(address, found.address());
+        assert_eq!(seed, found.seed());
+        assert_ne!(
+            seed_from_passphrase("correct horse battery stapler".into(), counter).unwrap(),
+            seed
+        );
+
+        let empty = seed_from_passphrase(String::new(), 0).unwrap_err();
+        assert_eq!(empty.0.kind(), ErrorKind::InvalidOptions);
+    }
+}
@@ -0,0 +1,41 @@
+//! The pattern list text format, for lists users keep in a file.
+
+use tsify::Tsify;
+use vanity_core::{PatternEntry, VanityError};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// Reads a pattern list, one search per line:
+///
+/// ```text
+/// prefix:Jup ci          # ci: ignore case
+/// suffix:dao label=treasury
+/// prefix:So suffix:Ana
+/// contains:moon
+/// ```
+///
+/// Blank lines and `#` comments are skipped; an entry without `label=` is
+/// labelled by its directives, e.g. "prefix:Jup ci", and labels must be
+/// unique. Throws "InvalidPatternList" with the 1-based `line` for a bad
+/// line, or "Multiple" with one such error per bad line.
+#[wasm_bindgen(unchecked_return_type = "PatternEntry[]")]
+pub fn parse_pattern_list(text: &str) -> Result<js_sys::Array, VanityJsError> {
+    vanity_core::parse_pattern_list(text)?
+        .into_iter()
+        .map(|entry| Ok(JsValue::from(entry.into_ts().map_err(internal)?)))
+        .collect()
+}
+
+/// Inverse of `parse_pattern_list`: `entries` as a pattern list that reads
+/// back the same. Throws as `parse_pattern_list` would for an entry it
+/// couldn't read, with `line` the entry's 1-based position.
+#[wasm_bindgen]
+pub fn format_pattern_list(
+    #[wasm_bindgen(unchecked_param_type = "PatternEntry[]")] entries: JsValue,
+) -> Result<String, VanityJsError> {
+    let entries: Vec<PatternEntry> = serde_wasm_bindgen::from_value(entries)
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid pattern list: {e}")))?;
+    Ok(vanity_core::format_pattern_list(&entries)?)
+}
@@ -0,0 +1,795 @@
+//! A pool of web workers searching one pattern together, run from Rust:
+//! the page creates a `WorkerPool`, each worker script calls
+//! `pool_worker_main`, and the two talk over a small serde protocol.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use tsify::{Ts, Tsify};
+use vanity_core::{partition_counters, KeyRole, Pubkey, Searcher, VanityError};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    DedicatedWorkerGlobalScope, ErrorEvent, Event, MessageEvent, Worker, WorkerOptions, WorkerType,
+};
+
+use crate::error::VanityJsError;
+use crate::{device, internal, SearchOptions, VanityResult, VanityResultJson};
+
+const DEFAULT_BATCH_SIZE: u32 = 100_000;
+
+// The bootstrap imports this module by URL; wasm-pack puts snippets two
+// directories below it. Bundlers move both, hence `module_url`.
+#[wasm_bindgen(inline_js = "
+export function next_tick() {
+    return new Promise((resolve) => setTimeout(resolve, 0));
+}
+export function own_module_url() {
+    return new URL('../../rust_vanity.js', import.meta.url).href;
+}
+export function bootstrap_url(module_url) {
+    const source = `import init, { pool_worker_main } from ${JSON.stringify(module_url)};
+await init();
+pool_worker_main();
+`;
+    return URL.createObjectURL(new Blob([source], { type: 'text/javascript' }));
+}
+export function revoke_url(url) {
+    URL.revokeObjectURL(url);
+}
+")]
+extern "C" {
+    pub(crate) fn next_tick() -> js_sys::Promise;
+    fn own_module_url() -> String;
+    fn bootstrap_url(module_url: &str) -> String;
+    fn revoke_url(url: &str);
+}
+
+/// Options for `WorkerPool`, as a plain JS object.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct PoolOptions {
+    pub base_pubkey: Vec<u8>,
+    pub owner_pubkey: Vec<u8>,
+    pub pattern: SearchOptions,
+    /// Defaults to the device's hardware concurrency.
+    #[tsify(optional)]
+    pub workers: Option<u32>,
+    /// Start of the counters split between the workers.
+    #[tsify(optional)]
+    pub count_offset: u64,
+    /// Attempts each worker makes between progress reports; defaults to
+    /// 100 000.
+    #[tsify(optional)]
+    pub batch_size: Option<u32>,
+    /// A worker script that calls `pool_worker_main` once initialized.
+    #[tsify(optional)]
+    pub worker_url: Option<String>,
+    /// URL of this package's JS module, for the built-in worker script;
+    /// defaults to where wasm-pack puts it. Ignored with `worker_url`.
+    #[tsify(optional)]
+    pub module_url: Option<String>,
+}
+
+/// Passed to the progress callback of a `WorkerPool`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct PoolProgress {
+    /// Attempts made by all workers together.
+    pub attempts: u64,
+    /// Workers still loading or searching.
+    pub running: u32,
+    pub failed: u32,
+}
+
+/// A worker's share of the search.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Assignment {
+    base_pubkey: Vec<u8>,
+    owner_pubkey: Vec<u8>,
+    pattern: SearchOptions,
+    /// In decimal, like `VanityResultJson.counter`.
+    count_offset: String,
+    batch_size: u32,
+}
+
+impl Assignment {
+    fn searcher(&self) -> Result<Searcher, VanityError> {
+        let base_pubkey = Pubkey::for_role(&self.base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(&self.owner_pubkey, KeyRole::Owner)?;
+        let count_offset = self
+            .count_offset
+            .parse()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid count offset: {e}")))?;
+        Searcher::new(
+            base_pubkey.as_bytes(),
+            owner_pubkey.as_bytes(),
+            self.pattern.prefix.clone(),
+            self.pattern.suffix.clone(),
+            self.pattern.case_insensitive,
+            count_offset,
+        )
+    }
+}
+
+/// Host to worker.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Command {
+    Start(Assignment),
+    Stop,
+}
+
+/// Worker to host.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Report {
+    /// The module is initialized and listening for commands.
+    Ready,
+    Progress {
+        attempts: u64,
+    },
+    Found {
+        result: VanityResultJson,
+    },
+    /// Sent after `Found`, on `Stop`, or when the counters run out.
+    Stopped {
+        attempts: u64,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+/// One `Start` per worker, over adjacent counter ranges. Fails before any
+/// worker exists on bad keys, a bad pattern or zero workers.
+fn assignments(options: &PoolOptions) -> Result<Vec<Assignment>, VanityError> {
+    let workers = match options.workers {
+        Some(0) => {
+            return Err(VanityError::InvalidOptions(
+                "a pool needs at least one worker".to_string(),
+            ))
+        }
+        Some(workers) => workers,
+        None => device::hardware_concurrency().max(1),
+    };
+    let template = Assignment {
+        base_pubkey: options.base_pubkey.clone(),
+        owner_pubkey: options.owner_pubkey.clone(),
+        pattern: options.pattern.clone(),
+        count_offset: options.count_offset.to_string(),
+        batch_size: options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+    };
+    template.searcher()?;
+    Ok(partition_counters(options.count_offset, workers)?
+        .into_iter()
+        .map(|range| Assignment {
+            count_offset: range.offset.to_string(),
+            ..template.clone()
+        })
+        .collect())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WorkerState {
+    Loading,
+    Searching,
+    /// Told to stop; waiting for `Stopped`.
+    Stopping,
+    Stopped,
+    Failed,
+}
+
+/// What the host does in response to a report, once the state is no longer
+/// borrowed: callbacks may call back into the pool.
+#[derive(Clone, Debug, PartialEq)]
+enum Action {
+    Send(usize, Command),
+    Terminate(usize),
+    Match(VanityResultJson),
+    Progress,
+    Fail(usize, String),
+}
+
+/// The host's view of its workers, without any JS.
+struct PoolState {
+    workers: Vec<WorkerState>,
+    attempts: Vec<u64>,
+    assignments: Vec<Assignment>,
+    /// Set on the first match or on `stop`; nothing starts after it.
+    stopping: bool,
+    found: bool,
+}
+
+impl PoolState {
+    fn new(assignments: Vec<Assignment>) -> PoolState {
+        PoolState {
+            workers: vec![WorkerState::Loading; assignments.len()],
+            attempts: vec![0; assignments.len()],
+            assignments,
+            stopping: false,
+            found: false,
+        }
+    }
+
+    fn handle(&mut self, worker: usize, report: Report) -> Vec<Action> {
+        let state = self.workers[worker];
+        if matches!(state, WorkerState::Stopped | WorkerState::Failed) {
+            return Vec::new();
+        }
+        match report {
+            Report::Ready if state == WorkerState::Loading => {
+                if self.stopping {
+                    self.workers[worker] = WorkerState::Stopped;
+                    return vec![Action::Terminate(worker), Action::Progress];
+                }
+                self.workers[worker] = WorkerState::Searching;
+                let start = Command::Start(self.assignments[worker].clone());
+                vec![Action::Send(worker, start)]
+            }
+            Report::Ready => Vec::new(),
+            Report::Progress { attempts } => {
+                self.attempts[worker] = attempts;
+                vec![Action::Progress]
+            }
+            Report::Found { result } => {
+                self.attempts[worker] = result.attempts;
+                if self.found {
+                    return Vec::new();
+                }
+                self.found = true;
+                let mut actions = vec![Action::Match(result)];
+                actions.extend(self.stop(Some(worker)));
+                actions
+            }
+            Report::Stopped { attempts } => {
+                self.attempts[worker] = attempts;
+                self.workers[worker] = WorkerState::Stopped;
+                vec![Action::Terminate(worker), Action::Progress]
+            }
+            Report::Failed { message } => self.fail(worker, message),
+        }
+    }
+
+    fn fail(&mut self, worker: usize, message: String) -> Vec<Action> {
+        if matches!(
+            self.workers[worker],
+            WorkerState::Stopped | WorkerState::Failed
+        ) {
+            return Vec::new();
+        }
+        self.workers[worker] = WorkerState::Failed;
+        vec![
+            Action::Terminate(worker),
+            Action::Fail(worker, message),
+            Action::Progress,
+        ]
+    }
+
+    /// Asks searching workers to stop, except `finder`, which stops by
+    /// itself after a match, and drops ones still loading.
+    fn stop(&mut self, finder: Option<usize>) -> Vec<Action> {
+        self.stopping = true;
+        let mut actions = Vec::new();
+        for (worker, state) in self.workers.iter_mut().enumerate() {
+            match state {
+                WorkerState::Loading => {
+                    *state = WorkerState::Stopped;
+                    actions.push(Action::Terminate(worker));
+                }
+                WorkerState::Searching => {
+                    *state = WorkerState::Stopping;
+                    if finder != Some(worker) {
+                        actions.push(Action::Send(worker, Command::Stop));
+                    }
+                }
+                _ => {}
+            }
+        }
+        actions
+    }
+
+    fn count(&self, wanted: impl Fn(WorkerState) -> bool) -> u32 {
+        self.workers.iter().filter(|&&state| wanted(state)).count() as u32
+    }
+
+    fn progress(&self) -> PoolProgress {
+        PoolProgress {
+            attempts: self.attempts.iter().sum(),
+            running: self
+                .count(|state| !matches!(state, WorkerState::Stopped | WorkerState::Failed)),
+            failed: self.count(|state| state == WorkerState::Failed),
+        }
+    }
+}
+
+/// What the pool and its workers' event handlers share.
+struct Shared {
+    workers: Vec<Worker>,
+    state: RefCell<PoolState>,
+    on_match: js_sys::Function,
+    on_progress: RefCell<Option<js_sys::Function>>,
+    on_failure: RefCell<Option<js_sys::Function>>,
+}
+
+impl Shared {
+    fn dispatch(&self, worker: usize, report: Report) {
+        let actions = self.state.borrow_mut().handle(worker, report);
+        self.run(actions);
+    }
+
+    fn run(&self, actions: Vec<Action>) {
+        for action in actions {
+            match action {
+                Action::Send(worker, command) => {
+                    let sent = serde_wasm_bindgen::to_value(&command)
+                        .map_err(|e| format!("{e}"))
+                        .and_then(|message| {
+                            self.workers[worker]
+                                .post_message(&message)
+                                .map_err(|e| format!("{e:?}"))
+                        });
+                    if let Err(error) = sent {
+                        let actions = self
+                            .state
+                            .borrow_mut()
+                            .fail(worker, format!("couldn't send a command: {error}"));
+                        self.run(actions);
+                    }
+                }
+                Action::Terminate(worker) => self.workers[worker].terminate(),
+                Action::Match(result) => match VanityResult::from_plain(result) {
+                    Ok(result) => {
+                        log_info!("pool: match {}", result.address);
+                        if self
+                            .on_match
+                            .call1(&JsValue::NULL, &JsValue::from(result))
+                            .is_err()
+                        {
+                            log_error!("match callback threw");
+                        }
+                    }
+                    Err(error) => log_error!("pool: {error}"),
+                },
+                Action::Progress => {
+                    let Some(callback) = self.on_progress.borrow().clone() else {
+                        continue;
+                    };
+                    let progress = self.state.borrow().progress();
+                    let called = progress
+                        .into_ts()
+                        .map_err(internal)
+                        .map(|progress| callback.call1(&JsValue::NULL, &progress.into()));
+                    if !matches!(called, Ok(Ok(_))) {
+                        log_error!("progress callback threw");
+                    }
+                }
+                Action::Fail(worker, message) => {
+                    let error = VanityError::WorkerFailed(format!("worker {worker}: {message}"));
+                    log_warn!("pool: {error}");
+                    if let Some(callback) = self.on_failure.borrow().clone() {
+                        let error = JsValue::from(VanityJsError::from(error));
+                        if callback.call1(&JsValue::NULL, &error).is_err() {
+                            log_error!("failure callback threw");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Event handlers installed on one worker.
+struct Handlers {
+    _message: Closure<dyn FnMut(MessageEvent)>,
+    _error: Closure<dyn FnMut(Event)>,
+}
+
+fn describe(error: &JsValue) -> String {
+    error
+        .dyn_ref::<js_sys::Error>()
+        .map(|error| String::from(error.message()))
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| format!("{error:?}"))
+}
+
+/// Splits a search between web workers: each gets the pattern and its own
+/// range of counters, reports progress, and is stopped as soon as any of
+/// them finds a match, which goes to `on_match` (exactly once). Workers are
+/// terminated once they stop, fail, or the pool is freed.
+#[wasm_bindgen]
+pub struct WorkerPool {
+    shared: Rc<Shared>,
+    _handlers: Vec<Handlers>,
+    bootstrap_url: Option<String>,
+}
+
+#[wasm_bindgen]
+impl WorkerPool {
+    /// Fails without starting anything on bad options, or if a worker
+    /// can't be created; a worker that fails to load later is reported to
+    /// the failure callback.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        options: Ts<PoolOptions>,
+        on_match: js_sys::Function,
+    ) -> Result<WorkerPool, VanityJsError> {
+        let options = options
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid pool options: {e}")))?;
+        let assignments = assignments(&options)?;
+        let bootstrap_url = match &options.worker_url {
+            Some(_) => None,
+            None => Some(bootstrap_url(
+                &options.module_url.clone().unwrap_or_else(own_module_url),
+            )),
+        };
+        let script_url = options
+            .worker_url
+            .as_deref()
+            .or(bootstrap_url.as_deref())
+            .unwrap_or_default();
+
+        let worker_options = WorkerOptions::new();
+        worker_options.set_type(WorkerType::Module);
+        let mut workers = Vec::with_capacity(assignments.len());
+        for index in 0..assignments.len() {
+            match Worker::new_with_options(script_url, &worker_options) {
+                Ok(worker) => workers.push(worker),
+                Err(error) => {
+                    workers.iter().for_each(Worker::terminate);
+                    if let Some(url) = &bootstrap_url {
+                        revoke_url(url);
+                    }
+                    return Err(VanityError::WorkerFailed(format!(
+                        "couldn't create worker {index}: {}",
+                        describe(&error)
+                    ))
+                    .into());
+                }
+            }
+        }
+        log_debug!("pool: {} workers loading {script_url}", workers.len());
+
+        let shared = Rc::new(Shared {
+            workers,
+            state: RefCell::new(PoolState::new(assignments)),
+            on_match,
+            on_progress: RefCell::new(None),
+            on_failure: RefCell::new(None),
+        });
+        let handlers = shared
+            .workers
+            .iter()
+            .enumerate()
+            .map(|(index, worker)| {
+                let message =
+                    {
+                        let shared = shared.clone();
+                        Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                            let report = serde_wasm_bindgen::from_value(event.data())
+                                .unwrap_or_else(|e| Report::Failed {
+                                    message: format!("unreadable report: {e}"),
+                                });
+                            shared.dispatch(index, report);
+                        })
+                    };
+                let error = {
+                    let shared = shared.clone();
+                    let script_url = script_url.to_string();
+                    Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+                        event.prevent_default();
+                        let message = event
+                            .dyn_ref::<ErrorEvent>()
+                            .map(ErrorEvent::message)
+                            .filter(|message| !message.is_empty())
+                            .unwrap_or_else(|| format!("couldn't load {script_url}"));
+                        let actions = shared.state.borrow_mut().fail(index, message);
+                        shared.run(actions);
+                    })
+                };
+                worker.set_onmessage(Some(message.as_ref().unchecked_ref()));
+                worker.set_onerror(Some(error.as_ref().unchecked_ref()));
+                Handlers {
+                    _message: message,
+                    _error: error,
+                }
+            })
+            .collect();
+        Ok(WorkerPool {
+            shared,
+            _handlers: handlers,
+            bootstrap_url,
+        })
+    }
+
+    /// Called with a `PoolProgress` whenever a worker reports progress or
+    /// stops.
+    #[wasm_bindgen]
+    pub fn set_progress_callback(&mut self, callback: js_sys::Function) {
+        *self.shared.on_progress.borrow_mut() = Some(callback);
+    }
+
+    /// Called with a `VanityError` of kind `WorkerFailed` for each worker
+    /// that fails to load or to search; the others keep going.
+    #[wasm_bindgen]
+    pub fn set_failure_callback(&mut self, callback: js_sys::Function) {
+        *self.shared.on_failure.borrow_mut() = Some(callback);
+    }
+
+    /// Asks every worker to stop; each is terminated once it has.
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        let actions = self.shared.state.borrow_mut().stop(None);
+        self.shared.run(actions);
+    }
+
+    /// Terminates every worker at once, without waiting for their last
+    /// progress reports.
+    #[wasm_bindgen]
+    pub fn terminate(&mut self) {
+        let mut pool = self.shared.state.borrow_mut();
+        pool.stopping = true;
+        for (worker, state) in pool.workers.iter_mut().enumerate() {
+            if !matches!(state, WorkerState::Stopped | WorkerState::Failed) {
+                *state = WorkerState::Stopped;
+                self.shared.workers[worker].terminate();
+            }
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn workers(&self) -> u32 {
+        self.shared.workers.len() as u32
+    }
+
+    /// Workers still loading or searching; 0 once the pool is done.
+    #[wasm_bindgen(getter)]
+    pub fn running(&self) -> u32 {
+        self.shared.state.borrow().progress().running
+    }
+
+    /// Attempts made by all workers together, as of their last reports.
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.shared.state.borrow().progress().attempts
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn found(&self) -> bool {
+        self.shared.state.borrow().found
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.terminate();
+        for worker in &self.shared.workers {
+            worker.set_onmessage(None);
+            worker.set_onerror(None);
+        }
+        if let Some(url) = &self.bootstrap_url {
+            revoke_url(url);
+        }
+    }
+}
+
+fn post(scope: &DedicatedWorkerGlobalScope, report: &Report) {
+    let posted = serde_wasm_bindgen::to_value(report)
+        .map_err(|e| format!("{e}"))
+        .and_then(|message| scope.post_message(&message).map_err(|e| format!("{e:?}")));
+    if let Err(error) = posted {
+        log_error!("pool worker: couldn't post a report: {error}");
+    }
+}
+
+/// Searches an assignment in batches, yielding between them so `Stop` can
+/// arrive.
+async fn run_assignment(
+    scope: DedicatedWorkerGlobalScope,
+    assignment: Assignment,
+    stop: Rc<Cell<bool>>,
+) {
+    let mut searcher = match assignment.searcher() {
+        Ok(searcher) => searcher,
+        Err(error) => {
+            let message = error.to_string();
+            return post(&scope, &Report::Failed { message });
+        }
+    };
+    while !stop.get() && !searcher.is_exhausted() {
+        if let Some(found) = searcher.search_batch(assignment.batch_size) {
+            let result = VanityResult::from(found)
+                .with_keys(searcher.derivation())
+                .to_plain();
+            post(&scope, &Report::Found { result });
+            break;
+        }
+        let attempts = searcher.attempts();
+        post(&scope, &Report::Progress { attempts });
+        if let Err(error) = JsFuture::from(next_tick()).await {
+            log_warn!("pool worker: {}", describe(&error));
+        }
+    }
+    let attempts = searcher.attempts();
+    post(&scope, &Report::Stopped { attempts });
+}
+
+/// The worker side of a `WorkerPool`: call once from the worker script,
+/// after `init()`. Fails outside a dedicated worker.
+#[wasm_bindgen]
+pub fn pool_worker_main() -> Result<(), VanityJsError> {
+    let scope = js_sys::global()
+        .dyn_into::<DedicatedWorkerGlobalScope>()
+        .map_err(|_| {
+            VanityError::Unsupported("pool_worker_main must run in a dedicated worker".into())
+        })?;
+    let stop = Rc::new(Cell::new(false));
+    let handler = {
+        let scope = scope.clone();
+        Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            match serde_wasm_bindgen::from_value(event.data()) {
+                Ok(Command::Start(assignment)) => {
+                    stop.set(false);
+                    let run = run_assignment(scope.clone(), assignment, stop.clone());
+                    wasm_bindgen_futures::spawn_local(run);
+                }
+                Ok(Command::Stop) => stop.set(true),
+                Err(e) => post(
+                    &scope,
+                    &Report::Failed {
+                        message: format!("unreadable command: {e}"),
+                    },
+                ),
+            }
+        })
+    };
+    scope.set_onmessage(Some(handler.as_ref().unchecked_ref()));
+    // The handler lives as long as the worker does.
+    handler.forget();
+    post(&scope, &Report::Ready);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use vanity_core::ErrorKind;
+
+    use super::*;
+
+    fn options(workers: u32) -> PoolOptions {
+        PoolOptions {
+            base_pubkey: vec![1; 32],
+            owner_pubkey: vec![4; 32],
+            pattern: SearchOptions {
+                prefix: Some("A".into()),
+                ..SearchOptions::default()
+            },
+            workers: Some(workers),
+            count_offset: 10,
+            ..PoolOptions::default()
+        }
+    }
+
+    fn found(attempts: u64) -> Report {
+        let result =
+            VanityResult::from_seed_bytes("Abc".into(), b"seed".to_vec(), attempts, Some(u64::MAX));
+        Report::Found {
+            result: result.to_plain(),
+        }
+    }
+
+    fn ready_pool(workers: u32) -> PoolState {
+        let mut state = PoolState::new(assignments(&options(workers)).unwrap());
+        for worker in 0..workers as usize {
+            let actions = state.handle(worker, Report::Ready);
+            assert!(matches!(actions[..], [Action::Send(w, Command::Start(_))] if w == worker));
+        }
+        state
+    }
+
+    #[test]
+    fn test_assignments_split_the_counters() {
+        let split = assignments(&options(2)).unwrap();
+        let offsets: Vec<u64> = split
+            .iter()
+            .map(|a| a.count_offset.parse().unwrap())
+            .collect();
+        assert_eq!(offsets, [10, 10 + (u64::MAX - 10) / 2]);
+        assert_eq!(split[0].batch_size, DEFAULT_BATCH_SIZE);
+        assert_eq!(split[1].pattern, options(2).pattern);
+        assert!(split[1].searcher().is_ok());
+
+        let error = |options: PoolOptions| assignments(&options).unwrap_err().kind();
+        assert_eq!(error(options(0)), ErrorKind::InvalidOptions);
+        assert_eq!(
+            error(PoolOptions {
+                base_pubkey: vec![1; 31],
+                ..options(2)
+            }),
+            ErrorKind::BadBaseLength
+        );
+        assert_eq!(
+            error(PoolOptions {
+                pattern: SearchOptions::default(),
+                ..options(2)
+            }),
+            ErrorKind::EmptyPattern
+        );
+    }
+
+    #[test]
+    fn test_first_match_wins_and_stops_the_rest() {
+        let mut state = ready_pool(3);
+        assert_eq!(
+            state.handle(1, Report::Progress { attempts: 40 }),
+            [Action::Progress]
+        );
+        // Worker 2 fails; the others keep going.
+        assert_eq!(
+            state.handle(
+                2,
+                Report::Failed {
+                    message: "x".into()
+                }
+            ),
+            [
+                Action::Terminate(2),
+                Action::Fail(2, "x".into()),
+                Action::Progress
+            ]
+        );
+        let actions = state.handle(0, found(7));
+        assert!(matches!(actions[0], Action::Match(ref result) if result.attempts == 7));
+        assert_eq!(actions[1..], [Action::Send(1, Command::Stop)]);
+        assert!(state.found);
+        assert_eq!(state.progress().running, 2);
+
+        // A second match racing the stop is dropped.
+        assert_eq!(state.handle(1, found(50)), []);
+        assert_eq!(
+            state.handle(0, Report::Stopped { attempts: 7 }),
+            [Action::Terminate(0), Action::Progress]
+        );
+        assert_eq!(
+            state.handle(1, Report::Stopped { attempts: 60 }),
+            [Action::Terminate(1), Action::Progress]
+        );
+        assert_eq!(
+            state.progress(),
+            PoolProgress {
+                attempts: 67,
+                running: 0,
+                failed: 1,
+            }
+        );
+        assert_eq!(state.handle(1, found(70)), []);
+    }
+
+    #[test]
+    fn test_stop_drops_loading_workers() {
+        let mut state = PoolState::new(assignments(&options(2)).unwrap());
+        state.handle(0, Report::Ready);
+        assert_eq!(
+            state.stop(None),
+            [Action::Send(0, Command::Stop), Action::Terminate(1)]
+        );
+        // A late `Ready` doesn't start anything.
+        assert_eq!(state.handle(1, Report::Ready), []);
+        assert_eq!(state.progress().running, 1);
+        state.handle(0, Report::Stopped { attempts: 5 });
+        assert_eq!(state.progress().running, 0);
+        assert!(!state.found);
+    }
+
+    #[test]
+    fn test_results_survive_the_round_trip() {
+        let Report::Found { result } = found(3) else {
+            unreachable!()
+        };
+        let back = VanityResult::from_plain(result).unwrap();
+        assert_eq!(back.counter(), Some(u64::MAX));
+        assert_eq!(back.attempts(), 3);
+    }
+}
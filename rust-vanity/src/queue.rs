@@ -0,0 +1,220 @@
+//! Several searches for the same keys, run one after another.
+
+use serde::{Deserialize, Serialize};
+use tsify::{Ts, Tsify};
+use vanity_core::{seed_to_string, JobQueue, JobStatus, KeyRole, Pubkey, Searcher, VanityError};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::{internal, VanityResult};
+
+/// One job for `VanityJobQueue.push`, as a plain JS object.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct JobOptions {
+    #[tsify(optional)]
+    pub prefix: Option<String>,
+    #[tsify(optional)]
+    pub suffix: Option<String>,
+    #[tsify(optional)]
+    pub case_insensitive: bool,
+    /// Start of the job's counter space.
+    #[tsify(optional)]
+    pub count_offset: u64,
+    /// Attempts after which the job is given up and the next one starts.
+    #[tsify(optional)]
+    pub max_attempts: Option<u64>,
+}
+
+/// Returned by `VanityJobQueue.progress`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct QueueProgress {
+    /// Index of the job being worked on; absent once all are done.
+    #[tsify(optional)]
+    pub job: Option<u32>,
+    pub jobs: u32,
+    pub finished: u32,
+    /// Attempts made by the current job.
+    pub job_attempts: u64,
+    /// Attempts made by all jobs together.
+    pub total_attempts: u64,
+}
+
+/// One entry of `VanityJobQueue.results`.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct JobResult {
+    pub job: u32,
+    pub address: String,
+    pub seed: String,
+    /// Attempts the job made to find this match.
+    pub attempts: u64,
+    /// Absolute counter the seed was generated from.
+    pub counter: u64,
+}
+
+/// Runs queued searches for one base/owner pair in order, e.g. "one each of
+/// A, B and C, by priority". Each job has its own pattern, counter space and
+/// attempt cap; `run_batch` moves on to the next job by itself.
+#[wasm_bindgen]
+pub struct VanityJobQueue {
+    base_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    queue: JobQueue,
+}
+
+#[wasm_bindgen]
+impl VanityJobQueue {
+    #[wasm_bindgen(constructor)]
+    pub fn new(base_pubkey: &[u8], owner_pubkey: &[u8]) -> Result<VanityJobQueue, VanityJsError> {
+        Ok(VanityJobQueue {
+            base_pubkey: Pubkey::for_role(base_pubkey, KeyRole::Base)?,
+            owner_pubkey: Pubkey::for_role(owner_pubkey, KeyRole::Owner)?,
+            queue: JobQueue::new(),
+        })
+    }
+
+    /// Queues a job and returns its index. Fails, queuing nothing, if the
+    /// pattern is empty or can't match, as the `VanitySearcher` constructor
+    /// would.
+    #[wasm_bindgen]
+    pub fn push(&mut self, options: Ts<JobOptions>) -> Result<u32, VanityJsError> {
+        let options = options
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid job options: {e}")))?;
+        Ok(self.push_job(options)? as u32)
+    }
+
+    /// Runs up to `batch_size` attempts on the current job, continuing with
+    /// the next one if it is exhausted part-way. A match ends the batch and is
+    /// returned; `current_job` then already points past its job.
+    #[wasm_bindgen]
+    pub fn run_batch(&mut self, batch_size: u32) -> Option<VanityResult> {
+        let (index, found) = self.queue.run_batch(batch_size)?;
+        log_info!(
+            "job {index}: match {} after {} attempts",
+            found.address,
+            found.attempts
+        );
+        Some(VanityResult::from(found))
+    }
+
+    /// Index of the job being worked on, or `undefined` once all are done.
+    #[wasm_bindgen]
+    pub fn current_job(&self) -> Option<u32> {
+        self.queue.current_job().map(|index| index as u32)
+    }
+
+    /// Gives up on the current job and returns its index, or `undefined` if
+    /// all jobs are done.
+    #[wasm_bindgen]
+    pub fn skip(&mut self) -> Option<u32> {
+        let index = self.queue.skip()?;
+        log_debug!("job {index} skipped");
+        Some(index as u32)
+    }
+
+    /// `"pending"`, `"found"`, `"exhausted"` or `"skipped"`, or `undefined`
+    /// for an index that was never queued.
+    #[wasm_bindgen]
+    pub fn job_status(&self, index: u32) -> Result<Option<Ts<JobStatus>>, VanityJsError> {
+        self.queue
+            .status(index as usize)
+            .map(|status| Ok(status.into_ts().map_err(internal)?))
+            .transpose()
+    }
+
+    /// Matches found so far, in job order, each tagged with its job's index.
+    #[wasm_bindgen(unchecked_return_type = "JobResult[]")]
+    pub fn results(&self) -> Result<js_sys::Array, VanityJsError> {
+        self.job_results()
+            .into_iter()
+            .map(|result| Ok(JsValue::from(result.into_ts().map_err(internal)?)))
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn progress(&self) -> Result<Ts<QueueProgress>, VanityJsError> {
+        Ok(self.snapshot().into_ts().map_err(internal)?)
+    }
+}
+
+impl VanityJobQueue {
+    fn push_job(&mut self, options: JobOptions) -> Result<usize, VanityError> {
+        let mut searcher = Searcher::new(
+            self.base_pubkey.as_bytes(),
+            self.owner_pubkey.as_bytes(),
+            options.prefix,
+            options.suffix,
+            options.case_insensitive,
+            options.count_offset,
+        )?;
+        searcher.set_max_attempts(options.max_attempts);
+        Ok(self.queue.push(searcher))
+    }
+
+    fn job_results(&self) -> Vec<JobResult> {
+        self.queue
+            .results()
+            .map(|(index, found)| JobResult {
+                job: index as u32,
+                address: found.address.clone(),
+                seed: seed_to_string(&found.seed),
+                attempts: found.attempts,
+                counter: found.counter,
+            })
+            .collect()
+    }
+
+    fn snapshot(&self) -> QueueProgress {
+        let current = self.queue.current_job();
+        QueueProgress {
+            job: current.map(|index| index as u32),
+            jobs: self.queue.len() as u32,
+            finished: self.queue.finished() as u32,
+            job_attempts: current
+                .and_then(|index| self.queue.searcher(index))
+                .map_or(0, Searcher::attempts),
+            total_attempts: self.queue.total_attempts(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(prefix: &str) -> JobOptions {
+        JobOptions {
+            prefix: Some(prefix.into()),
+            ..JobOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_snapshot_follows_current_job() {
+        let mut queue = VanityJobQueue::new(&[1; 32], &[4; 32]).unwrap();
+        queue.push_job(job("A")).unwrap();
+        queue.push_job(job("B")).unwrap();
+        assert_eq!(
+            queue.push_job(job("0")).unwrap_err().kind(),
+            vanity_core::ErrorKind::InvalidPatternChar
+        );
+        assert_eq!(queue.snapshot().jobs, 2);
+
+        while queue.run_batch(100).is_none() {}
+        let progress = queue.snapshot();
+        assert_eq!(progress.job, Some(1));
+        assert_eq!(progress.finished, 1);
+        assert_eq!(progress.job_attempts, 0);
+
+        while queue.current_job().is_some() {
+            queue.run_batch(100);
+        }
+        let results = queue.job_results();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].address.starts_with('A'));
+        assert_eq!(results[1].job, 1);
+        assert!(results[1].address.starts_with('B'));
+        assert_eq!(queue.snapshot().job, None);
+    }
+}
@@ -0,0 +1,62 @@
+//! Auditing the counter ranges an orchestrator hands out.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{RangeAudit, VanityError, WorkRange};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::{internal, MAX_SAFE_INTEGER};
+
+/// Parses `{ offset, length }` records. The core checks catch ranges that
+/// wrap past u64; on top of that, every range has to end by 2^53 so what
+/// comes back holds only exact JS numbers.
+fn work_ranges(ranges: JsValue) -> Result<Vec<WorkRange>, VanityError> {
+    let ranges: Vec<WorkRange> = serde_wasm_bindgen::from_value(ranges)
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid ranges: {e}")))?;
+    vanity_core::merge_ranges(&ranges)?;
+    if let Some((index, range)) = ranges
+        .iter()
+        .enumerate()
+        .find(|(_, range)| range.end().is_some_and(|end| end > MAX_SAFE_INTEGER + 1))
+    {
+        return Err(VanityError::InvalidOptions(format!(
+            "range {index} ({} + {}) ends past Number.MAX_SAFE_INTEGER + 1",
+            range.offset, range.length
+        )));
+    }
+    Ok(ranges)
+}
+
+/// Sorts and scans the `{ offset, length }` work units given to workers and
+/// reports every pair that overlaps (with the shared counters), every gap
+/// between them, and how many counters they cover in total. Throws
+/// "InvalidOptions" for malformed records and for a range that wraps past
+/// the end of the counter space or passes 2^53.
+#[wasm_bindgen]
+pub fn audit_ranges(
+    #[wasm_bindgen(unchecked_param_type = "WorkRange[]")] ranges: JsValue,
+) -> Result<Ts<RangeAudit>, VanityJsError> {
+    let ranges = work_ranges(ranges)?;
+    let audit = vanity_core::audit_ranges(&ranges)?;
+    if !audit.overlaps.is_empty() || !audit.gaps.is_empty() {
+        log_warn!(
+            "{} overlapping pairs and {} gaps in {} ranges",
+            audit.overlaps.len(),
+            audit.gaps.len(),
+            ranges.len()
+        );
+    }
+    Ok(audit.into_ts().map_err(internal)?)
+}
+
+/// The union of the ranges as sorted, disjoint ranges, e.g. to persist a
+/// coverage log compactly. Throws as `audit_ranges` does.
+#[wasm_bindgen(unchecked_return_type = "WorkRange[]")]
+pub fn merge_ranges(
+    #[wasm_bindgen(unchecked_param_type = "WorkRange[]")] ranges: JsValue,
+) -> Result<js_sys::Array, VanityJsError> {
+    vanity_core::merge_ranges(&work_ranges(ranges)?)?
+        .iter()
+        .map(|range| Ok(JsValue::from(range.into_ts().map_err(internal)?)))
+        .collect()
+}
@@ -0,0 +1,147 @@
+//! Panics and internal errors routed to a host-provided callback, so the UI
+//! can show an error screen instead of leaving them in the console.
+
+use std::cell::RefCell;
+use std::sync::Once;
+
+use serde::Serialize;
+use tsify::Tsify;
+use vanity_core::{ErrorKind, VanityError};
+use wasm_bindgen::prelude::*;
+
+/// Where an [`ErrorReport`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorSource {
+    /// A Rust panic; the module can't be trusted afterwards.
+    Panic,
+    /// An error of kind "Internal" or "SelfTestFailed" on its way to JS.
+    Internal,
+}
+
+/// What the error callback is called with.
+#[derive(Clone, Debug, PartialEq, Serialize, Tsify)]
+pub struct ErrorReport {
+    pub source: ErrorSource,
+    pub message: String,
+    /// The `VanityError` code and kind, unless it was a panic.
+    #[tsify(optional)]
+    pub code: Option<u16>,
+    #[tsify(optional)]
+    pub kind: Option<ErrorKind>,
+    /// Whether the searcher (and module) can still be used: false after a
+    /// panic, and after a failed self test, whose results can't be trusted.
+    pub searcher_usable: bool,
+}
+
+impl ErrorReport {
+    fn from_error(error: &VanityError) -> ErrorReport {
+        ErrorReport {
+            source: ErrorSource::Internal,
+            message: error.to_string(),
+            code: Some(error.code()),
+            kind: Some(error.kind()),
+            searcher_usable: error.kind() != ErrorKind::SelfTestFailed,
+        }
+    }
+}
+
+type Sink = Box<dyn Fn(&ErrorReport)>;
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_sink(sink: Option<Sink>) {
+    SINK.with(|slot| *slot.borrow_mut() = sink);
+}
+
+fn report(report: &ErrorReport) {
+    SINK.with(|slot| {
+        // A panic inside the callback itself must not recurse.
+        if let Ok(sink) = slot.try_borrow() {
+            if let Some(sink) = sink.as_ref() {
+                sink(report);
+            }
+        }
+    });
+}
+
+/// Reports `error` if it is one the UI should treat as a crash rather than
+/// a bad input.
+pub(crate) fn report_error(error: &VanityError) {
+    if matches!(
+        error.kind(),
+        ErrorKind::Internal | ErrorKind::SelfTestFailed
+    ) {
+        report(&ErrorReport::from_error(error));
+    }
+}
+
+/// Chains the console hook (if built in) with the error callback. Runs once.
+pub(crate) fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        std::panic::set_hook(Box::new(|info| {
+            #[cfg(feature = "console_error_panic_hook")]
+            console_error_panic_hook::hook(info);
+            report(&ErrorReport {
+                source: ErrorSource::Panic,
+                message: info.to_string(),
+                code: None,
+                kind: None,
+                searcher_usable: false,
+            });
+        }));
+    });
+}
+
+/// Calls `callback(report)` with an `ErrorReport` whenever the crate panics
+/// or returns an internal error, on top of the console output; pass
+/// `undefined` to stop. Errors from bad input (patterns, keys, options)
+/// aren't reported: the call that failed already threw them.
+#[wasm_bindgen]
+pub fn set_error_callback(callback: Option<js_sys::Function>) {
+    install_panic_hook();
+    set_sink(callback.map(|callback| -> Sink {
+        Box::new(move |report| {
+            let Ok(report) = serde_wasm_bindgen::to_value(report) else {
+                return;
+            };
+            let _ = callback.call1(&JsValue::NULL, &report);
+        })
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn capture() -> Rc<RefCell<Vec<ErrorReport>>> {
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&captured);
+        set_sink(Some(Box::new(move |report| {
+            sink.borrow_mut().push(report.clone())
+        })));
+        captured
+    }
+
+    #[test]
+    fn test_only_internal_errors_are_reported() {
+        let captured = capture();
+        report_error(&VanityError::EmptyPattern);
+        report_error(&VanityError::Internal("broken invariant".into()));
+        report_error(&VanityError::SelfTestFailed("vector 1".into()));
+        let reports = captured.borrow();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].source, ErrorSource::Internal);
+        assert_eq!(reports[0].code, Some(15));
+        assert_eq!(reports[0].kind, Some(ErrorKind::Internal));
+        assert!(reports[0].message.contains("broken invariant"));
+        assert!(reports[0].searcher_usable);
+        assert!(!reports[1].searcher_usable);
+        drop(reports);
+        set_sink(None);
+    }
+}
@@ -0,0 +1,411 @@
+//! Continuous collection of matches into a bounded queue the UI drains,
+//! pausing the search, or dropping results, instead of letting them pile
+//! up.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use tsify::{Ts, Tsify};
+use vanity_core::VanityError;
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::{VanityResult, VanitySearcher};
+
+/// Queue length that pauses collection until results are taken.
+const DEFAULT_HIGH_WATER_MARK: u32 = 256;
+
+/// What `collect_batch` does with a match while the queue is at its
+/// high-water mark.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueOverflow {
+    /// Keep the match and pause collection until results are taken.
+    #[default]
+    Pause,
+    /// Drop the match and keep collecting.
+    RejectNew,
+    /// Drop the oldest queued result to make room and keep collecting.
+    EvictOldest,
+}
+
+/// Matches `collect_batch` found and the UI hasn't taken yet.
+pub(crate) struct ResultQueue {
+    queue: VecDeque<VanityResult>,
+    high_water_mark: u32,
+    overflow: QueueOverflow,
+    /// Set when the match callback returned `false`, until `resume()`.
+    held: bool,
+    callback: Option<js_sys::Function>,
+    eviction_callback: Option<js_sys::Function>,
+    /// Every match `collect_batch` found, queued or not.
+    found: u64,
+    /// Matches dropped by the overflow policy.
+    dropped: u64,
+}
+
+impl Default for ResultQueue {
+    fn default() -> ResultQueue {
+        ResultQueue {
+            queue: VecDeque::new(),
+            high_water_mark: DEFAULT_HIGH_WATER_MARK,
+            overflow: QueueOverflow::default(),
+            held: false,
+            callback: None,
+            eviction_callback: None,
+            found: 0,
+            dropped: 0,
+        }
+    }
+}
+
+impl ResultQueue {
+    fn is_full(&self) -> bool {
+        self.queue.len() >= self.high_water_mark as usize
+    }
+
+    /// Queues `result` as the overflow policy allows, returning whether it
+    /// was queued and what was dropped: the oldest results, or `result`
+    /// itself.
+    fn push(&mut self, result: VanityResult) -> (bool, Vec<VanityResult>) {
+        let mut dropped = Vec::new();
+        match self.overflow {
+            QueueOverflow::RejectNew if self.is_full() => {
+                self.dropped += 1;
+                return (false, vec![result]);
+            }
+            QueueOverflow::EvictOldest => {
+                while self.is_full() {
+                    dropped.extend(self.queue.pop_front());
+                }
+            }
+            _ => {}
+        }
+        self.dropped += dropped.len() as u64;
+        self.queue.push_back(result);
+        (true, dropped)
+    }
+}
+
+#[wasm_bindgen]
+impl VanitySearcher {
+    /// Like `search_batch`, but keeps going after a match: every match in
+    /// the batch is passed to the match callback, if any, and queued for
+    /// `take_results`. Collection pauses, ending the batch early, when the
+    /// callback returns `false` (until `resume()`) or, by default, when the
+    /// queue reaches its high-water mark (until results are taken); a
+    /// paused searcher does no work, though a `stop_at` deadline that has
+    /// passed still stops it. With another `QueueOverflow` policy a
+    /// full queue drops results instead, through the eviction callback.
+    /// Returns the matches queued by this call. A throwing callback stops
+    /// the search as a throwing progress callback does.
+    #[wasm_bindgen]
+    pub fn collect_batch(&mut self, batch_size: u32) -> u32 {
+        let mut queued = 0;
+        let mut remaining = batch_size;
+        // A deadline passes whether or not collection is paused.
+        self.apply_deadline();
+        while remaining > 0 && !self.is_paused() {
+            let start_count = self.searcher.attempts();
+            let found = self.run_batch(remaining);
+            let covered = self.searcher.attempts() - start_count;
+            remaining = remaining.saturating_sub(covered.try_into().unwrap_or(u32::MAX));
+            let Some(found) = found else {
+                break;
+            };
+            let result = self.to_result(found);
+            self.notify_match(&result);
+            self.results.found += 1;
+            let (was_queued, dropped) = self.results.push(result);
+            queued += u32::from(was_queued);
+            for result in &dropped {
+                self.notify_eviction(result);
+            }
+            if self.is_paused() && self.results.is_full() {
+                log_debug!("result queue reached its high-water mark; pausing");
+            }
+        }
+        queued
+    }
+
+    /// Called with each `VanityResult` `collect_batch` queues and the
+    /// searcher's `SearchControl`; returning `false` pauses collection until
+    /// `resume()`. The callback must not call methods of this searcher,
+    /// which is busy collecting: those throw, and left uncaught that stops
+    /// the search with a `ReentrantCall` error. `control.stop()` stops it
+    /// cleanly once the callback returns.
+    #[wasm_bindgen]
+    pub fn set_match_callback(
+        &mut self,
+        #[wasm_bindgen(
+            unchecked_param_type = "(result: VanityResult, control: SearchControl) => boolean | void"
+        )]
+        callback: js_sys::Function,
+    ) {
+        self.results.callback = Some(callback);
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_match_callback(&mut self) {
+        self.results.callback = None;
+    }
+
+    /// Called with each `VanityResult` the overflow policy drops, before it
+    /// is gone, e.g. to persist it elsewhere, and the searcher's
+    /// `SearchControl`. Its return value is ignored; otherwise it behaves
+    /// as the match callback does.
+    #[wasm_bindgen]
+    pub fn set_eviction_callback(
+        &mut self,
+        #[wasm_bindgen(
+            unchecked_param_type = "(result: VanityResult, control: SearchControl) => void"
+        )]
+        callback: js_sys::Function,
+    ) {
+        self.results.eviction_callback = Some(callback);
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_eviction_callback(&mut self) {
+        self.results.eviction_callback = None;
+    }
+
+    /// What `collect_batch` does with a match while the queue is at its
+    /// high-water mark; "pause" by default.
+    #[wasm_bindgen]
+    pub fn set_queue_overflow(&mut self, policy: Ts<QueueOverflow>) -> Result<(), VanityJsError> {
+        self.results.overflow = policy
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid queue overflow: {e}")))?;
+        Ok(())
+    }
+
+    /// Queue length at which `collect_batch` pauses or, with another
+    /// overflow policy, drops results; 256 by default. Fails with
+    /// `InvalidOptions` for 0.
+    #[wasm_bindgen]
+    pub fn set_high_water_mark(&mut self, high_water_mark: u32) -> Result<(), VanityJsError> {
+        if high_water_mark == 0 {
+            return Err(
+                VanityError::InvalidOptions("high-water mark must be at least 1".into()).into(),
+            );
+        }
+        self.results.high_water_mark = high_water_mark;
+        Ok(())
+    }
+
+    /// Takes up to `max` queued results (all if omitted), oldest first.
+    /// Taking enough to drop below the high-water mark lifts that pause.
+    #[wasm_bindgen(unchecked_return_type = "VanityResult[]")]
+    pub fn take_results(&mut self, max: Option<u32>) -> js_sys::Array {
+        let queue = &mut self.results.queue;
+        let count = max.map_or(queue.len(), |max| queue.len().min(max as usize));
+        queue.drain(..count).map(JsValue::from).collect()
+    }
+
+    /// Results queued and not yet taken.
+    #[wasm_bindgen]
+    pub fn pending_results(&self) -> u32 {
+        self.results.queue.len() as u32
+    }
+
+    /// Every match `collect_batch` has found, whether it was queued, taken
+    /// or dropped since.
+    #[wasm_bindgen]
+    pub fn results_found(&self) -> u64 {
+        self.results.found
+    }
+
+    /// Matches the overflow policy has dropped.
+    #[wasm_bindgen]
+    pub fn results_dropped(&self) -> u64 {
+        self.results.dropped
+    }
+
+    /// Whether `collect_batch` is paused, by the match callback or a full
+    /// queue under the "pause" policy.
+    #[wasm_bindgen]
+    pub fn is_paused(&self) -> bool {
+        self.results.held
+            || (self.results.overflow == QueueOverflow::Pause && self.results.is_full())
+    }
+
+    /// Lifts a pause the match callback asked for. A full queue stays
+    /// paused until results are taken.
+    #[wasm_bindgen]
+    pub fn resume(&mut self) {
+        self.results.held = false;
+    }
+}
+
+impl VanitySearcher {
+    fn notify_match(&mut self, result: &VanityResult) {
+        let Some(callback) = self.results.callback.clone() else {
+            return;
+        };
+        if self.call_result_callback("match", &callback, result) == Some(false) {
+            log_debug!("match callback paused collection");
+            self.results.held = true;
+        }
+    }
+
+    fn notify_eviction(&mut self, result: &VanityResult) {
+        if let Some(callback) = self.results.eviction_callback.clone() {
+            self.call_result_callback("eviction", &callback, result);
+        }
+    }
+
+    /// Calls `callback` with `result` and the control, returning what it
+    /// returned as a bool, if it did. A throw stops the search.
+    fn call_result_callback(
+        &mut self,
+        name: &str,
+        callback: &js_sys::Function,
+        result: &VanityResult,
+    ) -> Option<bool> {
+        let control = self.control.clone().into();
+        let returned = match callback.call2(&JsValue::NULL, &result.clone().into(), &control) {
+            Ok(returned) => returned.as_bool(),
+            Err(error) => {
+                log_error!("{name} callback threw; stopping search");
+                self.callback_error = Some(crate::control::callback_error(name, error));
+                self.searcher.stop();
+                None
+            }
+        };
+        self.apply_stop_request();
+        returned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collector() -> VanitySearcher {
+        VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("A".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn take(searcher: &mut VanitySearcher, n: usize) {
+        searcher.results.queue.drain(..n);
+    }
+
+    #[test]
+    fn test_collection_pauses_at_the_high_water_mark() {
+        let mut searcher = collector();
+        searcher.set_high_water_mark(3).unwrap();
+        assert_eq!(searcher.collect_batch(100_000), 3);
+        assert!(searcher.is_paused());
+        assert_eq!(
+            (searcher.results_found(), searcher.results_dropped()),
+            (3, 0)
+        );
+        assert_eq!(searcher.pending_results(), 3);
+        // Paused: no work at all.
+        let attempts = searcher.attempts();
+        assert_eq!(searcher.collect_batch(100_000), 0);
+        assert_eq!(searcher.attempts(), attempts);
+        // A deadline still stops it.
+        searcher.stop_at(crate::clock::epoch_ms() - 1.0).unwrap();
+        assert_eq!(searcher.collect_batch(100_000), 0);
+        assert!(searcher.is_stopped() && searcher.deadline_reached());
+        searcher.clear_stop_at();
+        searcher.restart();
+
+        // Taking one lifts the pause for one more.
+        take(&mut searcher, 1);
+        assert!(!searcher.is_paused());
+        assert_eq!(searcher.collect_batch(100_000), 1);
+        assert!(searcher.is_paused());
+
+        // Nothing skipped or repeated: results continue the same sequence
+        // a plain search finds.
+        take(&mut searcher, 3);
+        searcher.collect_batch(100_000);
+        let mut plain = collector();
+        let expected: Vec<_> = (0..7)
+            .map(|_| plain.search_batch(100_000).unwrap().attempts())
+            .collect();
+        assert_eq!(queued_attempts(&searcher), expected[4..]);
+    }
+
+    fn queued_attempts(searcher: &VanitySearcher) -> Vec<u64> {
+        searcher
+            .results
+            .queue
+            .iter()
+            .map(|r| r.attempts())
+            .collect()
+    }
+
+    #[test]
+    fn test_full_queues_drop_results_under_either_policy() {
+        let mut plain = collector();
+        let expected: Vec<_> = (0..9)
+            .map(|_| plain.search_batch(100_000).unwrap().attempts())
+            .collect();
+        let batch = expected[7] as u32;
+
+        let mut newest = collector();
+        newest.set_high_water_mark(3).unwrap();
+        newest.results.overflow = QueueOverflow::EvictOldest;
+        assert_eq!(newest.collect_batch(batch), 8);
+        assert!(!newest.is_paused());
+        assert_eq!(queued_attempts(&newest), expected[5..8]);
+        assert_eq!((newest.results_found(), newest.results_dropped()), (8, 5));
+
+        let mut oldest = collector();
+        oldest.set_high_water_mark(3).unwrap();
+        oldest.results.overflow = QueueOverflow::RejectNew;
+        assert_eq!(oldest.collect_batch(batch), 3);
+        assert!(!oldest.is_paused());
+        assert_eq!(oldest.attempts(), expected[7]);
+        assert_eq!(queued_attempts(&oldest), expected[..3]);
+        assert_eq!((oldest.results_found(), oldest.results_dropped()), (8, 5));
+
+        // Taking makes room again.
+        take(&mut oldest, 3);
+        oldest.collect_batch((expected[8] - expected[7]) as u32);
+        assert_eq!(queued_attempts(&oldest), expected[8..]);
+        assert_eq!(oldest.results_found(), 9);
+    }
+
+    #[test]
+    fn test_collect_batch_respects_the_batch_size() {
+        let mut searcher = collector();
+        let queued = searcher.collect_batch(1_000);
+        assert_eq!(searcher.attempts(), 1_000);
+        assert_eq!(searcher.pending_results(), queued);
+        assert!(queued > 1);
+        searcher.results.held = true;
+        assert_eq!(searcher.collect_batch(1_000), 0);
+        searcher.resume();
+        searcher.collect_batch(1_000);
+        assert_eq!(searcher.attempts(), 2_000);
+    }
+
+    #[test]
+    fn test_collect_batch_budget_covers_skipped_blocks() {
+        let mut searcher = collector();
+        let mut seen = vanity_core::SeenFilter::new(10, 1e-9).unwrap();
+        seen.insert_range(0, vanity_core::SEEN_BLOCK_SIZE);
+        searcher.searcher.set_seen_filter(Some(seen));
+        assert_eq!(searcher.collect_batch(10), 0);
+        assert_eq!((searcher.attempts(), searcher.skipped()), (10, 10));
+        // The rest of the block, then a search past it.
+        let rest = vanity_core::SEEN_BLOCK_SIZE as u32 - 10;
+        assert!(searcher.collect_batch(rest + 1_000) > 0);
+        assert_eq!(searcher.attempts(), vanity_core::SEEN_BLOCK_SIZE + 1_000);
+        assert_eq!(searcher.skipped(), vanity_core::SEEN_BLOCK_SIZE);
+    }
+}
@@ -0,0 +1,80 @@
+//! A persistable record of counter blocks already searched.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+
+/// A Bloom filter of searched counter blocks (2^20 counters each, see
+/// `SEEN_BLOCK_SIZE`) to save, merge across devices and hand to
+/// `VanitySearcher.set_seen_filter`, which then skips the blocks it holds.
+/// `maybe_covered` never misses an inserted block, but says true for
+/// others at about the rate the filter was sized for once it holds the
+/// expected number of blocks, and more often past that; a searcher skips
+/// such false positives unsearched. Only whole blocks are recorded. Keep
+/// one filter per set of keys and pattern.
+#[wasm_bindgen]
+pub struct SeenFilter {
+    inner: vanity_core::SeenFilter,
+}
+
+#[wasm_bindgen]
+impl SeenFilter {
+    /// A filter for about `expected_blocks` blocks at
+    /// `false_positive_rate`, e.g. 1e-6. Throws "InvalidOptions" unless
+    /// `expected_blocks > 0` and `0 < false_positive_rate < 1`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        expected_blocks: u64,
+        false_positive_rate: f64,
+    ) -> Result<SeenFilter, VanityJsError> {
+        Ok(SeenFilter {
+            inner: vanity_core::SeenFilter::new(expected_blocks, false_positive_rate)?,
+        })
+    }
+
+    /// Reads a filter saved with `serialize`. Throws "InvalidSeenFilter" if
+    /// `bytes` aren't one.
+    #[wasm_bindgen]
+    pub fn deserialize(bytes: &[u8]) -> Result<SeenFilter, VanityJsError> {
+        Ok(SeenFilter {
+            inner: vanity_core::SeenFilter::from_bytes(bytes)?,
+        })
+    }
+
+    /// Records the blocks lying wholly inside `len` counters from `start`,
+    /// e.g. a finished work range, and returns how many there are.
+    #[wasm_bindgen]
+    pub fn insert_range(&mut self, start: u64, len: u64) -> u64 {
+        self.inner.insert_range(start, len)
+    }
+
+    /// Whether `counter`'s block was recorded, or is a false positive.
+    #[wasm_bindgen]
+    pub fn maybe_covered(&self, counter: u64) -> bool {
+        self.inner.maybe_covered(counter)
+    }
+
+    /// Adds the blocks `other` holds. Throws "InvalidSeenFilter" unless
+    /// both were created with the same sizes.
+    #[wasm_bindgen]
+    pub fn merge(&mut self, other: &SeenFilter) -> Result<(), VanityJsError> {
+        Ok(self.inner.merge(&other.inner)?)
+    }
+
+    #[wasm_bindgen]
+    pub fn serialize(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// The current chance of a false positive, estimated from the bits set.
+    #[wasm_bindgen(getter)]
+    pub fn false_positive_rate(&self) -> f64 {
+        self.inner.false_positive_rate()
+    }
+}
+
+impl SeenFilter {
+    pub(crate) fn inner(&self) -> &vanity_core::SeenFilter {
+        &self.inner
+    }
+}
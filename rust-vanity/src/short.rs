@@ -0,0 +1,107 @@
+//! Shortest-seed-first search.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    short_seed_estimate, KeyRole, MatchType, Pubkey, ShortMatch, ShortSeedEstimate,
+    ShortSeedSearcher,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// Tries every 1-character seed, then every 2-character seed, and so on,
+/// so the first match has the shortest seed that works, for seeds typed by
+/// hand. Use `estimate_short_seed` first: for hard patterns every short
+/// seed will be tried without a match.
+#[wasm_bindgen]
+pub struct VanityShortSeedSearcher {
+    searcher: ShortSeedSearcher,
+}
+
+#[wasm_bindgen]
+impl VanityShortSeedSearcher {
+    /// `count_offset` is an index into the length-ordered enumeration; 0
+    /// starts at the 1-character seeds.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: Option<u64>,
+    ) -> Result<VanityShortSeedSearcher, VanityJsError> {
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let searcher = ShortSeedSearcher::new(
+            base_pubkey.as_bytes(),
+            owner_pubkey.as_bytes(),
+            MatchType::new(prefix, suffix, case_insensitive),
+            case_insensitive,
+            count_offset.unwrap_or(0),
+        )?;
+        if let Some(warning) = searcher.estimate().warning {
+            log_warn!("{warning}");
+        }
+        Ok(VanityShortSeedSearcher { searcher })
+    }
+
+    /// Tries up to `batch_size` more seeds and returns the first match, if
+    /// any.
+    #[wasm_bindgen]
+    pub fn search_batch(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Option<Ts<ShortMatch>>, VanityJsError> {
+        let Some(found) = self.searcher.search_batch(batch_size) else {
+            return Ok(None);
+        };
+        log_info!(
+            "match {} with {}-character seed {:?} after {} attempts",
+            found.address,
+            found.seed_len,
+            found.seed,
+            found.attempts
+        );
+        Ok(Some(found.into_ts().map_err(internal)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    /// Length of the seeds being tried, or `undefined` once the counter
+    /// space is used up.
+    #[wasm_bindgen(getter)]
+    pub fn current_seed_len(&self) -> Option<u32> {
+        self.searcher.current_seed_len()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+
+    #[wasm_bindgen]
+    pub fn estimate(&self) -> Result<Ts<ShortSeedEstimate>, VanityJsError> {
+        Ok(self.searcher.estimate().into_ts().map_err(internal)?)
+    }
+}
+
+/// Expected attempts for the pattern and the seed length to expect with
+/// `VanityShortSeedSearcher`, with a warning if every short seed will
+/// probably be tried without a match.
+#[wasm_bindgen]
+pub fn estimate_short_seed(
+    prefix: Option<String>,
+    suffix: Option<String>,
+    case_insensitive: bool,
+) -> Result<Ts<ShortSeedEstimate>, VanityJsError> {
+    let probability =
+        vanity_core::match_probability(prefix.as_deref(), suffix.as_deref(), case_insensitive);
+    Ok(short_seed_estimate(probability)
+        .into_ts()
+        .map_err(internal)?)
+}
@@ -0,0 +1,172 @@
+//! Squads multisig vault grinding.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{MatchType, Pubkey, SquadsMatch, SquadsSearcher};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::{internal, master_secret_or_random};
+
+/// Grinds create keys until the vault of the Squads multisig they would
+/// create matches the pattern. The result's `create_key_secret` must sign
+/// the multisig creation, so treat it like any other secret key.
+#[wasm_bindgen]
+pub struct VanitySquadsSearcher {
+    searcher: SquadsSearcher,
+    master_secret: [u8; 32],
+}
+
+#[wasm_bindgen]
+impl VanitySquadsSearcher {
+    /// `program_id` defaults to Squads v4 and `vault_index` to 0.
+    /// `master_secret` (32 bytes) seeds every create key and is random if
+    /// omitted; workers splitting a search by `count_offset` must share it.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        program_id: Option<String>,
+        vault_index: Option<u8>,
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: Option<u64>,
+        master_secret: Option<Vec<u8>>,
+    ) -> Result<VanitySquadsSearcher, VanityJsError> {
+        let program_id = Pubkey::from_base58(
+            program_id
+                .as_deref()
+                .unwrap_or(vanity_core::SQUADS_PROGRAM_ID),
+        )?;
+        let master_secret = master_secret_or_random(master_secret.as_deref())?;
+        let searcher = SquadsSearcher::new(
+            program_id.as_bytes(),
+            vault_index.unwrap_or(0),
+            &master_secret,
+            MatchType::new(prefix, suffix, case_insensitive),
+            case_insensitive,
+            count_offset.unwrap_or(0),
+        )?;
+        Ok(VanitySquadsSearcher {
+            searcher,
+            master_secret,
+        })
+    }
+
+    /// Tries up to `batch_size` more create keys and returns the first
+    /// match, if any.
+    #[wasm_bindgen]
+    pub fn search_batch(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Option<Ts<SquadsMatch>>, VanityJsError> {
+        let Some(found) = self.searcher.search_batch(batch_size) else {
+            return Ok(None);
+        };
+        log_info!(
+            "vault {} (multisig {}) after {} attempts",
+            found.vault,
+            found.multisig,
+            found.attempts
+        );
+        Ok(Some(found.into_ts().map_err(internal)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn vault_index(&self) -> u8 {
+        self.searcher.vault_index()
+    }
+
+    /// The secret every create key is derived from, to hand to other
+    /// workers.
+    #[wasm_bindgen(getter)]
+    pub fn master_secret(&self) -> Vec<u8> {
+        self.master_secret.to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        self.searcher.expected_attempts()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_a_vault_with_a_shared_secret() {
+        let new = |offset| {
+            VanitySquadsSearcher::new(
+                None,
+                Some(2),
+                Some("A".into()),
+                None,
+                false,
+                Some(offset),
+                Some(vec![9; 32]),
+            )
+            .unwrap()
+        };
+        let mut searcher = new(0);
+        let found = loop {
+            if let Some(found) = searcher.searcher.search_batch(100) {
+                break found;
+            }
+        };
+        assert!(found.vault.starts_with('A'));
+        assert_eq!(searcher.vault_index(), 2);
+        assert_eq!(searcher.master_secret(), vec![9; 32]);
+        // A worker with the same secret derives the same keys.
+        let mut other = new(found.counter);
+        let again = other.searcher.search_batch(1).unwrap();
+        assert_eq!(again.create_key_secret, found.create_key_secret);
+        assert_eq!(again.vault, found.vault);
+    }
+
+    #[test]
+    fn test_rejects_bad_inputs() {
+        let pattern = || Some("A".to_string());
+        let kind = |result: Result<VanitySquadsSearcher, VanityJsError>| {
+            result.err().map(|error| error.0.kind())
+        };
+        assert_eq!(
+            kind(VanitySquadsSearcher::new(
+                None,
+                None,
+                pattern(),
+                None,
+                false,
+                None,
+                Some(vec![1; 31])
+            )),
+            Some(vanity_core::ErrorKind::InvalidOptions)
+        );
+        assert_eq!(
+            kind(VanitySquadsSearcher::new(
+                Some("0OIl".into()),
+                None,
+                pattern(),
+                None,
+                false,
+                None,
+                None
+            )),
+            Some(vanity_core::ErrorKind::InvalidBase58)
+        );
+        assert_eq!(
+            kind(VanitySquadsSearcher::new(
+                None, None, None, None, false, None, None
+            )),
+            Some(vanity_core::ErrorKind::EmptyPattern)
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Deterministic mode for tests: while it is on, nothing the wrapper does
+//! depends on real time, and only master secrets on randomness.
+
+use std::cell::Cell;
+
+use wasm_bindgen::prelude::*;
+
+use crate::clock;
+
+/// Counter used in place of a random one in test mode.
+pub(crate) const PINNED_COUNTER: u64 = 0;
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Makes results reproducible, for tests: the clock behind `rate`,
+/// `progress()`, `elapsed_ms` and `benchmark_hash_rate` starts at 0 and
+/// advances exactly 1 ms per reading. Three values that would be random
+/// are pinned: the start of `sample_addresses` and the key of
+/// `permute_counters` to 0, and the tag of `namespace_seeds` to all "1"s.
+/// Master secrets stay random; pass `master_secret` for reproducible keys.
+/// Applies to the calling thread only; `test_mode(false)` restores normal
+/// behaviour.
+#[wasm_bindgen]
+pub fn test_mode(enabled: bool) {
+    ENABLED.set(enabled);
+    clock::set_stepping(enabled);
+}
+
+pub(crate) fn enabled() -> bool {
+    ENABLED.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VanitySearcher;
+
+    fn searcher() -> VanitySearcher {
+        VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_timings_are_reproducible() {
+        let outcomes: Vec<_> = (0..2)
+            .map(|_| {
+                test_mode(true);
+                let mut vanity_searcher = searcher();
+                let outcome = vanity_searcher.batch_outcome(1_000);
+                (outcome, vanity_searcher.rate())
+            })
+            .collect();
+        assert_eq!(outcomes[0], outcomes[1]);
+        let (outcome, rate) = &outcomes[0];
+        // Read at the start and end of the call, and around its one chunk.
+        assert_eq!(outcome.elapsed_ms, 3.0);
+        assert_eq!(*rate, Some(1_000_000.0));
+        test_mode(false);
+        assert!(!enabled());
+    }
+}
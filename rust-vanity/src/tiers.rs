@@ -0,0 +1,96 @@
+//! Fallback pattern tiers.
+
+use tsify::{Ts, Tsify};
+use vanity_core::{KeyRole, PatternTier, Pubkey, TieredMatch, TieredSearcher, VanityError};
+use wasm_bindgen::prelude::*;
+
+use crate::error::VanityJsError;
+use crate::internal;
+
+/// Searches for the first tier's pattern, settling for the next one once
+/// `after_attempts` attempts have gone by without a match, and so on down
+/// the list: e.g. prefix "SoLaNa" from the start, "SoLan" after 50M
+/// attempts, "SoLa" after 150M. Each match says which tier it satisfied.
+#[wasm_bindgen]
+pub struct VanityTieredSearcher {
+    searcher: TieredSearcher,
+}
+
+#[wasm_bindgen]
+impl VanityTieredSearcher {
+    /// `tiers` go from most to least wanted; the first has an
+    /// `after_attempts` of 0 and none comes before the one above it.
+    /// Patterns are checked as `format_pattern_list` checks entries, with an
+    /// empty label standing for the entry's directives.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        base_pubkey: &[u8],
+        owner_pubkey: &[u8],
+        #[wasm_bindgen(unchecked_param_type = "PatternTier[]")] tiers: JsValue,
+        count_offset: Option<u64>,
+    ) -> Result<VanityTieredSearcher, VanityJsError> {
+        let tiers: Vec<PatternTier> = serde_wasm_bindgen::from_value(tiers)
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid pattern tiers: {e}")))?;
+        let base_pubkey = Pubkey::for_role(base_pubkey, KeyRole::Base)?;
+        let owner_pubkey = Pubkey::for_role(owner_pubkey, KeyRole::Owner)?;
+        let searcher = TieredSearcher::new(
+            base_pubkey.as_bytes(),
+            owner_pubkey.as_bytes(),
+            tiers,
+            count_offset.unwrap_or(0),
+        )?;
+        Ok(VanityTieredSearcher { searcher })
+    }
+
+    /// Tries up to `batch_size` more counters and returns the first match,
+    /// if any.
+    #[wasm_bindgen]
+    pub fn search_batch(
+        &mut self,
+        batch_size: u32,
+    ) -> Result<Option<Ts<TieredMatch>>, VanityJsError> {
+        let active = self.searcher.active_tiers();
+        let found = self.searcher.search_batch(batch_size);
+        if self.searcher.active_tiers() > active {
+            log_info!(
+                "tiered search: accepting {} tiers after {} attempts",
+                self.searcher.active_tiers(),
+                self.searcher.attempts()
+            );
+        }
+        let Some(found) = found else {
+            return Ok(None);
+        };
+        log_info!(
+            "match {} for tier {} ({}) after {} attempts",
+            found.address,
+            found.tier,
+            found.label,
+            found.attempts
+        );
+        Ok(Some(found.into_ts().map_err(internal)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    /// Tiers accepted so far, at least 1.
+    #[wasm_bindgen(getter)]
+    pub fn active_tiers(&self) -> u32 {
+        self.searcher.active_tiers() as u32
+    }
+
+    /// Attempt count at which the next tier is accepted, or `undefined`
+    /// once every tier is.
+    #[wasm_bindgen(getter)]
+    pub fn next_tier_at(&self) -> Option<u64> {
+        self.searcher.next_tier_at()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+}
@@ -0,0 +1,267 @@
+//! Token-2022 mint grinding.
+
+use serde::Deserialize;
+use tsify::{Ts, Tsify};
+use vanity_core::{
+    token_metadata_len, MatchType, MintInstructionParams, MintLayout, Token2022Match,
+    Token2022MintSearcher, VanityError,
+};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroize;
+
+use crate::error::VanityJsError;
+use crate::{internal, master_secret_or_random};
+
+/// Metadata to be stored in the mint, sized for its rent.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct TokenMetadataOptions {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    #[tsify(optional)]
+    pub additional_metadata: Vec<(String, String)>,
+}
+
+/// Options for `VanityToken2022Searcher`, as a plain JS object.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[serde(default)]
+pub struct Token2022Options {
+    #[tsify(optional)]
+    pub prefix: Option<String>,
+    #[tsify(optional)]
+    pub suffix: Option<String>,
+    #[tsify(optional)]
+    pub case_insensitive: bool,
+    /// Bitset of the mint's extensions, from `token2022_extensions`.
+    #[tsify(optional)]
+    pub extensions: u32,
+    /// Needs the metadata pointer extension.
+    #[tsify(optional)]
+    pub token_metadata: Option<TokenMetadataOptions>,
+    #[tsify(optional)]
+    pub count_offset: u64,
+    /// 32 bytes every mint key is derived from; random if omitted. Workers
+    /// splitting a search by `count_offset` must share it.
+    #[tsify(optional)]
+    pub master_secret: Option<Vec<u8>>,
+}
+
+/// The mint extensions the searcher can size, with the bit each stands for
+/// in `Token2022Options.extensions`.
+#[wasm_bindgen(unchecked_return_type = "MintExtensionInfo[]")]
+pub fn token2022_extensions() -> Result<js_sys::Array, VanityJsError> {
+    vanity_core::mint_extensions()
+        .iter()
+        .map(|info| Ok(JsValue::from(info.into_ts().map_err(internal)?)))
+        .collect()
+}
+
+/// A mint found by `VanityToken2022Searcher`. The secret is wiped from wasm
+/// memory once the result is freed.
+#[wasm_bindgen]
+pub struct Token2022MintResult {
+    found: Token2022Match,
+}
+
+#[wasm_bindgen]
+impl Token2022MintResult {
+    #[wasm_bindgen(getter)]
+    pub fn mint(&self) -> String {
+        self.found.mint.clone()
+    }
+
+    /// Base58 of the mint's 64-byte keypair; it signs the `createAccount`.
+    #[wasm_bindgen(getter)]
+    pub fn mint_secret(&self) -> String {
+        self.found.mint_secret.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn extensions(&self) -> u32 {
+        self.found.extensions
+    }
+
+    /// Bytes to create the mint account with.
+    #[wasm_bindgen(getter)]
+    pub fn space(&self) -> u64 {
+        self.found.space
+    }
+
+    /// Bytes the account grows to once group data and metadata are added.
+    #[wasm_bindgen(getter)]
+    pub fn rent_space(&self) -> u64 {
+        self.found.rent_space
+    }
+
+    /// Rent exemption for `rent_space`, at the default rent.
+    #[wasm_bindgen(getter)]
+    pub fn rent_exempt_lamports(&self) -> u64 {
+        self.found.rent_exempt_lamports
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.found.attempts
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn counter(&self) -> u64 {
+        self.found.counter
+    }
+
+    /// Parameters for `SystemProgram.createAccount` and
+    /// `createInitializeMint2Instruction`, keys in base58. Each extension's
+    /// own initialize instruction goes between the two.
+    #[wasm_bindgen]
+    pub fn instruction_params(
+        &self,
+        payer: &str,
+        mint_authority: &str,
+        decimals: u8,
+        freeze_authority: Option<String>,
+    ) -> Result<Ts<MintInstructionParams>, VanityJsError> {
+        let params = self.found.instruction_params(
+            payer,
+            mint_authority,
+            freeze_authority.as_deref(),
+            decimals,
+        )?;
+        Ok(params.into_ts().map_err(internal)?)
+    }
+}
+
+impl Drop for Token2022MintResult {
+    fn drop(&mut self) {
+        self.found.mint_secret.zeroize();
+    }
+}
+
+/// Grinds mint keypairs until the mint address matches the pattern, and
+/// sizes the mint for the extensions it will have.
+#[wasm_bindgen]
+pub struct VanityToken2022Searcher {
+    searcher: Token2022MintSearcher,
+    master_secret: [u8; 32],
+}
+
+#[wasm_bindgen]
+impl VanityToken2022Searcher {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Ts<Token2022Options>) -> Result<VanityToken2022Searcher, VanityJsError> {
+        let options = options
+            .to_rust()
+            .map_err(|e| VanityError::InvalidOptions(format!("invalid token-2022 options: {e}")))?;
+        VanityToken2022Searcher::from_options(options)
+    }
+
+    /// Tries up to `batch_size` more mints and returns the first match, if
+    /// any.
+    #[wasm_bindgen]
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<Token2022MintResult> {
+        let found = self.searcher.search_batch(batch_size)?;
+        log_info!("mint {} after {} attempts", found.mint, found.attempts);
+        Some(Token2022MintResult { found })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attempts(&self) -> u64 {
+        self.searcher.attempts()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_exhausted(&self) -> bool {
+        self.searcher.is_exhausted()
+    }
+
+    /// The secret every mint key is derived from, to hand to other workers.
+    #[wasm_bindgen(getter)]
+    pub fn master_secret(&self) -> Vec<u8> {
+        self.master_secret.to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn expected_attempts(&self) -> f64 {
+        self.searcher.expected_attempts()
+    }
+}
+
+impl VanityToken2022Searcher {
+    fn from_options(options: Token2022Options) -> Result<VanityToken2022Searcher, VanityJsError> {
+        let metadata_len = options.token_metadata.as_ref().map(|metadata| {
+            token_metadata_len(
+                &metadata.name,
+                &metadata.symbol,
+                &metadata.uri,
+                &metadata.additional_metadata,
+            )
+        });
+        let layout = MintLayout::new(options.extensions, metadata_len)?;
+        let master_secret = master_secret_or_random(options.master_secret.as_deref())?;
+        let case_insensitive = options.case_insensitive;
+        let searcher = Token2022MintSearcher::new(
+            &master_secret,
+            MatchType::new(options.prefix, options.suffix, case_insensitive),
+            case_insensitive,
+            layout,
+            options.count_offset,
+        )?;
+        Ok(VanityToken2022Searcher {
+            searcher,
+            master_secret,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vanity_core::MintExtension;
+
+    #[test]
+    fn test_options_size_the_mint() {
+        let mut searcher = VanityToken2022Searcher::from_options(Token2022Options {
+            prefix: Some("A".into()),
+            extensions: MintExtension::MetadataPointer.bit(),
+            token_metadata: Some(TokenMetadataOptions {
+                name: "Vanity".into(),
+                symbol: "VNTY".into(),
+                uri: "https://example.com/v.json".into(),
+                additional_metadata: Vec::new(),
+            }),
+            master_secret: Some(vec![4; 32]),
+            ..Token2022Options::default()
+        })
+        .unwrap();
+        assert_eq!(searcher.master_secret(), vec![4; 32]);
+        let found = loop {
+            if let Some(found) = searcher.search_batch(100) {
+                break found;
+            }
+        };
+        assert!(found.mint().starts_with('A'));
+        assert_eq!(found.space(), 234);
+        assert_eq!(found.rent_space(), 234 + 4 + 116);
+    }
+
+    #[test]
+    fn test_rejects_bad_options() {
+        let kind = |options| {
+            VanityToken2022Searcher::from_options(options)
+                .err()
+                .map(|error| error.0.kind())
+        };
+        assert_eq!(
+            kind(Token2022Options::default()),
+            Some(vanity_core::ErrorKind::EmptyPattern)
+        );
+        assert_eq!(
+            kind(Token2022Options {
+                prefix: Some("A".into()),
+                token_metadata: Some(TokenMetadataOptions::default()),
+                ..Token2022Options::default()
+            }),
+            Some(vanity_core::ErrorKind::InvalidOptions)
+        );
+    }
+}
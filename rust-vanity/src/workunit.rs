@@ -0,0 +1,103 @@
+//! Work units for splitting a search across machines by hand.
+
+use serde::Deserialize;
+use tsify::{Ts, Tsify};
+use vanity_core::{CollatedWork, VanityError, WorkConfig, WorkRecord, WorkUnit};
+use wasm_bindgen::prelude::*;
+
+use crate::clock::now_ms;
+use crate::error::VanityJsError;
+use crate::{internal, MAX_SAFE_INTEGER};
+
+/// Options for `create_work_units`: the search, where the units start and
+/// who they are for.
+#[derive(Debug, Deserialize, Tsify)]
+pub struct WorkUnitOptions {
+    #[serde(flatten)]
+    pub config: WorkConfig,
+    /// First counter of the first unit; 0 if omitted.
+    #[serde(default)]
+    #[tsify(optional)]
+    pub start: u64,
+    #[serde(default)]
+    #[tsify(optional)]
+    pub issued_to: Option<String>,
+}
+
+/// `count` work units of `unit_size` counters each, back to back from
+/// `options.start`, stamped with the time of issue. Each is plain JSON
+/// holding the whole configuration and its hash, so whoever receives one
+/// needs nothing else to run it. Throws "InvalidOptions" for an empty size
+/// or count, or units ending past 2^53, and as the `VanitySearcher`
+/// constructor does for bad keys or patterns.
+#[wasm_bindgen(unchecked_return_type = "WorkUnit[]")]
+pub fn create_work_units(
+    options: Ts<WorkUnitOptions>,
+    unit_size: u64,
+    count: u32,
+) -> Result<js_sys::Array, VanityJsError> {
+    let options = options
+        .to_rust()
+        .map_err(|e| VanityError::InvalidOptions(format!("invalid work unit options: {e}")))?;
+    let end = unit_size
+        .checked_mul(count as u64)
+        .and_then(|total| options.start.checked_add(total));
+    if end.is_some_and(|end| end > MAX_SAFE_INTEGER + 1) {
+        return Err(VanityError::InvalidOptions(
+            "work units end past Number.MAX_SAFE_INTEGER + 1".into(),
+        )
+        .into());
+    }
+    let units = vanity_core::create_work_units(
+        &options.config,
+        options.start,
+        unit_size,
+        count,
+        options.issued_to.as_deref(),
+        now_ms(),
+    )?;
+    units
+        .iter()
+        .map(|unit| Ok(JsValue::from(unit.into_ts().map_err(internal)?)))
+        .collect()
+}
+
+/// Searches exactly the unit's range, blocking until done, and returns the
+/// completion record to send back: every match with its counter, the
+/// attempts and a digest of it all. Throws "InvalidWorkUnit" if the unit's
+/// version or config hash doesn't fit its configuration.
+#[wasm_bindgen]
+pub fn run_work_unit(unit: Ts<WorkUnit>) -> Result<Ts<WorkRecord>, VanityJsError> {
+    let unit = unit
+        .to_rust()
+        .map_err(|e| VanityError::InvalidWorkUnit(e.to_string()))?;
+    let record = vanity_core::run_work_unit(&unit)?;
+    log_info!(
+        "work unit {} done: {} matches in {} attempts",
+        record.index,
+        record.matches.len(),
+        record.attempts
+    );
+    Ok(record.into_ts().map_err(internal)?)
+}
+
+/// Checks completion records against the first one's search, re-deriving
+/// every match they claim, and merges the coverage and matches of those
+/// that pass. Rejected records are listed with the reason rather than
+/// thrown. Throws "InvalidWorkUnit" if `records` is empty or malformed.
+#[wasm_bindgen]
+pub fn collate_results(
+    #[wasm_bindgen(unchecked_param_type = "WorkRecord[]")] records: JsValue,
+) -> Result<Ts<CollatedWork>, VanityJsError> {
+    let records: Vec<WorkRecord> = serde_wasm_bindgen::from_value(records)
+        .map_err(|e| VanityError::InvalidWorkUnit(format!("records: {e}")))?;
+    let collated = vanity_core::collate_results(&records)?;
+    if !collated.rejected.is_empty() {
+        log_warn!(
+            "{} of {} work records rejected",
+            collated.rejected.len(),
+            records.len()
+        );
+    }
+    Ok(collated.into_ts().map_err(internal)?)
+}
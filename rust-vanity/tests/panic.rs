@@ -0,0 +1,62 @@
+//! Kept apart from `web.rs`: the deliberate trap leaves the module instance
+//! unusable for any test that would run after it.
+#![cfg(all(target_arch = "wasm32", feature = "console_error_panic_hook"))]
+
+use rust_vanity::{set_error_callback, start};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+/// Replaces `console.error` with a recorder and returns the recorded calls.
+fn capture_console_error() -> js_sys::Array {
+    let calls = js_sys::Array::new();
+    let record = js_sys::Function::new_with_args(
+        "calls",
+        "return function(...args) { calls.push(args.join(' ')); };",
+    )
+    .call1(&JsValue::NULL, &calls)
+    .unwrap();
+    let console = js_sys::Reflect::get(&js_sys::global(), &"console".into()).unwrap();
+    js_sys::Reflect::set(&console, &"error".into(), &record).unwrap();
+    calls
+}
+
+#[wasm_bindgen_test]
+fn panic_after_start_reports_readable_console_error() {
+    // Drop the runner's own panic hook so only the crate's is in place.
+    let _ = std::panic::take_hook();
+    start();
+    let calls = capture_console_error();
+    let reports = js_sys::Array::new();
+    let record = js_sys::Function::new_with_args("reports", "return r => { reports.push(r); };")
+        .call1(&JsValue::NULL, &reports)
+        .unwrap();
+    set_error_callback(Some(record.unchecked_into()));
+
+    // Panicking inside a JS-invoked closure turns the trap into an exception
+    // the test can catch after the hook has logged.
+    let panicking = Closure::<dyn Fn()>::new(|| panic!("deliberate test panic"));
+    let result = panicking
+        .as_ref()
+        .unchecked_ref::<js_sys::Function>()
+        .call0(&JsValue::NULL);
+    assert!(result.is_err());
+
+    let logged: Vec<String> = calls.iter().filter_map(|c| c.as_string()).collect();
+    assert!(
+        logged
+            .iter()
+            .any(|line| line.contains("deliberate test panic")),
+        "{logged:?}"
+    );
+
+    // The error callback saw it too.
+    assert_eq!(reports.length(), 1);
+    let field = |key: &str| js_sys::Reflect::get(&reports.get(0), &key.into()).unwrap();
+    assert_eq!(field("source").as_string().as_deref(), Some("panic"));
+    assert!(field("message")
+        .as_string()
+        .unwrap()
+        .contains("deliberate test panic"));
+    assert_eq!(field("searcher_usable").as_bool(), Some(false));
+}
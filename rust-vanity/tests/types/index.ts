@@ -0,0 +1,45 @@
+// Type-level checks of the generated declarations. Build the package into
+// rust-vanity/pkg first (`wasm-pack build --target web` in rust-vanity), then
+// run `npm run typecheck:wasm`. Nothing here is executed.
+import {
+  VanitySearcher,
+  plan_search,
+  type Difficulty,
+  type ProgressSnapshot,
+  type SearchOptions,
+  type SearchPlan,
+  type VanityResultJson,
+} from "../../pkg/rust_vanity.js";
+
+const options: SearchOptions = { prefix: "Jup", case_insensitive: true };
+// @ts-expect-error unknown option
+const _badOptions: SearchOptions = { prefx: "Jup" };
+
+const plan: SearchPlan = plan_search(options, 250);
+const batchSize: number = plan.batch_size;
+const wallMs: number | undefined = plan.expected_wall_ms;
+const difficulty: Difficulty = plan.difficulty;
+// @ts-expect-error not a difficulty class
+const _badDifficulty: Difficulty = "weeks";
+
+const searcher = new VanitySearcher(
+  new Uint8Array(32),
+  new Uint8Array(32),
+  "Jup",
+  undefined,
+  false,
+  0n,
+);
+const progress: ProgressSnapshot = searcher.progress();
+// @ts-expect-error rates are undefined until the first batch
+const _rate: number = progress.instant_rate;
+
+searcher.set_progress_callback((snapshot) => snapshot.probability_found < 0.99, 10_000);
+// @ts-expect-error the callback receives a snapshot, not a number
+searcher.set_progress_callback((attempts: number) => attempts > 0, 10_000);
+
+const found = searcher.search_batch(batchSize);
+const json: VanityResultJson | undefined = found?.toJSON();
+const address: string | undefined = json?.address;
+
+export { address, difficulty, wallMs };
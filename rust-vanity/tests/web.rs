@@ -0,0 +1,2971 @@
+//! Tests of the wasm-bindgen surface; run with
+//! `cargo test --target wasm32-unknown-unknown` (see `.cargo/config.toml`),
+//! or in headless Chrome with `npm run test:wasm:browser`.
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rust_vanity::*;
+use tsify::Ts;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::*;
+
+#[wasm_bindgen_test]
+fn start_marks_module_initialized() {
+    // The test runner instantiates the module without running start
+    // functions, so call it the way the generated glue would.
+    start();
+    assert!(is_initialized());
+    start();
+    init_panic_hook();
+    assert!(is_initialized());
+}
+
+fn error_message(error: &JsValue) -> String {
+    js_sys::Error::from(error.clone()).message().into()
+}
+
+/// The `code` and `kind` a `VanityError` carries to JS.
+fn error_kind(error: &JsValue) -> (u16, String) {
+    let get = |key: &str| js_sys::Reflect::get(error, &key.into()).unwrap();
+    (
+        get("code").as_f64().unwrap() as u16,
+        get("kind").as_string().unwrap(),
+    )
+}
+
+#[wasm_bindgen_test]
+fn key_length_and_pattern_errors_carry_codes() {
+    let kind =
+        |result: Result<VanitySearcher, VanityJsError>| error_kind(&result.err().unwrap().into());
+    assert_eq!(
+        kind(VanitySearcher::new(
+            &[1; 3],
+            &[4; 32],
+            Some("A".into()),
+            None,
+            false,
+            0,
+            None,
+            None
+        )),
+        (4, "BadBaseLength".into())
+    );
+    assert_eq!(
+        kind(VanitySearcher::new(
+            &[1; 32],
+            &[4; 3],
+            Some("A".into()),
+            None,
+            false,
+            0,
+            None,
+            None
+        )),
+        (5, "BadOwnerLength".into())
+    );
+    assert_eq!(
+        kind(VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("0".into()),
+            None,
+            false,
+            0,
+            None,
+            None
+        )),
+        (2, "InvalidPatternChar".into())
+    );
+    assert_eq!(
+        error_kind(&set_log_level("loud").unwrap_err().into()).1,
+        "InvalidOptions"
+    );
+}
+
+#[wasm_bindgen_test]
+fn empty_pattern_is_rejected_unless_allowed() {
+    let error = VanitySearcher::new(&[1; 32], &[4; 32], None, None, false, 0, None, None)
+        .err()
+        .unwrap();
+    let error = JsValue::from(error);
+    assert_eq!(error_kind(&error), (1, "EmptyPattern".into()));
+    let message = error_message(&error);
+    assert!(message.contains("pattern is empty"), "{message}");
+    assert!(error.is_instance_of::<js_sys::Error>());
+    assert_eq!(
+        String::from(js_sys::Error::from(error).name()),
+        "VanityError"
+    );
+
+    assert!(VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("".into()),
+        None,
+        false,
+        0,
+        None,
+        None
+    )
+    .is_err());
+    assert!(
+        VanitySearcher::new(&[1; 32], &[4; 32], None, None, false, 0, Some(true), None).is_ok()
+    );
+    assert!(VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None
+    )
+    .is_ok());
+}
+
+#[wasm_bindgen_test]
+fn plan_search_returns_a_recommendation() {
+    start();
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(&options, &"suffix".into(), &"A".into()).unwrap();
+    let plan = JsValue::from(plan_search(Ts::new_unchecked(options.into()), 50.0).unwrap());
+
+    let get = |key: &str| js_sys::Reflect::get(&plan, &key.into()).unwrap();
+    assert!(get("measured_rate").as_f64().unwrap() > 0.0);
+    assert!(get("batch_size").as_f64().unwrap() >= 1_000.0);
+    assert!(get("workers").as_f64().unwrap() >= 1.0);
+    assert_eq!(get("expected_attempts").as_f64(), Some(58.0));
+    assert_eq!(get("difficulty").as_string().as_deref(), Some("trivial"));
+
+    let error = plan_search(Ts::new_unchecked("A".into()), 50.0).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn stop_flag_interrupts_a_running_batch() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let unshared = js_sys::Int32Array::new_with_length(1);
+    let error = searcher.set_stop_flag(&unshared).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidStopFlag");
+
+    let flag = js_sys::Int32Array::new(&js_sys::SharedArrayBuffer::new(4));
+    searcher.set_stop_flag(&flag).unwrap();
+    assert!(searcher.search_batch(10_000).is_none());
+    assert!(!searcher.is_stopped());
+    assert_eq!(searcher.attempts(), 10_000);
+
+    // With the flag already raised, a huge batch returns after the first poll.
+    js_sys::Atomics::store(&flag, 0, 1).unwrap();
+    assert!(searcher.search_batch(u32::MAX).is_none());
+    assert!(searcher.is_stopped());
+    assert_eq!(searcher.attempts(), 10_000);
+
+    js_sys::Atomics::store(&flag, 0, 0).unwrap();
+    searcher.restart();
+    searcher.search_batch(100);
+    assert_eq!(searcher.attempts(), 10_100);
+}
+
+/// A progress callback that records the attempts it was called with and
+/// returns `keep_going`.
+fn progress_counter(keep_going: JsValue) -> (js_sys::Function, Rc<RefCell<Vec<f64>>>) {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let recorded = calls.clone();
+    let closure = Closure::<dyn FnMut(JsValue) -> JsValue>::new(move |progress: JsValue| {
+        let attempts = js_sys::Reflect::get(&progress, &"attempts".into()).unwrap();
+        recorded.borrow_mut().push(attempts.as_f64().unwrap());
+        keep_going.clone()
+    });
+    (closure.into_js_value().unchecked_into(), calls)
+}
+
+#[wasm_bindgen_test]
+fn progress_callback_fires_every_n_attempts() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let (callback, calls) = progress_counter(JsValue::UNDEFINED);
+    searcher.set_progress_callback(callback, 12_000);
+
+    assert!(searcher.search_batch(50_000).is_none());
+    assert_eq!(*calls.borrow(), [12_000.0, 24_000.0, 36_000.0, 48_000.0]);
+
+    // The cadence carries over between batches.
+    searcher.search_batch(10_000);
+    assert_eq!(calls.borrow().len(), 5);
+
+    // Requests below the floor are raised to 10 000.
+    let (callback, calls) = progress_counter(JsValue::UNDEFINED);
+    searcher.set_progress_callback(callback, 1);
+    searcher.search_batch(25_000);
+    assert_eq!(calls.borrow().len(), 2);
+}
+
+#[wasm_bindgen_test]
+fn progress_callback_can_stop_the_search() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let (callback, calls) = progress_counter(JsValue::FALSE);
+    searcher.set_progress_callback(callback, 10_000);
+
+    assert!(searcher.search_batch(1_000_000).is_none());
+    assert!(searcher.is_stopped());
+    assert_eq!(searcher.attempts(), 10_000);
+    assert_eq!(calls.borrow().len(), 1);
+    assert!(searcher.callback_error().is_undefined());
+}
+
+#[wasm_bindgen_test]
+fn throwing_progress_callback_stops_with_error() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let callback = js_sys::Function::new_no_args("throw new Error('boom');");
+    searcher.set_progress_callback(callback, 10_000);
+
+    assert!(searcher.search_batch(1_000_000).is_none());
+    assert!(searcher.is_stopped());
+    assert_eq!(searcher.attempts(), 10_000);
+    let error = js_sys::Error::from(searcher.callback_error());
+    assert_eq!(String::from(error.message()), "boom");
+
+    searcher.clear_progress_callback();
+    searcher.restart();
+    assert!(searcher.callback_error().is_undefined());
+    searcher.search_batch(100);
+    assert_eq!(searcher.attempts(), 10_100);
+}
+
+#[wasm_bindgen_test]
+fn result_serializes_to_json() {
+    let result = VanityResult::new("addr".into(), "seed".into(), 7, None).unwrap();
+    let json = js_sys::JSON::stringify(&JsValue::from(result)).unwrap();
+    assert_eq!(
+        String::from(json),
+        r#"{"address":"addr","seed":"seed","attempts":7}"#
+    );
+}
+
+#[wasm_bindgen_test]
+fn builder_reports_all_problems() {
+    let error = VanitySearcherBuilder::new()
+        .base(vec![1; 32])
+        .contains("moon".into())
+        .suffix("x".into())
+        .stop_flag(js_sys::Int32Array::new_with_length(1))
+        .build()
+        .err()
+        .unwrap();
+    let error = JsValue::from(error);
+    assert_eq!(error_kind(&error).1, "Multiple");
+    let message = error_message(&error);
+    assert!(message.contains("owner pubkey is required"), "{message}");
+    assert!(message.contains("contains cannot be combined"), "{message}");
+    assert!(message.contains("SharedArrayBuffer"), "{message}");
+
+    let errors = js_sys::Array::from(&js_sys::Reflect::get(&error, &"errors".into()).unwrap());
+    let kinds: Vec<String> = errors.iter().map(|e| error_kind(&e).1).collect();
+    assert_eq!(
+        kinds,
+        ["MissingOwner", "ConflictingOptions", "InvalidStopFlag"]
+    );
+}
+
+#[wasm_bindgen_test]
+fn builder_builds_a_working_searcher() {
+    let flag = js_sys::Int32Array::new(&js_sys::SharedArrayBuffer::new(4));
+    let mut searcher = VanitySearcherBuilder::new()
+        .base(vec![1; 32])
+        .owner(vec![4; 32])
+        .prefix("zzzz".into())
+        .count_offset(10)
+        .stop_flag(flag.clone())
+        .build()
+        .unwrap();
+    assert!(searcher.search_batch(100).is_none());
+    assert_eq!(searcher.absolute_position(), Some(110));
+
+    js_sys::Atomics::store(&flag, 0, 1).unwrap();
+    searcher.search_batch(100);
+    assert!(searcher.is_stopped());
+}
+
+fn outcome_field(outcome: &JsValue, key: &str) -> JsValue {
+    js_sys::Reflect::get(outcome, &key.into()).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn batch_outcome_reports_mid_batch_stops_and_errors() {
+    let new = || {
+        VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap()
+    };
+
+    let mut searcher = new();
+    let outcome = JsValue::from(searcher.search_batch_outcome(100).unwrap());
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "not_found"
+    );
+    assert!(outcome_field(&outcome, "result").is_undefined());
+    assert_eq!(outcome_field(&outcome, "attempts").as_f64(), Some(100.0));
+    assert!(outcome_field(&outcome, "elapsed_ms").as_f64().is_some());
+    assert_eq!(
+        outcome_field(&outcome, "first_counter")
+            .as_string()
+            .unwrap(),
+        "0"
+    );
+    assert_eq!(
+        outcome_field(&outcome, "last_counter").as_string().unwrap(),
+        "99"
+    );
+    assert_eq!(outcome_field(&outcome, "evaluated").as_f64(), Some(100.0));
+
+    // Stopped partway through by the progress callback.
+    let (callback, _calls) = progress_counter(JsValue::FALSE);
+    searcher.set_progress_callback(callback, 10_000);
+    let outcome = JsValue::from(searcher.search_batch_outcome(1_000_000).unwrap());
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "stopped"
+    );
+    assert_eq!(outcome_field(&outcome, "attempts").as_f64(), Some(10_000.0));
+    // Exactly what was covered before the stop, right after the last call.
+    assert_eq!(
+        outcome_field(&outcome, "first_counter")
+            .as_string()
+            .unwrap(),
+        "100"
+    );
+    assert_eq!(
+        outcome_field(&outcome, "last_counter").as_string().unwrap(),
+        "10099"
+    );
+
+    let mut searcher = new();
+    let callback = js_sys::Function::new_no_args("throw new Error('boom');");
+    searcher.set_progress_callback(callback, 10_000);
+    let outcome = JsValue::from(searcher.search_batch_outcome(1_000_000).unwrap());
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "error"
+    );
+    assert!(!searcher.callback_error().is_undefined());
+}
+
+#[wasm_bindgen_test]
+fn deadline_ends_a_long_search_on_time() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let start = js_sys::Date::now();
+    searcher.stop_at(start + 500.0).unwrap();
+    assert_eq!(searcher.deadline(), Some(start + 500.0));
+    let outcome = JsValue::from(searcher.search_batch_outcome(u32::MAX).unwrap());
+    let elapsed = js_sys::Date::now() - start;
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "stopped"
+    );
+    assert_eq!(
+        outcome_field(&outcome, "deadline_reached").as_bool(),
+        Some(true)
+    );
+    assert!(
+        (500.0..3_000.0).contains(&elapsed),
+        "stopped after {elapsed} ms"
+    );
+
+    // Moved out of the way, the search runs again.
+    searcher.stop_at(js_sys::Date::now() + 60_000.0).unwrap();
+    searcher.restart();
+    let outcome = JsValue::from(searcher.search_batch_outcome(1_000).unwrap());
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "not_found"
+    );
+    assert_eq!(
+        outcome_field(&outcome, "deadline_reached").as_bool(),
+        Some(false)
+    );
+    searcher.clear_stop_at();
+    assert_eq!(searcher.deadline(), None);
+}
+
+#[wasm_bindgen_test]
+fn batch_outcome_carries_the_match() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("AAA".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let outcome = JsValue::from(searcher.search_batch_outcome(100_000).unwrap());
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "found"
+    );
+    let result = outcome_field(&outcome, "result");
+    let address = outcome_field(&result, "address").as_string().unwrap();
+    assert!(address.starts_with("AAA"), "{address}");
+    assert_eq!(
+        outcome_field(&result, "attempts").as_f64(),
+        Some(searcher.attempts() as f64)
+    );
+}
+
+#[wasm_bindgen_test]
+fn resume_token_is_bound_to_the_configuration() {
+    let new = |prefix: &str| {
+        VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some(prefix.into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap()
+    };
+    let mut searcher = new("zzzz");
+    searcher.search_batch(1_000);
+    let token = searcher.resume_token();
+
+    let mut resumed = new("zzzz");
+    resumed.resume_from(&token, None).unwrap();
+    assert_eq!(resumed.local_attempts(), 1_000);
+
+    let error = new("zzzy").resume_from(&token, None).unwrap_err();
+    assert_eq!(
+        error_kind(&error.into()),
+        (17, "ResumeTokenMismatch".into())
+    );
+    let error = new("zzzz").resume_from("v1.nope", None).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidResumeToken");
+}
+
+/// A checkpoint callback that records the tokens it was given and returns
+/// `keep_going`.
+fn checkpoint_recorder(keep_going: JsValue) -> (js_sys::Function, Rc<RefCell<Vec<String>>>) {
+    let tokens = Rc::new(RefCell::new(Vec::new()));
+    let recorded = tokens.clone();
+    let closure = Closure::<dyn FnMut(JsValue) -> JsValue>::new(move |token: JsValue| {
+        recorded.borrow_mut().push(token.as_string().unwrap());
+        keep_going.clone()
+    });
+    (closure.into_js_value().unchecked_into(), tokens)
+}
+
+/// Every match (attempt number and seed) until the attempt cap.
+fn matches_until_exhausted(searcher: &mut VanitySearcher) -> Vec<(u64, String)> {
+    let mut matches = Vec::new();
+    while !searcher.is_exhausted() {
+        if let Some(result) = searcher.search_batch(7_000) {
+            matches.push((result.attempts(), result.seed()));
+        }
+    }
+    matches
+}
+
+#[wasm_bindgen_test]
+fn checkpoint_resumes_without_skipping_or_repeating() {
+    let new = || {
+        let mut searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("A".into()),
+            None,
+            false,
+            3,
+            None,
+            None,
+        )
+        .unwrap();
+        searcher.set_max_attempts(Some(50_000));
+        searcher
+    };
+    let uninterrupted = matches_until_exhausted(&mut new());
+    assert!(uninterrupted.len() > 100);
+
+    let mut searcher = new();
+    let (callback, tokens) = checkpoint_recorder(JsValue::UNDEFINED);
+    searcher.set_checkpoint_callback(callback, 10_000);
+    searcher.set_max_attempts(Some(25_000));
+    let before = matches_until_exhausted(&mut searcher);
+    assert_eq!(tokens.borrow().len(), 2);
+
+    // Resume from the second checkpoint, taken mid-batch at 20 000 attempts.
+    let checkpoint = tokens.borrow()[1].clone();
+    let mut resumed = new();
+    resumed.resume_from(&checkpoint, None).unwrap();
+    assert_eq!(resumed.local_attempts(), 20_000);
+    let mut combined: Vec<_> = before
+        .into_iter()
+        .filter(|(attempts, _)| *attempts <= 20_000)
+        .collect();
+    combined.extend(matches_until_exhausted(&mut resumed));
+    assert_eq!(combined, uninterrupted);
+}
+
+#[wasm_bindgen_test]
+fn checkpoint_callback_can_pause_checkpoints() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let (callback, tokens) = checkpoint_recorder(JsValue::FALSE);
+    searcher.set_checkpoint_callback(callback, 10_000);
+    searcher.search_batch(35_000);
+    assert_eq!(tokens.borrow().len(), 1);
+    assert!(!searcher.is_stopped());
+
+    searcher.resume_checkpoints();
+    searcher.search_batch(10_000);
+    assert_eq!(tokens.borrow().len(), 2);
+
+    let mut resumed = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    resumed.resume_from(&tokens.borrow()[1], None).unwrap();
+    assert_eq!(resumed.local_attempts(), 45_000);
+}
+
+#[wasm_bindgen_test]
+fn sample_addresses_rederive() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("AAA".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    searcher.search_batch(10);
+
+    let samples = searcher.sample_addresses(20, Some(1_000)).unwrap();
+    assert_eq!(samples.length(), 20);
+    let deriver = vanity_core::Deriver::new(&[1; 32], &[4; 32]);
+    let mut buf = [0u8; vanity_core::MAX_ENCODED_LEN];
+    for (i, sample) in samples.iter().enumerate() {
+        let counter = outcome_field(&sample, "counter").as_f64().unwrap();
+        assert_eq!(counter, 1_000.0 + i as f64);
+        let seed = outcome_field(&sample, "seed").as_string().unwrap();
+        assert_eq!(
+            seed.as_bytes(),
+            vanity_core::generate_seed_from_counter(counter as u64)
+        );
+        let pubkey = deriver.derive(seed.as_bytes());
+        assert_eq!(
+            outcome_field(&sample, "address").as_string().unwrap(),
+            vanity_core::encode_pubkey(&pubkey, &mut buf)
+        );
+    }
+    assert_eq!(searcher.local_attempts(), 10);
+
+    assert_eq!(
+        searcher.sample_addresses(5_000, None).unwrap().length(),
+        1_000
+    );
+    let error = searcher.sample_addresses(2, Some(1 << 53)).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn permuted_samples_report_both_counters() {
+    let mut searcher = VanitySearcherBuilder::new()
+        .base(vec![1; 32])
+        .owner(vec![4; 32])
+        .prefix("A".into())
+        .permute_counters(None)
+        .build()
+        .unwrap();
+    let key = searcher.counter_permutation_key().unwrap();
+    let permutation = vanity_core::CounterPermutation::new(key);
+
+    let samples = searcher.sample_addresses(3, Some(1_000)).unwrap();
+    for (i, sample) in samples.iter().enumerate() {
+        assert_eq!(
+            outcome_field(&sample, "counter").as_f64(),
+            Some(1_000.0 + i as f64)
+        );
+        let physical = outcome_field(&sample, "physical_counter").as_string();
+        let physical: u64 = physical.unwrap().parse().unwrap();
+        assert_eq!(physical, permutation.apply(1_000 + i as u64));
+        let seed = outcome_field(&sample, "seed").as_string().unwrap();
+        assert_eq!(
+            seed.as_bytes(),
+            vanity_core::generate_seed_from_counter(physical)
+        );
+    }
+
+    let result = searcher.search_batch(10_000).unwrap();
+    let json = js_sys::JSON::stringify(&JsValue::from(result)).unwrap();
+    let counter = permutation.apply(searcher.local_attempts() - 1);
+    assert!(String::from(json).contains(&format!(r#""counter":"{counter}","#)));
+
+    searcher.clear_counter_permutation();
+    let sample = searcher.sample_addresses(1, Some(1_000)).unwrap().get(0);
+    assert!(outcome_field(&sample, "physical_counter").is_undefined());
+    let pinned = in_test_mode(|| searcher.permute_counters(None).unwrap());
+    assert_eq!(pinned, 0);
+}
+
+/// Runs `f` with `test_mode` on, switching it off afterwards.
+fn in_test_mode<R>(f: impl FnOnce() -> R) -> R {
+    test_mode(true);
+    let result = f();
+    test_mode(false);
+    result
+}
+
+#[wasm_bindgen_test]
+fn test_mode_makes_searches_reproducible() {
+    let run = || {
+        in_test_mode(|| {
+            let mut searcher = VanitySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                Some("A".into()),
+                None,
+                false,
+                0,
+                None,
+                None,
+            )
+            .unwrap();
+            let result = loop {
+                if let Some(result) = searcher.search_batch(10) {
+                    break result;
+                }
+            };
+            assert!(result.address().starts_with('A'));
+            assert_eq!(result.attempts(), searcher.local_attempts());
+            assert_eq!(result.seed().len(), vanity_core::SEED_LEN);
+            let sample = searcher.sample_addresses(1, None).unwrap().get(0);
+            (
+                result.address(),
+                result.seed(),
+                result.attempts(),
+                searcher.rate(),
+                outcome_field(&sample, "counter").as_f64(),
+            )
+        })
+    };
+    let first = run();
+    assert_eq!(first, run());
+    assert_eq!(first.4, Some(0.0));
+}
+
+#[wasm_bindgen_test]
+fn test_mode_pins_timings() {
+    let (rate, elapsed) = in_test_mode(|| {
+        let mut searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        for _ in 0..3 {
+            searcher.search_batch(10);
+        }
+        let progress = JsValue::from(searcher.progress().unwrap());
+        (
+            searcher.rate(),
+            outcome_field(&progress, "elapsed_ms").as_f64(),
+        )
+    });
+    // Each batch reads the stepping clock twice, so takes exactly 1 ms.
+    assert_eq!(rate, Some(10_000.0));
+    assert_eq!(elapsed, Some(3.0));
+}
+
+#[wasm_bindgen_test]
+fn self_test_passes() {
+    let start = js_sys::Date::now();
+    self_test().unwrap();
+    assert!(js_sys::Date::now() - start < 50.0);
+}
+
+#[wasm_bindgen_test]
+fn memory_stats_report_linear_memory() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("AAA".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let stats = JsValue::from(searcher.memory_stats().unwrap());
+    let linear = outcome_field(&stats, "linear_memory_bytes")
+        .as_f64()
+        .unwrap();
+    assert!(linear >= 65_536.0 && linear % 65_536.0 == 0.0);
+    let searcher_bytes = outcome_field(&stats, "searcher_bytes").as_f64().unwrap();
+    assert!(searcher_bytes > 0.0 && searcher_bytes < linear);
+
+    searcher.shrink();
+    let stats = JsValue::from(searcher.memory_stats().unwrap());
+    assert!(outcome_field(&stats, "searcher_bytes").as_f64().unwrap() <= searcher_bytes);
+}
+
+type Beats = Rc<RefCell<Vec<(f64, f64)>>>;
+
+/// A heartbeat callback recording each `(sequence, attempts)` it receives.
+fn heartbeat_recorder() -> (js_sys::Function, Beats) {
+    let beats = Rc::new(RefCell::new(Vec::new()));
+    let recorded = beats.clone();
+    let closure =
+        Closure::<dyn FnMut(JsValue, JsValue)>::new(move |sequence: JsValue, attempts: JsValue| {
+            recorded
+                .borrow_mut()
+                .push((sequence.as_f64().unwrap(), attempts.as_f64().unwrap()));
+        });
+    (closure.into_js_value().unchecked_into(), beats)
+}
+
+#[wasm_bindgen_test]
+fn heartbeat_follows_active_time() {
+    let beats = in_test_mode(|| {
+        let mut searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        let (callback, beats) = heartbeat_recorder();
+        searcher.set_heartbeat(callback, 10.0);
+        searcher.search_batch(100_000);
+        beats
+    });
+    // The stepping clock advances 1 ms per poll, i.e. per 4096 attempts.
+    assert_eq!(*beats.borrow(), [(0.0, 9.0 * 4096.0), (1.0, 19.0 * 4096.0)]);
+}
+
+#[wasm_bindgen_test]
+fn heartbeat_beats_over_real_time() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let (callback, beats) = heartbeat_recorder();
+    searcher.set_heartbeat(callback, 200.0);
+    let start = js_sys::Date::now();
+    while js_sys::Date::now() - start < 1_500.0 {
+        searcher.search_batch(20_000);
+    }
+    let beats = beats.borrow();
+    assert!((4..=8).contains(&beats.len()), "{beats:?}");
+    for (i, pair) in beats.windows(2).enumerate() {
+        assert_eq!(pair[0].0, i as f64);
+        assert!(pair[1].1 > pair[0].1);
+    }
+
+    let callback = js_sys::Function::new_no_args("throw new Error('stalled');");
+    searcher.set_heartbeat(callback, 0.0);
+    assert!(searcher.search_batch(10).is_none());
+    assert!(searcher.is_stopped());
+    assert!(!searcher.callback_error().is_undefined());
+}
+
+#[wasm_bindgen_test]
+fn difficulty_curve_reports_bad_positions() {
+    let curve = difficulty_curve("Ab9", true).unwrap();
+    assert_eq!(curve.len(), 3);
+    assert!(curve.windows(2).all(|pair| pair[1] > pair[0]));
+
+    let error: JsValue = difficulty_curve("AbIc", false).unwrap_err().into();
+    assert_eq!(error_kind(&error), (2, "InvalidPatternChar".into()));
+    let position = js_sys::Reflect::get(&error, &"position".into()).unwrap();
+    assert_eq!(position.as_f64(), Some(2.0));
+    let alphabet = js_sys::Reflect::get(&error, &"alphabet".into()).unwrap();
+    assert_eq!(alphabet.as_string().unwrap(), "base58");
+}
+
+fn job_options(entries: &[(&str, JsValue)]) -> Ts<JobOptions> {
+    let options = js_sys::Object::new();
+    for (key, value) in entries {
+        js_sys::Reflect::set(&options, &(*key).into(), value).unwrap();
+    }
+    Ts::new_unchecked(options.into())
+}
+
+#[wasm_bindgen_test]
+fn job_queue_runs_jobs_in_order() {
+    let mut queue = VanityJobQueue::new(&[1; 32], &[4; 32]).unwrap();
+    assert_eq!(
+        queue.push(job_options(&[("prefix", "A".into())])).unwrap(),
+        0
+    );
+    let second = job_options(&[
+        ("suffix", "b".into()),
+        ("case_insensitive", true.into()),
+        ("count_offset", 5_000.into()),
+    ]);
+    assert_eq!(queue.push(second).unwrap(), 1);
+    let error: JsValue = queue
+        .push(job_options(&[("prefix", "0".into())]))
+        .unwrap_err()
+        .into();
+    assert_eq!(error_kind(&error).0, 2);
+
+    let progress = |queue: &VanityJobQueue, key: &str| {
+        let progress = JsValue::from(queue.progress().unwrap());
+        js_sys::Reflect::get(&progress, &key.into()).unwrap()
+    };
+    assert_eq!(progress(&queue, "job").as_f64(), Some(0.0));
+
+    let mut found = Vec::new();
+    while let Some(job) = queue.current_job() {
+        if let Some(result) = queue.run_batch(50) {
+            found.push((job, result.address()));
+        }
+    }
+    assert_eq!(found.len(), 2);
+    assert!(found[0].1.starts_with('A'));
+    assert!(found[1].1.to_lowercase().ends_with('b'));
+    assert!(progress(&queue, "job").is_undefined());
+    assert_eq!(progress(&queue, "finished").as_f64(), Some(2.0));
+
+    let results = queue.results().unwrap();
+    assert_eq!(results.length(), 2);
+    let second = results.get(1);
+    let get = |key: &str| js_sys::Reflect::get(&second, &key.into()).unwrap();
+    assert_eq!(get("job").as_f64(), Some(1.0));
+    assert_eq!(get("address").as_string().unwrap(), found[1].1);
+    assert_eq!(
+        queue
+            .job_status(1)
+            .unwrap()
+            .map(JsValue::from)
+            .and_then(|s| s.as_string()),
+        Some("found".into())
+    );
+}
+
+#[wasm_bindgen_test]
+fn job_queue_moves_past_capped_and_skipped_jobs() {
+    let mut queue = VanityJobQueue::new(&[1; 32], &[4; 32]).unwrap();
+    let capped = job_options(&[("prefix", "zzzz".into()), ("max_attempts", 30.into())]);
+    queue.push(capped).unwrap();
+    queue
+        .push(job_options(&[("prefix", "zzzz".into())]))
+        .unwrap();
+    queue.push(job_options(&[("prefix", "A".into())])).unwrap();
+
+    assert!(queue.run_batch(50).is_none());
+    assert_eq!(queue.current_job(), Some(1));
+    assert_eq!(queue.skip(), Some(1));
+    let result = loop {
+        if let Some(result) = queue.run_batch(50) {
+            break result;
+        }
+    };
+    assert!(result.address().starts_with('A'));
+    assert_eq!(queue.current_job(), None);
+    assert_eq!(queue.skip(), None);
+    let status = |index| {
+        queue
+            .job_status(index)
+            .unwrap()
+            .map(JsValue::from)?
+            .as_string()
+    };
+    assert_eq!(status(0), Some("exhausted".into()));
+    assert_eq!(status(1), Some("skipped".into()));
+    assert_eq!(status(3), None);
+}
+
+#[wasm_bindgen_test]
+fn search_range_is_independent_of_the_searcher_position() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("AAA".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let found = loop {
+        if let Some(found) = searcher.search_batch(10_000) {
+            break found;
+        }
+    };
+    let counter = found.attempts() - 1;
+    let attempts = searcher.attempts();
+
+    let field = |outcome: &JsValue, key: &str| js_sys::Reflect::get(outcome, &key.into()).unwrap();
+    let range = |searcher: &mut VanitySearcher, start: u64, end: u64| {
+        JsValue::from(searcher.search_range(start, end).unwrap())
+    };
+    let counters = |outcome: &JsValue| -> Vec<f64> {
+        js_sys::Array::from(&field(outcome, "matches"))
+            .iter()
+            .map(|m| field(&m, "counter").as_f64().unwrap())
+            .collect()
+    };
+
+    let around = range(&mut searcher, counter - 5, counter + 5);
+    assert_eq!(counters(&around), [counter as f64]);
+    let matched = js_sys::Array::from(&field(&around, "matches")).get(0);
+    assert_eq!(
+        field(&matched, "address").as_string().unwrap(),
+        found.address()
+    );
+    assert_eq!(field(&around, "attempts").as_f64(), Some(10.0));
+    assert_eq!(field(&around, "interrupted").as_bool(), Some(false));
+    assert_eq!(searcher.attempts(), attempts);
+
+    let first = range(&mut searcher, 0, counter);
+    let second = range(&mut searcher, counter, counter + 1);
+    assert_eq!(field(&first, "attempts").as_f64(), Some(counter as f64));
+    assert!(!counters(&first).contains(&(counter as f64)));
+    assert_eq!(counters(&second), [counter as f64]);
+
+    let error = searcher.search_range(0, (1 << 53) + 1).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+
+    let flag = js_sys::Int32Array::new(&js_sys::SharedArrayBuffer::new(4));
+    searcher.set_stop_flag(&flag).unwrap();
+    js_sys::Atomics::store(&flag, 0, 1).unwrap();
+    let stopped = range(&mut searcher, 0, 1_000_000);
+    assert_eq!(field(&stopped, "interrupted").as_bool(), Some(true));
+    assert_eq!(field(&stopped, "attempts").as_f64(), Some(0.0));
+}
+
+#[wasm_bindgen_test]
+fn multi_searcher_tags_results_by_job() {
+    let mut multi = VanityMultiSearcher::new(0);
+    let easy = multi
+        .add_job(&[1; 32], &[4; 32], Some("A".into()), None, false, Some(2))
+        .unwrap();
+    let hard = multi
+        .add_job(&[2; 32], &[3; 32], None, Some("zz".into()), false, None)
+        .unwrap();
+    let error = multi
+        .add_job(&[2; 32], &[3; 3], Some("A".into()), None, false, None)
+        .unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "BadOwnerLength");
+
+    let mut found = Vec::new();
+    while !multi.is_done() {
+        found.extend(multi.search_batch(1_000).unwrap().iter());
+    }
+    let field = |result: &JsValue, key: &str| js_sys::Reflect::get(result, &key.into()).unwrap();
+    let addresses = |job: u32| -> Vec<String> {
+        found
+            .iter()
+            .filter(|result| field(result, "job").as_f64() == Some(job as f64))
+            .map(|result| field(result, "address").as_string().unwrap())
+            .collect()
+    };
+    let easy_found = addresses(easy);
+    assert_eq!(easy_found.len(), 2);
+    assert!(easy_found.iter().all(|address| address.starts_with('A')));
+    let hard_found = addresses(hard);
+    assert_eq!(hard_found.len(), 1);
+    assert!(hard_found[0].ends_with("zz"));
+
+    assert_eq!(multi.job_results(easy), Some(2));
+    assert_eq!(multi.is_job_done(hard), Some(true));
+    assert_eq!(multi.job_attempts(hard), Some(multi.attempts()));
+    assert!(multi.job_attempts(easy) < multi.job_attempts(hard));
+    assert_eq!(multi.job_attempts(7), None);
+}
+
+#[wasm_bindgen_test]
+fn family_searcher_grinds_matching_seeds() {
+    let strategy = |name: &str| Ts::new_unchecked(JsValue::from(name));
+    let mut family = VanityFamilySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        "stake-".into(),
+        3,
+        strategy("independent"),
+    )
+    .unwrap();
+    while !family.search_batch(100) {}
+
+    let members = family.members().unwrap();
+    assert_eq!(members.length(), 3);
+    for (i, member) in members.iter().enumerate() {
+        let get = |key: &str| js_sys::Reflect::get(&member, &key.into()).unwrap();
+        assert_eq!(get("index").as_f64(), Some(i as f64));
+        let seed = get("seed").as_string().unwrap();
+        assert!(seed.starts_with("stake-") && seed.ends_with(&i.to_string()));
+        assert!(get("address").as_string().unwrap().starts_with('A'));
+    }
+
+    let estimate = |name: &str| {
+        JsValue::from(estimate_family(Some("A".into()), None, false, 3, strategy(name)).unwrap())
+    };
+    let warning = |estimate: &JsValue| js_sys::Reflect::get(estimate, &"warning".into()).unwrap();
+    assert!(warning(&estimate("independent")).is_undefined());
+    assert!(warning(&estimate("all_match"))
+        .as_string()
+        .unwrap()
+        .contains("all 3"));
+
+    let error = VanityFamilySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        "stake-".into(),
+        3,
+        strategy("sequential"),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn every_preset_builds_a_searcher() {
+    let presets = presets().unwrap();
+    assert!(presets.length() >= 5);
+    for preset in presets.iter() {
+        let get = |key: &str| js_sys::Reflect::get(&preset, &key.into()).unwrap();
+        let id = get("id").as_string().unwrap();
+        assert!(get("expected_attempts").as_f64().unwrap().is_finite());
+        assert!(get("difficulty").is_string());
+        let searcher = VanitySearcher::from_preset(&id, &[1; 32], &[4; 32], 0).unwrap();
+        assert_eq!(searcher.attempts(), 0);
+    }
+    let error = VanitySearcher::from_preset("nope", &[1; 32], &[4; 32], 0)
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn auto_fix_reports_pattern_rewrites() {
+    let builder = VanitySearcherBuilder::new()
+        .base(vec![1; 32])
+        .owner(vec![4; 32])
+        .prefix("S0L".into());
+    let error = builder.clone().build().err().unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidPatternChar");
+
+    let mut searcher = builder.auto_fix(true).build().unwrap();
+    let rewrites = searcher.pattern_rewrites().unwrap();
+    assert_eq!(rewrites.length(), 1);
+    let rewrite = rewrites.get(0);
+    let get = |key: &str| js_sys::Reflect::get(&rewrite, &key.into()).unwrap();
+    assert_eq!(get("pattern").as_string().unwrap(), "prefix");
+    assert_eq!(get("position").as_f64(), Some(1.0));
+    assert_eq!(get("original").as_string().unwrap(), "0");
+    assert_eq!(get("replacement").as_string().unwrap(), "o");
+
+    let found = loop {
+        if let Some(found) = searcher.search_batch(100_000) {
+            break found;
+        }
+    };
+    assert!(found.address().starts_with("SoL"));
+}
+
+#[wasm_bindgen_test]
+fn search_best_always_returns_a_scored_result() {
+    let scoring = |kind: &str, pattern: Option<&str>| {
+        let scoring = js_sys::Object::new();
+        js_sys::Reflect::set(&scoring, &"kind".into(), &kind.into()).unwrap();
+        if let Some(pattern) = pattern {
+            js_sys::Reflect::set(&scoring, &"pattern".into(), &pattern.into()).unwrap();
+        }
+        Ts::new_unchecked(scoring.into())
+    };
+    // A zero duration still runs one batch.
+    let best = search_best(&[1; 32], &[4; 32], 0.0, scoring("trailing_run", None), None).unwrap();
+    assert!(best.score() >= 1);
+    assert!(best.attempts() >= 1);
+
+    let best = search_best(
+        &[1; 32],
+        &[4; 32],
+        20.0,
+        scoring("leading_match", Some("AAAA")),
+        Some(0),
+    )
+    .unwrap();
+    let score = best.score() as usize;
+    assert!(score >= 1);
+    assert!(best.address().starts_with(&"AAAA"[..score]));
+
+    let error = search_best(&[1; 32], &[4; 32], 0.0, scoring("prettiest", None), None)
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn shared_best_score_filters_reports() {
+    let scoring = || {
+        let scoring = js_sys::Object::new();
+        js_sys::Reflect::set(&scoring, &"kind".into(), &"trailing_run".into()).unwrap();
+        Ts::new_unchecked(scoring.into())
+    };
+    // Node has no Worker here, so the workers take turns on one thread.
+    let score = js_sys::Int32Array::new(&js_sys::SharedArrayBuffer::new(4).into());
+    score.fill(-1, 0, 1);
+    let mut workers: Vec<_> = (0..4u64)
+        .map(|i| {
+            let mut worker =
+                VanityBestSearcher::new(&[1; 32], &[4; 32], scoring(), Some(i << 32)).unwrap();
+            assert!(worker.set_shared_best(&score));
+            worker
+        })
+        .collect();
+    let mut reported = Vec::new();
+    for _ in 0..20 {
+        for worker in &mut workers {
+            if let Some(found) = worker.search_batch(200) {
+                reported.push(found.score());
+            }
+        }
+    }
+    // Only global records are reported: each beats all before it.
+    assert!(reported.windows(2).all(|pair| pair[0] < pair[1]));
+    let best = workers
+        .iter()
+        .map(|w| w.best().unwrap().score())
+        .max()
+        .unwrap();
+    assert_eq!(reported.last(), Some(&best));
+    assert_eq!(js_sys::Atomics::load(&score, 0).unwrap(), best as i32);
+
+    // A plain array falls back to this worker's own records.
+    let mut local = VanityBestSearcher::new(&[1; 32], &[4; 32], scoring(), None).unwrap();
+    assert!(!local.set_shared_best(&js_sys::Int32Array::new_with_length(1)));
+    assert!(local.search_batch(200).is_some());
+    assert!(local.search_batch(0).is_none());
+}
+
+#[wasm_bindgen_test]
+fn progress_aggregator_totals_workers() {
+    let mut workers: Vec<_> = (0..3u64)
+        .map(|i| {
+            VanitySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                Some("zzzz".into()),
+                None,
+                false,
+                i << 32,
+                None,
+                None,
+            )
+            .unwrap()
+        })
+        .collect();
+    let mut totals =
+        ProgressAggregator::new(estimate_attempts(Some("zzzz".into()), None, false), None);
+    assert_eq!(totals.stale_ms(0), None);
+    assert_eq!(totals.attempts(), 0);
+    for (id, worker) in workers.iter_mut().enumerate() {
+        worker.search_batch(100 * (id as u32 + 1));
+        totals
+            .update(id as u32, worker.progress().unwrap())
+            .unwrap();
+    }
+    assert_eq!(totals.attempts(), 600);
+
+    // A restarted worker starts again from zero; the total keeps its work.
+    workers[0] = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    workers[0].search_batch(50);
+    totals.update(0, workers[0].progress().unwrap()).unwrap();
+    assert_eq!(totals.attempts(), 650);
+
+    let snapshot = JsValue::from(totals.snapshot().unwrap());
+    assert_eq!(outcome_field(&snapshot, "attempts").as_f64(), Some(650.0));
+    let workers = js_sys::Array::from(&outcome_field(&snapshot, "workers"));
+    assert_eq!(workers.length(), 3);
+    assert!(totals.stale_ms(2).unwrap() >= 0.0);
+    assert!(totals.probability_found() > 0.0);
+
+    let error = totals
+        .update(1, Ts::new_unchecked(js_sys::JSON::parse("{}").unwrap()))
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn features_list_the_modes_built() {
+    let built = features();
+    for mode in ["keypair", "metadata", "squads", "passphrase", "token2022"] {
+        let enabled = match mode {
+            "keypair" => cfg!(feature = "keypair"),
+            "metadata" => cfg!(feature = "metadata"),
+            "squads" => cfg!(feature = "squads"),
+            "token2022" => cfg!(feature = "token2022"),
+            _ => cfg!(feature = "passphrase"),
+        };
+        assert_eq!(
+            built.iter().any(|feature| feature == mode),
+            enabled,
+            "{mode}"
+        );
+    }
+    let capabilities = JsValue::from(capabilities().unwrap());
+    let listed = js_sys::Array::from(&outcome_field(&capabilities, "features"));
+    assert_eq!(listed.length() as usize, built.len());
+}
+
+#[wasm_bindgen_test]
+fn progress_buffer_tracks_a_running_batch() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let unshared = js_sys::BigUint64Array::new_with_length(2);
+    let error = searcher.attach_progress_buffer(&unshared, 0).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidProgressBuffer");
+    let counters = js_sys::BigUint64Array::new(&js_sys::SharedArrayBuffer::new(16));
+    let error = searcher.attach_progress_buffer(&counters, 2).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidProgressBuffer");
+
+    // Read the counter from a heartbeat on every poll, as another thread
+    // would between the searcher's stores. (`Atomics::load_bigint` takes
+    // its index as a BigInt, which `Atomics.load` rejects; on one thread a
+    // plain read sees the same values.)
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let recorded = seen.clone();
+    let view = counters.clone();
+    let closure =
+        Closure::<dyn FnMut(JsValue, JsValue)>::new(move |_: JsValue, attempts: JsValue| {
+            let counter = view.get_index(1);
+            recorded
+                .borrow_mut()
+                .push((attempts.as_f64().unwrap(), counter as f64));
+        });
+    searcher.set_heartbeat(closure.into_js_value().unchecked_into(), 0.0);
+    searcher.attach_progress_buffer(&counters, 1).unwrap();
+    searcher.search_batch(50_000);
+
+    let seen = seen.borrow();
+    assert_eq!(seen.len(), 13);
+    assert!(seen.iter().all(|(attempts, counter)| attempts == counter));
+    assert_eq!(counters.get_index(1), 50_000);
+    assert_eq!(counters.get_index(0), 0);
+
+    searcher.detach_progress_buffer();
+    searcher.search_batch(1_000);
+    assert_eq!(counters.get_index(1), 50_000);
+}
+
+#[wasm_bindgen_test]
+fn seen_filter_skips_covered_blocks() {
+    const BLOCK: u64 = 1 << 20;
+    let mut laptop = SeenFilter::new(100, 1e-6).unwrap();
+    assert_eq!(laptop.insert_range(0, BLOCK), 1);
+    let mut phone = SeenFilter::new(100, 1e-6).unwrap();
+    phone.insert_range(BLOCK, BLOCK);
+
+    let mut merged = SeenFilter::deserialize(&laptop.serialize()).unwrap();
+    merged.merge(&phone).unwrap();
+    assert!(merged.maybe_covered(5) && merged.maybe_covered(BLOCK + 5));
+    assert!(!merged.maybe_covered(2 * BLOCK));
+    assert!(merged.false_positive_rate() < 1e-6);
+
+    let error = merged.merge(&SeenFilter::new(1, 0.5).unwrap()).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidSeenFilter");
+    let error = SeenFilter::deserialize(&[1, 20]).err().unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidSeenFilter");
+
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    searcher.set_seen_filter(&merged);
+    // A batch covers at most its size, skipped or not.
+    assert!(searcher.search_batch(1_000).is_none());
+    assert_eq!(searcher.skipped(), 1_000);
+    assert!(searcher.search_batch(2 * BLOCK as u32).is_none());
+    assert_eq!(searcher.skipped(), 2 * BLOCK);
+    assert_eq!(searcher.absolute_position(), Some(2 * BLOCK + 1_000));
+}
+
+#[wasm_bindgen_test]
+fn work_units_round_trip_through_json() {
+    let options = js_sys::JSON::parse(
+        r#"{"base": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+            "owner": "Vote111111111111111111111111111111111111111",
+            "prefix": "A", "start": 5000, "issued_to": "bob"}"#,
+    )
+    .unwrap();
+    let units = create_work_units(Ts::new_unchecked(options), 300, 3).unwrap();
+    assert_eq!(units.length(), 3);
+    let unit = units.get(1);
+    assert_eq!(
+        outcome_field(&unit, "issued_to").as_string().unwrap(),
+        "bob"
+    );
+    assert!(outcome_field(&unit, "issued_at_ms").as_f64().unwrap() > 0.0);
+
+    // Units and records survive being sent around as JSON text.
+    let through_json = |value: &JsValue| {
+        js_sys::JSON::parse(&js_sys::JSON::stringify(value).unwrap().as_string().unwrap()).unwrap()
+    };
+    let records = js_sys::Array::new();
+    for unit in units.iter() {
+        let record = run_work_unit(Ts::new_unchecked(through_json(&unit))).unwrap();
+        records.push(&through_json(&JsValue::from(record)));
+    }
+
+    // A claimed match moved to a counter it doesn't come from.
+    let tampered = records.get(2);
+    let matches: js_sys::Array = outcome_field(&tampered, "matches").unchecked_into();
+    let fake = through_json(
+        &outcome_field(&records.get(0), "matches")
+            .unchecked_into::<js_sys::Array>()
+            .get(0),
+    );
+    js_sys::Reflect::set(&fake, &"counter".into(), &5_700.into()).unwrap();
+    matches.push(&fake);
+
+    let collated = JsValue::from(collate_results(records.into()).unwrap());
+    let accepted: Vec<_> = js_sys::Array::from(&outcome_field(&collated, "accepted"))
+        .iter()
+        .map(|index| index.as_f64().unwrap())
+        .collect();
+    assert_eq!(accepted, [0.0, 1.0]);
+    assert_eq!(outcome_field(&collated, "covered").as_f64(), Some(600.0));
+    let rejected = js_sys::Array::from(&outcome_field(&collated, "rejected")).get(0);
+    assert_eq!(
+        outcome_field(&rejected, "reason").as_string().unwrap(),
+        "digest doesn't match the record"
+    );
+
+    let error = collate_results(js_sys::Array::new().into()).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidWorkUnit");
+}
+
+#[wasm_bindgen_test]
+fn infeasible_patterns_need_force() {
+    let options = |json: &str| Ts::new_unchecked(js_sys::JSON::parse(json).unwrap());
+    let error =
+        VanitySearcher::from_options(&[1; 32], &[4; 32], options(r#"{"suffix": "zzzzzzzzzz"}"#))
+            .err()
+            .unwrap()
+            .into();
+    assert_eq!(error_kind(&error).1, "Infeasible");
+    let get = |key: &str| js_sys::Reflect::get(&error, &key.into()).unwrap();
+    assert_eq!(get("expected_attempts").as_f64(), Some(58f64.powi(10)));
+    assert_eq!(get("duration").as_string().unwrap(), "13661 years");
+
+    let forced = options(r#"{"suffix": "zzzzzzzzzz", "force": true}"#);
+    assert!(VanitySearcher::from_options(&[1; 32], &[4; 32], forced).is_ok());
+    let lowered = options(r#"{"suffix": "zzz", "max_expected_attempts": 1000}"#);
+    let error = VanitySearcher::from_options(&[1; 32], &[4; 32], lowered)
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(&error.into()).1, "Infeasible");
+    let borderline = options(r#"{"suffix": "zz", "max_expected_attempts": 3400}"#);
+    assert!(VanitySearcher::from_options(&[1; 32], &[4; 32], borderline).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn pattern_lists_feed_the_multi_searcher() {
+    let text = "# wanted\nprefix:A ci\n\ncontains:zz label=twin-z  # anywhere\n";
+    let entries = parse_pattern_list(text).unwrap();
+    assert_eq!(entries.length(), 2);
+    assert_eq!(
+        outcome_field(&entries.get(0), "label").as_string().unwrap(),
+        "prefix:A ci"
+    );
+    let formatted = format_pattern_list(entries.clone().into()).unwrap();
+    assert_eq!(formatted, "prefix:A ci\ncontains:zz label=twin-z\n");
+
+    let mut multi = VanityMultiSearcher::new(0);
+    let jobs = multi
+        .add_pattern_list(&[1; 32], &[4; 32], entries.into(), None)
+        .unwrap();
+    assert_eq!(jobs, [0, 1]);
+    let mut found = Vec::new();
+    while !multi.is_done() {
+        found.extend(multi.search_batch(5_000).unwrap().iter());
+    }
+    let stats = multi.pattern_stats().unwrap();
+    assert_eq!(stats.length(), 2);
+    for (job, label) in [(0, "prefix:A ci"), (1, "twin-z")] {
+        let stats = stats.get(job);
+        assert_eq!(outcome_field(&stats, "label").as_string().unwrap(), label);
+        assert_eq!(outcome_field(&stats, "matches_found").as_f64(), Some(1.0));
+        let result = found
+            .iter()
+            .find(|result| outcome_field(result, "job").as_f64() == Some(job as f64))
+            .unwrap();
+        assert_eq!(
+            outcome_field(&stats, "attempts_at_last_match").as_f64(),
+            outcome_field(result, "attempts").as_f64()
+        );
+        assert!(outcome_field(&stats, "empirical_vs_expected_ratio")
+            .as_f64()
+            .unwrap()
+            .is_finite());
+    }
+
+    let error: JsValue = parse_pattern_list("prefix:A\nprefix:0")
+        .err()
+        .unwrap()
+        .into();
+    assert_eq!(error_kind(&error).1, "InvalidPatternList");
+    let line = js_sys::Reflect::get(&error, &"line".into()).unwrap();
+    assert_eq!(line.as_f64(), Some(2.0));
+    let error = multi
+        .add_pattern_list(
+            &[1; 32],
+            &[4; 32],
+            js_sys::Array::of1(&"x".into()).into(),
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+    assert_eq!(multi.job_attempts(2), None);
+}
+
+#[wasm_bindgen_test]
+fn switching_keys_keeps_earlier_results_verifiable() {
+    let (old_base, new_base, owner) = ([1u8; 32], [9u8; 32], [4u8; 32]);
+    let mut searcher = VanitySearcher::new(
+        &old_base,
+        &owner,
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let before = searcher.search_batch(1_000).unwrap();
+    let attempts = searcher.attempts();
+
+    let new_base58 = encode_pubkey(&new_base).unwrap();
+    searcher
+        .set_base_pubkey(new_base58.clone().into(), None)
+        .unwrap();
+    assert_eq!(searcher.attempts(), attempts);
+    let after = searcher.search_batch(1_000).unwrap();
+    assert_eq!(
+        before.base_pubkey(),
+        Some(encode_pubkey(&old_base).unwrap())
+    );
+    assert_eq!(after.base_pubkey(), Some(new_base58));
+    assert_eq!(after.owner_pubkey(), Some(encode_pubkey(&owner).unwrap()));
+    let rederive = |base: &[u8], result: &VanityResult| {
+        reproduce(base, &owner, result.counter().unwrap(), None, None)
+            .unwrap()
+            .address()
+    };
+    assert_eq!(rederive(&old_base, &before), before.address());
+    assert_eq!(rederive(&new_base, &after), after.address());
+    assert_ne!(rederive(&new_base, &before), before.address());
+
+    let plain = outcome_field(&before.to_json().unwrap().into(), "base_pubkey");
+    assert_eq!(plain.as_string(), before.base_pubkey());
+
+    searcher
+        .set_owner(js_sys::Uint8Array::from(&[5u8; 32][..]).into(), Some(true))
+        .unwrap();
+    assert_eq!(searcher.attempts(), 0);
+    let error = searcher
+        .set_base_pubkey(js_sys::Uint8Array::from(&[1u8; 31][..]).into(), None)
+        .unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "BadBaseLength");
+    let error = searcher.set_owner(5.into(), None).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOwner");
+    let found = searcher.search_batch(1_000).unwrap();
+    assert_eq!(found.owner_pubkey(), Some(encode_pubkey(&[5; 32]).unwrap()));
+}
+
+#[wasm_bindgen_test]
+fn rate_history_buckets_active_time() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(searcher.rate_history().length(), 0);
+    searcher.set_rate_history(0.01, Some(5)).unwrap();
+    while searcher.rate_history().length() < 5 {
+        searcher.search_batch(2_000);
+    }
+    let rates = searcher.rate_history().to_vec();
+    assert!(rates.iter().all(|rate| rate.is_finite() && *rate >= 0.0));
+    searcher.search_batch(2_000);
+    assert_eq!(searcher.rate_history().length(), 5);
+
+    searcher.clear_rate_history();
+    assert_eq!(searcher.rate_history().length(), 0);
+    let error = searcher.set_rate_history(1_000.0, Some(0)).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+    let too_many = max_rate_history_buckets() + 1;
+    assert!(searcher.set_rate_history(1_000.0, Some(too_many)).is_err());
+}
+
+#[wasm_bindgen_test]
+fn results_link_to_explorers_and_solana_pay() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let result = searcher.search_batch(1_000).unwrap();
+    let address = result.address();
+    assert_eq!(
+        result.explorer_url("devnet", "solanafm").unwrap(),
+        format!("https://solana.fm/address/{address}?cluster=devnet-solana")
+    );
+    assert_eq!(
+        result
+            .solana_pay_url(Some(0.25), Some("my vault".into()))
+            .unwrap(),
+        format!("solana:{address}?amount=0.25&label=my%20vault")
+    );
+
+    let error = result.explorer_url("localnet", "solscan").unwrap_err();
+    assert_eq!(error_kind(&error.into()), (28, "UnknownCluster".into()));
+    let error = result.explorer_url("mainnet", "etherscan").unwrap_err();
+    assert_eq!(error_kind(&error.into()), (29, "UnknownExplorer".into()));
+    let error = result.solana_pay_url(Some(-1.0), None).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn derive_batch_agrees_with_reproduce() {
+    let counters: Vec<u64> = (0..100_000u64).map(|i| i * 7_919 + (i << 40)).collect();
+    let records = derive_batch(&[1; 32], &[4; 32], &counters, Some("tab-1".into())).unwrap();
+    assert_eq!(records.length(), 100_000);
+    for i in [0, 1, 4_242, 99_999] {
+        let record = records.get(i);
+        let expected = reproduce(
+            &[1; 32],
+            &[4; 32],
+            counters[i as usize],
+            Some("tab-1".into()),
+            None,
+        )
+        .unwrap();
+        let field = |key| outcome_field(&record, key).as_string().unwrap();
+        assert_eq!(field("counter"), counters[i as usize].to_string());
+        assert_eq!(field("seed"), expected.seed());
+        assert_eq!(field("address"), expected.address());
+    }
+
+    let error = derive_batch(&[1; 31], &[4; 32], &[0], None).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "BadBaseLength");
+    let too_many = vec![0; 1_000_001];
+    let error = derive_batch(&[1; 32], &[4; 32], &too_many, None).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn approx_searcher_reports_edits() {
+    let mut searcher = VanityApproxSearcher::new(
+        &[1; 32],
+        &[4; 32],
+        None,
+        Some("Moon".into()),
+        2,
+        None,
+        false,
+        Some(7),
+    )
+    .unwrap();
+    assert!(searcher.expected_attempts() < 1_000_000.0);
+    let found = loop {
+        if let Some(found) = searcher.search_batch(10_000).unwrap() {
+            break JsValue::from(found);
+        }
+    };
+    let edits = outcome_field(&found, "edits").as_f64().unwrap();
+    assert!(edits <= 2.0);
+    let address = outcome_field(&found, "address").as_string().unwrap();
+    let matching = address
+        .bytes()
+        .rev()
+        .zip("Moon".bytes().rev())
+        .filter(|(a, b)| a == b)
+        .count();
+    assert_eq!(4 - matching, edits as usize, "{address}");
+    assert_eq!(
+        outcome_field(&found, "attempts").as_f64(),
+        Some(searcher.attempts() as f64)
+    );
+
+    let new = |prefix: Option<&str>, suffix: Option<&str>, max_edits| {
+        VanityApproxSearcher::new(
+            &[1; 32],
+            &[4; 32],
+            prefix.map(Into::into),
+            suffix.map(Into::into),
+            max_edits,
+            Some(true),
+            false,
+            None,
+        )
+    };
+    let kind = |result: Result<VanityApproxSearcher, VanityJsError>| match result {
+        Ok(_) => panic!("expected an error"),
+        Err(error) => error_kind(&error.into()).1,
+    };
+    assert_eq!(
+        kind(new(Some("Moon"), Some("Moon"), 1)),
+        "ConflictingOptions"
+    );
+    assert_eq!(kind(new(None, None, 1)), "EmptyPattern");
+    assert_eq!(kind(new(Some("Moo"), None, 3)), "InvalidOptions");
+    assert!(new(Some("Moo"), None, 2).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn tiered_searcher_falls_back_after_its_threshold() {
+    let tiers = |json: &str| js_sys::JSON::parse(json).unwrap();
+    let mut searcher = VanityTieredSearcher::new(
+        &[1; 32],
+        &[4; 32],
+        tiers(
+            r#"[{"pattern":{"label":"ideal","prefix":"zzzzzzzz"}},
+                {"pattern":{"label":"","prefix":"A"},"after_attempts":2000}]"#,
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(searcher.active_tiers(), 1);
+    assert_eq!(searcher.next_tier_at(), Some(2000));
+    assert!(searcher.search_batch(2000).unwrap().is_none());
+    let found = loop {
+        if let Some(found) = searcher.search_batch(1000).unwrap() {
+            break JsValue::from(found);
+        }
+    };
+    assert_eq!(outcome_field(&found, "tier").as_f64(), Some(1.0));
+    assert_eq!(
+        outcome_field(&found, "label").as_string().unwrap(),
+        "prefix:A"
+    );
+    assert_eq!(outcome_field(&found, "active_tiers").as_f64(), Some(2.0));
+    assert!(outcome_field(&found, "counter").as_f64().unwrap() >= 2000.0);
+    assert_eq!(searcher.next_tier_at(), None);
+
+    let error = |json: &str| match VanityTieredSearcher::new(&[1; 32], &[4; 32], tiers(json), None)
+    {
+        Ok(_) => panic!("expected an error for {json}"),
+        Err(error) => error_kind(&error.into()).1,
+    };
+    assert_eq!(error("[]"), "InvalidOptions");
+    assert_eq!(
+        error(r#"[{"pattern":{"label":"a","prefix":"A"},"after_attempts":5}]"#),
+        "InvalidOptions"
+    );
+    assert_eq!(
+        error(r#"[{"pattern":{"label":"a","prefix":"0"}}]"#),
+        "InvalidPatternList"
+    );
+    assert_eq!(error(r#"[{"prefix":"A"}]"#), "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn results_round_trip_through_json() {
+    // Past 2^53, where a JSON number would round to another counter.
+    let counter = (1u64 << 60) + 3;
+    let found = reproduce(&[1; 32], &[4; 32], counter, Some("worker-2".into()), None).unwrap();
+    let seed = vec![0xff, 0x00, b'A', 0xc3, 0x28, 0xf0];
+    let pubkey = vanity_core::Deriver::new(&[1; 32], &[4; 32]).derive(&seed);
+    let binary = VanityResult::from_seed_bytes(
+        vanity_core::Pubkey::new(pubkey).to_string(),
+        seed.clone(),
+        0,
+        Some(u64::MAX),
+    );
+    // A result built directly has no keys; add the ones it was derived
+    // with, as a worker's message would carry them.
+    let parsed = JsValue::from(binary.to_json().unwrap());
+    for (key, value) in [
+        ("base_pubkey", found.base_pubkey()),
+        ("owner_pubkey", found.owner_pubkey()),
+    ] {
+        js_sys::Reflect::set(&parsed, &key.into(), &value.unwrap().into()).unwrap();
+    }
+    let binary = VanityResult::from_js(parsed).unwrap();
+    assert_eq!(binary.seed_bytes(), seed);
+    assert_eq!(binary.counter(), Some(u64::MAX));
+    assert!(binary.verify().unwrap());
+
+    for result in [found, binary] {
+        let json = js_sys::JSON::stringify(&JsValue::from(result.to_json().unwrap())).unwrap();
+        let back = VanityResult::from_js(js_sys::JSON::parse(&json.as_string().unwrap()).unwrap())
+            .unwrap();
+        assert_eq!(back.address(), result.address());
+        assert_eq!(back.seed_bytes(), result.seed_bytes());
+        assert_eq!(back.attempts(), result.attempts());
+        assert_eq!(back.counter(), result.counter());
+        assert_eq!(back.seed_namespace(), result.seed_namespace());
+        assert_eq!(back.base_pubkey(), result.base_pubkey());
+        assert_eq!(back.owner_pubkey(), result.owner_pubkey());
+        assert!(back.verify().unwrap());
+    }
+
+    let error = VanityResult::from_js(js_sys::JSON::parse(r#"{"address":"x"}"#).unwrap());
+    assert_eq!(error_kind(&error.err().unwrap().into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn replay_logs_pinpoint_divergent_derivation() {
+    let new = |owner: u8| {
+        let mut searcher = VanitySearcher::new(
+            &[1; 32],
+            &[owner; 32],
+            Some("zzzzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        searcher.set_replay_log(100, 7).unwrap();
+        searcher
+    };
+    let mut reference = new(4);
+    assert!(reference.search_batch(700).is_none());
+    let log = reference.replay_log();
+    assert_eq!(log.len(), 100 * 16);
+    assert_eq!(u64::from_le_bytes(log[16..24].try_into().unwrap()), 7);
+
+    let mut drifting = new(4);
+    drifting.search_batch(300);
+    drifting
+        .set_owner(
+            JsValue::from(js_sys::Uint8Array::from(&[5u8; 32][..])),
+            Some(false),
+        )
+        .unwrap();
+    drifting.search_batch(400);
+    let comparison = JsValue::from(compare_replay(&log, &drifting.replay_log()).unwrap());
+    assert_eq!(outcome_field(&comparison, "compared").as_f64(), Some(100.0));
+    assert_eq!(
+        outcome_field(&comparison, "first_divergent_counter").as_f64(),
+        Some(301.0)
+    );
+    let same = JsValue::from(compare_replay(&log, &log).unwrap());
+    assert!(outcome_field(&same, "first_divergent_counter").is_undefined());
+
+    reference.clear_replay_log();
+    assert!(reference.replay_log().is_empty());
+    let error = compare_replay(&log[..15], &log).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn charset_seed_search_ends_when_every_seed_is_tried() {
+    // 2^5 = 32 seeds, far too few for the pattern.
+    let mut searcher = VanityCharsetSeedSearcher::new(
+        &[1; 32],
+        &[4; 32],
+        "ab".into(),
+        5,
+        None,
+        Some("zzzz".into()),
+        false,
+        None,
+    )
+    .unwrap();
+    assert_eq!(searcher.seed_space(), Some(32));
+    assert!(searcher
+        .seed_space_warning()
+        .unwrap()
+        .contains("only 32 seeds"));
+
+    let outcome: JsValue = searcher.search_batch_outcome(20).unwrap().into();
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "not_found"
+    );
+    let progress: JsValue = searcher.progress().unwrap().into();
+    assert_eq!(
+        outcome_field(&progress, "seed_space_coverage").as_f64(),
+        Some(20.0 / 32.0)
+    );
+
+    let outcome: JsValue = searcher.search_batch_outcome(1_000).unwrap().into();
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "exhausted"
+    );
+    assert_eq!(outcome_field(&outcome, "attempts").as_f64(), Some(12.0));
+    assert_eq!(searcher.attempts(), 32);
+    assert!(searcher.is_exhausted());
+    let progress: JsValue = searcher.progress().unwrap().into();
+    assert_eq!(
+        outcome_field(&progress, "seed_space_coverage").as_f64(),
+        Some(1.0)
+    );
+
+    let (_, kind) = error_kind(
+        &VanityCharsetSeedSearcher::new(
+            &[1; 32],
+            &[4; 32],
+            "aa".into(),
+            5,
+            None,
+            Some("z".into()),
+            false,
+            None,
+        )
+        .err()
+        .unwrap()
+        .into(),
+    );
+    assert_eq!(kind, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn match_callback_can_stop_through_its_control() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let callback = js_sys::Function::new_with_args("result, control", "control.stop();");
+    searcher.set_match_callback(callback);
+    assert_eq!(searcher.collect_batch(100_000), 1);
+    assert!(searcher.is_stopped());
+    assert!(searcher.callback_error().is_undefined());
+    assert!(!searcher.control().stop_requested());
+
+    // Usable again after a restart, with the next match.
+    searcher.restart();
+    assert_eq!(searcher.collect_batch(100_000), 1);
+    assert!(searcher.is_stopped());
+    assert_eq!(searcher.pending_results(), 2);
+
+    // A stop requested between batches applies to the next one.
+    searcher.clear_match_callback();
+    searcher.restart();
+    searcher.control().stop();
+    assert_eq!(searcher.collect_batch(100_000), 0);
+    assert!(searcher.is_stopped());
+}
+
+#[wasm_bindgen_test]
+fn reentrant_calls_from_callbacks_fail_cleanly() {
+    let searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    // Through JS, as an app would hold it, so the callback can reach it.
+    let searcher = JsValue::from(searcher);
+    let global = js_sys::global();
+    js_sys::Reflect::set(&global, &"reentrantSearcher".into(), &searcher).unwrap();
+    let call = |method: &str, args: &[JsValue]| {
+        let method: js_sys::Function = js_sys::Reflect::get(&searcher, &method.into())
+            .unwrap()
+            .unchecked_into();
+        method.apply(&searcher, &args.iter().collect()).unwrap()
+    };
+
+    let callback =
+        js_sys::Function::new_with_args("result", "globalThis.reentrantSearcher.search_batch(10);");
+    call("set_match_callback", &[callback.into()]);
+    assert_eq!(call("collect_batch", &[100_000.into()]).as_f64(), Some(1.0));
+    assert_eq!(call("is_stopped", &[]).as_bool(), Some(true));
+    let error = js_sys::Reflect::get(&searcher, &"callback_error".into()).unwrap();
+    assert_eq!(error_kind(&error), (30, "ReentrantCall".into()));
+
+    // Nothing was left borrowed: the searcher goes on after a restart.
+    call("clear_match_callback", &[]);
+    call("restart", &[]);
+    assert!(js_sys::Reflect::get(&searcher, &"callback_error".into())
+        .unwrap()
+        .is_undefined());
+    let result = call("search_batch", &[100_000.into()]);
+    let address = js_sys::Reflect::get(&result, &"address".into()).unwrap();
+    assert!(address.as_string().unwrap().starts_with('A'));
+    assert_eq!(call("pending_results", &[]).as_f64(), Some(1.0));
+    js_sys::Reflect::delete_property(
+        global.unchecked_ref::<js_sys::Object>(),
+        &"reentrantSearcher".into(),
+    )
+    .unwrap();
+}
+
+#[wasm_bindgen_test]
+fn base58_pubkey_helpers() {
+    for fill in [0u8, 1, 4, 255] {
+        let bytes = [fill; 32];
+        let encoded = encode_pubkey(&bytes).unwrap();
+        assert!(is_valid_base58(&encoded));
+        assert_eq!(decode_pubkey(&encoded).unwrap(), bytes);
+    }
+    let error: JsValue = encode_pubkey(&[1; 31]).unwrap_err().into();
+    assert_eq!(error_kind(&error), (20, "BadPubkeyLength".into()));
+
+    let error: JsValue = decode_pubkey("AbcO").unwrap_err().into();
+    assert_eq!(error_kind(&error), (19, "InvalidBase58".into()));
+    let position = js_sys::Reflect::get(&error, &"position".into()).unwrap();
+    assert_eq!(position.as_f64(), Some(3.0));
+    let error: JsValue = decode_pubkey("Abc").unwrap_err().into();
+    assert_eq!(error_kind(&error).1, "BadPubkeyLength");
+
+    assert!(is_valid_base58("Abc"));
+    assert!(!is_valid_base58("Ab0"));
+    assert!(!is_valid_base58(""));
+}
+
+#[wasm_bindgen_test]
+fn short_seed_searcher_finds_a_short_seed() {
+    let mut searcher =
+        VanityShortSeedSearcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, None)
+            .unwrap();
+    assert_eq!(searcher.current_seed_len(), Some(1));
+    let found = loop {
+        if let Some(found) = searcher.search_batch(100).unwrap() {
+            break JsValue::from(found);
+        }
+    };
+    let seed = outcome_field(&found, "seed").as_string().unwrap();
+    assert_eq!(
+        outcome_field(&found, "seed_len").as_f64(),
+        Some(seed.len() as f64)
+    );
+    assert!(seed.len() <= 2);
+    assert!(outcome_field(&found, "address")
+        .as_string()
+        .unwrap()
+        .starts_with('A'));
+
+    let estimate = JsValue::from(estimate_short_seed(Some("abcdefg".into()), None, false).unwrap());
+    assert!(outcome_field(&estimate, "warning").is_string());
+}
+
+fn work_ranges(spans: &[(f64, f64)]) -> JsValue {
+    spans
+        .iter()
+        .map(|&(offset, length)| {
+            let range = js_sys::Object::new();
+            js_sys::Reflect::set(&range, &"offset".into(), &offset.into()).unwrap();
+            js_sys::Reflect::set(&range, &"length".into(), &length.into()).unwrap();
+            JsValue::from(range)
+        })
+        .collect::<js_sys::Array>()
+        .into()
+}
+
+#[wasm_bindgen_test]
+fn audit_ranges_reports_overlaps_and_gaps() {
+    let ranges = work_ranges(&[(0.0, 100.0), (50.0, 100.0), (300.0, 10.0), (0.0, 100.0)]);
+    let audit = JsValue::from(audit_ranges(ranges.clone()).unwrap());
+    let overlaps = js_sys::Array::from(&outcome_field(&audit, "overlaps"));
+    assert_eq!(overlaps.length(), 3);
+    let first = overlaps.get(0);
+    assert_eq!(outcome_field(&first, "second").as_f64(), Some(3.0));
+    assert_eq!(outcome_field(&first, "length").as_f64(), Some(100.0));
+    let gaps = js_sys::Array::from(&outcome_field(&audit, "gaps"));
+    assert_eq!(outcome_field(&gaps.get(0), "offset").as_f64(), Some(150.0));
+    assert_eq!(outcome_field(&audit, "covered").as_f64(), Some(160.0));
+
+    let merged = merge_ranges(ranges).unwrap();
+    assert_eq!(merged.length(), 2);
+    assert_eq!(outcome_field(&merged.get(1), "length").as_f64(), Some(10.0));
+
+    for bad in [
+        work_ranges(&[(2f64.powi(53), 1.0)]),
+        JsValue::from("nope"),
+        work_ranges(&[(-1.0, 1.0)]),
+    ] {
+        let error = audit_ranges(bad).unwrap_err();
+        assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+    }
+}
+
+#[wasm_bindgen_test]
+fn byte_mask_searcher_matches_raw_bytes() {
+    let mut searcher =
+        VanitySearcher::with_byte_mask(&[1; 32], &[4; 32], &[0xff], &[0x00], None, false, 0)
+            .unwrap();
+    let result = loop {
+        if let Some(result) = searcher.search_batch(1000) {
+            break result;
+        }
+    };
+    let pubkey = decode_pubkey(&result.address()).unwrap();
+    assert_eq!(pubkey[0], 0);
+    // A leading zero byte encodes as a leading '1'.
+    assert!(result.address().starts_with('1'));
+
+    let error =
+        VanitySearcher::with_byte_mask(&[1; 32], &[4; 32], &[0xff; 2], &[0], None, false, 0)
+            .err()
+            .unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidByteMask");
+}
+
+#[wasm_bindgen_test]
+fn capabilities_report_the_environment() {
+    let capabilities = JsValue::from(capabilities().unwrap());
+    for flag in [
+        "simd_compiled",
+        "simd_supported",
+        "threads_compiled",
+        "shared_array_buffer",
+        "cross_origin_isolated",
+        "webgpu",
+    ] {
+        let value = outcome_field(&capabilities, flag);
+        assert!(value.as_bool().is_some(), "{flag}");
+    }
+    assert!(outcome_field(&capabilities, "version").is_string());
+    assert!(outcome_field(&capabilities, "features").is_array());
+    assert!(outcome_field(&capabilities, "hardware_concurrency")
+        .as_f64()
+        .is_some());
+    let backends = js_sys::Array::from(&outcome_field(&capabilities, "backends"));
+    assert_eq!(backends.get(0).as_string().as_deref(), Some("scalar"));
+
+    let preference = Ts::new_unchecked(JsValue::from("threads"));
+    let selection = JsValue::from(select_backend(Some(preference)).unwrap());
+    assert_eq!(
+        outcome_field(&selection, "active").as_string().as_deref(),
+        Some("scalar")
+    );
+    assert!(outcome_field(&selection, "fallback_reason").is_string());
+    let bad = Ts::new_unchecked(JsValue::from("quantum"));
+    let error = select_backend(Some(bad)).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+async fn matches_iterate_with_for_await() {
+    let searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let options = Ts::new_unchecked(js_sys::JSON::parse(r#"{"batch_size":1000}"#).unwrap());
+    let iterator = searcher.matches(Some(options)).unwrap();
+
+    let consume = js_sys::Function::new_with_args(
+        "matches",
+        "return (async () => {
+            const found = [];
+            for await (const result of matches) {
+                found.push([result.address, Number(result.attempts)]);
+                if (found.length === 2) break;
+            }
+            return [found, await matches.next()];
+        })();",
+    );
+    let promise = consume.call1(&JsValue::NULL, &iterator).unwrap();
+    let output = wasm_bindgen_futures::JsFuture::from(js_sys::Promise::from(promise))
+        .await
+        .unwrap();
+    let output = js_sys::Array::from(&output);
+    let found = js_sys::Array::from(&output.get(0));
+    assert_eq!(found.length(), 2);
+    let mut last_attempts = 0.0;
+    for entry in found.iter() {
+        let entry = js_sys::Array::from(&entry);
+        assert!(entry.get(0).as_string().unwrap().starts_with('A'));
+        let attempts = entry.get(1).as_f64().unwrap();
+        assert!(attempts > last_attempts);
+        last_attempts = attempts;
+    }
+    // `break` called return(), so the searcher is gone.
+    assert_eq!(outcome_field(&output.get(1), "done").as_bool(), Some(true));
+}
+
+#[wasm_bindgen_test]
+fn matcher_reads_js_options_and_explains() {
+    let options = r#"{"prefix":"So","suffix":"pump","case_insensitive":true}"#;
+    let matcher = Matcher::new(Ts::new_unchecked(js_sys::JSON::parse(options).unwrap())).unwrap();
+    assert!(matcher.matches("SOxyzPump"));
+    assert!(!matcher.matches("Sxyzpump"));
+    let explanation: JsValue = matcher.explain("Sxyzpump").unwrap().into();
+    assert_eq!(
+        outcome_field(&explanation, "matches").as_bool(),
+        Some(false)
+    );
+    let checks = js_sys::Array::from(&outcome_field(&explanation, "checks"));
+    assert_eq!(checks.length(), 2);
+    assert_eq!(
+        outcome_field(&checks.get(0), "part").as_string().unwrap(),
+        "prefix"
+    );
+    assert_eq!(
+        outcome_field(&checks.get(0), "matched_chars").as_f64(),
+        Some(1.0)
+    );
+    assert_eq!(
+        outcome_field(&checks.get(1), "passed").as_bool(),
+        Some(true)
+    );
+
+    let bad = Ts::new_unchecked(js_sys::JSON::parse(r#"{"prefix":"S0L"}"#).unwrap());
+    let error = Matcher::new(bad).err().unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidPatternChar");
+}
+
+#[wasm_bindgen_test]
+fn trace_agrees_with_search_and_reproduce() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    searcher.namespace_seeds(Some("docs".into())).unwrap();
+    let found = searcher.search_batch(10_000).unwrap();
+    let counter = found.counter().unwrap();
+    let trace: JsValue = searcher.trace(counter).unwrap().into();
+    let field = |key: &str| outcome_field(&trace, key).as_string().unwrap();
+    assert_eq!(field("address"), found.address());
+    assert_eq!(field("seed"), found.seed());
+    assert_eq!(field("counter"), counter.to_string());
+    assert_eq!(field("hash_input_hex").len(), 160);
+    assert_eq!(field("digest_hex").len(), 64);
+    assert_eq!(outcome_field(&trace, "matches").as_bool(), Some(true));
+    let timing = outcome_field(&trace, "timing");
+    assert!(outcome_field(&timing, "derive_ms").as_f64().unwrap() >= 0.0);
+
+    let again = reproduce(&[1; 32], &[4; 32], counter, found.seed_namespace(), None).unwrap();
+    assert_eq!(field("address"), again.address());
+    assert_eq!(field("seed"), again.seed());
+}
+
+#[wasm_bindgen_test]
+fn attempt_quantiles_translate_into_time() {
+    let options = Ts::new_unchecked(js_sys::JSON::parse(r#"{"suffix":"2"}"#).unwrap());
+    let quantiles: JsValue = attempt_quantiles(options, Some(10.0)).unwrap().into();
+    let rows = js_sys::Array::from(&outcome_field(&quantiles, "quantiles"));
+    assert_eq!(rows.length(), 3);
+    let row = |i: u32, key: &str| outcome_field(&rows.get(i), key).as_f64().unwrap();
+    assert_eq!((row(0, "confidence"), row(0, "attempts")), (0.5, 40.0));
+    assert_eq!((row(2, "confidence"), row(2, "attempts")), (0.99, 265.0));
+    assert_eq!(row(1, "seconds"), 13.3);
+    assert_eq!(
+        outcome_field(&quantiles, "expected_attempts").as_f64(),
+        Some(58.0)
+    );
+
+    let bad = Ts::new_unchecked(js_sys::JSON::parse(r#"{"prefix":5}"#).unwrap());
+    let error = attempt_quantiles(bad, None).err().unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+fn sleep_ms(ms: i32) -> wasm_bindgen_futures::JsFuture {
+    let promise = js_sys::Function::new_with_args(
+        "ms",
+        "return new Promise((resolve) => setTimeout(resolve, ms));",
+    )
+    .call1(&JsValue::NULL, &ms.into())
+    .unwrap();
+    wasm_bindgen_futures::JsFuture::from(js_sys::Promise::from(promise))
+}
+
+// Spawns real workers, so it only does anything in a browser
+// (`npm run test:wasm:browser`); Node has no `Worker`.
+#[wasm_bindgen_test]
+async fn worker_pool_keeps_one_match_and_stops_every_worker() {
+    let worker = js_sys::Reflect::get(&js_sys::global(), &"Worker".into()).unwrap();
+    if worker.is_undefined() {
+        return;
+    }
+    let matches = js_sys::Array::new();
+    let on_match = js_sys::Function::new_with_args(
+        "matches",
+        "return (result) => { matches.push(result.address); };",
+    )
+    .call1(&JsValue::NULL, &matches)
+    .unwrap();
+    // The test runner serves the module under its own name.
+    let module_url = js_sys::Function::new_no_args(
+        "return new URL('wasm-bindgen-test.js', location.href).href;",
+    )
+    .call0(&JsValue::NULL)
+    .unwrap()
+    .as_string()
+    .unwrap();
+    let options = format!(
+        r#"{{"base_pubkey":{:?},"owner_pubkey":{:?},"pattern":{{"prefix":"A"}},
+            "workers":2,"batch_size":500,"module_url":"{module_url}"}}"#,
+        [1u8; 32], [4u8; 32]
+    );
+    let options = Ts::new_unchecked(js_sys::JSON::parse(&options).unwrap());
+    let pool = WorkerPool::new(options, on_match.into()).unwrap();
+    assert_eq!(pool.workers(), 2);
+
+    for _ in 0..500 {
+        if pool.running() == 0 {
+            break;
+        }
+        sleep_ms(20).await.unwrap();
+    }
+    assert_eq!(pool.running(), 0);
+    assert!(pool.found());
+    assert!(pool.attempts() > 0);
+    assert_eq!(matches.length(), 1);
+    assert!(matches.get(0).as_string().unwrap().starts_with('A'));
+}
+
+#[wasm_bindgen_test]
+fn sampling_ring_drains_rederivable_addresses() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("zzzz".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(searcher.drain_samples().length(), 0);
+    searcher.set_sampling(3, 100).unwrap();
+    searcher.search_batch(1_000);
+
+    // Counters 0, 100, ..., 900 were sampled; the ring kept the last 3.
+    let drained = searcher.drain_samples();
+    assert_eq!(drained.length(), 3);
+    for (i, address) in drained.iter().enumerate() {
+        let sample = searcher
+            .sample_addresses(1, Some(700 + 100 * i as u64))
+            .unwrap();
+        assert_eq!(address, outcome_field(&sample.get(0), "address"));
+    }
+    assert_eq!(searcher.drain_samples().length(), 0);
+
+    searcher.clear_sampling();
+    searcher.search_batch(1_000);
+    assert_eq!(searcher.drain_samples().length(), 0);
+    let error = searcher.set_sampling(0, 1).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn clean_prefix_filters_matches_and_reports_its_set() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        None,
+        Some("x".into()),
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(searcher.confusables().unwrap().is_none());
+    searcher.set_clean_prefix(6, None, None).unwrap();
+    let set = JsValue::from(searcher.confusables().unwrap().unwrap());
+    assert_eq!(
+        outcome_field(&set, "chars").as_string().as_deref(),
+        Some(vanity_core::CLEAN_PREFIX_CHARS)
+    );
+    assert_eq!(
+        js_sys::Array::from(&outcome_field(&set, "pairs")).length(),
+        3
+    );
+    let clean = vanity_core::CleanPrefix::with_default_set(6).unwrap();
+    let found = searcher.search_batch(100_000).unwrap();
+    assert!(clean.is_clean(&found.address()));
+
+    searcher
+        .set_clean_prefix(2, Some("A".into()), Some(vec!["bc".into()]))
+        .unwrap();
+    let set = JsValue::from(searcher.confusables().unwrap().unwrap());
+    assert_eq!(
+        outcome_field(&set, "chars").as_string().as_deref(),
+        Some("A")
+    );
+    let error = searcher
+        .set_clean_prefix(2, None, Some(vec!["b".into()]))
+        .unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+    searcher.clear_clean_prefix();
+    assert!(searcher.confusables().unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+fn char_distribution_counts_every_sample() {
+    let distribution = JsValue::from(char_distribution(0, 500).unwrap());
+    let counts = js_sys::Array::from(&outcome_field(&distribution, "counts"));
+    assert_eq!(counts.length(), 58);
+    let total: f64 = counts
+        .iter()
+        .map(|count| outcome_field(&count, "count").as_f64().unwrap())
+        .sum();
+    assert_eq!(total, 500.0);
+    let first = counts.get(0);
+    assert_eq!(
+        outcome_field(&first, "character").as_string().as_deref(),
+        Some("1")
+    );
+    assert!(outcome_field(&first, "expected").as_f64().is_some());
+    let error = char_distribution(44, 1).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn eviction_callback_sees_every_dropped_result() {
+    let mut plain = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let expected: Vec<u64> = (0..6)
+        .map(|_| plain.search_batch(100_000).unwrap().attempts())
+        .collect();
+    for (policy, dropped, kept) in [
+        ("evict_oldest", &expected[..4], &expected[4..]),
+        ("reject_new", &expected[2..], &expected[..2]),
+    ] {
+        let mut searcher = VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("A".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let callback = {
+            let seen = seen.clone();
+            Closure::<dyn FnMut(VanityResult)>::new(move |result: VanityResult| {
+                seen.borrow_mut().push(result.attempts());
+            })
+        };
+        searcher.set_eviction_callback(
+            callback
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone(),
+        );
+        searcher
+            .set_queue_overflow(Ts::new_unchecked(policy.into()))
+            .unwrap();
+        searcher.set_high_water_mark(2).unwrap();
+        searcher.collect_batch(expected[5] as u32);
+        assert_eq!(*seen.borrow(), dropped, "{policy}");
+        let taken: Vec<u64> = searcher
+            .take_results(None)
+            .iter()
+            .map(|result| {
+                let attempts = outcome_field(&result, "attempts");
+                u64::try_from(attempts.unchecked_into::<js_sys::BigInt>()).unwrap()
+            })
+            .collect();
+        assert_eq!(taken, kept, "{policy}");
+        assert_eq!(searcher.results_found(), 6);
+        assert_eq!(searcher.results_dropped(), 4);
+    }
+
+    let mut searcher = plain;
+    let error = searcher
+        .set_queue_overflow(Ts::new_unchecked("drop_all".into()))
+        .unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn match_callback_pauses_collection() {
+    let mut searcher = VanitySearcher::new(
+        &[1; 32],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    )
+    .unwrap();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let callback = {
+        let seen = seen.clone();
+        Closure::<dyn FnMut(VanityResult) -> bool>::new(move |result: VanityResult| {
+            seen.borrow_mut().push(result.attempts());
+            // Pause after the second match.
+            seen.borrow().len() != 2
+        })
+    };
+    searcher.set_match_callback(
+        callback
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    );
+    assert_eq!(searcher.collect_batch(100_000), 2);
+    assert!(searcher.is_paused());
+    assert_eq!(searcher.collect_batch(100_000), 0);
+
+    searcher.resume();
+    searcher.set_high_water_mark(3).unwrap();
+    assert_eq!(searcher.collect_batch(100_000), 1);
+    assert!(searcher.is_paused());
+    assert_eq!(searcher.pending_results(), 3);
+    let taken = searcher.take_results(Some(2));
+    assert_eq!(taken.length(), 2);
+    assert!(!searcher.is_paused());
+    assert_eq!(searcher.pending_results(), 1);
+    assert_eq!(seen.borrow().len(), 3);
+    let error = searcher.set_high_water_mark(0).unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[wasm_bindgen_test]
+fn internal_errors_reach_the_error_callback() {
+    let reports = js_sys::Array::new();
+    let record = js_sys::Function::new_with_args("reports", "return r => { reports.push(r); };")
+        .call1(&JsValue::NULL, &reports)
+        .unwrap();
+    set_error_callback(Some(record.unchecked_into()));
+
+    // Bad input throws but isn't reported.
+    let bad = VanitySearcher::new(
+        &[1; 31],
+        &[4; 32],
+        Some("A".into()),
+        None,
+        false,
+        0,
+        None,
+        None,
+    );
+    assert!(bad.is_err());
+    assert_eq!(reports.length(), 0);
+
+    // Attempts past 2^53 can't be serialized: an internal error.
+    let result = VanityResult::new("addr".into(), "seed".into(), 1 << 60, None).unwrap();
+    let error = result.to_json().unwrap_err();
+    assert_eq!(error_kind(&error.into()).1, "Internal");
+    set_error_callback(None);
+
+    assert_eq!(reports.length(), 1);
+    let report = reports.get(0);
+    assert_eq!(
+        outcome_field(&report, "source").as_string().as_deref(),
+        Some("internal")
+    );
+    assert_eq!(outcome_field(&report, "code").as_f64(), Some(15.0));
+    assert_eq!(
+        outcome_field(&report, "kind").as_string().as_deref(),
+        Some("Internal")
+    );
+    assert_eq!(
+        outcome_field(&report, "searcher_usable").as_bool(),
+        Some(true)
+    );
+}
+
+#[cfg(feature = "metadata")]
+#[wasm_bindgen_test]
+fn metadata_searcher_reads_js_options() {
+    let options = js_sys::JSON::parse(
+        r#"{"mint":{"prefix":"a","case_insensitive":true},"metadata":{"prefix":"B"},"require":"either"}"#,
+    )
+    .unwrap();
+    let secret = js_sys::Uint8Array::from(&[7u8; 32][..]);
+    js_sys::Reflect::set(&options, &"master_secret".into(), &secret).unwrap();
+    let mut searcher = VanityMetadataSearcher::new(Ts::new_unchecked(options)).unwrap();
+    assert_eq!(searcher.master_secret(), vec![7; 32]);
+    let found = loop {
+        if let Some(found) = searcher.search_batch(100).unwrap() {
+            break JsValue::from(found);
+        }
+    };
+    let mint_matches = outcome_field(&found, "mint_matches").as_bool().unwrap();
+    let metadata_matches = outcome_field(&found, "metadata_matches").as_bool().unwrap();
+    assert!(mint_matches || metadata_matches);
+    assert!(outcome_field(&found, "mint_secret").is_string());
+
+    let bad = js_sys::JSON::parse(r#"{"mint":{"prefix":"A"},"require":"all"}"#).unwrap();
+    let Err(error) = VanityMetadataSearcher::new(Ts::new_unchecked(bad)) else {
+        panic!("unknown requirement accepted");
+    };
+    assert_eq!(error_kind(&error.into()).1, "InvalidOptions");
+}
+
+#[cfg(feature = "token2022")]
+#[wasm_bindgen_test]
+fn token2022_searcher_emits_instruction_params() {
+    let pointer = token2022_extensions()
+        .unwrap()
+        .iter()
+        .find(|info| outcome_field(info, "extension").as_string().unwrap() == "metadata_pointer")
+        .unwrap();
+    let options = js_sys::JSON::parse(r#"{"prefix":"A"}"#).unwrap();
+    js_sys::Reflect::set(
+        &options,
+        &"extensions".into(),
+        &outcome_field(&pointer, "bit"),
+    )
+    .unwrap();
+    let secret = js_sys::Uint8Array::from(&[5u8; 32][..]);
+    js_sys::Reflect::set(&options, &"master_secret".into(), &secret).unwrap();
+    let mut searcher = VanityToken2022Searcher::new(Ts::new_unchecked(options)).unwrap();
+    let found = loop {
+        if let Some(found) = searcher.search_batch(100) {
+            break found;
+        }
+    };
+    let payer = "11111111111111111111111111111111";
+    let params = JsValue::from(found.instruction_params(payer, payer, 9, None).unwrap());
+    let create = outcome_field(&params, "createAccount");
+    assert_eq!(outcome_field(&create, "space").as_f64(), Some(234.0));
+    assert_eq!(
+        outcome_field(&create, "newAccountPubkey").as_string(),
+        Some(found.mint())
+    );
+    let init = outcome_field(&params, "initializeMint2");
+    assert_eq!(outcome_field(&init, "decimals").as_f64(), Some(9.0));
+    assert!(outcome_field(&init, "freezeAuthority").is_undefined());
+
+    let error = found
+        .instruction_params("0OIl", payer, 9, None)
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(&error.into()).1, "InvalidBase58");
+}
+
+#[wasm_bindgen_test]
+fn one_cancel_token_stops_two_searchers() {
+    let token = CancelToken::new();
+    let new = || {
+        VanitySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzzz".into()),
+            None,
+            false,
+            0,
+            None,
+            None,
+        )
+        .unwrap()
+    };
+    let mut first = new();
+    first.set_cancel_token(&token);
+    let mut second = VanitySearcherBuilder::new()
+        .base(vec![1; 32])
+        .owner(vec![4; 32])
+        .prefix("zzzzz".into())
+        .count_offset(1 << 40)
+        .cancel_token(&token)
+        .build()
+        .unwrap();
+    second.search_batch(1_000);
+
+    // Cancelled from the first searcher's third heartbeat; the stop lands
+    // on the poll after it.
+    let beats = Rc::new(RefCell::new(0));
+    let (counted, cancelling) = (beats.clone(), token.clone());
+    let closure = Closure::<dyn FnMut(JsValue)>::new(move |_: JsValue| {
+        *counted.borrow_mut() += 1;
+        if *counted.borrow() == 3 {
+            cancelling.cancel();
+        }
+    });
+    first.set_heartbeat(closure.into_js_value().unchecked_into(), 0.0);
+    let outcome = JsValue::from(first.search_batch_outcome(1_000_000).unwrap());
+    assert!(token.is_cancelled());
+    assert_eq!(*beats.borrow(), 3);
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "stopped"
+    );
+    assert_eq!(
+        outcome_field(&outcome, "attempts").as_f64(),
+        Some(3.0 * 4096.0)
+    );
+    assert_eq!(
+        outcome_field(&outcome, "last_counter").as_string().unwrap(),
+        (3 * 4096 - 1).to_string()
+    );
+    assert!(first.is_stopped());
+
+    let outcome = JsValue::from(second.search_batch_outcome(1_000_000).unwrap());
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "stopped"
+    );
+    assert_eq!(outcome_field(&outcome, "attempts").as_f64(), Some(0.0));
+    assert_eq!(second.attempts(), 1_000);
+
+    // Until the token is cleared, a restart stops again straight away.
+    second.restart();
+    second.search_batch(1_000);
+    assert!(second.is_stopped());
+    second.clear_cancel_token();
+    second.restart();
+    second.search_batch(1_000);
+    assert!(!second.is_stopped());
+}
+
+#[wasm_bindgen_test]
+fn unmatched_outcome_reports_the_closest_address() {
+    let mut searcher = VanitySearcherBuilder::new()
+        .base(vec![1; 32])
+        .owner(vec![4; 32])
+        .prefix("zzzz".into())
+        .max_attempts(2_000)
+        .partial_tracking(true)
+        .build()
+        .unwrap();
+    let outcome = JsValue::from(searcher.search_batch_outcome(1_000).unwrap());
+    let partial = outcome_field(&outcome, "partial");
+    let stats = outcome_field(&partial, "stats");
+    assert_eq!(outcome_field(&stats, "tracked").as_f64(), Some(1_000.0));
+    let outcome = JsValue::from(searcher.search_batch_outcome(5_000).unwrap());
+    assert_eq!(
+        outcome_field(&outcome, "status").as_string().unwrap(),
+        "exhausted"
+    );
+    let partial = outcome_field(&outcome, "partial");
+
+    // The same 2,000 addresses, prefix characters counted by hand.
+    let (mut best, mut near, mut closest) = (0, 0, None);
+    for start in [0, 1_000] {
+        let samples = searcher.sample_addresses(1_000, Some(start)).unwrap();
+        for (i, sample) in samples.iter().enumerate() {
+            let address = outcome_field(&sample, "address").as_string().unwrap();
+            let matched = address.bytes().take_while(|&b| b == b'z').count().min(4);
+            near += u32::from(matched == 3);
+            if closest.is_none() || matched > best {
+                closest = Some((address, start + i as u64));
+            }
+            best = best.max(matched);
+        }
+    }
+    let stats = outcome_field(&partial, "stats");
+    assert_eq!(outcome_field(&stats, "tracked").as_f64(), Some(2_000.0));
+    assert_eq!(
+        outcome_field(&stats, "closest_chars").as_f64(),
+        Some(best as f64)
+    );
+    let parts = js_sys::Array::from(&outcome_field(&stats, "parts"));
+    assert_eq!(parts.length(), 1);
+    let prefix = parts.get(0);
+    assert_eq!(
+        outcome_field(&prefix, "best_chars").as_f64(),
+        Some(best as f64)
+    );
+    assert_eq!(outcome_field(&prefix, "full").as_f64(), Some(0.0));
+    assert_eq!(
+        outcome_field(&prefix, "near_misses").as_f64(),
+        Some(near as f64)
+    );
+
+    let (address, counter) = closest.unwrap();
+    let result = outcome_field(&partial, "closest");
+    assert_eq!(outcome_field(&result, "address").as_string(), Some(address));
+    assert_eq!(
+        outcome_field(&result, "counter").as_string(),
+        Some(counter.to_string())
+    );
+    assert_eq!(
+        outcome_field(&result, "attempts").as_f64(),
+        Some(counter as f64 + 1.0)
+    );
+
+    searcher.set_partial_tracking(false);
+    assert!(searcher.partial().unwrap().is_undefined());
+    let outcome = JsValue::from(searcher.search_batch_outcome(10).unwrap());
+    assert!(outcome_field(&outcome, "partial").is_undefined());
+}
+
+fn find_options(entries: &[(&str, JsValue)]) -> Ts<FindOptions> {
+    let options = js_sys::Object::new();
+    let keys = [
+        (
+            "base_pubkey",
+            js_sys::Uint8Array::from(&[1u8; 32][..]).into(),
+        ),
+        (
+            "owner_pubkey",
+            js_sys::Uint8Array::from(&[4u8; 32][..]).into(),
+        ),
+    ];
+    for (key, value) in keys.iter().chain(entries) {
+        js_sys::Reflect::set(&options, &(*key).into(), value).unwrap();
+    }
+    Ts::new_unchecked(options.into())
+}
+
+fn find_limits(entries: &[(&str, JsValue)]) -> Option<Ts<FindLimits>> {
+    let limits = js_sys::Object::new();
+    for (key, value) in entries {
+        js_sys::Reflect::set(&limits, &(*key).into(), value).unwrap();
+    }
+    Some(Ts::new_unchecked(limits.into()))
+}
+
+#[wasm_bindgen_test]
+async fn find_one_packages_a_verified_match() {
+    let options = find_options(&[("prefix", "A".into()), ("space", 165.into())]);
+    let found = JsValue::from(find_one(options, None).await.unwrap());
+
+    // Everything checked from the packaged object alone.
+    let result = outcome_field(&found, "result");
+    let reparsed = VanityResult::from_js(result.clone()).unwrap();
+    assert!(reparsed.verify().unwrap());
+    let address = outcome_field(&result, "address").as_string().unwrap();
+    assert!(address.starts_with('A'));
+    let seed = outcome_field(&result, "seed").as_string().unwrap();
+    let pubkey = vanity_core::Deriver::new(&[1; 32], &[4; 32]).derive(seed.as_bytes());
+    assert_eq!(vanity_core::Pubkey::new(pubkey).to_string(), address);
+
+    let params = outcome_field(&found, "create_account_with_seed");
+    let field = |key| outcome_field(&params, key).as_string().unwrap();
+    let base = vanity_core::Pubkey::new([1; 32]).to_string();
+    assert_eq!(field("newAccountPubkey"), address);
+    assert_eq!(field("fromPubkey"), base);
+    assert_eq!(field("basePubkey"), base);
+    assert_eq!(field("seed"), seed);
+    assert_eq!(
+        field("programId"),
+        vanity_core::Pubkey::new([4; 32]).to_string()
+    );
+    let lamports = outcome_field(&params, "lamports").as_f64();
+    assert_eq!(
+        lamports,
+        Some(vanity_core::rent_exempt_lamports(165) as f64)
+    );
+
+    let stats = outcome_field(&found, "stats");
+    let attempts = outcome_field(&stats, "actual_attempts").as_f64();
+    assert_eq!(attempts, outcome_field(&result, "attempts").as_f64());
+}
+
+#[wasm_bindgen_test]
+async fn find_one_rejects_with_typed_errors() {
+    let reject = |options, limits| async move {
+        let error: JsValue = find_one(options, limits).await.unwrap_err().into();
+        let attempts = js_sys::Reflect::get(&error, &"attempts".into()).unwrap();
+        (error_kind(&error), attempts.as_f64())
+    };
+    let prefix = || find_options(&[("prefix", "zzzzz".into())]);
+
+    let (kind, attempts) = reject(prefix(), find_limits(&[("max_attempts", 300.into())])).await;
+    assert_eq!(kind, (31, "SearchExhausted".into()));
+    assert_eq!(attempts, Some(300.0));
+
+    let limits = find_limits(&[("max_ms", 0.into()), ("batch_size", 50.into())]);
+    let (kind, attempts) = reject(prefix(), limits).await;
+    assert_eq!(kind, (32, "SearchStopped".into()));
+    assert_eq!(attempts, Some(50.0));
+
+    let bad = find_options(&[("prefix", "A0".into())]);
+    let (kind, _) = reject(bad, None).await;
+    assert_eq!(kind, (2, "InvalidPatternChar".into()));
+    let bad = find_options(&[("prefix", "A".into()), ("payer", "n0pe".into())]);
+    assert_eq!(reject(bad, None).await.0 .0, 19);
+}
+
+#[wasm_bindgen_test]
+fn seed_scheme_descriptor_round_trips_and_reproduces() {
+    let descriptor = JsValue::from(seed_scheme_descriptor(None).unwrap());
+    let json = js_sys::JSON::stringify(&descriptor).unwrap();
+    let parsed = js_sys::JSON::parse(&String::from(json)).unwrap();
+    let parsed: vanity_core::SeedSchemeDescriptor = serde_wasm_bindgen::from_value(parsed).unwrap();
+    assert_eq!(parsed, vanity_core::SeedScheme::LATEST.descriptor());
+    assert_eq!(parsed.version, seed_scheme_version());
+    assert_eq!(parsed.charset, seed_charset());
+
+    for vector in &parsed.vectors {
+        let mut counter: u64 = vector.counter.parse().unwrap();
+        if let Some(tag) = &vector.namespace {
+            counter = vanity_core::SeedNamespace::new(tag).unwrap().apply(counter);
+        }
+        let seed = vanity_core::generate_seed_from_counter(counter);
+        assert_eq!(vector.seed.as_bytes(), seed);
+    }
+
+    let legacy = JsValue::from(seed_scheme_descriptor(Some(0)).unwrap());
+    let mixing = outcome_field(&legacy, "mixing");
+    assert_eq!(outcome_field(&mixing, "index_bits").as_f64(), Some(32.0));
+    let error: JsValue = seed_scheme_descriptor(Some(2)).unwrap_err().into();
+    assert_eq!(error_kind(&error).1, "InvalidOptions");
+}
@@ -0,0 +1,220 @@
+//! Native grinder over `vanity-core`, producing seeds usable in the web app.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use vanity_core::{
+    check_feasible, partition_counters, seed_to_string, CancelToken, KeyRole, MatchType, Pubkey,
+    Searcher, VanityError, DEFAULT_MAX_EXPECTED_ATTEMPTS,
+};
+
+const BATCH_SIZE: u32 = 10_000;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Parser)]
+#[command(about = "Grind createWithSeed vanity addresses")]
+struct Args {
+    /// Base pubkey (base58)
+    #[arg(long)]
+    base: String,
+    /// Owner program id (base58)
+    #[arg(long)]
+    owner: String,
+    #[arg(long)]
+    prefix: Option<String>,
+    #[arg(long)]
+    suffix: Option<String>,
+    /// Match anywhere in the address; cannot be combined with prefix/suffix
+    #[arg(long, conflicts_with_all = ["prefix", "suffix"])]
+    contains: Option<String>,
+    #[arg(short = 'i', long)]
+    case_insensitive: bool,
+    /// Accept a missing pattern, making every attempt a match (for benchmarking)
+    #[arg(long)]
+    allow_empty_pattern: bool,
+    /// Search even if the pattern is expected to take impractically long
+    #[arg(long)]
+    force: bool,
+    /// Worker threads, defaults to the number of CPUs
+    #[arg(short, long)]
+    threads: Option<usize>,
+    #[arg(long, default_value_t = 0)]
+    count_offset: u64,
+    /// Stop after this many matches
+    #[arg(short = 'n', long, default_value_t = 1)]
+    max_results: usize,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Seconds between progress lines on stderr, 0 to disable
+    #[arg(long, default_value_t = 2.0)]
+    progress_interval: f64,
+}
+
+#[derive(Serialize)]
+struct FoundRecord {
+    address: String,
+    seed: String,
+    counter: u64,
+    attempts: u64,
+}
+
+fn parse_pubkey(role: KeyRole, value: &str) -> Result<Pubkey, String> {
+    Pubkey::from_base58_for_role(value, role).map_err(|e| e.to_string())
+}
+
+fn print_record(format: Format, record: &FoundRecord, first: bool) {
+    match format {
+        Format::Text => println!("{} {}", record.address, record.seed),
+        Format::Json => println!("{}", serde_json::to_string(record).unwrap()),
+        Format::Csv => {
+            if first {
+                println!("address,seed,counter,attempts");
+            }
+            println!(
+                "{},{},{},{}",
+                record.address, record.seed, record.counter, record.attempts
+            );
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let base = parse_pubkey(KeyRole::Base, &args.base)?;
+    let owner = parse_pubkey(KeyRole::Owner, &args.owner)?;
+    let match_type = match args.contains {
+        Some(pattern) => MatchType::contains(pattern, args.case_insensitive),
+        None => MatchType::new(args.prefix, args.suffix, args.case_insensitive),
+    };
+    if match_type.is_empty() && !args.allow_empty_pattern {
+        return Err(VanityError::EmptyPattern.to_string());
+    }
+    // The searchers are built unchecked, so a pattern no address can match
+    // must be caught here rather than grinding forever.
+    match_type
+        .validate(args.case_insensitive)
+        .map_err(|e| e.to_string())?;
+    if !args.force {
+        check_feasible(
+            match_type.probability(args.case_insensitive),
+            DEFAULT_MAX_EXPECTED_ATTEMPTS,
+        )
+        .map_err(|e| match e {
+            // The core's message names the web option.
+            VanityError::Infeasible {
+                expected_attempts,
+                duration,
+            } => format!(
+                "pattern needs about {expected_attempts:.1e} attempts on average, {duration} at \
+                 1M attempts/s; pass --force to search anyway"
+            ),
+            e => e.to_string(),
+        })?;
+    }
+    let threads = args
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .max(1);
+
+    let total_attempts = Arc::new(AtomicU64::new(0));
+    // Stops every thread mid-batch once enough matches are in.
+    let done = CancelToken::new();
+    let (tx, rx) = mpsc::channel();
+
+    // Each thread walks its own slice of the counter space past the offset,
+    // split as the web worker pool splits it, capped so it never runs into
+    // the next; the last runs to the end.
+    let ranges = partition_counters(args.count_offset, threads.try_into().unwrap_or(u32::MAX))
+        .map_err(|e| e.to_string())?;
+    let last = ranges.len() - 1;
+    let handles: Vec<_> = ranges
+        .into_iter()
+        .enumerate()
+        .map(|(i, range)| {
+            let mut searcher = Searcher::with_match_type(
+                base.as_bytes(),
+                owner.as_bytes(),
+                match_type.clone(),
+                args.case_insensitive,
+                range.offset,
+            );
+            if i < last {
+                searcher.set_max_attempts(Some(range.length));
+            }
+            searcher.set_cancel_token(Some(done.clone()));
+            let total_attempts = Arc::clone(&total_attempts);
+            let tx = tx.clone();
+            thread::spawn(move || {
+                while !searcher.is_stopped() && !searcher.is_exhausted() {
+                    let before = searcher.attempts();
+                    let found = searcher.search_batch(BATCH_SIZE);
+                    total_attempts.fetch_add(searcher.attempts() - before, Ordering::Relaxed);
+                    if let Some(found) = found {
+                        if tx.send(found).is_err() {
+                            break;
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let start = Instant::now();
+    let mut last_report = start;
+    let interval = Duration::from_secs_f64(args.progress_interval.max(0.0));
+    let mut found_count = 0;
+    while found_count < args.max_results {
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(found) => {
+                let record = FoundRecord {
+                    address: found.address,
+                    seed: seed_to_string(&found.seed),
+                    counter: found.counter,
+                    attempts: total_attempts.load(Ordering::Relaxed),
+                };
+                print_record(args.format, &record, found_count == 0);
+                found_count += 1;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        if !interval.is_zero() && last_report.elapsed() >= interval {
+            last_report = Instant::now();
+            let attempts = total_attempts.load(Ordering::Relaxed);
+            let rate = attempts as f64 / start.elapsed().as_secs_f64();
+            eprintln!("attempts: {attempts}, rate: {rate:.0}/s");
+        }
+    }
+
+    done.cancel();
+    drop(rx);
+    for handle in handles {
+        let _ = handle.join();
+    }
+    if found_count < args.max_results {
+        return Err(format!(
+            "counter space exhausted after {} attempts with {found_count} of {} matches",
+            total_attempts.load(Ordering::Relaxed),
+            args.max_results
+        ));
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Args::parse()) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
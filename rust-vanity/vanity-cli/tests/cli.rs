@@ -0,0 +1,153 @@
+use std::process::Command;
+
+use vanity_core::{encode_pubkey, Deriver};
+
+const BASE: [u8; 32] = [1; 32];
+const OWNER: [u8; 32] = [4; 32];
+
+fn base58(bytes: &[u8; 32]) -> String {
+    let mut buf = [0u8; 44];
+    encode_pubkey(bytes, &mut buf).to_string()
+}
+
+fn run(extra: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_vanity-cli"))
+        .args(["--base", &base58(&BASE), "--owner", &base58(&OWNER)])
+        .args(["--progress-interval", "0"])
+        .args(extra)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_json_output_rederives() {
+    let stdout = run(&[
+        "--prefix",
+        "A",
+        "--format",
+        "json",
+        "--threads",
+        "2",
+        "-n",
+        "2",
+    ]);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+
+    for line in lines {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        let address = record["address"].as_str().unwrap();
+        let seed = record["seed"].as_str().unwrap();
+        assert!(address.starts_with('A'));
+        assert!(record["counter"].is_u64());
+        assert!(record["attempts"].as_u64().unwrap() > 0);
+
+        let pubkey = Deriver::new(&BASE, &OWNER).derive(seed.as_bytes());
+        assert_eq!(base58(&pubkey), address);
+    }
+}
+
+#[test]
+fn test_csv_output() {
+    let stdout = run(&["--suffix", "z", "-i", "--format", "csv", "--threads", "1"]);
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("address,seed,counter,attempts"));
+    let row: Vec<_> = lines.next().unwrap().split(',').collect();
+    assert_eq!(row.len(), 4);
+    assert!(row[0].to_lowercase().ends_with('z'));
+}
+
+#[test]
+fn test_single_thread_matches_library_search() {
+    let stdout = run(&["--contains", "ab", "--format", "json", "--threads", "1"]);
+    let record: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+
+    let mut searcher = vanity_core::Searcher::with_match_type(
+        &BASE,
+        &OWNER,
+        vanity_core::MatchType::contains("ab".into(), false),
+        false,
+        0,
+    );
+    let found = loop {
+        if let Some(found) = searcher.search_batch(10_000) {
+            break found;
+        }
+    };
+    assert_eq!(record["address"], found.address.as_str());
+    assert_eq!(record["counter"], found.counter);
+}
+
+#[test]
+fn test_empty_pattern_requires_opt_in() {
+    let output = Command::new(env!("CARGO_BIN_EXE_vanity-cli"))
+        .args(["--base", &base58(&BASE), "--owner", &base58(&OWNER)])
+        .args(["--prefix", " "])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pattern is empty"));
+
+    let stdout = run(&[
+        "--allow-empty-pattern",
+        "--format",
+        "json",
+        "--threads",
+        "1",
+    ]);
+    let record: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    assert_eq!(record["counter"], 0);
+}
+
+#[test]
+fn test_unmatchable_pattern_fails() {
+    let fails_with = |pattern: &[&str], message: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_vanity-cli"))
+            .args(["--base", &base58(&BASE), "--owner", &base58(&OWNER)])
+            .args(pattern)
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{stderr}");
+    };
+    fails_with(&["--prefix", "0"], "not in the base58 alphabet");
+    fails_with(&["--contains", "aOb"], "not in the base58 alphabet");
+    fails_with(
+        &["--suffix", &"z".repeat(20)],
+        "pass --force to search anyway",
+    );
+}
+
+#[test]
+fn test_invalid_pubkey_fails() {
+    let output = Command::new(env!("CARGO_BIN_EXE_vanity-cli"))
+        .args([
+            "--base",
+            "0OIl",
+            "--owner",
+            &base58(&OWNER),
+            "--prefix",
+            "A",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid base pubkey"));
+}
+
+#[test]
+fn test_exhausted_counter_space_is_an_error() {
+    let offset = (u64::MAX - 9).to_string();
+    let output = Command::new(env!("CARGO_BIN_EXE_vanity-cli"))
+        .args(["--base", &base58(&BASE), "--owner", &base58(&OWNER)])
+        .args(["--prefix", "zzzzzz", "--threads", "3"])
+        .args(["--count-offset", &offset, "--progress-interval", "0"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("counter space exhausted after 10 attempts with 0 of 1 matches"));
+}
@@ -0,0 +1,164 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+#[cfg(feature = "keypair")]
+use curve25519_dalek::{edwards::EdwardsPoint, scalar::Scalar};
+use vanity_core::{
+    encode_pubkey, generate_seed_from_counter, try_encode_pubkey, Deriver, Searcher,
+    ALPHANUMERIC_CHARS,
+};
+#[cfg(feature = "keypair")]
+use vanity_core::{Candidate, Derivation, IncrementalKeyDerivation, SignerKeyDerivation};
+
+const BATCH: u32 = 10_000;
+
+fn derivation(c: &mut Criterion) {
+    let deriver = Deriver::new(&[1; 32], &[4; 32]);
+    let mut counter = 0u64;
+
+    c.bench_function("derive_and_encode", |b| {
+        b.iter(|| {
+            let seed = generate_seed_from_counter(counter);
+            counter += 1;
+            let pubkey = deriver.derive(&seed);
+            let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+            black_box(encode_pubkey(&pubkey, &mut buf).len())
+        })
+    });
+}
+
+/// `generate_seed_from_counter` as it was before its lookups were made
+/// provably in bounds, to compare against.
+fn indexed_seed_from_counter(counter: u64) -> [u8; 16] {
+    let chars: &[u8] = ALPHANUMERIC_CHARS;
+    let mut seed = [0u8; 16];
+    let mut state1 = counter;
+    let mut state2 = counter.wrapping_mul(0x9E3779B97F4A7C15);
+    let len = chars.len() as u64;
+    for i in 0..8 {
+        seed[i] = chars[(state1 % len) as usize];
+        seed[i + 8] = chars[(state2 % len) as usize];
+        state1 >>= 8;
+        state2 >>= 8;
+    }
+    seed
+}
+
+/// Each step of the search loop before and after removing its implicit
+/// panics, with the same inputs.
+fn hot_path(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("seed/indexed", |b| {
+        let mut counter = 0u64;
+        b.iter(|| {
+            for _ in 0..BATCH {
+                counter += 1;
+                black_box(indexed_seed_from_counter(black_box(counter)));
+            }
+        })
+    });
+    group.bench_function("seed/split_halves", |b| {
+        let mut counter = 0u64;
+        b.iter(|| {
+            for _ in 0..BATCH {
+                counter += 1;
+                black_box(generate_seed_from_counter(black_box(counter)));
+            }
+        })
+    });
+    let deriver = Deriver::new(&[1; 32], &[4; 32]);
+    let pubkeys: Vec<[u8; 32]> = (0..BATCH as u64)
+        .map(|counter| deriver.derive(&counter.to_le_bytes()))
+        .collect();
+    group.bench_function("encode/panicking", |b| {
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        b.iter(|| {
+            for pubkey in &pubkeys {
+                black_box(encode_pubkey(pubkey, &mut buf).len());
+            }
+        })
+    });
+    group.bench_function("encode/fallible", |b| {
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        b.iter(|| {
+            for pubkey in &pubkeys {
+                black_box(try_encode_pubkey(pubkey, &mut buf).map(str::len).ok());
+            }
+        })
+    });
+    group.finish();
+}
+
+const UNMATCHABLE: &str = "zzzzzzzzzz";
+
+fn search_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search_batch");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("unmatchable_prefix", |b| {
+        // Ten characters never match in practice, so every batch runs to
+        // completion.
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some(UNMATCHABLE.into()), None, false, 0).unwrap();
+        b.iter(|| black_box(searcher.search_batch(BATCH)))
+    });
+    group.bench_function("unmatchable_suffix", |b| {
+        // Ruled out from the last eight characters, without encoding.
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], None, Some(UNMATCHABLE.into()), false, 0).unwrap();
+        b.iter(|| black_box(searcher.search_batch(BATCH)))
+    });
+    group.finish();
+}
+
+/// Fresh keys per candidate against one point addition per candidate.
+/// `stepped_compress` is the incremental key as it was before a block of
+/// keys shared one inversion: a point addition and a `compress` per key.
+#[cfg(feature = "keypair")]
+fn keypairs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("keypair");
+    group.throughput(Throughput::Elements(1));
+    let signer = SignerKeyDerivation::new(&[7; 32]);
+    let mut counter = 0u64;
+    group.bench_function("signer_key", |b| {
+        let mut out = Candidate::default();
+        b.iter(|| {
+            counter += 1;
+            signer.candidate(counter, &mut out);
+            black_box(out.pubkey)
+        })
+    });
+    let incremental = IncrementalKeyDerivation::new(&[7; 32]);
+    let mut counter = 0u64;
+    group.bench_function("incremental_key", |b| {
+        let mut out = Candidate::default();
+        b.iter(|| {
+            counter += 1;
+            incremental.candidate(counter, &mut out);
+            black_box(out.pubkey)
+        })
+    });
+    let step = EdwardsPoint::mul_base(&Scalar::from(8u8));
+    let mut point = EdwardsPoint::mul_base(&Scalar::from(7u8));
+    group.bench_function("stepped_compress", |b| {
+        b.iter(|| {
+            point += step;
+            black_box(point.compress().to_bytes())
+        })
+    });
+    // Every other counter, so each key is a jump and none is batched.
+    let mut counter = 0u64;
+    group.bench_function("incremental_key_jumps", |b| {
+        let mut out = Candidate::default();
+        b.iter(|| {
+            counter += 2;
+            incremental.candidate(counter, &mut out);
+            black_box(out.pubkey)
+        })
+    });
+    group.finish();
+}
+
+#[cfg(feature = "keypair")]
+criterion_group!(benches, derivation, hot_path, search_loop, keypairs);
+#[cfg(not(feature = "keypair"))]
+criterion_group!(benches, derivation, hot_path, search_loop);
+criterion_main!(benches);
@@ -0,0 +1,214 @@
+//! The character sets patterns are checked against. An [`Alphabet`] is
+//! its characters in digit order plus a lookup table built from them at
+//! compile time, so validation, the estimates and the matcher all ask the
+//! same table whether a character can appear and how likely it is.
+//! Ignoring case, a pattern character matches itself and its other case,
+//! whichever of the two the alphabet has.
+
+use alloc::string::ToString;
+
+use crate::charmap::other_case;
+use crate::error::VanityError;
+use crate::estimate::BASE58_ALPHABET;
+use crate::seed::ALPHANUMERIC_CHARS;
+
+/// Marks a byte outside the alphabet in its index table.
+const NOT_IN: u8 = u8::MAX;
+
+/// Base58 as Solana addresses use it; what `five8::encode_32` emits.
+pub const BASE58: Alphabet = Alphabet::new("base58", BASE58_ALPHABET);
+
+/// Lowercase hex, as digests and `hex:` seeds are written.
+pub const HEX: Alphabet = Alphabet::new("hex", b"0123456789abcdef");
+
+/// What counter seeds are made of: base58 plus '0', in its own order.
+pub const SEED_CHARSET: Alphabet = Alphabet::new("seed charset", ALPHANUMERIC_CHARS);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alphabet {
+    name: &'static str,
+    chars: &'static [u8],
+    indices: [u8; 256],
+}
+
+impl Alphabet {
+    /// Panics (at compile time, for a constant) if `chars` repeats a
+    /// character, has one outside ASCII or has more than 255.
+    pub const fn new(name: &'static str, chars: &'static [u8]) -> Alphabet {
+        assert!(chars.len() < NOT_IN as usize, "alphabet too long");
+        let mut indices = [NOT_IN; 256];
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i] as usize;
+            assert!(c < 128, "alphabet character outside ASCII");
+            assert!(indices[c] == NOT_IN, "alphabet character repeated");
+            indices[c] = i as u8;
+            i += 1;
+        }
+        Alphabet {
+            name,
+            chars,
+            indices,
+        }
+    }
+
+    /// What error messages call it, e.g. "base58".
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The characters in digit order.
+    pub const fn chars(&self) -> &'static [u8] {
+        self.chars
+    }
+
+    pub const fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Digit value of `b`, or `None` if it isn't in the alphabet.
+    #[inline]
+    pub const fn index(&self, b: u8) -> Option<u8> {
+        match self.indices[b as usize] {
+            NOT_IN => None,
+            index => Some(index),
+        }
+    }
+
+    #[inline]
+    pub const fn contains(&self, b: u8) -> bool {
+        self.indices[b as usize] != NOT_IN
+    }
+
+    /// Whether some character of the alphabet matches pattern byte `b`.
+    #[inline]
+    pub const fn can_match(&self, b: u8, case_insensitive: bool) -> bool {
+        self.contains(b) || (case_insensitive && self.contains(other_case(b)))
+    }
+
+    /// Digits a character may take to match pattern byte `b`, at most two.
+    pub fn variants(&self, b: u8, case_insensitive: bool) -> impl Iterator<Item = usize> + '_ {
+        let other = (case_insensitive && other_case(b) != b).then(|| other_case(b));
+        core::iter::once(b)
+            .chain(other)
+            .filter_map(|c| self.index(c))
+            .map(usize::from)
+    }
+
+    /// Chance a uniformly drawn character matches pattern byte `b`.
+    pub fn char_probability(&self, b: u8, case_insensitive: bool) -> f64 {
+        self.variants(b, case_insensitive).count() as f64 / self.len() as f64
+    }
+
+    /// Chance a run of uniformly drawn characters matches `pattern`.
+    pub fn uniform_probability(&self, pattern: &[u8], case_insensitive: bool) -> f64 {
+        pattern
+            .iter()
+            .map(|&b| self.char_probability(b, case_insensitive))
+            .product()
+    }
+
+    /// Fails with `InvalidPatternChar`, naming this alphabet, at the first
+    /// character of `pattern` nothing in it can match.
+    pub fn validate(&self, pattern: &str, case_insensitive: bool) -> Result<(), VanityError> {
+        let invalid = pattern
+            .chars()
+            .enumerate()
+            .find(|&(_, c)| !c.is_ascii() || !self.can_match(c as u8, case_insensitive));
+        match invalid {
+            Some((position, character)) => Err(VanityError::InvalidPatternChar {
+                pattern: pattern.to_string(),
+                character,
+                position,
+                alphabet: self.name,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeSet;
+
+    use super::*;
+    use crate::derive::MAX_ENCODED_LEN;
+
+    #[test]
+    fn test_base58_is_what_five8_emits() {
+        // The last digit of a small value is the digit itself, so this pins
+        // the order as well as the set.
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        for digit in 0..58u8 {
+            let mut pubkey = [0u8; 32];
+            pubkey[31] = digit;
+            let len = five8::encode_32(&pubkey, &mut buf) as usize;
+            assert_eq!(BASE58.index(buf[len - 1]), Some(digit));
+        }
+        let mut emitted = BTreeSet::new();
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..500 {
+            let mut pubkey = [0u8; 32];
+            for byte in &mut pubkey {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                *byte = (state >> 56) as u8;
+            }
+            let len = five8::encode_32(&pubkey, &mut buf) as usize;
+            emitted.extend(buf[..len].iter().copied());
+        }
+        let all: BTreeSet<u8> = (0..=u8::MAX).filter(|&b| BASE58.contains(b)).collect();
+        assert_eq!(emitted, all);
+        assert_eq!(BASE58.chars().len(), 58);
+    }
+
+    #[test]
+    fn test_builtins_differ_where_they_should() {
+        assert!(SEED_CHARSET.contains(b'0') && !BASE58.contains(b'0'));
+        assert_eq!(SEED_CHARSET.len(), BASE58.len() + 1);
+        assert_ne!(SEED_CHARSET.index(b'1'), BASE58.index(b'1'));
+        assert!(!HEX.contains(b'A') && HEX.can_match(b'A', true));
+        assert_eq!(HEX.index(b'f'), Some(15));
+        for alphabet in [&BASE58, &HEX, &SEED_CHARSET] {
+            for (i, &c) in alphabet.chars().iter().enumerate() {
+                assert_eq!(alphabet.index(c), Some(i as u8), "{}", alphabet.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_probabilities_follow_the_alphabet() {
+        assert_eq!(HEX.char_probability(b'a', false), 1.0 / 16.0);
+        assert_eq!(HEX.char_probability(b'A', false), 0.0);
+        assert_eq!(HEX.char_probability(b'A', true), 1.0 / 16.0);
+        assert_eq!(SEED_CHARSET.char_probability(b'a', true), 2.0 / 59.0);
+        assert_eq!(BASE58.char_probability(b'l', true), 1.0 / 58.0);
+        assert_eq!(
+            BASE58.uniform_probability(b"ab", false),
+            1.0 / (58.0 * 58.0)
+        );
+        assert_eq!(HEX.uniform_probability(b"", true), 1.0);
+    }
+
+    #[test]
+    fn test_validation_names_the_alphabet() {
+        assert_eq!(HEX.validate("c0ffee", false), Ok(()));
+        let error = HEX.validate("c0ffez", false).unwrap_err();
+        assert_eq!(
+            error,
+            VanityError::InvalidPatternChar {
+                pattern: "c0ffez".into(),
+                character: 'z',
+                position: 5,
+                alphabet: "hex",
+            }
+        );
+        assert!(error.to_string().contains("hex"));
+        let error = BASE58.validate("ab0", false).unwrap_err();
+        assert!(error.to_string().contains("base58"));
+        assert!(BASE58.validate("ÿ", true).is_err());
+    }
+}
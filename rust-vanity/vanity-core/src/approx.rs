@@ -0,0 +1,492 @@
+//! Approximate matching: a prefix or suffix that may be a few characters
+//! off, e.g. "M0on"-like addresses for "Moon" (one substitution), for users
+//! who would rather wait less than get the exact spelling.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::charmap::{fold_str, same_byte};
+use crate::derivation::{Candidate, CreateWithSeedDerivation, Derivation};
+use crate::derive::{encode_pubkey, MAX_ENCODED_LEN, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::float;
+use crate::matcher::MatchType;
+use crate::seed::seed_to_string;
+
+/// Most edits an [`ApproxPattern`] allows.
+pub const MAX_EDITS: u8 = 2;
+
+/// Longest window of an address an [`ApproxPattern`] compares.
+const MAX_WINDOW: usize = MAX_ENCODED_LEN + MAX_EDITS as usize;
+
+/// Which end of the address an [`ApproxPattern`] is anchored to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApproxSide {
+    Prefix,
+    Suffix,
+}
+
+/// A prefix or suffix matched within `max_edits` edits: substitutions, and
+/// with `allow_indels` also insertions and deletions, so the matched window
+/// of the address may be up to `max_edits` characters longer or shorter
+/// than the pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApproxPattern {
+    side: ApproxSide,
+    /// Folded if matching case-insensitively.
+    pattern: String,
+    /// `pattern` in the order it is compared in: from the anchored end.
+    compared: Vec<u8>,
+    max_edits: u8,
+    allow_indels: bool,
+    case_insensitive: bool,
+}
+
+impl ApproxPattern {
+    /// Fails with `EmptyPattern` for a blank pattern, as
+    /// [`MatchType::validate`] does for one that couldn't match even
+    /// exactly, or with `InvalidOptions` unless `max_edits` is at most
+    /// [`MAX_EDITS`] and less than the pattern's length.
+    pub fn new(
+        side: ApproxSide,
+        pattern: &str,
+        max_edits: u8,
+        allow_indels: bool,
+        case_insensitive: bool,
+    ) -> Result<ApproxPattern, VanityError> {
+        let pattern = pattern.trim();
+        let pattern = if case_insensitive {
+            fold_str(pattern)
+        } else {
+            pattern.to_string()
+        };
+        if pattern.is_empty() {
+            return Err(VanityError::EmptyPattern);
+        }
+        let exact = ApproxPattern::exact_match_type(side, &pattern);
+        exact.validate(case_insensitive)?;
+        if max_edits > MAX_EDITS || max_edits as usize >= pattern.len() {
+            return Err(VanityError::InvalidOptions(format!(
+                "max_edits must be at most {MAX_EDITS} and less than the pattern's {} \
+                 characters, got {max_edits}",
+                pattern.len()
+            )));
+        }
+        let compared = match side {
+            ApproxSide::Prefix => pattern.bytes().collect(),
+            ApproxSide::Suffix => pattern.bytes().rev().collect(),
+        };
+        Ok(ApproxPattern {
+            side,
+            pattern,
+            compared,
+            max_edits,
+            allow_indels,
+            case_insensitive,
+        })
+    }
+
+    fn exact_match_type(side: ApproxSide, pattern: &str) -> MatchType {
+        match side {
+            ApproxSide::Prefix => MatchType::Prefix(pattern.into()),
+            ApproxSide::Suffix => MatchType::Suffix(pattern.into()),
+        }
+    }
+
+    pub fn side(&self) -> ApproxSide {
+        self.side
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn max_edits(&self) -> u8 {
+        self.max_edits
+    }
+
+    pub fn allow_indels(&self) -> bool {
+        self.allow_indels
+    }
+
+    /// Edits between the pattern and the closest window at its end of
+    /// `encoded`, if there are at most `max_edits`.
+    pub fn distance(&self, encoded: &[u8]) -> Option<u8> {
+        let len = encoded
+            .len()
+            .min(self.compared.len() + self.max_edits as usize);
+        let mut window = [0u8; MAX_WINDOW];
+        match self.side {
+            ApproxSide::Prefix => window[..len].copy_from_slice(&encoded[..len]),
+            ApproxSide::Suffix => {
+                for (to, &from) in window[..len].iter_mut().zip(encoded.iter().rev()) {
+                    *to = from;
+                }
+            }
+        }
+        if self.allow_indels {
+            self.banded_distance(&window[..len])
+        } else {
+            self.substitutions(&window[..len])
+        }
+    }
+
+    fn substitutions(&self, window: &[u8]) -> Option<u8> {
+        if window.len() < self.compared.len() {
+            return None;
+        }
+        let mut edits = 0;
+        for (&a, &p) in window.iter().zip(&self.compared) {
+            if !same_byte(a, p, self.case_insensitive) {
+                edits += 1;
+                if edits > self.max_edits {
+                    return None;
+                }
+            }
+        }
+        Some(edits)
+    }
+
+    /// Levenshtein distance from the pattern to the best prefix of
+    /// `window`, computed only within `max_edits` of the diagonal (nothing
+    /// further off can be within `max_edits`), with distances capped at
+    /// `max_edits + 1`.
+    fn banded_distance(&self, window: &[u8]) -> Option<u8> {
+        let k = self.max_edits as usize;
+        let n = self.compared.len();
+        let over = self.max_edits + 1;
+        let mut prev = [over; MAX_WINDOW + 1];
+        let mut row = [over; MAX_WINDOW + 1];
+        for (j, cell) in prev.iter_mut().enumerate().take(window.len().min(k) + 1) {
+            *cell = j as u8;
+        }
+        for i in 1..=n {
+            row.fill(over);
+            let mut best = over;
+            for j in i.saturating_sub(k)..=(i + k).min(window.len()) {
+                let cell = if j == 0 {
+                    i as u8
+                } else {
+                    let same =
+                        same_byte(window[j - 1], self.compared[i - 1], self.case_insensitive);
+                    (prev[j - 1] + u8::from(!same))
+                        .min(prev[j] + 1)
+                        .min(row[j - 1] + 1)
+                };
+                row[j] = cell.min(over);
+                best = best.min(row[j]);
+            }
+            if best == over {
+                return None;
+            }
+            core::mem::swap(&mut prev, &mut row);
+        }
+        let edits = prev[n.saturating_sub(k)..=(n + k).min(window.len())]
+            .iter()
+            .copied()
+            .min()?;
+        (edits <= self.max_edits).then_some(edits)
+    }
+
+    /// Per-address probability of a match, counting substitutions only, so
+    /// with `allow_indels` matches come somewhat more often. Each character
+    /// is assumed to match with the same chance, whatever makes up the
+    /// exact pattern's probability.
+    pub fn probability(&self) -> f64 {
+        let n = self.pattern.len();
+        let exact = ApproxPattern::exact_match_type(self.side, &self.pattern)
+            .probability(self.case_insensitive);
+        let per_char = float::powf(exact, 1.0 / n as f64);
+        let mut choose = 1.0;
+        let mut total = 0.0;
+        for edits in 0..=self.max_edits as usize {
+            total += choose
+                * float::powi(1.0 - per_char, edits as i32)
+                * float::powi(per_char, (n - edits) as i32);
+            choose *= (n - edits) as f64 / (edits + 1) as f64;
+        }
+        total
+    }
+}
+
+/// A match found by an [`ApproxSearcher`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct ApproxMatch {
+    pub address: String,
+    pub seed: String,
+    pub attempts: u64,
+    pub counter: u64,
+    /// Edits from the pattern, 0 for an exact hit, e.g. to list those
+    /// first.
+    pub edits: u8,
+}
+
+/// Walks counters like [`crate::Searcher`], testing each address against
+/// an [`ApproxPattern`].
+#[derive(Clone)]
+pub struct ApproxSearcher {
+    derivation: CreateWithSeedDerivation,
+    pattern: ApproxPattern,
+    count: u64,
+    count_offset: u64,
+}
+
+impl ApproxSearcher {
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        pattern: ApproxPattern,
+        count_offset: u64,
+    ) -> ApproxSearcher {
+        ApproxSearcher {
+            derivation: CreateWithSeedDerivation::new(base_pubkey, owner_pubkey),
+            pattern,
+            count: 0,
+            count_offset,
+        }
+    }
+
+    /// Tries up to `batch_size` more counters, stopping at the first match
+    /// or the end of the counter space.
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<ApproxMatch> {
+        let mut candidate = Candidate::default();
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for _ in 0..batch_size {
+            let counter = self.absolute_position()?;
+            self.derivation.candidate(counter, &mut candidate);
+            let address = encode_pubkey(&candidate.pubkey, &mut encoded_buf);
+            self.count += 1;
+            if let Some(edits) = self.pattern.distance(address.as_bytes()) {
+                return Some(ApproxMatch {
+                    address: address.to_string(),
+                    seed: seed_to_string(&candidate.artifacts),
+                    attempts: self.count,
+                    counter,
+                    edits,
+                });
+            }
+        }
+        None
+    }
+
+    pub fn pattern(&self) -> &ApproxPattern {
+        &self.pattern
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// Counter the next attempt will use, or `None` once the counter space
+    /// is used up.
+    pub fn absolute_position(&self) -> Option<u64> {
+        self.count_offset.checked_add(self.count)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.absolute_position().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::Deriver;
+    use crate::error::ErrorKind;
+    use crate::estimate::BASE58_ALPHABET;
+
+    /// Every string `edits` substitutions from `pattern`.
+    fn substituted(pattern: &[u8], edits: usize) -> Vec<Vec<u8>> {
+        if edits == 0 {
+            return alloc::vec![pattern.to_vec()];
+        }
+        let mut variants = Vec::new();
+        for shorter in substituted(pattern, edits - 1) {
+            for i in 0..pattern.len() {
+                if shorter[i] != pattern[i] {
+                    continue;
+                }
+                for &c in BASE58_ALPHABET.iter().filter(|&&c| c != pattern[i]) {
+                    let mut variant = shorter.clone();
+                    variant[i] = c;
+                    variants.push(variant);
+                }
+            }
+        }
+        variants
+    }
+
+    #[test]
+    fn test_substitutions_within_tolerance_match() {
+        let prefix = ApproxPattern::new(ApproxSide::Prefix, "Moon", 1, false, false).unwrap();
+        let suffix = ApproxPattern::new(ApproxSide::Suffix, "Moon", 1, false, false).unwrap();
+        let at_start = |variant: &[u8]| [variant, b"xyz"].concat();
+        let at_end = |variant: &[u8]| [b"xyz", variant].concat();
+        assert_eq!(prefix.distance(&at_start(b"Moon")), Some(0));
+        assert_eq!(suffix.distance(&at_end(b"Moon")), Some(0));
+
+        let one = substituted(b"Moon", 1);
+        assert_eq!(one.len(), 4 * 57);
+        for variant in &one {
+            assert_eq!(prefix.distance(&at_start(variant)), Some(1), "{variant:?}");
+            assert_eq!(suffix.distance(&at_end(variant)), Some(1), "{variant:?}");
+            // At the other end, it's just noise.
+            assert_eq!(prefix.distance(&at_end(variant)), None, "{variant:?}");
+        }
+        for variant in substituted(b"Moon", 2) {
+            assert_eq!(prefix.distance(&at_start(&variant)), None, "{variant:?}");
+            assert_eq!(suffix.distance(&at_end(&variant)), None, "{variant:?}");
+        }
+        assert_eq!(prefix.distance(b"Moo"), None);
+
+        let folded = ApproxPattern::new(ApproxSide::Prefix, "Moon", 1, false, true).unwrap();
+        assert_eq!(folded.distance(b"mOONxyz"), Some(0));
+        assert_eq!(folded.distance(b"mO0nxyz"), Some(1));
+    }
+
+    #[test]
+    fn test_suffix_window_ends_with_the_address() {
+        for (_, address) in crate::fixtures::candidates() {
+            let bytes = address.as_bytes();
+            let len = bytes.len();
+            let tail = &address[len - 6..];
+            for allow_indels in [false, true] {
+                let exact = ApproxPattern::new(ApproxSide::Suffix, tail, 1, allow_indels, false);
+                assert_eq!(exact.unwrap().distance(bytes), Some(0), "{address}");
+                let mut off = tail.as_bytes().to_vec();
+                off[2] = if off[2] == b'z' { b'y' } else { b'z' };
+                let off = core::str::from_utf8(&off).unwrap();
+                let one = ApproxPattern::new(ApproxSide::Suffix, off, 1, allow_indels, false);
+                assert_eq!(one.unwrap().distance(bytes), Some(1), "{address}");
+            }
+            // A prefix as long as the address, whose window with indels
+            // would run past its end.
+            for allow_indels in [false, true] {
+                let whole =
+                    ApproxPattern::new(ApproxSide::Prefix, &address, 1, allow_indels, false);
+                assert_eq!(whole.unwrap().distance(bytes), Some(0), "{address}");
+            }
+        }
+    }
+
+    /// Levenshtein distance from `pattern` to the closest prefix of `text`,
+    /// without a band.
+    fn reference_distance(pattern: &[u8], text: &[u8]) -> usize {
+        let mut prev: Vec<usize> = (0..=text.len()).collect();
+        for (i, &p) in pattern.iter().enumerate() {
+            let mut row = alloc::vec![i + 1];
+            for (j, &t) in text.iter().enumerate() {
+                let cell = (prev[j] + usize::from(p != t))
+                    .min(prev[j + 1] + 1)
+                    .min(row[j] + 1);
+                row.push(cell);
+            }
+            prev = row;
+        }
+        prev.into_iter().min().unwrap()
+    }
+
+    #[test]
+    fn test_indels_agree_with_full_edit_distance() {
+        let mut state = 7u64;
+        let mut next = |n: u64| {
+            state = crate::permute::mix(state);
+            state % n
+        };
+        for _ in 0..20_000 {
+            // Two letters, so strings are often close to each other.
+            let pattern_len = 3 + next(4);
+            let pattern: Vec<u8> = (0..pattern_len).map(|_| b"ab"[next(2) as usize]).collect();
+            let text_len = next(10);
+            let text: Vec<u8> = (0..text_len).map(|_| b"ab"[next(2) as usize]).collect();
+            let max_edits = next(3) as u8;
+            let side = if next(2) == 0 {
+                ApproxSide::Prefix
+            } else {
+                ApproxSide::Suffix
+            };
+            let approx = ApproxPattern::new(
+                side,
+                core::str::from_utf8(&pattern).unwrap(),
+                max_edits,
+                true,
+                false,
+            )
+            .unwrap();
+            let expected = match side {
+                ApproxSide::Prefix => reference_distance(&pattern, &text),
+                ApproxSide::Suffix => {
+                    let reversed = |s: &[u8]| s.iter().rev().copied().collect::<Vec<_>>();
+                    reference_distance(&reversed(&pattern), &reversed(&text))
+                }
+            };
+            let expected = (expected <= max_edits as usize).then_some(expected as u8);
+            assert_eq!(
+                approx.distance(&text),
+                expected,
+                "{pattern:?} {text:?} {side:?}"
+            );
+        }
+
+        let moon = ApproxPattern::new(ApproxSide::Prefix, "Moon", 1, true, false).unwrap();
+        assert_eq!(moon.distance(b"Mon"), Some(1));
+        assert_eq!(moon.distance(b"Mooon"), Some(1));
+        assert_eq!(moon.distance(b"Moonx"), Some(0));
+        assert_eq!(moon.distance(b"MXoon"), Some(1));
+        assert_eq!(moon.distance(b"oonM"), Some(1));
+        assert_eq!(moon.distance(b"Mn"), None);
+    }
+
+    #[test]
+    fn test_pattern_validation() {
+        let kind = |pattern: &str, max_edits| {
+            ApproxPattern::new(ApproxSide::Prefix, pattern, max_edits, false, false)
+                .unwrap_err()
+                .kind()
+        };
+        assert_eq!(kind(" ", 0), ErrorKind::EmptyPattern);
+        assert_eq!(kind("M0on", 1), ErrorKind::InvalidPatternChar);
+        assert_eq!(kind("Mo", 2), ErrorKind::InvalidOptions);
+        assert_eq!(kind("Moonshot", 3), ErrorKind::InvalidOptions);
+        assert!(ApproxPattern::new(ApproxSide::Suffix, "Mo", 1, true, true).is_ok());
+    }
+
+    #[test]
+    fn test_tolerance_raises_the_probability() {
+        let probability = |max_edits| {
+            ApproxPattern::new(ApproxSide::Suffix, "Moon", max_edits, false, false)
+                .unwrap()
+                .probability()
+        };
+        let exact = MatchType::Suffix("Moon".into()).probability(false);
+        assert!((probability(0) - exact).abs() < exact * 1e-9);
+        // 1 + 4 * 57 strings of four characters are within one edit.
+        let expected = exact * (1.0 + 4.0 * 57.0);
+        assert!((probability(1) - expected).abs() < expected * 1e-9);
+        assert!(probability(2) > probability(1));
+    }
+
+    #[test]
+    fn test_searcher_reports_the_edits() {
+        let pattern = ApproxPattern::new(ApproxSide::Suffix, "Moon", 1, false, false).unwrap();
+        let mut searcher = ApproxSearcher::new(&[1; 32], &[4; 32], pattern.clone(), 100);
+        let found = loop {
+            if let Some(found) = searcher.search_batch(10_000) {
+                break found;
+            }
+        };
+        assert_eq!(
+            pattern.distance(found.address.as_bytes()),
+            Some(found.edits)
+        );
+        assert_eq!(found.counter, 100 + found.attempts - 1);
+        assert_eq!(searcher.attempts(), found.attempts);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let pubkey = Deriver::new(&[1; 32], &[4; 32]).derive(found.seed.as_bytes());
+        assert_eq!(encode_pubkey(&pubkey, &mut buf), found.address);
+    }
+}
@@ -0,0 +1,344 @@
+//! Best-effort search: no hard pattern, just the highest-scoring address
+//! seen so far.
+
+use alloc::string::{String, ToString};
+use core::sync::atomic::{AtomicI32, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::charmap::same_byte;
+use crate::derive::{encode_pubkey, Deriver, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::matcher::MatchType;
+use crate::seed::{generate_seed_from_counter, SEED_LEN};
+
+/// Words [`Scoring::DictionaryPrefix`] looks for, all spellable in base58
+/// when case is ignored.
+pub const DICTIONARY: &[&str] = &[
+    "ape", "based", "bear", "bonk", "bull", "cool", "cyber", "dao", "dark", "degen", "fire", "gem",
+    "gold", "hero", "hyper", "king", "magic", "mega", "moon", "nft", "pixel", "pump", "sol",
+    "star", "sun", "wave", "web", "zen",
+];
+
+/// How [`BestSearcher`] ranks addresses; higher is better.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Scoring {
+    /// How many characters of `pattern` the address starts with.
+    LeadingMatch {
+        pattern: String,
+        #[serde(default)]
+        #[cfg_attr(feature = "tsify", tsify(optional))]
+        case_insensitive: bool,
+    },
+    /// Length of the run of identical characters the address ends with.
+    TrailingRun,
+    /// Length of the longest [`DICTIONARY`] word the address starts with,
+    /// ignoring case.
+    DictionaryPrefix,
+}
+
+fn leading_match(address: &str, pattern: &str, case_insensitive: bool) -> u32 {
+    address
+        .bytes()
+        .zip(pattern.bytes())
+        .take_while(|&(a, p)| same_byte(a, p, case_insensitive))
+        .count() as u32
+}
+
+impl Scoring {
+    /// Scores an address without allocating.
+    pub fn score(&self, address: &str) -> u32 {
+        match self {
+            Scoring::LeadingMatch {
+                pattern,
+                case_insensitive,
+            } => leading_match(address, pattern, *case_insensitive),
+            Scoring::TrailingRun => match address.as_bytes().last() {
+                Some(&last) => address.bytes().rev().take_while(|&c| c == last).count() as u32,
+                None => 0,
+            },
+            Scoring::DictionaryPrefix => DICTIONARY
+                .iter()
+                .filter(|word| leading_match(address, word, true) == word.len() as u32)
+                .map(|word| word.len() as u32)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// The best score any of several workers has reported, shared between
+/// them so each reports only what beats every other. Holds `-1` before the
+/// first report.
+pub trait SharedBest {
+    fn load(&self) -> i32;
+
+    /// Replaces the score with `new` if it is still `current`; otherwise
+    /// returns the score found instead.
+    fn compare_exchange(&self, current: i32, new: i32) -> Result<i32, i32>;
+
+    /// Raises the score to `score` and returns `true` if `score` beats it,
+    /// retrying while other workers raise it concurrently.
+    fn raise(&self, score: u32) -> bool {
+        let score = i32::try_from(score).unwrap_or(i32::MAX);
+        let mut current = self.load();
+        while score > current {
+            match self.compare_exchange(current, score) {
+                Ok(_) => return true,
+                Err(found) => current = found,
+            }
+        }
+        false
+    }
+}
+
+impl SharedBest for AtomicI32 {
+    fn load(&self) -> i32 {
+        AtomicI32::load(self, Ordering::Acquire)
+    }
+
+    fn compare_exchange(&self, current: i32, new: i32) -> Result<i32, i32> {
+        AtomicI32::compare_exchange(self, current, new, Ordering::AcqRel, Ordering::Acquire)
+    }
+}
+
+/// The best address a [`BestSearcher`] has seen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BestMatch {
+    pub address: String,
+    pub seed: [u8; SEED_LEN],
+    /// Attempts made when it was found.
+    pub attempts: u64,
+    pub counter: u64,
+    pub score: u32,
+}
+
+/// Walks counters like [`crate::Searcher`] but never stops on a match:
+/// it keeps the highest-scoring address, the first one on ties. The address
+/// is only copied when the best improves.
+#[derive(Clone)]
+pub struct BestSearcher {
+    deriver: Deriver,
+    scoring: Scoring,
+    count: u64,
+    count_offset: u64,
+    best: Option<BestMatch>,
+}
+
+impl BestSearcher {
+    /// Fails if a [`Scoring::LeadingMatch`] pattern is empty or has
+    /// characters no address contains.
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        scoring: Scoring,
+        count_offset: u64,
+    ) -> Result<BestSearcher, VanityError> {
+        if let Scoring::LeadingMatch {
+            pattern,
+            case_insensitive,
+        } = &scoring
+        {
+            if pattern.is_empty() {
+                return Err(VanityError::EmptyPattern);
+            }
+            MatchType::Prefix(pattern.clone()).validate(*case_insensitive)?;
+        }
+        Ok(BestSearcher {
+            deriver: Deriver::new(base_pubkey, owner_pubkey),
+            scoring,
+            count: 0,
+            count_offset,
+            best: None,
+        })
+    }
+
+    /// Scores up to `batch_size` more addresses, stopping early at the end
+    /// of the counter space.
+    pub fn search_batch(&mut self, batch_size: u32) {
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for _ in 0..batch_size {
+            let Some(counter) = self.count_offset.checked_add(self.count) else {
+                return;
+            };
+            let seed = generate_seed_from_counter(counter);
+            let address = encode_pubkey(&self.deriver.derive(&seed), &mut encoded_buf);
+            self.count += 1;
+
+            let score = self.scoring.score(address);
+            if self.best.as_ref().is_none_or(|best| score > best.score) {
+                self.best = Some(BestMatch {
+                    address: address.to_string(),
+                    seed,
+                    attempts: self.count,
+                    counter,
+                    score,
+                });
+            }
+        }
+    }
+
+    /// Like [`BestSearcher::search_batch`], then offers the best address
+    /// seen to `global`: returns it if it beats the best score any worker
+    /// has reported, which is raised to it, and `None` otherwise, so a
+    /// worker never reports an address another has already bettered.
+    pub fn search_batch_shared(
+        &mut self,
+        batch_size: u32,
+        global: &impl SharedBest,
+    ) -> Option<BestMatch> {
+        self.search_batch(batch_size);
+        let best = self.best.as_ref()?;
+        global.raise(best.score).then(|| best.clone())
+    }
+
+    /// `None` before the first attempt.
+    pub fn best(&self) -> Option<&BestMatch> {
+        self.best.as_ref()
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether the counter space is used up.
+    pub fn is_exhausted(&self) -> bool {
+        self.count_offset.checked_add(self.count).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::search::Searcher;
+
+    #[test]
+    fn test_scores() {
+        let leading = Scoring::LeadingMatch {
+            pattern: "SoLana".into(),
+            case_insensitive: false,
+        };
+        assert_eq!(leading.score("SoLxyz"), 3);
+        assert_eq!(leading.score("solxyz"), 0);
+        let folded = Scoring::LeadingMatch {
+            pattern: "SoLana".into(),
+            case_insensitive: true,
+        };
+        assert_eq!(folded.score("solANAxyz"), 6);
+
+        assert_eq!(Scoring::TrailingRun.score("abczzz"), 3);
+        assert_eq!(Scoring::TrailingRun.score("abc"), 1);
+        assert_eq!(Scoring::TrailingRun.score(""), 0);
+
+        assert_eq!(Scoring::DictionaryPrefix.score("MoonXyz"), 4);
+        assert_eq!(Scoring::DictionaryPrefix.score("Basedx"), 5);
+        assert_eq!(Scoring::DictionaryPrefix.score("xyz"), 0);
+        for word in DICTIONARY {
+            assert!(MatchType::Prefix(word.to_string()).validate(true).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_best_is_maximal_over_the_sample() {
+        let base = [1; 32];
+        let owner = [4; 32];
+        for scoring in [
+            Scoring::LeadingMatch {
+                pattern: "ABC".into(),
+                case_insensitive: true,
+            },
+            Scoring::TrailingRun,
+            Scoring::DictionaryPrefix,
+        ] {
+            let mut best = BestSearcher::new(&base, &owner, scoring.clone(), 500).unwrap();
+            assert_eq!(best.best(), None);
+            best.search_batch(600);
+            best.search_batch(400);
+            let found = best.best().unwrap().clone();
+
+            let searcher = Searcher::new(&base, &owner, Some("A".into()), None, false, 0).unwrap();
+            let sample = searcher.sample_addresses(500, 1000);
+            let scores: Vec<_> = sample.iter().map(|s| scoring.score(&s.address)).collect();
+            let max = *scores.iter().max().unwrap();
+            assert_eq!(found.score, max);
+            // Ties keep the first address with the best score.
+            let first = scores.iter().position(|&score| score == max).unwrap();
+            assert_eq!(found.address, sample[first].address);
+            assert_eq!(found.counter, 500 + first as u64);
+            assert_eq!(found.attempts, first as u64 + 1);
+            assert_eq!(found.seed, sample[first].seed);
+        }
+    }
+
+    #[test]
+    fn test_shared_best_reports_only_records() {
+        let global = AtomicI32::new(-1);
+        let workers = 4;
+        let local_improvements = core::sync::atomic::AtomicU32::new(0);
+        let reports: Vec<Vec<u32>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    let (global, local_improvements) = (&global, &local_improvements);
+                    scope.spawn(move || {
+                        let offset = worker * 1_000_000;
+                        let mut searcher =
+                            BestSearcher::new(&[1; 32], &[4; 32], Scoring::TrailingRun, offset)
+                                .unwrap();
+                        let mut reports = Vec::new();
+                        let mut local_best = None;
+                        for _ in 0..40 {
+                            if let Some(found) = searcher.search_batch_shared(250, global) {
+                                reports.push(found.score);
+                            }
+                            let score = searcher.best().map(|best| best.score);
+                            if score != local_best {
+                                local_improvements.fetch_add(1, Ordering::Relaxed);
+                                local_best = score;
+                            }
+                        }
+                        assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+                        reports
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let mut all: Vec<u32> = reports.concat();
+        all.sort();
+        let max = *all.last().unwrap();
+        assert_eq!(global.load(Ordering::Relaxed), max as i32);
+        // Each report strictly raised the shared score, so there are at most
+        // as many as the scores from 0 to the maximum.
+        all.dedup();
+        assert_eq!(all.len(), reports.concat().len());
+        assert!(all.len() as u32 <= max + 1);
+        assert!(all.len() as u32 <= local_improvements.load(Ordering::Relaxed));
+
+        // Nothing is reported once another worker has done as well.
+        let mut late = BestSearcher::new(&[1; 32], &[4; 32], Scoring::TrailingRun, 0).unwrap();
+        assert_eq!(late.search_batch_shared(100, &global), None);
+        assert!(late.best().is_some());
+    }
+
+    #[test]
+    fn test_leading_pattern_is_validated() {
+        let new = |pattern: &str| {
+            let scoring = Scoring::LeadingMatch {
+                pattern: pattern.into(),
+                case_insensitive: false,
+            };
+            BestSearcher::new(&[1; 32], &[4; 32], scoring, 0).err()
+        };
+        assert_eq!(new(""), Some(VanityError::EmptyPattern));
+        assert_eq!(
+            new("S0L").map(|e| e.kind()),
+            Some(crate::ErrorKind::InvalidPatternChar)
+        );
+        assert!(new("SoL").is_none());
+    }
+}
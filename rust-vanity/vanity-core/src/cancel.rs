@@ -0,0 +1,119 @@
+//! Stopping several searchers at once.
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A stop shared by every searcher it is attached to: one
+/// [`CancelToken::cancel`] stops each of them within
+/// [`INTERRUPT_POLL_INTERVAL`](crate::search::INTERRUPT_POLL_INTERVAL)
+/// attempts, on whatever thread it runs, as if its own `stop` had been
+/// called. Clones share the same stop.
+///
+/// Cancelling is for good: a searcher restarted while its token is
+/// cancelled stops again on its next batch, until it is given another
+/// token or none.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi::MultiSearcher;
+    use crate::search::{BatchStatus, Searcher, INTERRUPT_POLL_INTERVAL};
+
+    fn searcher(count_offset: u64, token: &CancelToken) -> Searcher {
+        let mut searcher = Searcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzzzzzz".into()),
+            None,
+            false,
+            count_offset,
+        )
+        .unwrap();
+        searcher.set_cancel_token(Some(token.clone()));
+        searcher
+    }
+
+    #[test]
+    fn test_one_cancel_stops_every_searcher() {
+        let token = CancelToken::new();
+        let mut first = searcher(0, &token);
+        let mut second = searcher(1 << 40, &token);
+        second.search_batch(1_000);
+
+        // Cancelled from inside the first searcher's batch.
+        let mut polls = 0;
+        let found = first.search_batch_until(1_000_000, || {
+            polls += 1;
+            if polls == 3 {
+                token.cancel();
+            }
+            false
+        });
+        assert_eq!(found, None);
+        assert_eq!(first.batch_status(false), BatchStatus::Stopped);
+        assert_eq!(first.attempts(), 3 * INTERRUPT_POLL_INTERVAL as u64);
+
+        assert_eq!(second.search_batch(1_000), None);
+        assert_eq!(second.batch_status(false), BatchStatus::Stopped);
+        assert_eq!(second.attempts(), 1_000);
+
+        let mut multi = MultiSearcher::new(0);
+        multi.add_job(searcher(0, &CancelToken::new()), 1);
+        multi.set_cancel_token(Some(token.clone()));
+        assert!(multi.search_batch(1_000).is_empty());
+        assert!(multi.is_stopped());
+        assert_eq!(multi.attempts(), 0);
+
+        // Cancelling is for good, until the token is swapped out.
+        second.restart();
+        assert_eq!(second.search_batch(1_000), None);
+        assert!(second.is_stopped());
+        second.set_cancel_token(None);
+        second.restart();
+        second.search_batch(1_000);
+        assert_eq!(second.attempts(), 2_000);
+    }
+
+    #[test]
+    fn test_cancel_reaches_other_threads() {
+        let token = CancelToken::new();
+        let attempts: [u64; 2] = std::thread::scope(|scope| {
+            let handles = [0, 1 << 40].map(|count_offset| {
+                let mut searcher = searcher(count_offset, &token);
+                scope.spawn(move || {
+                    while !searcher.is_stopped() {
+                        searcher.search_batch(u32::MAX);
+                    }
+                    assert_eq!(searcher.batch_status(false), BatchStatus::Stopped);
+                    searcher.attempts()
+                })
+            });
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            token.cancel();
+            handles.map(|handle| handle.join().unwrap())
+        });
+        for attempts in attempts {
+            // Stopped at a poll, mid-batch.
+            assert!(attempts < u32::MAX as u64);
+            assert_eq!(attempts % INTERRUPT_POLL_INTERVAL as u64, 0);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Byte tables for classifying and folding pattern and address characters.
+//! The matcher, pattern validation and the estimates all look characters
+//! up here, so they agree on what folding does, and what base58 is through
+//! [`BASE58`]; the tables are built at compile time, allocate nothing and
+//! ignore locale.
+//! Bytes outside ASCII are never base58, never fold and belong to no
+//! group, so a lookup never moves an offset in UTF-8 text.
+
+use alloc::string::String;
+
+use crate::alphabet::BASE58;
+use crate::matcher::CONFUSABLES;
+
+/// Kind of character a byte is, as far as patterns care.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharClass {
+    Digit,
+    Upper,
+    Lower,
+    Other,
+}
+
+const CLASSES: [CharClass; 256] = {
+    let mut table = [CharClass::Other; 256];
+    let mut b = 0;
+    while b < 256 {
+        table[b] = match b as u8 {
+            b'0'..=b'9' => CharClass::Digit,
+            b'A'..=b'Z' => CharClass::Upper,
+            b'a'..=b'z' => CharClass::Lower,
+            _ => CharClass::Other,
+        };
+        b += 1;
+    }
+    table
+};
+
+const FOLDED: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut b = 0;
+    while b < 256 {
+        table[b] = match CLASSES[b] {
+            CharClass::Upper => b as u8 + (b'a' - b'A'),
+            _ => b as u8,
+        };
+        b += 1;
+    }
+    table
+};
+
+/// Confusable group of each byte, numbered from 1 in order of first
+/// appearance in [`CONFUSABLES`]; 0 for none. Each look-alike joins its
+/// replacement's group, so "0", "O" and "o" share one.
+const GROUPS: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut next = 1;
+    let mut i = 0;
+    while i < CONFUSABLES.len() {
+        let (from, to) = (CONFUSABLES[i].0 as usize, CONFUSABLES[i].1 as usize);
+        if table[to] == 0 {
+            table[to] = next;
+            next += 1;
+        }
+        table[from] = table[to];
+        i += 1;
+    }
+    table
+};
+
+/// Value of a base58 digit, or `None` for a byte outside the alphabet.
+#[inline]
+pub const fn base58_digit(b: u8) -> Option<u8> {
+    BASE58.index(b)
+}
+
+#[inline]
+pub const fn is_base58(b: u8) -> bool {
+    BASE58.contains(b)
+}
+
+#[inline]
+pub const fn char_class(b: u8) -> CharClass {
+    CLASSES[b as usize]
+}
+
+/// ASCII lowercase of `b`; any other byte is left as it is.
+#[inline]
+pub const fn fold_byte(b: u8) -> u8 {
+    FOLDED[b as usize]
+}
+
+/// `s` with every character folded by [`fold_byte`], as patterns are
+/// when case is ignored.
+pub fn fold_str(s: &str) -> String {
+    s.chars()
+        .map(|c| match c.is_ascii() {
+            true => fold_byte(c as u8) as char,
+            false => c,
+        })
+        .collect()
+}
+
+/// `b` in the other case, for a letter; any other byte is left as it is.
+#[inline]
+pub const fn other_case(b: u8) -> u8 {
+    match CLASSES[b as usize] {
+        CharClass::Upper | CharClass::Lower => b ^ (b'a' - b'A'),
+        _ => b,
+    }
+}
+
+/// Byte equality, ignoring ASCII case if `case_insensitive`.
+#[inline]
+pub const fn same_byte(a: u8, b: u8, case_insensitive: bool) -> bool {
+    a == b || (case_insensitive && FOLDED[a as usize] == FOLDED[b as usize])
+}
+
+/// Group of `b` in [`CONFUSABLES`], or 0 if it has no look-alike.
+#[inline]
+pub const fn confusable_group(b: u8) -> u8 {
+    GROUPS[b as usize]
+}
+
+/// Whether `a` and `b` are the same character or look-alikes of each
+/// other.
+#[inline]
+pub const fn same_group(a: u8, b: u8) -> bool {
+    a == b || (GROUPS[a as usize] != 0 && GROUPS[a as usize] == GROUPS[b as usize])
+}
+
+/// Whether an address character can match pattern byte `b`: it is base58,
+/// or with case ignored its other case is.
+#[inline]
+pub const fn can_match(b: u8, case_insensitive: bool) -> bool {
+    BASE58.can_match(b, case_insensitive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimate::BASE58_ALPHABET;
+
+    fn bytes() -> impl Iterator<Item = u8> + Clone {
+        0..=u8::MAX
+    }
+
+    #[test]
+    fn test_tables_agree_with_the_reference() {
+        for b in bytes() {
+            let digit = BASE58_ALPHABET.iter().position(|&a| a == b);
+            assert_eq!(base58_digit(b).map(usize::from), digit, "{b}");
+            assert_eq!(is_base58(b), digit.is_some(), "{b}");
+            assert_eq!(fold_byte(b), b.to_ascii_lowercase(), "{b}");
+            let class = match b {
+                _ if b.is_ascii_digit() => CharClass::Digit,
+                _ if b.is_ascii_uppercase() => CharClass::Upper,
+                _ if b.is_ascii_lowercase() => CharClass::Lower,
+                _ => CharClass::Other,
+            };
+            assert_eq!(char_class(b), class, "{b}");
+            let swapped = match b.is_ascii_uppercase() {
+                true => b.to_ascii_lowercase(),
+                false => b.to_ascii_uppercase(),
+            };
+            assert_eq!(other_case(b), swapped, "{b}");
+            for case_insensitive in [false, true] {
+                let reference = BASE58_ALPHABET
+                    .iter()
+                    .any(|&a| a == b || (case_insensitive && a.eq_ignore_ascii_case(&b)));
+                assert_eq!(can_match(b, case_insensitive), reference, "{b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_groups_follow_the_confusables() {
+        for &(from, to) in CONFUSABLES {
+            let (from, to) = (from as u8, to as u8);
+            assert!(same_group(from, to), "{from} {to}");
+            assert!(is_base58(to) && !is_base58(from));
+        }
+        assert!(same_group(b'0', b'O') && same_group(b'I', b'l'));
+        assert!(!same_group(b'o', b'1'));
+        let grouped = bytes().filter(|&b| confusable_group(b) != 0).count();
+        assert_eq!(grouped, 6);
+        for (a, b) in bytes().flat_map(|a| bytes().map(move |b| (a, b))) {
+            assert_eq!(same_group(a, b), same_group(b, a));
+            if !a.is_ascii() {
+                assert_eq!(same_group(a, b), a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_folding_is_idempotent_and_consistent() {
+        for b in bytes() {
+            assert_eq!(fold_byte(fold_byte(b)), fold_byte(b), "{b}");
+            assert_eq!(other_case(other_case(b)), b, "{b}");
+            // Folding the pattern up front can't change what it matches.
+            assert_eq!(can_match(fold_byte(b), true), can_match(b, true), "{b}");
+        }
+        for (a, p) in bytes().flat_map(|a| bytes().map(move |p| (a, p))) {
+            assert_eq!(same_byte(a, p, false), a == p);
+            let folded = same_byte(a, p, true);
+            assert_eq!(folded, fold_byte(a) == fold_byte(p), "{a} {p}");
+            assert_eq!(same_byte(a, fold_byte(p), true), folded, "{a} {p}");
+            assert_eq!(same_byte(fold_byte(a), p, true), folded, "{a} {p}");
+            assert_eq!(same_byte(p, a, true), folded, "{a} {p}");
+        }
+    }
+}
@@ -0,0 +1,646 @@
+//! Address schemes the search loop can grind, behind one trait.
+//!
+//! [`crate::Searcher`] owns everything scheme-independent: walking the
+//! counters (offset, permutation, attempt cap, end of the counter space),
+//! batching and interrupt polling, stopping, matching (pattern, byte mask,
+//! clean prefix), sampling, resume tokens and statistics. A [`Derivation`]
+//! only turns a counter into the pubkey to match, plus whatever the caller
+//! needs to use a match: a seed, a secret key, a bump.
+//!
+//! To add a scheme, implement [`Derivation`] and build a searcher with
+//! [`crate::Searcher::with_derivation`] (no pattern checks) or
+//! [`crate::Searcher::with_pattern`]; the loop itself needs no changes.
+
+#[cfg(feature = "keypair")]
+use alloc::format;
+use alloc::vec::Vec;
+#[cfg(feature = "keypair")]
+use core::cell::RefCell;
+use core::fmt::Debug;
+
+#[cfg(feature = "keypair")]
+use crate::edwards::{self, ExtendedPoint};
+#[cfg(feature = "keypair")]
+use curve25519_dalek::edwards::EdwardsPoint;
+#[cfg(feature = "keypair")]
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "keypair")]
+use sha2::{Digest, Sha256, Sha512};
+#[cfg(feature = "passphrase")]
+use zeroize::Zeroizing;
+
+use crate::derive::{Deriver, PUBKEY_LEN};
+#[cfg(feature = "keypair")]
+use crate::error::VanityError;
+#[cfg(feature = "keypair")]
+use crate::keys::{
+    clamp, pubkey_from_secret, secret_from_counter, verify_signature, ExpandedSecret,
+};
+#[cfg(feature = "passphrase")]
+use crate::passphrase::{passphrase_fingerprint, seed_from_passphrase, PASSPHRASE_SEED_VERSION};
+use crate::seed::{SeedCharset, SeedScheme, MAX_SEED_LEN, SEED_LEN};
+
+/// What a [`Derivation`] produces for one counter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Candidate<A> {
+    /// The bytes matched and base58-encoded.
+    pub pubkey: [u8; PUBKEY_LEN],
+    /// Whatever the result needs besides the address, e.g. the seed.
+    pub artifacts: A,
+}
+
+/// Maps counters to candidate addresses. Must be deterministic: the same
+/// counter always gives the same candidate, or ranges, resume tokens and
+/// re-derivation of sampled addresses stop meaning anything.
+pub trait Derivation: Clone {
+    /// Reported with every match as its `seed`.
+    type Artifacts: Clone + Debug + Default + PartialEq;
+
+    /// Fills `out` with the candidate for `counter`. `out` is reused across
+    /// counters, so every field has to be written.
+    fn candidate(&self, counter: u64, out: &mut Candidate<Self::Artifacts>);
+
+    /// Bytes that pin down which candidate every counter maps to (scheme
+    /// version, keys, ...), hashed into resume-token fingerprints so tokens
+    /// can't be applied to another scheme or configuration. Must not contain
+    /// secrets, only a hash of them.
+    fn identity(&self) -> Vec<u8>;
+
+    /// Distinct candidates the scheme has, if fewer than there are
+    /// counters; counters past it only repeat earlier candidates, so
+    /// [`crate::Searcher`] stops there.
+    fn seed_space(&self) -> Option<u64> {
+        None
+    }
+
+    /// The [`SeedScheme`] counters map to seeds with, for derivations that
+    /// have a choice of them.
+    fn seed_scheme(&self) -> Option<SeedScheme> {
+        None
+    }
+
+    /// This derivation with `scheme`'s seeds instead, or `None` if it has
+    /// no choice of scheme.
+    fn with_seed_scheme(&self, _scheme: SeedScheme) -> Option<Self> {
+        None
+    }
+}
+
+/// `createWithSeed`: counter → [`SeedScheme::seed_from_counter`] →
+/// `sha256(base || seed || owner)`. The original and default derivation.
+#[derive(Clone)]
+pub struct CreateWithSeedDerivation {
+    deriver: Deriver,
+    base_pubkey: [u8; PUBKEY_LEN],
+    owner_pubkey: [u8; PUBKEY_LEN],
+    scheme: SeedScheme,
+}
+
+impl CreateWithSeedDerivation {
+    /// With [`SeedScheme::LATEST`].
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+    ) -> CreateWithSeedDerivation {
+        CreateWithSeedDerivation::with_scheme(base_pubkey, owner_pubkey, SeedScheme::LATEST)
+    }
+
+    /// With an older scheme, to reproduce results or resume searches
+    /// saved under it.
+    pub fn with_scheme(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        scheme: SeedScheme,
+    ) -> CreateWithSeedDerivation {
+        CreateWithSeedDerivation {
+            deriver: Deriver::new(base_pubkey, owner_pubkey),
+            base_pubkey: *base_pubkey,
+            owner_pubkey: *owner_pubkey,
+            scheme,
+        }
+    }
+
+    pub fn scheme(&self) -> SeedScheme {
+        self.scheme
+    }
+
+    /// The address for a seed from anywhere, not just the counter space.
+    pub fn derive(&self, seed: &[u8]) -> [u8; PUBKEY_LEN] {
+        self.deriver.derive(seed)
+    }
+
+    pub fn base_pubkey(&self) -> &[u8; PUBKEY_LEN] {
+        &self.base_pubkey
+    }
+
+    pub fn owner_pubkey(&self) -> &[u8; PUBKEY_LEN] {
+        &self.owner_pubkey
+    }
+
+    /// The bytes `derive` hashes for `seed`: `base || seed || owner`.
+    pub fn hash_input(&self, seed: &[u8]) -> Vec<u8> {
+        [&self.base_pubkey[..], seed, &self.owner_pubkey].concat()
+    }
+}
+
+impl Derivation for CreateWithSeedDerivation {
+    type Artifacts = [u8; SEED_LEN];
+
+    fn candidate(&self, counter: u64, out: &mut Candidate<[u8; SEED_LEN]>) {
+        out.artifacts = self.scheme.seed_from_counter(counter);
+        out.pubkey = self.deriver.derive(&out.artifacts);
+    }
+
+    fn identity(&self) -> Vec<u8> {
+        // Kept byte for byte as before the trait existed, so saved resume
+        // tokens stay valid.
+        [
+            &self.scheme.fingerprint_version().to_le_bytes()[..],
+            &self.base_pubkey,
+            &self.owner_pubkey,
+        ]
+        .concat()
+    }
+
+    fn seed_scheme(&self) -> Option<SeedScheme> {
+        Some(self.scheme)
+    }
+
+    fn with_seed_scheme(&self, scheme: SeedScheme) -> Option<CreateWithSeedDerivation> {
+        Some(CreateWithSeedDerivation {
+            scheme,
+            ..self.clone()
+        })
+    }
+}
+
+/// `createWithSeed` with seeds from a [`SeedCharset`], e.g. short ones
+/// that are easy to type. The artifact is the seed, as long as the charset
+/// makes it.
+#[derive(Clone)]
+pub struct CharsetSeedDerivation {
+    deriver: Deriver,
+    base_pubkey: [u8; PUBKEY_LEN],
+    owner_pubkey: [u8; PUBKEY_LEN],
+    charset: SeedCharset,
+}
+
+impl CharsetSeedDerivation {
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        charset: SeedCharset,
+    ) -> CharsetSeedDerivation {
+        CharsetSeedDerivation {
+            deriver: Deriver::new(base_pubkey, owner_pubkey),
+            base_pubkey: *base_pubkey,
+            owner_pubkey: *owner_pubkey,
+            charset,
+        }
+    }
+
+    pub fn charset(&self) -> &SeedCharset {
+        &self.charset
+    }
+}
+
+impl Derivation for CharsetSeedDerivation {
+    type Artifacts = Vec<u8>;
+
+    fn candidate(&self, counter: u64, out: &mut Candidate<Vec<u8>>) {
+        let mut buf = [0u8; MAX_SEED_LEN];
+        let seed = self.charset.seed_from_counter(counter, &mut buf);
+        out.artifacts.clear();
+        out.artifacts.extend_from_slice(seed);
+        out.pubkey = self.deriver.derive(seed);
+    }
+
+    fn identity(&self) -> Vec<u8> {
+        [
+            &b"charset seed"[..],
+            &self.charset.seed_len().to_le_bytes(),
+            self.charset.charset(),
+            &self.base_pubkey,
+            &self.owner_pubkey,
+        ]
+        .concat()
+    }
+
+    fn seed_space(&self) -> Option<u64> {
+        self.charset.space()
+    }
+}
+
+#[cfg(feature = "passphrase")]
+/// `createWithSeed` with seeds from [`seed_from_passphrase`] instead of
+/// [`crate::generate_seed_from_counter`], so a match's seed can be regenerated
+/// from the passphrase and its counter instead of being stored. Each
+/// attempt costs four extra SHA-256 runs for the HKDF.
+#[derive(Clone)]
+pub struct PassphraseSeedDerivation {
+    deriver: Deriver,
+    base_pubkey: [u8; PUBKEY_LEN],
+    owner_pubkey: [u8; PUBKEY_LEN],
+    /// Wiped when the derivation is dropped.
+    passphrase: Zeroizing<Vec<u8>>,
+}
+
+#[cfg(feature = "passphrase")]
+impl PassphraseSeedDerivation {
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        passphrase: &[u8],
+    ) -> PassphraseSeedDerivation {
+        PassphraseSeedDerivation {
+            deriver: Deriver::new(base_pubkey, owner_pubkey),
+            base_pubkey: *base_pubkey,
+            owner_pubkey: *owner_pubkey,
+            passphrase: Zeroizing::new(passphrase.to_vec()),
+        }
+    }
+}
+
+#[cfg(feature = "passphrase")]
+impl Derivation for PassphraseSeedDerivation {
+    type Artifacts = [u8; SEED_LEN];
+
+    fn candidate(&self, counter: u64, out: &mut Candidate<[u8; SEED_LEN]>) {
+        out.artifacts = seed_from_passphrase(&self.passphrase, counter);
+        out.pubkey = self.deriver.derive(&out.artifacts);
+    }
+
+    fn identity(&self) -> Vec<u8> {
+        [
+            &b"passphrase seed"[..],
+            &PASSPHRASE_SEED_VERSION.to_le_bytes(),
+            &self.base_pubkey,
+            &self.owner_pubkey,
+            &passphrase_fingerprint(&self.passphrase),
+        ]
+        .concat()
+    }
+}
+
+#[cfg(feature = "keypair")]
+/// Plain keypairs: counter → [`secret_from_counter`] → Ed25519 public key.
+/// The address is the signer itself, e.g. a mint or a wallet; the artifact
+/// is its 32-byte secret.
+#[derive(Clone)]
+pub struct SignerKeyDerivation {
+    master_secret: [u8; 32],
+}
+
+#[cfg(feature = "keypair")]
+impl SignerKeyDerivation {
+    /// `master_secret` seeds every key: keep it as secret as the keys.
+    pub fn new(master_secret: &[u8; 32]) -> SignerKeyDerivation {
+        SignerKeyDerivation {
+            master_secret: *master_secret,
+        }
+    }
+}
+
+#[cfg(feature = "keypair")]
+impl Derivation for SignerKeyDerivation {
+    type Artifacts = [u8; 32];
+
+    fn candidate(&self, counter: u64, out: &mut Candidate<[u8; 32]>) {
+        out.artifacts = secret_from_counter(&self.master_secret, counter);
+        out.pubkey = pubkey_from_secret(&out.artifacts);
+    }
+
+    fn identity(&self) -> Vec<u8> {
+        let secret_hash = Sha256::new()
+            .chain_update(b"web-vanity signer identity")
+            .chain_update(self.master_secret)
+            .finalize();
+        [&b"signer key"[..], &secret_hash].concat()
+    }
+}
+
+#[cfg(feature = "keypair")]
+/// Signed by every key [`IncrementalKeyDerivation::verified_secret`]
+/// reconstructs, to check it against the address before handing it out.
+const TEST_MESSAGE: &[u8] = b"web-vanity incremental key check";
+
+#[cfg(feature = "keypair")]
+/// Keypairs along a line, one point addition apart: the key for counter `i`
+/// has scalar `a + 8i`, for a base scalar `a` hashed from the master
+/// secret, so its public key is the previous one plus `8B`. That is several
+/// times cheaper than [`SignerKeyDerivation`]'s hash and full scalar
+/// multiplication per key; the secret is only put together for a match.
+/// Consecutive counters are worked out 64 at a time, so that compressing
+/// them shares one field inversion.
+///
+/// `a` is clamped and below `2^254 + 2^253`, so every `a + 8i` is a clamped
+/// scalar too and means the same key to libraries that clamp on import.
+/// The keys have no RFC 8032 seed, though: a match gives an
+/// [`ExpandedSecret`], which wallets expecting a seed can't import.
+#[derive(Clone)]
+pub struct IncrementalKeyDerivation {
+    base_scalar: [u8; 32],
+    prefix: [u8; 32],
+    base_point: EdwardsPoint,
+    step: edwards::Step,
+    master_hash: [u8; 32],
+    block: RefCell<KeyBlock>,
+}
+
+/// Public keys [`IncrementalKeyDerivation`] compresses together, with
+/// one shared inversion. Much beyond this saves little: the inversion
+/// costs about as much as 250 multiplications, a key about 13.
+#[cfg(feature = "keypair")]
+const KEY_BLOCK: usize = 64;
+
+/// The run of keys derived last and what it takes to step on from it.
+#[cfg(feature = "keypair")]
+#[derive(Clone, Default)]
+struct KeyBlock {
+    /// The counter of `pubkeys[0]`.
+    first: u64,
+    pubkeys: Vec<[u8; 32]>,
+    /// The point of the last key, unless it came from a jump and was
+    /// never needed in extended coordinates.
+    last: Option<ExtendedPoint>,
+    points: Vec<ExtendedPoint>,
+    scratch: Vec<edwards::Fe>,
+}
+
+#[cfg(feature = "keypair")]
+impl IncrementalKeyDerivation {
+    /// `master_secret` seeds every key: keep it as secret as the keys.
+    pub fn new(master_secret: &[u8; 32]) -> IncrementalKeyDerivation {
+        let hash = Sha512::new()
+            .chain_update(b"web-vanity incremental key")
+            .chain_update(master_secret)
+            .finalize();
+        let mut base_scalar = clamp(hash[..32].try_into().unwrap());
+        // Room for 8 * u64::MAX below 2^255.
+        base_scalar[31] &= !32;
+        IncrementalKeyDerivation {
+            base_scalar,
+            prefix: hash[32..].try_into().unwrap(),
+            base_point: EdwardsPoint::mul_base_clamped(base_scalar),
+            step: edwards::Step::decompress(
+                &EdwardsPoint::mul_base(&Scalar::from(8u8))
+                    .compress()
+                    .to_bytes(),
+            )
+            .expect("8B is on the curve"),
+            master_hash: Sha256::new()
+                .chain_update(b"web-vanity incremental identity")
+                .chain_update(master_secret)
+                .finalize()
+                .into(),
+            block: RefCell::default(),
+        }
+    }
+
+    /// The key for `counter`: scalar `a + 8 * counter`, as an integer.
+    pub fn secret(&self, counter: u64) -> ExpandedSecret {
+        let mut scalar = self.base_scalar;
+        let mut carry = (counter as u128) << 3;
+        for byte in scalar.iter_mut() {
+            let sum = *byte as u128 + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        ExpandedSecret::new(scalar, self.prefix)
+    }
+
+    /// [`IncrementalKeyDerivation::secret`], checked before it is handed
+    /// out: it must have `pubkey` as its public key and sign a test message
+    /// that verifies under `pubkey`.
+    pub fn verified_secret(
+        &self,
+        counter: u64,
+        pubkey: &[u8; PUBKEY_LEN],
+    ) -> Result<ExpandedSecret, VanityError> {
+        let secret = self.secret(counter);
+        if secret.pubkey() != *pubkey {
+            return Err(VanityError::Internal(format!(
+                "key {counter} doesn't rebuild into its public key"
+            )));
+        }
+        if !verify_signature(pubkey, TEST_MESSAGE, &secret.sign(TEST_MESSAGE)) {
+            return Err(VanityError::Internal(format!(
+                "key {counter} signs what doesn't verify"
+            )));
+        }
+        Ok(secret)
+    }
+
+    fn pubkey(&self, counter: u64) -> [u8; 32] {
+        let mut block = self.block.borrow_mut();
+        let offset = counter.wrapping_sub(block.first);
+        if let Some(pubkey) = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| block.pubkeys.get(offset))
+        {
+            return *pubkey;
+        }
+        if offset != block.pubkeys.len() as u64 || block.pubkeys.is_empty() {
+            // A jump: one key from scratch, leaving batching to whatever
+            // comes after it.
+            let pubkey = (self.base_point
+                + EdwardsPoint::mul_base(&(Scalar::from(counter) * Scalar::from(8u8))))
+            .compress()
+            .to_bytes();
+            block.first = counter;
+            block.pubkeys.clear();
+            block.pubkeys.push(pubkey);
+            block.last = None;
+            return pubkey;
+        }
+        let KeyBlock {
+            pubkeys,
+            last,
+            points,
+            scratch,
+            ..
+        } = &mut *block;
+        let mut point = match last {
+            Some(point) => *point,
+            None => ExtendedPoint::decompress(pubkeys.last().unwrap())
+                .expect("an encoded public key decompresses"),
+        };
+        // Up to `u64::MAX` and no further.
+        let len = (u64::MAX - counter).min(KEY_BLOCK as u64 - 1) as usize + 1;
+        points.clear();
+        for _ in 0..len {
+            point = point.add_step(&self.step);
+            points.push(point);
+        }
+        edwards::encode_all(points, scratch, pubkeys);
+        *last = Some(point);
+        block.first = counter;
+        block.pubkeys[0]
+    }
+}
+
+#[cfg(feature = "keypair")]
+impl Derivation for IncrementalKeyDerivation {
+    /// The counter derived from, to rebuild the secret with.
+    type Artifacts = u64;
+
+    fn candidate(&self, counter: u64, out: &mut Candidate<u64>) {
+        out.artifacts = counter;
+        out.pubkey = self.pubkey(counter);
+    }
+
+    fn identity(&self) -> Vec<u8> {
+        [&b"incremental key"[..], &self.master_hash].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::generate_seed_from_counter;
+
+    #[test]
+    fn test_candidates_are_deterministic() {
+        let create_with_seed = CreateWithSeedDerivation::new(&[1; 32], &[4; 32]);
+        let mut out = Candidate::default();
+        create_with_seed.candidate(42, &mut out);
+        assert_eq!(out.artifacts, generate_seed_from_counter(42));
+        assert_eq!(
+            out.pubkey,
+            Deriver::new(&[1; 32], &[4; 32]).derive(&out.artifacts)
+        );
+
+        #[cfg(feature = "keypair")]
+        {
+            let signer = SignerKeyDerivation::new(&[9; 32]);
+            let mut out = Candidate::default();
+            signer.candidate(42, &mut out);
+            assert_eq!(out.artifacts, secret_from_counter(&[9; 32], 42));
+            assert_eq!(out.pubkey, pubkey_from_secret(&out.artifacts));
+        }
+
+        #[cfg(feature = "passphrase")]
+        {
+            let passphrase = PassphraseSeedDerivation::new(&[1; 32], &[4; 32], b"hunter2");
+            let mut out = Candidate::default();
+            passphrase.candidate(42, &mut out);
+            assert_eq!(out.artifacts, seed_from_passphrase(b"hunter2", 42));
+            assert_eq!(
+                out.pubkey,
+                Deriver::new(&[1; 32], &[4; 32]).derive(&out.artifacts)
+            );
+        }
+    }
+
+    #[test]
+    fn test_identities_differ_and_hide_secrets() {
+        assert_ne!(
+            CreateWithSeedDerivation::new(&[1; 32], &[4; 32]).identity(),
+            CreateWithSeedDerivation::new(&[4; 32], &[1; 32]).identity()
+        );
+        #[cfg(feature = "keypair")]
+        {
+            let a = SignerKeyDerivation::new(&[9; 32]).identity();
+            assert_ne!(a, SignerKeyDerivation::new(&[8; 32]).identity());
+            assert!(!a.windows(32).any(|window| window == [9; 32]));
+        }
+        #[cfg(feature = "passphrase")]
+        {
+            let passphrase =
+                |p: &[u8]| PassphraseSeedDerivation::new(&[1; 32], &[4; 32], p).identity();
+            let a = passphrase(b"correct horse battery staple");
+            assert_ne!(a, passphrase(b"correct horse battery stapler"));
+            assert!(!a.windows(7).any(|window| window == b"correct"));
+            assert_ne!(
+                a,
+                CreateWithSeedDerivation::new(&[1; 32], &[4; 32]).identity()
+            );
+        }
+    }
+
+    #[cfg(feature = "passphrase")]
+    #[test]
+    fn test_passphrase_seeds_regenerate_matches() {
+        use alloc::string::ToString;
+
+        let derivation = PassphraseSeedDerivation::new(&[1; 32], &[4; 32], b"hunter2");
+        let mut searcher =
+            crate::Searcher::with_pattern(derivation, Some("A".into()), None, false, 0).unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(1_000) {
+                break found;
+            }
+        };
+        // Only the counter is kept; the passphrase gives the seed back.
+        let seed = seed_from_passphrase(b"hunter2", found.counter);
+        let address = crate::derive::encode_pubkey(
+            &Deriver::new(&[1; 32], &[4; 32]).derive(&seed),
+            &mut [0; crate::derive::MAX_ENCODED_LEN],
+        )
+        .to_string();
+        assert_eq!(address, found.address);
+        assert!(address.starts_with('A'));
+    }
+
+    #[cfg(feature = "keypair")]
+    #[test]
+    fn test_incremental_keys_match_their_secrets() {
+        let derivation = IncrementalKeyDerivation::new(&[5; 32]);
+        let mut out = Candidate::default();
+        // Stepping from the last key and starting afresh agree, across
+        // blocks, going back into one and running into the last counter.
+        let counters = (0..200)
+            .chain([195, 7, 150, 1 << 40, (1 << 40) + 1, (1 << 40) + 70])
+            .chain((u64::MAX - 70..=u64::MAX).rev());
+        for counter in counters.chain([u64::MAX - 1, u64::MAX, 4, 5, 3]) {
+            derivation.candidate(counter, &mut out);
+            let secret = derivation.secret(counter);
+            assert_eq!(out.artifacts, counter);
+            assert_eq!(out.pubkey, secret.pubkey(), "{counter}");
+            assert_eq!(clamp(*secret.scalar()), *secret.scalar());
+        }
+        assert_eq!(
+            derivation.secret(1).scalar()[0],
+            derivation.secret(0).scalar()[0].wrapping_add(8)
+        );
+
+        let secret = derivation.verified_secret(3, &out.pubkey).unwrap();
+        let signature = secret.sign(b"hello");
+        assert!(verify_signature(&out.pubkey, b"hello", &signature));
+        let error = derivation.verified_secret(4, &out.pubkey).unwrap_err();
+        assert_eq!(error.kind(), crate::ErrorKind::Internal);
+
+        assert_ne!(
+            derivation.identity(),
+            IncrementalKeyDerivation::new(&[6; 32]).identity()
+        );
+        assert!(!derivation.identity().windows(32).any(|w| w == [5; 32]));
+    }
+
+    #[cfg(feature = "keypair")]
+    #[test]
+    fn test_incremental_matches_rebuild_into_signing_keys() {
+        let derivation = IncrementalKeyDerivation::new(&[5; 32]);
+        let mut searcher =
+            crate::Searcher::with_pattern(derivation.clone(), Some("ab".into()), None, true, 0)
+                .unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                break found;
+            }
+        };
+        let pubkey = crate::Pubkey::from_base58(&found.address).unwrap();
+        let secret = derivation
+            .verified_secret(found.seed, pubkey.as_bytes())
+            .unwrap();
+        assert_eq!(secret.pubkey(), pubkey.to_bytes());
+        assert_eq!(found.seed, found.counter);
+        // A library that clamps the scalar on import gets the same key.
+        assert_eq!(
+            EdwardsPoint::mul_base_clamped(*secret.scalar())
+                .compress()
+                .to_bytes(),
+            pubkey.to_bytes()
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! createWithSeed address derivation: `sha256(base || seed || owner)`.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::charmap::base58_digit;
+use crate::error::VanityError;
+use crate::estimate::BASE58_ALPHABET;
+
+pub const PUBKEY_LEN: usize = 32;
+
+/// Shortest base58 encoding of a pubkey (all zero bytes, "111…1").
+pub const MIN_ENCODED_LEN: usize = 32;
+/// Longest base58 encoding of a pubkey.
+pub const MAX_ENCODED_LEN: usize = five8::BASE58_ENCODED_32_MAX_LEN;
+
+/// Derives addresses for a fixed base and owner, reusing the hasher state
+/// that already absorbed the base pubkey.
+#[derive(Clone)]
+pub struct Deriver {
+    base_sha: Sha256,
+    owner_pubkey: [u8; PUBKEY_LEN],
+}
+
+impl Deriver {
+    pub fn new(base_pubkey: &[u8; PUBKEY_LEN], owner_pubkey: &[u8; PUBKEY_LEN]) -> Deriver {
+        Deriver {
+            base_sha: Sha256::new().chain_update(base_pubkey),
+            owner_pubkey: *owner_pubkey,
+        }
+    }
+
+    pub fn derive(&self, seed: &[u8]) -> [u8; PUBKEY_LEN] {
+        let mut hasher = self.base_sha.clone();
+        hasher.update(seed);
+        hasher.update(self.owner_pubkey);
+        hasher.finalize().into()
+    }
+}
+
+/// Base58-encodes a pubkey into `buf`, returning the encoded prefix of it.
+/// Panics if the encoder breaks its contract; the search loop uses
+/// [`try_encode_pubkey`] instead.
+pub fn encode_pubkey<'a>(
+    pubkey_bytes: &[u8; PUBKEY_LEN],
+    buf: &'a mut [u8; five8::BASE58_ENCODED_32_MAX_LEN],
+) -> &'a str {
+    match try_encode_pubkey(pubkey_bytes, buf) {
+        Ok(encoded) => encoded,
+        Err(error) => panic!("{error}"),
+    }
+}
+
+/// [`encode_pubkey`], failing with `Internal` instead of panicking if the
+/// encoder reports more bytes than `buf` holds or writes anything but
+/// base58: in wasm a panic takes the whole module down without saying why.
+/// Debug builds still assert, so tests catch it where it happens.
+pub fn try_encode_pubkey<'a>(
+    pubkey_bytes: &[u8; PUBKEY_LEN],
+    buf: &'a mut [u8; five8::BASE58_ENCODED_32_MAX_LEN],
+) -> Result<&'a str, VanityError> {
+    let encoded_len = five8::encode_32(pubkey_bytes, buf);
+    encoded_prefix(buf, encoded_len as usize)
+}
+
+/// `pubkey` encoded into a buffer of its own, with how many of its bytes
+/// are the address. That is 43 for about one pubkey in 17 and 44 for
+/// nearly all the rest, so code that looks at a position or a window of
+/// the address goes by this length rather than [`MAX_ENCODED_LEN`].
+#[inline]
+pub(crate) fn encode_candidate(
+    pubkey_bytes: &[u8; PUBKEY_LEN],
+) -> Result<([u8; MAX_ENCODED_LEN], usize), VanityError> {
+    let mut buf = [0u8; MAX_ENCODED_LEN];
+    let len = try_encode_pubkey(pubkey_bytes, &mut buf)?.len();
+    Ok((buf, len))
+}
+
+/// The first `len` bytes of `buf`, if they are text.
+fn encoded_prefix(buf: &[u8; MAX_ENCODED_LEN], len: usize) -> Result<&str, VanityError> {
+    let encoded = buf
+        .get(..len)
+        .and_then(|encoded| core::str::from_utf8(encoded).ok());
+    debug_assert!(encoded.is_some(), "bad base58 encoding: {len} of {buf:?}");
+    encoded.ok_or_else(|| {
+        VanityError::Internal(format!(
+            "base58 encoder produced {len} bytes that aren't an address"
+        ))
+    })
+}
+
+/// Most characters [`trailing_chars`] computes: 58^8 < 2^47, so its
+/// reduction stays within a u64.
+pub const MAX_TRAILING_CHARS: usize = 8;
+
+/// The last `k` characters (at most [`MAX_TRAILING_CHARS`]) of the pubkey's
+/// base58 encoding, without encoding the rest: they are the pubkey as a
+/// number modulo 58^k, written in base 58. Leading zero bytes only add '1's,
+/// digit 0, in front, which is what a short number is padded with anyway.
+/// Reduces two bytes at a time in a u64 rather than in u128, which wasm32
+/// has no native division for. A larger `k` is clamped, asserting in debug
+/// builds.
+pub fn trailing_chars<'a>(
+    pubkey_bytes: &[u8; PUBKEY_LEN],
+    k: usize,
+    buf: &'a mut [u8; MAX_TRAILING_CHARS],
+) -> &'a [u8] {
+    debug_assert!(k <= MAX_TRAILING_CHARS, "{k} trailing characters");
+    let (digits, _) = buf.split_at_mut(k.min(MAX_TRAILING_CHARS));
+    let modulus = 58u64.pow(digits.len() as u32);
+    let mut rem = 0u64;
+    for &[high, low] in pubkey_bytes.as_chunks::<2>().0 {
+        rem = ((rem << 16) | u16::from_be_bytes([high, low]) as u64) % modulus;
+    }
+    for c in digits.iter_mut().rev() {
+        *c = BASE58_ALPHABET[(rem % 58) as usize];
+        rem /= 58;
+    }
+    digits
+}
+
+/// Decodes base58 of any length, keeping leading zero bytes ("1"s). Fails
+/// at the first character outside the alphabet.
+pub fn decode_base58(encoded: &str) -> Result<Vec<u8>, VanityError> {
+    // Little-endian big integer, reversed at the end.
+    let mut bytes: Vec<u8> = Vec::new();
+    for (position, character) in encoded.chars().enumerate() {
+        let digit = u8::try_from(character).ok().and_then(base58_digit).ok_or(
+            VanityError::InvalidBase58 {
+                character,
+                position,
+            },
+        )?;
+        let mut carry = digit as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = encoded.bytes().take_while(|&b| b == b'1').count();
+    bytes.resize(bytes.len() + zeros, 0);
+    bytes.reverse();
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    // Debug builds assert instead, so this only runs in release.
+    #[cfg(not(debug_assertions))]
+    #[test]
+    fn test_bad_encodings_are_typed_errors_in_release() {
+        use crate::error::ErrorKind;
+
+        let mut buf = [b'1'; MAX_ENCODED_LEN];
+        assert_eq!(
+            encoded_prefix(&buf, 32),
+            Ok("11111111111111111111111111111111")
+        );
+        let kind = |result: Result<&str, VanityError>| result.unwrap_err().kind();
+        assert_eq!(
+            kind(encoded_prefix(&buf, MAX_ENCODED_LEN + 1)),
+            ErrorKind::Internal
+        );
+        buf[3] = 0xff;
+        assert_eq!(kind(encoded_prefix(&buf, 32)), ErrorKind::Internal);
+    }
+
+    #[test]
+    fn test_encoded_lengths_and_alphabet() {
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        assert_eq!(
+            encode_pubkey(&[0; PUBKEY_LEN], &mut buf).len(),
+            MIN_ENCODED_LEN
+        );
+        assert_eq!(
+            encode_pubkey(&[255; PUBKEY_LEN], &mut buf).len(),
+            MAX_ENCODED_LEN
+        );
+
+        // Every character five8 emits is in the alphabet, and every alphabet
+        // character shows up given enough keys.
+        let mut seen = [false; 58];
+        let deriver = Deriver::new(&[1; PUBKEY_LEN], &[4; PUBKEY_LEN]);
+        for i in 0u32..200 {
+            let pubkey = deriver.derive(&i.to_le_bytes());
+            for c in encode_pubkey(&pubkey, &mut buf).bytes() {
+                let index = BASE58_ALPHABET.iter().position(|&a| a == c).unwrap();
+                seen[index] = true;
+            }
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn test_derive_matches_plain_sha256() {
+        let base = [1; PUBKEY_LEN];
+        let owner = [4; PUBKEY_LEN];
+        let seed = b"AAAAAAAAAAAAAAAA";
+
+        let expected: [u8; PUBKEY_LEN] = Sha256::new()
+            .chain_update(base)
+            .chain_update(seed)
+            .chain_update(owner)
+            .finalize()
+            .into();
+        assert_eq!(Deriver::new(&base, &owner).derive(seed), expected);
+    }
+
+    #[test]
+    fn test_encode_pubkey() {
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        assert_eq!(
+            encode_pubkey(&[0; PUBKEY_LEN], &mut buf),
+            "11111111111111111111111111111111"
+        );
+        assert_eq!(encode_pubkey(&[255; PUBKEY_LEN], &mut buf).len(), 44);
+    }
+
+    #[test]
+    fn test_trailing_chars_match_the_encoding() {
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        let mut tail = [0u8; MAX_TRAILING_CHARS];
+        let mut check = |pubkey: &[u8; PUBKEY_LEN]| {
+            let encoded = encode_pubkey(pubkey, &mut buf).as_bytes();
+            for k in 0..=MAX_TRAILING_CHARS {
+                let expected = &encoded[encoded.len() - k..];
+                assert_eq!(trailing_chars(pubkey, k, &mut tail), expected, "{pubkey:?}");
+            }
+            encoded.len()
+        };
+
+        let mut lengths = [0usize; MAX_ENCODED_LEN + 1];
+        let mut state = 0x243f_6a88_85a3_08d3u64;
+        for i in 0..5_000 {
+            let mut pubkey = [0u8; PUBKEY_LEN];
+            for chunk in pubkey.chunks_exact_mut(8) {
+                state = crate::permute::mix(state);
+                chunk.copy_from_slice(&state.to_le_bytes());
+            }
+            // Leading zero bytes, up to all of them.
+            pubkey[..i % (PUBKEY_LEN + 1)].fill(0);
+            lengths[check(&pubkey)] += 1;
+        }
+        assert!(lengths[MAX_ENCODED_LEN] > 0 && lengths[MAX_ENCODED_LEN - 1] > 0);
+        assert!(lengths[MIN_ENCODED_LEN] > 0);
+
+        let mut small = [0u8; PUBKEY_LEN];
+        small[PUBKEY_LEN - 1] = 57;
+        assert_eq!(check(&small), MIN_ENCODED_LEN);
+        check(&[255; PUBKEY_LEN]);
+    }
+
+    #[test]
+    fn test_decode_base58() {
+        assert_eq!(decode_base58(""), Ok(vec![]));
+        assert_eq!(decode_base58("11"), Ok(vec![0, 0]));
+        assert_eq!(decode_base58("z"), Ok(vec![57]));
+        assert_eq!(decode_base58("21"), Ok(vec![58]));
+        assert_eq!(
+            decode_base58("2O"),
+            Err(VanityError::InvalidBase58 {
+                character: 'O',
+                position: 1
+            })
+        );
+    }
+}
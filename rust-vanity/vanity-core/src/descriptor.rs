@@ -0,0 +1,258 @@
+//! A machine-readable account of how a [`SeedScheme`] turns a counter
+//! into a seed, for implementations outside this crate (a verifier in
+//! another language, a GPU kernel) to reproduce it and check themselves
+//! against the embedded vectors. Every number comes from the constants
+//! the scheme itself runs on, and the vectors from the scheme itself, so
+//! the description can't drift from the code.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::permute::{
+    SeedNamespace, MIX_MULTIPLIERS, MIX_SHIFTS, NAMESPACE_DOMAIN, ROUNDS, ROUND_KEY_STEP,
+};
+use crate::seed::{
+    SeedScheme, ALPHANUMERIC_CHARS, SEED_LEN, SEED_MIX_MULTIPLIER, SEED_STATE_SHIFT,
+};
+
+/// Counters every descriptor has a vector for, with and without
+/// [`VECTOR_NAMESPACE`].
+const VECTOR_COUNTERS: [u64; 6] = [0, 1, 58, 123_456_789, 1 << 32, u64::MAX];
+
+/// Namespace tag of the namespaced vectors.
+const VECTOR_NAMESPACE: &str = "conformance";
+
+/// How a counter becomes a seed under one scheme. 64-bit constants are
+/// "0x" hex and counters decimal strings, since both pass what a JSON
+/// number holds exactly.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct SeedSchemeDescriptor {
+    /// [`SeedScheme::version`]; a different mapping always gets a new one.
+    pub version: u32,
+    /// Characters seeds are drawn from, indexed from 0.
+    pub charset: String,
+    pub seed_len: usize,
+    pub mixing: MixingDescriptor,
+    pub namespace: NamespaceDescriptor,
+    pub vectors: Vec<SeedVector>,
+}
+
+/// The counter → seed step itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct MixingDescriptor {
+    /// Names the function below, e.g. "split-golden-ratio-v1".
+    pub function: String,
+    pub multiplier: String,
+    pub shift_bits: u32,
+    /// Low bits of each state a character is picked with: 64 for the
+    /// whole state, 32 for the legacy wasm32 scheme's truncation.
+    pub index_bits: u32,
+    /// The function in words, one step per entry; all arithmetic wraps
+    /// at 2^64.
+    pub steps: Vec<String>,
+}
+
+/// How a [`SeedNamespace`] tag changes the counter before the mixing
+/// step: a keyed Feistel permutation over the counter's 32-bit halves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct NamespaceDescriptor {
+    /// Hashed with SHA-256 in front of the tag; the digest's first 8
+    /// bytes, little-endian, are the key.
+    pub domain: String,
+    pub rounds: u32,
+    /// Added to the running key before mixing out each round key.
+    pub round_key_step: String,
+    /// splitmix64's finalizer, `x ^= x >> s0; x *= m0; x ^= x >> s1;
+    /// x *= m1; x ^= x >> s2`.
+    pub mix_shifts: [u32; 3],
+    pub mix_multipliers: [String; 2],
+    pub steps: Vec<String>,
+}
+
+/// One conformance vector.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct SeedVector {
+    pub counter: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub namespace: Option<String>,
+    pub seed: String,
+}
+
+fn hex(value: u64) -> String {
+    format!("{value:#018x}")
+}
+
+fn steps(steps: &[&str]) -> Vec<String> {
+    steps.iter().map(|step| step.to_string()).collect()
+}
+
+impl SeedScheme {
+    pub fn descriptor(self) -> SeedSchemeDescriptor {
+        let (function, index_bits) = match self {
+            SeedScheme::LegacyWasm32 => ("split-golden-ratio-wasm32-legacy", 32),
+            SeedScheme::V1 => ("split-golden-ratio-v1", 64),
+        };
+        let mixing = MixingDescriptor {
+            function: function.into(),
+            multiplier: hex(SEED_MIX_MULTIPLIER),
+            shift_bits: SEED_STATE_SHIFT,
+            index_bits,
+            steps: steps(&[
+                "a = counter; b = counter * multiplier",
+                "for i in 0..seed_len/2: \
+                 seed[i] = charset[(a mod 2^index_bits) % len(charset)]; \
+                 seed[i + seed_len/2] = charset[(b mod 2^index_bits) % len(charset)]; \
+                 a >>= shift_bits; b >>= shift_bits",
+            ]),
+        };
+        let namespace = NamespaceDescriptor {
+            domain: String::from_utf8_lossy(NAMESPACE_DOMAIN).into_owned(),
+            rounds: ROUNDS as u32,
+            round_key_step: hex(ROUND_KEY_STEP),
+            mix_shifts: MIX_SHIFTS,
+            mix_multipliers: MIX_MULTIPLIERS.map(hex),
+            steps: steps(&[
+                "key = u64_le(sha256(domain || tag)[0..8])",
+                "state = key; for r in 0..rounds: state += round_key_step; \
+                 round_key[r] = mix(state)",
+                "l = counter >> 32; r = counter & 0xffffffff",
+                "for k in 0..rounds: (l, r) = (r, l ^ (mix(r ^ round_key[k]) >> 32))",
+                "counter = l << 32 | r",
+            ]),
+        };
+        let tagged = SeedNamespace::new(VECTOR_NAMESPACE).expect("valid tag");
+        let vectors = [None, Some(&tagged)]
+            .into_iter()
+            .flat_map(|namespace| VECTOR_COUNTERS.map(|counter| (namespace, counter)))
+            .map(|(namespace, counter)| {
+                let physical = namespace.map_or(counter, |namespace| namespace.apply(counter));
+                let seed = self.seed_from_counter(physical);
+                SeedVector {
+                    counter: counter.to_string(),
+                    namespace: namespace.map(|namespace| namespace.tag().into()),
+                    seed: String::from_utf8_lossy(&seed).into_owned(),
+                }
+            })
+            .collect();
+        SeedSchemeDescriptor {
+            version: self.version(),
+            charset: String::from_utf8_lossy(ALPHANUMERIC_CHARS).into_owned(),
+            seed_len: SEED_LEN,
+            mixing,
+            namespace,
+            vectors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    fn parse_hex(value: &str) -> u64 {
+        u64::from_str_radix(value.strip_prefix("0x").unwrap(), 16).unwrap()
+    }
+
+    /// The scheme rebuilt from nothing but its descriptor, as an outside
+    /// implementation would.
+    fn seed_from_descriptor(descriptor: &SeedSchemeDescriptor, counter: u64) -> String {
+        let charset = descriptor.charset.as_bytes();
+        let len = charset.len() as u64;
+        let half = descriptor.seed_len / 2;
+        let shift = descriptor.mixing.shift_bits;
+        let index_mask = u64::MAX >> (64 - descriptor.mixing.index_bits);
+        let mut a = counter;
+        let mut b = counter.wrapping_mul(parse_hex(&descriptor.mixing.multiplier));
+        let mut seed = alloc::vec![0u8; descriptor.seed_len];
+        for i in 0..half {
+            seed[i] = charset[((a & index_mask) % len) as usize];
+            seed[i + half] = charset[((b & index_mask) % len) as usize];
+            a >>= shift;
+            b >>= shift;
+        }
+        String::from_utf8(seed).unwrap()
+    }
+
+    fn namespace_from_descriptor(namespace: &NamespaceDescriptor, tag: &str, counter: u64) -> u64 {
+        let [s0, s1, s2] = namespace.mix_shifts;
+        let [m0, m1] = namespace.mix_multipliers.clone().map(|m| parse_hex(&m));
+        let mix = |mut x: u64| {
+            x = (x ^ (x >> s0)).wrapping_mul(m0);
+            x = (x ^ (x >> s1)).wrapping_mul(m1);
+            x ^ (x >> s2)
+        };
+        let digest = Sha256::new()
+            .chain_update(namespace.domain.as_bytes())
+            .chain_update(tag.as_bytes())
+            .finalize();
+        let mut state = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        let step = parse_hex(&namespace.round_key_step);
+        let round_keys: Vec<u64> = (0..namespace.rounds)
+            .map(|_| {
+                state = state.wrapping_add(step);
+                mix(state)
+            })
+            .collect();
+        let (mut l, mut r) = ((counter >> 32) as u32, counter as u32);
+        for key in round_keys {
+            (l, r) = (r, l ^ (mix(r as u64 ^ key) >> 32) as u32);
+        }
+        (l as u64) << 32 | r as u64
+    }
+
+    #[test]
+    fn test_descriptor_reproduces_the_scheme() {
+        for scheme in SeedScheme::ALL {
+            let descriptor = scheme.descriptor();
+            assert_eq!(descriptor.version, scheme.version());
+            assert_eq!(descriptor.vectors.len(), 2 * VECTOR_COUNTERS.len());
+            for vector in &descriptor.vectors {
+                let counter = vector.counter.parse().unwrap();
+                let physical = match &vector.namespace {
+                    Some(tag) => namespace_from_descriptor(&descriptor.namespace, tag, counter),
+                    None => counter,
+                };
+                assert_eq!(seed_from_descriptor(&descriptor, physical), vector.seed);
+            }
+            let strided = (0..2_000u64).map(|i| i.wrapping_mul(0x0123_4567_89ab_cdef));
+            for counter in strided {
+                let live = scheme.seed_from_counter(counter);
+                let rebuilt = seed_from_descriptor(&descriptor, counter);
+                assert_eq!(rebuilt.as_bytes(), live, "{counter}");
+                let tagged = SeedNamespace::new("abc").unwrap().apply(counter);
+                let namespace = namespace_from_descriptor(&descriptor.namespace, "abc", counter);
+                assert_eq!(namespace, tagged, "{counter}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_v1_vectors_are_pinned() {
+        // The same pins as the scheme's own test: a descriptor that
+        // changed under version 1 would break every saved result.
+        let vectors = SeedScheme::V1.descriptor().vectors;
+        let plain: Vec<_> = vectors
+            .iter()
+            .filter(|vector| vector.namespace.is_none())
+            .map(|vector| (vector.counter.as_str(), vector.seed.as_str()))
+            .collect();
+        assert_eq!(plain[0], ("0", "AAAAAAAAAAAAAAAA"));
+        assert_eq!(plain[1], ("1", "BAAAAAAAV4Zaw3fr"));
+        assert_eq!(plain[3], ("123456789", "7x5HAAAAuxbHZ8VP"));
+        assert_eq!(plain[5], ("18446744073709551615", "EQvS1kwVwWX2FsSp"));
+        assert_eq!(
+            SeedScheme::V1.descriptor().mixing.multiplier,
+            "0x9e3779b97f4a7c15"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Empirical character distributions, to show (and check) how far from
+//! uniform the encoded addresses are.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::charmap::base58_digit;
+use crate::derive::{encode_candidate, Deriver, MAX_ENCODED_LEN};
+use crate::error::VanityError;
+use crate::estimate::{prefix_probability, BASE58_ALPHABET};
+use crate::seed::generate_seed_from_counter;
+
+/// Keys for the throwaway addresses [`char_distribution`] derives.
+const SAMPLE_BASE: [u8; 32] = [0x11; 32];
+const SAMPLE_OWNER: [u8; 32] = [0x22; 32];
+
+/// How often one character turned up.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct CharCount {
+    pub character: char,
+    pub count: u32,
+    /// Probability of the character under the encoding intervals, for
+    /// position 0 only.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub expected: Option<f64>,
+}
+
+/// What [`char_distribution`] counted.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct CharDistribution {
+    pub position: u8,
+    pub samples: u32,
+    /// One entry per base58 character, in alphabet order.
+    pub counts: Vec<CharCount>,
+    /// Addresses too short to have a character at `position`; with the
+    /// counts, they add up to `samples`.
+    pub too_short: u32,
+}
+
+/// Counts the character at `position` over `samples` addresses derived
+/// from counters 0, 1, ... with fixed throwaway keys, the way a search
+/// derives them. For position 0 each count comes with the analytic
+/// probability the difficulty estimates use, so the two can be compared;
+/// the skew there comes from 2^256 not being a power of 58. Fails if
+/// `position` is past the longest address.
+pub fn char_distribution(position: u8, samples: u32) -> Result<CharDistribution, VanityError> {
+    if position as usize >= MAX_ENCODED_LEN {
+        return Err(VanityError::InvalidOptions(format!(
+            "position {position} is past the last character of an address ({})",
+            MAX_ENCODED_LEN - 1
+        )));
+    }
+    let deriver = Deriver::new(&SAMPLE_BASE, &SAMPLE_OWNER);
+    let mut counts = [0u32; 58];
+    let mut too_short = 0;
+    for counter in 0..samples as u64 {
+        let seed = generate_seed_from_counter(counter);
+        let (encoded, len) = encode_candidate(&deriver.derive(&seed))?;
+        let digit = encoded[..len]
+            .get(position as usize)
+            .and_then(|&c| base58_digit(c))
+            .map(usize::from);
+        match digit {
+            Some(digit) => counts[digit] += 1,
+            None => too_short += 1,
+        }
+    }
+    Ok(CharDistribution {
+        position,
+        samples,
+        counts: BASE58_ALPHABET
+            .iter()
+            .zip(counts)
+            .map(|(&c, count)| CharCount {
+                character: c as char,
+                count,
+                expected: (position == 0).then(|| prefix_probability(&[c], false)),
+            })
+            .collect(),
+        too_short,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(distribution: &CharDistribution) -> u32 {
+        distribution.counts.iter().map(|c| c.count).sum::<u32>() + distribution.too_short
+    }
+
+    #[test]
+    fn test_histogram_sums_to_samples() {
+        for position in [0, 10, 43] {
+            let distribution = char_distribution(position, 2_000).unwrap();
+            assert_eq!(distribution.counts.len(), 58);
+            assert_eq!(total(&distribution), 2_000, "{position}");
+            assert_eq!(
+                distribution.counts[0].expected.is_some(),
+                position == 0,
+                "{position}"
+            );
+        }
+        // Most addresses have 44 characters, but not all.
+        let last = char_distribution(43, 2_000).unwrap();
+        assert!(last.too_short > 0 && last.too_short < 400);
+        assert!(char_distribution(44, 1).is_err());
+        assert_eq!(total(&char_distribution(0, 0).unwrap()), 0);
+    }
+
+    #[test]
+    fn test_first_character_follows_the_analytic_distribution() {
+        let samples = 50_000;
+        let distribution = char_distribution(0, samples).unwrap();
+        let n = samples as f64;
+        for count in &distribution.counts {
+            let p = count.expected.unwrap();
+            let expected = n * p;
+            // Five standard deviations, plus slack for the rarest ones.
+            let tolerance = 5.0 * (n * p * (1.0 - p)).sqrt() + 3.0;
+            assert!(
+                (count.count as f64 - expected).abs() <= tolerance,
+                "{}: {} vs {expected:.1}",
+                count.character,
+                count.count
+            );
+        }
+        // Far from uniform: '2' is many times likelier than 'z'.
+        let of = |c: char| {
+            distribution
+                .counts
+                .iter()
+                .find(|count| count.character == c)
+        };
+        assert!(of('2').unwrap().count > 10 * of('z').unwrap().count.max(1));
+    }
+}
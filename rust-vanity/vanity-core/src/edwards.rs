@@ -0,0 +1,301 @@
+//! Just enough edwards25519 to encode a run of points with one inversion.
+//!
+//! Compressing a point divides by its `Z` coordinate, and that inversion is
+//! most of what [`curve25519_dalek::EdwardsPoint::compress`] costs.
+//! curve25519-dalek keeps its coordinates and field type private, so a run
+//! of consecutive keys is stepped here instead, on fiat-crypto's field
+//! arithmetic, and [`encode_all`] inverts every `Z` at once with
+//! Montgomery's trick: one inversion and three multiplications per point.
+
+use alloc::vec::Vec;
+
+#[cfg(not(target_pointer_width = "64"))]
+use fiat_crypto::curve25519_32 as fiat;
+#[cfg(target_pointer_width = "64")]
+use fiat_crypto::curve25519_64 as fiat;
+
+use fiat::{fiat_25519_loose_field_element as Loose, fiat_25519_tight_field_element as Tight};
+
+/// The curve constant `d = -121665 / 121666`.
+const D: [u8; 32] = [
+    163, 120, 89, 19, 202, 77, 235, 117, 171, 216, 65, 65, 77, 10, 112, 0, 152, 232, 121, 119, 121,
+    64, 199, 140, 115, 254, 111, 43, 238, 108, 3, 82,
+];
+
+/// `2^((p - 1) / 4)`, a square root of -1.
+const SQRT_M1: [u8; 32] = [
+    176, 160, 14, 74, 39, 27, 238, 196, 120, 228, 47, 173, 6, 24, 67, 47, 167, 215, 251, 61, 153,
+    0, 77, 43, 11, 223, 193, 79, 128, 36, 131, 43,
+];
+
+/// An integer mod `2^255 - 19`.
+#[derive(Clone, Copy)]
+pub(crate) struct Fe(Tight);
+
+impl Fe {
+    /// Ignores the top bit, which holds a sign in a compressed point.
+    fn from_bytes(bytes: &[u8; 32]) -> Fe {
+        let mut bytes = *bytes;
+        bytes[31] &= 0x7f;
+        let mut out = Fe::zero().0;
+        fiat::fiat_25519_from_bytes(&mut out, &bytes);
+        Fe(out)
+    }
+
+    fn zero() -> Fe {
+        let mut out = Tight(Default::default());
+        fiat::fiat_25519_from_bytes(&mut out, &[0; 32]);
+        Fe(out)
+    }
+
+    fn one() -> Fe {
+        let mut one = [0; 32];
+        one[0] = 1;
+        Fe::from_bytes(&one)
+    }
+
+    /// Canonical: fully reduced.
+    fn to_bytes(self) -> [u8; 32] {
+        let mut out = [0; 32];
+        fiat::fiat_25519_to_bytes(&mut out, &self.0);
+        out
+    }
+
+    fn is_negative(self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+
+    fn loose(self) -> Loose {
+        let mut out = Loose(Default::default());
+        fiat::fiat_25519_relax(&mut out, &self.0);
+        out
+    }
+
+    fn carry(loose: Loose) -> Fe {
+        let mut out = Fe::zero().0;
+        fiat::fiat_25519_carry(&mut out, &loose);
+        Fe(out)
+    }
+
+    fn add(self, other: Fe) -> Fe {
+        let mut out = Loose(Default::default());
+        fiat::fiat_25519_add(&mut out, &self.0, &other.0);
+        Fe::carry(out)
+    }
+
+    fn sub(self, other: Fe) -> Fe {
+        let mut out = Loose(Default::default());
+        fiat::fiat_25519_sub(&mut out, &self.0, &other.0);
+        Fe::carry(out)
+    }
+
+    fn neg(self) -> Fe {
+        let mut out = Loose(Default::default());
+        fiat::fiat_25519_opp(&mut out, &self.0);
+        Fe::carry(out)
+    }
+
+    fn mul(self, other: Fe) -> Fe {
+        let mut out = Fe::zero().0;
+        fiat::fiat_25519_carry_mul(&mut out, &self.loose(), &other.loose());
+        Fe(out)
+    }
+
+    /// `self^(2^n)`.
+    fn square_times(self, n: u32) -> Fe {
+        let mut out = self;
+        for _ in 0..n {
+            let mut square = Fe::zero().0;
+            fiat::fiat_25519_carry_square(&mut square, &out.loose());
+            out = Fe(square);
+        }
+        out
+    }
+
+    fn equals(self, other: Fe) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+
+    /// `(self^(2^250 - 1), self^11)`, the shared start of
+    /// [`Fe::invert`] and [`Fe::pow_p58`].
+    fn pow22501(self) -> (Fe, Fe) {
+        let t0 = self.square_times(1);
+        let t2 = self.mul(t0.square_times(2));
+        let t3 = t0.mul(t2);
+        let t5 = t2.mul(t3.square_times(1));
+        let t7 = t5.square_times(5).mul(t5);
+        let t9 = t7.square_times(10).mul(t7);
+        let t11 = t9.square_times(20).mul(t9);
+        let t13 = t11.square_times(10).mul(t7);
+        let t15 = t13.square_times(50).mul(t13);
+        let t17 = t15.square_times(100).mul(t15);
+        let t19 = t17.square_times(50).mul(t13);
+        (t19, t3)
+    }
+
+    /// `self^(p - 2)`, the inverse of a nonzero element.
+    fn invert(self) -> Fe {
+        let (t19, t3) = self.pow22501();
+        t19.square_times(5).mul(t3)
+    }
+
+    /// `self^((p - 5) / 8)`, for square roots.
+    fn pow_p58(self) -> Fe {
+        let (t19, _) = self.pow22501();
+        t19.square_times(2).mul(self)
+    }
+
+    /// `sqrt(u / v)`, if it exists.
+    fn sqrt_ratio(u: Fe, v: Fe) -> Option<Fe> {
+        let v3 = v.square_times(1).mul(v);
+        let v7 = v3.square_times(1).mul(v);
+        let r = u.mul(v3).mul(u.mul(v7).pow_p58());
+        let check = v.mul(r.square_times(1));
+        if check.equals(u) {
+            Some(r)
+        } else if check.equals(u.neg()) {
+            Some(r.mul(Fe::from_bytes(&SQRT_M1)))
+        } else {
+            None
+        }
+    }
+}
+
+/// A point in extended coordinates: `x = X/Z`, `y = Y/Z`, `xy = T/Z`.
+#[derive(Clone, Copy)]
+pub(crate) struct ExtendedPoint {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+/// A point to add again and again, kept as `(y + x, y - x, 2dxy)`.
+#[derive(Clone, Copy)]
+pub(crate) struct Step {
+    y_plus_x: Fe,
+    y_minus_x: Fe,
+    xy2d: Fe,
+}
+
+impl ExtendedPoint {
+    /// The point a 32-byte compressed encoding stands for, if any.
+    pub(crate) fn decompress(bytes: &[u8; 32]) -> Option<ExtendedPoint> {
+        let y = Fe::from_bytes(bytes);
+        let yy = y.square_times(1);
+        let u = yy.sub(Fe::one());
+        let v = yy.mul(Fe::from_bytes(&D)).add(Fe::one());
+        let mut x = Fe::sqrt_ratio(u, v)?;
+        if x.is_negative() != (bytes[31] >> 7 == 1) {
+            x = x.neg();
+        }
+        Some(ExtendedPoint {
+            x,
+            y,
+            z: Fe::one(),
+            t: x.mul(y),
+        })
+    }
+
+    /// `self + step`, in eight multiplications.
+    pub(crate) fn add_step(&self, step: &Step) -> ExtendedPoint {
+        let a = self.y.sub(self.x).mul(step.y_minus_x);
+        let b = self.y.add(self.x).mul(step.y_plus_x);
+        let c = self.t.mul(step.xy2d);
+        let d = self.z.add(self.z);
+        let (e, f, g, h) = (b.sub(a), d.sub(c), d.add(c), b.add(a));
+        ExtendedPoint {
+            x: e.mul(f),
+            y: g.mul(h),
+            z: f.mul(g),
+            t: e.mul(h),
+        }
+    }
+}
+
+impl Step {
+    /// The point a compressed encoding stands for, if any.
+    pub(crate) fn decompress(bytes: &[u8; 32]) -> Option<Step> {
+        // Decompressed points have `Z = 1`, so `X` and `Y` are affine.
+        let point = ExtendedPoint::decompress(bytes)?;
+        let d2 = Fe::from_bytes(&D).add(Fe::from_bytes(&D));
+        Some(Step {
+            y_plus_x: point.y.add(point.x),
+            y_minus_x: point.y.sub(point.x),
+            xy2d: point.t.mul(d2),
+        })
+    }
+}
+
+/// Compresses every point in `points` into `out`, with one inversion
+/// between them; `scratch` is reused, to save allocating.
+pub(crate) fn encode_all(points: &[ExtendedPoint], scratch: &mut Vec<Fe>, out: &mut Vec<[u8; 32]>) {
+    // Running products of the Zs, then their inverse, unwound one by one:
+    // 1 / Z_i = (Z_0 ... Z_i-1) / (Z_0 ... Z_i).
+    scratch.clear();
+    let mut product = Fe::one();
+    for point in points {
+        scratch.push(product);
+        product = product.mul(point.z);
+    }
+    let mut inverse = product.invert();
+    out.clear();
+    out.resize(points.len(), [0; 32]);
+    for ((point, before), encoded) in points.iter().zip(scratch.iter()).zip(out.iter_mut()).rev() {
+        let z_inv = inverse.mul(*before);
+        inverse = inverse.mul(point.z);
+        *encoded = point.y.mul(z_inv).to_bytes();
+        encoded[31] |= (point.x.mul(z_inv).is_negative() as u8) << 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::edwards::EdwardsPoint;
+    use curve25519_dalek::scalar::Scalar;
+
+    #[test]
+    fn test_stepped_points_encode_like_dalek() {
+        let start = EdwardsPoint::mul_base(&Scalar::from(12345u32));
+        let step = EdwardsPoint::mul_base(&Scalar::from(8u8));
+        let mut point = ExtendedPoint::decompress(&start.compress().to_bytes()).unwrap();
+        let fast_step = Step::decompress(&step.compress().to_bytes()).unwrap();
+        let mut points = Vec::new();
+        let mut expected = Vec::new();
+        let mut reference = start;
+        for _ in 0..100 {
+            point = point.add_step(&fast_step);
+            reference += step;
+            points.push(point);
+            expected.push(reference.compress().to_bytes());
+        }
+        let mut out = Vec::new();
+        encode_all(&points, &mut Vec::new(), &mut out);
+        assert_eq!(out, expected);
+        // Both signs of x come up, so the sign bit is checked both ways.
+        assert!(out.iter().any(|key| key[31] >> 7 == 1));
+        assert!(out.iter().any(|key| key[31] >> 7 == 0));
+        encode_all(&[], &mut Vec::new(), &mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_decompress_rejects_what_is_off_the_curve() {
+        // y = 2 gives x^2 = 3 / (4d + 1), which is not a square.
+        let mut two = [0; 32];
+        two[0] = 2;
+        assert!(ExtendedPoint::decompress(&two).is_none());
+        let one = Fe::one().to_bytes();
+        let identity = ExtendedPoint::decompress(&one).unwrap();
+        assert!(identity.x.equals(Fe::zero()));
+        assert!(Fe::from_bytes(&SQRT_M1)
+            .square_times(1)
+            .equals(Fe::one().neg()));
+        assert!(Fe::from_bytes(&D)
+            .mul(Fe::one())
+            .invert()
+            .invert()
+            .equals(Fe::from_bytes(&D)));
+    }
+}
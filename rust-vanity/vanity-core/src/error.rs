@@ -0,0 +1,335 @@
+//! Errors raised while configuring a search.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::Serialize;
+
+/// What went wrong, without the details. The discriminant is the error's
+/// stable numeric code; the serialized form is the variant name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[repr(u16)]
+pub enum ErrorKind {
+    EmptyPattern = 1,
+    InvalidPatternChar = 2,
+    PatternTooLong = 3,
+    BadBaseLength = 4,
+    BadOwnerLength = 5,
+    InvalidBase = 6,
+    InvalidOwner = 7,
+    MissingBase = 8,
+    MissingOwner = 9,
+    ConflictingOptions = 10,
+    InvalidOptions = 11,
+    InvalidStopFlag = 12,
+    Multiple = 13,
+    Unsupported = 14,
+    Internal = 15,
+    InvalidResumeToken = 16,
+    ResumeTokenMismatch = 17,
+    SelfTestFailed = 18,
+    InvalidBase58 = 19,
+    BadPubkeyLength = 20,
+    InvalidByteMask = 21,
+    WorkerFailed = 22,
+    InvalidProgressBuffer = 23,
+    InvalidSeenFilter = 24,
+    InvalidWorkUnit = 25,
+    Infeasible = 26,
+    InvalidPatternList = 27,
+    UnknownCluster = 28,
+    UnknownExplorer = 29,
+    ReentrantCall = 30,
+    SearchExhausted = 31,
+    SearchStopped = 32,
+}
+
+impl ErrorKind {
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VanityError {
+    /// Neither a prefix nor a suffix was given (or both were blank), so the
+    /// first attempt would trivially match.
+    EmptyPattern,
+    /// The pattern contains a character no address can contain, e.g. `0`.
+    InvalidPatternChar {
+        pattern: String,
+        character: char,
+        /// Index of `character` in the pattern, counted in characters.
+        position: usize,
+        /// Name of the [`Alphabet`](crate::Alphabet) checked against.
+        alphabet: &'static str,
+    },
+    /// A pattern longer than any encoded address.
+    PatternTooLong {
+        len: usize,
+        max: usize,
+    },
+    BadBaseLength(usize),
+    BadOwnerLength(usize),
+    /// The base pubkey isn't valid base58 for 32 bytes.
+    InvalidBase(String),
+    InvalidOwner(String),
+    MissingBase,
+    MissingOwner,
+    ConflictingOptions(String),
+    /// An options object or option value couldn't be understood.
+    InvalidOptions(String),
+    InvalidStopFlag(String),
+    /// Several problems found at once, e.g. by a builder.
+    Multiple(Vec<VanityError>),
+    Unsupported(String),
+    /// A bug rather than bad input.
+    Internal(String),
+    /// A resume token that couldn't be parsed.
+    InvalidResumeToken(String),
+    /// A well-formed resume token saved from a searcher with different keys,
+    /// pattern, case flag or seed scheme.
+    ResumeTokenMismatch,
+    /// The build computes different addresses than the known-answer
+    /// vectors; it must not be used.
+    SelfTestFailed(String),
+    /// A string that isn't base58; `position` counts characters.
+    InvalidBase58 {
+        character: char,
+        position: usize,
+    },
+    /// A pubkey that isn't 32 bytes, given as bytes or decoded from base58.
+    BadPubkeyLength(usize),
+    /// A byte mask or value that is too long, of mismatched lengths, or
+    /// that no pubkey could match.
+    InvalidByteMask(String),
+    /// A web worker couldn't be created or loaded, or gave up on its search.
+    WorkerFailed(String),
+    /// A progress counter array that isn't shared memory, or a slot outside
+    /// it.
+    InvalidProgressBuffer(String),
+    /// Seen-filter bytes that can't be read, or filters that can't be
+    /// merged.
+    InvalidSeenFilter(String),
+    /// A work unit whose version or config hash doesn't fit, or nothing to
+    /// collate.
+    InvalidWorkUnit(String),
+    /// A pattern expected to take longer than the caller allows; see
+    /// [`crate::check_feasible`].
+    Infeasible {
+        expected_attempts: f64,
+        /// How long `expected_attempts` take at the reference rate, for
+        /// people, e.g. "240 years".
+        duration: String,
+    },
+    /// A line of a pattern list that can't be read or searched for; see
+    /// [`crate::parse_pattern_list`].
+    InvalidPatternList {
+        /// 1-based.
+        line: usize,
+        reason: String,
+    },
+    /// A cluster name other than mainnet(-beta), devnet or testnet.
+    UnknownCluster(String),
+    /// An explorer [`crate::explorer_url`] can't link to.
+    UnknownExplorer(String),
+    /// A callback, named here, called a method of the searcher running it.
+    ReentrantCall(String),
+    /// A search that had to find a match ran out of attempts, or of
+    /// counters, first.
+    SearchExhausted {
+        attempts: u64,
+    },
+    /// A search that had to find a match was stopped first, e.g. by a time
+    /// limit.
+    SearchStopped {
+        attempts: u64,
+    },
+}
+
+impl VanityError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            VanityError::EmptyPattern => ErrorKind::EmptyPattern,
+            VanityError::InvalidPatternChar { .. } => ErrorKind::InvalidPatternChar,
+            VanityError::PatternTooLong { .. } => ErrorKind::PatternTooLong,
+            VanityError::BadBaseLength(_) => ErrorKind::BadBaseLength,
+            VanityError::BadOwnerLength(_) => ErrorKind::BadOwnerLength,
+            VanityError::InvalidBase(_) => ErrorKind::InvalidBase,
+            VanityError::InvalidOwner(_) => ErrorKind::InvalidOwner,
+            VanityError::MissingBase => ErrorKind::MissingBase,
+            VanityError::MissingOwner => ErrorKind::MissingOwner,
+            VanityError::ConflictingOptions(_) => ErrorKind::ConflictingOptions,
+            VanityError::InvalidOptions(_) => ErrorKind::InvalidOptions,
+            VanityError::InvalidStopFlag(_) => ErrorKind::InvalidStopFlag,
+            VanityError::Multiple(_) => ErrorKind::Multiple,
+            VanityError::Unsupported(_) => ErrorKind::Unsupported,
+            VanityError::Internal(_) => ErrorKind::Internal,
+            VanityError::InvalidResumeToken(_) => ErrorKind::InvalidResumeToken,
+            VanityError::ResumeTokenMismatch => ErrorKind::ResumeTokenMismatch,
+            VanityError::SelfTestFailed(_) => ErrorKind::SelfTestFailed,
+            VanityError::InvalidBase58 { .. } => ErrorKind::InvalidBase58,
+            VanityError::BadPubkeyLength(_) => ErrorKind::BadPubkeyLength,
+            VanityError::InvalidByteMask(_) => ErrorKind::InvalidByteMask,
+            VanityError::WorkerFailed(_) => ErrorKind::WorkerFailed,
+            VanityError::InvalidProgressBuffer(_) => ErrorKind::InvalidProgressBuffer,
+            VanityError::InvalidSeenFilter(_) => ErrorKind::InvalidSeenFilter,
+            VanityError::InvalidWorkUnit(_) => ErrorKind::InvalidWorkUnit,
+            VanityError::Infeasible { .. } => ErrorKind::Infeasible,
+            VanityError::InvalidPatternList { .. } => ErrorKind::InvalidPatternList,
+            VanityError::UnknownCluster(_) => ErrorKind::UnknownCluster,
+            VanityError::UnknownExplorer(_) => ErrorKind::UnknownExplorer,
+            VanityError::ReentrantCall(_) => ErrorKind::ReentrantCall,
+            VanityError::SearchExhausted { .. } => ErrorKind::SearchExhausted,
+            VanityError::SearchStopped { .. } => ErrorKind::SearchStopped,
+        }
+    }
+
+    pub fn code(&self) -> u16 {
+        self.kind().code()
+    }
+
+    /// Collapses a list of problems: `None` if empty, the error itself if
+    /// there is just one, `Multiple` otherwise.
+    pub fn from_problems(mut problems: Vec<VanityError>) -> Option<VanityError> {
+        match problems.len() {
+            0 => None,
+            1 => problems.pop(),
+            _ => Some(VanityError::Multiple(problems)),
+        }
+    }
+}
+
+impl fmt::Display for VanityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VanityError::EmptyPattern => {
+                write!(
+                    f,
+                    "pattern is empty; set a prefix or suffix, or allow an empty pattern"
+                )
+            }
+            VanityError::InvalidPatternChar {
+                pattern,
+                character,
+                position,
+                alphabet,
+            } => write!(
+                f,
+                "pattern {pattern:?} contains {character:?} at position {position}, which is not in the {alphabet} alphabet"
+            ),
+            VanityError::PatternTooLong { len, max } => {
+                write!(f, "pattern is {len} characters; addresses have at most {max}")
+            }
+            VanityError::BadBaseLength(len) => write!(f, "base pubkey must be 32 bytes, got {len}"),
+            VanityError::BadOwnerLength(len) => {
+                write!(f, "owner pubkey must be 32 bytes, got {len}")
+            }
+            VanityError::InvalidBase(detail) => write!(f, "invalid base pubkey: {detail}"),
+            VanityError::InvalidOwner(detail) => write!(f, "invalid owner pubkey: {detail}"),
+            VanityError::MissingBase => write!(f, "base pubkey is required"),
+            VanityError::MissingOwner => write!(f, "owner pubkey is required"),
+            VanityError::ConflictingOptions(detail)
+            | VanityError::InvalidOptions(detail)
+            | VanityError::InvalidStopFlag(detail)
+            | VanityError::InvalidProgressBuffer(detail)
+            | VanityError::Unsupported(detail) => write!(f, "{detail}"),
+            VanityError::Multiple(errors) => {
+                write!(f, "invalid searcher options: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{error}")?;
+                }
+                Ok(())
+            }
+            VanityError::Internal(detail) => write!(f, "internal error: {detail}"),
+            VanityError::InvalidResumeToken(detail) => {
+                write!(f, "invalid resume token: {detail}")
+            }
+            VanityError::SelfTestFailed(detail) => write!(f, "self test failed: {detail}"),
+            VanityError::InvalidBase58 {
+                character,
+                position,
+            } => write!(
+                f,
+                "{character:?} at position {position} is not a base58 character"
+            ),
+            VanityError::BadPubkeyLength(len) => write!(f, "pubkey must be 32 bytes, got {len}"),
+            VanityError::InvalidByteMask(detail) => write!(f, "invalid byte mask: {detail}"),
+            VanityError::WorkerFailed(detail) => write!(f, "worker failed: {detail}"),
+            VanityError::InvalidSeenFilter(detail) => write!(f, "invalid seen filter: {detail}"),
+            VanityError::InvalidWorkUnit(detail) => write!(f, "invalid work unit: {detail}"),
+            VanityError::Infeasible {
+                expected_attempts,
+                duration,
+            } => write!(
+                f,
+                "pattern needs about {expected_attempts:.1e} attempts on average, {duration} at \
+                 1M attempts/s; use force to search anyway"
+            ),
+            VanityError::InvalidPatternList { line, reason } => write!(f, "line {line}: {reason}"),
+            VanityError::UnknownCluster(name) => write!(
+                f,
+                "unknown cluster {name:?}; expected mainnet, devnet or testnet"
+            ),
+            VanityError::UnknownExplorer(name) => write!(
+                f,
+                "unknown explorer {name:?}; expected explorer.solana.com, solscan or solanafm"
+            ),
+            VanityError::ReentrantCall(callback) => write!(
+                f,
+                "the {callback} callback called into the searcher running it; stop it through \
+                 the SearchControl passed to the callback instead"
+            ),
+            VanityError::SearchExhausted { attempts } => {
+                write!(f, "no match within {attempts} attempts")
+            }
+            VanityError::SearchStopped { attempts } => {
+                write!(f, "search stopped after {attempts} attempts without a match")
+            }
+            VanityError::ResumeTokenMismatch => write!(
+                f,
+                "resume token was saved for a different search configuration"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for VanityError {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_codes_are_stable() {
+        assert_eq!(VanityError::EmptyPattern.code(), 1);
+        assert_eq!(VanityError::BadBaseLength(3).code(), 4);
+        assert_eq!(VanityError::Internal(String::new()).code(), 15);
+        assert_eq!(VanityError::MissingOwner.kind(), ErrorKind::MissingOwner);
+    }
+
+    #[test]
+    fn test_from_problems() {
+        assert_eq!(VanityError::from_problems(vec![]), None);
+        assert_eq!(
+            VanityError::from_problems(vec![VanityError::MissingBase]),
+            Some(VanityError::MissingBase)
+        );
+        let error =
+            VanityError::from_problems(vec![VanityError::MissingBase, VanityError::MissingOwner])
+                .unwrap();
+        assert_eq!(error.kind(), ErrorKind::Multiple);
+        assert_eq!(
+            error.to_string(),
+            "invalid searcher options: base pubkey is required; owner pubkey is required"
+        );
+    }
+}
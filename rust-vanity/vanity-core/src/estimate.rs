@@ -0,0 +1,574 @@
+//! Difficulty estimation for vanity patterns.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::alphabet::BASE58;
+use crate::error::VanityError;
+use crate::float;
+use crate::matcher::MatchType;
+
+pub const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// Number of leading pattern characters whose probability is computed exactly
+// from the encoding intervals; past this point the digits are uniform enough
+// that 1/58 per position is indistinguishable from the exact value.
+const EXACT_PREFIX_CHARS: usize = 4;
+
+/// Base58 digits an address character may take to match pattern character `c`.
+fn digit_variants(c: u8, case_insensitive: bool) -> Vec<usize> {
+    BASE58.variants(c, case_insensitive).collect()
+}
+
+/// Probability that a uniformly random `n_bytes`-byte value with a non-zero
+/// leading byte encodes to a string starting with `digits`.
+fn exact_prefix_probability(digits: &[usize], n_bytes: i32) -> f64 {
+    let lo = float::powi(256.0, n_bytes - 1);
+    let hi = float::powi(256.0, n_bytes);
+    let value = digits.iter().fold(0f64, |acc, &d| acc * 58.0 + d as f64);
+    let k = digits.len() as i32;
+    let max_len = float::ceil(n_bytes as f64 * float::ln(256.0) / float::ln(58.0)) as i32;
+
+    let mut covered = 0f64;
+    for len in k..=max_len {
+        let scale = float::powi(58.0, len - k);
+        let start = (value * scale).max(lo);
+        let end = ((value + 1.0) * scale).min(hi);
+        if end > start {
+            covered += end - start;
+        }
+    }
+    covered / (hi - lo)
+}
+
+pub fn prefix_probability(prefix: &[u8], case_insensitive: bool) -> f64 {
+    // Each leading '1' stands for a leading zero byte rather than a digit.
+    let ones = prefix.iter().take_while(|&&c| c == b'1').count();
+    if ones >= 32 {
+        return 0.0;
+    }
+    let rest = &prefix[ones..];
+    let mut probability = float::powi(256.0, -(ones as i32));
+    if rest.is_empty() {
+        return probability;
+    }
+    let n_bytes = 32 - ones as i32;
+    probability *= 255.0 / 256.0;
+
+    let exact_len = rest.len().min(EXACT_PREFIX_CHARS);
+    let variants: Vec<Vec<usize>> = rest[..exact_len]
+        .iter()
+        .map(|&c| digit_variants(c, case_insensitive))
+        .collect();
+
+    let exact = sum_variant_probabilities(&variants, &mut Vec::new(), n_bytes);
+    probability * exact * uniform_probability(&rest[exact_len..], case_insensitive)
+}
+
+/// Sums the exact prefix probability over every case variant of the digits.
+fn sum_variant_probabilities(
+    variants: &[Vec<usize>],
+    digits: &mut Vec<usize>,
+    n_bytes: i32,
+) -> f64 {
+    match variants.split_first() {
+        None => exact_prefix_probability(digits, n_bytes),
+        Some((first, rest)) => first
+            .iter()
+            .map(|&d| {
+                digits.push(d);
+                let p = sum_variant_probabilities(rest, digits, n_bytes);
+                digits.pop();
+                p
+            })
+            .sum(),
+    }
+}
+
+pub fn uniform_probability(chars: &[u8], case_insensitive: bool) -> f64 {
+    BASE58.uniform_probability(chars, case_insensitive)
+}
+
+/// Per-attempt probability that a derived address matches the pattern.
+pub fn match_probability(
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+    case_insensitive: bool,
+) -> f64 {
+    let prefix_p = prefix.map_or(1.0, |p| prefix_probability(p.as_bytes(), case_insensitive));
+    let suffix_p = suffix.map_or(1.0, |s| uniform_probability(s.as_bytes(), case_insensitive));
+    prefix_p * suffix_p
+}
+
+/// Probability that the pattern appears anywhere in a typical 44-character
+/// address, treating each window as an independent uniform draw.
+pub fn contains_probability(pattern: &str, case_insensitive: bool) -> f64 {
+    let windows = five8::BASE58_ENCODED_32_MAX_LEN.saturating_sub(pattern.len()) + 1;
+    probability_found(
+        uniform_probability(pattern.as_bytes(), case_insensitive),
+        windows as u64,
+    )
+}
+
+/// Probability of at least one match in `attempts` tries, computed in log
+/// space so tiny probabilities and huge attempt counts stay finite.
+pub fn probability_found(probability: f64, attempts: u64) -> f64 {
+    if attempts == 0 || probability <= 0.0 {
+        return 0.0;
+    }
+    if probability >= 1.0 {
+        return 1.0;
+    }
+    -float::exp_m1(attempts as f64 * float::ln_1p(-probability))
+}
+
+/// Expected attempts to match each prefix of `pattern`, from its first
+/// character up to the whole pattern, as the user types it. Fails on the
+/// first character no address can contain, reporting its position.
+pub fn difficulty_curve(pattern: &str, case_insensitive: bool) -> Result<Vec<f64>, VanityError> {
+    MatchType::Prefix(pattern.to_string()).validate(case_insensitive)?;
+    let bytes = pattern.as_bytes();
+    Ok((1..=bytes.len())
+        .map(|len| 1.0 / prefix_probability(&bytes[..len], case_insensitive))
+        .collect())
+}
+
+/// How many attempts a search took compared with the odds.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct LuckStats {
+    /// Mean attempts to a match, `1 / p`; infinite if the pattern can't
+    /// match.
+    pub expected_attempts: f64,
+    pub actual_attempts: u64,
+    /// `expected_attempts / actual_attempts`, so 5.0 means a match five
+    /// times sooner than average. `None` before the first attempt.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub luck: Option<f64>,
+    /// Where `actual_attempts` falls in the geometric distribution of
+    /// attempts to a match: the chance a search succeeds within that many
+    /// attempts. 0.1 means luckier than 90% of searches.
+    pub percentile: f64,
+}
+
+/// Luck of a search that took `actual_attempts` at per-attempt match
+/// probability `probability`.
+pub fn luck_stats(probability: f64, actual_attempts: u64) -> LuckStats {
+    let expected_attempts = 1.0 / probability;
+    LuckStats {
+        expected_attempts,
+        actual_attempts,
+        luck: (actual_attempts > 0).then(|| expected_attempts / actual_attempts as f64),
+        percentile: probability_found(probability, actual_attempts),
+    }
+}
+
+/// Chances of success [`attempt_quantiles`] reports attempts for.
+pub const QUANTILE_CONFIDENCES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Attempts (and time) within which a search succeeds with some chance.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct AttemptQuantile {
+    /// E.g. 0.9 for "90% of searches are done by then".
+    pub confidence: f64,
+    /// Fewest attempts with at least `confidence` chance of a match;
+    /// infinite if the pattern can't match.
+    pub attempts: f64,
+    /// `attempts` at the rate given, in seconds; unset without a rate.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub seconds: Option<f64>,
+}
+
+/// What [`attempt_quantiles`] found.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct AttemptQuantiles {
+    /// Mean attempts to a match, which about 63% of searches beat.
+    pub expected_attempts: f64,
+    /// One per [`QUANTILE_CONFIDENCES`], in that order.
+    pub quantiles: Vec<AttemptQuantile>,
+}
+
+/// Fewest attempts `k` with `1 - (1 - p)^k >= confidence`, i.e.
+/// `ceil(ln(1 - confidence) / ln(1 - p))`, with both logarithms taken with
+/// `ln_1p` so tiny probabilities keep their precision. Infinite if
+/// `probability` is 0 (or so small the answer overflows), 1 if it is 1.
+pub fn attempts_for_confidence(probability: f64, confidence: f64) -> f64 {
+    if confidence <= 0.0 {
+        return 0.0;
+    }
+    if probability <= 0.0 || confidence >= 1.0 {
+        return f64::INFINITY;
+    }
+    if probability >= 1.0 {
+        return 1.0;
+    }
+    float::ceil(float::ln_1p(-confidence) / float::ln_1p(-probability)).max(1.0)
+}
+
+/// Attempts needed for each of [`QUANTILE_CONFIDENCES`] at per-attempt
+/// match probability `probability`, and the time they take at `rate`
+/// attempts per second, if given and positive. The distribution is
+/// geometric, so the median is only about 69% of the mean while the 99th
+/// percentile is 4.6 times it.
+pub fn attempt_quantiles(probability: f64, rate: Option<f64>) -> AttemptQuantiles {
+    let rate = rate.filter(|&rate| rate > 0.0);
+    AttemptQuantiles {
+        expected_attempts: 1.0 / probability,
+        quantiles: QUANTILE_CONFIDENCES
+            .iter()
+            .map(|&confidence| {
+                let attempts = attempts_for_confidence(probability, confidence);
+                AttemptQuantile {
+                    confidence,
+                    attempts,
+                    seconds: rate.map(|rate| attempts / rate),
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Hash rate [`check_feasible`] quotes durations at.
+pub const FEASIBILITY_REFERENCE_RATE: f64 = 1e6;
+
+/// Expected attempts past which [`check_feasible`] refuses a pattern by
+/// default: ten years at [`FEASIBILITY_REFERENCE_RATE`], between eight and
+/// nine case-sensitive characters.
+pub const DEFAULT_MAX_EXPECTED_ATTEMPTS: f64 = FEASIBILITY_REFERENCE_RATE * 10.0 * YEAR_SECONDS;
+
+const YEAR_SECONDS: f64 = 365.0 * 24.0 * 3600.0;
+
+/// A duration for people in the largest unit it fills: "3.2 hours", "12
+/// days", "1.5e9 years", or "forever" if it isn't finite.
+pub fn describe_duration(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        return "forever".into();
+    }
+    let (value, unit) = [
+        (YEAR_SECONDS, "years"),
+        (24.0 * 3600.0, "days"),
+        (3600.0, "hours"),
+        (60.0, "minutes"),
+    ]
+    .into_iter()
+    .find(|&(size, _)| seconds >= size)
+    .map_or((seconds, "seconds"), |(size, unit)| (seconds / size, unit));
+    if value >= 1e6 {
+        format!("{value:.1e} {unit}")
+    } else if value >= 10.0 {
+        format!("{} {unit}", float::round(value))
+    } else {
+        format!("{value:.1} {unit}")
+    }
+}
+
+/// Fails with `Infeasible`, quoting the expected attempts and how long they
+/// take at [`FEASIBILITY_REFERENCE_RATE`], if a match at `probability` per
+/// attempt is expected to take more than `max_expected_attempts`.
+pub fn check_feasible(probability: f64, max_expected_attempts: f64) -> Result<(), VanityError> {
+    let expected_attempts = 1.0 / probability;
+    if expected_attempts <= max_expected_attempts {
+        return Ok(());
+    }
+    Err(VanityError::Infeasible {
+        expected_attempts,
+        duration: describe_duration(expected_attempts / FEASIBILITY_REFERENCE_RATE),
+    })
+}
+
+/// A warning if a match at `probability` per attempt is expected to take
+/// more attempts than there are seeds: the search then ends with every
+/// seed tried, and quite possibly no match among them.
+pub fn seed_space_warning(probability: f64, seed_space: u64) -> Option<String> {
+    let expected_attempts = 1.0 / probability;
+    if expected_attempts <= seed_space as f64 {
+        return None;
+    }
+    let found = probability_found(probability, seed_space);
+    Some(format!(
+        "pattern needs about {expected_attempts:.1e} attempts on average but there are only \
+         {seed_space} seeds; a match exists with probability {:.1}%",
+        found * 100.0
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::search::Searcher;
+    use crate::ErrorKind;
+
+    fn count_matches(
+        prefix: Option<&str>,
+        suffix: Option<&str>,
+        case_insensitive: bool,
+        attempts: u64,
+    ) -> u64 {
+        let mut searcher = Searcher::new(
+            &[7; 32],
+            &[9; 32],
+            prefix.map(Into::into),
+            suffix.map(Into::into),
+            case_insensitive,
+            0,
+        )
+        .unwrap();
+
+        let mut matches = 0;
+        while searcher.attempts() < attempts {
+            let remaining = (attempts - searcher.attempts()) as u32;
+            if searcher.search_batch(remaining).is_some() {
+                matches += 1;
+            }
+        }
+        matches
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= expected * tolerance,
+            "{actual} not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn test_first_char_probabilities_sum_to_one() {
+        let total: f64 = BASE58_ALPHABET
+            .iter()
+            .map(|&c| prefix_probability(&[c], false))
+            .sum();
+        assert_close(total, 1.0, 1e-9);
+    }
+
+    #[test]
+    fn test_first_char_is_skewed() {
+        // 44-character encodings can only start with '2'..='J'.
+        let two = prefix_probability(b"2", false);
+        let z = prefix_probability(b"z", false);
+        assert!(two > 40.0 * z);
+    }
+
+    #[test]
+    fn test_suffix_probability_is_uniform() {
+        let attempts = |suffix: &str, case_insensitive| {
+            1.0 / match_probability(None, Some(suffix), case_insensitive)
+        };
+        assert_close(attempts("ab", false), 58.0 * 58.0, 1e-12);
+        assert_close(attempts("a", true), 29.0, 1e-12);
+        assert_close(attempts("2", true), 58.0, 1e-12);
+        // 'l' is not base58 but matches 'L' case-insensitively.
+        assert_close(attempts("l", true), 58.0, 1e-12);
+        // Per-character odds come from the alphabet, multiplied.
+        for suffix in ["ab", "Zz9", "l", "0"] {
+            for case_insensitive in [false, true] {
+                let odds: f64 = suffix
+                    .bytes()
+                    .map(|c| BASE58.char_probability(c, case_insensitive))
+                    .product();
+                assert_eq!(
+                    match_probability(None, Some(suffix), case_insensitive),
+                    odds
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_prefix_and_suffix_combine() {
+        let prefix = match_probability(Some("AB"), None, true);
+        let suffix = match_probability(None, Some("cd"), true);
+        let both = match_probability(Some("AB"), Some("cd"), true);
+        assert_close(both, prefix * suffix, 1e-12);
+    }
+
+    #[test]
+    fn test_contains_probability() {
+        let one = uniform_probability(b"ab", false);
+        let windows = contains_probability("ab", false);
+        assert!(windows > 40.0 * one && windows < 43.0 * one);
+        assert_eq!(contains_probability("0", false), 0.0);
+    }
+
+    #[test]
+    fn test_impossible_pattern() {
+        assert_eq!(match_probability(Some("0"), None, false), 0.0);
+        assert_eq!(match_probability(None, Some("Ol"), false), 0.0);
+    }
+
+    #[test]
+    fn test_estimate_matches_empirical_counts() {
+        let attempts = 60_000;
+        for (prefix, suffix, case_insensitive, tolerance) in [
+            (Some("2"), None, false, 0.1),
+            (Some("z"), None, false, 0.35),
+            (Some("j"), None, true, 0.1),
+            (None, Some("a"), true, 0.1),
+        ] {
+            let expected = attempts as f64 * match_probability(prefix, suffix, case_insensitive);
+            let actual = count_matches(prefix, suffix, case_insensitive, attempts) as f64;
+            assert_close(actual, expected, tolerance);
+        }
+    }
+
+    #[test]
+    fn test_probability_found_edge_cases() {
+        assert_eq!(probability_found(0.5, 0), 0.0);
+        assert_eq!(probability_found(0.0, u64::MAX), 0.0);
+        assert_eq!(probability_found(1.0, 1), 1.0);
+        assert_close(probability_found(0.5, 2), 0.75, 1e-12);
+
+        let tiny = probability_found(1e-80, 1_000);
+        assert!(tiny.is_finite() && tiny > 0.0);
+        assert_close(tiny, 1e-77, 1e-9);
+        assert_eq!(probability_found(1e-3, u64::MAX), 1.0);
+    }
+
+    #[test]
+    fn test_luck_stats() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+        // Geometric CDF 1 - (1 - p)^k, worked by hand.
+        let stats = luck_stats(0.5, 3);
+        assert_eq!(stats.expected_attempts, 2.0);
+        assert!(close(stats.luck.unwrap(), 2.0 / 3.0));
+        assert!(close(stats.percentile, 0.875));
+        assert!(close(luck_stats(0.5, 1).percentile, 0.5));
+
+        let p = 1.0 / 58.0;
+        assert!(close(luck_stats(p, 1).percentile, p));
+        // At the mean the CDF is 1 - (57/58)^58, close to 1 - 1/e.
+        let at_mean = luck_stats(p, 58);
+        assert!(close(at_mean.luck.unwrap(), 1.0));
+        assert!(close(at_mean.percentile, 0.635_314_914_510_321_8));
+        let tiny = luck_stats(1e-9, 1_000_000_000);
+        assert!((tiny.percentile - 0.632_120_558_6).abs() < 1e-9);
+
+        let none = luck_stats(0.25, 0);
+        assert_eq!((none.luck, none.percentile), (None, 0.0));
+        let impossible = luck_stats(0.0, 10);
+        assert!(impossible.expected_attempts.is_infinite());
+        assert_eq!(impossible.percentile, 0.0);
+    }
+
+    #[test]
+    fn test_difficulty_curve() {
+        for (pattern, case_insensitive) in [("AbC1", false), ("abc1", true), ("11z9", true)] {
+            let curve = difficulty_curve(pattern, case_insensitive).unwrap();
+            assert_eq!(curve.len(), 4);
+            for (len, expected) in curve.into_iter().enumerate() {
+                let prefix = &pattern[..=len];
+                let direct = 1.0 / match_probability(Some(prefix), None, case_insensitive);
+                assert_eq!(expected, direct, "{pattern} up to {prefix}");
+            }
+        }
+        assert_eq!(difficulty_curve("", false), Ok(vec![]));
+
+        // Folding case helps letters but not digits.
+        let sensitive = difficulty_curve("zz9", false).unwrap();
+        let folded = difficulty_curve("zz9", true).unwrap();
+        assert!(folded[1] < sensitive[1]);
+        assert!((folded[2] / folded[1] - sensitive[2] / sensitive[1]).abs() < 1e-6);
+
+        let error = difficulty_curve("Ab0c", false).unwrap_err();
+        assert!(matches!(
+            error,
+            VanityError::InvalidPatternChar {
+                position: 2,
+                character: '0',
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_attempt_quantiles() {
+        let attempts = |probability: f64| -> Vec<f64> {
+            attempt_quantiles(probability, None)
+                .quantiles
+                .iter()
+                .map(|quantile| quantile.attempts)
+                .collect()
+        };
+        // ceil(ln(1 - q) / ln(1 - p)), worked by hand.
+        assert_eq!(attempts(0.5), [1.0, 4.0, 7.0]);
+        assert_eq!(attempts(1.0 / 58.0), [40.0, 133.0, 265.0]);
+        for (probability, quantile) in [
+            (0.5, 4.0),
+            (1.0 / 58.0, 133.0),
+            (1e-6, attempts_for_confidence(1e-6, 0.9)),
+        ] {
+            assert!(probability_found(probability, quantile as u64) >= 0.9);
+            assert!(probability_found(probability, quantile as u64 - 1) < 0.9);
+        }
+
+        // Tiny probabilities: k is about -ln(1 - q) / p, without blowing up.
+        let tiny = attempt_quantiles(1e-30, Some(1e6));
+        assert_close(tiny.quantiles[0].attempts, 2f64.ln() * 1e30, 1e-12);
+        assert_close(tiny.quantiles[2].attempts, 100f64.ln() * 1e30, 1e-12);
+        assert_close(tiny.quantiles[0].seconds.unwrap(), 2f64.ln() * 1e24, 1e-12);
+        assert_eq!(tiny.quantiles[1].confidence, 0.9);
+        assert_close(tiny.expected_attempts, 1e30, 1e-12);
+
+        let impossible = attempt_quantiles(0.0, Some(1e6));
+        assert!(impossible
+            .quantiles
+            .iter()
+            .all(|q| q.attempts.is_infinite()));
+        assert!(impossible.quantiles[0].seconds.unwrap().is_infinite());
+        assert_eq!(attempts(1.0), [1.0, 1.0, 1.0]);
+        assert_eq!(attempt_quantiles(0.5, Some(0.0)).quantiles[0].seconds, None);
+        assert_eq!(attempts_for_confidence(0.5, 0.0), 0.0);
+        assert!(attempts_for_confidence(0.5, 1.0).is_infinite());
+    }
+
+    #[test]
+    fn test_describe_duration() {
+        assert_eq!(describe_duration(45.0), "45 seconds");
+        assert_eq!(describe_duration(3.0), "3.0 seconds");
+        assert_eq!(describe_duration(90.0), "1.5 minutes");
+        assert_eq!(describe_duration(3.2 * 3600.0), "3.2 hours");
+        assert_eq!(describe_duration(12.4 * 24.0 * 3600.0), "12 days");
+        assert_eq!(describe_duration(1.5e9 * YEAR_SECONDS), "1.5e9 years");
+        assert_eq!(describe_duration(f64::INFINITY), "forever");
+    }
+
+    #[test]
+    fn test_check_feasible() {
+        // A suffix of 8 is 58^8, about 1.3e14 attempts or four years at 1M/s;
+        // 9 is 58 times that.
+        let eight = match_probability(None, Some("zzzzzzzz"), false);
+        assert!(check_feasible(eight, DEFAULT_MAX_EXPECTED_ATTEMPTS).is_ok());
+        let nine = match_probability(None, Some("zzzzzzzzz"), false);
+        let error = check_feasible(nine, DEFAULT_MAX_EXPECTED_ATTEMPTS).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Infeasible);
+        assert_eq!(
+            error.to_string(),
+            "pattern needs about 7.4e15 attempts on average, 236 years at 1M attempts/s; \
+             use force to search anyway"
+        );
+        assert!(check_feasible(0.0, f64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_seed_space_warning() {
+        // 4 lowercase letters are 456,976 seeds; a suffix of "zzzz" takes
+        // 58^4, about 11.3M attempts.
+        let four = match_probability(None, Some("zzzz"), false);
+        let warning = seed_space_warning(four, 456_976).unwrap();
+        assert_eq!(
+            warning,
+            "pattern needs about 1.1e7 attempts on average but there are only 456976 seeds; \
+             a match exists with probability 4.0%"
+        );
+        assert_eq!(seed_space_warning(four, 1 << 40), None);
+        assert!(seed_space_warning(0.0, u64::MAX).is_some());
+    }
+}
@@ -0,0 +1,360 @@
+//! Families of seeds `stem + salt + index` whose addresses all match a
+//! pattern, e.g. `stake-Ab0`, `stake-Ab1`, … sorting together in explorers.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::derive::{encode_pubkey, Deriver, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::float;
+use crate::matcher::MatchType;
+use crate::seed::MAX_SEED_LEN;
+
+/// Most members a family can have.
+pub const MAX_FAMILY_SIZE: u32 = 1000;
+
+/// Salts are letters only, so where the salt ends and the index begins
+/// stays obvious in the seed.
+const SALT_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum FamilyStrategy {
+    /// One salt shared by the whole family, so the seeds differ only by
+    /// index. Exponentially harder in the family size: every address has to
+    /// match at once.
+    AllMatch,
+    /// A salt ground for each index on its own; about `size` times the work
+    /// of a single match.
+    Independent,
+}
+
+/// One seed of a family and the address it derives.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct FamilyMember {
+    pub index: u32,
+    /// The full seed, `stem + salt + index`.
+    pub seed: String,
+    pub address: String,
+}
+
+/// Odds of finding a whole family.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct FamilyEstimate {
+    /// Mean derivations until every member is found; infinite if the
+    /// pattern can't match or the odds underflow.
+    pub expected_attempts: f64,
+    /// How many times the work of grinding each member independently.
+    pub relative_to_independent: f64,
+    /// Set for [`FamilyStrategy::AllMatch`] families of more than one.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub warning: Option<String>,
+}
+
+/// Expected work for a family of `size` members when a single address
+/// matches with `probability`. With [`FamilyStrategy::AllMatch`], each salt
+/// is abandoned at its first non-matching member, which the estimate
+/// accounts for.
+pub fn family_estimate(probability: f64, size: u32, strategy: FamilyStrategy) -> FamilyEstimate {
+    let size_f = size as f64;
+    let independent = size_f / probability;
+    let expected_attempts = match strategy {
+        FamilyStrategy::Independent => independent,
+        FamilyStrategy::AllMatch if probability >= 1.0 => size_f,
+        FamilyStrategy::AllMatch => {
+            // Geometric number of salts, 1 / p^n, each costing
+            // 1 + p + … + p^(n-1) derivations on average.
+            let all = float::powi(probability, size as i32);
+            (1.0 - all) / ((1.0 - probability) * all)
+        }
+    };
+    let relative_to_independent = expected_attempts / independent;
+    let warning = (strategy == FamilyStrategy::AllMatch && size > 1).then(|| {
+        format!(
+            "all {size} addresses must match at once: about {relative_to_independent:.3e} times \
+             the work of grinding each one independently"
+        )
+    });
+    FamilyEstimate {
+        expected_attempts,
+        relative_to_independent,
+        warning,
+    }
+}
+
+/// The `n`th salt: "A" … "z", then "AA", "AB", …, so every salt is distinct.
+fn salt(mut n: u64) -> String {
+    let base = SALT_CHARS.len() as u64;
+    let mut salt = Vec::new();
+    loop {
+        salt.push(SALT_CHARS[(n % base) as usize]);
+        n /= base;
+        if n == 0 {
+            break;
+        }
+        n -= 1;
+    }
+    salt.reverse();
+    String::from_utf8(salt).unwrap()
+}
+
+fn digits(n: u32) -> usize {
+    n.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// Grinds salts until `size` seeds `stem + salt + index` (index from 0) all
+/// derive matching addresses, with one shared salt or one per index
+/// depending on the [`FamilyStrategy`]. Each derivation counts as an
+/// attempt.
+#[derive(Clone)]
+pub struct FamilySearcher {
+    deriver: Deriver,
+    match_type: MatchType,
+    case_insensitive: bool,
+    stem: String,
+    size: u32,
+    strategy: FamilyStrategy,
+    /// Salt being tried for the next member.
+    salt: u64,
+    /// Members found so far; with `AllMatch`, those of the current salt.
+    members: Vec<FamilyMember>,
+    attempts: u64,
+    exhausted: bool,
+}
+
+impl FamilySearcher {
+    /// Fails on an empty or unmatchable pattern, as [`crate::Searcher::new`]
+    /// does, on a size outside `1..=MAX_FAMILY_SIZE`, or if the stem leaves no
+    /// room for a salt and index within [`MAX_SEED_LEN`] bytes.
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        match_type: MatchType,
+        case_insensitive: bool,
+        stem: String,
+        size: u32,
+        strategy: FamilyStrategy,
+    ) -> Result<FamilySearcher, VanityError> {
+        if match_type.is_empty() {
+            return Err(VanityError::EmptyPattern);
+        }
+        match_type.validate(case_insensitive)?;
+        if !(1..=MAX_FAMILY_SIZE).contains(&size) {
+            return Err(VanityError::InvalidOptions(format!(
+                "family size must be from 1 to {MAX_FAMILY_SIZE}, got {size}"
+            )));
+        }
+        let shortest = stem.len() + 1 + digits(size - 1);
+        if shortest > MAX_SEED_LEN {
+            return Err(VanityError::InvalidOptions(format!(
+                "seed stem is {} bytes; with a salt and index, seeds would pass {MAX_SEED_LEN}",
+                stem.len()
+            )));
+        }
+        Ok(FamilySearcher {
+            deriver: Deriver::new(base_pubkey, owner_pubkey),
+            match_type,
+            case_insensitive,
+            stem,
+            size,
+            strategy,
+            salt: 0,
+            members: Vec::new(),
+            attempts: 0,
+            exhausted: false,
+        })
+    }
+
+    /// Runs up to `batch_size` derivations and returns whether the family is
+    /// complete.
+    pub fn search_batch(&mut self, batch_size: u32) -> bool {
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for _ in 0..batch_size {
+            if self.is_complete() || self.exhausted {
+                break;
+            }
+            let index = self.members.len() as u32;
+            let seed = format!("{}{}{index}", self.stem, salt(self.salt));
+            if seed.len() > MAX_SEED_LEN {
+                self.exhausted = true;
+                break;
+            }
+
+            let address = encode_pubkey(&self.deriver.derive(seed.as_bytes()), &mut encoded_buf);
+            self.attempts += 1;
+            if self
+                .match_type
+                .matches(address.as_bytes(), self.case_insensitive)
+            {
+                self.members.push(FamilyMember {
+                    index,
+                    seed,
+                    address: address.to_string(),
+                });
+                if self.strategy == FamilyStrategy::Independent {
+                    self.salt = 0;
+                }
+            } else {
+                if self.strategy == FamilyStrategy::AllMatch {
+                    self.members.clear();
+                }
+                self.salt += 1;
+            }
+        }
+        self.is_complete()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.members.len() == self.size as usize
+    }
+
+    /// Whether the salts grew too long to fit in a seed before the family
+    /// was complete.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// The family once complete; before that, the members found so far
+    /// (for `AllMatch`, those sharing the salt being tried).
+    pub fn members(&self) -> &[FamilyMember] {
+        &self.members
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.attempts
+    }
+
+    pub fn estimate(&self) -> FamilyEstimate {
+        family_estimate(
+            self.match_type.probability(self.case_insensitive),
+            self.size,
+            self.strategy,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn family(pattern: &str, size: u32, strategy: FamilyStrategy) -> FamilySearcher {
+        FamilySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            MatchType::Prefix(pattern.into()),
+            false,
+            "stake-".into(),
+            size,
+            strategy,
+        )
+        .unwrap()
+    }
+
+    fn assert_verified(members: &[FamilyMember], prefix: &str) {
+        let deriver = Deriver::new(&[1; 32], &[4; 32]);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for (i, member) in members.iter().enumerate() {
+            assert_eq!(member.index, i as u32);
+            assert!(member.seed.starts_with("stake-"));
+            assert!(member.seed.ends_with(&i.to_string()));
+            let pubkey = deriver.derive(member.seed.as_bytes());
+            assert_eq!(encode_pubkey(&pubkey, &mut buf), member.address);
+            assert!(member.address.starts_with(prefix));
+        }
+    }
+
+    #[test]
+    fn test_salts_are_distinct_letters() {
+        assert_eq!(salt(0), "A");
+        assert_eq!(salt(48), "z");
+        assert_eq!(salt(49), "AA");
+        assert_eq!(salt(49 + 49 * 49), "AAA");
+        let salts: std::collections::HashSet<_> = (0..5000).map(salt).collect();
+        assert_eq!(salts.len(), 5000);
+        assert!(SALT_CHARS.iter().all(u8::is_ascii_alphabetic));
+    }
+
+    #[test]
+    fn test_independent_family() {
+        let mut searcher = family("A", 5, FamilyStrategy::Independent);
+        while !searcher.search_batch(100) {}
+        assert_eq!(searcher.members().len(), 5);
+        assert_verified(searcher.members(), "A");
+    }
+
+    #[test]
+    fn test_all_match_family_shares_one_salt() {
+        let mut searcher = family("A", 2, FamilyStrategy::AllMatch);
+        while !searcher.search_batch(1000) {}
+        let members = searcher.members();
+        assert_verified(members, "A");
+        let salted = |member: &FamilyMember| member.seed[..member.seed.len() - 1].to_string();
+        assert_eq!(salted(&members[0]), salted(&members[1]));
+        assert!(searcher.search_batch(1000));
+    }
+
+    #[test]
+    fn test_family_estimate() {
+        let p = 0.1;
+        let independent = family_estimate(p, 3, FamilyStrategy::Independent);
+        assert!((independent.expected_attempts - 30.0).abs() < 1e-9);
+        assert_eq!(independent.warning, None);
+
+        // (1 - p^3) / ((1 - p) p^3) = 0.999 / 0.0009 = 1110 derivations.
+        let all = family_estimate(p, 3, FamilyStrategy::AllMatch);
+        assert!((all.expected_attempts - 1110.0).abs() < 1e-6);
+        assert!((all.relative_to_independent - 37.0).abs() < 1e-9);
+        assert!(all.warning.unwrap().contains("all 3 addresses"));
+        assert_eq!(
+            family_estimate(p, 1, FamilyStrategy::AllMatch).warning,
+            None
+        );
+        assert!(family_estimate(1e-9, 50, FamilyStrategy::AllMatch)
+            .expected_attempts
+            .is_infinite());
+    }
+
+    #[test]
+    fn test_new_validates() {
+        let new = |stem: &str, size| {
+            FamilySearcher::new(
+                &[1; 32],
+                &[4; 32],
+                MatchType::Prefix("A".into()),
+                false,
+                stem.into(),
+                size,
+                FamilyStrategy::Independent,
+            )
+            .err()
+            .map(|e| e.kind())
+        };
+        assert_eq!(new("stake-", 0), Some(crate::ErrorKind::InvalidOptions));
+        assert_eq!(
+            new("stake-", MAX_FAMILY_SIZE + 1),
+            Some(crate::ErrorKind::InvalidOptions)
+        );
+        // 29 + one salt letter + three index digits is 33 bytes.
+        assert_eq!(
+            new(&"s".repeat(29), 1000),
+            Some(crate::ErrorKind::InvalidOptions)
+        );
+        assert_eq!(new(&"s".repeat(29), 100), None);
+        let empty = FamilySearcher::new(
+            &[1; 32],
+            &[4; 32],
+            MatchType::Prefix(String::new()),
+            false,
+            "s".into(),
+            1,
+            FamilyStrategy::AllMatch,
+        );
+        assert_eq!(empty.err(), Some(VanityError::EmptyPattern));
+    }
+}
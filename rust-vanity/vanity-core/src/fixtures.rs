@@ -0,0 +1,52 @@
+//! Addresses of both lengths for the matching tests: a pubkey encodes to 44
+//! characters most of the time but to 43 about once in 17, and everything
+//! that looks at a position or an end of the address must handle both.
+
+use alloc::string::String;
+
+use crate::derive::{encode_candidate, Deriver, PUBKEY_LEN};
+use crate::seed::generate_seed_from_counter;
+
+pub(crate) const BASE: [u8; PUBKEY_LEN] = [1; PUBKEY_LEN];
+pub(crate) const OWNER: [u8; PUBKEY_LEN] = [4; PUBKEY_LEN];
+
+/// Counters whose address, for [`BASE`] and [`OWNER`], is 43 characters.
+pub(crate) const SHORT_COUNTERS: [u64; 4] = [17, 43, 53, 88];
+
+/// Counters whose address, for [`BASE`] and [`OWNER`], is 44 characters.
+pub(crate) const LONG_COUNTERS: [u64; 3] = [0, 1, 2];
+
+pub(crate) fn pubkey(counter: u64) -> [u8; PUBKEY_LEN] {
+    Deriver::new(&BASE, &OWNER).derive(&generate_seed_from_counter(counter))
+}
+
+pub(crate) fn address(counter: u64) -> String {
+    let (encoded, len) = encode_candidate(&pubkey(counter)).unwrap();
+    String::from_utf8(encoded[..len].to_vec()).unwrap()
+}
+
+/// `(pubkey, address)` for every fixture counter, short ones first.
+pub(crate) fn candidates() -> impl Iterator<Item = ([u8; PUBKEY_LEN], String)> {
+    SHORT_COUNTERS
+        .into_iter()
+        .chain(LONG_COUNTERS)
+        .map(|counter| (pubkey(counter), address(counter)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::MAX_ENCODED_LEN;
+
+    #[test]
+    fn test_fixture_lengths() {
+        for counter in SHORT_COUNTERS {
+            assert_eq!(address(counter).len(), MAX_ENCODED_LEN - 1, "{counter}");
+        }
+        for counter in LONG_COUNTERS {
+            assert_eq!(address(counter).len(), MAX_ENCODED_LEN, "{counter}");
+        }
+        assert_eq!(address(17), "x7HSLmSHSHvGtQ6d41jWCmwDZhy6UcAW8RyU9w6jNbT");
+        assert_eq!(address(0), "AQJvXepRVJWnWukbwEEyqGRG9buzKytds3dTFHypMxqe");
+    }
+}
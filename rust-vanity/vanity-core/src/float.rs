@@ -0,0 +1,51 @@
+//! The `f64` functions `std` provides and `core` doesn't: `std`'s own
+//! with the `std` feature, so results don't move, and `libm`'s without.
+
+macro_rules! float_fns {
+    ($($name:ident($($arg:ident: $ty:ty),*) => $libm:expr;)*) => {
+        $(
+            #[cfg(feature = "std")]
+            pub(crate) fn $name(x: f64 $(, $arg: $ty)*) -> f64 {
+                x.$name($($arg),*)
+            }
+
+            #[cfg(not(feature = "std"))]
+            pub(crate) fn $name(x: f64 $(, $arg: $ty)*) -> f64 {
+                $libm(x $(, $arg)*)
+            }
+        )*
+    };
+}
+
+float_fns! {
+    ceil() => libm::ceil;
+    exp_m1() => libm::expm1;
+    ln() => libm::log;
+    ln_1p() => libm::log1p;
+    powf(n: f64) => libm::pow;
+    powi(n: i32) => |x, n: i32| libm::pow(x, n.into());
+    round() => libm::round;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agrees_with_std() {
+        let close = |a: f64, b: f64| a == b || (a - b).abs() <= 1e-15 * b.abs().max(1.0);
+        for x in [1e-300, 1e-12, 0.017, 0.5, 1.0, 2.5, 58.0, 256.0, 1e30] {
+            assert!(close(ln(x), x.ln()), "{x}");
+            assert!(
+                close(ln_1p(-x / (1.0 + x)), (-x / (1.0 + x)).ln_1p()),
+                "{x}"
+            );
+            assert!(close(exp_m1(-x), (-x).exp_m1()), "{x}");
+            assert_eq!(ceil(x), x.ceil());
+            assert_eq!(round(x), x.round());
+            assert!(close(powi(x, 3), x.powi(3)), "{x}");
+            assert!(close(powf(x, 0.25), x.powf(0.25)), "{x}");
+            assert!(close(powi(x.min(1e3), -4), x.min(1e3).powi(-4)), "{x}");
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! Ed25519 signer keys generated inside the search loop, for modes whose
+//! grind target is a keypair rather than a `createWithSeed` seed.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::derive::{decode_base58, encode_pubkey, MAX_ENCODED_LEN, PUBKEY_LEN};
+use crate::matcher::AddressMatcher;
+use crate::pubkey::Pubkey;
+
+/// Length of a Solana keypair: the 32-byte secret, then the public key.
+pub const KEYPAIR_LEN: usize = 64;
+
+/// The Ed25519 public key for a 32-byte secret, as RFC 8032 derives it.
+pub fn pubkey_from_secret(secret: &[u8; 32]) -> [u8; PUBKEY_LEN] {
+    let hash = Sha512::digest(secret);
+    let scalar: [u8; 32] = hash[..32].try_into().unwrap();
+    EdwardsPoint::mul_base_clamped(scalar).compress().to_bytes()
+}
+
+/// An Ed25519 signing key as the signing algorithm uses it: the integer
+/// scalar, already clamped, and the prefix nonces are hashed from. RFC 8032
+/// expands a 32-byte seed into these with SHA-512, but keys made another
+/// way (see [`crate::IncrementalKeyDerivation`]) have no seed, and this is
+/// all there is of them.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExpandedSecret {
+    scalar: [u8; 32],
+    prefix: [u8; 32],
+}
+
+impl ExpandedSecret {
+    /// The key RFC 8032 derives from `seed`, as [`pubkey_from_secret`] does.
+    pub fn from_seed(seed: &[u8; 32]) -> ExpandedSecret {
+        let hash = Sha512::digest(seed);
+        ExpandedSecret::new(
+            clamp(hash[..32].try_into().unwrap()),
+            hash[32..].try_into().unwrap(),
+        )
+    }
+
+    /// `scalar` must be clamped (low 3 bits and bit 255 clear, bit 254
+    /// set), or libraries that clamp on import will sign with another key.
+    pub fn new(scalar: [u8; 32], prefix: [u8; 32]) -> ExpandedSecret {
+        debug_assert_eq!(clamp(scalar), scalar);
+        ExpandedSecret { scalar, prefix }
+    }
+
+    pub fn scalar(&self) -> &[u8; 32] {
+        &self.scalar
+    }
+
+    /// Scalar then prefix, the 64-byte form of e.g. ed25519-dalek's
+    /// `ExpandedSecretKey`. Not a wallet keypair, whose first half is a seed.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.scalar);
+        bytes[32..].copy_from_slice(&self.prefix);
+        bytes
+    }
+
+    /// [`ExpandedSecret::to_bytes`] in base58.
+    pub fn to_base58(&self) -> String {
+        encode_64(&self.to_bytes())
+    }
+
+    pub fn pubkey(&self) -> [u8; PUBKEY_LEN] {
+        EdwardsPoint::mul_base_clamped(self.scalar)
+            .compress()
+            .to_bytes()
+    }
+
+    /// An RFC 8032 signature of `message`.
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        let r = wide_scalar(
+            Sha512::new()
+                .chain_update(self.prefix)
+                .chain_update(message),
+        );
+        let big_r = EdwardsPoint::mul_base(&r).compress();
+        let k = wide_scalar(
+            Sha512::new()
+                .chain_update(big_r.as_bytes())
+                .chain_update(self.pubkey())
+                .chain_update(message),
+        );
+        let s = r + k * Scalar::from_bytes_mod_order(self.scalar);
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(big_r.as_bytes());
+        signature[32..].copy_from_slice(s.as_bytes());
+        signature
+    }
+}
+
+impl Drop for ExpandedSecret {
+    fn drop(&mut self) {
+        self.scalar.zeroize();
+        self.prefix.zeroize();
+    }
+}
+
+/// Shows the public key only.
+impl fmt::Debug for ExpandedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpandedSecret")
+            .field("pubkey", &Pubkey::new(self.pubkey()))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether `signature` is a valid Ed25519 signature of `message` by
+/// `pubkey`, i.e. `[S]B = R + [k]A`.
+pub fn verify_signature(pubkey: &[u8; PUBKEY_LEN], message: &[u8], signature: &[u8; 64]) -> bool {
+    let Some(a) = CompressedEdwardsY(*pubkey).decompress() else {
+        return false;
+    };
+    let Some(s) = Option::<Scalar>::from(Scalar::from_canonical_bytes(
+        signature[32..].try_into().unwrap(),
+    )) else {
+        return false;
+    };
+    let k = wide_scalar(
+        Sha512::new()
+            .chain_update(&signature[..32])
+            .chain_update(pubkey)
+            .chain_update(message),
+    );
+    let big_r = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-a, &s);
+    big_r.compress().as_bytes()[..] == signature[..32]
+}
+
+/// Signed by every key [`verify_expanded_secret`] rebuilds.
+const VERIFY_MESSAGE: &[u8] = b"web-vanity result verification";
+
+/// The checks [`verify_keypair`] and [`verify_expanded_secret`] make, in
+/// the order they make them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStep {
+    /// The secret is base58 and 64 bytes long.
+    Decode,
+    /// The public key rebuilt from the secret is the keypair's public
+    /// half, or for an expanded key, signs what verifies under it.
+    Derive,
+    /// The rebuilt public key encodes to the reported address.
+    Address,
+    /// The address matches the pattern searched for.
+    Pattern,
+}
+
+/// What [`verify_keypair`] or [`verify_expanded_secret`] found.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct KeyVerification {
+    /// Every step passed: the secret is safe to fund.
+    pub ok: bool,
+    /// Steps that passed, in order.
+    pub passed: Vec<VerifyStep>,
+    /// The first step that failed; later ones aren't tried.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub failed: Option<VerifyStep>,
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub reason: Option<String>,
+}
+
+impl KeyVerification {
+    fn fail(mut self, step: VerifyStep, reason: String) -> KeyVerification {
+        self.failed = Some(step);
+        self.reason = Some(reason);
+        self
+    }
+
+    /// The steps after [`VerifyStep::Derive`], shared by both key forms.
+    fn check_address(
+        mut self,
+        pubkey: &[u8; PUBKEY_LEN],
+        address: &str,
+        matcher: &AddressMatcher,
+    ) -> KeyVerification {
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        let encoded = encode_pubkey(pubkey, &mut buf);
+        if encoded != address {
+            return self.fail(
+                VerifyStep::Address,
+                format!("the secret's public key is {encoded}, not {address}"),
+            );
+        }
+        self.passed.push(VerifyStep::Address);
+        if !matcher.matches(address) {
+            return self.fail(
+                VerifyStep::Pattern,
+                format!("{address} doesn't match the pattern"),
+            );
+        }
+        self.passed.push(VerifyStep::Pattern);
+        self.ok = true;
+        self
+    }
+}
+
+/// `encoded` decoded into storage wiped on drop, if it is 64 bytes.
+fn decode_secret(encoded: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let bytes = Zeroizing::new(decode_base58(encoded).map_err(|e| e.to_string())?);
+    if bytes.len() != KEYPAIR_LEN {
+        return Err(format!(
+            "the secret is {} bytes, not {KEYPAIR_LEN}",
+            bytes.len()
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Checks a keypair result from scratch before the address is funded: the
+/// base58 keypair (as [`encode_keypair`] writes it) is decoded, its public
+/// key derived from the secret half with the full RFC 8032 computation and
+/// compared with its public half, then encoded and compared with `address`,
+/// which is matched against `matcher` again. Catches a faulty shortcut in
+/// the search or a result garbled on its way from a worker.
+pub fn verify_keypair(
+    secret_key: &str,
+    address: &str,
+    matcher: &AddressMatcher,
+) -> KeyVerification {
+    let report = KeyVerification::default();
+    let keypair = match decode_secret(secret_key) {
+        Ok(keypair) => keypair,
+        Err(reason) => return report.fail(VerifyStep::Decode, reason),
+    };
+    let mut report = KeyVerification {
+        passed: alloc::vec![VerifyStep::Decode],
+        ..report
+    };
+    let secret = Zeroizing::new(<[u8; 32]>::try_from(&keypair[..32]).unwrap());
+    let pubkey = pubkey_from_secret(&secret);
+    if pubkey[..] != keypair[32..] {
+        return report.fail(
+            VerifyStep::Derive,
+            "the secret's public key isn't the keypair's public half".into(),
+        );
+    }
+    report.passed.push(VerifyStep::Derive);
+    report.check_address(&pubkey, address, matcher)
+}
+
+/// [`verify_keypair`] for a base58 [`ExpandedSecret::to_bytes`], which has
+/// no public half: the rebuilt key must instead be clamped and sign a test
+/// message that verifies under its public key.
+pub fn verify_expanded_secret(
+    expanded_secret_key: &str,
+    address: &str,
+    matcher: &AddressMatcher,
+) -> KeyVerification {
+    let report = KeyVerification::default();
+    let bytes = match decode_secret(expanded_secret_key) {
+        Ok(bytes) => bytes,
+        Err(reason) => return report.fail(VerifyStep::Decode, reason),
+    };
+    let mut report = KeyVerification {
+        passed: alloc::vec![VerifyStep::Decode],
+        ..report
+    };
+    let scalar: [u8; 32] = bytes[..32].try_into().unwrap();
+    if clamp(scalar) != scalar {
+        return report.fail(VerifyStep::Derive, "the scalar isn't clamped".into());
+    }
+    let secret = ExpandedSecret::new(scalar, bytes[32..].try_into().unwrap());
+    let pubkey = secret.pubkey();
+    if !verify_signature(&pubkey, VERIFY_MESSAGE, &secret.sign(VERIFY_MESSAGE)) {
+        return report.fail(
+            VerifyStep::Derive,
+            "the key signs what doesn't verify".into(),
+        );
+    }
+    report.passed.push(VerifyStep::Derive);
+    report.check_address(&pubkey, address, matcher)
+}
+
+/// Ed25519's clamping: a multiple of the cofactor 8 with bit 254 its top bit.
+pub(crate) fn clamp(mut scalar: [u8; 32]) -> [u8; 32] {
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    scalar
+}
+
+fn wide_scalar(hasher: Sha512) -> Scalar {
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+/// The secret for `counter` under `master`: SHA-256 over a domain tag,
+/// `master` and the counter. Anyone holding `master` can regenerate every
+/// key, so it has to be as secret as the keys and random per session.
+pub fn secret_from_counter(master: &[u8; 32], counter: u64) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"web-vanity signer key")
+        .chain_update(master)
+        .chain_update(counter.to_le_bytes())
+        .finalize()
+        .into()
+}
+
+/// The 64-byte keypair wallets and the Solana CLI import.
+pub fn keypair_bytes(secret: &[u8; 32], pubkey: &[u8; PUBKEY_LEN]) -> [u8; KEYPAIR_LEN] {
+    let mut keypair = [0u8; KEYPAIR_LEN];
+    keypair[..32].copy_from_slice(secret);
+    keypair[32..].copy_from_slice(pubkey);
+    keypair
+}
+
+/// [`keypair_bytes`] in base58, the form wallets import.
+pub fn encode_keypair(secret: &[u8; 32], pubkey: &[u8; PUBKEY_LEN]) -> String {
+    encode_64(&keypair_bytes(secret, pubkey))
+}
+
+fn encode_64(bytes: &[u8; 64]) -> String {
+    let mut buf = [0u8; five8::BASE58_ENCODED_64_MAX_LEN];
+    let len = five8::encode_64(bytes, &mut buf) as usize;
+    // Base58 is ASCII.
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> [u8; 32] {
+        core::array::from_fn(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+    }
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        for (secret, public) in [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            ),
+        ] {
+            assert_eq!(pubkey_from_secret(&hex(secret)), hex(public));
+        }
+    }
+
+    #[test]
+    fn test_rfc8032_signatures() {
+        let seed = hex("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60");
+        let secret = ExpandedSecret::from_seed(&seed);
+        assert_eq!(secret.pubkey(), pubkey_from_secret(&seed));
+        let signature = secret.sign(b"");
+        let expected = [
+            hex("e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155"),
+            hex("5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"),
+        ]
+        .concat();
+        assert_eq!(signature[..], expected[..]);
+        assert!(verify_signature(&secret.pubkey(), b"", &signature));
+
+        assert!(!verify_signature(&secret.pubkey(), b"x", &signature));
+        let mut forged = signature;
+        forged[40] ^= 1;
+        assert!(!verify_signature(&secret.pubkey(), b"", &forged));
+        let other = pubkey_from_secret(&[1; 32]);
+        assert!(!verify_signature(&other, b"", &signature));
+    }
+
+    #[test]
+    fn test_verification_names_the_failing_step() {
+        let secret = [3; 32];
+        let pubkey = pubkey_from_secret(&secret);
+        let address = Pubkey::new(pubkey).to_string();
+        let matcher = AddressMatcher::new(Some(address[..2].into()), None, false).unwrap();
+        let keypair = encode_keypair(&secret, &pubkey);
+        let report = verify_keypair(&keypair, &address, &matcher);
+        assert!(report.ok);
+        assert_eq!(
+            report.passed,
+            [
+                VerifyStep::Decode,
+                VerifyStep::Derive,
+                VerifyStep::Address,
+                VerifyStep::Pattern
+            ]
+        );
+        assert_eq!((report.failed, report.reason), (None, None));
+
+        let failed = |report: KeyVerification| {
+            assert!(!report.ok);
+            (report.failed.unwrap(), report.passed.len())
+        };
+        // A flipped secret bit no longer derives the public half.
+        let mut corrupted = keypair_bytes(&secret, &pubkey);
+        corrupted[5] ^= 1;
+        assert_eq!(
+            failed(verify_keypair(&encode_64(&corrupted), &address, &matcher)),
+            (VerifyStep::Derive, 1)
+        );
+        // A consistent keypair for another address.
+        let other = pubkey_from_secret(&[4; 32]);
+        let tampered = Pubkey::new(other).to_string();
+        assert_eq!(
+            failed(verify_keypair(&keypair, &tampered, &matcher)),
+            (VerifyStep::Address, 2)
+        );
+        let elsewhere = AddressMatcher::new(None, Some("zzzz".into()), false).unwrap();
+        assert_eq!(
+            failed(verify_keypair(&keypair, &address, &elsewhere)),
+            (VerifyStep::Pattern, 3)
+        );
+        assert_eq!(
+            failed(verify_keypair("0OIl", &address, &matcher)),
+            (VerifyStep::Decode, 0)
+        );
+        assert_eq!(
+            failed(verify_keypair(&address, &address, &matcher)),
+            (VerifyStep::Decode, 0)
+        );
+
+        let expanded = ExpandedSecret::from_seed(&secret);
+        assert!(verify_expanded_secret(&expanded.to_base58(), &address, &matcher).ok);
+        let mut corrupted = expanded.to_bytes();
+        corrupted[0] |= 1;
+        assert_eq!(
+            failed(verify_expanded_secret(
+                &encode_64(&corrupted),
+                &address,
+                &matcher
+            )),
+            (VerifyStep::Derive, 1)
+        );
+        let mut corrupted = expanded.to_bytes();
+        corrupted[0] ^= 8;
+        assert_eq!(
+            failed(verify_expanded_secret(
+                &encode_64(&corrupted),
+                &address,
+                &matcher
+            )),
+            (VerifyStep::Address, 2)
+        );
+    }
+
+    #[test]
+    fn test_secrets_depend_on_master_and_counter() {
+        let master = [7; 32];
+        assert_eq!(
+            secret_from_counter(&master, 1),
+            secret_from_counter(&master, 1)
+        );
+        assert_ne!(
+            secret_from_counter(&master, 1),
+            secret_from_counter(&master, 2)
+        );
+        assert_ne!(
+            secret_from_counter(&master, 1),
+            secret_from_counter(&[8; 32], 1)
+        );
+        let secret = secret_from_counter(&master, 1);
+        let keypair = keypair_bytes(&secret, &pubkey_from_secret(&secret));
+        assert_eq!(keypair[..32], secret);
+        assert_eq!(keypair[32..], pubkey_from_secret(&secret));
+    }
+}
@@ -0,0 +1,158 @@
+//! Core vanity address search logic, free of any wasm or browser dependency.
+//!
+//! The `rust-vanity` crate wraps this for the web; everything here runs and
+//! tests natively.
+//!
+//! Without the default `std` feature the crate is `no_std` and needs only
+//! `alloc`, e.g. for wasm runtimes without WASI or a signing device.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+pub mod alphabet;
+pub mod approx;
+pub mod best;
+pub mod cancel;
+pub mod charmap;
+pub mod derivation;
+pub mod derive;
+pub mod descriptor;
+pub mod distribution;
+#[cfg(feature = "keypair")]
+mod edwards;
+pub mod error;
+pub mod estimate;
+pub mod family;
+#[cfg(test)]
+mod fixtures;
+mod float;
+#[cfg(feature = "keypair")]
+pub mod keys;
+pub mod links;
+pub mod matcher;
+#[cfg(feature = "metadata")]
+pub mod metadata;
+pub mod multi;
+pub mod partial;
+#[cfg(feature = "passphrase")]
+pub mod passphrase;
+pub mod patterns;
+#[cfg(feature = "pda")]
+pub mod pda;
+pub mod permute;
+pub mod plan;
+pub mod presets;
+pub mod progress;
+pub mod pubkey;
+pub mod queue;
+pub mod ranges;
+#[cfg(feature = "raw-abi")]
+pub mod raw;
+pub mod rent;
+pub mod replay;
+mod resume;
+pub mod sampling;
+pub mod search;
+pub mod seed;
+pub mod seen;
+mod selftest;
+pub mod short;
+#[cfg(feature = "squads")]
+pub mod squads;
+pub mod tiers;
+#[cfg(feature = "token2022")]
+pub mod token2022;
+pub mod trace;
+pub mod workunit;
+
+pub use alphabet::Alphabet;
+pub use approx::{ApproxMatch, ApproxPattern, ApproxSearcher, ApproxSide, MAX_EDITS};
+pub use best::{BestMatch, BestSearcher, Scoring, SharedBest, DICTIONARY};
+pub use cancel::CancelToken;
+#[cfg(feature = "passphrase")]
+pub use derivation::PassphraseSeedDerivation;
+pub use derivation::{Candidate, CharsetSeedDerivation, CreateWithSeedDerivation, Derivation};
+#[cfg(feature = "keypair")]
+pub use derivation::{IncrementalKeyDerivation, SignerKeyDerivation};
+pub use derive::{
+    decode_base58, encode_pubkey, try_encode_pubkey, Deriver, MAX_ENCODED_LEN, MIN_ENCODED_LEN,
+    PUBKEY_LEN,
+};
+pub use descriptor::{MixingDescriptor, NamespaceDescriptor, SeedSchemeDescriptor, SeedVector};
+pub use distribution::{char_distribution, CharCount, CharDistribution};
+pub use error::{ErrorKind, VanityError};
+pub use estimate::{
+    attempt_quantiles, attempts_for_confidence, check_feasible, describe_duration,
+    difficulty_curve, luck_stats, match_probability, probability_found, seed_space_warning,
+    AttemptQuantile, AttemptQuantiles, LuckStats, BASE58_ALPHABET, DEFAULT_MAX_EXPECTED_ATTEMPTS,
+    FEASIBILITY_REFERENCE_RATE, QUANTILE_CONFIDENCES,
+};
+pub use family::{
+    family_estimate, FamilyEstimate, FamilyMember, FamilySearcher, FamilyStrategy, MAX_FAMILY_SIZE,
+};
+pub use links::{explorer_url, solana_pay_url, Cluster, Explorer, SOL_DECIMALS};
+pub use matcher::{
+    fix_confusables, AddressMatcher, ByteMask, CleanPrefix, ConfusableSet, MatchCheck,
+    MatchExplanation, MatchPart, MatchType, PatternRewrite, PatternSide, CLEAN_PREFIX_CHARS,
+    CLEAN_PREFIX_PAIRS, CONFUSABLES,
+};
+#[cfg(feature = "metadata")]
+pub use metadata::{
+    joint_probability, JointRequirement, MatchSpec, MetadataMatch, MetadataSearcher,
+    METADATA_PROGRAM_ID,
+};
+pub use multi::{MultiMatch, MultiSearcher, PatternStats};
+pub use partial::{PartStats, PartialStats};
+#[cfg(feature = "passphrase")]
+pub use passphrase::{seed_from_passphrase, PASSPHRASE_SEED_VERSION};
+pub use patterns::{check_pattern_list, format_pattern_list, parse_pattern_list, PatternEntry};
+pub use permute::{CounterPermutation, SeedNamespace, MAX_NAMESPACE_LEN};
+pub use plan::{plan_search, Difficulty, SearchPlan};
+pub use presets::{preset, presets, Preset, PresetInfo, PRESETS, PRESET_REFERENCE_RATE};
+pub use progress::{
+    AggregateProgress, Heartbeat, ProgressAggregator, ProgressSnapshot, RateHistory, SearchStats,
+    WorkerProgress, DEFAULT_RATE_HISTORY_BUCKETS, DEFAULT_RATE_HISTORY_RESOLUTION_MS,
+    DEFAULT_RATE_WINDOW_MS, DEFAULT_STALE_AFTER_MS, MAX_RATE_HISTORY_BUCKETS,
+};
+pub use pubkey::{KeyRole, Pubkey};
+pub use queue::{JobQueue, JobStatus};
+pub use ranges::{
+    audit_ranges, merge_ranges, partition_counters, RangeAudit, RangeOverlap, WorkRange,
+};
+pub use rent::rent_exempt_lamports;
+pub use replay::{
+    compare_replay, ReplayComparison, ReplayLog, MAX_REPLAY_ENTRIES, REPLAY_DIGEST_LEN,
+    REPLAY_ENTRY_LEN,
+};
+pub use sampling::AddressSampler;
+pub use search::{
+    derive_counters, BatchStatus, DerivedAddress, RangeOutcome, SearchMatch, Searcher,
+    INTERRUPT_POLL_INTERVAL, MAX_DERIVE_COUNTERS, MAX_SAMPLES,
+};
+pub use seed::{
+    generate_seed_from_counter, seed_from_string, seed_to_string, short_seed_from_counter,
+    short_seed_tier_start, SeedCharset, SeedScheme, ALPHANUMERIC_CHARS, HEX_SEED_PREFIX,
+    MAX_SEED_LEN, SEED_LEN, SEED_SCHEME_VERSION,
+};
+pub use seen::{SeenFilter, SEEN_BLOCK_BITS, SEEN_BLOCK_SIZE};
+pub use selftest::self_test;
+pub use short::{
+    short_seed_estimate, ShortMatch, ShortSeedEstimate, ShortSeedSearcher, SHORT_SEED_LEN,
+};
+#[cfg(feature = "squads")]
+pub use squads::{SquadsMatch, SquadsSearcher, SQUADS_PROGRAM_ID};
+pub use tiers::{PatternTier, TieredMatch, TieredSearcher};
+#[cfg(feature = "token2022")]
+pub use token2022::{
+    mint_extensions, token_metadata_len, CreateAccountParams, InitializeMint2Params, MintExtension,
+    MintExtensionInfo, MintInstructionParams, MintLayout, Token2022Match, Token2022MintSearcher,
+    MINT_LEN, TOKEN_2022_PROGRAM_ID,
+};
+pub use trace::{DerivationTrace, TraceTiming};
+pub use workunit::{
+    collate_results, create_work_units, run_work_unit, CollatedWork, RejectedRecord, WorkConfig,
+    WorkMatch, WorkRecord, WorkUnit, WORK_UNIT_VERSION,
+};
@@ -0,0 +1,253 @@
+//! Links for a found address: its page on an explorer and a Solana Pay
+//! request to receive SOL at it, built in one place so every caller names
+//! clusters the same way.
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::error::VanityError;
+use crate::pubkey::Pubkey;
+
+/// Most decimal places a SOL amount can have: a lamport is 1e-9 SOL.
+pub const SOL_DECIMALS: usize = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+}
+
+impl Cluster {
+    /// "mainnet" (or "mainnet-beta"), "devnet" or "testnet", in any case;
+    /// anything else is `UnknownCluster`.
+    pub fn from_name(name: &str) -> Result<Cluster, VanityError> {
+        match name.to_ascii_lowercase().as_str() {
+            "mainnet" | "mainnet-beta" => Ok(Cluster::Mainnet),
+            "devnet" => Ok(Cluster::Devnet),
+            "testnet" => Ok(Cluster::Testnet),
+            _ => Err(VanityError::UnknownCluster(name.into())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Explorer {
+    /// explorer.solana.com.
+    Solana,
+    Solscan,
+    SolanaFm,
+}
+
+impl Explorer {
+    /// "explorer.solana.com" (or "explorer"), "solscan" or "solanafm", in
+    /// any case; anything else is `UnknownExplorer`.
+    pub fn from_name(name: &str) -> Result<Explorer, VanityError> {
+        match name.to_ascii_lowercase().as_str() {
+            "explorer.solana.com" | "explorer" => Ok(Explorer::Solana),
+            "solscan" => Ok(Explorer::Solscan),
+            "solanafm" => Ok(Explorer::SolanaFm),
+            _ => Err(VanityError::UnknownExplorer(name.into())),
+        }
+    }
+
+    /// The page for `address` on `cluster`. Mainnet is each explorer's
+    /// default, so it gets no cluster parameter.
+    pub fn address_url(self, address: &Pubkey, cluster: Cluster) -> String {
+        let (page, cluster) = match self {
+            Explorer::Solana => (
+                "https://explorer.solana.com/address",
+                match cluster {
+                    Cluster::Mainnet => None,
+                    Cluster::Devnet => Some("devnet"),
+                    Cluster::Testnet => Some("testnet"),
+                },
+            ),
+            Explorer::Solscan => (
+                "https://solscan.io/account",
+                match cluster {
+                    Cluster::Mainnet => None,
+                    Cluster::Devnet => Some("devnet"),
+                    Cluster::Testnet => Some("testnet"),
+                },
+            ),
+            Explorer::SolanaFm => (
+                "https://solana.fm/address",
+                match cluster {
+                    Cluster::Mainnet => None,
+                    Cluster::Devnet => Some("devnet-solana"),
+                    Cluster::Testnet => Some("testnet-solana"),
+                },
+            ),
+        };
+        match cluster {
+            Some(cluster) => format!("{page}/{address}?cluster={cluster}"),
+            None => format!("{page}/{address}"),
+        }
+    }
+}
+
+/// [`Explorer::address_url`] by name, for `address` in base58. Fails with
+/// `UnknownCluster` or `UnknownExplorer` for names [`Cluster::from_name`]
+/// and [`Explorer::from_name`] don't know, or as [`Pubkey::from_base58`]
+/// does for a bad address.
+pub fn explorer_url(address: &str, cluster: &str, explorer: &str) -> Result<String, VanityError> {
+    let cluster = Cluster::from_name(cluster)?;
+    let explorer = Explorer::from_name(explorer)?;
+    Ok(explorer.address_url(&Pubkey::from_base58(address)?, cluster))
+}
+
+/// A Solana Pay transfer request for SOL to `recipient` (base58), e.g.
+/// `solana:<recipient>?amount=0.5&label=Tip%20jar`, with `amount` in SOL
+/// and `label` naming the recipient in the wallet. Without an amount the
+/// wallet asks for one. Fails with `InvalidOptions` for a negative or
+/// non-finite amount or one finer than a lamport, or as
+/// [`Pubkey::from_base58`] does for a bad recipient.
+pub fn solana_pay_url(
+    recipient: &str,
+    amount: Option<f64>,
+    label: Option<&str>,
+) -> Result<String, VanityError> {
+    let mut url = format!("solana:{}", Pubkey::from_base58(recipient)?);
+    let mut separator = '?';
+    if let Some(amount) = amount {
+        url.push(separator);
+        url.push_str("amount=");
+        url.push_str(&format_amount(amount)?);
+        separator = '&';
+    }
+    if let Some(label) = label {
+        url.push(separator);
+        url.push_str("label=");
+        url.push_str(&percent_encode(label));
+    }
+    Ok(url)
+}
+
+/// `amount` as Solana Pay wants it: plain decimal, a leading zero before
+/// the point, no exponent. `f64`'s `Display` already writes that, and the
+/// shortest digits that round-trip, so 0.1 stays "0.1".
+fn format_amount(amount: f64) -> Result<String, VanityError> {
+    if !(amount.is_finite() && amount >= 0.0) {
+        return Err(VanityError::InvalidOptions(format!(
+            "amount must be a non-negative number of SOL, got {amount}"
+        )));
+    }
+    // Adding zero turns -0 into 0.
+    let formatted = format!("{}", amount + 0.0);
+    let decimals = formatted
+        .split_once('.')
+        .map_or(0, |(_, digits)| digits.len());
+    if decimals > SOL_DECIMALS {
+        return Err(VanityError::InvalidOptions(format!(
+            "amount {formatted} has more than {SOL_DECIMALS} decimal places"
+        )));
+    }
+    Ok(formatted)
+}
+
+/// `text` with every byte of its UTF-8 but the unreserved characters of
+/// RFC 3986 (letters, digits, `-`, `.`, `_`, `~`) written as `%XX`.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            // Writing to a String can't fail.
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    const ADDRESS: &str = "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi";
+
+    #[test]
+    fn test_explorer_urls() {
+        let cases = [
+            ("explorer.solana.com", "mainnet", ""),
+            ("explorer.solana.com", "devnet", "?cluster=devnet"),
+            ("explorer.solana.com", "testnet", "?cluster=testnet"),
+            ("solscan", "mainnet-beta", ""),
+            ("solscan", "devnet", "?cluster=devnet"),
+            ("solscan", "testnet", "?cluster=testnet"),
+            ("solanafm", "mainnet", ""),
+            ("solanafm", "devnet", "?cluster=devnet-solana"),
+            ("solanafm", "testnet", "?cluster=testnet-solana"),
+        ];
+        for (explorer, cluster, query) in cases {
+            let page = match explorer {
+                "explorer.solana.com" => "https://explorer.solana.com/address",
+                "solscan" => "https://solscan.io/account",
+                _ => "https://solana.fm/address",
+            };
+            assert_eq!(
+                explorer_url(ADDRESS, cluster, explorer).unwrap(),
+                format!("{page}/{ADDRESS}{query}")
+            );
+        }
+        assert_eq!(
+            explorer_url(ADDRESS, "Devnet", "explorer").unwrap(),
+            format!("https://explorer.solana.com/address/{ADDRESS}?cluster=devnet")
+        );
+
+        let kind = |result: Result<String, VanityError>| result.unwrap_err().kind();
+        assert_eq!(
+            kind(explorer_url(ADDRESS, "localnet", "solscan")),
+            ErrorKind::UnknownCluster
+        );
+        assert_eq!(
+            kind(explorer_url(ADDRESS, "devnet", "etherscan")),
+            ErrorKind::UnknownExplorer
+        );
+        assert_eq!(
+            kind(explorer_url("0x", "devnet", "solscan")),
+            ErrorKind::InvalidBase58
+        );
+    }
+
+    #[test]
+    fn test_solana_pay_urls() {
+        let url = |amount, label| solana_pay_url(ADDRESS, amount, label).unwrap();
+        assert_eq!(url(None, None), format!("solana:{ADDRESS}"));
+        assert_eq!(url(Some(1.0), None), format!("solana:{ADDRESS}?amount=1"));
+        assert_eq!(
+            url(Some(0.000000001), Some("Tip jar")),
+            format!("solana:{ADDRESS}?amount=0.000000001&label=Tip%20jar")
+        );
+        assert_eq!(url(Some(-0.0), None), format!("solana:{ADDRESS}?amount=0"));
+        assert_eq!(
+            url(Some(12345678.5), None),
+            format!("solana:{ADDRESS}?amount=12345678.5")
+        );
+        assert_eq!(
+            url(None, Some("Café & co. #1 ~ok_")),
+            format!("solana:{ADDRESS}?label=Caf%C3%A9%20%26%20co.%20%231%20~ok_")
+        );
+        assert_eq!(
+            url(None, Some("🦀=a/b?")),
+            format!("solana:{ADDRESS}?label=%F0%9F%A6%80%3Da%2Fb%3F")
+        );
+
+        for amount in [-1.0, f64::NAN, f64::INFINITY, 0.0000000001] {
+            assert_eq!(
+                solana_pay_url(ADDRESS, Some(amount), None)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidOptions,
+                "{amount}"
+            );
+        }
+        assert_eq!(
+            solana_pay_url("short", None, None).unwrap_err().kind(),
+            ErrorKind::BadPubkeyLength
+        );
+    }
+}
@@ -0,0 +1,1190 @@
+//! Prefix/suffix matching of encoded addresses.
+//!
+//! An address is 44 characters or, about once in 17, 43 (fewer is all but
+//! impossible), and matching goes by its actual length. A prefix, and any
+//! position reported in an address, counts from the start; a suffix counts
+//! from the actual end, so on a 43-character address it ends at character
+//! 42. A pattern longer than the address doesn't match, which makes a
+//! 44-character pattern unmatchable by a 43-character address, while a
+//! [`CleanPrefix`] longer than the address checks all of it. Nothing here
+//! panics on a short address.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use serde::Serialize;
+
+use crate::alphabet::BASE58;
+use crate::charmap::{fold_str, same_byte};
+use crate::derive::{trailing_chars, MAX_ENCODED_LEN, MAX_TRAILING_CHARS, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::float;
+
+fn same_bytes(a: &[u8], b: &[u8], case_insensitive: bool) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(&a, &b)| same_byte(a, b, case_insensitive))
+}
+
+/// Offset of the first occurrence of `pattern` in `encoded`.
+fn find_bytes(encoded: &[u8], pattern: &[u8], case_insensitive: bool) -> Option<usize> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    encoded
+        .windows(pattern.len())
+        .position(|window| same_bytes(window, pattern, case_insensitive))
+}
+
+/// Look-alike replacements for the characters base58 leaves out.
+pub const CONFUSABLES: &[(char, char)] = &[('0', 'o'), ('O', 'o'), ('I', '1'), ('l', '1')];
+
+/// Which pattern a [`PatternRewrite`] was made in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum PatternSide {
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+/// A character [`fix_confusables`] replaced.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct PatternRewrite {
+    pub pattern: PatternSide,
+    /// Index in the trimmed pattern, counted in characters.
+    pub position: usize,
+    pub original: char,
+    pub replacement: char,
+}
+
+fn is_base58_char(c: char, case_insensitive: bool) -> bool {
+    c.is_ascii() && BASE58.can_match(c as u8, case_insensitive)
+}
+
+/// Trims `pattern` and replaces each character no address can contain
+/// (allowing for case folding) with its look-alike from [`CONFUSABLES`],
+/// e.g. "S0L" becomes "SoL". Returns the fixed pattern and what was
+/// replaced; characters without a look-alike are kept, for
+/// [`MatchType::validate`] to reject.
+pub fn fix_confusables(
+    pattern: &str,
+    side: PatternSide,
+    case_insensitive: bool,
+) -> (String, Vec<PatternRewrite>) {
+    let mut rewrites = Vec::new();
+    let fixed = pattern
+        .trim()
+        .chars()
+        .enumerate()
+        .map(|(position, c)| {
+            let replacement = CONFUSABLES
+                .iter()
+                .find(|&&(from, _)| from == c && !is_base58_char(c, case_insensitive));
+            match replacement {
+                Some(&(original, replacement)) => {
+                    rewrites.push(PatternRewrite {
+                        pattern: side,
+                        position,
+                        original,
+                        replacement,
+                    });
+                    replacement
+                }
+                None => c,
+            }
+        })
+        .collect();
+    (fixed, rewrites)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchType {
+    Prefix(String),
+    Suffix(String),
+    Both(String, String),
+    Contains(String),
+}
+
+impl MatchType {
+    /// Builds the match spec, folding the patterns to ASCII lowercase when
+    /// matching case-insensitively. Surrounding whitespace is trimmed and
+    /// blank patterns count as absent.
+    pub fn new(
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+    ) -> MatchType {
+        let fold = |s: String| if case_insensitive { fold_str(&s) } else { s };
+        let clean = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        match (clean(prefix), clean(suffix)) {
+            (Some(p), Some(s)) => MatchType::Both(fold(p), fold(s)),
+            (Some(p), None) => MatchType::Prefix(fold(p)),
+            (None, Some(s)) => MatchType::Suffix(fold(s)),
+            (None, None) => MatchType::Prefix(String::new()), // Default to empty prefix
+        }
+    }
+
+    /// Checks an encoded address against the patterns, ignoring ASCII case
+    /// if `case_insensitive`. A suffix is matched against the address's
+    /// own end, whatever its length. A pattern longer than the address
+    /// never matches; an empty one always does.
+    pub fn matches(&self, encoded: &[u8], case_insensitive: bool) -> bool {
+        // `get` rather than indexing: an address shorter than the pattern
+        // just doesn't match.
+        let starts_with = |pattern: &str| {
+            let pattern = pattern.as_bytes();
+            encoded
+                .get(..pattern.len())
+                .is_some_and(|head| same_bytes(head, pattern, case_insensitive))
+        };
+        let ends_with = |pattern: &str| {
+            let pattern = pattern.as_bytes();
+            encoded
+                .len()
+                .checked_sub(pattern.len())
+                .and_then(|start| encoded.get(start..))
+                .is_some_and(|tail| same_bytes(tail, pattern, case_insensitive))
+        };
+        match self {
+            MatchType::Prefix(prefix) => starts_with(prefix),
+            MatchType::Suffix(suffix) => ends_with(suffix),
+            MatchType::Both(prefix, suffix) => starts_with(prefix) && ends_with(suffix),
+            MatchType::Contains(pattern) => {
+                find_bytes(encoded, pattern.as_bytes(), case_insensitive).is_some()
+            }
+        }
+    }
+
+    /// Each pattern with the part it is, the prefix first.
+    pub(crate) fn parts(&self) -> [Option<(MatchPart, &str)>; 2] {
+        match self {
+            MatchType::Prefix(pattern) => [Some((MatchPart::Prefix, pattern)), None],
+            MatchType::Suffix(pattern) => [Some((MatchPart::Suffix, pattern)), None],
+            MatchType::Both(prefix, suffix) => [
+                Some((MatchPart::Prefix, prefix)),
+                Some((MatchPart::Suffix, suffix)),
+            ],
+            MatchType::Contains(pattern) => [Some((MatchPart::Contains, pattern)), None],
+        }
+    }
+
+    pub(crate) fn patterns(&self) -> Vec<&str> {
+        match self {
+            MatchType::Prefix(pattern)
+            | MatchType::Suffix(pattern)
+            | MatchType::Contains(pattern) => vec![pattern],
+            MatchType::Both(prefix, suffix) => vec![prefix, suffix],
+        }
+    }
+
+    /// Rejects patterns no address could match: characters outside the
+    /// base58 alphabet (allowing for case folding) or more characters than
+    /// an address has. Emptiness is left to the caller.
+    pub fn validate(&self, case_insensitive: bool) -> Result<(), VanityError> {
+        for pattern in self.patterns() {
+            let len = pattern.chars().count();
+            if len > MAX_ENCODED_LEN {
+                return Err(VanityError::PatternTooLong {
+                    len,
+                    max: MAX_ENCODED_LEN,
+                });
+            }
+            BASE58.validate(pattern, case_insensitive)?;
+        }
+        Ok(())
+    }
+
+    /// Positions in `address` where the region it matched differs from
+    /// these patterns only in case, for a pattern kept as typed (built
+    /// with `case_insensitive` off) and an address that matched it with
+    /// case ignored. Sorted; empty when the case matches exactly.
+    pub fn case_mismatch_positions(&self, address: &str) -> Vec<u32> {
+        let mut positions: Vec<u32> = match self {
+            MatchType::Prefix(prefix) => case_differences(address, 0, prefix).collect(),
+            MatchType::Suffix(suffix) => {
+                let start = address.len().saturating_sub(suffix.len());
+                case_differences(address, start, suffix).collect()
+            }
+            MatchType::Both(prefix, suffix) => {
+                let start = address.len().saturating_sub(suffix.len());
+                case_differences(address, 0, prefix)
+                    .chain(case_differences(address, start, suffix))
+                    .collect()
+            }
+            MatchType::Contains(pattern) => {
+                find_bytes(address.as_bytes(), pattern.as_bytes(), true)
+                    .map(|start| case_differences(address, start, pattern).collect())
+                    .unwrap_or_default()
+            }
+        };
+        // A prefix and suffix can overlap on a short address.
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+
+    /// Heap bytes held by the patterns.
+    pub fn heap_bytes(&self) -> usize {
+        match self {
+            MatchType::Prefix(pattern)
+            | MatchType::Suffix(pattern)
+            | MatchType::Contains(pattern) => pattern.capacity(),
+            MatchType::Both(prefix, suffix) => prefix.capacity() + suffix.capacity(),
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            MatchType::Prefix(pattern)
+            | MatchType::Suffix(pattern)
+            | MatchType::Contains(pattern) => pattern.shrink_to_fit(),
+            MatchType::Both(prefix, suffix) => {
+                prefix.shrink_to_fit();
+                suffix.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Whether every address matches, i.e. no pattern was given.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            MatchType::Prefix(pattern)
+            | MatchType::Suffix(pattern)
+            | MatchType::Contains(pattern) => pattern.is_empty(),
+            MatchType::Both(prefix, suffix) => prefix.is_empty() && suffix.is_empty(),
+        }
+    }
+
+    /// Matches the pattern anywhere in the address.
+    pub fn contains(pattern: String, case_insensitive: bool) -> MatchType {
+        MatchType::Contains(if case_insensitive {
+            fold_str(&pattern)
+        } else {
+            pattern
+        })
+    }
+
+    pub fn probability(&self, case_insensitive: bool) -> f64 {
+        use crate::estimate::{contains_probability, match_probability};
+
+        match self {
+            MatchType::Prefix(prefix) => match_probability(Some(prefix), None, case_insensitive),
+            MatchType::Suffix(suffix) => match_probability(None, Some(suffix), case_insensitive),
+            MatchType::Both(prefix, suffix) => {
+                match_probability(Some(prefix), Some(suffix), case_insensitive)
+            }
+            MatchType::Contains(pattern) => contains_probability(pattern, case_insensitive),
+        }
+    }
+}
+
+/// A constraint on the raw pubkey bytes: a pubkey matches if
+/// `pubkey & mask == value`. Checked before base58 encoding, so a search on
+/// bytes alone never encodes a non-matching pubkey.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByteMask {
+    mask: [u8; PUBKEY_LEN],
+    value: [u8; PUBKEY_LEN],
+}
+
+impl ByteMask {
+    /// `mask` and `value` cover the leading bytes of the pubkey and must be
+    /// the same length, at most 32; the bytes after them are unconstrained.
+    /// Fails if the mask selects no bits, or if `value` sets a bit the mask
+    /// clears, which no pubkey could match.
+    pub fn new(mask: &[u8], value: &[u8]) -> Result<ByteMask, VanityError> {
+        let invalid = |detail: String| Err(VanityError::InvalidByteMask(detail));
+        if mask.len() > PUBKEY_LEN {
+            return invalid(format!(
+                "mask is {} bytes; pubkeys have {PUBKEY_LEN}",
+                mask.len()
+            ));
+        }
+        if value.len() != mask.len() {
+            return invalid(format!(
+                "value is {} bytes but mask is {}",
+                value.len(),
+                mask.len()
+            ));
+        }
+        if let Some(index) = (0..mask.len()).find(|&i| value[i] & !mask[i] != 0) {
+            return invalid(format!(
+                "value byte {index} ({:#04x}) sets bits outside mask byte {index} ({:#04x})",
+                value[index], mask[index]
+            ));
+        }
+        if mask.iter().all(|&byte| byte == 0) {
+            return invalid("mask selects no bits".into());
+        }
+        let mut byte_mask = ByteMask {
+            mask: [0; PUBKEY_LEN],
+            value: [0; PUBKEY_LEN],
+        };
+        byte_mask.mask[..mask.len()].copy_from_slice(mask);
+        byte_mask.value[..value.len()].copy_from_slice(value);
+        Ok(byte_mask)
+    }
+
+    pub fn matches(&self, pubkey: &[u8; PUBKEY_LEN]) -> bool {
+        pubkey
+            .iter()
+            .zip(&self.mask)
+            .zip(&self.value)
+            .all(|((byte, mask), value)| byte & mask == *value)
+    }
+
+    /// Chance a random pubkey matches: one half per masked bit.
+    pub fn probability(&self) -> f64 {
+        let bits: u32 = self.mask.iter().map(|byte| byte.count_ones()).sum();
+        float::powi(0.5, bits as i32)
+    }
+
+    pub fn mask(&self) -> &[u8; PUBKEY_LEN] {
+        &self.mask
+    }
+
+    pub fn value(&self) -> &[u8; PUBKEY_LEN] {
+        &self.value
+    }
+}
+
+/// Base58 characters easily misread as one another: S/5, Z/2, B/8, G/6
+/// and g/q.
+pub const CLEAN_PREFIX_CHARS: &str = "S5Z2B8G6gq";
+
+/// Adjacent pairs that read as another glyph: "rn" as m, "vv" as w.
+pub const CLEAN_PREFIX_PAIRS: &[&str] = &["rn", "vv", "VV"];
+
+/// A [`CleanPrefix`]'s settings, as listed for a UI.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct ConfusableSet {
+    pub clean_prefix_len: u8,
+    pub chars: String,
+    pub pairs: Vec<String>,
+}
+
+/// Rejects addresses whose first `len` characters contain a flagged
+/// character or adjacent pair, for addresses meant to be read out or
+/// printed. Characters are checked through a byte table and pairs through
+/// a bit per (first, second) byte, so no allocation happens per address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CleanPrefix {
+    len: u8,
+    chars: String,
+    pairs: Vec<String>,
+    flagged: [bool; 128],
+    /// Bit `b` of `flagged_pairs[a]` flags the pair `ab`.
+    flagged_pairs: [u128; 128],
+}
+
+impl CleanPrefix {
+    /// Fails if `len` is 0 or longer than an address, or a character or
+    /// pair isn't base58 (pairs must be exactly two characters).
+    pub fn new(
+        len: u8,
+        chars: &str,
+        pairs: &[impl AsRef<str>],
+    ) -> Result<CleanPrefix, VanityError> {
+        let invalid = |detail: String| Err(VanityError::InvalidOptions(detail));
+        if len == 0 || len as usize > MAX_ENCODED_LEN {
+            return invalid(format!(
+                "clean prefix length must be between 1 and {MAX_ENCODED_LEN}, got {len}"
+            ));
+        }
+        let mut flagged = [false; 128];
+        for c in chars.chars() {
+            if !is_base58_char(c, false) {
+                return invalid(format!("confusable character {c:?} is not base58"));
+            }
+            flagged[c as usize] = true;
+        }
+        let mut flagged_pairs = [0u128; 128];
+        for pair in pairs {
+            let pair = pair.as_ref();
+            match pair.as_bytes() {
+                &[a, b] if is_base58_char(a as char, false) && is_base58_char(b as char, false) => {
+                    flagged_pairs[a as usize] |= 1 << b;
+                }
+                _ => {
+                    return invalid(format!(
+                        "confusable pair {pair:?} is not two base58 characters"
+                    ))
+                }
+            }
+        }
+        Ok(CleanPrefix {
+            len,
+            chars: chars.to_string(),
+            pairs: pairs.iter().map(|pair| pair.as_ref().to_string()).collect(),
+            flagged,
+            flagged_pairs,
+        })
+    }
+
+    /// [`CLEAN_PREFIX_CHARS`] and [`CLEAN_PREFIX_PAIRS`] over the first
+    /// `len` characters.
+    pub fn with_default_set(len: u8) -> Result<CleanPrefix, VanityError> {
+        CleanPrefix::new(len, CLEAN_PREFIX_CHARS, CLEAN_PREFIX_PAIRS)
+    }
+
+    /// Whether the first `len` characters of `address` (all of it if
+    /// shorter) avoid every flagged character and pair.
+    pub fn is_clean(&self, address: &str) -> bool {
+        let head = &address.as_bytes()[..address.len().min(self.len as usize)];
+        // Base58 addresses are ASCII, so indexing by byte stays in bounds.
+        if head.iter().any(|&c| self.flagged[(c & 0x7f) as usize]) {
+            return false;
+        }
+        !head
+            .windows(2)
+            .any(|pair| self.flagged_pairs[(pair[0] & 0x7f) as usize] >> (pair[1] & 0x7f) & 1 != 0)
+    }
+
+    /// Checks that `prefix`, matched case-sensitively, leaves room for a
+    /// clean address: it can't fix a flagged character or pair in place.
+    pub fn check_prefix(&self, prefix: &str) -> Result<(), VanityError> {
+        if self.is_clean(prefix) {
+            return Ok(());
+        }
+        Err(VanityError::ConflictingOptions(format!(
+            "prefix {prefix:?} has a confusable character or pair within the first {} characters",
+            self.len
+        )))
+    }
+
+    /// Rough chance a random address passes: each unconstrained position
+    /// among the first `len`, past `fixed` characters a pattern already
+    /// pins, avoids the flagged characters, taken as uniform. Pairs are
+    /// ignored.
+    pub fn probability(&self, fixed: usize) -> f64 {
+        let flagged = self.flagged.iter().filter(|&&flagged| flagged).count();
+        let free = (self.len as usize).saturating_sub(fixed);
+        float::powi(1.0 - flagged as f64 / 58.0, free as i32)
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.len
+    }
+
+    pub fn set(&self) -> ConfusableSet {
+        ConfusableSet {
+            clean_prefix_len: self.len,
+            chars: self.chars.clone(),
+            pairs: self.pairs.clone(),
+        }
+    }
+}
+
+/// A part of an [`AddressMatcher`], as reported by
+/// [`AddressMatcher::explain`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum MatchPart {
+    Prefix,
+    Suffix,
+    Contains,
+    CleanPrefix,
+}
+
+/// How an address fared against one [`MatchPart`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct MatchCheck {
+    pub part: MatchPart,
+    /// The pattern as matched, i.e. lowercased when case is ignored; unset
+    /// for a clean prefix.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub pattern: Option<String>,
+    pub passed: bool,
+    /// Characters of the pattern the address has in place: leading ones
+    /// for a prefix, trailing ones for a suffix, and the longest start of
+    /// the pattern found anywhere for a contains pattern. Unset for a clean
+    /// prefix.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub matched_chars: Option<u32>,
+}
+
+/// What [`AddressMatcher::explain`] found.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct MatchExplanation {
+    /// Whether every check passed, as [`AddressMatcher::matches`] says.
+    pub matches: bool,
+    /// In the order prefix, suffix, contains, clean prefix; parts that
+    /// weren't set are left out.
+    pub checks: Vec<MatchCheck>,
+}
+
+/// A pattern ready to test encoded addresses exactly as
+/// [`crate::Searcher`] does, which holds one: the address is case-folded if
+/// asked, matched against the [`MatchType`], then checked against the
+/// clean prefix, if any.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressMatcher {
+    match_type: MatchType,
+    case_insensitive: bool,
+    /// The unfolded pattern, kept only when matching case-insensitively.
+    typed_pattern: Option<MatchType>,
+    clean_prefix: Option<CleanPrefix>,
+}
+
+impl AddressMatcher {
+    /// Builds a validated prefix and/or suffix matcher, as
+    /// [`crate::Searcher::new`] does: at least one non-blank pattern is
+    /// required.
+    pub fn new(
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+    ) -> Result<AddressMatcher, VanityError> {
+        let typed = MatchType::new(prefix.clone(), suffix.clone(), false);
+        let match_type = MatchType::new(prefix, suffix, case_insensitive);
+        if match_type.is_empty() {
+            return Err(VanityError::EmptyPattern);
+        }
+        match_type.validate(case_insensitive)?;
+        let mut matcher = AddressMatcher::with_match_type(match_type, case_insensitive);
+        matcher.set_typed_pattern(typed);
+        Ok(matcher)
+    }
+
+    /// A matcher for an already folded match spec, unchecked.
+    pub fn with_match_type(match_type: MatchType, case_insensitive: bool) -> AddressMatcher {
+        AddressMatcher {
+            match_type,
+            case_insensitive,
+            typed_pattern: None,
+            clean_prefix: None,
+        }
+    }
+
+    /// See [`crate::Searcher::set_typed_pattern`].
+    pub fn set_typed_pattern(&mut self, typed: MatchType) {
+        self.typed_pattern = self.case_insensitive.then_some(typed);
+    }
+
+    /// See [`crate::Searcher::set_clean_prefix`].
+    pub fn set_clean_prefix(
+        &mut self,
+        clean_prefix: Option<CleanPrefix>,
+    ) -> Result<(), VanityError> {
+        if let (Some(clean_prefix), false) = (&clean_prefix, self.case_insensitive) {
+            if let MatchType::Prefix(prefix) | MatchType::Both(prefix, _) = &self.match_type {
+                clean_prefix.check_prefix(prefix)?;
+            }
+        }
+        self.clean_prefix = clean_prefix;
+        Ok(())
+    }
+
+    pub fn clean_prefix(&self) -> Option<&CleanPrefix> {
+        self.clean_prefix.as_ref()
+    }
+
+    pub fn match_type(&self) -> &MatchType {
+        &self.match_type
+    }
+
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub fn matches(&self, address: &str) -> bool {
+        if let Some(clean_prefix) = &self.clean_prefix {
+            if !clean_prefix.is_clean(address) {
+                return false;
+            }
+        }
+        self.match_type
+            .matches(address.as_bytes(), self.case_insensitive)
+    }
+
+    /// Whether `pubkey` certainly doesn't match, judged by the end of its
+    /// encoding alone: a suffix (up to its last [`MAX_TRAILING_CHARS`]
+    /// characters) is checked with [`trailing_chars`], far cheaper than
+    /// encoding the whole pubkey. `false` only means it might match.
+    pub fn rules_out(&self, pubkey: &[u8; PUBKEY_LEN]) -> bool {
+        let (MatchType::Suffix(suffix) | MatchType::Both(_, suffix)) = &self.match_type else {
+            return false;
+        };
+        let suffix = suffix.as_bytes();
+        let (_, tail) = suffix.split_at(suffix.len().saturating_sub(MAX_TRAILING_CHARS));
+        let mut buf = [0u8; MAX_TRAILING_CHARS];
+        !same_bytes(
+            trailing_chars(pubkey, tail.len(), &mut buf),
+            tail,
+            self.case_insensitive,
+        )
+    }
+
+    /// See [`MatchType::case_mismatch_positions`]; empty when matching
+    /// case-sensitively or without a typed pattern.
+    pub fn case_mismatch_positions(&self, address: &str) -> Vec<u32> {
+        self.typed_pattern
+            .as_ref()
+            .map(|typed| typed.case_mismatch_positions(address))
+            .unwrap_or_default()
+    }
+
+    /// Per-address probability of a match, clean prefix included.
+    pub fn probability(&self) -> f64 {
+        let pattern = self.match_type.probability(self.case_insensitive);
+        match &self.clean_prefix {
+            Some(clean_prefix) => {
+                let fixed = match &self.match_type {
+                    MatchType::Prefix(prefix) | MatchType::Both(prefix, _) => prefix.len(),
+                    MatchType::Suffix(_) | MatchType::Contains(_) => 0,
+                };
+                pattern * clean_prefix.probability(fixed)
+            }
+            None => pattern,
+        }
+    }
+
+    /// Checks each part of the matcher on its own, e.g. to show which part
+    /// of a pattern an address misses.
+    pub fn explain(&self, address: &str) -> MatchExplanation {
+        let mut checks: Vec<MatchCheck> = self
+            .match_type
+            .parts()
+            .into_iter()
+            .flatten()
+            .map(|(part, pattern)| {
+                let matched =
+                    matched_chars(part, address.as_bytes(), pattern, self.case_insensitive);
+                MatchCheck {
+                    part,
+                    pattern: Some(pattern.to_string()),
+                    passed: matched == pattern.len(),
+                    matched_chars: Some(matched as u32),
+                }
+            })
+            .collect();
+        // An empty pattern matches everything; it isn't a part worth listing.
+        checks.retain(|check| check.pattern.as_ref().is_some_and(|p| !p.is_empty()));
+        if let Some(clean_prefix) = &self.clean_prefix {
+            checks.push(MatchCheck {
+                part: MatchPart::CleanPrefix,
+                pattern: None,
+                passed: clean_prefix.is_clean(address),
+                matched_chars: None,
+            });
+        }
+        MatchExplanation {
+            matches: checks.iter().all(|check| check.passed),
+            checks,
+        }
+    }
+
+    /// Heap bytes held by the patterns.
+    pub fn heap_bytes(&self) -> usize {
+        self.match_type.heap_bytes() + self.typed_pattern.as_ref().map_or(0, MatchType::heap_bytes)
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.match_type.shrink_to_fit();
+        if let Some(typed) = &mut self.typed_pattern {
+            typed.shrink_to_fit();
+        }
+    }
+}
+
+/// Positions from `start` on where `address` and `pattern` (both ASCII
+/// once matched, as base58 is) differ.
+/// Characters of `pattern` that `address` has in place as `part`, counted
+/// as [`MatchCheck::matched_chars`] says; 0 for a clean prefix.
+pub(crate) fn matched_chars(
+    part: MatchPart,
+    address: &[u8],
+    pattern: &str,
+    case_insensitive: bool,
+) -> usize {
+    let same = |(&a, p): (&u8, u8)| same_byte(a, p, case_insensitive);
+    let leading = |a: &[u8]| {
+        a.iter()
+            .zip(pattern.bytes())
+            .take_while(|&pair| same(pair))
+            .count()
+    };
+    match part {
+        MatchPart::Prefix => leading(address),
+        MatchPart::Suffix => address
+            .iter()
+            .rev()
+            .zip(pattern.bytes().rev())
+            .take_while(|&pair| same(pair))
+            .count(),
+        MatchPart::Contains => (0..address.len())
+            .map(|start| leading(&address[start..]))
+            .max()
+            .unwrap_or(0),
+        MatchPart::CleanPrefix => 0,
+    }
+}
+
+fn case_differences<'a>(
+    address: &'a str,
+    start: usize,
+    pattern: &'a str,
+) -> impl Iterator<Item = u32> + 'a {
+    address
+        .bytes()
+        .skip(start)
+        .zip(pattern.bytes())
+        .enumerate()
+        .filter(|(_, (a, p))| a != p)
+        .map(move |(i, _)| (start + i) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_mask_matches() {
+        let mut pubkey = [0xab; PUBKEY_LEN];
+        // Whole bytes: first byte 0x00, then 0xbeef.
+        let full = ByteMask::new(&[0xff, 0xff, 0xff], &[0x00, 0xbe, 0xef]).unwrap();
+        assert!(!full.matches(&pubkey));
+        pubkey[..3].copy_from_slice(&[0x00, 0xbe, 0xef]);
+        assert!(full.matches(&pubkey));
+        assert_eq!(full.probability(), 0.5f64.powi(24));
+
+        // High nibble of byte 1 only.
+        let nibble = ByteMask::new(&[0x00, 0xf0], &[0x00, 0xb0]).unwrap();
+        assert!(nibble.matches(&pubkey));
+        pubkey[1] = 0xbf;
+        assert!(nibble.matches(&pubkey));
+        pubkey[1] = 0xcf;
+        assert!(!nibble.matches(&pubkey));
+        assert_eq!(nibble.probability(), 1.0 / 16.0);
+    }
+
+    #[test]
+    fn test_byte_mask_validation() {
+        let kind = |mask: &[u8], value: &[u8]| ByteMask::new(mask, value).err().map(|e| e.kind());
+        let invalid = Some(crate::ErrorKind::InvalidByteMask);
+        assert_eq!(kind(&[0xff; 33], &[0; 33]), invalid);
+        assert_eq!(kind(&[0xff; 2], &[0; 1]), invalid);
+        assert_eq!(kind(&[0x0f], &[0x10]), invalid);
+        assert_eq!(kind(&[0; 4], &[0; 4]), invalid);
+        assert_eq!(kind(&[], &[]), invalid);
+        assert_eq!(kind(&[0xff; 32], &[7; 32]), None);
+    }
+
+    #[test]
+    fn test_match_type_new() {
+        assert_eq!(
+            MatchType::new(Some("Ab".into()), None, true),
+            MatchType::Prefix("ab".into())
+        );
+        assert_eq!(
+            MatchType::new(None, Some("Cd".into()), false),
+            MatchType::Suffix("Cd".into())
+        );
+        assert_eq!(
+            MatchType::new(Some("A".into()), Some("B".into()), true),
+            MatchType::Both("a".into(), "b".into())
+        );
+        assert_eq!(
+            MatchType::new(None, None, false),
+            MatchType::Prefix(String::new())
+        );
+    }
+
+    #[test]
+    fn test_blank_patterns_are_absent() {
+        assert_eq!(
+            MatchType::new(Some(" ab ".into()), Some("  ".into()), false),
+            MatchType::Prefix("ab".into())
+        );
+        assert!(MatchType::new(Some("".into()), Some(" \t".into()), false).is_empty());
+        assert!(MatchType::new(None, None, true).is_empty());
+        assert!(MatchType::contains(String::new(), false).is_empty());
+        assert!(!MatchType::new(None, Some("x".into()), false).is_empty());
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(MatchType::Prefix("ab".into()).matches(b"abc", false));
+        assert!(!MatchType::Prefix("bc".into()).matches(b"abc", false));
+        assert!(MatchType::Suffix("bc".into()).matches(b"abc", false));
+        assert!(MatchType::Both("a".into(), "c".into()).matches(b"abc", false));
+        assert!(!MatchType::Both("a".into(), "b".into()).matches(b"abc", false));
+        assert!(MatchType::Contains("b".into()).matches(b"abc", false));
+        assert!(!MatchType::Contains("d".into()).matches(b"abc", false));
+    }
+
+    #[test]
+    fn test_contains() {
+        assert_eq!(
+            MatchType::contains("Moon".into(), true),
+            MatchType::Contains("moon".into())
+        );
+        assert!(MatchType::contains("moon".into(), false).matches(b"xxmoonxx", false));
+    }
+
+    #[test]
+    fn test_case_folding() {
+        assert!(MatchType::new(Some("aBc".into()), None, true).matches(b"AbC", true));
+        assert!(!MatchType::new(Some("abc".into()), None, false).matches(b"AbC", false));
+    }
+
+    #[test]
+    fn test_case_mismatch_positions() {
+        let typed = MatchType::new(Some("SoLana".into()), None, false);
+        assert_eq!(typed.case_mismatch_positions("SoLanaXyz"), []);
+        assert_eq!(
+            typed.case_mismatch_positions("sOlANAXyz"),
+            [0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(typed.case_mismatch_positions("SoLANaXyz"), [3, 4]);
+
+        let suffix = MatchType::new(None, Some("pump".into()), false);
+        assert_eq!(suffix.case_mismatch_positions("abcpump"), []);
+        assert_eq!(suffix.case_mismatch_positions("abcPUMP"), [3, 4, 5, 6]);
+        assert_eq!(suffix.case_mismatch_positions("abcPumP"), [3, 6]);
+
+        let both = MatchType::new(Some("Ab".into()), Some("cD".into()), false);
+        assert_eq!(both.case_mismatch_positions("Abxyzcd"), [6]);
+        assert_eq!(both.case_mismatch_positions("aBxyzCD"), [0, 1, 5]);
+        // Overlapping prefix and suffix report each position once.
+        let overlap = MatchType::new(Some("abc".into()), Some("bcd".into()), false);
+        assert_eq!(overlap.case_mismatch_positions("aBCd"), [1, 2]);
+
+        let contains = MatchType::contains("moon".into(), false);
+        assert_eq!(contains.case_mismatch_positions("xxMoOnxx"), [2, 4]);
+        assert_eq!(contains.case_mismatch_positions("xxmoonxx"), []);
+    }
+
+    #[test]
+    fn test_address_matcher_explains_each_part() {
+        let folded = AddressMatcher::new(Some("MoOn".into()), None, true).unwrap();
+        assert!(folded.matches("moONxyz"));
+        assert_eq!(folded.case_mismatch_positions("moONxyz"), [0, 3]);
+        let explanation = folded.explain("mooxyz");
+        assert!(!explanation.matches);
+        assert_eq!(
+            explanation.checks,
+            [MatchCheck {
+                part: MatchPart::Prefix,
+                pattern: Some("moon".into()),
+                passed: false,
+                matched_chars: Some(3),
+            }]
+        );
+
+        let contains =
+            AddressMatcher::with_match_type(MatchType::contains("pump".into(), false), false);
+        let check = |address: &str| contains.explain(address).checks[0].matched_chars;
+        assert_eq!(check("xxpumxpupumpx"), Some(4));
+        assert_eq!(check("xxpumxpu"), Some(3));
+        assert_eq!(check("é"), Some(0));
+
+        let mut clean = AddressMatcher::new(None, Some("z".into()), false).unwrap();
+        clean
+            .set_clean_prefix(Some(CleanPrefix::with_default_set(3).unwrap()))
+            .unwrap();
+        let explanation = clean.explain("S12z");
+        assert_eq!(
+            explanation
+                .checks
+                .iter()
+                .map(|check| (check.part, check.passed))
+                .collect::<Vec<_>>(),
+            [(MatchPart::Suffix, true), (MatchPart::CleanPrefix, false)]
+        );
+        assert!(!explanation.matches);
+        assert!(!clean.matches("S12z"));
+        assert!(clean.explain("a13z").matches);
+
+        // An empty pattern has nothing to explain and matches everything.
+        let empty = AddressMatcher::with_match_type(MatchType::new(None, None, false), false);
+        assert_eq!(empty.explain("abc").checks, []);
+        assert!(empty.explain("abc").matches);
+        assert_eq!(
+            AddressMatcher::new(None, Some(" ".into()), false),
+            Err(VanityError::EmptyPattern)
+        );
+    }
+
+    #[test]
+    fn test_clean_prefix_rejects_flagged_characters() {
+        let clean = CleanPrefix::with_default_set(4).unwrap();
+        assert!(clean.is_clean("abcdS5Z2"));
+        for address in ["Sabcd", "ab5cd", "xyzq", "Gxyz"] {
+            assert!(!clean.is_clean(address), "{address}");
+        }
+        // Only the first `len` characters count.
+        assert!(clean.is_clean("abcd5"));
+        assert!(clean.is_clean("ab"));
+        let custom = CleanPrefix::new(3, "x", &[] as &[&str]).unwrap();
+        assert!(custom.is_clean("S5Z"));
+        assert!(!custom.is_clean("axb"));
+    }
+
+    #[test]
+    fn test_clean_prefix_rejects_flagged_pairs() {
+        let clean = CleanPrefix::with_default_set(5).unwrap();
+        assert!(!clean.is_clean("abrnc"));
+        assert!(!clean.is_clean("vvabc"));
+        assert!(!clean.is_clean("aaaVV"));
+        // Each letter alone is fine, and so is the pair past the prefix.
+        assert!(clean.is_clean("rvnrv"));
+        assert!(clean.is_clean("aaaarn"));
+        assert!(clean.is_clean("abnrc"));
+        assert!(clean.check_prefix("abc").is_ok());
+        assert_eq!(
+            clean.check_prefix("arn").unwrap_err().kind(),
+            crate::ErrorKind::ConflictingOptions
+        );
+    }
+
+    #[test]
+    fn test_clean_prefix_validation() {
+        let err = |result: Result<CleanPrefix, VanityError>| result.unwrap_err().kind();
+        assert_eq!(
+            err(CleanPrefix::with_default_set(0)),
+            crate::ErrorKind::InvalidOptions
+        );
+        assert!(CleanPrefix::with_default_set(45).is_err());
+        assert!(CleanPrefix::new(4, "0", &[] as &[&str]).is_err());
+        assert!(CleanPrefix::new(4, "", &["r"]).is_err());
+        assert!(CleanPrefix::new(4, "", &["rnm"]).is_err());
+        assert!(CleanPrefix::new(4, "", &["rO"]).is_err());
+        let set = CleanPrefix::new(4, "S", &["rn"]).unwrap().set();
+        assert_eq!(set.chars, "S");
+        assert_eq!(set.pairs, ["rn"]);
+        assert_eq!(set.clean_prefix_len, 4);
+        let clean = CleanPrefix::with_default_set(4).unwrap();
+        assert_eq!(clean.probability(4), 1.0);
+        assert_eq!(clean.probability(2), (48.0f64 / 58.0).powi(2));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            MatchType::new(Some("Abc".into()), None, false).validate(false),
+            Ok(())
+        );
+        assert_eq!(
+            MatchType::new(None, Some("x0".into()), false).validate(false),
+            Err(VanityError::InvalidPatternChar {
+                pattern: "x0".into(),
+                character: '0',
+                position: 1,
+                alphabet: "base58",
+            })
+        );
+        // 'l' can't appear as-is, but matches 'L' when folding case.
+        let lower_l = MatchType::new(Some("l".into()), None, true);
+        assert!(lower_l.validate(true).is_ok());
+        assert!(MatchType::Prefix("l".into()).validate(false).is_err());
+        assert_eq!(
+            MatchType::contains("é".into(), false)
+                .validate(false)
+                .unwrap_err()
+                .kind(),
+            crate::ErrorKind::InvalidPatternChar
+        );
+
+        let long = "z".repeat(MAX_ENCODED_LEN + 1);
+        assert_eq!(
+            MatchType::new(Some("A".into()), Some(long), false).validate(false),
+            Err(VanityError::PatternTooLong {
+                len: MAX_ENCODED_LEN + 1,
+                max: MAX_ENCODED_LEN,
+            })
+        );
+    }
+
+    #[test]
+    fn test_fix_confusables() {
+        for (original, replacement) in [('0', 'o'), ('O', 'o'), ('I', '1'), ('l', '1')] {
+            let (fixed, rewrites) =
+                fix_confusables(&format!("A{original}"), PatternSide::Prefix, false);
+            assert_eq!(fixed, format!("A{replacement}"));
+            assert_eq!(
+                rewrites,
+                [PatternRewrite {
+                    pattern: PatternSide::Prefix,
+                    position: 1,
+                    original,
+                    replacement,
+                }]
+            );
+        }
+
+        let (fixed, rewrites) = fix_confusables(" S0lID ", PatternSide::Suffix, false);
+        assert_eq!(fixed, "So11D");
+        let positions: Vec<_> = rewrites.iter().map(|r| r.position).collect();
+        assert_eq!(positions, [1, 2, 3]);
+        assert!(MatchType::Suffix(fixed).validate(false).is_ok());
+
+        // Folding case already makes O, I and l matchable; only 0 is fixed.
+        let (fixed, rewrites) = fix_confusables("0OIl", PatternSide::Contains, true);
+        assert_eq!(fixed, "oOIl");
+        assert_eq!(rewrites.len(), 1);
+
+        // Characters without a look-alike are left for validate.
+        let (fixed, rewrites) = fix_confusables("A_0", PatternSide::Prefix, false);
+        assert_eq!((fixed.as_str(), rewrites.len()), ("A_o", 1));
+    }
+
+    #[test]
+    fn test_matches_matrix() {
+        let variants = |pattern: &str| {
+            [
+                MatchType::Prefix(pattern.into()),
+                MatchType::Suffix(pattern.into()),
+                MatchType::Both(pattern.into(), pattern.into()),
+                MatchType::Contains(pattern.into()),
+            ]
+        };
+        for case_insensitive in [false, true] {
+            for match_type in variants("abc") {
+                let matches = |encoded: &[u8]| match_type.matches(encoded, case_insensitive);
+                // Equal length: the whole address is the pattern.
+                assert!(matches(b"abc"), "{match_type:?}");
+                assert_eq!(matches(b"aBC"), case_insensitive, "{match_type:?}");
+                // Pattern longer than the address.
+                assert!(!matches(b"ab"), "{match_type:?}");
+                assert!(!matches(b""), "{match_type:?}");
+                // Not ASCII: never folded, never offset.
+                assert!(!matches("\u{e0}bc".as_bytes()), "{match_type:?}");
+                assert!(!matches("ab\u{c7}".as_bytes()), "{match_type:?}");
+            }
+            // An empty pattern, which only a validation bypass lets through,
+            // matches everything.
+            for match_type in variants("") {
+                assert!(match_type.matches(b"", case_insensitive));
+                assert!(match_type.matches(b"xyz", case_insensitive));
+            }
+        }
+
+        let at = |match_type: &MatchType, encoded: &[u8]| {
+            [false, true].map(|case_insensitive| match_type.matches(encoded, case_insensitive))
+        };
+        let [prefix, suffix, both, contains] = variants("ab");
+        assert_eq!(at(&prefix, b"abxy"), [true, true]);
+        assert_eq!(at(&prefix, b"ABxy"), [false, true]);
+        assert_eq!(at(&prefix, b"xyab"), [false, false]);
+        assert_eq!(at(&suffix, b"xyAB"), [false, true]);
+        assert_eq!(at(&suffix, b"abxy"), [false, false]);
+        assert_eq!(at(&both, b"abxyAb"), [false, true]);
+        assert_eq!(at(&both, b"abxy"), [false, false]);
+        // Prefix and suffix may overlap on a short address.
+        assert_eq!(at(&both, b"ab"), [true, true]);
+        assert_eq!(at(&contains, b"xAby"), [false, true]);
+        assert_eq!(at(&contains, b"xa"), [false, false]);
+    }
+
+    #[test]
+    fn test_rules_out_checks_the_suffix_tail() {
+        let pubkey = [7u8; PUBKEY_LEN];
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        let address = crate::derive::encode_pubkey(&pubkey, &mut buf).to_string();
+        let tail = |n: usize| &address[address.len() - n..];
+        let rules_out = |match_type: MatchType, case_insensitive: bool| {
+            AddressMatcher::with_match_type(match_type, case_insensitive).rules_out(&pubkey)
+        };
+
+        for n in [1, 8, 12] {
+            assert!(!rules_out(MatchType::Suffix(tail(n).into()), false), "{n}");
+            assert!(!rules_out(
+                MatchType::Both("x".into(), tail(n).into()),
+                false
+            ));
+            assert!(!rules_out(MatchType::Suffix(tail(n).to_uppercase()), true));
+        }
+        // Only the last eight characters are looked at.
+        let wrong_head = ["z", tail(10)].concat();
+        assert!(!rules_out(MatchType::Suffix(wrong_head), false));
+        let mut wrong_tail = tail(3).to_string();
+        wrong_tail.push(if address.ends_with('z') { 'y' } else { 'z' });
+        assert!(rules_out(MatchType::Suffix(wrong_tail), false));
+        // Nothing else is prefiltered.
+        assert!(!rules_out(MatchType::Prefix("zzzz".into()), false));
+        assert!(!rules_out(MatchType::Contains("zzzz".into()), false));
+    }
+
+    #[test]
+    fn test_short_and_long_addresses() {
+        let mut lengths = Vec::new();
+        for (pubkey, address) in crate::fixtures::candidates() {
+            let len = address.len();
+            lengths.push(len);
+            let bytes = address.as_bytes();
+            let tail = |n: usize| &address[len - n..];
+            let matcher = |match_type| AddressMatcher::with_match_type(match_type, false);
+
+            // A suffix ends where the address does.
+            for n in [1, 8, 12, len] {
+                let suffix = MatchType::Suffix(tail(n).into());
+                assert!(suffix.matches(bytes, false), "{address} {n}");
+                assert!(!matcher(suffix).rules_out(&pubkey), "{address} {n}");
+            }
+            let shifted = MatchType::Suffix(address[len - 9..len - 1].into());
+            assert!(!shifted.matches(bytes, false), "{address}");
+            assert!(matcher(shifted).rules_out(&pubkey), "{address}");
+            let both = MatchType::Both(address[..4].into(), tail(4).into());
+            assert!(both.matches(bytes, false), "{address}");
+            assert!(MatchType::Contains(tail(5).into()).matches(bytes, false));
+
+            // The whole address matches as any pattern; one character more
+            // matches as none, even where it is a valid 44-character one.
+            let longer = [address.as_str(), "z"].concat();
+            for pattern in [&address, &longer] {
+                for match_type in [
+                    MatchType::Prefix(pattern.clone()),
+                    MatchType::Suffix(pattern.clone()),
+                    MatchType::Contains(pattern.clone()),
+                ] {
+                    assert_eq!(
+                        match_type.matches(bytes, false),
+                        pattern == &address,
+                        "{match_type:?}"
+                    );
+                    assert_eq!(
+                        match_type.validate(false).is_ok(),
+                        pattern.len() <= MAX_ENCODED_LEN
+                    );
+                }
+            }
+
+            // Suffix characters are counted back from the actual end.
+            let other = if bytes[len - 6] == b'z' { "y" } else { "z" };
+            let explanation =
+                matcher(MatchType::Suffix([other, tail(5)].concat())).explain(&address);
+            assert_eq!(explanation.checks[0].matched_chars, Some(5), "{address}");
+            assert!(!explanation.matches);
+            let flipped: String = tail(6)
+                .chars()
+                .map(|c| match c.is_ascii_lowercase() {
+                    true => c.to_ascii_uppercase(),
+                    false => c.to_ascii_lowercase(),
+                })
+                .collect();
+            let expected: Vec<u32> = (len - 6..len)
+                .filter(|&i| bytes[i].is_ascii_alphabetic())
+                .map(|i| i as u32)
+                .collect();
+            let typed = MatchType::new(None, Some(flipped), false);
+            assert_eq!(typed.case_mismatch_positions(&address), expected);
+
+            // A clean prefix as long as the longest address covers all of
+            // a shorter one.
+            let last = &address[len - 1..];
+            let clean = CleanPrefix::new(MAX_ENCODED_LEN as u8, last, &[] as &[&str]).unwrap();
+            assert!(!clean.is_clean(&address), "{address}");
+        }
+        assert!(lengths.contains(&(MAX_ENCODED_LEN - 1)) && lengths.contains(&MAX_ENCODED_LEN));
+    }
+}
@@ -0,0 +1,367 @@
+//! Joint mint + metadata grinding: a mint keypair whose address and whose
+//! Metaplex metadata PDA both (or either) match a pattern.
+
+use alloc::string::{String, ToString};
+
+use serde::{Deserialize, Serialize};
+
+use crate::derive::{encode_pubkey, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::float;
+use crate::keys::{encode_keypair, pubkey_from_secret, secret_from_counter};
+use crate::matcher::MatchType;
+use crate::pda::find_program_address;
+
+/// The Metaplex Token Metadata program.
+pub const METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+const SEED_METADATA: &[u8] = b"metadata";
+
+/// The metadata PDA for `mint`, with its bump.
+pub fn metadata_address(
+    program_id: &[u8; PUBKEY_LEN],
+    mint: &[u8; PUBKEY_LEN],
+) -> ([u8; PUBKEY_LEN], u8) {
+    find_program_address(&[SEED_METADATA, program_id, mint], program_id)
+        .expect("no bump takes the metadata address off the curve")
+}
+
+/// Which of the two patterns a [`MetadataSearcher`] result has to match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum JointRequirement {
+    #[default]
+    Both,
+    Either,
+}
+
+/// One side's pattern.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchSpec {
+    pub match_type: MatchType,
+    pub case_insensitive: bool,
+}
+
+impl MatchSpec {
+    pub fn new(
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+    ) -> MatchSpec {
+        MatchSpec {
+            match_type: MatchType::new(prefix, suffix, case_insensitive),
+            case_insensitive,
+        }
+    }
+
+    pub fn matches(&self, address: &str) -> bool {
+        self.match_type
+            .matches(address.as_bytes(), self.case_insensitive)
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.match_type.probability(self.case_insensitive)
+    }
+}
+
+/// Odds that one mint passes: the specs are independent, so `Both`
+/// multiplies them and `Either` is one minus both failing. A missing spec
+/// constrains nothing and is left out.
+pub fn joint_probability(
+    mint: Option<&MatchSpec>,
+    metadata: Option<&MatchSpec>,
+    requirement: JointRequirement,
+) -> f64 {
+    let given = [mint, metadata]
+        .into_iter()
+        .flatten()
+        .map(MatchSpec::probability);
+    match requirement {
+        JointRequirement::Both => given.product(),
+        // 1 - (1 - p1)(1 - p2), accurate for tiny p.
+        JointRequirement::Either => -float::exp_m1(given.map(|p| float::ln_1p(-p)).sum()),
+    }
+}
+
+/// A mint found by a [`MetadataSearcher`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct MetadataMatch {
+    /// Base58 of the mint's 64-byte keypair, as wallets import it.
+    pub mint_secret: String,
+    pub mint: String,
+    pub metadata: String,
+    pub metadata_bump: u8,
+    /// Which patterns matched; with [`JointRequirement::Either`] only one
+    /// may have.
+    pub mint_matches: bool,
+    pub metadata_matches: bool,
+    pub attempts: u64,
+    pub counter: u64,
+}
+
+/// Walks counters like [`crate::Searcher`], but each counter becomes a mint
+/// keypair (through [`secret_from_counter`]) and both the mint and its
+/// metadata PDA are matched. With [`JointRequirement::Both`] the PDA is
+/// only derived for mints that already match.
+#[derive(Clone)]
+pub struct MetadataSearcher {
+    program_id: [u8; PUBKEY_LEN],
+    master_secret: [u8; 32],
+    mint_spec: Option<MatchSpec>,
+    metadata_spec: Option<MatchSpec>,
+    requirement: JointRequirement,
+    count: u64,
+    count_offset: u64,
+}
+
+impl MetadataSearcher {
+    /// Empty specs count as absent; fails if both are, or if either can't
+    /// match. `master_secret` seeds every mint key: keep it as secret as the
+    /// keys themselves.
+    pub fn new(
+        program_id: &[u8; PUBKEY_LEN],
+        master_secret: &[u8; 32],
+        mint_spec: Option<MatchSpec>,
+        metadata_spec: Option<MatchSpec>,
+        requirement: JointRequirement,
+        count_offset: u64,
+    ) -> Result<MetadataSearcher, VanityError> {
+        let given = |spec: Option<MatchSpec>| spec.filter(|spec| !spec.match_type.is_empty());
+        let (mint_spec, metadata_spec) = (given(mint_spec), given(metadata_spec));
+        if mint_spec.is_none() && metadata_spec.is_none() {
+            return Err(VanityError::EmptyPattern);
+        }
+        for spec in mint_spec.iter().chain(&metadata_spec) {
+            spec.match_type.validate(spec.case_insensitive)?;
+        }
+        Ok(MetadataSearcher {
+            program_id: *program_id,
+            master_secret: *master_secret,
+            mint_spec,
+            metadata_spec,
+            requirement,
+            count: 0,
+            count_offset,
+        })
+    }
+
+    /// The derivation for one counter, matched or not.
+    pub fn derive(&self, counter: u64) -> MetadataMatch {
+        let secret = secret_from_counter(&self.master_secret, counter);
+        let mint = pubkey_from_secret(&secret);
+        let (metadata, metadata_bump) = metadata_address(&self.program_id, &mint);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let mint_secret = encode_keypair(&secret, &mint);
+        let mint = encode_pubkey(&mint, &mut buf).to_string();
+        let metadata = encode_pubkey(&metadata, &mut buf).to_string();
+        MetadataMatch {
+            mint_secret,
+            mint_matches: self
+                .mint_spec
+                .as_ref()
+                .is_some_and(|spec| spec.matches(&mint)),
+            metadata_matches: self
+                .metadata_spec
+                .as_ref()
+                .is_some_and(|spec| spec.matches(&metadata)),
+            mint,
+            metadata,
+            metadata_bump,
+            attempts: self.count,
+            counter,
+        }
+    }
+
+    /// Whether the mint for `counter` passes.
+    fn passes(&self, counter: u64, buf: &mut [u8; five8::BASE58_ENCODED_32_MAX_LEN]) -> bool {
+        let mint = pubkey_from_secret(&secret_from_counter(&self.master_secret, counter));
+        let mint_matches = self
+            .mint_spec
+            .as_ref()
+            .map(|spec| spec.matches(encode_pubkey(&mint, buf)));
+        match (self.requirement, mint_matches) {
+            (JointRequirement::Both, Some(false)) => return false,
+            (JointRequirement::Either, Some(true)) => return true,
+            _ => {}
+        }
+        let Some(spec) = &self.metadata_spec else {
+            return mint_matches == Some(true);
+        };
+        let (metadata, _) = metadata_address(&self.program_id, &mint);
+        spec.matches(encode_pubkey(&metadata, buf))
+    }
+
+    /// Tries up to `batch_size` more mints, stopping at the first that
+    /// passes or the end of the counter space.
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<MetadataMatch> {
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for _ in 0..batch_size {
+            let counter = self.absolute_position()?;
+            self.count += 1;
+            if self.passes(counter, &mut encoded_buf) {
+                return Some(self.derive(counter));
+            }
+        }
+        None
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// The next counter to try, or `None` once the counter space is used up.
+    pub fn absolute_position(&self) -> Option<u64> {
+        self.count_offset.checked_add(self.count)
+    }
+
+    /// Whether the counter space is used up.
+    pub fn is_exhausted(&self) -> bool {
+        self.absolute_position().is_none()
+    }
+
+    pub fn probability(&self) -> f64 {
+        joint_probability(
+            self.mint_spec.as_ref(),
+            self.metadata_spec.as_ref(),
+            self.requirement,
+        )
+    }
+
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.probability()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_pubkey::Pubkey;
+
+    fn program_id() -> [u8; PUBKEY_LEN] {
+        crate::Pubkey::from_base58(METADATA_PROGRAM_ID)
+            .unwrap()
+            .to_bytes()
+    }
+
+    fn spec(prefix: &str) -> Option<MatchSpec> {
+        Some(MatchSpec::new(Some(prefix.into()), None, false))
+    }
+
+    fn searcher(
+        mint: Option<MatchSpec>,
+        metadata: Option<MatchSpec>,
+        requirement: JointRequirement,
+    ) -> MetadataSearcher {
+        MetadataSearcher::new(&program_id(), &[6; 32], mint, metadata, requirement, 0).unwrap()
+    }
+
+    fn first_match(searcher: &mut MetadataSearcher) -> MetadataMatch {
+        loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                return found;
+            }
+        }
+    }
+
+    #[test]
+    fn test_metadata_address_matches_the_reference_derivation() {
+        let program = Pubkey::new_from_array(program_id());
+        for mint in [[1u8; 32], [77; 32]] {
+            let (expected, bump) =
+                Pubkey::find_program_address(&[b"metadata", program.as_ref(), &mint], &program);
+            assert_eq!(
+                metadata_address(&program_id(), &mint),
+                (expected.to_bytes(), bump)
+            );
+        }
+    }
+
+    #[test]
+    fn test_known_mint() {
+        let found = searcher(spec("A"), None, JointRequirement::Both).derive(0);
+        // Pinned, so a change to the key or seed scheme can't go unnoticed.
+        assert_eq!(found.mint, "dgYA8JMgCZvRdNuoDD363NBaJmmWJroxF5vhka7ZWr9");
+        assert_eq!(
+            found.metadata,
+            "GojNnun3mBo2JK9dqLgXy39TsVN25g49U2uVYT1Etez9"
+        );
+        assert_eq!(found.metadata_bump, 255);
+
+        let keypair = crate::decode_base58(&found.mint_secret).unwrap();
+        let secret: [u8; 32] = keypair[..32].try_into().unwrap();
+        assert_eq!(secret, secret_from_counter(&[6; 32], 0));
+        let mint = crate::Pubkey::from_base58(&found.mint).unwrap().to_bytes();
+        assert_eq!(pubkey_from_secret(&secret), mint);
+        assert_eq!(keypair[32..], mint);
+    }
+
+    #[test]
+    fn test_both_must_match() {
+        let mut both = searcher(spec("A"), spec("B"), JointRequirement::Both);
+        let found = first_match(&mut both);
+        assert!(found.mint.starts_with('A') && found.metadata.starts_with('B'));
+        assert!(found.mint_matches && found.metadata_matches);
+        assert_eq!(found.counter, found.attempts - 1);
+        assert!((0..found.counter).all(|counter| {
+            let derived = both.derive(counter);
+            !(derived.mint_matches && derived.metadata_matches)
+        }));
+    }
+
+    #[test]
+    fn test_either_may_match() {
+        let mut either = searcher(spec("AB"), spec("AB"), JointRequirement::Either);
+        let found = first_match(&mut either);
+        assert!(found.mint_matches || found.metadata_matches);
+        assert_eq!(found.mint_matches, found.mint.starts_with("AB"));
+        assert_eq!(found.metadata_matches, found.metadata.starts_with("AB"));
+        assert!((0..found.counter).all(|counter| {
+            let derived = either.derive(counter);
+            !(derived.mint_matches || derived.metadata_matches)
+        }));
+
+        // One side alone is a plain search on that side.
+        let mut metadata_only = searcher(None, spec("C"), JointRequirement::Both);
+        let found = first_match(&mut metadata_only);
+        assert!(found.metadata.starts_with('C') && !found.mint_matches);
+    }
+
+    #[test]
+    fn test_joint_probability() {
+        let a = spec("A").unwrap();
+        let b = spec("BC").unwrap();
+        let (pa, pb) = (a.probability(), b.probability());
+        let both = joint_probability(Some(&a), Some(&b), JointRequirement::Both);
+        assert_eq!(both, pa * pb);
+        let either = joint_probability(Some(&a), Some(&b), JointRequirement::Either);
+        assert!((either / (pa + pb - pa * pb) - 1.0).abs() < 1e-12);
+        let single = joint_probability(None, Some(&b), JointRequirement::Either);
+        assert!((single / pb - 1.0).abs() < 1e-12);
+        assert_eq!(
+            searcher(Some(a), Some(b), JointRequirement::Both).expected_attempts(),
+            1.0 / both
+        );
+    }
+
+    #[test]
+    fn test_specs_are_validated() {
+        let new = |mint, metadata| {
+            MetadataSearcher::new(
+                &program_id(),
+                &[6; 32],
+                mint,
+                metadata,
+                JointRequirement::Both,
+                0,
+            )
+            .err()
+        };
+        assert_eq!(new(None, spec("")), Some(VanityError::EmptyPattern));
+        assert_eq!(
+            new(spec("A"), spec("0")).map(|e| e.kind()),
+            Some(crate::ErrorKind::InvalidPatternChar)
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! One search loop over several independent jobs, generating each seed once.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::cancel::CancelToken;
+use crate::error::VanityError;
+use crate::search::{Searcher, INTERRUPT_POLL_INTERVAL};
+use crate::seed::{generate_seed_from_counter, SEED_LEN};
+
+/// A match found by a [`MultiSearcher`], tagged with its job.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiMatch {
+    /// Index returned by [`MultiSearcher::add_job`].
+    pub job: usize,
+    pub address: String,
+    pub seed: [u8; SEED_LEN],
+    /// Attempts the job had made, including this one.
+    pub attempts: u64,
+    /// Absolute counter the seed was generated from.
+    pub counter: u64,
+}
+
+/// How a [`MultiSearcher`] job's attempts are paying off, from
+/// [`MultiSearcher::pattern_stats`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct PatternStats {
+    pub label: String,
+    pub matches_found: u32,
+    /// Counters the job checked while it was running.
+    pub attempts: u64,
+    /// Per-attempt probability of a match, as the estimator puts it.
+    pub expected_probability: f64,
+    /// The job's attempts when it found its latest match; unset before the
+    /// first.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub attempts_at_last_match: Option<u64>,
+    /// Matches per attempt over `expected_probability`: around 1 when the
+    /// estimate holds, below 1 when matches are coming slower than
+    /// predicted. Unset before the first attempt.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub empirical_vs_expected_ratio: Option<f64>,
+}
+
+#[derive(Clone)]
+struct MultiJob {
+    searcher: Searcher,
+    label: String,
+    max_results: u32,
+    results: u32,
+    attempts: u64,
+    attempts_at_last_match: Option<u64>,
+}
+
+impl MultiJob {
+    fn is_done(&self) -> bool {
+        self.results >= self.max_results
+    }
+
+    fn stats(&self) -> PatternStats {
+        let expected_probability = self.searcher.match_probability();
+        PatternStats {
+            label: self.label.clone(),
+            matches_found: self.results,
+            attempts: self.attempts,
+            expected_probability,
+            attempts_at_last_match: self.attempts_at_last_match,
+            empirical_vs_expected_ratio: (self.attempts > 0)
+                .then(|| f64::from(self.results) / self.attempts as f64 / expected_probability),
+        }
+    }
+}
+
+/// Searches for several jobs at once, each with its own keys and pattern.
+/// Every counter's seed is generated once and checked against every job
+/// still running; a job drops out of the loop once it has its
+/// `max_results` matches.
+#[derive(Clone, Default)]
+pub struct MultiSearcher {
+    jobs: Vec<MultiJob>,
+    /// Jobs not done yet.
+    active: usize,
+    count: u64,
+    count_offset: u64,
+    should_exit: bool,
+    cancel_token: Option<CancelToken>,
+}
+
+impl MultiSearcher {
+    /// A searcher with no jobs whose counters start at `count_offset`.
+    pub fn new(count_offset: u64) -> MultiSearcher {
+        MultiSearcher {
+            count_offset,
+            ..MultiSearcher::default()
+        }
+    }
+
+    /// Adds a job matching as `searcher` would, and returns its index. Only
+    /// the searcher's keys and pattern are used: its position, stop state and
+    /// attempt cap are ignored. A job added mid-search starts at the current
+    /// counter. `max_results` is at least 1.
+    pub fn add_job(&mut self, searcher: Searcher, max_results: u32) -> usize {
+        let label = format!("job {}", self.jobs.len());
+        self.add_labeled_job(searcher, max_results, label)
+    }
+
+    /// As [`MultiSearcher::add_job`], naming the job in its
+    /// [`PatternStats`]; unlabelled jobs are "job 0", "job 1" and so on.
+    pub fn add_labeled_job(
+        &mut self,
+        searcher: Searcher,
+        max_results: u32,
+        label: String,
+    ) -> usize {
+        self.jobs.push(MultiJob {
+            searcher,
+            label,
+            max_results: max_results.max(1),
+            results: 0,
+            attempts: 0,
+            attempts_at_last_match: None,
+        });
+        self.active += 1;
+        self.jobs.len() - 1
+    }
+
+    pub fn search_batch(&mut self, batch_size: u32) -> Vec<MultiMatch> {
+        self.search_batch_until(batch_size, || false)
+    }
+
+    /// Runs up to `batch_size` counters through every running job and
+    /// returns the matches, in counter order. Ends early once every job is
+    /// done or the counter space is used up, and polls `interrupted` as
+    /// [`Searcher::search_batch_until`] does.
+    pub fn search_batch_until(
+        &mut self,
+        batch_size: u32,
+        mut interrupted: impl FnMut() -> bool,
+    ) -> Vec<MultiMatch> {
+        let mut found = Vec::new();
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for i in 0..batch_size {
+            if i % INTERRUPT_POLL_INTERVAL == 0
+                && !self.should_exit
+                && (self.is_cancelled() || interrupted())
+            {
+                self.stop();
+            }
+            if self.should_exit || self.active == 0 {
+                break;
+            }
+            let Some(counter) = self.count_offset.checked_add(self.count) else {
+                break;
+            };
+            let seed = generate_seed_from_counter(counter);
+            self.count += 1;
+
+            for (index, job) in self.jobs.iter_mut().enumerate() {
+                if job.is_done() {
+                    continue;
+                }
+                job.attempts += 1;
+                let matched = match job.searcher.match_seed(&seed, &mut encoded_buf) {
+                    Ok(found) => found,
+                    Err(error) => {
+                        // Kept in the job's searcher; see `MultiSearcher::fault`.
+                        job.searcher.record_fault(error);
+                        self.should_exit = true;
+                        break;
+                    }
+                };
+                if let Some(address) = matched {
+                    found.push(MultiMatch {
+                        job: index,
+                        address: address.to_string(),
+                        seed,
+                        attempts: job.attempts,
+                        counter,
+                    });
+                    job.results += 1;
+                    job.attempts_at_last_match = Some(job.attempts);
+                    if job.is_done() {
+                        self.active -= 1;
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Whether every job has all its results (trivially true with no jobs).
+    pub fn is_done(&self) -> bool {
+        self.active == 0
+    }
+
+    /// Counters checked, across all jobs.
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether the counter space is used up, whatever jobs are left.
+    pub fn is_exhausted(&self) -> bool {
+        self.count_offset.checked_add(self.count).is_none()
+    }
+
+    /// Counters the job checked while it was running.
+    pub fn job_attempts(&self, job: usize) -> Option<u64> {
+        self.jobs.get(job).map(|job| job.attempts)
+    }
+
+    /// Matches the job has found so far.
+    pub fn job_results(&self, job: usize) -> Option<u32> {
+        self.jobs.get(job).map(|job| job.results)
+    }
+
+    pub fn is_job_done(&self, job: usize) -> Option<bool> {
+        self.jobs.get(job).map(MultiJob::is_done)
+    }
+
+    /// Matches found against attempts made, one entry per job in job
+    /// order, e.g. to show which labels are paying off.
+    pub fn pattern_stats(&self) -> Vec<PatternStats> {
+        self.jobs.iter().map(MultiJob::stats).collect()
+    }
+
+    pub fn stop(&mut self) {
+        self.should_exit = true;
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.should_exit
+    }
+
+    /// As [`Searcher::set_cancel_token`], for the whole loop; the jobs'
+    /// own tokens are ignored.
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) {
+        self.cancel_token = token;
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// The first job's [`Searcher::fault`], if one stopped the search.
+    pub fn fault(&self) -> Option<&VanityError> {
+        self.jobs.iter().find_map(|job| job.searcher.fault())
+    }
+
+    /// Clears a stop, and any job's fault along with it.
+    pub fn restart(&mut self) {
+        self.should_exit = false;
+        for job in &mut self.jobs {
+            job.searcher.restart();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::{encode_pubkey, Deriver, MAX_ENCODED_LEN};
+    use crate::matcher::MatchType;
+
+    #[test]
+    fn test_jobs_complete_independently() {
+        let keys = [([1; 32], [4; 32]), ([2; 32], [3; 32])];
+        let mut multi = MultiSearcher::new(10);
+        let easy = Searcher::new(&keys[0].0, &keys[0].1, Some("A".into()), None, false, 0);
+        let hard = Searcher::new(&keys[1].0, &keys[1].1, None, Some("zz".into()), false, 0);
+        assert_eq!(multi.add_job(easy.unwrap(), 3), 0);
+        assert_eq!(multi.add_job(hard.unwrap(), 1), 1);
+
+        let mut found = Vec::new();
+        while !multi.is_done() {
+            found.extend(multi.search_batch(500));
+        }
+        assert_eq!(multi.search_batch(500), []);
+
+        let easy: Vec<_> = found.iter().filter(|m| m.job == 0).collect();
+        let hard: Vec<_> = found.iter().filter(|m| m.job == 1).collect();
+        assert_eq!((easy.len(), hard.len()), (3, 1));
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        for found in &found {
+            let (base, owner) = &keys[found.job];
+            let pubkey = Deriver::new(base, owner).derive(&found.seed);
+            assert_eq!(encode_pubkey(&pubkey, &mut buf), found.address);
+            assert_eq!(found.seed, generate_seed_from_counter(found.counter));
+        }
+        assert!(found
+            .iter()
+            .all(|m| m.job == 1 || m.address.starts_with('A')));
+        assert!(hard[0].address.ends_with("zz"));
+
+        // The easy job stopped counting when it finished; the hard one ran to
+        // the end.
+        let last_easy = easy[2];
+        assert_eq!(multi.job_attempts(0), Some(last_easy.attempts));
+        assert_eq!(last_easy.attempts, last_easy.counter - 10 + 1);
+        assert_eq!(multi.job_attempts(1), Some(multi.attempts()));
+        assert_eq!(hard[0].attempts, multi.attempts());
+        assert!(multi.job_attempts(0) < multi.job_attempts(1));
+        assert_eq!(multi.job_results(0), Some(3));
+        assert_eq!(multi.is_job_done(1), Some(true));
+        assert_eq!(multi.job_attempts(2), None);
+    }
+
+    #[test]
+    fn test_pattern_stats_follow_the_matches() {
+        let mut multi = MultiSearcher::new(0);
+        let job = |pattern: &str| {
+            Searcher::new(&[1; 32], &[4; 32], Some(pattern.into()), None, false, 0).unwrap()
+        };
+        multi.add_labeled_job(job("A"), 5, "a".into());
+        multi.add_labeled_job(job("B"), 1_000, "b".into());
+        multi.add_job(job("zzzz"), 1);
+        let stats = multi.pattern_stats();
+        assert_eq!(stats[2].label, "job 2");
+        assert!(stats
+            .iter()
+            .all(|s| s.attempts == 0 && s.empirical_vs_expected_ratio.is_none()));
+
+        let mut found = Vec::new();
+        for _ in 0..20 {
+            found.extend(multi.search_batch(1_000));
+        }
+        let stats = multi.pattern_stats();
+        for (job, stats) in stats.iter().enumerate().take(2) {
+            let matches: Vec<_> = found.iter().filter(|m| m.job == job).collect();
+            assert_eq!(
+                stats.matches_found as usize,
+                matches.len(),
+                "{}",
+                stats.label
+            );
+            assert_eq!(
+                stats.attempts_at_last_match,
+                matches.last().map(|m| m.attempts)
+            );
+            assert_eq!(Some(stats.attempts), multi.job_attempts(job));
+            let expected = MatchType::Prefix(["A", "B"][job].into()).probability(false);
+            assert_eq!(stats.expected_probability, expected);
+            let ratio = stats.matches_found as f64 / stats.attempts as f64 / expected;
+            assert_eq!(stats.empirical_vs_expected_ratio, Some(ratio));
+        }
+        // "a" stopped at its five matches; "b" ran long enough to be near
+        // its estimate.
+        assert_eq!(stats[0].matches_found, 5);
+        assert!(stats[0].attempts < multi.attempts());
+        assert!(stats[1].matches_found > 100);
+        let ratio = stats[1].empirical_vs_expected_ratio.unwrap();
+        assert!((0.5..2.0).contains(&ratio), "{ratio}");
+        assert_eq!(
+            (stats[2].matches_found, stats[2].attempts),
+            (0, multi.attempts())
+        );
+        assert_eq!(stats[2].empirical_vs_expected_ratio, Some(0.0));
+    }
+
+    #[test]
+    fn test_stop_and_empty() {
+        let mut multi = MultiSearcher::new(0);
+        assert!(multi.is_done());
+        assert_eq!(multi.search_batch(100), []);
+        assert_eq!(multi.attempts(), 0);
+
+        let job = Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0);
+        multi.add_job(job.unwrap(), 0);
+        assert!(!multi.is_done());
+        multi.stop();
+        assert_eq!(multi.search_batch(100), []);
+        assert_eq!(multi.attempts(), 0);
+        multi.restart();
+        multi.search_batch(100);
+        assert_eq!(multi.attempts(), 100);
+        assert_eq!(multi.job_attempts(0), Some(100));
+    }
+
+    #[test]
+    fn test_counter_space_runs_out() {
+        let mut multi = MultiSearcher::new(u64::MAX - 6);
+        let job = Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0);
+        multi.add_job(job.unwrap(), 1);
+        assert_eq!(multi.search_batch(100), []);
+        assert_eq!(multi.attempts(), 7);
+        assert!(multi.is_exhausted() && !multi.is_done());
+        assert_eq!(multi.search_batch(100), []);
+        assert_eq!(multi.attempts(), 7);
+    }
+}
@@ -0,0 +1,230 @@
+//! What a search learns on the way even when it ends without a match: the
+//! address that came closest and how often each part of the pattern
+//! nearly matched, so a stopped or exhausted search can still show "no
+//! luck, but here is the closest we got".
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::derive::{MAX_ENCODED_LEN, PUBKEY_LEN};
+use crate::matcher::{matched_chars, AddressMatcher, MatchPart};
+
+/// How one part of the pattern fared over the candidates tracked.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct PartStats {
+    pub part: MatchPart,
+    /// The pattern as matched, i.e. lowercased when case is ignored.
+    pub pattern: String,
+    /// Most characters of it any candidate had in place.
+    pub best_chars: u32,
+    /// Candidates that had all of it, whether or not the rest matched.
+    pub full: u64,
+    /// Candidates that had all but one of its characters; always 0 for a
+    /// one-character pattern.
+    pub near_misses: u64,
+}
+
+/// Counts kept by [`PartialTracker`], as of the last attempt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct PartialStats {
+    /// Candidates seen since tracking started.
+    pub tracked: u64,
+    /// Of those, the ones a prefilter (byte mask or suffix tail) didn't
+    /// rule out before encoding.
+    pub passed_prefilter: u64,
+    /// Matched characters of [`Searcher::closest_match`](crate::Searcher::closest_match),
+    /// summed over the parts; 0 before any candidate was tracked.
+    pub closest_chars: u32,
+    /// One entry per part of the pattern, the prefix first.
+    pub parts: Vec<PartStats>,
+}
+
+#[derive(Clone, Copy)]
+struct Closest {
+    address: [u8; MAX_ENCODED_LEN],
+    len: u8,
+    /// Physical counter, so the seed can be regenerated.
+    counter: u64,
+    attempts: u64,
+}
+
+/// Accumulates [`PartialStats`] and the closest address from every
+/// candidate the search loop encodes, copying an address only when the
+/// closest one improves (the first one on ties).
+#[derive(Clone)]
+pub struct PartialTracker {
+    stats: PartialStats,
+    closest: Option<Closest>,
+}
+
+impl PartialTracker {
+    pub(crate) fn new(matcher: &AddressMatcher) -> PartialTracker {
+        let parts = matcher
+            .match_type()
+            .parts()
+            .into_iter()
+            .flatten()
+            .map(|(part, pattern)| PartStats {
+                part,
+                pattern: pattern.to_string(),
+                best_chars: 0,
+                full: 0,
+                near_misses: 0,
+            })
+            .collect();
+        PartialTracker {
+            stats: PartialStats {
+                tracked: 0,
+                passed_prefilter: 0,
+                closest_chars: 0,
+                parts,
+            },
+            closest: None,
+        }
+    }
+
+    /// Records one candidate: `address` is its encoding, or `None` if a
+    /// byte mask ruled it out first. `attempts` includes this one.
+    #[inline]
+    pub(crate) fn observe(
+        &mut self,
+        matcher: &AddressMatcher,
+        pubkey: &[u8; PUBKEY_LEN],
+        address: Option<&str>,
+        counter: u64,
+        attempts: u64,
+    ) {
+        self.stats.tracked += 1;
+        let Some(address) = address else {
+            return;
+        };
+        if !matcher.rules_out(pubkey) {
+            self.stats.passed_prefilter += 1;
+        }
+        let mut total = 0;
+        for part in &mut self.stats.parts {
+            let matched = matched_chars(
+                part.part,
+                address.as_bytes(),
+                &part.pattern,
+                matcher.case_insensitive(),
+            );
+            let len = part.pattern.len();
+            part.best_chars = part.best_chars.max(matched as u32);
+            part.full += u64::from(matched == len);
+            part.near_misses += u64::from(len > 1 && matched + 1 == len);
+            total += matched as u32;
+        }
+        if self.closest.is_none() || total > self.stats.closest_chars {
+            let mut closest = Closest {
+                address: [0; MAX_ENCODED_LEN],
+                len: address.len() as u8,
+                counter,
+                attempts,
+            };
+            closest.address[..address.len()].copy_from_slice(address.as_bytes());
+            self.closest = Some(closest);
+            self.stats.closest_chars = total;
+        }
+    }
+
+    pub fn stats(&self) -> &PartialStats {
+        &self.stats
+    }
+
+    /// The closest address with its physical counter and the attempts made
+    /// when it was seen.
+    pub(crate) fn closest(&self) -> Option<(&str, u64, u64)> {
+        let closest = self.closest.as_ref()?;
+        let address = core::str::from_utf8(&closest.address[..closest.len as usize]).ok()?;
+        Some((address, closest.counter, closest.attempts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::{encode_pubkey, Deriver};
+    use crate::search::{BatchStatus, Searcher};
+    use crate::seed::generate_seed_from_counter;
+
+    #[test]
+    fn test_stopped_search_reports_what_it_tracked() {
+        let mut searcher = Searcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            Some("Moo".into()),
+            false,
+            500,
+        )
+        .unwrap();
+        assert_eq!(searcher.partial_stats(), None);
+        searcher.set_partial_tracking(true);
+        searcher.set_max_attempts(Some(6_000));
+        assert_eq!(searcher.search_batch(1_000), None);
+        // Stopped partway through a batch, then run out on the cap.
+        searcher.search_batch_until(1_000_000, || true);
+        assert_eq!(searcher.batch_status(false), BatchStatus::Stopped);
+        searcher.restart();
+        searcher.search_batch(1_000_000);
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+
+        // The same addresses, checked one by one.
+        let deriver = Deriver::new(&[1; 32], &[4; 32]);
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        let mut expected = [(0u32, 0u64, 0u64), (0, 0, 0)];
+        let mut closest = (0, None);
+        for attempt in 1..=6_000u64 {
+            let counter = 500 + attempt - 1;
+            let pubkey = deriver.derive(&generate_seed_from_counter(counter));
+            let address = encode_pubkey(&pubkey, &mut buf);
+            let checks = searcher.matcher().explain(address).checks;
+            let mut total = 0;
+            for (check, (best, full, near)) in checks.iter().zip(&mut expected) {
+                let matched = check.matched_chars.unwrap();
+                let len = check.pattern.as_ref().unwrap().len() as u32;
+                *best = (*best).max(matched);
+                *full += u64::from(matched == len);
+                *near += u64::from(matched + 1 == len);
+                total += matched;
+            }
+            if closest.1.is_none() || total > closest.0 {
+                closest = (total, Some((address.to_string(), counter, attempt)));
+            }
+        }
+
+        let stats = searcher.partial_stats().unwrap();
+        assert_eq!(stats.tracked, 6_000);
+        // The suffix is short enough for the prefilter to check in full.
+        assert_eq!(stats.passed_prefilter, expected[1].1);
+        assert_eq!(stats.closest_chars, closest.0);
+        let parts: Vec<_> = stats
+            .parts
+            .iter()
+            .map(|part| (part.best_chars, part.full, part.near_misses))
+            .collect();
+        assert_eq!(parts, expected);
+        assert_eq!(stats.parts[0].part, MatchPart::Prefix);
+        assert_eq!(stats.parts[1].pattern, "Moo");
+        assert!(expected[1].2 > 0);
+
+        let found = searcher.closest_match().unwrap();
+        let (address, counter, attempts) = closest.1.unwrap();
+        assert_eq!((&found.address, found.counter), (&address, counter));
+        assert_eq!(found.attempts, attempts);
+        let pubkey = deriver.derive(&found.seed);
+        assert_eq!(encode_pubkey(&pubkey, &mut buf), address);
+
+        // New keys start over; turning tracking off drops it.
+        searcher.set_keys(&[2; 32], &[4; 32], true);
+        assert_eq!(searcher.partial_stats().unwrap().tracked, 0);
+        assert_eq!(searcher.closest_match(), None);
+        searcher.set_partial_tracking(false);
+        assert_eq!(searcher.partial_stats(), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Passphrase seeds: `createWithSeed` seeds derived from a passphrase and
+//! the counter by HKDF, so they can be regenerated instead of stored.
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use crate::seed::{ALPHANUMERIC_CHARS, SEED_LEN};
+
+/// Version of the passphrase → seed mapping of [`seed_from_passphrase`].
+pub const PASSPHRASE_SEED_VERSION: u32 = 1;
+
+/// HKDF info string for passphrase seeds, so the same passphrase gives
+/// unrelated bytes in any other use of HKDF.
+const PASSPHRASE_SEED_INFO: &[u8] = b"web-vanity passphrase seed v1";
+
+const HMAC_BLOCK_LEN: usize = 64;
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; HMAC_BLOCK_LEN];
+    if key.len() > HMAC_BLOCK_LEN {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|b| b ^ byte);
+    let (mut ipad, mut opad) = (pad(0x36), pad(0x5c));
+    let mut inner: [u8; 32] = Sha256::new()
+        .chain_update(ipad)
+        .chain_update(message)
+        .finalize()
+        .into();
+    let mac = Sha256::new()
+        .chain_update(opad)
+        .chain_update(inner)
+        .finalize()
+        .into();
+    block.zeroize();
+    ipad.zeroize();
+    opad.zeroize();
+    inner.zeroize();
+    mac
+}
+
+/// HKDF-SHA256 (RFC 5869), output limited to one 32-byte block.
+fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8]) -> [u8; 32] {
+    let mut prk = hmac_sha256(salt, ikm);
+    let okm = hmac_sha256(&prk, &[info, &[1]].concat());
+    prk.zeroize();
+    okm
+}
+
+/// The seed for `counter` in passphrase mode: `HKDF-SHA256(ikm =
+/// passphrase, salt = counter as 8 little-endian bytes)`, its first 16
+/// bytes each mapped into [`ALPHANUMERIC_CHARS`]. Anyone with the
+/// passphrase and a result's counter can regenerate its seed, so nothing
+/// else needs storing; but the passphrase is then as good as the seed, and
+/// a guessable one can be brute-forced from the address.
+pub fn seed_from_passphrase(passphrase: &[u8], counter: u64) -> [u8; SEED_LEN] {
+    let mut okm = hkdf_sha256(&counter.to_le_bytes(), passphrase, PASSPHRASE_SEED_INFO);
+    let len = ALPHANUMERIC_CHARS.len();
+    let mut seed = [0u8; SEED_LEN];
+    for (c, byte) in seed.iter_mut().zip(okm) {
+        *c = ALPHANUMERIC_CHARS[byte as usize % len];
+    }
+    okm.zeroize();
+    seed
+}
+
+/// A hash of `passphrase` for resume-token fingerprints, unrelated to any
+/// seed it gives.
+pub(crate) fn passphrase_fingerprint(passphrase: &[u8]) -> [u8; 32] {
+    hkdf_sha256(&[], passphrase, b"web-vanity passphrase fingerprint v1")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::seed::generate_seed_from_counter;
+
+    #[test]
+    fn test_hkdf_matches_rfc_5869() {
+        // Test case 1, first 32 bytes of the output.
+        let ikm = [0x0b; 22];
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+        let okm = hkdf_sha256(&salt, &ikm, &info);
+        assert_eq!(
+            crate::trace::to_hex(&okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"
+        );
+        // Keys longer than a block are hashed first (RFC 4231 test case 6).
+        let mac = hmac_sha256(
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+        );
+        assert_eq!(
+            crate::trace::to_hex(&mac),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_passphrase_seeds() {
+        let seed = seed_from_passphrase(b"correct horse", 7);
+        assert!(seed.iter().all(|c| ALPHANUMERIC_CHARS.contains(c)));
+        assert_eq!(seed, seed_from_passphrase(b"correct horse", 7));
+        assert_ne!(seed, seed_from_passphrase(b"correct horse", 8));
+        assert_ne!(seed, seed_from_passphrase(b"correct horsf", 7));
+        assert_ne!(seed_from_passphrase(b"", 0), generate_seed_from_counter(0));
+    }
+}
@@ -0,0 +1,390 @@
+//! A line-oriented text format for lists of patterns, for users who keep
+//! theirs in a file and paste it in whole:
+//!
+//! ```text
+//! # team addresses
+//! prefix:Jup ci
+//! suffix:dao label=treasury
+//! prefix:So suffix:Ana
+//! contains:moon ci   # anywhere, any case
+//! ```
+//!
+//! A line holds a `prefix:` and/or a `suffix:` pattern, or one `contains:`
+//! pattern, then options: `ci` to ignore case and `label=NAME` to name the
+//! entry. Without a label an entry is named by its directives, e.g.
+//! "prefix:Jup ci". Labels are unique. `#` starts a comment; blank lines are
+//! skipped.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::VanityError;
+use crate::matcher::MatchType;
+
+/// One line of a pattern list.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct PatternEntry {
+    pub label: String,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub suffix: Option<String>,
+    /// Never set together with `prefix` or `suffix`.
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub contains: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub case_insensitive: bool,
+}
+
+impl PatternEntry {
+    /// The match spec to search with, folded if case is ignored.
+    pub fn match_type(&self) -> MatchType {
+        match &self.contains {
+            Some(pattern) => MatchType::contains(pattern.clone(), self.case_insensitive),
+            None => MatchType::new(
+                self.prefix.clone(),
+                self.suffix.clone(),
+                self.case_insensitive,
+            ),
+        }
+    }
+
+    /// The entry's directives and `ci`, as written in a list; also the label
+    /// of an entry that wasn't given one.
+    pub fn directives(&self) -> String {
+        let patterns = [
+            ("prefix", &self.prefix),
+            ("suffix", &self.suffix),
+            ("contains", &self.contains),
+        ];
+        let mut tokens: Vec<String> = patterns
+            .into_iter()
+            .filter_map(|(kind, pattern)| Some(format!("{kind}:{}", pattern.as_ref()?)))
+            .collect();
+        if self.case_insensitive {
+            tokens.push("ci".into());
+        }
+        tokens.join(" ")
+    }
+
+    /// Why the entry can't be searched for or written as a line, if it
+    /// can't.
+    fn problem(&self) -> Option<String> {
+        if self.contains.is_some() && (self.prefix.is_some() || self.suffix.is_some()) {
+            return Some("contains: can't be combined with prefix: or suffix:".into());
+        }
+        let match_type = self.match_type();
+        if match_type.is_empty() {
+            return Some("no pattern; expected prefix:, suffix: or contains:".into());
+        }
+        if let Err(error) = match_type.validate(self.case_insensitive) {
+            return Some(error.to_string());
+        }
+        if self.label.is_empty() {
+            return Some("label is empty".into());
+        }
+        // Default labels are never written out, so only a label given
+        // with label= has to survive being one.
+        let written = self.label != self.directives();
+        if written && self.label.contains(|c: char| c.is_whitespace() || c == '#') {
+            return Some(format!(
+                "label {:?} can't contain whitespace or '#'",
+                self.label
+            ));
+        }
+        None
+    }
+}
+
+fn parse_line(line: &str) -> Result<Option<PatternEntry>, String> {
+    let line = line.split('#').next().unwrap_or_default().trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let mut entry = PatternEntry::default();
+    let mut label = None;
+    for token in line.split_whitespace() {
+        if token == "ci" {
+            entry.case_insensitive = true;
+            continue;
+        }
+        if let Some(name) = token.strip_prefix("label=") {
+            if label.replace(name).is_some() {
+                return Err("label= given twice".into());
+            }
+            continue;
+        }
+        let Some((kind, pattern)) = token.split_once(':') else {
+            return Err(format!(
+                "unknown option {token:?}; expected ci or label=NAME"
+            ));
+        };
+        let slot = match kind {
+            "prefix" => &mut entry.prefix,
+            "suffix" => &mut entry.suffix,
+            "contains" => &mut entry.contains,
+            _ => {
+                return Err(format!(
+                    "unknown directive {kind:?}; expected prefix, suffix or contains"
+                ))
+            }
+        };
+        if pattern.is_empty() {
+            return Err(format!("{kind}: needs a pattern"));
+        }
+        if slot.replace(pattern.into()).is_some() {
+            return Err(format!("{kind}: given twice"));
+        }
+    }
+    entry.label = label.map_or_else(|| entry.directives(), String::from);
+    match entry.problem() {
+        Some(problem) => Err(problem),
+        None => Ok(Some(entry)),
+    }
+}
+
+/// Labels already seen, with the line they were first used on.
+#[derive(Default)]
+struct Labels<'a>(Vec<(&'a str, usize)>);
+
+impl<'a> Labels<'a> {
+    fn check(&mut self, label: &'a str, line: usize) -> Result<(), String> {
+        if let Some(&(_, first)) = self.0.iter().find(|&&(seen, _)| seen == label) {
+            return Err(format!(
+                "label {label:?} is already used on line {first}; add label=NAME"
+            ));
+        }
+        self.0.push((label, line));
+        Ok(())
+    }
+}
+
+/// Parses a pattern list (see the module docs). Every bad line is reported,
+/// each as `InvalidPatternList` with its 1-based line number; several come
+/// back as `Multiple`.
+pub fn parse_pattern_list(text: &str) -> Result<Vec<PatternEntry>, VanityError> {
+    let mut lines = Vec::new();
+    let mut problems = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        match parse_line(line) {
+            Ok(Some(entry)) => lines.push((index + 1, entry)),
+            Ok(None) => {}
+            Err(reason) => problems.push(VanityError::InvalidPatternList {
+                line: index + 1,
+                reason,
+            }),
+        }
+    }
+    let mut labels = Labels::default();
+    for (line, entry) in &lines {
+        if let Err(reason) = labels.check(&entry.label, *line) {
+            problems.push(VanityError::InvalidPatternList {
+                line: *line,
+                reason,
+            });
+        }
+    }
+    if let Some(error) = VanityError::from_problems(problems) {
+        return Err(error);
+    }
+    Ok(lines.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Checks entries that didn't come from [`parse_pattern_list`], e.g. UI
+/// state, as it would: `line` in the errors is the 1-based index.
+pub fn check_pattern_list(entries: &[PatternEntry]) -> Result<(), VanityError> {
+    let mut labels = Labels::default();
+    let problems = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let reason = entry
+                .problem()
+                .map_or_else(|| labels.check(&entry.label, index + 1), Err)
+                .err()?;
+            Some(VanityError::InvalidPatternList {
+                line: index + 1,
+                reason,
+            })
+        })
+        .collect();
+    match VanityError::from_problems(problems) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Writes entries as a pattern list, one line each, that
+/// [`parse_pattern_list`] reads back unchanged. A label is written only if
+/// it isn't the default. Fails as [`check_pattern_list`] does.
+pub fn format_pattern_list(entries: &[PatternEntry]) -> Result<String, VanityError> {
+    check_pattern_list(entries)?;
+    let mut text = String::new();
+    for entry in entries {
+        let directives = entry.directives();
+        text.push_str(&directives);
+        if entry.label != directives {
+            text.push_str(" label=");
+            text.push_str(&entry.label);
+        }
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn entry(label: &str, prefix: Option<&str>, suffix: Option<&str>) -> PatternEntry {
+        PatternEntry {
+            label: label.into(),
+            prefix: prefix.map(String::from),
+            suffix: suffix.map(String::from),
+            ..PatternEntry::default()
+        }
+    }
+
+    fn reasons(error: VanityError) -> Vec<String> {
+        match error {
+            VanityError::Multiple(errors) => errors.into_iter().flat_map(reasons).collect(),
+            error => vec![error.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_directives() {
+        let entries = parse_pattern_list(
+            "prefix:Jup\nsuffix:dao ci\ncontains:moon ci label=moon\nprefix:So suffix:Ana",
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            [
+                entry("prefix:Jup", Some("Jup"), None),
+                PatternEntry {
+                    case_insensitive: true,
+                    ..entry("suffix:dao ci", None, Some("dao"))
+                },
+                PatternEntry {
+                    label: "moon".into(),
+                    contains: Some("moon".into()),
+                    case_insensitive: true,
+                    ..PatternEntry::default()
+                },
+                entry("prefix:So suffix:Ana", Some("So"), Some("Ana")),
+            ]
+        );
+        assert_eq!(entries[1].match_type(), MatchType::Suffix("dao".into()));
+        assert_eq!(entries[2].match_type(), MatchType::Contains("moon".into()));
+        assert_eq!(
+            entries[3].match_type(),
+            MatchType::Both("So".into(), "Ana".into())
+        );
+    }
+
+    #[test]
+    fn test_bad_lines_are_reported_with_their_numbers() {
+        let text = "prefix:Jup\n\
+                    infix:moon\n\
+                    prefix:\n\
+                    suffix:a suffix:b\n\
+                    contains:moon prefix:A\n\
+                    ci\n\
+                    prefix:A fast\n\
+                    prefix:0x\n\
+                    prefix:B label=x label=y";
+        let error = parse_pattern_list(text).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Multiple);
+        assert_eq!(
+            reasons(error),
+            [
+                "line 2: unknown directive \"infix\"; expected prefix, suffix or contains",
+                "line 3: prefix: needs a pattern",
+                "line 4: suffix: given twice",
+                "line 5: contains: can't be combined with prefix: or suffix:",
+                "line 6: no pattern; expected prefix:, suffix: or contains:",
+                "line 7: unknown option \"fast\"; expected ci or label=NAME",
+                "line 8: pattern \"0x\" contains '0' at position 0, which is not in the \
+                 base58 alphabet",
+                "line 9: label= given twice",
+            ]
+        );
+
+        let error = parse_pattern_list("prefix:A\nprefix:0").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidPatternList);
+        assert_eq!(
+            error,
+            VanityError::InvalidPatternList {
+                line: 2,
+                reason: "pattern \"0\" contains '0' at position 0, which is not in the \
+                         base58 alphabet"
+                    .into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_duplicate_labels() {
+        let error = parse_pattern_list("prefix:A\nsuffix:B label=x\n\nprefix:A\nprefix:C label=x")
+            .unwrap_err();
+        assert_eq!(
+            reasons(error),
+            [
+                "line 4: label \"prefix:A\" is already used on line 1; add label=NAME",
+                "line 5: label \"x\" is already used on line 2; add label=NAME",
+            ]
+        );
+        // Case makes a different search, and a different default label.
+        assert_eq!(
+            parse_pattern_list("prefix:A\nprefix:A ci").unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_realistic_file_round_trips() {
+        let text = "\
+# Addresses for the team, most wanted first.
+
+prefix:Jup ci            # the app
+suffix:dao label=treasury
+   contains:moon ci
+prefix:So suffix:Ana     label=sol-ana
+# suffix:old  (retired)
+";
+        let entries = parse_pattern_list(text).unwrap();
+        let labels: Vec<_> = entries.iter().map(|entry| entry.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["prefix:Jup ci", "treasury", "contains:moon ci", "sol-ana"]
+        );
+        let formatted = format_pattern_list(&entries).unwrap();
+        assert_eq!(
+            formatted,
+            "prefix:Jup ci\nsuffix:dao label=treasury\ncontains:moon ci\n\
+             prefix:So suffix:Ana label=sol-ana\n"
+        );
+        assert_eq!(parse_pattern_list(&formatted).unwrap(), entries);
+
+        let unlabeled = [entry("", Some("A"), None)];
+        assert_eq!(
+            format_pattern_list(&unlabeled).unwrap_err().to_string(),
+            "line 1: label is empty"
+        );
+        let spaced = [entry("my key", Some("A"), None)];
+        assert!(format_pattern_list(&spaced).is_err());
+        assert_eq!(format_pattern_list(&[]).unwrap(), "");
+    }
+}
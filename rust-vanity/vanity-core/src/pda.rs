@@ -0,0 +1,82 @@
+//! Program derived addresses, computed as the Solana runtime does.
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::{Digest, Sha256};
+
+use crate::derive::PUBKEY_LEN;
+
+/// Suffix hashed after the seeds and program id.
+const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
+/// Whether `bytes` is a valid Ed25519 point, i.e. could have a secret key.
+/// Program addresses must not.
+pub fn is_on_curve(bytes: &[u8; PUBKEY_LEN]) -> bool {
+    CompressedEdwardsY(*bytes).decompress().is_some()
+}
+
+/// `create_program_address`: the address for `seeds` under `program_id`,
+/// or `None` if it lands on the curve. Seeds are taken as given; the
+/// runtime's limits (16 seeds of at most 32 bytes) are the caller's to
+/// respect.
+pub fn create_program_address(
+    seeds: &[&[u8]],
+    program_id: &[u8; PUBKEY_LEN],
+) -> Option<[u8; PUBKEY_LEN]> {
+    let mut hasher = Sha256::new();
+    for seed in seeds {
+        hasher.update(seed);
+    }
+    let address: [u8; PUBKEY_LEN] = hasher
+        .chain_update(program_id)
+        .chain_update(PDA_MARKER)
+        .finalize()
+        .into();
+    (!is_on_curve(&address)).then_some(address)
+}
+
+/// `find_program_address`: the address for `seeds` plus the highest bump
+/// byte that takes it off the curve, and that bump.
+pub fn find_program_address(
+    seeds: &[&[u8]],
+    program_id: &[u8; PUBKEY_LEN],
+) -> Option<([u8; PUBKEY_LEN], u8)> {
+    (0..=u8::MAX).rev().find_map(|bump| {
+        let bump_seed = [bump];
+        let mut with_bump = seeds.to_vec();
+        with_bump.push(&bump_seed);
+        create_program_address(&with_bump, program_id).map(|address| (address, bump))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_pubkey::Pubkey;
+
+    #[test]
+    fn test_matches_the_reference_derivation() {
+        let program_id = [9; 32];
+        for owner in [[1u8; 32], [2; 32], [200; 32]] {
+            let seeds: [&[u8]; 3] = [b"metadata", &program_id, &owner];
+            let (address, bump) = find_program_address(&seeds, &program_id).unwrap();
+            let (expected, expected_bump) =
+                Pubkey::find_program_address(&seeds, &Pubkey::new_from_array(program_id));
+            assert_eq!(address, expected.to_bytes());
+            assert_eq!(bump, expected_bump);
+            assert!(!is_on_curve(&address));
+        }
+    }
+
+    #[test]
+    fn test_on_curve_points_are_rejected() {
+        // The Ed25519 base point, compressed.
+        let mut base_point = [0x66; 32];
+        base_point[0] = 0x58;
+        assert!(is_on_curve(&base_point));
+        let program_id = [4; 32];
+        let seeds: [&[u8]; 2] = [b"seed", &[1]];
+        let created = create_program_address(&seeds, &program_id);
+        let expected = Pubkey::create_program_address(&seeds, &Pubkey::new_from_array(program_id));
+        assert_eq!(created, expected.ok().map(|pubkey| pubkey.to_bytes()));
+    }
+}
@@ -0,0 +1,188 @@
+//! Keyed bijection over the counter space: a contiguous run of logical
+//! counters maps to a scattered, still non-overlapping set of physical ones.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::VanityError;
+
+/// Longest [`SeedNamespace`] tag.
+pub const MAX_NAMESPACE_LEN: usize = 32;
+
+/// Feistel rounds; four make the halves depend on each other both ways.
+pub(crate) const ROUNDS: usize = 4;
+
+/// Added to the key before mixing each round key.
+pub(crate) const ROUND_KEY_STEP: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// splitmix64's finalizer constants: the shifts and the two multipliers
+/// between them.
+pub(crate) const MIX_SHIFTS: [u32; 3] = [30, 27, 31];
+pub(crate) const MIX_MULTIPLIERS: [u64; 2] = [0xbf58_476d_1ce4_e5b9, 0x94d0_49bb_1331_11eb];
+
+/// Hashed in front of a [`SeedNamespace`] tag to key its permutation.
+pub(crate) const NAMESPACE_DOMAIN: &[u8] = b"web-vanity seed namespace";
+
+/// splitmix64's finalizer.
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> MIX_SHIFTS[0])).wrapping_mul(MIX_MULTIPLIERS[0]);
+    x = (x ^ (x >> MIX_SHIFTS[1])).wrapping_mul(MIX_MULTIPLIERS[1]);
+    x ^ (x >> MIX_SHIFTS[2])
+}
+
+/// A permutation of `u64` chosen by a key, built as a balanced Feistel
+/// network over the two 32-bit halves, so it is a bijection whatever the
+/// round function. Not meant to be cryptographic: it only has to keep
+/// sessions with different keys from walking the same counters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CounterPermutation {
+    key: u64,
+    round_keys: [u64; ROUNDS],
+}
+
+impl CounterPermutation {
+    pub fn new(key: u64) -> CounterPermutation {
+        let mut state = key;
+        let round_keys = core::array::from_fn(|_| {
+            state = state.wrapping_add(ROUND_KEY_STEP);
+            mix(state)
+        });
+        CounterPermutation { key, round_keys }
+    }
+
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    fn round(&self, round: usize, half: u32) -> u32 {
+        (mix(half as u64 ^ self.round_keys[round]) >> 32) as u32
+    }
+
+    /// The physical counter for a logical one.
+    pub fn apply(&self, counter: u64) -> u64 {
+        let (mut left, mut right) = ((counter >> 32) as u32, counter as u32);
+        for round in 0..ROUNDS {
+            (left, right) = (right, left ^ self.round(round, right));
+        }
+        (left as u64) << 32 | right as u64
+    }
+
+    /// The logical counter `apply` maps to `physical`.
+    pub fn invert(&self, physical: u64) -> u64 {
+        let (mut left, mut right) = ((physical >> 32) as u32, physical as u32);
+        for round in (0..ROUNDS).rev() {
+            (left, right) = (right ^ self.round(round, left), left);
+        }
+        (left as u64) << 32 | right as u64
+    }
+}
+
+/// A session tag folded into the seed material: counter `c` is derived as
+/// counter `mix(tag, c)`, a bijection keyed by a hash of the tag. Sessions
+/// with different tags walk statistically disjoint seeds even from the same
+/// counters, without any record of what the others covered. Unlike a
+/// [`CounterPermutation`], results keep reporting the unmixed counter, so
+/// reproducing one takes the tag as well.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeedNamespace {
+    tag: String,
+    permutation: CounterPermutation,
+}
+
+impl SeedNamespace {
+    /// Tags are 1 to [`MAX_NAMESPACE_LEN`] ASCII letters, digits, `-` or
+    /// `_`, so they fit in resume tokens as they are.
+    pub fn new(tag: &str) -> Result<SeedNamespace, VanityError> {
+        if tag.is_empty() || tag.len() > MAX_NAMESPACE_LEN {
+            return Err(VanityError::InvalidOptions(format!(
+                "seed namespace must be 1 to {MAX_NAMESPACE_LEN} characters, got {}",
+                tag.len()
+            )));
+        }
+        if let Some(bad) = tag
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_')
+        {
+            return Err(VanityError::InvalidOptions(format!(
+                "seed namespace may only contain letters, digits, '-' and '_', got {bad:?}"
+            )));
+        }
+        let digest = Sha256::new()
+            .chain_update(NAMESPACE_DOMAIN)
+            .chain_update(tag.as_bytes())
+            .finalize();
+        let key = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        Ok(SeedNamespace {
+            tag: tag.to_string(),
+            permutation: CounterPermutation::new(key),
+        })
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The counter seeds are actually derived from.
+    pub fn apply(&self, counter: u64) -> u64 {
+        self.permutation.apply(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_permutation_is_a_bijection() {
+        let permutation = CounterPermutation::new(0x5eed);
+        let logical = (0..50_000)
+            .chain((1 << 32) - 100..(1 << 32) + 100)
+            .chain(u64::MAX - 1_000..=u64::MAX);
+        let mut seen = HashSet::new();
+        for counter in logical {
+            let physical = permutation.apply(counter);
+            assert!(seen.insert(physical), "{counter} collides");
+            assert_eq!(permutation.invert(physical), counter);
+        }
+        // Consecutive logical counters don't stay together.
+        let low = (0..1_000)
+            .filter(|&c| permutation.apply(c) < 1 << 40)
+            .count();
+        assert!(low < 5, "{low} of 1000 stayed low");
+    }
+
+    #[test]
+    fn test_keys_give_unrelated_sequences() {
+        let a = CounterPermutation::new(1);
+        let b = CounterPermutation::new(2);
+        assert_ne!(a, b);
+        let first: HashSet<_> = (0..20_000).map(|c| a.apply(c)).collect();
+        assert!((0..20_000).all(|c| !first.contains(&b.apply(c))));
+        assert_eq!(CounterPermutation::new(1), a);
+        assert_eq!(a.key(), 1);
+    }
+
+    #[test]
+    fn test_namespaces_are_keyed_by_their_tag() {
+        let a = SeedNamespace::new("session-a").unwrap();
+        let b = SeedNamespace::new("session_b").unwrap();
+        assert_eq!(a, SeedNamespace::new("session-a").unwrap());
+        assert_eq!(a.tag(), "session-a");
+        let first: HashSet<_> = (0..20_000).map(|c| a.apply(c)).collect();
+        assert_eq!(first.len(), 20_000);
+        assert!((0..20_000).all(|c| !first.contains(&b.apply(c))));
+
+        for bad in [
+            "",
+            "has space",
+            "dot.ted",
+            &"x".repeat(MAX_NAMESPACE_LEN + 1),
+        ] {
+            let error = SeedNamespace::new(bad).unwrap_err();
+            assert_eq!(error.kind(), crate::ErrorKind::InvalidOptions, "{bad:?}");
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Turning a measured hash rate into batch size and worker recommendations.
+
+use serde::Serialize;
+
+use crate::float;
+
+const MINUTE_MS: f64 = 60_000.0;
+const HOUR_MS: f64 = 60.0 * MINUTE_MS;
+const DAY_MS: f64 = 24.0 * HOUR_MS;
+const YEAR_MS: f64 = 365.0 * DAY_MS;
+
+/// Bounds on the recommended batch size: small enough batches waste time
+/// crossing the wasm boundary, huge ones make `stop()` unresponsive.
+pub const MIN_BATCH_SIZE: u32 = 1_000;
+pub const MAX_BATCH_SIZE: u32 = 10_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// Under a minute.
+    Trivial,
+    Minutes,
+    Hours,
+    Days,
+    /// More than a year, or impossible.
+    Infeasible,
+}
+
+impl Difficulty {
+    pub fn from_expected_ms(expected_ms: Option<f64>) -> Difficulty {
+        match expected_ms {
+            Some(ms) if ms < MINUTE_MS => Difficulty::Trivial,
+            Some(ms) if ms < HOUR_MS => Difficulty::Minutes,
+            Some(ms) if ms < DAY_MS => Difficulty::Hours,
+            Some(ms) if ms < YEAR_MS => Difficulty::Days,
+            _ => Difficulty::Infeasible,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct SearchPlan {
+    /// Attempts per second of a single worker, as measured.
+    pub measured_rate: f64,
+    pub batch_size: u32,
+    pub workers: u32,
+    pub expected_attempts: Option<f64>,
+    /// Expected time to a match with all recommended workers running.
+    pub expected_wall_ms: Option<f64>,
+    pub difficulty: Difficulty,
+}
+
+/// Plans a search from a single worker's measured rate. One hardware thread
+/// is left for the page itself when more than one is available.
+pub fn plan_search(
+    measured_rate: f64,
+    hardware_concurrency: u32,
+    match_probability: f64,
+    target_batch_ms: f64,
+) -> SearchPlan {
+    let workers = hardware_concurrency.saturating_sub(1).max(1);
+    let batch_size = float::round(measured_rate * target_batch_ms / 1000.0)
+        .clamp(MIN_BATCH_SIZE as f64, MAX_BATCH_SIZE as f64) as u32;
+
+    let expected_attempts = (match_probability > 0.0).then(|| 1.0 / match_probability);
+    let total_rate = measured_rate * workers as f64;
+    let expected_wall_ms = expected_attempts
+        .filter(|_| total_rate > 0.0)
+        .map(|attempts| attempts / total_rate * 1000.0)
+        .filter(|ms| ms.is_finite());
+
+    SearchPlan {
+        measured_rate,
+        batch_size,
+        workers,
+        expected_attempts,
+        expected_wall_ms,
+        difficulty: Difficulty::from_expected_ms(expected_wall_ms),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_size_hits_latency_target() {
+        let plan = plan_search(200_000.0, 8, 1.0 / 58.0, 250.0);
+        assert_eq!(plan.batch_size, 50_000);
+        assert_eq!(plan.workers, 7);
+    }
+
+    #[test]
+    fn test_batch_size_is_clamped() {
+        assert_eq!(plan_search(10.0, 4, 0.5, 100.0).batch_size, MIN_BATCH_SIZE);
+        assert_eq!(plan_search(1e12, 4, 0.5, 1000.0).batch_size, MAX_BATCH_SIZE);
+    }
+
+    #[test]
+    fn test_single_core_keeps_one_worker() {
+        assert_eq!(plan_search(1000.0, 1, 0.5, 100.0).workers, 1);
+        assert_eq!(plan_search(1000.0, 0, 0.5, 100.0).workers, 1);
+    }
+
+    #[test]
+    fn test_wall_time_and_difficulty() {
+        // 1e6 expected attempts at 4 workers * 100k/s = 2.5s.
+        let plan = plan_search(100_000.0, 5, 1e-6, 100.0);
+        assert_eq!(plan.expected_attempts, Some(1e6));
+        assert_eq!(plan.expected_wall_ms, Some(2_500.0));
+        assert_eq!(plan.difficulty, Difficulty::Trivial);
+
+        let plan = plan_search(100_000.0, 5, 1e-9, 100.0);
+        assert_eq!(plan.difficulty, Difficulty::Minutes);
+        let plan = plan_search(100_000.0, 5, 1e-10, 100.0);
+        assert_eq!(plan.difficulty, Difficulty::Hours);
+        let plan = plan_search(100_000.0, 5, 1e-12, 100.0);
+        assert_eq!(plan.difficulty, Difficulty::Days);
+        let plan = plan_search(100_000.0, 5, 1e-14, 100.0);
+        assert_eq!(plan.difficulty, Difficulty::Infeasible);
+    }
+
+    #[test]
+    fn test_impossible_pattern_is_infeasible() {
+        let plan = plan_search(100_000.0, 4, 0.0, 100.0);
+        assert_eq!(plan.expected_attempts, None);
+        assert_eq!(plan.expected_wall_ms, None);
+        assert_eq!(plan.difficulty, Difficulty::Infeasible);
+    }
+}
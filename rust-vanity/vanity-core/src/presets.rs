@@ -0,0 +1,173 @@
+//! Built-in patterns for popular vanity targets.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::derive::PUBKEY_LEN;
+use crate::error::VanityError;
+use crate::matcher::MatchType;
+use crate::plan::Difficulty;
+use crate::search::Searcher;
+
+/// Attempts per second the preset difficulties are computed for: roughly
+/// one browser worker.
+pub const PRESET_REFERENCE_RATE: f64 = 100_000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Preset {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub prefix: Option<&'static str>,
+    pub suffix: Option<&'static str>,
+    pub case_insensitive: bool,
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        id: "pump",
+        label: "Ends in \"pump\"",
+        prefix: None,
+        suffix: Some("pump"),
+        case_insensitive: false,
+    },
+    Preset {
+        id: "bonk",
+        label: "Ends in \"bonk\"",
+        prefix: None,
+        suffix: Some("bonk"),
+        case_insensitive: false,
+    },
+    Preset {
+        id: "dao",
+        label: "Starts with \"dao\", any case",
+        prefix: Some("dao"),
+        suffix: None,
+        case_insensitive: true,
+    },
+    Preset {
+        id: "moon",
+        label: "Starts with \"moon\", any case",
+        prefix: Some("moon"),
+        suffix: None,
+        case_insensitive: true,
+    },
+    // Base58 has no capital O, so "SOL" can only match case-insensitively.
+    Preset {
+        id: "sol",
+        label: "Starts with \"SOL\", any case",
+        prefix: Some("SOL"),
+        suffix: None,
+        case_insensitive: true,
+    },
+];
+
+/// A preset as listed for a UI, with its odds.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct PresetInfo {
+    pub id: String,
+    pub label: String,
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub prefix: Option<String>,
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub suffix: Option<String>,
+    pub case_insensitive: bool,
+    pub expected_attempts: f64,
+    /// Classification at [`PRESET_REFERENCE_RATE`].
+    pub difficulty: Difficulty,
+}
+
+impl Preset {
+    pub fn match_type(&self) -> MatchType {
+        MatchType::new(
+            self.prefix.map(Into::into),
+            self.suffix.map(Into::into),
+            self.case_insensitive,
+        )
+    }
+
+    pub fn searcher(
+        &self,
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        count_offset: u64,
+    ) -> Result<Searcher, VanityError> {
+        Searcher::new(
+            base_pubkey,
+            owner_pubkey,
+            self.prefix.map(Into::into),
+            self.suffix.map(Into::into),
+            self.case_insensitive,
+            count_offset,
+        )
+    }
+
+    pub fn info(&self) -> PresetInfo {
+        let expected_attempts = 1.0 / self.match_type().probability(self.case_insensitive);
+        let expected_ms = expected_attempts / PRESET_REFERENCE_RATE * 1000.0;
+        PresetInfo {
+            id: self.id.into(),
+            label: self.label.into(),
+            prefix: self.prefix.map(Into::into),
+            suffix: self.suffix.map(Into::into),
+            case_insensitive: self.case_insensitive,
+            expected_attempts,
+            difficulty: Difficulty::from_expected_ms(Some(expected_ms).filter(|ms| ms.is_finite())),
+        }
+    }
+}
+
+/// Looks a preset up by id.
+pub fn preset(id: &str) -> Result<&'static Preset, VanityError> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.id == id)
+        .ok_or_else(|| VanityError::InvalidOptions(format!("unknown preset {id:?}")))
+}
+
+pub fn presets() -> Vec<PresetInfo> {
+    PRESETS.iter().map(Preset::info).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_validate_and_build() {
+        for preset in PRESETS {
+            let match_type = preset.match_type();
+            assert!(!match_type.is_empty(), "{}", preset.id);
+            assert_eq!(match_type.validate(preset.case_insensitive), Ok(()));
+            assert!(
+                preset.searcher(&[1; 32], &[4; 32], 0).is_ok(),
+                "{}",
+                preset.id
+            );
+            assert!(preset.info().expected_attempts.is_finite());
+        }
+        let mut ids: Vec<_> = PRESETS.iter().map(|preset| preset.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), PRESETS.len());
+    }
+
+    #[test]
+    fn test_info_uses_the_matcher_odds() {
+        let info = preset("pump").unwrap().info();
+        assert_eq!(info.expected_attempts, 58f64.powi(4));
+        assert_eq!(info.difficulty, Difficulty::Minutes);
+        let sol = preset("sol").unwrap();
+        assert_eq!(
+            sol.info().expected_attempts,
+            1.0 / sol.match_type().probability(true)
+        );
+        assert_eq!(
+            preset("nope").unwrap_err().kind(),
+            crate::ErrorKind::InvalidOptions
+        );
+    }
+}
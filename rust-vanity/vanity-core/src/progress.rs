@@ -0,0 +1,783 @@
+//! Batch timing statistics and progress snapshots, one worker's or
+//! several workers' together.
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::VanityError;
+use crate::estimate::probability_found;
+use crate::float;
+
+fn finite(value: f64) -> Option<f64> {
+    value.is_finite().then_some(value)
+}
+
+/// Default time constant of the smoothed rate.
+pub const DEFAULT_RATE_WINDOW_MS: f64 = 5_000.0;
+
+/// Default width of a [`RateHistory`] bucket: one second of active time.
+pub const DEFAULT_RATE_HISTORY_RESOLUTION_MS: f64 = 1_000.0;
+
+/// Default number of [`RateHistory`] buckets: five minutes at one per second.
+pub const DEFAULT_RATE_HISTORY_BUCKETS: u32 = 300;
+
+/// Most buckets a [`RateHistory`] may keep.
+pub const MAX_RATE_HISTORY_BUCKETS: u32 = 86_400;
+
+/// Attempts per second over consecutive, equally long stretches of active
+/// time, the most recent `buckets` of them, e.g. for a sparkline. Like
+/// [`SearchStats`], it only sees time inside batches, so a pause adds no
+/// buckets: the ones either side of it are simply adjacent. A batch's
+/// attempts are spread evenly over its duration, so one spanning several
+/// buckets fills each at its rate.
+#[derive(Clone, Debug)]
+pub struct RateHistory {
+    resolution_ms: f64,
+    buckets: u32,
+    /// Rates of completed buckets, oldest first.
+    rates: VecDeque<f64>,
+    /// Attempts and active time in the bucket being filled.
+    current_attempts: f64,
+    current_ms: f64,
+}
+
+impl Default for RateHistory {
+    fn default() -> RateHistory {
+        RateHistory {
+            resolution_ms: DEFAULT_RATE_HISTORY_RESOLUTION_MS,
+            buckets: DEFAULT_RATE_HISTORY_BUCKETS,
+            rates: VecDeque::new(),
+            current_attempts: 0.0,
+            current_ms: 0.0,
+        }
+    }
+}
+
+impl RateHistory {
+    /// Fails with `InvalidOptions` unless `resolution_ms` is positive and
+    /// finite and `buckets` is 1 to [`MAX_RATE_HISTORY_BUCKETS`].
+    pub fn new(resolution_ms: f64, buckets: u32) -> Result<RateHistory, VanityError> {
+        if !(resolution_ms.is_finite() && resolution_ms > 0.0) {
+            return Err(VanityError::InvalidOptions(format!(
+                "rate history resolution must be a positive number of milliseconds, got {resolution_ms}"
+            )));
+        }
+        if !(1..=MAX_RATE_HISTORY_BUCKETS).contains(&buckets) {
+            return Err(VanityError::InvalidOptions(format!(
+                "rate history must have 1 to {MAX_RATE_HISTORY_BUCKETS} buckets, got {buckets}"
+            )));
+        }
+        Ok(RateHistory {
+            resolution_ms,
+            buckets,
+            ..RateHistory::default()
+        })
+    }
+
+    pub fn record_batch(&mut self, attempts: u64, elapsed_ms: f64) {
+        if !(elapsed_ms > 0.0 && elapsed_ms.is_finite()) {
+            self.current_attempts += attempts as f64;
+            return;
+        }
+        let per_ms = attempts as f64 / elapsed_ms;
+        let room_ms = self.resolution_ms - self.current_ms;
+        if elapsed_ms < room_ms {
+            self.current_attempts += per_ms * elapsed_ms;
+            self.current_ms += elapsed_ms;
+            return;
+        }
+        self.push((self.current_attempts + per_ms * room_ms) * 1000.0 / self.resolution_ms);
+        let remaining_ms = elapsed_ms - room_ms;
+        let whole = (remaining_ms / self.resolution_ms) as u64;
+        // Only the last `buckets` whole ones could be kept anyway.
+        for _ in 0..whole.min(self.buckets as u64) {
+            self.push(per_ms * 1000.0);
+        }
+        self.current_ms = (remaining_ms - whole as f64 * self.resolution_ms).max(0.0);
+        self.current_attempts = per_ms * self.current_ms;
+    }
+
+    fn push(&mut self, rate: f64) {
+        if self.rates.len() == self.buckets as usize {
+            self.rates.pop_front();
+        }
+        self.rates.push_back(rate);
+    }
+
+    /// Attempts per second of each completed bucket, oldest first; the one
+    /// being filled isn't included until it's complete.
+    pub fn rates(&self) -> Vec<f64> {
+        self.rates.iter().copied().collect()
+    }
+
+    /// Forgets every bucket, keeping the resolution and length.
+    pub fn clear(&mut self) {
+        *self = RateHistory {
+            rates: VecDeque::new(),
+            current_attempts: 0.0,
+            current_ms: 0.0,
+            ..*self
+        };
+    }
+
+    pub fn resolution_ms(&self) -> f64 {
+        self.resolution_ms
+    }
+
+    pub fn buckets(&self) -> u32 {
+        self.buckets
+    }
+}
+
+/// Active search time and per-batch throughput, fed by whoever owns the clock.
+/// Only time spent inside batches is recorded, so gaps while the searcher is
+/// stopped or the worker is idle never dilute the rates.
+#[derive(Clone, Debug)]
+pub struct SearchStats {
+    active_ms: f64,
+    last_batch_attempts: u64,
+    last_batch_ms: f64,
+    rate_window_ms: f64,
+    smoothed_rate: Option<f64>,
+    history: RateHistory,
+}
+
+impl Default for SearchStats {
+    fn default() -> SearchStats {
+        SearchStats::with_rate_window(DEFAULT_RATE_WINDOW_MS)
+    }
+}
+
+impl SearchStats {
+    /// Stats whose smoothed rate forgets old batches with a time constant of
+    /// `rate_window_ms` of active time.
+    pub fn with_rate_window(rate_window_ms: f64) -> SearchStats {
+        SearchStats {
+            active_ms: 0.0,
+            last_batch_attempts: 0,
+            last_batch_ms: 0.0,
+            rate_window_ms,
+            smoothed_rate: None,
+            history: RateHistory::default(),
+        }
+    }
+
+    pub fn record_batch(&mut self, attempts: u64, elapsed_ms: f64) {
+        self.history.record_batch(attempts, elapsed_ms);
+        self.last_batch_attempts = attempts;
+        self.last_batch_ms = elapsed_ms;
+        self.active_ms += elapsed_ms;
+
+        // Weight each batch by its duration, so one long batch counts as much
+        // as many short ones covering the same time.
+        let Some(batch_rate) = (elapsed_ms > 0.0)
+            .then(|| attempts as f64 * 1000.0 / elapsed_ms)
+            .and_then(finite)
+        else {
+            return;
+        };
+        self.smoothed_rate = Some(match self.smoothed_rate {
+            None => batch_rate,
+            Some(rate) => {
+                let alpha = -float::exp_m1(-elapsed_ms / self.rate_window_ms);
+                rate + alpha * (batch_rate - rate)
+            }
+        });
+    }
+
+    pub fn active_ms(&self) -> f64 {
+        self.active_ms
+    }
+
+    /// Exponential moving average of attempts per second, or `None` before
+    /// the first timed batch.
+    pub fn rate(&self) -> Option<f64> {
+        self.smoothed_rate
+    }
+
+    pub fn rate_window_ms(&self) -> f64 {
+        self.rate_window_ms
+    }
+
+    /// Rates over the recent past, bucketed; see [`RateHistory`].
+    pub fn rate_history(&self) -> &RateHistory {
+        &self.history
+    }
+
+    pub fn rate_history_mut(&mut self) -> &mut RateHistory {
+        &mut self.history
+    }
+
+    /// Replaces the history, e.g. with a different resolution.
+    pub fn set_rate_history(&mut self, history: RateHistory) {
+        self.history = history;
+    }
+
+    pub fn snapshot(&self, attempts: u64, match_probability: f64) -> ProgressSnapshot {
+        let expected_attempts = finite(1.0 / match_probability);
+        let instant_rate = (self.last_batch_ms > 0.0)
+            .then(|| self.last_batch_attempts as f64 * 1000.0 / self.last_batch_ms)
+            .and_then(finite);
+        let lifetime_rate = (self.active_ms > 0.0)
+            .then(|| attempts as f64 * 1000.0 / self.active_ms)
+            .and_then(finite);
+        let eta_ms = match (expected_attempts, self.smoothed_rate) {
+            (Some(expected), Some(rate)) if rate > 0.0 => finite(expected / rate * 1000.0),
+            _ => None,
+        };
+
+        ProgressSnapshot {
+            attempts,
+            elapsed_ms: self.active_ms,
+            instant_rate,
+            rate: self.smoothed_rate,
+            lifetime_rate,
+            expected_attempts,
+            eta_ms,
+            probability_found: probability_found(match_probability, attempts),
+            stop_at_probability: None,
+            seed_space_coverage: None,
+        }
+    }
+}
+
+/// Point-in-time view of a searcher's progress, serialized for postMessage.
+/// Values that cannot be computed yet (or are unbounded) are `None`, which
+/// reaches JS as `undefined`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct ProgressSnapshot {
+    pub attempts: u64,
+    pub elapsed_ms: f64,
+    /// Attempts per second over the most recent batch.
+    pub instant_rate: Option<f64>,
+    /// Attempts per second, smoothed over recent batches.
+    pub rate: Option<f64>,
+    /// Attempts per second over all active time.
+    pub lifetime_rate: Option<f64>,
+    pub expected_attempts: Option<f64>,
+    /// Expected time to a match at the smoothed rate. The search is
+    /// memoryless, so this does not shrink as attempts accumulate.
+    pub eta_ms: Option<f64>,
+    pub probability_found: f64,
+    /// The `probability_found` at which the searcher gives up, if it was
+    /// given one, to show e.g. "coverage 63% of 95%".
+    pub stop_at_probability: Option<f64>,
+    /// Fraction of the seeds tried, when there are fewer seeds than
+    /// counters; the search ends at 1.
+    pub seed_space_coverage: Option<f64>,
+}
+
+/// Default time after which [`ProgressAggregator`] stops counting a silent
+/// worker's rate.
+pub const DEFAULT_STALE_AFTER_MS: f64 = 10_000.0;
+
+/// What [`ProgressAggregator`] knows of one worker.
+#[derive(Clone, Debug)]
+struct WorkerTotals {
+    id: u32,
+    /// Attempts made before the worker last restarted.
+    earlier_attempts: u64,
+    last_attempts: u64,
+    last_update_ms: f64,
+    restarts: u32,
+    /// Total attempts and time of the last rate sample.
+    sample: (u64, f64),
+    rate: Option<f64>,
+}
+
+impl WorkerTotals {
+    fn attempts(&self) -> u64 {
+        self.earlier_attempts.saturating_add(self.last_attempts)
+    }
+}
+
+/// Combines the [`ProgressSnapshot`]s several workers post into totals for
+/// the whole search, on the main thread's clock:
+///
+/// - each worker's rate is smoothed from the attempts between its updates,
+///   as [`SearchStats`] smooths batches, so the combined rate and ETA don't
+///   jump with every message;
+/// - a worker whose attempts go down has restarted: what it made before is
+///   kept, so totals never go backwards;
+/// - a worker silent for longer than `stale_after_ms` no longer counts
+///   towards the rate, only its attempts do.
+#[derive(Clone, Debug)]
+pub struct ProgressAggregator {
+    match_probability: f64,
+    rate_window_ms: f64,
+    stale_after_ms: f64,
+    workers: Vec<WorkerTotals>,
+}
+
+/// One worker, as [`ProgressAggregator::snapshot`] reports it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct WorkerProgress {
+    pub worker_id: u32,
+    /// Attempts since the worker first reported, across restarts.
+    pub attempts: u64,
+    pub rate: Option<f64>,
+    /// Time since the worker's last update.
+    pub stale_ms: f64,
+    pub stale: bool,
+    pub restarts: u32,
+}
+
+/// Progress of a search split across workers, serialized for rendering.
+/// Values that cannot be computed yet are `None`, as in
+/// [`ProgressSnapshot`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct AggregateProgress {
+    pub attempts: u64,
+    /// Smoothed attempts per second of the workers that aren't stale.
+    pub rate: Option<f64>,
+    pub expected_attempts: Option<f64>,
+    /// Expected time to a match at the combined rate; like a single
+    /// worker's, it does not shrink as attempts accumulate.
+    pub eta_ms: Option<f64>,
+    pub probability_found: f64,
+    /// Ordered by `worker_id`.
+    pub workers: Vec<WorkerProgress>,
+}
+
+impl ProgressAggregator {
+    /// Totals for a search whose pattern matches each attempt with
+    /// probability `match_probability`.
+    pub fn new(match_probability: f64) -> ProgressAggregator {
+        ProgressAggregator::with_windows(
+            match_probability,
+            DEFAULT_RATE_WINDOW_MS,
+            DEFAULT_STALE_AFTER_MS,
+        )
+    }
+
+    pub fn with_windows(
+        match_probability: f64,
+        rate_window_ms: f64,
+        stale_after_ms: f64,
+    ) -> ProgressAggregator {
+        ProgressAggregator {
+            match_probability,
+            rate_window_ms,
+            stale_after_ms,
+            workers: Vec::new(),
+        }
+    }
+
+    /// Takes `progress`, posted by worker `worker_id`, received at `now_ms`.
+    pub fn update(&mut self, worker_id: u32, progress: &ProgressSnapshot, now_ms: f64) {
+        let Some(worker) = self.workers.iter_mut().find(|w| w.id == worker_id) else {
+            // The worker's own smoothed rate stands in until a second update.
+            let position = self.workers.partition_point(|w| w.id < worker_id);
+            self.workers.insert(
+                position,
+                WorkerTotals {
+                    id: worker_id,
+                    earlier_attempts: 0,
+                    last_attempts: progress.attempts,
+                    last_update_ms: now_ms,
+                    restarts: 0,
+                    sample: (progress.attempts, now_ms),
+                    rate: progress.rate.and_then(finite),
+                },
+            );
+            return;
+        };
+        if progress.attempts < worker.last_attempts {
+            worker.earlier_attempts = worker.attempts();
+            worker.restarts += 1;
+        }
+        worker.last_attempts = progress.attempts;
+        worker.last_update_ms = worker.last_update_ms.max(now_ms);
+
+        let (sample_attempts, sample_ms) = worker.sample;
+        let elapsed_ms = now_ms - sample_ms;
+        if elapsed_ms <= 0.0 {
+            return;
+        }
+        let attempts = worker.attempts();
+        worker.sample = (attempts, now_ms);
+        let Some(sample_rate) = finite((attempts - sample_attempts) as f64 * 1000.0 / elapsed_ms)
+        else {
+            return;
+        };
+        worker.rate = Some(match worker.rate {
+            None => sample_rate,
+            Some(rate) => {
+                let alpha = -float::exp_m1(-elapsed_ms / self.rate_window_ms);
+                rate + alpha * (sample_rate - rate)
+            }
+        });
+    }
+
+    /// Attempts of every worker, across restarts.
+    pub fn attempts(&self) -> u64 {
+        self.workers
+            .iter()
+            .fold(0, |total, w| total.saturating_add(w.attempts()))
+    }
+
+    /// Combined smoothed rate of the workers heard from within
+    /// `stale_after_ms` of `now_ms`, or `None` if none has a rate.
+    pub fn rate(&self, now_ms: f64) -> Option<f64> {
+        self.workers
+            .iter()
+            .filter(|w| now_ms - w.last_update_ms <= self.stale_after_ms)
+            .filter_map(|w| w.rate)
+            .reduce(|a, b| a + b)
+    }
+
+    /// Time since `worker_id` last reported, or `None` for a worker never
+    /// heard from.
+    pub fn stale_ms(&self, worker_id: u32, now_ms: f64) -> Option<f64> {
+        self.workers
+            .iter()
+            .find(|w| w.id == worker_id)
+            .map(|w| (now_ms - w.last_update_ms).max(0.0))
+    }
+
+    pub fn snapshot(&self, now_ms: f64) -> AggregateProgress {
+        let attempts = self.attempts();
+        let rate = self.rate(now_ms);
+        let expected_attempts = finite(1.0 / self.match_probability);
+        let eta_ms = match (expected_attempts, rate) {
+            (Some(expected), Some(rate)) if rate > 0.0 => finite(expected / rate * 1000.0),
+            _ => None,
+        };
+        AggregateProgress {
+            attempts,
+            rate,
+            expected_attempts,
+            eta_ms,
+            probability_found: probability_found(self.match_probability, attempts),
+            workers: self
+                .workers
+                .iter()
+                .map(|w| {
+                    let stale_ms = (now_ms - w.last_update_ms).max(0.0);
+                    WorkerProgress {
+                        worker_id: w.id,
+                        attempts: w.attempts(),
+                        rate: w.rate,
+                        stale_ms,
+                        stale: stale_ms > self.stale_after_ms,
+                        restarts: w.restarts,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Schedules a time-based heartbeat from active search time, so beats stop
+/// while the searcher is idle or stopped.
+#[derive(Clone, Debug)]
+pub struct Heartbeat {
+    interval_ms: f64,
+    next_at_ms: f64,
+    sequence: u64,
+}
+
+impl Heartbeat {
+    /// First beat once `interval_ms` more active time than `active_ms` has
+    /// passed. Negative or NaN intervals count as zero.
+    pub fn new(interval_ms: f64, active_ms: f64) -> Heartbeat {
+        let interval_ms = interval_ms.max(0.0);
+        Heartbeat {
+            interval_ms,
+            next_at_ms: active_ms + interval_ms,
+            sequence: 0,
+        }
+    }
+
+    /// The sequence number of the beat due at `active_ms`, if any, counting
+    /// from 0. The next beat is due a full interval later.
+    pub fn beat(&mut self, active_ms: f64) -> Option<u64> {
+        if active_ms < self.next_at_ms {
+            return None;
+        }
+        self.next_at_ms = active_ms + self.interval_ms;
+        self.sequence += 1;
+        Some(self.sequence - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_before_first_batch() {
+        let snapshot = SearchStats::default().snapshot(0, 1.0 / 58.0);
+        assert_eq!(snapshot.attempts, 0);
+        assert_eq!(snapshot.instant_rate, None);
+        assert_eq!(snapshot.rate, None);
+        assert_eq!(snapshot.lifetime_rate, None);
+        assert_eq!(snapshot.eta_ms, None);
+        assert_eq!(snapshot.probability_found, 0.0);
+    }
+
+    #[test]
+    fn test_snapshot_rates() {
+        let mut stats = SearchStats::default();
+        stats.record_batch(1_000, 100.0);
+        stats.record_batch(1_000, 400.0);
+
+        let snapshot = stats.snapshot(2_000, 1e-4);
+        assert_eq!(snapshot.elapsed_ms, 500.0);
+        assert_eq!(snapshot.instant_rate, Some(2_500.0));
+        assert_eq!(snapshot.lifetime_rate, Some(4_000.0));
+        assert_eq!(snapshot.expected_attempts, Some(10_000.0));
+        let rate = snapshot.rate.unwrap();
+        assert!(rate > 2_500.0 && rate < 10_000.0);
+        assert_eq!(snapshot.eta_ms, Some(10_000.0 / rate * 1000.0));
+        assert!(snapshot.probability_found > 0.0 && snapshot.probability_found < 1.0);
+    }
+
+    #[test]
+    fn test_snapshot_impossible_pattern() {
+        let mut stats = SearchStats::default();
+        stats.record_batch(100, 1.0);
+
+        let snapshot = stats.snapshot(100, 0.0);
+        assert_eq!(snapshot.expected_attempts, None);
+        assert_eq!(snapshot.eta_ms, None);
+        assert_eq!(snapshot.probability_found, 0.0);
+    }
+
+    #[test]
+    fn test_snapshot_extreme_values_stay_finite() {
+        let mut stats = SearchStats::default();
+        stats.record_batch(u64::MAX, f64::MIN_POSITIVE);
+
+        let snapshot = stats.snapshot(u64::MAX, 1e-300);
+        assert!(snapshot.instant_rate.is_none_or(f64::is_finite));
+        assert!(snapshot.eta_ms.is_none_or(f64::is_finite));
+        assert!(snapshot.probability_found.is_finite());
+    }
+
+    fn posted(attempts: u64) -> ProgressSnapshot {
+        ProgressSnapshot {
+            attempts,
+            ..SearchStats::default().snapshot(attempts, 1e-6)
+        }
+    }
+
+    #[test]
+    fn test_aggregator_combines_workers() {
+        let mut totals = ProgressAggregator::with_windows(1e-6, 1_000.0, 2_000.0);
+        assert_eq!(totals.rate(0.0), None);
+        assert_eq!(totals.snapshot(0.0).eta_ms, None);
+
+        // Three workers at 1k, 2k and 4k attempts/s reporting every 100 ms,
+        // the fastest restarting halfway.
+        let rates = [1_000, 2_000, 4_000];
+        let mut last_total = 0;
+        for step in 0..=100u64 {
+            let now = step as f64 * 100.0;
+            for (id, rate) in rates.into_iter().enumerate() {
+                let attempts = match id {
+                    2 if step >= 50 => (step - 50) * rate / 10,
+                    _ => step * rate / 10,
+                };
+                totals.update(id as u32, &posted(attempts), now);
+            }
+            let total = totals.attempts();
+            assert!(total >= last_total, "went back at step {step}");
+            last_total = total;
+        }
+        let snapshot = totals.snapshot(10_000.0);
+        // The restarted worker keeps its 19 600 attempts from before; the
+        // 400 it made between its last two updates are lost.
+        assert_eq!(snapshot.attempts, 10_000 + 20_000 + 39_600);
+        assert_eq!(snapshot.workers[2].restarts, 1);
+        assert_eq!(snapshot.workers[2].attempts, 39_600);
+        assert_eq!(snapshot.workers[0].restarts, 0);
+        for (worker, rate) in snapshot.workers.iter().zip(rates) {
+            assert!(
+                (worker.rate.unwrap() / rate as f64 - 1.0).abs() < 0.01,
+                "{worker:?}"
+            );
+            assert_eq!(worker.stale_ms, 0.0);
+        }
+        let rate = snapshot.rate.unwrap();
+        assert!((rate / 7_000.0 - 1.0).abs() < 0.01);
+        assert_eq!(snapshot.expected_attempts, Some(1e6));
+        assert_eq!(snapshot.eta_ms, Some(1e6 / rate * 1000.0));
+        assert_eq!(snapshot.probability_found, probability_found(1e-6, 69_600));
+
+        // The slowest goes quiet: its attempts stay, its rate drops out.
+        for step in 101..=130u64 {
+            let now = step as f64 * 100.0;
+            totals.update(1, &posted(step * 200), now);
+            totals.update(2, &posted((step - 50) * 400), now);
+        }
+        let snapshot = totals.snapshot(13_000.0);
+        assert!(snapshot.workers[0].stale);
+        assert_eq!(snapshot.workers[0].stale_ms, 3_000.0);
+        assert_eq!(totals.stale_ms(0, 13_000.0), Some(3_000.0));
+        assert_eq!(totals.stale_ms(7, 13_000.0), None);
+        assert!((snapshot.rate.unwrap() / 6_000.0 - 1.0).abs() < 0.01);
+        assert_eq!(snapshot.attempts, 10_000 + 26_000 + 51_600);
+    }
+
+    #[test]
+    fn test_aggregator_first_update_uses_the_workers_rate() {
+        let mut totals = ProgressAggregator::new(1.0 / 58.0);
+        let mut stats = SearchStats::default();
+        stats.record_batch(500, 100.0);
+        totals.update(3, &stats.snapshot(500, 1.0 / 58.0), 50.0);
+        // A repeat at the same time changes nothing.
+        totals.update(3, &stats.snapshot(500, 1.0 / 58.0), 50.0);
+        assert_eq!(totals.rate(50.0), Some(5_000.0));
+        assert_eq!(totals.attempts(), 500);
+        assert_eq!(totals.snapshot(50.0).workers[0].worker_id, 3);
+    }
+
+    /// Replays batches as (attempts, start ms, end ms) timestamps, the way
+    /// a caller sampling a clock at batch boundaries would.
+    fn replay(stats: &mut SearchStats, batches: &[(u64, f64, f64)]) {
+        for &(attempts, start_ms, end_ms) in batches {
+            stats.record_batch(attempts, end_ms - start_ms);
+        }
+    }
+
+    #[test]
+    fn test_rate_starts_at_first_batch() {
+        let mut stats = SearchStats::default();
+        assert_eq!(stats.rate(), None);
+        stats.record_batch(0, 0.0);
+        assert_eq!(stats.rate(), None);
+        replay(&mut stats, &[(500, 10.0, 110.0)]);
+        assert_eq!(stats.rate(), Some(5_000.0));
+    }
+
+    #[test]
+    fn test_rate_follows_a_step_change() {
+        let mut stats = SearchStats::with_rate_window(1_000.0);
+        replay(&mut stats, &[(1_000, 0.0, 1_000.0)]);
+
+        // One window at double speed covers 1 - 1/e of the gap...
+        let mut now = 1_000.0;
+        for _ in 0..10 {
+            replay(&mut stats, &[(200, now, now + 100.0)]);
+            now += 100.0;
+        }
+        let expected = 2_000.0 - 1_000.0 * (-1.0f64).exp();
+        assert!((stats.rate().unwrap() - expected).abs() < 1e-6);
+
+        // ...and a few more windows all but close it.
+        for _ in 0..50 {
+            replay(&mut stats, &[(200, now, now + 100.0)]);
+            now += 100.0;
+        }
+        assert!((stats.rate().unwrap() - 2_000.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_rate_is_weighted_by_batch_duration() {
+        let mut short = SearchStats::with_rate_window(1_000.0);
+        let mut long = short.clone();
+        replay(&mut short, &[(1_000, 0.0, 1_000.0), (50, 1_000.0, 1_001.0)]);
+        replay(
+            &mut long,
+            &[(1_000, 0.0, 1_000.0), (50_000, 1_000.0, 2_000.0)],
+        );
+
+        // A 1ms blip at 50x speed barely moves the rate; a full window does.
+        assert!(short.rate().unwrap() < 1_100.0);
+        assert!(long.rate().unwrap() > 30_000.0);
+    }
+
+    #[test]
+    fn test_idle_time_is_excluded() {
+        let mut stats = SearchStats::default();
+        // A minute passes between the two batches while the searcher is
+        // stopped; only the time inside batches is recorded.
+        replay(
+            &mut stats,
+            &[(1_000, 0.0, 100.0), (1_000, 60_100.0, 60_200.0)],
+        );
+        assert_eq!(stats.rate(), Some(10_000.0));
+        assert_eq!(stats.active_ms(), 200.0);
+    }
+
+    #[test]
+    fn test_rate_history_buckets() {
+        let mut history = RateHistory::new(1_000.0, 4).unwrap();
+        // 400ms at 1/ms, then 800ms at 2/ms: the first bucket closes
+        // 600ms into the second batch.
+        history.record_batch(400, 400.0);
+        assert_eq!(history.rates(), [0.0; 0]);
+        history.record_batch(1_600, 800.0);
+        assert_eq!(history.rates(), [400.0 + 1_200.0]);
+
+        // A pause between batches adds no bucket, and one exactly filling
+        // the rest of a bucket closes it.
+        history.record_batch(800, 800.0);
+        assert_eq!(history.rates(), [1_600.0, 1_200.0]);
+        history.record_batch(0, 0.0);
+        assert_eq!(history.rates(), [1_600.0, 1_200.0]);
+
+        // A long batch fills whole buckets at its rate and wraps around.
+        history.record_batch(35_000, 3_500.0);
+        assert_eq!(history.rates(), [1_200.0, 10_000.0, 10_000.0, 10_000.0]);
+        history.record_batch(1_000, 500.0);
+        assert_eq!(
+            history.rates(),
+            [10_000.0; 3]
+                .into_iter()
+                .chain([6_000.0])
+                .collect::<Vec<_>>()
+        );
+        history.record_batch(u64::MAX, 1e12);
+        assert_eq!(history.rates().len(), 4);
+
+        history.clear();
+        assert_eq!(history.rates(), [0.0; 0]);
+        assert_eq!((history.resolution_ms(), history.buckets()), (1_000.0, 4));
+        for (resolution_ms, buckets) in [(0.0, 4), (f64::NAN, 4), (1_000.0, 0)] {
+            assert!(RateHistory::new(resolution_ms, buckets).is_err());
+        }
+        assert!(RateHistory::new(1.0, MAX_RATE_HISTORY_BUCKETS + 1).is_err());
+    }
+
+    #[test]
+    fn test_search_stats_keep_a_rate_history() {
+        let mut stats = SearchStats::default();
+        replay(
+            &mut stats,
+            &[(500, 0.0, 500.0), (3_000, 60_000.0, 61_500.0)],
+        );
+        // The minute between the batches isn't a bucket.
+        assert_eq!(stats.rate_history().rates(), [500.0 + 1_000.0, 2_000.0]);
+        stats.set_rate_history(RateHistory::new(250.0, 10).unwrap());
+        stats.record_batch(100, 500.0);
+        assert_eq!(stats.rate_history().rates(), [200.0, 200.0]);
+        stats.rate_history_mut().clear();
+        assert_eq!(stats.rate_history().rates(), [0.0; 0]);
+    }
+
+    #[test]
+    fn test_heartbeat_cadence() {
+        let mut heartbeat = Heartbeat::new(100.0, 50.0);
+        // A mock clock polled every 30 ms of active time.
+        let beats: Vec<(u64, u64)> = (0..20)
+            .filter_map(|i| {
+                let now = 50.0 + 30.0 * i as f64;
+                heartbeat.beat(now).map(|sequence| (sequence, i))
+            })
+            .collect();
+        // Due at 150, 270, 390, ... i.e. every fourth poll after the first.
+        assert_eq!(beats, [(0, 4), (1, 8), (2, 12), (3, 16)]);
+
+        let mut heartbeat = Heartbeat::new(f64::NAN, 0.0);
+        assert_eq!(heartbeat.beat(0.0), Some(0));
+        assert_eq!(heartbeat.beat(0.0), Some(1));
+    }
+}
@@ -0,0 +1,185 @@
+//! A 32-byte public key, checked once where it comes in.
+
+use alloc::string::ToString;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::derive::{decode_base58, encode_pubkey, MAX_ENCODED_LEN, PUBKEY_LEN};
+use crate::error::VanityError;
+
+/// A Solana public key. Raw bytes and base58 are both parsed and
+/// length-checked here, so code holding a `Pubkey` never re-checks it.
+/// Displays as base58.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pubkey([u8; PUBKEY_LEN]);
+
+/// Which key of a derivation a [`Pubkey`] is, so parse errors can say so.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyRole {
+    Base,
+    Owner,
+}
+
+impl Pubkey {
+    pub const fn new(bytes: [u8; PUBKEY_LEN]) -> Pubkey {
+        Pubkey(bytes)
+    }
+
+    /// Fails with `BadPubkeyLength` unless `bytes` is exactly 32 bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Pubkey, VanityError> {
+        bytes
+            .try_into()
+            .map(Pubkey)
+            .map_err(|_| VanityError::BadPubkeyLength(bytes.len()))
+    }
+
+    /// Fails with `InvalidBase58` (with its position) at the first character
+    /// outside the alphabet, or with `BadPubkeyLength` if `encoded` doesn't
+    /// decode to exactly 32 bytes.
+    pub fn from_base58(encoded: &str) -> Result<Pubkey, VanityError> {
+        Pubkey::from_bytes(&decode_base58(encoded)?)
+    }
+
+    /// Like [`Pubkey::from_bytes`], but a wrong length is reported as
+    /// `BadBaseLength` or `BadOwnerLength`.
+    pub fn for_role(bytes: &[u8], role: KeyRole) -> Result<Pubkey, VanityError> {
+        Pubkey::from_bytes(bytes).map_err(|_| match role {
+            KeyRole::Base => VanityError::BadBaseLength(bytes.len()),
+            KeyRole::Owner => VanityError::BadOwnerLength(bytes.len()),
+        })
+    }
+
+    /// Like [`Pubkey::from_base58`], but any failure is reported as
+    /// `InvalidBase` or `InvalidOwner`.
+    pub fn from_base58_for_role(encoded: &str, role: KeyRole) -> Result<Pubkey, VanityError> {
+        Pubkey::from_base58(encoded).map_err(|error| match role {
+            KeyRole::Base => VanityError::InvalidBase(error.to_string()),
+            KeyRole::Owner => VanityError::InvalidOwner(error.to_string()),
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8; PUBKEY_LEN] {
+        &self.0
+    }
+
+    pub fn to_bytes(self) -> [u8; PUBKEY_LEN] {
+        self.0
+    }
+}
+
+impl From<[u8; PUBKEY_LEN]> for Pubkey {
+    fn from(bytes: [u8; PUBKEY_LEN]) -> Pubkey {
+        Pubkey(bytes)
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = VanityError;
+
+    fn from_str(encoded: &str) -> Result<Pubkey, VanityError> {
+        Pubkey::from_base58(encoded)
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        f.write_str(encode_pubkey(&self.0, &mut buf))
+    }
+}
+
+impl fmt::Debug for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pubkey({self})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    #[test]
+    fn test_base58_round_trips() {
+        let mut keys = vec![[0; PUBKEY_LEN], [255; PUBKEY_LEN]];
+        // Pseudo-random keys, 1 to 3 leading zero bytes included.
+        for i in 0u32..300 {
+            let mut key: [u8; PUBKEY_LEN] = Sha256::digest(i.to_le_bytes()).into();
+            key[..(i % 4) as usize].fill(0);
+            keys.push(key);
+        }
+        for key in keys {
+            let pubkey = Pubkey::new(key);
+            let encoded = pubkey.to_string();
+            assert_eq!(Pubkey::from_base58(&encoded), Ok(pubkey), "{encoded}");
+            assert_eq!(encoded.parse(), Ok(pubkey));
+            assert_eq!(Pubkey::from_bytes(&key), Ok(pubkey));
+            assert_eq!(pubkey.as_bytes(), &key);
+        }
+        assert_eq!(
+            Pubkey::default().to_string(),
+            "11111111111111111111111111111111"
+        );
+        assert_eq!(
+            format!("{:?}", Pubkey::new([0; PUBKEY_LEN])),
+            "Pubkey(11111111111111111111111111111111)"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (position, character) in ['0', 'O', 'I', 'l'].into_iter().enumerate() {
+            let encoded = format!("{}{character}zz", "A".repeat(position));
+            assert_eq!(
+                Pubkey::from_base58(&encoded),
+                Err(VanityError::InvalidBase58 {
+                    character,
+                    position
+                })
+            );
+        }
+        assert_eq!(
+            Pubkey::from_base58("abc"),
+            Err(VanityError::BadPubkeyLength(3))
+        );
+        let long = format!("{}z", Pubkey::new([255; PUBKEY_LEN]));
+        assert_eq!(
+            Pubkey::from_base58(&long),
+            Err(VanityError::BadPubkeyLength(33))
+        );
+        assert_eq!(
+            Pubkey::from_bytes(&[1; 31]),
+            Err(VanityError::BadPubkeyLength(31))
+        );
+        // Base58 text passed as bytes is caught by its length.
+        let text = Pubkey::new([7; PUBKEY_LEN]).to_string();
+        assert_eq!(
+            Pubkey::from_bytes(text.as_bytes()),
+            Err(VanityError::BadPubkeyLength(text.len()))
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_key() {
+        assert_eq!(
+            Pubkey::for_role(&[1; 31], KeyRole::Base),
+            Err(VanityError::BadBaseLength(31))
+        );
+        assert_eq!(
+            Pubkey::for_role(&[1; 33], KeyRole::Owner),
+            Err(VanityError::BadOwnerLength(33))
+        );
+        assert_eq!(
+            Pubkey::for_role(&[1; 32], KeyRole::Owner),
+            Ok(Pubkey::new([1; 32]))
+        );
+        let error = Pubkey::from_base58_for_role("S0L", KeyRole::Base).unwrap_err();
+        assert_eq!(error.kind(), crate::ErrorKind::InvalidBase);
+        assert!(error.to_string().contains("'0'"), "{error}");
+        let error = Pubkey::from_base58_for_role("abc", KeyRole::Owner).unwrap_err();
+        assert_eq!(error.kind(), crate::ErrorKind::InvalidOwner);
+    }
+}
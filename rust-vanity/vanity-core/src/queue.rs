@@ -0,0 +1,204 @@
+//! Several searches run one after another, in the order they were queued.
+
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::search::{SearchMatch, Searcher};
+
+/// Where a queued job stands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Not finished yet; the first pending job is the current one.
+    Pending,
+    Found,
+    /// Hit its attempt cap or ran out of counters without a match.
+    Exhausted,
+    Skipped,
+}
+
+#[derive(Clone)]
+struct Job {
+    searcher: Searcher,
+    status: JobStatus,
+    result: Option<SearchMatch>,
+}
+
+/// Jobs worked on one at a time. Each keeps its own searcher, so its own
+/// counter space, attempt cap and result; the queue moves on to the next
+/// job as soon as the current one finds a match, is exhausted or skipped.
+#[derive(Clone, Default)]
+pub struct JobQueue {
+    jobs: Vec<Job>,
+    /// Index of the current job; equal to `jobs.len()` once all are done.
+    current: usize,
+}
+
+impl JobQueue {
+    pub fn new() -> JobQueue {
+        JobQueue::default()
+    }
+
+    /// Queues a job after the existing ones and returns its index.
+    pub fn push(&mut self, searcher: Searcher) -> usize {
+        self.jobs.push(Job {
+            searcher,
+            status: JobStatus::Pending,
+            result: None,
+        });
+        self.jobs.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Index of the job `run_batch` works on, or `None` once every job is
+    /// done.
+    pub fn current_job(&self) -> Option<usize> {
+        (self.current < self.jobs.len()).then_some(self.current)
+    }
+
+    /// Jobs that are no longer pending.
+    pub fn finished(&self) -> usize {
+        self.current
+    }
+
+    pub fn status(&self, index: usize) -> Option<JobStatus> {
+        self.jobs.get(index).map(|job| job.status)
+    }
+
+    pub fn searcher(&self, index: usize) -> Option<&Searcher> {
+        self.jobs.get(index).map(|job| &job.searcher)
+    }
+
+    /// Matches found so far, with the index of the job that found each.
+    pub fn results(&self) -> impl Iterator<Item = (usize, &SearchMatch)> {
+        self.jobs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, job)| job.result.as_ref().map(|found| (index, found)))
+    }
+
+    /// Attempts made by every job together.
+    pub fn total_attempts(&self) -> u64 {
+        self.jobs.iter().map(|job| job.searcher.attempts()).sum()
+    }
+
+    /// Gives up on the current job and returns its index, or `None` if
+    /// there is nothing left to skip.
+    pub fn skip(&mut self) -> Option<usize> {
+        let index = self.current_job()?;
+        self.finish(JobStatus::Skipped);
+        Some(index)
+    }
+
+    /// Runs up to `batch_size` attempts, starting with the current job. If
+    /// that job is exhausted part-way, the rest of the batch goes to the
+    /// next one; a match ends the batch early and is returned with its job's
+    /// index.
+    pub fn run_batch(&mut self, batch_size: u32) -> Option<(usize, SearchMatch)> {
+        let mut remaining = batch_size;
+        while remaining > 0 {
+            let index = self.current_job()?;
+            let searcher = &mut self.jobs[index].searcher;
+            let start_count = searcher.attempts();
+            let found = searcher.search_batch(remaining);
+            let used = searcher.attempts() - start_count;
+
+            if let Some(found) = found {
+                self.jobs[index].result = Some(found.clone());
+                self.finish(JobStatus::Found);
+                return Some((index, found));
+            }
+            if !searcher.is_exhausted() {
+                break;
+            }
+            self.finish(JobStatus::Exhausted);
+            remaining -= used as u32;
+        }
+        None
+    }
+
+    fn finish(&mut self, status: JobStatus) {
+        self.jobs[self.current].status = status;
+        self.current += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn searcher(prefix: &str, count_offset: u64) -> Searcher {
+        Searcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some(prefix.into()),
+            None,
+            false,
+            count_offset,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_runs_jobs_in_order() {
+        let mut queue = JobQueue::new();
+        assert_eq!(queue.push(searcher("A", 0)), 0);
+        assert_eq!(queue.push(searcher("B", 1_000)), 1);
+        assert_eq!(queue.current_job(), Some(0));
+
+        let mut found = Vec::new();
+        while queue.current_job().is_some() {
+            found.extend(queue.run_batch(100).map(|(index, _)| index));
+        }
+        assert_eq!(found, [0, 1]);
+        assert_eq!(queue.finished(), 2);
+        assert_eq!(queue.run_batch(100), None);
+
+        let results: Vec<_> = queue.results().collect();
+        assert!(results[0].1.address.starts_with('A'));
+        assert!(results[1].1.address.starts_with('B'));
+        // The second job works in its own counter space.
+        assert_eq!(results[1].1.counter, 1_000 + results[1].1.attempts - 1);
+        assert_eq!(
+            queue.total_attempts(),
+            results[0].1.attempts + results[1].1.attempts
+        );
+    }
+
+    #[test]
+    fn test_exhausted_job_hands_over_rest_of_batch() {
+        let mut capped = searcher("zzzz", 0);
+        capped.set_max_attempts(Some(10));
+        let mut queue = JobQueue::new();
+        queue.push(capped);
+        queue.push(searcher("zzzz", 0));
+
+        assert_eq!(queue.run_batch(25), None);
+        assert_eq!(queue.status(0), Some(JobStatus::Exhausted));
+        assert_eq!(queue.current_job(), Some(1));
+        assert_eq!(queue.searcher(1).unwrap().attempts(), 15);
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut queue = JobQueue::new();
+        queue.push(searcher("zzzz", 0));
+        queue.push(searcher("A", 0));
+        assert_eq!(queue.skip(), Some(0));
+        assert_eq!(queue.status(0), Some(JobStatus::Skipped));
+        assert_eq!(queue.status(1), Some(JobStatus::Pending));
+        assert_eq!(queue.skip(), Some(1));
+        assert_eq!(queue.skip(), None);
+        assert_eq!(queue.current_job(), None);
+        assert_eq!(queue.results().count(), 0);
+    }
+}
@@ -0,0 +1,270 @@
+//! Checks on the counter ranges handed out to workers: overlaps mean
+//! duplicate work (and duplicate results), gaps mean counters nobody
+//! searched.
+
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::VanityError;
+
+/// `length` counters from `offset` on, e.g. a worker's `count_offset` and
+/// the attempts it was given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct WorkRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl WorkRange {
+    /// First counter past the range, or `None` if it would wrap past
+    /// `u64::MAX`.
+    pub fn end(&self) -> Option<u64> {
+        self.offset.checked_add(self.length)
+    }
+}
+
+/// Counters two ranges share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct RangeOverlap {
+    /// Indices of the two ranges in the input, `first < second`.
+    pub first: u32,
+    pub second: u32,
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// What [`audit_ranges`] found.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct RangeAudit {
+    /// Every pair of ranges that share counters, by where the shared
+    /// counters start.
+    pub overlaps: Vec<RangeOverlap>,
+    /// Counters between the lowest and highest ranges that no range covers.
+    pub gaps: Vec<WorkRange>,
+    /// Counters covered at least once.
+    pub covered: u64,
+}
+
+/// Ends of the non-empty ranges with their input indices, sorted by start.
+fn sorted_spans(ranges: &[WorkRange]) -> Result<Vec<(u64, u64, u32)>, VanityError> {
+    let mut spans = Vec::with_capacity(ranges.len());
+    for (index, range) in ranges.iter().enumerate() {
+        let Some(end) = range.end() else {
+            return Err(VanityError::InvalidOptions(format!(
+                "range {index} ({} + {}) wraps past the end of the counter space",
+                range.offset, range.length
+            )));
+        };
+        if range.length > 0 {
+            spans.push((range.offset, end, index as u32));
+        }
+    }
+    spans.sort_unstable();
+    Ok(spans)
+}
+
+/// The union of `ranges`, as sorted, disjoint, non-adjacent ranges: a
+/// compact coverage log. Empty ranges are dropped. Fails if a range wraps
+/// past `u64::MAX`, which would mean a misassigned offset.
+pub fn merge_ranges(ranges: &[WorkRange]) -> Result<Vec<WorkRange>, VanityError> {
+    let mut merged: Vec<WorkRange> = Vec::new();
+    for (start, end, _) in sorted_spans(ranges)? {
+        match merged.last_mut() {
+            Some(last) if start <= last.offset + last.length => {
+                last.length = last.length.max(end - last.offset);
+            }
+            _ => merged.push(WorkRange {
+                offset: start,
+                length: end - start,
+            }),
+        }
+    }
+    Ok(merged)
+}
+
+/// Finds every overlap between `ranges` (in any order) and every gap
+/// between them, and counts the counters they cover. Adjacent ranges
+/// neither overlap nor leave a gap; empty ranges are ignored. Fails as
+/// [`merge_ranges`] does.
+pub fn audit_ranges(ranges: &[WorkRange]) -> Result<RangeAudit, VanityError> {
+    let spans = sorted_spans(ranges)?;
+    let mut overlaps = Vec::new();
+    // Ranges that may still overlap the ones after them.
+    let mut open: Vec<(u64, u32)> = Vec::new();
+    for &(start, end, index) in &spans {
+        open.retain(|&(open_end, _)| open_end > start);
+        for &(open_end, other) in &open {
+            overlaps.push(RangeOverlap {
+                first: index.min(other),
+                second: index.max(other),
+                offset: start,
+                length: end.min(open_end) - start,
+            });
+        }
+        open.push((end, index));
+    }
+    overlaps.sort_unstable_by_key(|overlap| (overlap.offset, overlap.first, overlap.second));
+
+    let merged = merge_ranges(ranges)?;
+    let gaps = merged
+        .windows(2)
+        .map(|pair| {
+            let end = pair[0].offset + pair[0].length;
+            WorkRange {
+                offset: end,
+                length: pair[1].offset - end,
+            }
+        })
+        .collect();
+    Ok(RangeAudit {
+        overlaps,
+        gaps,
+        covered: merged.iter().map(|range| range.length).sum(),
+    })
+}
+
+/// Splits the counters from `offset` to the end of the counter space into
+/// `parts` adjacent ranges, e.g. one per worker; the last takes the
+/// remainder. The final counter, `u64::MAX`, is left out so every range end
+/// fits in a `u64`.
+pub fn partition_counters(offset: u64, parts: u32) -> Result<Vec<WorkRange>, VanityError> {
+    if parts == 0 {
+        return Err(VanityError::InvalidOptions(
+            "counters must be split into at least one range".to_string(),
+        ));
+    }
+    let total = u64::MAX - offset;
+    let length = total / parts as u64;
+    Ok((0..parts as u64)
+        .map(|part| WorkRange {
+            offset: offset + part * length,
+            length: if part + 1 == parts as u64 {
+                total - part * length
+            } else {
+                length
+            },
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(spans: &[(u64, u64)]) -> Vec<WorkRange> {
+        spans
+            .iter()
+            .map(|&(offset, length)| WorkRange { offset, length })
+            .collect()
+    }
+
+    fn overlap(first: u32, second: u32, offset: u64, length: u64) -> RangeOverlap {
+        RangeOverlap {
+            first,
+            second,
+            offset,
+            length,
+        }
+    }
+
+    #[test]
+    fn test_adjacent_ranges_are_clean() {
+        let input = ranges(&[(200, 100), (0, 100), (100, 100)]);
+        let audit = audit_ranges(&input).unwrap();
+        assert_eq!(audit.overlaps, []);
+        assert_eq!(audit.gaps, []);
+        assert_eq!(audit.covered, 300);
+        assert_eq!(merge_ranges(&input).unwrap(), ranges(&[(0, 300)]));
+    }
+
+    #[test]
+    fn test_gaps_between_ranges() {
+        let input = ranges(&[(500, 10), (0, 100), (150, 50)]);
+        let audit = audit_ranges(&input).unwrap();
+        assert_eq!(audit.overlaps, []);
+        assert_eq!(audit.gaps, ranges(&[(100, 50), (200, 300)]));
+        assert_eq!(audit.covered, 160);
+        assert_eq!(
+            merge_ranges(&input).unwrap(),
+            ranges(&[(0, 100), (150, 50), (500, 10)])
+        );
+    }
+
+    #[test]
+    fn test_partial_nested_and_duplicate_overlaps() {
+        let input = ranges(&[(0, 100), (50, 100), (60, 10), (0, 100)]);
+        let audit = audit_ranges(&input).unwrap();
+        assert_eq!(
+            audit.overlaps,
+            [
+                overlap(0, 3, 0, 100),
+                overlap(0, 1, 50, 50),
+                overlap(1, 3, 50, 50),
+                overlap(0, 2, 60, 10),
+                overlap(1, 2, 60, 10),
+                overlap(2, 3, 60, 10),
+            ]
+        );
+        assert_eq!(audit.gaps, []);
+        assert_eq!(audit.covered, 150);
+        assert_eq!(merge_ranges(&input).unwrap(), ranges(&[(0, 150)]));
+    }
+
+    #[test]
+    fn test_overlap_ends_where_the_first_range_does() {
+        // The long range stays open past the short one that follows it.
+        let input = ranges(&[(0, 1_000), (10, 10), (500, 600)]);
+        let audit = audit_ranges(&input).unwrap();
+        assert_eq!(
+            audit.overlaps,
+            [overlap(0, 1, 10, 10), overlap(0, 2, 500, 500)]
+        );
+        assert_eq!(audit.covered, 1_100);
+    }
+
+    #[test]
+    fn test_empty_and_boundary_ranges() {
+        assert_eq!(audit_ranges(&[]).unwrap(), RangeAudit::default());
+        let audit = audit_ranges(&ranges(&[(5, 0), (5, 0), (10, 5)])).unwrap();
+        assert_eq!(audit.overlaps, []);
+        assert_eq!(audit.covered, 5);
+
+        let top = ranges(&[(u64::MAX - 10, 10), (0, 10)]);
+        let audit = audit_ranges(&top).unwrap();
+        assert_eq!(audit.gaps, ranges(&[(10, u64::MAX - 20)]));
+        assert_eq!(audit.covered, 20);
+
+        let wrapping = ranges(&[(0, 10), (u64::MAX - 10, 11)]);
+        let error = audit_ranges(&wrapping).unwrap_err();
+        assert_eq!(error.kind(), crate::ErrorKind::InvalidOptions);
+        assert!(error.to_string().contains("range 1"));
+        assert!(merge_ranges(&wrapping).is_err());
+    }
+
+    #[test]
+    fn test_partitions_cover_the_rest_of_the_counter_space() {
+        for (offset, parts) in [(0, 1), (0, 2), (1_000, 3), (u64::MAX - 5, 8)] {
+            let partition = partition_counters(offset, parts).unwrap();
+            assert_eq!(partition.len(), parts as usize);
+            assert_eq!(partition[0].offset, offset);
+            let audit = audit_ranges(&partition).unwrap();
+            assert_eq!(audit.overlaps, [], "{offset} / {parts}");
+            assert_eq!(audit.gaps, []);
+            assert_eq!(audit.covered, u64::MAX - offset);
+        }
+        assert_eq!(
+            partition_counters(0, 2).unwrap(),
+            ranges(&[(0, u64::MAX / 2), (u64::MAX / 2, u64::MAX / 2 + 1)])
+        );
+        assert_eq!(
+            partition_counters(0, 0).unwrap_err().kind(),
+            crate::ErrorKind::InvalidOptions
+        );
+    }
+}
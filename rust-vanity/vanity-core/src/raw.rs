@@ -0,0 +1,269 @@
+//! A C-style ABI over [`Searcher`] for hosts that load the module without
+//! wasm-bindgen's JS glue. Enabled by the `raw-abi` feature; build the module
+//! with `cargo rustc -p vanity-core --release --target wasm32-unknown-unknown
+//! --features raw-abi --crate-type cdylib`.
+//!
+//! Memory ownership: buffers passed in are owned by the host, which should
+//! get them from [`vanity_alloc`] and release them with [`vanity_dealloc`]
+//! (with the same length). They are only read or written during the call.
+//! A handle from [`vanity_new`] belongs to the host until it is passed to
+//! [`vanity_free`], exactly once.
+//!
+//! Return codes: a negative value is the negated [`VanityError`] code, e.g.
+//! `-2` for an invalid pattern character. Otherwise `vanity_new` returns 0
+//! and `vanity_search_batch` one of the `STATUS_*` values.
+
+use alloc::alloc::{alloc, dealloc, Layout};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::ptr::{self, NonNull};
+use core::slice;
+
+use crate::derive::{MAX_ENCODED_LEN, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::search::{BatchStatus, Searcher};
+use crate::seed::SEED_LEN;
+
+pub const STATUS_NOT_FOUND: i32 = 0;
+pub const STATUS_FOUND: i32 = 1;
+pub const STATUS_STOPPED: i32 = 2;
+pub const STATUS_EXHAUSTED: i32 = 3;
+
+/// Size of the result `vanity_search_batch` writes on a match: the seed, the
+/// attempt count as a little-endian u64, the address length as one byte, and
+/// the address, zero-padded to [`MAX_ENCODED_LEN`] bytes.
+pub const RESULT_LEN: usize = SEED_LEN + 8 + 1 + MAX_ENCODED_LEN;
+
+fn error_code(error: VanityError) -> i32 {
+    -(error.code() as i32)
+}
+
+/// Allocates `len` bytes for the host to fill, or returns null if that
+/// fails. A zero `len` returns a dangling, non-null pointer.
+#[no_mangle]
+pub extern "C" fn vanity_alloc(len: usize) -> *mut u8 {
+    match Layout::array::<u8>(len) {
+        Ok(_) if len == 0 => NonNull::dangling().as_ptr(),
+        // SAFETY: the layout has a non-zero size.
+        Ok(layout) => unsafe { alloc(layout) },
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Frees a buffer from [`vanity_alloc`].
+///
+/// # Safety
+///
+/// `ptr` must come from `vanity_alloc(len)` with the same `len`, and must not
+/// be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn vanity_dealloc(ptr: *mut u8, len: usize) {
+    if len == 0 || ptr.is_null() {
+        return;
+    }
+    dealloc(ptr, Layout::array::<u8>(len).unwrap());
+}
+
+/// # Safety
+///
+/// `ptr` must be valid for reading `len` bytes unless `len` is zero.
+unsafe fn pattern(ptr: *const u8, len: usize) -> Result<Option<String>, VanityError> {
+    if len == 0 {
+        return Ok(None);
+    }
+    let bytes = slice::from_raw_parts(ptr, len);
+    let pattern = core::str::from_utf8(bytes)
+        .map_err(|_| VanityError::InvalidOptions("pattern is not valid UTF-8".into()))?;
+    Ok(Some(pattern.to_string()))
+}
+
+/// Creates a searcher, as [`Searcher::new`] would, and stores its handle in
+/// `*out_handle`. An empty prefix or suffix is passed as a zero length.
+/// Returns 0, or a negated error code without touching `*out_handle`.
+///
+/// # Safety
+///
+/// `base` and `owner` must be valid for reading 32 bytes, each pattern for
+/// reading its length in bytes, and `out_handle` for writing a pointer.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn vanity_new(
+    base: *const u8,
+    owner: *const u8,
+    prefix: *const u8,
+    prefix_len: usize,
+    suffix: *const u8,
+    suffix_len: usize,
+    case_insensitive: u32,
+    count_offset: u64,
+    out_handle: *mut *mut Searcher,
+) -> i32 {
+    if base.is_null() {
+        return error_code(VanityError::MissingBase);
+    }
+    if owner.is_null() {
+        return error_code(VanityError::MissingOwner);
+    }
+    if out_handle.is_null() {
+        return error_code(VanityError::InvalidOptions("null out_handle".into()));
+    }
+    let base = &*(base as *const [u8; PUBKEY_LEN]);
+    let owner = &*(owner as *const [u8; PUBKEY_LEN]);
+    let searcher = pattern(prefix, prefix_len).and_then(|prefix| {
+        let suffix = pattern(suffix, suffix_len)?;
+        Searcher::new(
+            base,
+            owner,
+            prefix,
+            suffix,
+            case_insensitive != 0,
+            count_offset,
+        )
+    });
+    match searcher {
+        Ok(searcher) => {
+            *out_handle = Box::into_raw(Box::new(searcher));
+            0
+        }
+        Err(error) => error_code(error),
+    }
+}
+
+/// Runs up to `batch_size` attempts. On a match, writes [`RESULT_LEN`]
+/// bytes to `out` and returns [`STATUS_FOUND`].
+///
+/// # Safety
+///
+/// `handle` must come from [`vanity_new`] and not have been freed, and `out`
+/// must be valid for writing `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vanity_search_batch(
+    handle: *mut Searcher,
+    batch_size: u32,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    let Some(searcher) = handle.as_mut() else {
+        return error_code(VanityError::InvalidOptions("null handle".into()));
+    };
+    if out.is_null() || out_len < RESULT_LEN {
+        return error_code(VanityError::InvalidOptions(format!(
+            "result buffer must hold {RESULT_LEN} bytes"
+        )));
+    }
+
+    let found = searcher.search_batch(batch_size);
+    if let Some(found) = &found {
+        let out = slice::from_raw_parts_mut(out, RESULT_LEN);
+        out.fill(0);
+        let (seed, rest) = out.split_at_mut(SEED_LEN);
+        seed.copy_from_slice(&found.seed);
+        rest[..8].copy_from_slice(&found.attempts.to_le_bytes());
+        rest[8] = found.address.len() as u8;
+        rest[9..9 + found.address.len()].copy_from_slice(found.address.as_bytes());
+    }
+    match searcher.batch_status(found.is_some()) {
+        BatchStatus::Found => STATUS_FOUND,
+        BatchStatus::NotFound => STATUS_NOT_FOUND,
+        BatchStatus::Stopped => STATUS_STOPPED,
+        // The raw ABI can't set a stop probability.
+        BatchStatus::Exhausted | BatchStatus::CoverageReached => STATUS_EXHAUSTED,
+        BatchStatus::Error => error_code(VanityError::Internal("batch failed".into())),
+    }
+}
+
+/// Frees a searcher. A null handle is ignored.
+///
+/// # Safety
+///
+/// `handle` must come from [`vanity_new`] and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn vanity_free(handle: *mut Searcher) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::{encode_pubkey, Deriver};
+
+    /// Copies `bytes` into a buffer from `vanity_alloc`, as a host would.
+    fn host_buffer(bytes: &[u8]) -> *mut u8 {
+        let ptr = vanity_alloc(bytes.len());
+        assert!(!ptr.is_null());
+        unsafe { ptr.copy_from_nonoverlapping(bytes.as_ptr(), bytes.len()) };
+        ptr
+    }
+
+    #[test]
+    fn test_raw_abi_end_to_end() {
+        let base = host_buffer(&[1; 32]);
+        let owner = host_buffer(&[4; 32]);
+        let prefix = host_buffer(b"AAA");
+        let out = vanity_alloc(RESULT_LEN);
+        let mut handle = ptr::null_mut();
+
+        unsafe {
+            let status = vanity_new(base, owner, prefix, 3, ptr::null(), 0, 0, 0, &mut handle);
+            assert_eq!(status, 0);
+            assert!(!handle.is_null());
+
+            let mut batches = 0;
+            loop {
+                match vanity_search_batch(handle, 10_000, out, RESULT_LEN) {
+                    STATUS_NOT_FOUND => batches += 1,
+                    STATUS_FOUND => break,
+                    status => panic!("unexpected status {status}"),
+                }
+            }
+            assert_eq!(batches, 5);
+
+            let result = slice::from_raw_parts(out, RESULT_LEN);
+            let seed = &result[..SEED_LEN];
+            let attempts = u64::from_le_bytes(result[SEED_LEN..SEED_LEN + 8].try_into().unwrap());
+            let len = result[SEED_LEN + 8] as usize;
+            let address = core::str::from_utf8(&result[SEED_LEN + 9..SEED_LEN + 9 + len]).unwrap();
+            assert_eq!(attempts, 51052);
+            assert_eq!(address, "AAArB3C2J4JtzZDGh9kQBDVU1c3fdDoH1UF1qiyGLzoj");
+            let mut buf = [0u8; MAX_ENCODED_LEN];
+            let pubkey = Deriver::new(&[1; 32], &[4; 32]).derive(seed);
+            assert_eq!(encode_pubkey(&pubkey, &mut buf), address);
+
+            assert_eq!(
+                vanity_search_batch(handle, 10, out, RESULT_LEN - 1),
+                -(crate::ErrorKind::InvalidOptions.code() as i32)
+            );
+            vanity_free(handle);
+
+            let bad = host_buffer(b"A0");
+            let mut unset = ptr::null_mut();
+            let status = vanity_new(base, owner, ptr::null(), 0, bad, 2, 0, 0, &mut unset);
+            assert_eq!(
+                status,
+                -(crate::ErrorKind::InvalidPatternChar.code() as i32)
+            );
+            assert!(unset.is_null());
+            let status = vanity_new(
+                base,
+                owner,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                0,
+                0,
+                &mut unset,
+            );
+            assert_eq!(status, -(crate::ErrorKind::EmptyPattern.code() as i32));
+
+            vanity_dealloc(bad, 2);
+            vanity_dealloc(base, 32);
+            vanity_dealloc(owner, 32);
+            vanity_dealloc(prefix, 3);
+            vanity_dealloc(out, RESULT_LEN);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Rent at the cluster's default rate, for sizing the accounts a search
+//! result is about to create.
+
+/// Bytes every account pays rent on besides its data.
+const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+
+/// The cluster's default rent: lamports per byte-year, and years of it an
+/// account must hold to be exempt.
+const LAMPORTS_PER_BYTE_YEAR: u64 = 3480;
+const EXEMPTION_YEARS: u64 = 2;
+
+/// Lamports an account of `space` bytes needs to be rent-exempt at the
+/// default rent.
+pub const fn rent_exempt_lamports(space: usize) -> u64 {
+    (ACCOUNT_STORAGE_OVERHEAD + space as u64) * LAMPORTS_PER_BYTE_YEAR * EXEMPTION_YEARS
+}
@@ -0,0 +1,275 @@
+//! A log of what the search loop derived for a sample of counters, to
+//! compare across builds, versions or backends: two logs that disagree on
+//! a counter mean one of them derives addresses wrongly from there on.
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::derive::PUBKEY_LEN;
+use crate::error::VanityError;
+
+/// Bytes of SHA-256 kept per entry.
+pub const REPLAY_DIGEST_LEN: usize = 8;
+
+/// Bytes per entry in [`ReplayLog::to_bytes`]: the counter, little-endian,
+/// then the digest.
+pub const REPLAY_ENTRY_LEN: usize = 8 + REPLAY_DIGEST_LEN;
+
+/// Most entries a [`ReplayLog`] keeps, 16 MiB of them.
+pub const MAX_REPLAY_ENTRIES: u32 = 1 << 20;
+
+/// The latest `capacity` digests of the pubkeys derived for counters that
+/// are a multiple of `every`. Sampling by counter rather than by attempt
+/// makes logs from searchers at different offsets line up wherever their
+/// ranges overlap.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayLog {
+    entries: VecDeque<(u64, [u8; REPLAY_DIGEST_LEN])>,
+    capacity: usize,
+    every: u64,
+}
+
+impl ReplayLog {
+    /// Fails with `InvalidOptions` unless `capacity` is between 1 and
+    /// [`MAX_REPLAY_ENTRIES`] and `every` is at least 1.
+    pub fn new(capacity: u32, every: u64) -> Result<ReplayLog, VanityError> {
+        if capacity == 0 || capacity > MAX_REPLAY_ENTRIES {
+            return Err(VanityError::InvalidOptions(format!(
+                "replay log capacity must be between 1 and {MAX_REPLAY_ENTRIES}, got {capacity}"
+            )));
+        }
+        if every == 0 {
+            return Err(VanityError::InvalidOptions(
+                "replay log interval must be at least 1 counter".into(),
+            ));
+        }
+        Ok(ReplayLog {
+            entries: VecDeque::with_capacity(capacity as usize),
+            capacity: capacity as usize,
+            every,
+        })
+    }
+
+    /// Records `pubkey` if `counter` is due, dropping the oldest entry if
+    /// the log is full.
+    #[inline]
+    pub(crate) fn observe(&mut self, counter: u64, pubkey: &[u8; PUBKEY_LEN]) {
+        if !counter.is_multiple_of(self.every) {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((counter, digest(pubkey)));
+    }
+
+    /// Counters and digests, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = (u64, [u8; REPLAY_DIGEST_LEN])> + '_ {
+        self.entries.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity as u32
+    }
+
+    pub fn every(&self) -> u64 {
+        self.every
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// [`REPLAY_ENTRY_LEN`] bytes per entry, oldest first, for
+    /// [`compare_replay`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * REPLAY_ENTRY_LEN);
+        for (counter, digest) in &self.entries {
+            bytes.extend_from_slice(&counter.to_le_bytes());
+            bytes.extend_from_slice(digest);
+        }
+        bytes
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        self.entries.capacity() * core::mem::size_of::<(u64, [u8; REPLAY_DIGEST_LEN])>()
+    }
+}
+
+fn digest(pubkey: &[u8; PUBKEY_LEN]) -> [u8; REPLAY_DIGEST_LEN] {
+    Sha256::digest(pubkey)[..REPLAY_DIGEST_LEN]
+        .try_into()
+        .unwrap()
+}
+
+/// What [`compare_replay`] found.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct ReplayComparison {
+    /// Counters in both logs.
+    pub compared: u32,
+    /// Counters in only one of them, e.g. where their ranges don't
+    /// overlap.
+    pub unmatched: u32,
+    /// Lowest counter both logs have with different digests; unset if they
+    /// agree wherever they overlap.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub first_divergent_counter: Option<u64>,
+}
+
+fn parse_log<'a>(bytes: &'a [u8], name: &str) -> Result<Vec<(u64, &'a [u8])>, VanityError> {
+    if !bytes.len().is_multiple_of(REPLAY_ENTRY_LEN) {
+        return Err(VanityError::InvalidOptions(format!(
+            "{name} is {} bytes, not a multiple of {REPLAY_ENTRY_LEN}",
+            bytes.len()
+        )));
+    }
+    let mut entries: Vec<(u64, &[u8])> = bytes
+        .chunks_exact(REPLAY_ENTRY_LEN)
+        .map(|entry| {
+            let counter = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            (counter, &entry[8..])
+        })
+        .collect();
+    // With a counter permutation entries are in search order, not counter
+    // order.
+    entries.sort_unstable_by_key(|&(counter, _)| counter);
+    Ok(entries)
+}
+
+/// Lines up two [`ReplayLog::to_bytes`] logs by counter and reports the
+/// first counter they disagree on. Fails with `InvalidOptions` for a log
+/// whose length isn't a whole number of entries.
+pub fn compare_replay(log_a: &[u8], log_b: &[u8]) -> Result<ReplayComparison, VanityError> {
+    let a = parse_log(log_a, "log_a")?;
+    let b = parse_log(log_b, "log_b")?;
+    let mut comparison = ReplayComparison::default();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let ((counter_a, digest_a), (counter_b, digest_b)) = (a[i], b[j]);
+        match counter_a.cmp(&counter_b) {
+            core::cmp::Ordering::Less => {
+                comparison.unmatched += 1;
+                i += 1;
+            }
+            core::cmp::Ordering::Greater => {
+                comparison.unmatched += 1;
+                j += 1;
+            }
+            core::cmp::Ordering::Equal => {
+                comparison.compared += 1;
+                if digest_a != digest_b && comparison.first_divergent_counter.is_none() {
+                    comparison.first_divergent_counter = Some(counter_a);
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    comparison.unmatched += (a.len() - i + b.len() - j) as u32;
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::search::Searcher;
+
+    fn searcher(owner: u8, count_offset: u64) -> Searcher {
+        Searcher::new(
+            &[1; 32],
+            &[owner; 32],
+            Some("zzzzzzzz".into()),
+            None,
+            false,
+            count_offset,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_comparison_pinpoints_the_first_divergence() {
+        let mut reference = searcher(4, 0);
+        reference.set_replay_log(Some((1_000, 10))).unwrap();
+        reference.search_batch(2_000);
+        let log = reference.replay_log().unwrap();
+        assert_eq!(log.len(), 200);
+        assert!(log.entries().all(|(counter, _)| counter % 10 == 0));
+
+        // A build that derives with the wrong owner from counter 1234 on.
+        let mut drifting = searcher(4, 0);
+        drifting.set_replay_log(Some((1_000, 10))).unwrap();
+        drifting.search_batch(1_234);
+        drifting.set_keys(&[1; 32], &[5; 32], false);
+        drifting.search_batch(2_000 - 1_234);
+
+        let bytes = reference.replay_log().unwrap().to_bytes();
+        assert_eq!(bytes.len(), 200 * REPLAY_ENTRY_LEN);
+        let comparison =
+            compare_replay(&bytes, &drifting.replay_log().unwrap().to_bytes()).unwrap();
+        assert_eq!(
+            comparison,
+            ReplayComparison {
+                compared: 200,
+                unmatched: 0,
+                first_divergent_counter: Some(1_240),
+            }
+        );
+        assert_eq!(
+            compare_replay(&bytes, &bytes)
+                .unwrap()
+                .first_divergent_counter,
+            None
+        );
+
+        // Logs line up by counter, whatever the searchers' offsets.
+        let mut later = searcher(4, 1_500);
+        later.set_replay_log(Some((1_000, 10))).unwrap();
+        later.search_batch(1_000);
+        let comparison = compare_replay(&bytes, &later.replay_log().unwrap().to_bytes()).unwrap();
+        assert_eq!(
+            comparison,
+            ReplayComparison {
+                compared: 50,
+                unmatched: 150 + 50,
+                first_divergent_counter: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_log_is_bounded() {
+        let mut log = ReplayLog::new(3, 2).unwrap();
+        for counter in 0..20 {
+            log.observe(counter, &[counter as u8; 32]);
+        }
+        let counters: Vec<u64> = log.entries().map(|(counter, _)| counter).collect();
+        assert_eq!(counters, [14, 16, 18]);
+        assert_eq!(log.entries().last().unwrap().1, digest(&[18; 32]));
+
+        let kind = |result: Result<ReplayLog, VanityError>| result.unwrap_err().kind();
+        assert_eq!(kind(ReplayLog::new(0, 1)), ErrorKind::InvalidOptions);
+        assert_eq!(
+            kind(ReplayLog::new(MAX_REPLAY_ENTRIES + 1, 1)),
+            ErrorKind::InvalidOptions
+        );
+        assert_eq!(kind(ReplayLog::new(1, 0)), ErrorKind::InvalidOptions);
+        assert_eq!(
+            compare_replay(&[0; 17], &[]).unwrap_err().kind(),
+            ErrorKind::InvalidOptions
+        );
+    }
+}
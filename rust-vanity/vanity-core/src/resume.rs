@@ -0,0 +1,306 @@
+//! Resume tokens: a searcher's position bound to a fingerprint of what it was
+//! searching, so saved progress can't be applied to a different search.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::error::VanityError;
+use crate::matcher::{ByteMask, CleanPrefix, MatchType};
+use crate::permute::SeedNamespace;
+use crate::seed::SeedScheme;
+
+/// Leading field of every token, bumped if the format ever changes.
+const TOKEN_VERSION: &str = "v1";
+
+/// Hash of everything that decides which address a counter maps to and
+/// whether it matches: the derivation's [`Derivation::identity`] (for
+/// `createWithSeed`, the seed scheme and both keys), the match spec and the
+/// case flag. Truncated to 64 bits, which is plenty to catch mistakes.
+///
+/// [`Derivation::identity`]: crate::derivation::Derivation::identity
+pub(crate) fn config_fingerprint(
+    identity: &[u8],
+    match_type: &MatchType,
+    case_insensitive: bool,
+) -> u64 {
+    let tag: u8 = match match_type {
+        MatchType::Prefix(_) => 0,
+        MatchType::Suffix(_) => 1,
+        MatchType::Both(..) => 2,
+        MatchType::Contains(_) => 3,
+    };
+    let mut hasher = Sha256::new()
+        .chain_update(b"web-vanity resume")
+        .chain_update(identity)
+        .chain_update([tag, case_insensitive as u8]);
+    for pattern in match_type.patterns() {
+        hasher.update((pattern.len() as u32).to_le_bytes());
+        hasher.update(pattern.as_bytes());
+    }
+    let digest = hasher.finalize();
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+/// Folds a byte mask into a [`config_fingerprint`]. Searchers without one
+/// keep the plain fingerprint, so their saved tokens stay valid.
+pub(crate) fn byte_mask_fingerprint(fingerprint: u64, byte_mask: &ByteMask) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(b"web-vanity byte mask")
+        .chain_update(fingerprint.to_le_bytes())
+        .chain_update(byte_mask.mask())
+        .chain_update(byte_mask.value())
+        .finalize();
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+pub(crate) fn clean_prefix_fingerprint(fingerprint: u64, clean_prefix: &CleanPrefix) -> u64 {
+    let set = clean_prefix.set();
+    let mut hasher = Sha256::new()
+        .chain_update(b"web-vanity clean prefix")
+        .chain_update(fingerprint.to_le_bytes())
+        .chain_update([set.clean_prefix_len])
+        .chain_update(set.chars.len().to_le_bytes())
+        .chain_update(set.chars.as_bytes());
+    for pair in &set.pairs {
+        hasher.update(pair.as_bytes());
+    }
+    let digest = hasher.finalize();
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+/// Marks the field holding a seed namespace tag.
+const NAMESPACE_FIELD: &str = "ns-";
+
+/// Marks the field holding a seed scheme version.
+const SEED_SCHEME_FIELD: &str = "seed-v";
+
+/// A parsed token: `v1.<fingerprint as 16 hex digits>.<offset>.<attempts>`,
+/// followed by `.<permutation key as 16 hex digits>` if the searcher
+/// permutes its counters, `.ns-<tag>` if it has a seed namespace and
+/// `.seed-v<version>` if its seed scheme is newer than V1, which tokens
+/// without the field were all saved under. The permutation and namespace
+/// aren't in the fingerprint and the scheme is there only through the
+/// derivation's identity: all three are restored from the token, so a
+/// fresh searcher can resume without being told them first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ResumeToken {
+    pub fingerprint: u64,
+    pub count_offset: u64,
+    pub attempts: u64,
+    pub permutation_key: Option<u64>,
+    pub namespace: Option<SeedNamespace>,
+    pub seed_scheme: Option<SeedScheme>,
+}
+
+impl ResumeToken {
+    pub fn encode(&self) -> String {
+        let mut token = format!(
+            "{TOKEN_VERSION}.{:016x}.{}.{}",
+            self.fingerprint, self.count_offset, self.attempts
+        );
+        if let Some(key) = self.permutation_key {
+            token.push_str(&format!(".{key:016x}"));
+        }
+        if let Some(namespace) = &self.namespace {
+            token.push_str(&format!(".{NAMESPACE_FIELD}{}", namespace.tag()));
+        }
+        if let Some(scheme) = self.seed_scheme {
+            token.push_str(&format!(".{SEED_SCHEME_FIELD}{}", scheme.version()));
+        }
+        token
+    }
+
+    pub fn parse(token: &str) -> Result<ResumeToken, VanityError> {
+        let invalid = |detail: &str| VanityError::InvalidResumeToken(detail.into());
+        let fields: Vec<&str> = token.trim().split('.').collect();
+        let (version, fingerprint, count_offset, attempts, extra) = match fields[..] {
+            [version, fingerprint, count_offset, attempts, ref extra @ ..] if extra.len() <= 3 => {
+                (version, fingerprint, count_offset, attempts, extra)
+            }
+            _ => return Err(invalid("expected four to seven '.'-separated fields")),
+        };
+        if version != TOKEN_VERSION {
+            return Err(VanityError::InvalidResumeToken(format!(
+                "unsupported version {version:?}"
+            )));
+        }
+        let hex = |field: &str, detail: &str| {
+            if field.len() != 16 {
+                return Err(invalid(detail));
+            }
+            u64::from_str_radix(field, 16).map_err(|_| invalid(detail))
+        };
+        let fingerprint = hex(fingerprint, "fingerprint must be 16 hex digits")?;
+        // The permutation key, namespace and scheme come in that order, each
+        // at most once.
+        let mut extra = extra.iter().copied().peekable();
+        let permutation_key = extra.next_if(|field| {
+            !field.starts_with(NAMESPACE_FIELD) && !field.starts_with(SEED_SCHEME_FIELD)
+        });
+        let namespace = extra.next_if(|field| field.starts_with(NAMESPACE_FIELD));
+        let seed_scheme = extra.next_if(|field| field.starts_with(SEED_SCHEME_FIELD));
+        if extra.next().is_some() {
+            return Err(invalid("unexpected or out-of-order field"));
+        }
+        let seed_scheme = seed_scheme
+            .map(|field| {
+                field[SEED_SCHEME_FIELD.len()..]
+                    .parse::<u32>()
+                    .map_err(|_| invalid("seed scheme version must be an unsigned integer"))
+                    .and_then(|version| {
+                        SeedScheme::from_version(version)
+                            .map_err(|error| VanityError::InvalidResumeToken(error.to_string()))
+                    })
+            })
+            .transpose()?;
+        let permutation_key = permutation_key
+            .map(|key| hex(key, "permutation key must be 16 hex digits"))
+            .transpose()?;
+        let namespace = namespace
+            .map(|tag| {
+                SeedNamespace::new(&tag[NAMESPACE_FIELD.len()..])
+                    .map_err(|error| VanityError::InvalidResumeToken(error.to_string()))
+            })
+            .transpose()?;
+        let count = |field: &str| {
+            field
+                .parse::<u64>()
+                .map_err(|_| invalid("offset and attempts must be unsigned integers"))
+        };
+        Ok(ResumeToken {
+            fingerprint,
+            count_offset: count(count_offset)?,
+            attempts: count(attempts)?,
+            permutation_key,
+            namespace,
+            seed_scheme,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derivation::{CreateWithSeedDerivation, Derivation};
+    use crate::derive::PUBKEY_LEN;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_token_round_trip() {
+        let token = ResumeToken {
+            fingerprint: 0x00ab_cdef_0123_4567,
+            count_offset: 1 << 40,
+            attempts: u64::MAX,
+            permutation_key: None,
+            namespace: None,
+            seed_scheme: None,
+        };
+        let encoded = token.encode();
+        assert_eq!(
+            encoded,
+            "v1.00abcdef01234567.1099511627776.18446744073709551615"
+        );
+        assert_eq!(ResumeToken::parse(&encoded), Ok(token.clone()));
+
+        let permuted = ResumeToken {
+            permutation_key: Some(0xff),
+            ..token.clone()
+        };
+        let encoded = permuted.encode();
+        assert!(encoded.ends_with(".18446744073709551615.00000000000000ff"));
+        assert_eq!(ResumeToken::parse(&encoded), Ok(permuted.clone()));
+
+        let namespace = Some(SeedNamespace::new("tab-2").unwrap());
+        for token in [
+            ResumeToken {
+                namespace: namespace.clone(),
+                ..token
+            },
+            ResumeToken {
+                namespace,
+                ..permuted
+            },
+        ] {
+            let encoded = token.encode();
+            assert!(encoded.ends_with(".ns-tab-2"));
+            assert_eq!(ResumeToken::parse(&encoded), Ok(token.clone()));
+
+            let versioned = ResumeToken {
+                seed_scheme: Some(SeedScheme::V1),
+                ..token
+            };
+            let encoded = versioned.encode();
+            assert!(encoded.ends_with(".ns-tab-2.seed-v1"));
+            assert_eq!(ResumeToken::parse(&encoded), Ok(versioned));
+        }
+    }
+
+    #[test]
+    fn test_malformed_tokens() {
+        for token in [
+            "",
+            "v1.00abcdef01234567.1",
+            "v2.00abcdef01234567.1.2",
+            "v1.abcdef.1.2",
+            "v1.00abcdef0123456g.1.2",
+            "v1.00abcdef01234567.-1.2",
+            "v1.00abcdef01234567.1.2.3",
+            "v1.00abcdef01234567.1.2.000000000000000g",
+            "v1.00abcdef01234567.1.2.00000000000000ff.3",
+            "v1.00abcdef01234567.1.2.ns-a.00000000000000ff",
+            "v1.00abcdef01234567.1.2.ns-bad!tag",
+            "v1.00abcdef01234567.1.2.ns-",
+            "v1.00abcdef01234567.1.2.00000000000000ff.ns-a.ns-b",
+            "v1.00abcdef01234567.1.2.seed-v1.ns-a",
+            "v1.00abcdef01234567.1.2.seed-vx",
+            "v1.00abcdef01234567.1.2.seed-v99",
+        ] {
+            let kind = ResumeToken::parse(token).unwrap_err().kind();
+            assert_eq!(kind, ErrorKind::InvalidResumeToken, "{token:?}");
+        }
+    }
+
+    fn seed_fingerprint(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        match_type: &MatchType,
+        case_insensitive: bool,
+    ) -> u64 {
+        let derivation = CreateWithSeedDerivation::new(base_pubkey, owner_pubkey);
+        config_fingerprint(&derivation.identity(), match_type, case_insensitive)
+    }
+
+    #[test]
+    fn test_fingerprint_covers_configuration() {
+        let prefix = MatchType::Prefix("ab".into());
+        let fingerprint = seed_fingerprint(&[1; 32], &[4; 32], &prefix, false);
+        assert_eq!(
+            fingerprint,
+            seed_fingerprint(&[1; 32], &[4; 32], &prefix, false)
+        );
+        for other in [
+            seed_fingerprint(&[2; 32], &[4; 32], &prefix, false),
+            seed_fingerprint(&[1; 32], &[5; 32], &prefix, false),
+            seed_fingerprint(&[1; 32], &[4; 32], &prefix, true),
+            seed_fingerprint(&[1; 32], &[4; 32], &MatchType::Suffix("ab".into()), false),
+            seed_fingerprint(&[1; 32], &[4; 32], &MatchType::Prefix("abc".into()), false),
+            seed_fingerprint(
+                &[1; 32],
+                &[4; 32],
+                &MatchType::Both("a".into(), "b".into()),
+                false,
+            ),
+            seed_fingerprint(
+                &[1; 32],
+                &[4; 32],
+                &MatchType::Both("ab".into(), String::new()),
+                false,
+            ),
+        ] {
+            assert_ne!(fingerprint, other);
+        }
+    }
+}
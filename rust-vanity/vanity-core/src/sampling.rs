@@ -0,0 +1,184 @@
+//! A ring of addresses sampled from the search loop, for UIs that animate
+//! the candidates being tried without pulling every one across to JS.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::derive::MAX_ENCODED_LEN;
+use crate::error::VanityError;
+use crate::search::{DerivedAddress, MAX_SAMPLES};
+use crate::seed::generate_seed_from_counter;
+
+#[derive(Clone, Copy)]
+struct Slot {
+    address: [u8; MAX_ENCODED_LEN],
+    len: u8,
+    /// Physical counter, so the seed can be regenerated on drain.
+    counter: u64,
+}
+
+const EMPTY_SLOT: Slot = Slot {
+    address: [0; MAX_ENCODED_LEN],
+    len: 0,
+    counter: 0,
+};
+
+/// Keeps the latest `capacity` of every `every`th address in a slab
+/// allocated up front: recording one copies its bytes and nothing else, and
+/// once full the oldest entries are overwritten.
+#[derive(Clone)]
+pub struct AddressSampler {
+    slots: Box<[Slot]>,
+    every: u32,
+    /// Attempts left until the next one is sampled.
+    until_next: u32,
+    /// Index of the oldest entry.
+    start: usize,
+    len: usize,
+}
+
+impl AddressSampler {
+    /// Fails unless `capacity` is between 1 and [`MAX_SAMPLES`] and `every`
+    /// is at least 1.
+    pub fn new(capacity: u32, every: u32) -> Result<AddressSampler, VanityError> {
+        if capacity == 0 || capacity > MAX_SAMPLES {
+            return Err(VanityError::InvalidOptions(format!(
+                "sample capacity must be between 1 and {MAX_SAMPLES}, got {capacity}"
+            )));
+        }
+        if every == 0 {
+            return Err(VanityError::InvalidOptions(
+                "sampling interval must be at least 1 attempt".into(),
+            ));
+        }
+        Ok(AddressSampler {
+            slots: vec![EMPTY_SLOT; capacity as usize].into_boxed_slice(),
+            every,
+            until_next: 1,
+            start: 0,
+            len: 0,
+        })
+    }
+
+    /// Counts an attempt and says whether it is due to be sampled.
+    #[inline]
+    pub(crate) fn tick(&mut self) -> bool {
+        self.until_next -= 1;
+        if self.until_next == 0 {
+            self.until_next = self.every;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Stores `address`, dropping the oldest entry if the ring is full.
+    pub(crate) fn record(&mut self, counter: u64, address: &str) {
+        let capacity = self.slots.len();
+        let index = (self.start + self.len) % capacity;
+        let slot = &mut self.slots[index];
+        slot.address[..address.len()].copy_from_slice(address.as_bytes());
+        slot.len = address.len() as u8;
+        slot.counter = counter;
+        if self.len == capacity {
+            self.start = (self.start + 1) % capacity;
+        } else {
+            self.len += 1;
+        }
+    }
+
+    /// The entries recorded since the last drain, oldest first, leaving the
+    /// ring empty.
+    pub fn drain(&mut self) -> Vec<DerivedAddress> {
+        self.drain_with(generate_seed_from_counter)
+    }
+
+    /// [`AddressSampler::drain`] for any derivation: `seed` regenerates the
+    /// artifacts for a recorded counter.
+    pub fn drain_with<A>(&mut self, mut seed: impl FnMut(u64) -> A) -> Vec<DerivedAddress<A>> {
+        let capacity = self.slots.len();
+        let samples = (0..self.len)
+            .map(|i| {
+                let slot = &self.slots[(self.start + i) % capacity];
+                DerivedAddress {
+                    // Only ever filled from a `&str`.
+                    address: String::from_utf8_lossy(&slot.address[..slot.len as usize])
+                        .into_owned(),
+                    seed: seed(slot.counter),
+                    counter: slot.counter,
+                }
+            })
+            .collect();
+        self.start = 0;
+        self.len = 0;
+        samples
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    pub fn every(&self) -> u32 {
+        self.every
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn heap_bytes(&self) -> usize {
+        core::mem::size_of_val(&*self.slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(samples: &[DerivedAddress]) -> Vec<u64> {
+        samples.iter().map(|sample| sample.counter).collect()
+    }
+
+    #[test]
+    fn test_ring_wraps_and_keeps_the_latest() {
+        let mut sampler = AddressSampler::new(3, 2).unwrap();
+        let due: Vec<_> = (0..6).map(|_| sampler.tick()).collect();
+        assert_eq!(due, [true, false, true, false, true, false]);
+
+        for counter in 0..5 {
+            sampler.record(counter, &format!("addr{counter}"));
+        }
+        assert_eq!(sampler.len(), 3);
+        let samples = sampler.drain();
+        assert_eq!(counters(&samples), [2, 3, 4]);
+        assert_eq!(samples[0].address, "addr2");
+        assert_eq!(samples[2].seed, generate_seed_from_counter(4));
+        assert!(sampler.is_empty());
+        assert_eq!(sampler.drain(), []);
+
+        // Refilling after a drain starts from the front again.
+        sampler.record(9, "a");
+        sampler.record(10, "bb");
+        assert_eq!(counters(&sampler.drain()), [9, 10]);
+    }
+
+    #[test]
+    fn test_sampler_options_are_validated() {
+        assert!(AddressSampler::new(0, 1).is_err());
+        assert!(AddressSampler::new(MAX_SAMPLES + 1, 1).is_err());
+        assert!(AddressSampler::new(1, 0).is_err());
+        let sampler = AddressSampler::new(MAX_SAMPLES, 7).unwrap();
+        assert_eq!(sampler.capacity(), MAX_SAMPLES);
+        assert_eq!(sampler.every(), 7);
+        assert_eq!(
+            sampler.heap_bytes(),
+            MAX_SAMPLES as usize * core::mem::size_of::<Slot>()
+        );
+    }
+}
@@ -0,0 +1,2489 @@
+//! The search loop: counter → candidate → address → match, written once for
+//! every [`Derivation`].
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::cancel::CancelToken;
+use crate::derivation::{Candidate, CreateWithSeedDerivation, Derivation};
+use crate::derive::{encode_pubkey, try_encode_pubkey, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::estimate::{attempts_for_confidence, luck_stats, probability_found, LuckStats};
+use crate::matcher::{AddressMatcher, ByteMask, CleanPrefix, MatchType};
+use crate::partial::{PartialStats, PartialTracker};
+use crate::permute::{CounterPermutation, SeedNamespace};
+use crate::replay::ReplayLog;
+use crate::resume::{
+    byte_mask_fingerprint, clean_prefix_fingerprint, config_fingerprint, ResumeToken,
+};
+use crate::sampling::AddressSampler;
+use crate::seed::{seed_to_string, SeedScheme, SEED_LEN};
+use crate::seen::{SeenFilter, SEEN_BLOCK_BITS, SEEN_BLOCK_SIZE};
+use crate::trace::{to_hex, DerivationTrace, TraceTiming};
+
+/// How many attempts `search_batch_until` makes between polls.
+pub const INTERRUPT_POLL_INTERVAL: u32 = 4096;
+
+/// A match; `A` is the derivation's [`Derivation::Artifacts`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch<A = [u8; SEED_LEN]> {
+    pub address: String,
+    /// What the address was derived from: the `createWithSeed` seed, or
+    /// e.g. a signer's secret.
+    pub seed: A,
+    pub attempts: u64,
+    /// Absolute counter the seed was generated from, after any
+    /// [`Searcher::set_counter_permutation`].
+    pub counter: u64,
+    /// With case ignored, where the address differs in case from the
+    /// pattern as typed (see [`MatchType::case_mismatch_positions`]).
+    /// Empty when the case matches exactly or is matched exactly anyway.
+    pub case_mismatch_positions: Vec<u32>,
+    /// Tag of the [`Searcher::set_seed_namespace`] the seed was derived
+    /// under; without it `counter` doesn't reproduce the seed.
+    pub seed_namespace: Option<String>,
+    /// [`Derivation::seed_scheme`] the seed was generated with, if the
+    /// derivation has one; `counter` reproduces the seed only under it.
+    pub seed_scheme: Option<SeedScheme>,
+}
+
+/// Most addresses [`Searcher::sample_addresses`] derives per call.
+pub const MAX_SAMPLES: u32 = 1000;
+
+/// An address derived for a counter, whether or not it matches.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DerivedAddress<A = [u8; SEED_LEN]> {
+    pub address: String,
+    pub seed: A,
+    /// Physical counter, as in [`SearchMatch::counter`].
+    pub counter: u64,
+}
+
+/// Most counters [`derive_counters`] takes at once.
+pub const MAX_DERIVE_COUNTERS: usize = 1_000_000;
+
+/// The address for each of `counters`, in order, as a search with
+/// `derivation` under `namespace` (if any) would derive it, e.g. to
+/// spot-check counters a worker claims. Fails with `InvalidOptions` for
+/// more than [`MAX_DERIVE_COUNTERS`].
+pub fn derive_counters<D: Derivation>(
+    derivation: &D,
+    namespace: Option<&SeedNamespace>,
+    counters: &[u64],
+) -> Result<Vec<DerivedAddress<D::Artifacts>>, VanityError> {
+    if counters.len() > MAX_DERIVE_COUNTERS {
+        return Err(VanityError::InvalidOptions(format!(
+            "can derive at most {MAX_DERIVE_COUNTERS} counters at once, got {}",
+            counters.len()
+        )));
+    }
+    let mut candidate = Candidate::default();
+    let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+    Ok(counters
+        .iter()
+        .map(|&counter| {
+            derive_in_namespace(derivation, namespace, counter, &mut candidate);
+            DerivedAddress {
+                address: encode_pubkey(&candidate.pubkey, &mut encoded_buf).to_string(),
+                seed: candidate.artifacts.clone(),
+                counter,
+            }
+        })
+        .collect())
+}
+
+/// What [`Searcher::search_range`] found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeOutcome<A = [u8; SEED_LEN]> {
+    /// Every match in the range, in counter order.
+    pub matches: Vec<DerivedAddress<A>>,
+    /// Counters scanned: the size of the range unless interrupted, in which
+    /// case they are the first `attempts` counters of the range.
+    pub attempts: u64,
+    /// Whether a stop cut the scan short.
+    pub interrupted: bool,
+}
+
+/// How a batch ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Found,
+    /// The whole batch ran without a match; call again.
+    NotFound,
+    /// `stop` was called, before or during the batch.
+    Stopped,
+    /// The attempt cap or the counter or seed space is used up.
+    Exhausted,
+    /// No match by the attempts at which one was as likely as the chance
+    /// given to [`Searcher::set_stop_at_probability`]: the pattern is
+    /// probably harder than estimated, or unlucky.
+    CoverageReached,
+    /// Something failed and stopped the search: a host callback, or the
+    /// searcher itself (see [`Searcher::fault`]).
+    Error,
+}
+
+impl<A> Default for RangeOutcome<A> {
+    fn default() -> RangeOutcome<A> {
+        RangeOutcome {
+            matches: Vec::new(),
+            attempts: 0,
+            interrupted: false,
+        }
+    }
+}
+
+/// See [`Searcher::set_stop_at_probability`].
+#[derive(Clone, Copy, Debug)]
+struct CoverageStop {
+    probability: f64,
+    /// Attempts at which `probability` is reached, kept so the search loop
+    /// doesn't take logarithms on every attempt.
+    attempts: u64,
+}
+
+/// Grinds the addresses a [`Derivation`] produces; `createWithSeed` unless
+/// told otherwise.
+#[derive(Clone)]
+pub struct Searcher<D: Derivation = CreateWithSeedDerivation> {
+    derivation: D,
+    matcher: AddressMatcher,
+    count: u64,
+    count_offset: u64,
+    should_exit: bool,
+    max_attempts: Option<u64>,
+    coverage_stop: Option<CoverageStop>,
+    /// See [`Searcher::resume_token`].
+    fingerprint: u64,
+    permutation: Option<CounterPermutation>,
+    namespace: Option<SeedNamespace>,
+    byte_mask: Option<ByteMask>,
+    sampler: Option<AddressSampler>,
+    replay: Option<ReplayLog>,
+    seen: Option<SeenFilter>,
+    /// Counters passed over because `seen` had them; part of `count`.
+    skipped: u64,
+    /// See [`Searcher::fault`].
+    fault: Option<VanityError>,
+    cancel_token: Option<CancelToken>,
+    partial: Option<PartialTracker>,
+}
+
+impl Searcher {
+    /// Searcher for a prefix and/or suffix. At least one non-blank pattern is
+    /// required, and it must be matchable (see [`MatchType::validate`]); use
+    /// [`Searcher::with_match_type`] to skip these checks.
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Result<Searcher, VanityError> {
+        Searcher::with_pattern(
+            CreateWithSeedDerivation::new(base_pubkey, owner_pubkey),
+            prefix,
+            suffix,
+            case_insensitive,
+            count_offset,
+        )
+    }
+
+    /// Searcher for an explicit match spec. No validation is done, so an empty
+    /// pattern (every address matches) is allowed, e.g. for benchmarking.
+    pub fn with_match_type(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        match_type: MatchType,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Searcher {
+        Searcher::with_derivation(
+            CreateWithSeedDerivation::new(base_pubkey, owner_pubkey),
+            match_type,
+            case_insensitive,
+            count_offset,
+        )
+    }
+
+    /// Searcher for pubkeys whose raw bytes match `byte_mask`, optionally
+    /// also starting with `prefix` (both must hold). The prefix is checked
+    /// as [`Searcher::new`] checks it, but may be absent; without one,
+    /// pubkeys are only base58-encoded once they match, which makes
+    /// byte-only searches much faster.
+    pub fn with_byte_mask(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        byte_mask: ByteMask,
+        prefix: Option<String>,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Result<Searcher, VanityError> {
+        let typed = MatchType::new(prefix.clone(), None, false);
+        let match_type = MatchType::new(prefix, None, case_insensitive);
+        match_type.validate(case_insensitive)?;
+        let mut searcher = Searcher::with_match_type(
+            base_pubkey,
+            owner_pubkey,
+            match_type,
+            case_insensitive,
+            count_offset,
+        );
+        searcher.set_typed_pattern(typed);
+        searcher.fingerprint = byte_mask_fingerprint(searcher.fingerprint, &byte_mask);
+        searcher.byte_mask = Some(byte_mask);
+        Ok(searcher)
+    }
+
+    /// Derives from `base_pubkey` and `owner_pubkey` from now on, e.g. after
+    /// the user switched wallets, keeping the pattern, options and attempts.
+    /// The old keys' hash midstate goes, as does the seen filter, which
+    /// described them, and resume tokens saved under them no longer apply.
+    /// With `reset_counter`, the search starts over at `count_offset` with
+    /// no attempts; otherwise it carries on from the current counter, which
+    /// is as good, since the new keys make every counter new.
+    pub fn set_keys(
+        &mut self,
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        reset_counter: bool,
+    ) {
+        let scheme = self.derivation.scheme();
+        self.set_derivation(CreateWithSeedDerivation::with_scheme(
+            base_pubkey,
+            owner_pubkey,
+            scheme,
+        ));
+        if reset_counter {
+            self.count = 0;
+            self.skipped = 0;
+        }
+    }
+
+    /// Derives the address for `seed` and returns it if it matches, for
+    /// loops that generate seeds themselves. A byte mask is checked first,
+    /// so pubkeys failing it are never encoded.
+    pub(crate) fn match_seed<'a>(
+        &self,
+        seed: &[u8; SEED_LEN],
+        encoded_buf: &'a mut [u8; five8::BASE58_ENCODED_32_MAX_LEN],
+    ) -> Result<Option<&'a str>, VanityError> {
+        self.matching_address(&self.derivation.derive(seed), encoded_buf)
+    }
+
+    /// Derives the address for `counter` one step at a time, through the
+    /// same functions the search loop calls, and reports every value along
+    /// the way. `counter` is one a result reports, i.e. after any counter
+    /// permutation; the seed namespace is applied as in a search. `now`
+    /// reads a millisecond clock to time the steps. The searcher's position
+    /// is not affected.
+    pub fn trace(&self, counter: u64, mut now: impl FnMut() -> f64) -> DerivationTrace {
+        let mut candidate = Candidate::default();
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let start = now();
+        derive_in_namespace(
+            &self.derivation,
+            self.namespace.as_ref(),
+            counter,
+            &mut candidate,
+        );
+        let derived = now();
+        let address = encode_pubkey(&candidate.pubkey, &mut encoded_buf);
+        let encoded = now();
+        let byte_mask_passed = self
+            .byte_mask
+            .as_ref()
+            .map(|byte_mask| byte_mask.matches(&candidate.pubkey));
+        let matches = byte_mask_passed != Some(false) && self.matcher.matches(address);
+        let matched = now();
+
+        let seed = candidate.artifacts;
+        let seed_counter = self
+            .namespace
+            .as_ref()
+            .map_or(counter, |namespace| namespace.apply(counter));
+        DerivationTrace {
+            counter: counter.to_string(),
+            seed_counter: seed_counter.to_string(),
+            seed_namespace: self.namespace.as_ref().map(|ns| ns.tag().to_string()),
+            seed: seed_to_string(&seed),
+            seed_hex: to_hex(&seed),
+            hash_input_hex: to_hex(&self.derivation.hash_input(&seed)),
+            digest_hex: to_hex(&candidate.pubkey),
+            address: address.to_string(),
+            byte_mask_passed,
+            matches,
+            checks: self.matcher.explain(address).checks,
+            timing: TraceTiming {
+                derive_ms: derived - start,
+                encode_ms: encoded - derived,
+                match_ms: matched - encoded,
+            },
+        }
+    }
+}
+
+impl<D: Derivation> Searcher<D> {
+    /// [`Searcher::new`] for any derivation: the pattern is checked the same
+    /// way.
+    pub fn with_pattern(
+        derivation: D,
+        prefix: Option<String>,
+        suffix: Option<String>,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Result<Searcher<D>, VanityError> {
+        let matcher = AddressMatcher::new(prefix, suffix, case_insensitive)?;
+        Ok(Searcher::with_matcher(derivation, matcher, count_offset))
+    }
+
+    /// [`Searcher::with_match_type`] for any derivation, equally unchecked.
+    pub fn with_derivation(
+        derivation: D,
+        match_type: MatchType,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Searcher<D> {
+        let matcher = AddressMatcher::with_match_type(match_type, case_insensitive);
+        Searcher::with_matcher(derivation, matcher, count_offset)
+    }
+
+    /// A searcher testing addresses with `matcher`, as it is: its checks,
+    /// if any, were made when it was built.
+    pub fn with_matcher(derivation: D, matcher: AddressMatcher, count_offset: u64) -> Searcher<D> {
+        let fingerprint = config_fingerprint(
+            &derivation.identity(),
+            matcher.match_type(),
+            matcher.case_insensitive(),
+        );
+
+        Searcher {
+            derivation,
+            matcher,
+            count: 0,
+            count_offset,
+            should_exit: false,
+            max_attempts: None,
+            coverage_stop: None,
+            fingerprint,
+            permutation: None,
+            namespace: None,
+            byte_mask: None,
+            sampler: None,
+            replay: None,
+            seen: None,
+            skipped: 0,
+            fault: None,
+            cancel_token: None,
+            partial: None,
+        }
+    }
+
+    /// Keeps `typed`, the pattern before case folding, so case-insensitive
+    /// matches report [`SearchMatch::case_mismatch_positions`]. Set by
+    /// [`Searcher::new`]; callers of [`Searcher::with_match_type`] pass the
+    /// unfolded patterns here. Ignored when matching case-sensitively.
+    pub fn set_typed_pattern(&mut self, typed: MatchType) {
+        self.matcher.set_typed_pattern(typed);
+    }
+
+    /// What addresses are tested with.
+    pub fn matcher(&self) -> &AddressMatcher {
+        &self.matcher
+    }
+
+    /// The derivation addresses come from.
+    pub fn derivation(&self) -> &D {
+        &self.derivation
+    }
+
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<SearchMatch<D::Artifacts>> {
+        self.search_batch_until(batch_size, || false)
+    }
+
+    /// Like `search_batch`, but polls `interrupted` every
+    /// [`INTERRUPT_POLL_INTERVAL`] attempts (starting with the first) and
+    /// stops the searcher as soon as it returns true.
+    pub fn search_batch_until(
+        &mut self,
+        batch_size: u32,
+        interrupted: impl FnMut() -> bool,
+    ) -> Option<SearchMatch<D::Artifacts>> {
+        // Separate loops, so sampling, the replay log and partial tracking
+        // cost nothing unless one is on.
+        if self.sampler.is_some() || self.replay.is_some() || self.partial.is_some() {
+            self.search_loop::<true>(batch_size, interrupted)
+        } else {
+            self.search_loop::<false>(batch_size, interrupted)
+        }
+    }
+
+    fn search_loop<const SAMPLING: bool>(
+        &mut self,
+        batch_size: u32,
+        mut interrupted: impl FnMut() -> bool,
+    ) -> Option<SearchMatch<D::Artifacts>> {
+        let mut candidate = Candidate::default();
+        // Counters covered by this batch so far; a skipped block covers many
+        // at once, so polls come at the first count past each interval.
+        let mut covered = 0;
+        let mut next_poll = 0;
+        while covered < batch_size {
+            if covered >= next_poll {
+                next_poll = (covered / INTERRUPT_POLL_INTERVAL + 1) * INTERRUPT_POLL_INTERVAL;
+                if !self.should_exit && (self.is_cancelled() || interrupted()) {
+                    self.stop();
+                }
+            }
+            if self.should_exit || self.is_exhausted() {
+                return None;
+            }
+
+            // Past u64::MAX there are no counters left; never wrap around
+            // into another searcher's range.
+            let logical = self.count_offset.checked_add(self.count)?;
+            if covered == 0 || logical % SEEN_BLOCK_SIZE == 0 {
+                let skipped = self.skip_seen_block(logical, batch_size - covered);
+                if skipped > 0 {
+                    covered += skipped;
+                    continue;
+                }
+            }
+            let counter = self.candidate_at(logical, &mut candidate);
+            let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+            let found = if SAMPLING {
+                if let Some(replay) = self.replay.as_mut() {
+                    replay.observe(counter, &candidate.pubkey);
+                }
+                // Samples are of every address, so each is encoded in full.
+                let encoded = match self.encode_candidate(&candidate.pubkey, &mut encoded_buf) {
+                    Ok(encoded) => encoded,
+                    Err(error) => {
+                        self.record_fault(error);
+                        return None;
+                    }
+                };
+                if let (Some(sampler), Some(address)) = (self.sampler.as_mut(), encoded) {
+                    if sampler.tick() {
+                        sampler.record(counter, address);
+                    }
+                }
+                if let Some(partial) = self.partial.as_mut() {
+                    let attempts = self.count + 1;
+                    partial.observe(&self.matcher, &candidate.pubkey, encoded, counter, attempts);
+                }
+                encoded.filter(|address| self.matcher.matches(address))
+            } else {
+                match self.matching_address(&candidate.pubkey, &mut encoded_buf) {
+                    Ok(found) => found,
+                    Err(error) => {
+                        self.record_fault(error);
+                        return None;
+                    }
+                }
+            };
+
+            self.count += 1;
+            covered += 1;
+
+            if let Some(address) = found {
+                return Some(SearchMatch {
+                    address: address.to_string(),
+                    seed: candidate.artifacts,
+                    attempts: self.count,
+                    counter,
+                    case_mismatch_positions: self.matcher.case_mismatch_positions(address),
+                    seed_namespace: self.namespace.as_ref().map(|ns| ns.tag().to_string()),
+                    seed_scheme: self.derivation.seed_scheme(),
+                });
+            }
+        }
+
+        None
+    }
+
+    pub fn search_range(&mut self, start: u64, end: u64) -> RangeOutcome<D::Artifacts> {
+        self.search_range_until(start, end, || false)
+    }
+
+    /// Scans exactly the counters in `start..end` and reports every match,
+    /// for work units handed out by an external coordinator. The scan is
+    /// independent of this searcher's own position: attempts, `count_offset`
+    /// and the attempt cap are neither used nor updated, so the same range
+    /// always gives the same outcome. The range is of logical counters, so
+    /// with a permutation the matches report scattered physical ones. A
+    /// stop, before or during the scan (`interrupted` is polled as in
+    /// `search_batch_until`), ends it early.
+    pub fn search_range_until(
+        &mut self,
+        start: u64,
+        end: u64,
+        mut interrupted: impl FnMut() -> bool,
+    ) -> RangeOutcome<D::Artifacts> {
+        let mut outcome = RangeOutcome::default();
+        let mut candidate = Candidate::default();
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for logical in start..end {
+            if outcome.attempts % INTERRUPT_POLL_INTERVAL as u64 == 0
+                && !self.should_exit
+                && (self.is_cancelled() || interrupted())
+            {
+                self.stop();
+            }
+            if self.should_exit {
+                outcome.interrupted = true;
+                break;
+            }
+
+            let counter = self.candidate_at(logical, &mut candidate);
+            let found = match self.matching_address(&candidate.pubkey, &mut encoded_buf) {
+                Ok(found) => found,
+                Err(error) => {
+                    self.record_fault(error);
+                    outcome.interrupted = true;
+                    break;
+                }
+            };
+            if let Some(address) = found {
+                outcome.matches.push(DerivedAddress {
+                    address: address.to_string(),
+                    seed: candidate.artifacts.clone(),
+                    counter,
+                });
+            }
+            outcome.attempts += 1;
+        }
+        outcome
+    }
+
+    /// Derives `n` consecutive addresses (at most [`MAX_SAMPLES`]) from
+    /// counter `start` on, the same way a search would, but without matching
+    /// them or touching this searcher's position. Stops early at the end of
+    /// the counter space.
+    pub fn sample_addresses(&self, start: u64, n: u32) -> Vec<DerivedAddress<D::Artifacts>> {
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let mut candidate = Candidate::default();
+        (0..n.min(MAX_SAMPLES) as u64)
+            .map_while(|i| start.checked_add(i))
+            .map(|logical| {
+                let counter = self.candidate_at(logical, &mut candidate);
+                let address = encode_pubkey(&candidate.pubkey, &mut encoded_buf);
+                DerivedAddress {
+                    address: address.to_string(),
+                    seed: candidate.artifacts.clone(),
+                    counter,
+                }
+            })
+            .collect()
+    }
+
+    /// `pubkey` encoded if it matches. A byte mask and a suffix are checked
+    /// before encoding, so most pubkeys never are.
+    fn matching_address<'a>(
+        &self,
+        pubkey: &[u8; PUBKEY_LEN],
+        encoded_buf: &'a mut [u8; five8::BASE58_ENCODED_32_MAX_LEN],
+    ) -> Result<Option<&'a str>, VanityError> {
+        if self.matcher.rules_out(pubkey) {
+            return Ok(None);
+        }
+        let encoded = self.encode_candidate(pubkey, encoded_buf)?;
+        Ok(encoded.filter(|address| self.matcher.matches(address)))
+    }
+
+    /// `pubkey` encoded, unless a byte mask rules it out unencoded.
+    fn encode_candidate<'a>(
+        &self,
+        pubkey: &[u8; PUBKEY_LEN],
+        encoded_buf: &'a mut [u8; five8::BASE58_ENCODED_32_MAX_LEN],
+    ) -> Result<Option<&'a str>, VanityError> {
+        if let Some(byte_mask) = &self.byte_mask {
+            if !byte_mask.matches(pubkey) {
+                return Ok(None);
+            }
+        }
+        try_encode_pubkey(pubkey, encoded_buf).map(Some)
+    }
+
+    /// Stops the search on a broken invariant, keeping the error for
+    /// [`Searcher::fault`] rather than panicking.
+    #[cold]
+    pub(crate) fn record_fault(&mut self, error: VanityError) {
+        self.fault = Some(error);
+        self.should_exit = true;
+    }
+
+    /// What stopped the search if it was the searcher itself, e.g. an
+    /// encoder breaking its contract: an `Internal` error. Such a searcher
+    /// reports [`BatchStatus::Error`] until [`Searcher::restart`].
+    pub fn fault(&self) -> Option<&VanityError> {
+        self.fault.as_ref()
+    }
+
+    /// Maps a logical counter through the permutation, if any, fills `out`
+    /// with the candidate for the resulting physical counter and returns it.
+    fn candidate_at(&self, logical: u64, out: &mut Candidate<D::Artifacts>) -> u64 {
+        let counter = match &self.permutation {
+            Some(permutation) => permutation.apply(logical),
+            None => logical,
+        };
+        derive_in_namespace(&self.derivation, self.namespace.as_ref(), counter, out);
+        counter
+    }
+
+    /// A fresh searcher with the same keys, pattern, attempt cap and counter
+    /// permutation, starting at `count_offset` instead, and without
+    /// sampling. `self` is left untouched.
+    pub fn fork(&self, count_offset: u64) -> Searcher<D> {
+        Searcher {
+            count: 0,
+            count_offset,
+            skipped: 0,
+            should_exit: false,
+            fault: None,
+            sampler: None,
+            replay: None,
+            partial: None,
+            ..self.clone()
+        }
+    }
+
+    /// Records every `every`th address `search_batch` derives into a ring
+    /// of the latest `capacity`, read with [`Searcher::drain_samples`];
+    /// `None` turns sampling off and drops the ring. Addresses a byte mask
+    /// rejects are never encoded, so with one only the counted attempts
+    /// that pass it are recorded. Fails as [`AddressSampler::new`] does.
+    pub fn set_sampling(&mut self, sampling: Option<(u32, u32)>) -> Result<(), VanityError> {
+        self.sampler = sampling
+            .map(|(capacity, every)| AddressSampler::new(capacity, every))
+            .transpose()?;
+        Ok(())
+    }
+
+    pub fn sampler(&self) -> Option<&AddressSampler> {
+        self.sampler.as_ref()
+    }
+
+    /// Logs a digest of the pubkey `search_batch` derives for every counter
+    /// that is a multiple of `every`, keeping the latest `capacity`, to
+    /// check another build against with [`crate::compare_replay`]; `None`
+    /// turns the log off and drops it. Fails as [`ReplayLog::new`] does.
+    pub fn set_replay_log(&mut self, replay: Option<(u32, u64)>) -> Result<(), VanityError> {
+        self.replay = replay
+            .map(|(capacity, every)| ReplayLog::new(capacity, every))
+            .transpose()?;
+        Ok(())
+    }
+
+    pub fn replay_log(&self) -> Option<&ReplayLog> {
+        self.replay.as_ref()
+    }
+
+    /// Keeps [`PartialStats`] and the closest address over every attempt
+    /// from now on, for a search that ends without a match; `false` stops
+    /// and drops them. Tracking encodes every address in full, like
+    /// sampling. Already tracking, `true` keeps what was gathered; new keys,
+    /// seed scheme or namespace start it over, since the closest address
+    /// couldn't be reproduced under them.
+    pub fn set_partial_tracking(&mut self, track: bool) {
+        match (track, self.partial.is_some()) {
+            (true, false) => self.partial = Some(PartialTracker::new(&self.matcher)),
+            (false, _) => self.partial = None,
+            (true, true) => {}
+        }
+    }
+
+    fn restart_partial(&mut self) {
+        if self.partial.is_some() {
+            self.partial = Some(PartialTracker::new(&self.matcher));
+        }
+    }
+
+    /// What partial tracking has gathered; `None` unless it is on.
+    pub fn partial_stats(&self) -> Option<&PartialStats> {
+        self.partial.as_ref().map(PartialTracker::stats)
+    }
+
+    /// The tracked address that came closest to matching, with its seed
+    /// and the attempts made when it was derived, as if it had matched.
+    /// `None` unless partial tracking is on and has seen an address.
+    pub fn closest_match(&self) -> Option<SearchMatch<D::Artifacts>> {
+        let (address, counter, attempts) = self.partial.as_ref()?.closest()?;
+        let mut candidate = Candidate::default();
+        derive_in_namespace(
+            &self.derivation,
+            self.namespace.as_ref(),
+            counter,
+            &mut candidate,
+        );
+        Some(SearchMatch {
+            address: address.to_string(),
+            seed: candidate.artifacts,
+            attempts,
+            counter,
+            case_mismatch_positions: self.matcher.case_mismatch_positions(address),
+            seed_namespace: self.namespace.as_ref().map(|ns| ns.tag().to_string()),
+            seed_scheme: self.derivation.seed_scheme(),
+        })
+    }
+
+    /// Sampled addresses since the last drain, oldest first; empty if
+    /// sampling is off.
+    pub fn drain_samples(&mut self) -> Vec<DerivedAddress<D::Artifacts>> {
+        let Some(sampler) = self.sampler.as_mut() else {
+            return Vec::new();
+        };
+        let (derivation, namespace) = (&self.derivation, self.namespace.as_ref());
+        let mut candidate = Candidate::default();
+        sampler.drain_with(|counter| {
+            derive_in_namespace(derivation, namespace, counter, &mut candidate);
+            candidate.artifacts.clone()
+        })
+    }
+
+    pub fn stop(&mut self) {
+        self.should_exit = true;
+    }
+
+    /// Whether `stop` was called. A stopped searcher does no work, so a `None`
+    /// from `search_batch` means "stopped" rather than "no match yet".
+    pub fn is_stopped(&self) -> bool {
+        self.should_exit
+    }
+
+    /// Clears a previous `stop`, continuing from the current counter.
+    pub fn restart(&mut self) {
+        self.should_exit = false;
+        self.fault = None;
+    }
+
+    /// Stops the searcher once `token` is cancelled, polled along with the
+    /// `interrupted` of [`Searcher::search_batch_until`]; `None` detaches
+    /// it.
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) {
+        self.cancel_token = token;
+    }
+
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel_token.as_ref()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Caps the attempts (excluding `count_offset`) this searcher will make;
+    /// `None` removes the cap. Once reached, `search_batch` does nothing
+    /// until the cap is raised or removed.
+    pub fn set_max_attempts(&mut self, max_attempts: Option<u64>) {
+        self.max_attempts = max_attempts;
+    }
+
+    pub fn max_attempts(&self) -> Option<u64> {
+        self.max_attempts
+    }
+
+    /// Gives up once the attempts made (excluding `count_offset`) would
+    /// have found a match with chance `probability` at the pattern's odds,
+    /// e.g. 0.95 stops after about three times the expected attempts. Like
+    /// the attempt cap, whichever comes first ends the search, but batch
+    /// status is `CoverageReached` rather than `Exhausted`. `None` removes
+    /// it. Fails with `InvalidOptions` unless `0 < probability < 1`.
+    pub fn set_stop_at_probability(&mut self, probability: Option<f64>) -> Result<(), VanityError> {
+        if let Some(probability) = probability.filter(|p| !(*p > 0.0 && *p < 1.0)) {
+            return Err(VanityError::InvalidOptions(format!(
+                "stop probability must be between 0 and 1 (exclusive), got {probability}"
+            )));
+        }
+        self.coverage_stop = probability.map(|probability| CoverageStop {
+            probability,
+            attempts: 0,
+        });
+        self.update_coverage_stop();
+        Ok(())
+    }
+
+    pub fn stop_at_probability(&self) -> Option<f64> {
+        self.coverage_stop.map(|stop| stop.probability)
+    }
+
+    /// Attempts at which `set_stop_at_probability` stops the search; `None`
+    /// without one, `u64::MAX` if the pattern can't match.
+    pub fn coverage_stop_attempts(&self) -> Option<u64> {
+        self.coverage_stop.map(|stop| stop.attempts)
+    }
+
+    /// Chance that the attempts made so far would have found a match, i.e.
+    /// how much of the likely search space they cover.
+    pub fn coverage(&self) -> f64 {
+        probability_found(self.match_probability(), self.count)
+    }
+
+    /// Skips counters another session already covered: while a filter is
+    /// set, each block it reports as covered is passed over, as if searched,
+    /// without deriving anything, up to what is left of the batch. Skipped
+    /// counters count towards `attempts` (and the cap and coverage), since
+    /// the search space they stand for is done, and are tallied in
+    /// `skipped`. A false positive skips a block nobody searched; see
+    /// [`SeenFilter`]. `None` stops skipping.
+    pub fn set_seen_filter(&mut self, seen: Option<SeenFilter>) {
+        self.seen = seen;
+    }
+
+    pub fn seen_filter(&self) -> Option<&SeenFilter> {
+        self.seen.as_ref()
+    }
+
+    /// Counters skipped as already covered, out of `attempts`.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Moves past the rest of `logical`'s block if the seen filter has it,
+    /// never beyond `budget` counters or the attempt cap. Returns how many
+    /// counters it skipped.
+    fn skip_seen_block(&mut self, logical: u64, budget: u32) -> u32 {
+        let Some(seen) = &self.seen else {
+            return 0;
+        };
+        if !seen.maybe_covered(logical) {
+            return 0;
+        }
+        // In u128, so the last block ends at 2^64 and takes u64::MAX with it.
+        let block_end = (u128::from(logical >> SEEN_BLOCK_BITS) + 1) << SEEN_BLOCK_BITS;
+        let mut skip = (block_end - u128::from(logical)).min(u128::from(budget)) as u32;
+        if let Some(max) = self.max_attempts {
+            skip = skip.min(max.saturating_sub(self.count).min(u64::from(u32::MAX)) as u32);
+        }
+        self.count += u64::from(skip);
+        self.skipped += u64::from(skip);
+        skip
+    }
+
+    /// Recomputes the attempts for the stop probability from the current
+    /// match probability.
+    fn update_coverage_stop(&mut self) {
+        let probability = self.match_probability();
+        if let Some(stop) = self.coverage_stop.as_mut() {
+            // Saturates, so an impossible pattern never stops on coverage.
+            stop.attempts = attempts_for_confidence(probability, stop.probability) as u64;
+        }
+    }
+
+    /// Maps every logical counter (`count_offset + attempts`, and the
+    /// counters given to `search_range` and `sample_addresses`) through the
+    /// bijection keyed by `key` before generating its seed; `None` walks
+    /// counters in order again. With a fresh random key per session, a
+    /// restart from offset 0 no longer re-grinds the counters an earlier
+    /// session already covered, while ranges handed to workers still never
+    /// overlap. The key is saved in the resume token; changing it mid-search
+    /// doesn't revisit or skip logical counters, but the physical ones
+    /// already covered are no longer accounted for.
+    pub fn set_counter_permutation(&mut self, key: Option<u64>) {
+        self.permutation = key.map(CounterPermutation::new);
+    }
+
+    pub fn counter_permutation_key(&self) -> Option<u64> {
+        self.permutation.as_ref().map(CounterPermutation::key)
+    }
+
+    /// Folds `namespace` into every counter before deriving from it (after
+    /// any permutation), so sessions with different namespaces try
+    /// different seeds from the same counters; `None` goes back to plain
+    /// counters. Reported counters stay unmixed: a match is reproduced from
+    /// its counter and the namespace together. Saved in the resume token.
+    pub fn set_seed_namespace(&mut self, namespace: Option<SeedNamespace>) {
+        self.namespace = namespace;
+        self.restart_partial();
+    }
+
+    pub fn seed_namespace(&self) -> Option<&SeedNamespace> {
+        self.namespace.as_ref()
+    }
+
+    /// See [`Derivation::seed_scheme`].
+    pub fn seed_scheme(&self) -> Option<SeedScheme> {
+        self.derivation.seed_scheme()
+    }
+
+    /// Generates seeds with `scheme` from now on, e.g. to carry on a search
+    /// saved under an older one, keeping the position. As with
+    /// [`Searcher::set_keys`], the seen filter goes and resume tokens saved
+    /// under the old scheme no longer apply. Fails with `InvalidOptions` if
+    /// the derivation has no choice of scheme.
+    pub fn set_seed_scheme(&mut self, scheme: SeedScheme) -> Result<(), VanityError> {
+        if self.derivation.seed_scheme() == Some(scheme) {
+            return Ok(());
+        }
+        let derivation = self.derivation.with_seed_scheme(scheme).ok_or_else(|| {
+            VanityError::InvalidOptions("this derivation has no seed schemes".into())
+        })?;
+        self.set_derivation(derivation);
+        Ok(())
+    }
+
+    /// Replaces the derivation along with everything describing it: the
+    /// fingerprint and the seen filter.
+    fn set_derivation(&mut self, derivation: D) {
+        self.fingerprint = self.base_fingerprint(&derivation);
+        self.derivation = derivation;
+        self.seen = None;
+        self.restart_partial();
+    }
+
+    /// [`config_fingerprint`] for `derivation` and the searcher's pattern,
+    /// with any byte mask folded in but not the clean prefix.
+    fn base_fingerprint(&self, derivation: &D) -> u64 {
+        let fingerprint = config_fingerprint(
+            &derivation.identity(),
+            self.matcher.match_type(),
+            self.matcher.case_insensitive(),
+        );
+        match &self.byte_mask {
+            Some(byte_mask) => byte_mask_fingerprint(fingerprint, byte_mask),
+            None => fingerprint,
+        }
+    }
+
+    /// Whether the search can't go on: the attempt cap or the stop
+    /// probability was reached, or the counter or seed space is used up. Unlike
+    /// `stop`, `restart` doesn't clear this.
+    pub fn is_exhausted(&self) -> bool {
+        self.max_attempts.is_some_and(|max| self.count >= max)
+            || self
+                .coverage_stop
+                .is_some_and(|stop| self.count >= stop.attempts)
+            || self.absolute_position().is_none()
+    }
+
+    /// Status of the batch that just ran, given whether it found a match.
+    /// A stop takes precedence over exhaustion, and the attempt cap over the
+    /// stop probability when both are reached.
+    pub fn batch_status(&self, found: bool) -> BatchStatus {
+        if found {
+            BatchStatus::Found
+        } else if self.fault.is_some() {
+            BatchStatus::Error
+        } else if self.should_exit {
+            BatchStatus::Stopped
+        } else if self.max_attempts.is_some_and(|max| self.count >= max) {
+            BatchStatus::Exhausted
+        } else if self
+            .coverage_stop
+            .is_some_and(|stop| self.count >= stop.attempts)
+        {
+            BatchStatus::CoverageReached
+        } else if self.is_exhausted() {
+            BatchStatus::Exhausted
+        } else {
+            BatchStatus::NotFound
+        }
+    }
+
+    /// Bytes this searcher occupies, inline and on the heap.
+    pub fn memory_bytes(&self) -> usize {
+        core::mem::size_of::<Searcher<D>>()
+            + self.matcher.heap_bytes()
+            + self.sampler.as_ref().map_or(0, AddressSampler::heap_bytes)
+            + self.replay.as_ref().map_or(0, ReplayLog::heap_bytes)
+    }
+
+    /// Releases spare capacity in the searcher's own buffers.
+    pub fn shrink(&mut self) {
+        self.matcher.shrink_to_fit();
+    }
+
+    /// Attempts made by this searcher, not counting its offset.
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// First and last logical counters, inclusive, covered since the
+    /// searcher had made `since_attempts` attempts, or `None` if it has
+    /// covered none since (or started over). Exact however the batches in
+    /// between ended, so consecutive spans tile the counters with no gap or
+    /// overlap.
+    pub fn counters_since(&self, since_attempts: u64) -> Option<(u64, u64)> {
+        if self.count <= since_attempts {
+            return None;
+        }
+        Some((
+            self.count_offset + since_attempts,
+            self.count_offset + (self.count - 1),
+        ))
+    }
+
+    /// Absolute counter the next attempt will use (`count_offset + attempts`),
+    /// or `None` once the counter space, or a smaller seed space, is used
+    /// up.
+    pub fn absolute_position(&self) -> Option<u64> {
+        let position = self.count_offset.checked_add(self.count)?;
+        match self.seed_space() {
+            Some(space) if position >= space => None,
+            _ => Some(position),
+        }
+    }
+
+    /// Distinct seeds the derivation has, if fewer than there are counters
+    /// (see [`Derivation::seed_space`]). The search is exhausted once
+    /// counters reach it, rather than trying the same seeds again. A
+    /// counter permutation or seed namespace maps counters across the whole
+    /// counter space, which then wraps around the seed space, so the seeds
+    /// tried are no longer all distinct.
+    pub fn seed_space(&self) -> Option<u64> {
+        self.derivation.seed_space()
+    }
+
+    /// Fraction of the seed space this searcher has tried, with a bounded
+    /// one.
+    pub fn seed_space_coverage(&self) -> Option<f64> {
+        self.seed_space()
+            .map(|space| (self.count as f64 / space as f64).min(1.0))
+    }
+
+    /// How the attempts made so far compare with the odds for the pattern,
+    /// e.g. after a match. Accounts for case folding and for both sides of a
+    /// prefix-and-suffix pattern through [`Searcher::match_probability`].
+    pub fn luck_stats(&self) -> LuckStats {
+        luck_stats(self.match_probability(), self.count)
+    }
+
+    /// Hash of the keys, pattern, case flag, seed scheme and any byte mask
+    /// or clean prefix: what decides whether two searchers try the same
+    /// addresses for the same counters. Resume tokens carry it.
+    pub fn config_fingerprint(&self) -> u64 {
+        self.fingerprint()
+    }
+
+    /// Compact string recording the position (`count_offset`, attempts, any
+    /// counter permutation key and seed namespace) together with a hash of
+    /// the keys, pattern, case flag and seed scheme, for persisting
+    /// progress. See [`Searcher::resume_from`].
+    pub fn resume_token(&self) -> String {
+        ResumeToken {
+            fingerprint: self.fingerprint(),
+            count_offset: self.count_offset,
+            attempts: self.count,
+            permutation_key: self.counter_permutation_key(),
+            namespace: self.namespace.clone(),
+            seed_scheme: self
+                .derivation
+                .seed_scheme()
+                .filter(|&scheme| scheme != SeedScheme::V1),
+        }
+        .encode()
+    }
+
+    /// Moves to the position saved in `token`, restoring its offset, attempt
+    /// count, counter permutation and seed namespace (or their absence).
+    /// A derivation with seed schemes switches to the token's, V1 for tokens
+    /// that don't name one, so progress saved under an older scheme carries
+    /// on with its seeds. Fails without changing anything if the token is
+    /// malformed or was saved by a searcher with a different configuration.
+    /// The stop state and attempt cap are left as they are.
+    pub fn resume_from(&mut self, token: &str) -> Result<(), VanityError> {
+        self.resume_from_as(token, SeedScheme::V1)
+    }
+
+    /// [`Searcher::resume_from`], taking a token that doesn't name its
+    /// scheme to be `unnamed`'s rather than V1's: pass
+    /// [`SeedScheme::LegacyWasm32`] for a token saved by a web build from
+    /// before schemes were versioned. A token that names its scheme keeps
+    /// it.
+    pub fn resume_from_as(&mut self, token: &str, unnamed: SeedScheme) -> Result<(), VanityError> {
+        let token = ResumeToken::parse(token)?;
+        let scheme = token.seed_scheme.unwrap_or(unnamed);
+        let rederived = match self.derivation.seed_scheme() {
+            Some(current) if current != scheme => self.derivation.with_seed_scheme(scheme),
+            _ => None,
+        };
+        let base_fingerprint = match &rederived {
+            Some(derivation) => self.base_fingerprint(derivation),
+            None => self.fingerprint,
+        };
+        if token.fingerprint != self.with_clean_prefix(base_fingerprint) {
+            return Err(VanityError::ResumeTokenMismatch);
+        }
+        if let Some(derivation) = rederived {
+            self.set_derivation(derivation);
+        }
+        self.count_offset = token.count_offset;
+        self.count = token.attempts;
+        // Tokens don't say how many of their attempts were skipped.
+        self.skipped = 0;
+        self.set_counter_permutation(token.permutation_key);
+        self.set_seed_namespace(token.namespace);
+        Ok(())
+    }
+
+    /// Per-attempt probability of a match for the configured pattern (and
+    /// byte mask, if any).
+    pub fn match_probability(&self) -> f64 {
+        self.matcher.probability() * self.byte_mask.as_ref().map_or(1.0, ByteMask::probability)
+    }
+
+    /// See [`Searcher::resume_token`]; a clean prefix is part of it.
+    fn fingerprint(&self) -> u64 {
+        self.with_clean_prefix(self.fingerprint)
+    }
+
+    fn with_clean_prefix(&self, fingerprint: u64) -> u64 {
+        match self.matcher.clean_prefix() {
+            Some(clean_prefix) => clean_prefix_fingerprint(fingerprint, clean_prefix),
+            None => fingerprint,
+        }
+    }
+
+    /// Also requires matches to pass `clean_prefix`, on top of the pattern;
+    /// `None` drops the requirement. Fails with `ConflictingOptions` if a
+    /// case-sensitive prefix itself breaks it, as then nothing could match.
+    /// Resume tokens saved with a different clean prefix no longer apply.
+    pub fn set_clean_prefix(
+        &mut self,
+        clean_prefix: Option<CleanPrefix>,
+    ) -> Result<(), VanityError> {
+        self.matcher.set_clean_prefix(clean_prefix)?;
+        self.update_coverage_stop();
+        Ok(())
+    }
+
+    pub fn clean_prefix(&self) -> Option<&CleanPrefix> {
+        self.matcher.clean_prefix()
+    }
+
+    pub fn byte_mask(&self) -> Option<&ByteMask> {
+        self.byte_mask.as_ref()
+    }
+}
+
+/// The candidate for `counter`, mixed with `namespace` first if there is one.
+fn derive_in_namespace<D: Derivation>(
+    derivation: &D,
+    namespace: Option<&SeedNamespace>,
+    counter: u64,
+    out: &mut Candidate<D::Artifacts>,
+) {
+    let counter = namespace.map_or(counter, |namespace| namespace.apply(counter));
+    derivation.candidate(counter, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::Deriver;
+    use crate::seed::generate_seed_from_counter;
+
+    #[test]
+    fn test_search_batch_finds_prefix() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0).unwrap();
+
+        let found = loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                break found;
+            }
+        };
+        assert!(found.address.starts_with('A'));
+        assert_eq!(found.attempts, searcher.attempts());
+    }
+
+    #[test]
+    fn test_found_seed_rederives_address() {
+        let base = [2; 32];
+        let owner = [3; 32];
+        let mut searcher = Searcher::new(&base, &owner, None, Some("x".into()), true, 99).unwrap();
+
+        let found = loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                break found;
+            }
+        };
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let pubkey = Deriver::new(&base, &owner).derive(&found.seed);
+        assert_eq!(encode_pubkey(&pubkey, &mut buf), found.address);
+        assert_eq!(found.counter, 99 + found.attempts - 1);
+        assert_eq!(found.seed, generate_seed_from_counter(found.counter));
+    }
+
+    #[test]
+    fn test_case_insensitive_matches_report_case_differences() {
+        let mut searcher = Searcher::new(
+            &[2; 32],
+            &[3; 32],
+            Some("B".into()),
+            Some("x".into()),
+            true,
+            0,
+        )
+        .unwrap();
+        let mut seen_exact = false;
+        let mut seen_mixed = false;
+        while !(seen_exact && seen_mixed) {
+            let Some(found) = searcher.search_batch(100_000) else {
+                continue;
+            };
+            let address = found.address.as_bytes();
+            let last = address.len() - 1;
+            let expected: Vec<u32> = [(0, b'B'), (last, b'x')]
+                .into_iter()
+                .filter(|&(i, typed)| address[i] != typed)
+                .map(|(i, _)| i as u32)
+                .collect();
+            assert_eq!(found.case_mismatch_positions, expected, "{}", found.address);
+            seen_exact |= expected.is_empty();
+            seen_mixed |= !expected.is_empty();
+        }
+
+        let mut exact =
+            Searcher::new(&[2; 32], &[3; 32], Some("a".into()), None, false, 0).unwrap();
+        let found = exact.search_batch(100_000).unwrap();
+        assert_eq!(found.case_mismatch_positions, []);
+    }
+
+    #[test]
+    fn test_empty_pattern_is_rejected() {
+        for (prefix, suffix) in [(None, None), (Some(""), None), (Some(" "), Some("\t"))] {
+            let result = Searcher::new(
+                &[1; 32],
+                &[4; 32],
+                prefix.map(Into::into),
+                suffix.map(Into::into),
+                false,
+                0,
+            );
+            assert_eq!(result.err(), Some(VanityError::EmptyPattern));
+        }
+    }
+
+    #[test]
+    fn test_unmatchable_pattern_is_rejected() {
+        let result = Searcher::new(&[1; 32], &[4; 32], Some("0x".into()), None, false, 0);
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(crate::ErrorKind::InvalidPatternChar)
+        );
+        let result = Searcher::new(&[1; 32], &[4; 32], None, Some("z".repeat(45)), false, 0);
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(crate::ErrorKind::PatternTooLong)
+        );
+    }
+
+    #[test]
+    fn test_empty_pattern_opt_in_matches_immediately() {
+        let match_type = MatchType::new(None, None, false);
+        let mut searcher = Searcher::with_match_type(&[1; 32], &[4; 32], match_type, false, 0);
+        let found = searcher.search_batch(10).unwrap();
+        assert_eq!(found.attempts, 1);
+    }
+
+    #[test]
+    fn test_single_sided_patterns_are_accepted() {
+        let new = |prefix: Option<&str>, suffix: Option<&str>| {
+            Searcher::new(
+                &[1; 32],
+                &[4; 32],
+                prefix.map(Into::into),
+                suffix.map(Into::into),
+                false,
+                0,
+            )
+        };
+        assert!(new(Some("A"), None).is_ok());
+        assert!(new(None, Some("z")).is_ok());
+        assert!(new(Some(" A"), Some("")).is_ok());
+    }
+
+    #[test]
+    fn test_positions_with_offset() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 1_000).unwrap();
+        assert_eq!(searcher.absolute_position(), Some(1_000));
+
+        searcher.search_batch(250);
+        assert_eq!(searcher.attempts(), 250);
+        assert_eq!(searcher.absolute_position(), Some(1_250));
+    }
+
+    #[test]
+    fn test_counter_does_not_wrap() {
+        let mut searcher = Searcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            u64::MAX - 2,
+        )
+        .unwrap();
+        assert_eq!(searcher.search_batch(2), None);
+        assert_eq!(searcher.batch_status(false), BatchStatus::NotFound);
+        // u64::MAX itself is the last counter.
+        assert_eq!(searcher.search_batch(10), None);
+        assert_eq!(searcher.attempts(), 3);
+        assert_eq!(searcher.absolute_position(), None);
+        assert!(searcher.is_exhausted());
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+
+        assert_eq!(searcher.search_batch(10), None);
+        assert_eq!(searcher.attempts(), 3);
+        searcher.restart();
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+    }
+
+    #[test]
+    fn test_fork_searches_a_disjoint_range() {
+        let mut original =
+            Searcher::new(&[1; 32], &[4; 32], Some("a".into()), None, true, 0).unwrap();
+        original.search_batch(500);
+        original.stop();
+        let attempts = original.attempts();
+        let mut fork = original.fork(1 << 40);
+        assert_eq!(original.attempts(), attempts);
+        assert!(original.is_stopped());
+        assert_eq!(fork.attempts(), 0);
+        assert!(!fork.is_stopped());
+        assert_eq!(fork.absolute_position(), Some(1 << 40));
+
+        original.restart();
+        let mut seeds = std::collections::HashSet::new();
+        for searcher in [&mut original, &mut fork] {
+            for _ in 0..20 {
+                let found = loop {
+                    if let Some(found) = searcher.search_batch(1000) {
+                        break found;
+                    }
+                };
+                assert!(found.address.to_lowercase().starts_with('a'));
+                assert!(seeds.insert(found.seed), "seed found twice");
+            }
+        }
+    }
+
+    #[test]
+    fn test_fork_starts_with_no_skips() {
+        let mut seen = SeenFilter::new(10, 1e-9).unwrap();
+        seen.insert_range(0, SEEN_BLOCK_SIZE);
+        let mut original =
+            Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0).unwrap();
+        original.set_seen_filter(Some(seen));
+        assert_eq!(original.search_batch(SEEN_BLOCK_SIZE as u32), None);
+        assert_eq!(original.skipped(), SEEN_BLOCK_SIZE);
+        let fork = original.fork(1 << 40);
+        assert_eq!((fork.attempts(), fork.skipped()), (0, 0));
+        assert_eq!(original.skipped(), SEEN_BLOCK_SIZE);
+    }
+
+    #[test]
+    fn test_max_attempts_exhausts_at_exact_count() {
+        // The first "AAA" for these keys is at attempt 51052.
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("AAA".into()), None, false, 7).unwrap();
+        searcher.set_max_attempts(Some(50_000));
+        while !searcher.is_exhausted() {
+            assert_eq!(searcher.search_batch(3_000), None);
+        }
+        assert_eq!(searcher.attempts(), 50_000);
+        assert!(!searcher.is_stopped());
+
+        // Further batches are no-ops, and restart doesn't lift the cap.
+        searcher.restart();
+        assert_eq!(searcher.search_batch(3_000), None);
+        assert_eq!(searcher.attempts(), 50_000);
+
+        searcher.set_max_attempts(None);
+        assert!(!searcher.is_exhausted());
+        assert!(searcher.search_batch(10_000).is_some());
+    }
+
+    #[test]
+    fn test_counter_space_end_is_exhausted() {
+        let mut searcher = Searcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            u64::MAX,
+        )
+        .unwrap();
+        searcher.search_batch(10);
+        assert!(searcher.is_exhausted());
+    }
+
+    #[test]
+    fn test_small_seed_space_is_exhausted_at_its_size() {
+        use crate::derivation::CharsetSeedDerivation;
+        use crate::seed::SeedCharset;
+
+        // 3^4 = 81 seeds, none likely to give "zzzz".
+        let charset = SeedCharset::new("abc", 4).unwrap();
+        let derivation = CharsetSeedDerivation::new(&[1; 32], &[4; 32], charset);
+        let mut searcher =
+            Searcher::with_pattern(derivation, Some("zzzz".into()), None, false, 0).unwrap();
+        assert_eq!(searcher.seed_space(), Some(81));
+        assert_eq!(searcher.seed_space_coverage(), Some(0.0));
+        assert_eq!(searcher.search_batch(50), None);
+        assert_eq!(searcher.batch_status(false), BatchStatus::NotFound);
+        assert_eq!(searcher.search_batch(1_000), None);
+        assert_eq!(searcher.attempts(), 81);
+        assert_eq!(searcher.absolute_position(), None);
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+        assert_eq!(searcher.seed_space_coverage(), Some(1.0));
+        assert_eq!(searcher.search_batch(1_000), None);
+        assert_eq!(searcher.attempts(), 81);
+
+        // An offset covers the rest of the space.
+        let mut rest = searcher.fork(80);
+        assert_eq!(rest.search_batch(1_000), None);
+        assert_eq!(rest.attempts(), 1);
+
+        // Every seed, once each.
+        let derivation = searcher.derivation().clone();
+        let mut candidate = Candidate::default();
+        let seeds: std::collections::HashSet<_> = (0..81)
+            .map(|counter| {
+                derivation.candidate(counter, &mut candidate);
+                candidate.artifacts.clone()
+            })
+            .collect();
+        assert_eq!(seeds.len(), 81);
+        assert_eq!(
+            Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0)
+                .unwrap()
+                .seed_space(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_batch_status() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("AAA".into()), None, false, 0).unwrap();
+        let found = searcher.search_batch(100);
+        assert_eq!(
+            searcher.batch_status(found.is_some()),
+            BatchStatus::NotFound
+        );
+        assert_eq!(searcher.batch_status(true), BatchStatus::Found);
+
+        searcher.set_max_attempts(Some(100));
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+        searcher.stop();
+        assert_eq!(searcher.batch_status(false), BatchStatus::Stopped);
+    }
+
+    #[test]
+    fn test_counter_spans_tile_across_early_exits() {
+        // One batch, stopped after `polls` interrupt polls; its span.
+        fn batch(searcher: &mut Searcher, polls: u32) -> (Option<SearchMatch>, Option<(u64, u64)>) {
+            let since = searcher.attempts();
+            let mut polled = 0;
+            let found = searcher.search_batch_until(50_000, || {
+                polled += 1;
+                polled > polls
+            });
+            searcher.restart();
+            (found, searcher.counters_since(since))
+        }
+
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("AAA".into()), None, false, 7).unwrap();
+        searcher.set_max_attempts(Some(120_000));
+        // Stopped partway, then a match, then the cap.
+        let (_, stopped) = batch(&mut searcher, 3);
+        assert_eq!(
+            stopped,
+            Some((7, 7 + 3 * INTERRUPT_POLL_INTERVAL as u64 - 1))
+        );
+        let mut spans = alloc::vec![stopped.unwrap()];
+        let found = loop {
+            let (found, span) = batch(&mut searcher, u32::MAX);
+            spans.push(span.unwrap());
+            if let Some(found) = found {
+                break found;
+            }
+        };
+        assert_eq!(spans.last().unwrap().1, found.counter);
+        while !searcher.is_exhausted() {
+            spans.push(batch(&mut searcher, u32::MAX).1.unwrap());
+        }
+        assert_eq!(batch(&mut searcher, u32::MAX).1, None);
+
+        assert!(spans.windows(2).all(|pair| pair[1].0 == pair[0].1 + 1));
+        assert_eq!(spans.last().unwrap().1, 7 + 120_000 - 1);
+    }
+
+    #[test]
+    fn test_seen_blocks_are_skipped() {
+        let mut seen = SeenFilter::new(10, 1e-9).unwrap();
+        seen.insert_range(0, SEEN_BLOCK_SIZE);
+        seen.insert_range(2 * SEEN_BLOCK_SIZE, 2 * SEEN_BLOCK_SIZE);
+        let new = |count_offset| {
+            let mut searcher = Searcher::new(
+                &[1; 32],
+                &[4; 32],
+                Some("zzzz".into()),
+                None,
+                false,
+                count_offset,
+            )
+            .unwrap();
+            searcher.set_seen_filter(Some(seen.clone()));
+            searcher
+        };
+
+        // The rest of block 0 goes at once, then block 1 is searched.
+        let mut searcher = new(10);
+        assert_eq!(searcher.search_batch(SEEN_BLOCK_SIZE as u32), None);
+        assert_eq!(searcher.skipped(), SEEN_BLOCK_SIZE - 10);
+        assert_eq!(searcher.attempts(), SEEN_BLOCK_SIZE);
+        assert_eq!(searcher.absolute_position(), Some(SEEN_BLOCK_SIZE + 10));
+
+        // A skip never covers more than the batch: crossing into block 2,
+        // the batch ends at its size, and the next one picks up from there,
+        // skipping blocks 2 and 3 one after the other.
+        let mut searcher = new(2 * SEEN_BLOCK_SIZE - 5);
+        assert_eq!(searcher.search_batch(8), None);
+        assert_eq!((searcher.attempts(), searcher.skipped()), (8, 3));
+        assert_eq!(searcher.absolute_position(), Some(2 * SEEN_BLOCK_SIZE + 3));
+        assert_eq!(searcher.search_batch(2 * SEEN_BLOCK_SIZE as u32), None);
+        assert_eq!(searcher.skipped(), 2 * SEEN_BLOCK_SIZE);
+        assert_eq!(searcher.attempts(), 2 * SEEN_BLOCK_SIZE + 8);
+        assert_eq!(searcher.absolute_position(), Some(4 * SEEN_BLOCK_SIZE + 3));
+
+        // Skipping stops at the attempt cap.
+        let mut searcher = new(0);
+        searcher.set_max_attempts(Some(1_000));
+        assert_eq!(searcher.search_batch(5_000), None);
+        assert_eq!(searcher.skipped(), 1_000);
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+    }
+
+    #[test]
+    fn test_seen_last_block_takes_the_last_counter() {
+        let mut seen = SeenFilter::new(10, 1e-9).unwrap();
+        seen.insert_block(u64::MAX >> SEEN_BLOCK_BITS);
+        let mut searcher = Searcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("zzzz".into()),
+            None,
+            false,
+            u64::MAX - 2,
+        )
+        .unwrap();
+        searcher.set_seen_filter(Some(seen));
+        assert_eq!(searcher.search_batch(10), None);
+        assert_eq!((searcher.attempts(), searcher.skipped()), (3, 3));
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+    }
+
+    #[test]
+    fn test_stop_at_probability() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0).unwrap();
+        let p = searcher.match_probability();
+        searcher.set_stop_at_probability(Some(0.002)).unwrap();
+        // ceil(ln(0.998) / ln(1 - p)), about 0.002 / p.
+        let threshold = searcher.coverage_stop_attempts().unwrap();
+        assert_eq!(threshold, (0.998f64.ln() / (-p).ln_1p()).ceil() as u64);
+        assert!(probability_found(p, threshold) >= 0.002);
+        assert!(probability_found(p, threshold - 1) < 0.002);
+
+        // The attempt cap comes first here, and wins.
+        searcher.set_max_attempts(Some(10_000));
+        while !searcher.is_exhausted() {
+            assert_eq!(searcher.search_batch(6_000), None);
+        }
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+
+        searcher.set_max_attempts(None);
+        while !searcher.is_exhausted() {
+            assert_eq!(searcher.search_batch(6_000), None);
+        }
+        assert_eq!(searcher.attempts(), threshold);
+        assert_eq!(searcher.batch_status(false), BatchStatus::CoverageReached);
+        assert!((searcher.coverage() - 0.002).abs() < 1e-6);
+
+        searcher.set_stop_at_probability(None).unwrap();
+        assert!(!searcher.is_exhausted());
+        for bad in [0.0, 1.0, -0.5, f64::NAN] {
+            let error = searcher.set_stop_at_probability(Some(bad)).unwrap_err();
+            assert_eq!(error.kind(), crate::ErrorKind::InvalidOptions);
+        }
+    }
+
+    #[test]
+    fn test_resume_token_restores_position() {
+        let new = || Searcher::new(&[1; 32], &[4; 32], Some("AAA".into()), None, false, 0);
+        let mut searcher = new().unwrap();
+        assert_eq!(searcher.search_batch(30_000), None);
+        let token = searcher.resume_token();
+
+        let mut resumed = new().unwrap();
+        resumed.resume_from(&token).unwrap();
+        assert_eq!(resumed.attempts(), 30_000);
+        assert_eq!(resumed.absolute_position(), Some(30_000));
+        // Same first match as an uninterrupted run.
+        let found = resumed.search_batch(30_000).unwrap();
+        assert_eq!(found.attempts, 51052);
+
+        // Forks share the configuration, so their tokens transfer too.
+        let fork = searcher.fork(1 << 40);
+        resumed.resume_from(&fork.resume_token()).unwrap();
+        assert_eq!(resumed.absolute_position(), Some(1 << 40));
+    }
+
+    #[test]
+    fn test_resume_token_rejects_other_configurations() {
+        let searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("AAA".into()), None, false, 5).unwrap();
+        let token = searcher.resume_token();
+        let others = [
+            Searcher::new(&[1; 32], &[9; 32], Some("AAA".into()), None, false, 5),
+            Searcher::new(&[9; 32], &[4; 32], Some("AAA".into()), None, false, 5),
+            Searcher::new(&[1; 32], &[4; 32], Some("AAB".into()), None, false, 5),
+            Searcher::new(&[1; 32], &[4; 32], None, Some("AAA".into()), false, 5),
+            Searcher::new(&[1; 32], &[4; 32], Some("AAA".into()), None, true, 5),
+        ];
+        for other in others {
+            let mut other = other.unwrap();
+            other.search_batch(10);
+            assert_eq!(
+                other.resume_from(&token),
+                Err(VanityError::ResumeTokenMismatch)
+            );
+            assert_eq!(other.attempts(), 10);
+            assert_eq!(other.absolute_position(), Some(15));
+        }
+
+        let mut other = searcher.clone();
+        assert_eq!(
+            other.resume_from("nonsense").map_err(|e| e.kind()),
+            Err(crate::ErrorKind::InvalidResumeToken)
+        );
+    }
+
+    #[test]
+    fn test_set_keys_between_batches() {
+        let (old_base, new_base, owner) = ([1; 32], [9; 32], [4; 32]);
+        let derives = |base: &[u8; 32], found: &SearchMatch| {
+            let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+            let pubkey = Deriver::new(base, &owner).derive(&found.seed);
+            encode_pubkey(&pubkey, &mut buf) == found.address
+        };
+        let mut searcher =
+            Searcher::new(&old_base, &owner, Some("A".into()), None, false, 10).unwrap();
+        let before = searcher.search_batch(1000).unwrap();
+        let token = searcher.resume_token();
+        let attempts = searcher.attempts();
+
+        searcher.set_keys(&new_base, &owner, false);
+        assert_eq!(searcher.attempts(), attempts);
+        let after = searcher.search_batch(1000).unwrap();
+        assert!(derives(&old_base, &before) && !derives(&new_base, &before));
+        assert!(derives(&new_base, &after) && !derives(&old_base, &after));
+        assert!(after.counter > before.counter);
+        assert_eq!(
+            searcher.resume_from(&token),
+            Err(VanityError::ResumeTokenMismatch)
+        );
+
+        // Same as a searcher built with the new keys; here, from the start.
+        searcher.set_keys(&new_base, &owner, true);
+        assert_eq!(searcher.absolute_position(), Some(10));
+        let fresh = Searcher::new(&new_base, &owner, Some("A".into()), None, false, 10).unwrap();
+        assert_eq!(searcher.config_fingerprint(), fresh.config_fingerprint());
+        let restarted = searcher.search_batch(1000).unwrap();
+        assert!(derives(&new_base, &restarted));
+        assert!(restarted.counter <= after.counter);
+    }
+
+    #[test]
+    fn test_counter_permutation_records_physical_counters() {
+        let base = [2; 32];
+        let owner = [3; 32];
+        let new = || Searcher::new(&base, &owner, Some("A".into()), None, false, 0).unwrap();
+        let mut permuted = new();
+        permuted.set_counter_permutation(Some(42));
+        assert_eq!(permuted.counter_permutation_key(), Some(42));
+        let permutation = CounterPermutation::new(42);
+
+        let found = loop {
+            if let Some(found) = permuted.search_batch(1000) {
+                break found;
+            }
+        };
+        assert_eq!(found.counter, permutation.apply(found.attempts - 1));
+        assert_eq!(found.seed, generate_seed_from_counter(found.counter));
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let pubkey = Deriver::new(&base, &owner).derive(&found.seed);
+        assert_eq!(encode_pubkey(&pubkey, &mut buf), found.address);
+        // The physical counter reproduces the match without the permutation.
+        let mut plain = new();
+        let range = plain.search_range(found.counter, found.counter + 1);
+        assert_eq!(range.matches[0].address, found.address);
+
+        let samples = permuted.sample_addresses(5, 3);
+        for (i, sample) in samples.iter().enumerate() {
+            assert_eq!(sample.counter, permutation.apply(5 + i as u64));
+        }
+
+        // The key travels in the resume token, and its absence does too.
+        let token = permuted.resume_token();
+        let mut resumed = new();
+        resumed.resume_from(&token).unwrap();
+        assert_eq!(resumed.counter_permutation_key(), Some(42));
+        assert_eq!(resumed.search_batch(1000), permuted.search_batch(1000));
+        resumed.resume_from(&new().resume_token()).unwrap();
+        assert_eq!(resumed.counter_permutation_key(), None);
+        assert_eq!(permuted.fork(9).counter_permutation_key(), Some(42));
+    }
+
+    #[test]
+    fn test_byte_mask_search() {
+        let base = [1; 32];
+        let owner = [4; 32];
+        let deriver = Deriver::new(&base, &owner);
+        let find = |searcher: &mut Searcher| loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                break found;
+            }
+        };
+
+        // First byte zero and the high nibble of the second 0xa.
+        let byte_mask = ByteMask::new(&[0xff, 0xf0], &[0x00, 0xa0]).unwrap();
+        let mut searcher =
+            Searcher::with_byte_mask(&base, &owner, byte_mask.clone(), None, false, 0).unwrap();
+        assert_eq!(searcher.match_probability(), 1.0 / 4096.0);
+        let found = find(&mut searcher);
+        let pubkey = deriver.derive(&found.seed);
+        assert!(pubkey[0] == 0 && pubkey[1] >> 4 == 0xa);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        assert_eq!(encode_pubkey(&pubkey, &mut buf), found.address);
+        let earlier = searcher.search_range(0, found.counter);
+        assert_eq!(earlier.matches, []);
+
+        // Combined with a prefix, both have to hold.
+        let byte_mask = ByteMask::new(&[0x00, 0x00, 0x80], &[0x00, 0x00, 0x80]).unwrap();
+        let mut both =
+            Searcher::with_byte_mask(&base, &owner, byte_mask, Some("a".into()), true, 0).unwrap();
+        for _ in 0..5 {
+            let found = find(&mut both);
+            assert!(found.address.to_lowercase().starts_with('a'));
+            assert!(deriver.derive(&found.seed)[2] & 0x80 != 0);
+        }
+
+        // The mask is part of the configuration a resume token is bound to.
+        let other = ByteMask::new(&[0xff], &[0x01]).unwrap();
+        let mut other = Searcher::with_byte_mask(&base, &owner, other, None, false, 0).unwrap();
+        assert_eq!(
+            other.resume_from(&searcher.resume_token()),
+            Err(VanityError::ResumeTokenMismatch)
+        );
+        let any = ByteMask::new(&[0x01], &[0x00]).unwrap();
+        let bad_prefix = Searcher::with_byte_mask(&base, &owner, any, Some("0".into()), false, 0);
+        assert_eq!(
+            bad_prefix.err().map(|e| e.kind()),
+            Some(crate::ErrorKind::InvalidPatternChar)
+        );
+    }
+
+    #[test]
+    fn test_sample_addresses_rederive() {
+        let base = [2; 32];
+        let owner = [3; 32];
+        let mut searcher =
+            Searcher::new(&base, &owner, Some("AAA".into()), None, false, 0).unwrap();
+        searcher.search_batch(10);
+
+        let samples = searcher.sample_addresses(1 << 33, 20);
+        assert_eq!(samples.len(), 20);
+        let deriver = Deriver::new(&base, &owner);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for (i, sample) in samples.iter().enumerate() {
+            assert_eq!(sample.counter, (1 << 33) + i as u64);
+            assert_eq!(sample.seed, generate_seed_from_counter(sample.counter));
+            let pubkey = deriver.derive(&sample.seed);
+            assert_eq!(encode_pubkey(&pubkey, &mut buf), sample.address);
+        }
+        assert_eq!(searcher.attempts(), 10);
+
+        assert_eq!(
+            searcher.sample_addresses(0, u32::MAX).len(),
+            MAX_SAMPLES as usize
+        );
+        assert_eq!(searcher.sample_addresses(u64::MAX - 1, 5).len(), 2);
+    }
+
+    #[test]
+    fn test_sampling_records_every_nth_address() {
+        let base = [2; 32];
+        let owner = [3; 32];
+        let mut searcher =
+            Searcher::new(&base, &owner, Some("zzzz".into()), None, false, 100).unwrap();
+        assert_eq!(searcher.drain_samples(), []);
+        searcher.set_sampling(Some((4, 10))).unwrap();
+        searcher.search_batch(95);
+
+        // Attempts 1, 11, ..., 91 were sampled; the ring kept the last 4.
+        let samples = searcher.drain_samples();
+        let counters: Vec<_> = samples.iter().map(|sample| sample.counter).collect();
+        assert_eq!(counters, [160, 170, 180, 190]);
+        let deriver = Deriver::new(&base, &owner);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for sample in &samples {
+            assert_eq!(sample.seed, generate_seed_from_counter(sample.counter));
+            let pubkey = deriver.derive(&sample.seed);
+            assert_eq!(encode_pubkey(&pubkey, &mut buf), sample.address);
+        }
+        assert_eq!(searcher.drain_samples(), []);
+
+        // The count carries across batches.
+        searcher.search_batch(6);
+        assert_eq!(searcher.drain_samples()[0].counter, 200);
+        assert!(searcher.fork(0).sampler().is_none());
+        searcher.set_sampling(None).unwrap();
+        searcher.search_batch(20);
+        assert_eq!(searcher.drain_samples(), []);
+        assert_eq!(
+            searcher.set_sampling(Some((0, 1))).unwrap_err().kind(),
+            crate::ErrorKind::InvalidOptions
+        );
+    }
+
+    #[test]
+    fn test_suffix_prefilter_finds_what_full_encoding_does() {
+        // Sampling encodes every address, so it searches without the
+        // trailing-characters check.
+        for (prefix, suffix, case_insensitive) in [
+            (None, "Ab", false),
+            (None, "ab", true),
+            (Some("C"), "x", false),
+        ] {
+            let search = |sampling: bool| {
+                let mut searcher = Searcher::new(
+                    &[2; 32],
+                    &[3; 32],
+                    prefix.map(Into::into),
+                    Some(suffix.into()),
+                    case_insensitive,
+                    0,
+                )
+                .unwrap();
+                if sampling {
+                    searcher.set_sampling(Some((1, 1_000_000))).unwrap();
+                }
+                let found: Vec<_> = (0..3)
+                    .map(|_| loop {
+                        if let Some(found) = searcher.search_batch(10_000) {
+                            break (found.address, found.counter);
+                        }
+                    })
+                    .collect();
+                found
+            };
+            assert_eq!(search(false), search(true), "{suffix}");
+        }
+    }
+
+    #[test]
+    fn test_clean_prefix_composes_with_the_pattern() {
+        let mut searcher =
+            Searcher::new(&[2; 32], &[3; 32], None, Some("x".into()), false, 0).unwrap();
+        let plain_probability = searcher.match_probability();
+        let plain_token = searcher.resume_token();
+        let clean_prefix = CleanPrefix::with_default_set(6).unwrap();
+        searcher
+            .set_clean_prefix(Some(clean_prefix.clone()))
+            .unwrap();
+        assert!(searcher.match_probability() < plain_probability);
+        assert_eq!(
+            searcher.resume_from(&plain_token),
+            Err(VanityError::ResumeTokenMismatch)
+        );
+
+        for _ in 0..5 {
+            let found = loop {
+                if let Some(found) = searcher.search_batch(10_000) {
+                    break found;
+                }
+            };
+            assert!(found.address.ends_with('x'));
+            assert!(clean_prefix.is_clean(&found.address), "{}", found.address);
+        }
+        // Some plain matches were skipped for not being clean.
+        let mut plain =
+            Searcher::new(&[2; 32], &[3; 32], None, Some("x".into()), false, 0).unwrap();
+        let mut skipped = 0;
+        while plain.attempts() < searcher.attempts() {
+            match plain.search_batch((searcher.attempts() - plain.attempts()) as u32) {
+                Some(found) if !clean_prefix.is_clean(&found.address) => skipped += 1,
+                _ => {}
+            }
+        }
+        assert!(skipped > 0);
+
+        let mut prefixed =
+            Searcher::new(&[2; 32], &[3; 32], Some("S".into()), None, false, 0).unwrap();
+        assert_eq!(
+            prefixed
+                .set_clean_prefix(Some(clean_prefix))
+                .unwrap_err()
+                .kind(),
+            crate::ErrorKind::ConflictingOptions
+        );
+        assert!(prefixed.clean_prefix().is_none());
+    }
+
+    #[test]
+    fn test_search_range_reports_known_match() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("AAA".into()), None, false, 0).unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(10_000) {
+                break found;
+            }
+        };
+
+        let mut other = searcher.fork(7);
+        let outcome = other.search_range(found.counter - 10, found.counter + 10);
+        assert_eq!(outcome.attempts, 20);
+        assert!(!outcome.interrupted);
+        assert_eq!(
+            outcome.matches,
+            [DerivedAddress {
+                address: found.address,
+                seed: found.seed,
+                counter: found.counter,
+            }]
+        );
+        // The searcher's own position is untouched.
+        assert_eq!(other.attempts(), 0);
+        assert_eq!(other.absolute_position(), Some(7));
+        assert_eq!(other.search_range(5, 5), RangeOutcome::default());
+    }
+
+    #[test]
+    fn test_adjacent_ranges_partition_the_counters() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0).unwrap();
+        let whole = searcher.search_range(1_000, 3_000);
+        let first = searcher.search_range(1_000, 2_000);
+        let second = searcher.search_range(2_000, 3_000);
+        assert_eq!(first.attempts + second.attempts, whole.attempts);
+        assert!(!whole.matches.is_empty());
+        assert_eq!([first.matches, second.matches].concat(), whole.matches);
+    }
+
+    #[test]
+    fn test_search_range_stops_when_interrupted() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0).unwrap();
+        let mut polls = 0;
+        let outcome = searcher.search_range_until(0, 100_000, || {
+            polls += 1;
+            polls == 3
+        });
+        assert!(outcome.interrupted);
+        assert_eq!(outcome.attempts, 2 * INTERRUPT_POLL_INTERVAL as u64);
+        assert!(searcher.is_stopped());
+        assert!(searcher.search_range(0, 10).interrupted);
+    }
+
+    #[test]
+    fn test_luck_stats_use_the_configured_pattern() {
+        let mut searcher = Searcher::new(
+            &[1; 32],
+            &[4; 32],
+            Some("A".into()),
+            Some("b".into()),
+            true,
+            0,
+        )
+        .unwrap();
+        searcher.search_batch(100);
+        let stats = searcher.luck_stats();
+        assert_eq!(stats.actual_attempts, 100);
+        assert_eq!(stats.expected_attempts, 1.0 / searcher.match_probability());
+        assert_eq!(
+            stats.percentile,
+            crate::probability_found(searcher.match_probability(), 100)
+        );
+    }
+
+    #[test]
+    fn test_memory_bytes() {
+        let (prefix, suffix) = (String::with_capacity(64) + "A", "b".repeat(20));
+        let mut searcher = Searcher::with_match_type(
+            &[1; 32],
+            &[4; 32],
+            MatchType::Both(prefix, suffix),
+            false,
+            0,
+        );
+        let before = searcher.memory_bytes();
+        assert!(before >= core::mem::size_of::<Searcher>() + 64 + 20);
+        searcher.shrink();
+        assert_eq!(
+            searcher.memory_bytes(),
+            core::mem::size_of::<Searcher>() + 1 + 20
+        );
+    }
+
+    #[test]
+    fn test_stop() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0).unwrap();
+        assert!(!searcher.is_stopped());
+        searcher.stop();
+        assert!(searcher.is_stopped());
+        assert_eq!(searcher.search_batch(1000), None);
+        assert_eq!(searcher.attempts(), 0);
+    }
+
+    #[test]
+    fn test_interrupt_is_polled_during_batch() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0).unwrap();
+        let mut polls = 0;
+        let result = searcher.search_batch_until(1_000_000, || {
+            polls += 1;
+            polls == 3
+        });
+        assert_eq!(result, None);
+        assert!(searcher.is_stopped());
+        assert_eq!(searcher.attempts(), 2 * INTERRUPT_POLL_INTERVAL as u64);
+
+        let mut polls = 0;
+        searcher.restart();
+        searcher.search_batch_until(10_000, || {
+            polls += 1;
+            false
+        });
+        assert_eq!(polls, 3);
+        assert_eq!(
+            searcher.attempts(),
+            2 * INTERRUPT_POLL_INTERVAL as u64 + 10_000
+        );
+    }
+
+    #[test]
+    fn test_restart_after_stop() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0).unwrap();
+        searcher.stop();
+        assert_eq!(searcher.search_batch(1000), None);
+        assert_eq!(searcher.attempts(), 0);
+
+        searcher.restart();
+        assert!(!searcher.is_stopped());
+        let found = loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                break found;
+            }
+        };
+        assert!(found.address.starts_with('A'));
+        assert_eq!(found.attempts, searcher.attempts());
+    }
+
+    #[test]
+    fn test_create_with_seed_derivation_keeps_earlier_results() {
+        // Pinned from the loop as it was before derivations were pluggable:
+        // the same counters match and saved resume tokens still apply.
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("AB".into()), None, true, 7).unwrap();
+        let found = searcher.search_batch(1_000_000).unwrap();
+        assert_eq!(
+            found.address,
+            "ABk6vjpgsgQi1tu2Tbd7YTgGjzPHfb3ckQ6La8w5vLnv"
+        );
+        assert_eq!((found.counter, found.attempts), (114, 108));
+        assert_eq!(found.seed, generate_seed_from_counter(114));
+        assert_eq!(searcher.resume_token(), "v1.c17106ccebcc1dfe.7.108");
+
+        let range = searcher.search_range(0, 20_000);
+        let counters: Vec<_> = range.matches.iter().map(|m| m.counter).collect();
+        assert_eq!(
+            counters,
+            [
+                114, 426, 1193, 1728, 2186, 3774, 4606, 4679, 4799, 5354, 5448, 6637, 7151, 7213,
+                10069, 10692, 11487, 11549, 12203, 12934, 13652, 14970, 15287, 16983, 17599, 17925,
+                18448, 18466, 19739, 19929, 19974
+            ]
+        );
+
+        let deriver = Deriver::new(&[1; 32], &[4; 32]);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for sample in searcher.sample_addresses(5_000, 500) {
+            let seed = generate_seed_from_counter(sample.counter);
+            assert_eq!(sample.seed, seed);
+            assert_eq!(
+                sample.address,
+                encode_pubkey(&deriver.derive(&seed), &mut buf)
+            );
+        }
+    }
+
+    #[cfg(feature = "keypair")]
+    #[test]
+    fn test_signer_keys_share_the_search_loop() {
+        use crate::keys::{pubkey_from_secret, secret_from_counter};
+        use crate::SignerKeyDerivation;
+
+        let derivation = SignerKeyDerivation::new(&[3; 32]);
+        let mut searcher =
+            Searcher::with_pattern(derivation.clone(), Some("A".into()), None, false, 10).unwrap();
+        searcher.set_sampling(Some((4, 1))).unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(100) {
+                break found;
+            }
+        };
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        assert_eq!(found.seed, secret_from_counter(&[3; 32], found.counter));
+        assert_eq!(
+            encode_pubkey(&pubkey_from_secret(&found.seed), &mut buf),
+            found.address
+        );
+        for sample in searcher.drain_samples() {
+            assert_eq!(sample.seed, secret_from_counter(&[3; 32], sample.counter));
+        }
+
+        // Stops, caps and resume tokens come with the loop.
+        searcher.set_max_attempts(Some(searcher.attempts()));
+        assert_eq!(searcher.search_batch(10), None);
+        assert_eq!(searcher.batch_status(false), BatchStatus::Exhausted);
+        let mut other =
+            Searcher::with_pattern(derivation, Some("A".into()), None, false, 0).unwrap();
+        other.resume_from(&searcher.resume_token()).unwrap();
+        assert_eq!(other.absolute_position(), searcher.absolute_position());
+        let seed_token = Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0)
+            .unwrap()
+            .resume_token();
+        assert_eq!(
+            other.resume_from(&seed_token),
+            Err(VanityError::ResumeTokenMismatch)
+        );
+    }
+
+    #[test]
+    fn test_derive_counters_matches_the_search() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0).unwrap();
+        let counters = [7, 0, u64::MAX, 1 << 40, 7];
+        for namespace in [None, Some(SeedNamespace::new("tab-1").unwrap())] {
+            searcher.set_seed_namespace(namespace.clone());
+            let derived =
+                derive_counters(searcher.derivation(), namespace.as_ref(), &counters).unwrap();
+            assert_eq!(derived.len(), counters.len());
+            for (derived, &counter) in derived.iter().zip(&counters) {
+                assert_eq!(derived, &searcher.sample_addresses(counter, 1)[0]);
+            }
+        }
+        assert_eq!(
+            derive_counters(searcher.derivation(), None, &[]),
+            Ok(Vec::new())
+        );
+        let too_many = alloc::vec![0; MAX_DERIVE_COUNTERS + 1];
+        assert_eq!(
+            derive_counters(searcher.derivation(), None, &too_many)
+                .unwrap_err()
+                .kind(),
+            crate::ErrorKind::InvalidOptions
+        );
+    }
+
+    #[test]
+    fn test_seed_namespaces_change_seeds_at_the_same_counters() {
+        let new = || Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0).unwrap();
+        let plain = new();
+        let mut tagged = new();
+        let namespace = SeedNamespace::new("tab-1").unwrap();
+        tagged.set_seed_namespace(Some(namespace.clone()));
+        let plain_samples = plain.sample_addresses(0, 200);
+        let tagged_samples = tagged.sample_addresses(0, 200);
+        for (plain, tagged) in plain_samples.iter().zip(&tagged_samples) {
+            assert_eq!(plain.counter, tagged.counter);
+            assert_ne!(plain.seed, tagged.seed);
+            assert_ne!(plain.address, tagged.address);
+            assert_eq!(
+                tagged.seed,
+                generate_seed_from_counter(namespace.apply(tagged.counter))
+            );
+        }
+
+        // Matches report the unmixed counter; the namespace reproduces them.
+        tagged.set_sampling(Some((8, 1))).unwrap();
+        let found = loop {
+            if let Some(found) = tagged.search_batch(100) {
+                break found;
+            }
+        };
+        assert_eq!(found.counter, found.attempts - 1);
+        assert_eq!(found.seed_namespace.as_deref(), Some("tab-1"));
+        assert_eq!(
+            found.seed,
+            generate_seed_from_counter(namespace.apply(found.counter))
+        );
+        for sample in tagged.drain_samples() {
+            let seed = generate_seed_from_counter(namespace.apply(sample.counter));
+            assert_eq!(sample.seed, seed);
+        }
+
+        // The token carries the namespace, and restores its absence too.
+        let mut resumed = new();
+        resumed.resume_from(&tagged.resume_token()).unwrap();
+        assert_eq!(resumed.seed_namespace(), Some(&namespace));
+        assert_eq!(resumed.sample_addresses(0, 200), tagged_samples);
+        resumed.resume_from(&plain.resume_token()).unwrap();
+        assert_eq!(resumed.seed_namespace(), None);
+        assert_eq!(tagged.fork(0).seed_namespace(), Some(&namespace));
+    }
+
+    #[test]
+    fn test_resuming_into_a_namespace_restarts_partial_tracking() {
+        let mut tagged =
+            Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0).unwrap();
+        tagged.set_seed_namespace(Some(SeedNamespace::new("tab-1").unwrap()));
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("zzzz".into()), None, false, 0).unwrap();
+        searcher.set_partial_tracking(true);
+        assert_eq!(searcher.search_batch(1_000), None);
+        assert!(searcher.closest_match().is_some());
+        // The closest address came from no namespace, which the token drops.
+        searcher.resume_from(&tagged.resume_token()).unwrap();
+        assert_eq!(searcher.closest_match(), None);
+        assert_eq!(searcher.search_batch(1_000), None);
+        let closest = searcher.closest_match().unwrap();
+        assert_eq!(closest.seed_namespace.as_deref(), Some("tab-1"));
+    }
+
+    #[test]
+    fn test_search_loop_agrees_with_a_plain_scan() {
+        const COUNTERS: u64 = 200_000;
+        let deriver = Deriver::new(&[1; 32], &[4; 32]);
+        let mut buf = [0u8; crate::derive::MAX_ENCODED_LEN];
+        let addresses: Vec<String> = (0..COUNTERS)
+            .map(|counter| {
+                let pubkey = deriver.derive(&generate_seed_from_counter(counter));
+                encode_pubkey(&pubkey, &mut buf).to_string()
+            })
+            .collect();
+        for (prefix, suffix, case_insensitive) in [
+            (Some("A"), None, false),
+            (None, Some("zz"), false),
+            (Some("b"), Some("C"), true),
+            (None, Some("abcdefghij"), false),
+        ] {
+            let fold = |text: &str| match case_insensitive {
+                true => text.to_ascii_lowercase(),
+                false => text.to_string(),
+            };
+            let expected: Vec<u64> = (0..COUNTERS)
+                .filter(|&counter| {
+                    let address = fold(&addresses[counter as usize]);
+                    prefix.is_none_or(|prefix| address.starts_with(&fold(prefix)))
+                        && suffix.is_none_or(|suffix| address.ends_with(&fold(suffix)))
+                })
+                .collect();
+            let mut searcher = Searcher::new(
+                &[1; 32],
+                &[4; 32],
+                prefix.map(String::from),
+                suffix.map(String::from),
+                case_insensitive,
+                0,
+            )
+            .unwrap();
+            let outcome = searcher.search_range(0, COUNTERS);
+            let found: Vec<u64> = outcome.matches.iter().map(|found| found.counter).collect();
+            assert_eq!(found, expected, "{prefix:?} {suffix:?}");
+            assert!(outcome
+                .matches
+                .iter()
+                .all(|found| found.address == addresses[found.counter as usize]));
+        }
+    }
+
+    #[test]
+    fn test_faults_stop_the_search_with_a_typed_error() {
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("A".into()), None, false, 0).unwrap();
+        searcher.record_fault(VanityError::Internal("encoder wrote garbage".into()));
+        assert_eq!(searcher.search_batch(1_000), None);
+        assert_eq!(searcher.attempts(), 0);
+        assert_eq!(searcher.batch_status(false), BatchStatus::Error);
+        assert_eq!(
+            searcher.fault().map(VanityError::kind),
+            Some(crate::ErrorKind::Internal)
+        );
+        assert!(searcher.search_range(0, 10).interrupted);
+
+        let mut fork = searcher.fork(1 << 40);
+        assert_eq!(fork.fault(), None);
+        assert_eq!(fork.batch_status(false), BatchStatus::NotFound);
+        assert!(fork.search_batch(1_000).is_some());
+
+        searcher.restart();
+        assert_eq!(searcher.fault(), None);
+        assert!(searcher.search_batch(1_000).is_some());
+    }
+
+    #[test]
+    fn test_v1_tokens_keep_resuming_with_v1_seeds() {
+        let new = || Searcher::new(&[1; 32], &[4; 32], Some("zz".into()), None, false, 0).unwrap();
+        let mut saved = new();
+        saved.search_batch(30);
+        // V1 tokens are written as they were before schemes were recorded.
+        let token = saved.resume_token();
+        assert!(!token.contains("seed-v"));
+
+        for token in [token.clone(), format!("{token}.seed-v1")] {
+            let mut resumed = new();
+            resumed.resume_from(&token).unwrap();
+            assert_eq!(resumed.seed_scheme(), Some(SeedScheme::V1));
+            assert_eq!(resumed.attempts(), 30);
+            let found = loop {
+                if let Some(found) = resumed.search_batch(10_000) {
+                    break found;
+                }
+            };
+            assert_eq!(found.seed_scheme, Some(SeedScheme::V1));
+            assert_eq!(found.seed, SeedScheme::V1.seed_from_counter(found.counter));
+        }
+
+        let mut searcher = new();
+        searcher.set_seed_scheme(SeedScheme::V1).unwrap();
+        assert_eq!(searcher.resume_token(), new().resume_token());
+        let charset = crate::seed::SeedCharset::new("abc", 4).unwrap();
+        let mut charset_searcher = Searcher::with_pattern(
+            crate::derivation::CharsetSeedDerivation::new(&[1; 32], &[4; 32], charset),
+            Some("A".into()),
+            None,
+            false,
+            0,
+        )
+        .unwrap();
+        assert_eq!(charset_searcher.seed_scheme(), None);
+        assert_eq!(
+            charset_searcher
+                .set_seed_scheme(SeedScheme::V1)
+                .unwrap_err()
+                .kind(),
+            crate::ErrorKind::InvalidOptions
+        );
+    }
+
+    #[test]
+    fn test_legacy_wasm32_tokens_regenerate_their_seeds() {
+        let new = || Searcher::new(&[1; 32], &[4; 32], Some("zz".into()), None, false, 0).unwrap();
+        let search = |searcher: &mut Searcher| loop {
+            if let Some(found) = searcher.search_batch(10_000) {
+                break found;
+            }
+        };
+        // An old web session: legacy seeds, but a token that names no
+        // scheme and is fingerprinted as version 1, like any other then.
+        let mut old = new();
+        old.set_seed_scheme(SeedScheme::LegacyWasm32).unwrap();
+        old.search_batch(30);
+        let mut plain = new();
+        plain.search_batch(30);
+        let token = plain.resume_token();
+        let expected = search(&mut old);
+        assert_ne!(expected, search(&mut plain));
+
+        let mut resumed = new();
+        resumed
+            .resume_from_as(&token, SeedScheme::LegacyWasm32)
+            .unwrap();
+        assert_eq!(resumed.seed_scheme(), Some(SeedScheme::LegacyWasm32));
+        let found = search(&mut resumed);
+        assert_eq!(found, expected);
+        assert_eq!(
+            found.seed,
+            crate::seed::legacy_wasm32_seed_from_counter(found.counter)
+        );
+
+        // Tokens saved from now on name the legacy scheme, so they need no
+        // hint, and a hint doesn't override a named scheme.
+        let token = resumed.resume_token();
+        assert!(token.ends_with(".seed-v0"), "{token}");
+        let mut again = new();
+        again.resume_from(&token).unwrap();
+        assert_eq!(again.seed_scheme(), Some(SeedScheme::LegacyWasm32));
+        assert_eq!(search(&mut again), search(&mut resumed));
+        let mut named = new();
+        named
+            .resume_from_as(
+                &format!("{}.seed-v1", plain.resume_token()),
+                SeedScheme::LegacyWasm32,
+            )
+            .unwrap();
+        assert_eq!(named.seed_scheme(), Some(SeedScheme::V1));
+    }
+
+    #[test]
+    fn test_trace_shows_how_a_match_was_derived() {
+        use sha2::{Digest, Sha256};
+
+        let mut searcher =
+            Searcher::new(&[1; 32], &[4; 32], Some("ab".into()), None, true, 0).unwrap();
+        searcher.set_counter_permutation(Some(7));
+        searcher.set_seed_namespace(Some(SeedNamespace::new("trace").unwrap()));
+        let found = loop {
+            if let Some(found) = searcher.search_batch(10_000) {
+                break found;
+            }
+        };
+        let mut clock = 0.0;
+        let trace = searcher.trace(found.counter, || {
+            clock += 0.5;
+            clock
+        });
+        assert_eq!(trace.address, found.address);
+        assert_eq!(trace.seed.as_bytes(), found.seed);
+        assert_eq!(trace.counter, found.counter.to_string());
+        assert_eq!(trace.seed_namespace.as_deref(), Some("trace"));
+        assert_ne!(trace.seed_counter, trace.counter);
+        assert!(trace.matches);
+        assert_eq!(trace.byte_mask_passed, None);
+        assert!(trace.checks.iter().all(|check| check.passed));
+        assert_eq!(trace.timing.derive_ms, 0.5);
+
+        // The hex values are what a from-scratch derivation gives.
+        let input = [&[1; 32][..], &found.seed, &[4; 32]].concat();
+        assert_eq!(input.len(), 80);
+        assert_eq!(trace.hash_input_hex, to_hex(&input));
+        assert_eq!(trace.seed_hex, to_hex(&found.seed));
+        let digest = Sha256::digest(&input);
+        assert_eq!(trace.digest_hex, to_hex(&digest));
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        assert_eq!(
+            encode_pubkey(&digest.into(), &mut buf),
+            found.address.as_str()
+        );
+
+        let miss = searcher.trace(found.counter + 1, || 0.0);
+        assert_ne!(miss.address, found.address);
+        assert!(!miss.matches);
+
+        let byte_mask = ByteMask::new(&[0xff], &[0x00]).unwrap();
+        let masked =
+            Searcher::with_byte_mask(&[1; 32], &[4; 32], byte_mask, None, false, 0).unwrap();
+        let trace = masked.trace(3, || 0.0);
+        assert_eq!(trace.matches, trace.digest_hex.starts_with("00"));
+        assert_eq!(trace.byte_mask_passed, Some(trace.matches));
+        assert_eq!(trace.checks, []);
+    }
+}
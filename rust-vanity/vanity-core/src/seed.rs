@@ -0,0 +1,502 @@
+//! Mapping from search counters to createWithSeed seeds.
+//!
+//! Migration: until the self-test vectors were added, wasm32 builds reduced
+//! each seed state after truncating it to 32 bits, so the web app mapped
+//! counters to other seeds than native builds did. It now agrees with them.
+//! A web result that kept its seed is unaffected, since the seed is used as
+//! it is. A web counter or resume token from before then needs
+//! [`SeedScheme::LegacyWasm32`] (version 0) to get its seeds back: pass it
+//! to `reproduce`, or to `resume_from` for a token. Without it, the token
+//! carries on over seeds nobody searched.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::error::VanityError;
+use crate::trace::to_hex;
+
+pub const SEED_LEN: usize = 16;
+
+/// Longest seed `createWithSeed` accepts.
+pub const MAX_SEED_LEN: usize = 32;
+
+/// Version of the counter → seed mapping new searchers use,
+/// [`SeedScheme::LATEST`]. Seeds from different versions are not
+/// interchangeable, so saved progress should record it.
+pub const SEED_SCHEME_VERSION: u32 = SeedScheme::LATEST.version();
+
+/// A counter → seed mapping. Every scheme a release has used stays
+/// available, so results and progress saved under one keep reproducing
+/// after a newer one becomes the default. Saved state that doesn't record
+/// its scheme predates versioning and is [`SeedScheme::V1`], unless it came
+/// from a web build old enough to be [`SeedScheme::LegacyWasm32`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SeedScheme {
+    /// What wasm32 builds generated before the self-test vectors caught it
+    /// disagreeing with native builds: [`generate_seed_from_counter`] but
+    /// with each state truncated to its low 32 bits before the modulo, as
+    /// `state as usize` does there. Version 0. Back then every build called
+    /// its mapping version 1, so state saved by those web builds doesn't
+    /// say it used this one; choose it when restoring (see
+    /// [`crate::Searcher::resume_from_as`]). Never the default.
+    LegacyWasm32,
+    /// [`generate_seed_from_counter`], as native builds have always had it.
+    #[default]
+    V1,
+}
+
+impl SeedScheme {
+    pub const LATEST: SeedScheme = SeedScheme::V1;
+
+    /// Every scheme, oldest first.
+    pub const ALL: [SeedScheme; 2] = [SeedScheme::LegacyWasm32, SeedScheme::V1];
+
+    pub const fn version(self) -> u32 {
+        match self {
+            SeedScheme::LegacyWasm32 => 0,
+            SeedScheme::V1 => 1,
+        }
+    }
+
+    /// The version hashed into resume-token fingerprints. The legacy scheme
+    /// shares version 1's, since its tokens were fingerprinted before the
+    /// two were told apart.
+    pub(crate) const fn fingerprint_version(self) -> u32 {
+        match self {
+            SeedScheme::LegacyWasm32 | SeedScheme::V1 => 1,
+        }
+    }
+
+    /// Fails with `InvalidOptions` for a version this build doesn't know,
+    /// e.g. one saved by a newer release.
+    pub fn from_version(version: u32) -> Result<SeedScheme, VanityError> {
+        SeedScheme::ALL
+            .into_iter()
+            .find(|scheme| scheme.version() == version)
+            .ok_or_else(|| {
+                VanityError::InvalidOptions(format!(
+                    "unknown seed scheme version {version}; this build knows {} to {}",
+                    SeedScheme::ALL[0].version(),
+                    SeedScheme::LATEST.version()
+                ))
+            })
+    }
+
+    /// The seed for `counter` under this scheme.
+    pub fn seed_from_counter(self, counter: u64) -> [u8; SEED_LEN] {
+        match self {
+            SeedScheme::LegacyWasm32 => legacy_wasm32_seed_from_counter(counter),
+            SeedScheme::V1 => generate_seed_from_counter(counter),
+        }
+    }
+}
+
+pub const ALPHANUMERIC_CHARS: &[u8; 59] =
+    b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz0123456789";
+
+/// What [`generate_seed_from_counter`] multiplies the counter by for the
+/// second half of the seed: 2^64 over the golden ratio.
+pub(crate) const SEED_MIX_MULTIPLIER: u64 = 0x9E3779B97F4A7C15;
+
+/// Bits each half's state is shifted right by after every character.
+pub(crate) const SEED_STATE_SHIFT: u32 = 8;
+
+pub fn generate_seed_from_counter(counter: u64) -> [u8; SEED_LEN] {
+    // Use counter and hash to generate indices into valid chars - much faster than base conversion
+    let mut state1 = counter;
+    let mut state2 = counter.wrapping_mul(SEED_MIX_MULTIPLIER); // Golden ratio hash
+
+    // Reduce in u64 before indexing: `state as usize` would truncate on
+    // 32-bit targets such as wasm32 and give different seeds there. The
+    // halves are walked with iterators and the charset is an array indexed
+    // modulo its length, so nothing here is bounds-checked.
+    let len = ALPHANUMERIC_CHARS.len() as u64;
+    let mut seed = [0u8; SEED_LEN];
+    let (low, high) = seed.split_at_mut(SEED_LEN / 2);
+    for (low, high) in low.iter_mut().zip(high) {
+        *low = ALPHANUMERIC_CHARS[(state1 % len) as usize];
+        *high = ALPHANUMERIC_CHARS[(state2 % len) as usize];
+        state1 >>= SEED_STATE_SHIFT;
+        state2 >>= SEED_STATE_SHIFT;
+    }
+    seed
+}
+
+/// [`SeedScheme::LegacyWasm32`]: [`generate_seed_from_counter`] as it ran
+/// on 32-bit targets, reproduced on any target.
+pub fn legacy_wasm32_seed_from_counter(counter: u64) -> [u8; SEED_LEN] {
+    let mut state1 = counter;
+    let mut state2 = counter.wrapping_mul(SEED_MIX_MULTIPLIER);
+    let len = ALPHANUMERIC_CHARS.len() as u32;
+    let mut seed = [0u8; SEED_LEN];
+    let (low, high) = seed.split_at_mut(SEED_LEN / 2);
+    for (low, high) in low.iter_mut().zip(high) {
+        *low = ALPHANUMERIC_CHARS[(state1 as u32 % len) as usize];
+        *high = ALPHANUMERIC_CHARS[(state2 as u32 % len) as usize];
+        state1 >>= SEED_STATE_SHIFT;
+        state2 >>= SEED_STATE_SHIFT;
+    }
+    seed
+}
+
+/// Index of the first seed of `len` characters in the length-ordered
+/// enumeration [`short_seed_from_counter`] walks, or `None` if it is past
+/// the counter space (or `len` is 0).
+pub fn short_seed_tier_start(len: usize) -> Option<u64> {
+    if len == 0 {
+        return None;
+    }
+    let radix = ALPHANUMERIC_CHARS.len() as u64;
+    let mut start = 0u64;
+    let mut tier = 1u64;
+    for _ in 1..len {
+        tier = tier.checked_mul(radix)?;
+        start = start.checked_add(tier)?;
+    }
+    Some(start)
+}
+
+/// The `counter`th seed when every 1-character seed over
+/// [`ALPHANUMERIC_CHARS`] comes first, then every 2-character seed, and so
+/// on, each length in charset order. What's left of the counter after the
+/// shorter lengths is read as a base-59 number, most significant character
+/// first. The counter space reaches partway into 11-character seeds.
+pub fn short_seed_from_counter(counter: u64, buf: &mut [u8; MAX_SEED_LEN]) -> &[u8] {
+    let radix = ALPHANUMERIC_CHARS.len() as u64;
+    let mut index = counter;
+    let mut len = 1;
+    // Saturates only at 11 characters, a tier the counter space can't fill.
+    let mut tier = radix;
+    while index >= tier {
+        index -= tier;
+        len += 1;
+        tier = tier.saturating_mul(radix);
+    }
+    for c in buf[..len].iter_mut().rev() {
+        *c = ALPHANUMERIC_CHARS[(index % radix) as usize];
+        index /= radix;
+    }
+    &buf[..len]
+}
+
+/// Fixed-length seeds over a caller-chosen charset, e.g. 4 lowercase
+/// letters. The counter is read as a base-`charset.len()` number, most
+/// significant character first, so counters below [`SeedCharset::space`]
+/// each give a different seed and the ones past it repeat them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeedCharset {
+    charset: Vec<u8>,
+    seed_len: usize,
+}
+
+impl SeedCharset {
+    /// Fails with `InvalidOptions` unless `charset` has at least 2 distinct
+    /// printable ASCII characters and `seed_len` is between 1 and
+    /// [`MAX_SEED_LEN`].
+    pub fn new(charset: &str, seed_len: u32) -> Result<SeedCharset, VanityError> {
+        if seed_len == 0 || seed_len as usize > MAX_SEED_LEN {
+            return Err(VanityError::InvalidOptions(format!(
+                "seed length must be between 1 and {MAX_SEED_LEN}, got {seed_len}"
+            )));
+        }
+        let charset = charset.as_bytes();
+        if let Some(c) = charset.iter().find(|c| !c.is_ascii_graphic()) {
+            return Err(VanityError::InvalidOptions(format!(
+                "seed charset must be printable ASCII, got {:?}",
+                char::from(*c)
+            )));
+        }
+        if let Some(i) = (1..charset.len()).find(|&i| charset[..i].contains(&charset[i])) {
+            return Err(VanityError::InvalidOptions(format!(
+                "seed charset repeats {:?}",
+                char::from(charset[i])
+            )));
+        }
+        if charset.len() < 2 {
+            return Err(VanityError::InvalidOptions(
+                "seed charset needs at least 2 characters".into(),
+            ));
+        }
+        Ok(SeedCharset {
+            charset: charset.to_vec(),
+            seed_len: seed_len as usize,
+        })
+    }
+
+    pub fn charset(&self) -> &[u8] {
+        &self.charset
+    }
+
+    pub fn seed_len(&self) -> u32 {
+        self.seed_len as u32
+    }
+
+    /// Number of distinct seeds, or `None` if there are more than counters.
+    pub fn space(&self) -> Option<u64> {
+        (self.charset.len() as u64).checked_pow(self.seed_len as u32)
+    }
+
+    /// The `counter`th seed, wrapping around past [`SeedCharset::space`].
+    pub fn seed_from_counter<'a>(&self, counter: u64, buf: &'a mut [u8; MAX_SEED_LEN]) -> &'a [u8] {
+        let radix = self.charset.len() as u64;
+        let mut index = match self.space() {
+            Some(space) => counter % space,
+            None => counter,
+        };
+        for c in buf[..self.seed_len].iter_mut().rev() {
+            *c = self.charset[(index % radix) as usize];
+            index /= radix;
+        }
+        &buf[..self.seed_len]
+    }
+}
+
+/// Marks a seed shown by [`seed_to_string`] as hex rather than as text.
+pub const HEX_SEED_PREFIX: &str = "hex:";
+
+/// A seed for display: the seed itself when its bytes are UTF-8, otherwise
+/// (or if the text would itself start with [`HEX_SEED_PREFIX`]) the prefix
+/// followed by lowercase hex. Never lossy; [`seed_from_string`] gives the
+/// bytes back.
+pub fn seed_to_string(seed: &[u8]) -> String {
+    match core::str::from_utf8(seed) {
+        Ok(text) if !text.starts_with(HEX_SEED_PREFIX) => text.into(),
+        _ => format!("{HEX_SEED_PREFIX}{}", to_hex(seed)),
+    }
+}
+
+/// Inverse of [`seed_to_string`]. Fails with `InvalidOptions` if the hex
+/// after [`HEX_SEED_PREFIX`] is malformed.
+pub fn seed_from_string(seed: &str) -> Result<Vec<u8>, VanityError> {
+    let Some(hex) = seed.strip_prefix(HEX_SEED_PREFIX) else {
+        return Ok(seed.as_bytes().to_vec());
+    };
+    let invalid = || VanityError::InvalidOptions(format!("invalid hex seed {seed:?}"));
+    if hex.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    #[test]
+    fn test_seed_schemes_are_pinned() {
+        // Changing what any of these print breaks every result and resume
+        // token saved under the scheme: add a new scheme instead.
+        let pinned: [(SeedScheme, &[(u64, &str)]); 2] = [
+            (
+                SeedScheme::LegacyWasm32,
+                &[
+                    (0, "AAAAAAAAAAAAAAAA"),
+                    (1, "BAAAAAAAb7V0w3fr"),
+                    (123_456_789, "7x5HAAAA1gA3Z8VP"),
+                    (u64::MAX, "11111kwVc4hBFsSp"),
+                ],
+            ),
+            (
+                SeedScheme::V1,
+                &[
+                    (0, "AAAAAAAAAAAAAAAA"),
+                    (1, "BAAAAAAAV4Zaw3fr"),
+                    (123_456_789, "7x5HAAAAuxbHZ8VP"),
+                    (u64::MAX, "EQvS1kwVwWX2FsSp"),
+                ],
+            ),
+        ];
+        for (scheme, pairs) in pinned {
+            for &(counter, seed) in pairs {
+                let derived = scheme.seed_from_counter(counter);
+                assert_eq!(
+                    core::str::from_utf8(&derived).unwrap(),
+                    seed,
+                    "{scheme:?} {counter}"
+                );
+            }
+            assert_eq!(SeedScheme::from_version(scheme.version()), Ok(scheme));
+        }
+        assert_eq!(SeedScheme::ALL.last(), Some(&SeedScheme::LATEST));
+        assert_eq!(SEED_SCHEME_VERSION, SeedScheme::LATEST.version());
+        for version in [SeedScheme::LATEST.version() + 1, u32::MAX] {
+            assert_eq!(
+                SeedScheme::from_version(version).unwrap_err().kind(),
+                crate::ErrorKind::InvalidOptions
+            );
+        }
+    }
+
+    #[test]
+    fn test_seed_uses_charset() {
+        for counter in [0, 1, 57, 58, u64::MAX] {
+            let seed = generate_seed_from_counter(counter);
+            assert!(seed.iter().all(|c| ALPHANUMERIC_CHARS.contains(c)));
+        }
+    }
+
+    /// The loop [`generate_seed_from_counter`] was before its lookups were
+    /// made provably in bounds.
+    fn indexed_seed_from_counter(counter: u64) -> [u8; SEED_LEN] {
+        let mut seed = [0u8; SEED_LEN];
+        let mut state1 = counter;
+        let mut state2 = counter.wrapping_mul(0x9E3779B97F4A7C15);
+        let len = ALPHANUMERIC_CHARS.len() as u64;
+        for i in 0..8 {
+            seed[i] = ALPHANUMERIC_CHARS[(state1 % len) as usize];
+            seed[i + 8] = ALPHANUMERIC_CHARS[(state2 % len) as usize];
+            state1 >>= 8;
+            state2 >>= 8;
+        }
+        seed
+    }
+
+    #[test]
+    fn test_seeds_match_the_indexed_loop() {
+        let strided = (0..1_000_000u64).map(|i| i.wrapping_mul(0x0000_1234_5678_9abd));
+        for counter in (0..1_000_000)
+            .chain(strided)
+            .chain(u64::MAX - 1_000..=u64::MAX)
+        {
+            assert_eq!(
+                generate_seed_from_counter(counter),
+                indexed_seed_from_counter(counter),
+                "{counter}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_legacy_scheme_is_the_loop_wasm32_ran() {
+        // The loop before the fix, with usize 32 bits wide as on wasm32.
+        let wasm32 = |counter: u64| {
+            let mut seed = [0u8; SEED_LEN];
+            let mut state1 = counter;
+            let mut state2 = counter.wrapping_mul(0x9E3779B97F4A7C15);
+            for i in 0..8 {
+                seed[i] = ALPHANUMERIC_CHARS[state1 as u32 as usize % ALPHANUMERIC_CHARS.len()];
+                seed[i + 8] = ALPHANUMERIC_CHARS[state2 as u32 as usize % ALPHANUMERIC_CHARS.len()];
+                state1 >>= 8;
+                state2 >>= 8;
+            }
+            seed
+        };
+        let strided = (0..100_000u64).map(|i| i.wrapping_mul(0x0000_1234_5678_9abd));
+        let mut differ = 0;
+        for counter in (0..100_000).chain(strided) {
+            let legacy = legacy_wasm32_seed_from_counter(counter);
+            assert_eq!(legacy, wasm32(counter), "{counter}");
+            differ += usize::from(legacy != generate_seed_from_counter(counter));
+        }
+        // Almost every counter's second half differs.
+        assert!(differ > 199_000, "{differ}");
+    }
+
+    #[test]
+    fn test_seed_is_deterministic() {
+        assert_eq!(generate_seed_from_counter(0), *b"AAAAAAAAAAAAAAAA");
+        assert_eq!(
+            generate_seed_from_counter(12345),
+            generate_seed_from_counter(12345)
+        );
+        assert_ne!(generate_seed_from_counter(1), generate_seed_from_counter(2));
+    }
+
+    #[test]
+    fn test_short_seeds_enumerate_by_length() {
+        let mut buf = [0u8; MAX_SEED_LEN];
+        let mut seed = |counter| {
+            String::from_utf8(short_seed_from_counter(counter, &mut buf).to_vec()).unwrap()
+        };
+        assert_eq!(seed(0), "A");
+        assert_eq!(seed(58), "9");
+        assert_eq!(seed(59), "AA");
+        assert_eq!(seed(60), "AB");
+        assert_eq!(seed(59 + 58), "A9");
+        assert_eq!(seed(59 + 59), "BA");
+        assert_eq!(seed(59 + 59 * 59 - 1), "99");
+        assert_eq!(seed(59 + 59 * 59), "AAA");
+        assert_eq!(seed(u64::MAX).len(), 11);
+
+        assert_eq!(short_seed_tier_start(0), None);
+        assert_eq!(short_seed_tier_start(1), Some(0));
+        assert_eq!(short_seed_tier_start(3), Some(59 + 59 * 59));
+        assert!(short_seed_tier_start(11).is_some());
+        assert_eq!(short_seed_tier_start(12), None);
+    }
+
+    #[test]
+    fn test_short_seeds_are_distinct_across_tiers() {
+        let mut buf = [0u8; MAX_SEED_LEN];
+        let mut seen = std::collections::HashSet::new();
+        for len in 1..=11 {
+            let start = short_seed_tier_start(len).unwrap();
+            for counter in start.saturating_sub(50)..start + 50 {
+                let seed = short_seed_from_counter(counter, &mut buf);
+                let expected_len = if counter < start { len - 1 } else { len };
+                assert_eq!(seed.len(), expected_len, "{counter}");
+                assert!(seed.iter().all(|c| ALPHANUMERIC_CHARS.contains(c)));
+                seen.insert((counter, seed.to_vec()));
+            }
+        }
+        let seeds: std::collections::HashSet<_> = seen.iter().map(|(_, seed)| seed).collect();
+        assert_eq!(seeds.len(), seen.len());
+    }
+
+    #[test]
+    fn test_charset_seeds_cover_their_space() {
+        let charset = SeedCharset::new("ab", 3).unwrap();
+        assert_eq!(charset.space(), Some(8));
+        let mut buf = [0u8; MAX_SEED_LEN];
+        let mut seed = |counter| {
+            String::from_utf8(charset.seed_from_counter(counter, &mut buf).to_vec()).unwrap()
+        };
+        assert_eq!(seed(0), "aaa");
+        assert_eq!(seed(1), "aab");
+        assert_eq!(seed(7), "bbb");
+        assert_eq!(seed(8), "aaa");
+        let seeds: std::collections::HashSet<_> = (0..8).map(seed).collect();
+        assert_eq!(seeds.len(), 8);
+
+        assert_eq!(
+            SeedCharset::new("abcdefghijklmnopqrstuvwxyz", 4)
+                .unwrap()
+                .space(),
+            Some(456_976)
+        );
+        let alphanumeric = core::str::from_utf8(ALPHANUMERIC_CHARS).unwrap();
+        assert_eq!(SeedCharset::new(alphanumeric, 16).unwrap().space(), None);
+        for (charset, len) in [
+            ("a", 4),
+            ("aba", 4),
+            ("ab c", 4),
+            ("ab\u{e9}", 4),
+            ("ab", 0),
+            ("ab", 33),
+        ] {
+            assert!(SeedCharset::new(charset, len).is_err(), "{charset:?} {len}");
+        }
+    }
+
+    #[test]
+    fn test_seed_strings_round_trip() {
+        assert_eq!(seed_to_string(b"Ab3x"), "Ab3x");
+        let binary = [0xff, 0x00, b'a', 0xfe];
+        assert_eq!(seed_to_string(&binary), "hex:ff0061fe");
+        assert_eq!(seed_to_string(b"hex:00"), "hex:6865783a3030");
+        for seed in [&b"Ab3x"[..], &binary, b"hex:00", b""] {
+            assert_eq!(seed_from_string(&seed_to_string(seed)).unwrap(), seed);
+        }
+        for bad in ["hex:f", "hex:zz", "hex:\u{e9}0"] {
+            assert!(seed_from_string(bad).is_err());
+        }
+    }
+}
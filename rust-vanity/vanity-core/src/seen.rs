@@ -0,0 +1,272 @@
+//! A Bloom filter of counter blocks already searched, so a session can skip
+//! ground another session (or device) covered without keeping a full log.
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::VanityError;
+use crate::float;
+use crate::permute::mix;
+
+/// Counters per block are `2^SEEN_BLOCK_BITS`: the filter only ever records
+/// whole blocks, about 1M counters each.
+pub const SEEN_BLOCK_BITS: u32 = 20;
+pub const SEEN_BLOCK_SIZE: u64 = 1 << SEEN_BLOCK_BITS;
+
+/// First byte of [`SeenFilter::to_bytes`].
+const FORMAT_VERSION: u8 = 1;
+/// Version, block bits and hash count, then the bit words.
+const HEADER_LEN: usize = 3;
+/// 128 MiB of bits; anything bigger is a mistake in the sizing.
+const MAX_BITS: u64 = 1 << 30;
+const MAX_HASHES: u32 = 32;
+
+/// Covered counter blocks, as a Bloom filter: `maybe_covered` is never
+/// false for a block that was inserted, and true for one that wasn't with
+/// about the false positive rate it was sized for, once it holds the
+/// expected number of blocks (more blocks, more false positives). A false
+/// positive makes a searcher skip a block nobody searched, so size the
+/// filter with a small rate. Counters are logical ones, as in
+/// `count_offset` and ranges, and only mean something for one set of keys
+/// and pattern: filters from different searches must not be merged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeenFilter {
+    words: Vec<u64>,
+    hashes: u32,
+}
+
+impl SeenFilter {
+    /// A filter for about `expected_blocks` blocks at `false_positive_rate`,
+    /// with the optimal bit count `-n ln p / ln² 2` and `(m / n) ln 2`
+    /// hashes. Fails with `InvalidOptions` unless `expected_blocks > 0` and
+    /// `0 < false_positive_rate < 1`, or if the filter would be huge.
+    pub fn new(expected_blocks: u64, false_positive_rate: f64) -> Result<SeenFilter, VanityError> {
+        if expected_blocks == 0 {
+            return Err(VanityError::InvalidOptions(
+                "seen filter must expect at least one block".into(),
+            ));
+        }
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(VanityError::InvalidOptions(format!(
+                "false positive rate must be between 0 and 1 (exclusive), got {false_positive_rate}"
+            )));
+        }
+        let ln2 = core::f64::consts::LN_2;
+        let n = expected_blocks as f64;
+        let bits = float::ceil(-n * float::ln(false_positive_rate) / (ln2 * ln2));
+        if bits > MAX_BITS as f64 {
+            return Err(VanityError::InvalidOptions(format!(
+                "seen filter for {expected_blocks} blocks at {false_positive_rate} would need \
+                 {bits} bits; at most {MAX_BITS} are allowed"
+            )));
+        }
+        let words = (bits as u64).div_ceil(64).max(1) as usize;
+        let hashes = float::round(words as f64 * 64.0 / n * ln2).clamp(1.0, MAX_HASHES as f64);
+        Ok(SeenFilter {
+            words: vec![0; words],
+            hashes: hashes as u32,
+        })
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.words.len() as u64 * 64
+    }
+
+    pub fn hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    /// Bit positions for `block`, by double hashing.
+    fn positions(&self, block: u64) -> impl Iterator<Item = usize> {
+        let bits = self.bits();
+        let h1 = mix(block);
+        let h2 = mix(block ^ 0x5eed_f11e_c0de_b10c) | 1;
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bits) as usize)
+    }
+
+    pub fn insert_block(&mut self, block: u64) {
+        for position in self.positions(block).collect::<Vec<_>>() {
+            self.words[position / 64] |= 1 << (position % 64);
+        }
+    }
+
+    pub fn contains_block(&self, block: u64) -> bool {
+        self.positions(block)
+            .all(|position| self.words[position / 64] & (1 << (position % 64)) != 0)
+    }
+
+    /// Records the blocks lying wholly inside `len` counters from `start`
+    /// and returns how many there are. Blocks the range only partly covers
+    /// are left out, so insert ranges of whole blocks (multiples of
+    /// [`SEEN_BLOCK_SIZE`], aligned to one) to record everything searched.
+    pub fn insert_range(&mut self, start: u64, len: u64) -> u64 {
+        // In u128, so a range reaching 2^64 records the last block.
+        let end = (u128::from(start) + u128::from(len)).min(1 << 64);
+        let first = start.div_ceil(SEEN_BLOCK_SIZE);
+        let last = (end >> SEEN_BLOCK_BITS) as u64;
+        for block in first..last {
+            self.insert_block(block);
+        }
+        last.saturating_sub(first)
+    }
+
+    /// Whether `counter`'s block was recorded, or is a false positive.
+    pub fn maybe_covered(&self, counter: u64) -> bool {
+        self.contains_block(counter >> SEEN_BLOCK_BITS)
+    }
+
+    /// Adds every block `other` holds. Fails with `InvalidSeenFilter` unless
+    /// both were sized alike.
+    pub fn merge(&mut self, other: &SeenFilter) -> Result<(), VanityError> {
+        if self.words.len() != other.words.len() || self.hashes != other.hashes {
+            return Err(VanityError::InvalidSeenFilter(format!(
+                "can't merge a filter of {} bits and {} hashes into one of {} bits and {} hashes",
+                other.bits(),
+                other.hashes,
+                self.bits(),
+                self.hashes
+            )));
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+        Ok(())
+    }
+
+    /// The chance a block never inserted tests as covered, estimated from
+    /// the bits set: `(set / bits)^hashes`.
+    pub fn false_positive_rate(&self) -> f64 {
+        let set: u32 = self.words.iter().map(|word| word.count_ones()).sum();
+        float::powi(set as f64 / self.bits() as f64, self.hashes as i32)
+    }
+
+    /// A version byte, the block bits, the hash count, then the bits as
+    /// little-endian `u64` words.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.words.len() * 8);
+        bytes.extend([FORMAT_VERSION, SEEN_BLOCK_BITS as u8, self.hashes as u8]);
+        for word in &self.words {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Reads [`SeenFilter::to_bytes`] back. Fails with `InvalidSeenFilter`
+    /// on another version or block size, or a truncated filter.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SeenFilter, VanityError> {
+        let invalid = |detail: &str| Err(VanityError::InvalidSeenFilter(detail.into()));
+        let Some((&[version, block_bits, hashes], body)) = bytes.split_first_chunk() else {
+            return invalid("too short");
+        };
+        if version != FORMAT_VERSION {
+            return Err(VanityError::InvalidSeenFilter(format!(
+                "unsupported version {version}"
+            )));
+        }
+        if block_bits as u32 != SEEN_BLOCK_BITS {
+            return Err(VanityError::InvalidSeenFilter(format!(
+                "blocks of 2^{block_bits} counters, expected 2^{SEEN_BLOCK_BITS}"
+            )));
+        }
+        if hashes == 0 || hashes as u32 > MAX_HASHES {
+            return invalid("bad hash count");
+        }
+        if body.is_empty() || body.len() % 8 != 0 || body.len() as u64 * 8 > MAX_BITS {
+            return invalid("bits aren't a whole number of words");
+        }
+        Ok(SeenFilter {
+            words: body
+                .chunks_exact(8)
+                .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+                .collect(),
+            hashes: hashes as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_sizing() {
+        // 1000 blocks at 1%: 9586 bits, rounded up to words, and 7 hashes.
+        let filter = SeenFilter::new(1_000, 0.01).unwrap();
+        assert_eq!(filter.bits(), 9_600);
+        assert_eq!(filter.hashes(), 7);
+        assert_eq!(filter.false_positive_rate(), 0.0);
+
+        for (blocks, rate) in [(0, 0.01), (10, 0.0), (10, 1.0), (u64::MAX, 0.01)] {
+            let error = SeenFilter::new(blocks, rate).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidOptions);
+        }
+    }
+
+    #[test]
+    fn test_ranges_insert_whole_blocks() {
+        let mut filter = SeenFilter::new(100, 1e-6).unwrap();
+        // Only blocks 2 and 3 lie wholly inside.
+        let inserted = filter.insert_range(SEEN_BLOCK_SIZE + 5, 3 * SEEN_BLOCK_SIZE);
+        assert_eq!(inserted, 2);
+        assert!(!filter.maybe_covered(SEEN_BLOCK_SIZE + 5));
+        assert!(filter.maybe_covered(2 * SEEN_BLOCK_SIZE));
+        assert!(filter.maybe_covered(4 * SEEN_BLOCK_SIZE - 1));
+        assert!(!filter.maybe_covered(4 * SEEN_BLOCK_SIZE));
+        assert_eq!(filter.insert_range(7, 100), 0);
+        assert_eq!(filter.insert_range(u64::MAX - 5, 100), 0);
+        assert!(!filter.maybe_covered(u64::MAX));
+        let last_block = u64::MAX - SEEN_BLOCK_SIZE + 1;
+        assert_eq!(filter.insert_range(last_block, SEEN_BLOCK_SIZE), 1);
+        assert!(filter.maybe_covered(u64::MAX));
+    }
+
+    #[test]
+    fn test_false_positives_match_the_sizing() {
+        let mut filter = SeenFilter::new(1_000, 0.01).unwrap();
+        for block in 0..1_000 {
+            filter.insert_block(block * 7);
+        }
+        assert!((0..1_000).all(|block| filter.contains_block(block * 7)));
+        let false_positives = (1_000_000..1_100_000)
+            .filter(|&block| filter.contains_block(block))
+            .count();
+        // 1% of 100 000 is 1000; the estimate from the bits agrees.
+        assert!((700..1_300).contains(&false_positives), "{false_positives}");
+        let estimate = filter.false_positive_rate();
+        assert!((0.007..0.013).contains(&estimate), "{estimate}");
+    }
+
+    #[test]
+    fn test_round_trip_and_merge() {
+        let mut first = SeenFilter::new(50, 1e-4).unwrap();
+        first.insert_range(0, 10 * SEEN_BLOCK_SIZE);
+        let mut second = SeenFilter::new(50, 1e-4).unwrap();
+        second.insert_range(20 * SEEN_BLOCK_SIZE, 10 * SEEN_BLOCK_SIZE);
+
+        let bytes = first.to_bytes();
+        assert_eq!(bytes.len(), 3 + first.bits() as usize / 8);
+        let mut restored = SeenFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(restored, first);
+
+        restored.merge(&second).unwrap();
+        for block in (0..10).chain(20..30) {
+            assert!(restored.contains_block(block), "{block}");
+        }
+        assert!(!restored.contains_block(15));
+
+        let error = restored
+            .merge(&SeenFilter::new(500, 1e-4).unwrap())
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidSeenFilter);
+        for bad in [
+            &bytes[..2],
+            &bytes[..bytes.len() - 1],
+            &[2, 20, 7, 0, 0, 0, 0, 0, 0, 0, 0],
+        ] {
+            let error = SeenFilter::from_bytes(bad).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidSeenFilter);
+        }
+    }
+}
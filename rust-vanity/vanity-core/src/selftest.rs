@@ -0,0 +1,131 @@
+//! Known-answer vectors run through the whole counter → seed → address →
+//! match pipeline, so a miscompiled or corrupted build is caught before it
+//! starts grinding.
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::derive::PUBKEY_LEN;
+use crate::error::VanityError;
+use crate::matcher::MatchType;
+use crate::search::Searcher;
+use crate::seed::SEED_LEN;
+
+struct KnownAnswer {
+    base: &'static str,
+    owner: &'static str,
+    counter: u64,
+    seed: &'static [u8; SEED_LEN],
+    address: &'static str,
+}
+
+/// Addresses checked against `Pubkey::create_with_seed` from solana-pubkey
+/// (see the tests). They include a 43-character encoding and counters above
+/// 2^32, which exercise the seed mapping's full 64-bit state.
+const KNOWN_ANSWERS: [KnownAnswer; 5] = [
+    KnownAnswer {
+        base: "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+        owner: "GgBaCs3NCBuZN12kCJgAW63ydqohFkHEdfdEXBPzLHq",
+        counter: 51_051,
+        seed: b"SYAAAAAAW4UTiYNF",
+        address: "AAArB3C2J4JtzZDGh9kQBDVU1c3fdDoH1UF1qiyGLzoj",
+    },
+    KnownAnswer {
+        base: "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+        owner: "GgBaCs3NCBuZN12kCJgAW63ydqohFkHEdfdEXBPzLHq",
+        counter: 11_400_714_819_323_198_485,
+        seed: b"V4Zaw3frjSTNJrvx",
+        address: "AjYmeS8BSxkaNqLvGFtjMP2u7xRuwvb7DJQjzhT1XgYM",
+    },
+    KnownAnswer {
+        base: "1thX6LZfHDZZKUs92febYZhYRcXddmzfzF2NvTkPNE",
+        owner: "11111111111111111111111111111111",
+        counter: 4_294_967_303,
+        seed: b"9mxWBAAAhGPNXFrj",
+        address: "788SDCNiz2SXjqbjdzR6vCpGPvRko4ah1iRdKXegNgbY",
+    },
+    KnownAnswer {
+        base: "H5hM4fqRjygvCYXnp6dgFLgZ6o4uJ8Q9z7dAsTfapHmF",
+        owner: "17EtdeMwcxWvuEN3yCfYAowtnyHhRSRxhuhAeTEEhZa",
+        counter: 4_294_967_303,
+        seed: b"9mxWBAAAhGPNXFrj",
+        address: "QoABPr8vkbecv3jgfPtb5K9kgSiZnWg6YEq6b8mvx5n",
+    },
+    KnownAnswer {
+        base: "H5hM4fqRjygvCYXnp6dgFLgZ6o4uJ8Q9z7dAsTfapHmF",
+        owner: "17EtdeMwcxWvuEN3yCfYAowtnyHhRSRxhuhAeTEEhZa",
+        counter: u64::MAX,
+        seed: b"EQvS1kwVwWX2FsSp",
+        address: "E3zrgQHbeciDiVdg1YqyvMpkLP4akKZR2CLFnAz8nt8J",
+    },
+];
+
+fn decode(pubkey: &str) -> Result<[u8; PUBKEY_LEN], VanityError> {
+    let mut out = [0u8; PUBKEY_LEN];
+    five8::decode_32(pubkey, &mut out)
+        .map_err(|error| VanityError::Internal(format!("bad vector key {pubkey}: {error:?}")))?;
+    Ok(out)
+}
+
+/// Searches for each vector's own address, one attempt at its counter, and
+/// checks the match's seed and address. Takes a few microseconds.
+pub fn self_test() -> Result<(), VanityError> {
+    for (i, vector) in KNOWN_ANSWERS.iter().enumerate() {
+        let match_type = MatchType::new(Some(vector.address.into()), None, false);
+        let mut searcher = Searcher::with_match_type(
+            &decode(vector.base)?,
+            &decode(vector.owner)?,
+            match_type,
+            false,
+            vector.counter,
+        );
+        let found = searcher.search_batch(1);
+        let (seed, address) = found.as_ref().map_or((&[][..], "no match"), |found| {
+            (&found.seed[..], &found.address)
+        });
+        if seed != vector.seed || address != vector.address {
+            return Err(VanityError::SelfTestFailed(format!(
+                "vector {i}: counter {} gave seed {:?} and {address}, expected {:?} and {}",
+                vector.counter,
+                String::from_utf8_lossy(seed),
+                String::from_utf8_lossy(vector.seed),
+                vector.address,
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use core::str::FromStr;
+
+    use solana_pubkey::Pubkey;
+
+    use super::*;
+    use crate::seed::generate_seed_from_counter;
+
+    #[test]
+    fn test_self_test_passes() {
+        assert_eq!(self_test(), Ok(()));
+    }
+
+    #[test]
+    fn test_vectors_match_solana() {
+        for vector in &KNOWN_ANSWERS {
+            assert_eq!(generate_seed_from_counter(vector.counter), *vector.seed);
+            let address = Pubkey::create_with_seed(
+                &Pubkey::from_str(vector.base).unwrap(),
+                core::str::from_utf8(vector.seed).unwrap(),
+                &Pubkey::from_str(vector.owner).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(address.to_string(), vector.address);
+        }
+        let lengths: Vec<usize> = KNOWN_ANSWERS.iter().map(|v| v.address.len()).collect();
+        assert!(lengths.contains(&43) && lengths.contains(&44));
+    }
+}
@@ -0,0 +1,245 @@
+//! Shortest-seed-first search: every 1-character seed, then every
+//! 2-character seed, and so on, so the match found is as short to type as
+//! the pattern allows.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use serde::Serialize;
+
+use crate::derive::{encode_pubkey, Deriver, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::float;
+use crate::matcher::MatchType;
+use crate::seed::{seed_to_string, short_seed_from_counter, short_seed_tier_start, MAX_SEED_LEN};
+
+/// Seeds up to this long count as short for [`ShortSeedEstimate::warning`].
+pub const SHORT_SEED_LEN: u32 = 4;
+
+/// Longest seed the counter space reaches.
+const LONGEST_SHORT_SEED: usize = 11;
+
+/// A match found by a [`ShortSeedSearcher`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct ShortMatch {
+    pub address: String,
+    pub seed: String,
+    pub seed_len: u32,
+    pub attempts: u64,
+    /// Index of the seed in the length-ordered enumeration.
+    pub counter: u64,
+}
+
+/// Odds of a short seed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct ShortSeedEstimate {
+    pub expected_attempts: f64,
+    /// Shortest length whose seeds, with all shorter ones, more likely than
+    /// not include a match; unset if even the whole counter space probably
+    /// doesn't.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub likely_seed_len: Option<u32>,
+    /// Set when seeds of up to [`SHORT_SEED_LEN`] characters will probably
+    /// all be tried without a match.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub warning: Option<String>,
+}
+
+/// How long a seed to expect when a single address matches with
+/// `probability`.
+pub fn short_seed_estimate(probability: f64) -> ShortSeedEstimate {
+    let likely_seed_len = (1..=LONGEST_SHORT_SEED).find_map(|len| {
+        let seeds = short_seed_tier_start(len + 1).unwrap_or(u64::MAX) as f64;
+        // 1 - (1 - p)^seeds, accurate for tiny p.
+        let found = -float::exp_m1(seeds * float::ln_1p(-probability));
+        (found >= 0.5).then_some(len as u32)
+    });
+    let warning = match likely_seed_len {
+        Some(len) if len <= SHORT_SEED_LEN => None,
+        Some(len) => Some(format!(
+            "every seed of up to {SHORT_SEED_LEN} characters will probably be tried without a \
+             match; expect one of about {len}"
+        )),
+        None => Some("the counter space will probably run out before a match".to_string()),
+    };
+    ShortSeedEstimate {
+        expected_attempts: 1.0 / probability,
+        likely_seed_len,
+        warning,
+    }
+}
+
+/// Walks counters like [`crate::Searcher`], but through
+/// [`short_seed_from_counter`], so each length is used up before the next
+/// begins and the first match has the shortest seed that works (within a
+/// length, the first in charset order).
+#[derive(Clone)]
+pub struct ShortSeedSearcher {
+    deriver: Deriver,
+    match_type: MatchType,
+    case_insensitive: bool,
+    count: u64,
+    count_offset: u64,
+}
+
+impl ShortSeedSearcher {
+    /// Fails on an empty or unmatchable pattern, as [`crate::Searcher::new`]
+    /// does. `count_offset` is an index into the enumeration, e.g.
+    /// [`short_seed_tier_start`] to skip the shorter lengths.
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        match_type: MatchType,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Result<ShortSeedSearcher, VanityError> {
+        if match_type.is_empty() {
+            return Err(VanityError::EmptyPattern);
+        }
+        match_type.validate(case_insensitive)?;
+        Ok(ShortSeedSearcher {
+            deriver: Deriver::new(base_pubkey, owner_pubkey),
+            match_type,
+            case_insensitive,
+            count: 0,
+            count_offset,
+        })
+    }
+
+    /// Tries up to `batch_size` more seeds, stopping at the first match or
+    /// the end of the counter space.
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<ShortMatch> {
+        let mut seed_buf = [0u8; MAX_SEED_LEN];
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for _ in 0..batch_size {
+            let counter = self.absolute_position()?;
+            let seed = short_seed_from_counter(counter, &mut seed_buf);
+            let address = encode_pubkey(&self.deriver.derive(seed), &mut encoded_buf);
+            self.count += 1;
+
+            if self
+                .match_type
+                .matches(address.as_bytes(), self.case_insensitive)
+            {
+                return Some(ShortMatch {
+                    address: address.to_string(),
+                    seed: seed_to_string(seed),
+                    seed_len: seed.len() as u32,
+                    attempts: self.count,
+                    counter,
+                });
+            }
+        }
+        None
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// Index of the next seed to try, or `None` once the counter space is
+    /// used up.
+    pub fn absolute_position(&self) -> Option<u64> {
+        self.count_offset.checked_add(self.count)
+    }
+
+    /// Length of the seeds being tried.
+    pub fn current_seed_len(&self) -> Option<u32> {
+        let mut seed_buf = [0u8; MAX_SEED_LEN];
+        self.absolute_position()
+            .map(|counter| short_seed_from_counter(counter, &mut seed_buf).len() as u32)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.absolute_position().is_none()
+    }
+
+    pub fn estimate(&self) -> ShortSeedEstimate {
+        short_seed_estimate(self.match_type.probability(self.case_insensitive))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_the_shortest_matching_seed() {
+        let base = [1; 32];
+        let owner = [4; 32];
+        let match_type = MatchType::Prefix("AB".into());
+        let mut searcher =
+            ShortSeedSearcher::new(&base, &owner, match_type.clone(), false, 0).unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(1000) {
+                break found;
+            }
+        };
+        assert!(found.address.starts_with("AB"));
+        assert_eq!(found.seed.len() as u32, found.seed_len);
+        assert_eq!(found.counter, found.attempts - 1);
+
+        // No earlier seed in the enumeration matches.
+        let deriver = Deriver::new(&base, &owner);
+        let mut seed_buf = [0u8; MAX_SEED_LEN];
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for counter in 0..found.counter {
+            let seed = short_seed_from_counter(counter, &mut seed_buf);
+            assert!(seed.len() <= found.seed.len());
+            let address = encode_pubkey(&deriver.derive(seed), &mut buf);
+            assert!(!match_type.matches(address.as_bytes(), false), "{counter}");
+        }
+        let pubkey = deriver.derive(found.seed.as_bytes());
+        assert_eq!(encode_pubkey(&pubkey, &mut buf), found.address);
+    }
+
+    #[test]
+    fn test_tiers_roll_over() {
+        let start = short_seed_tier_start(3).unwrap();
+        let mut searcher = ShortSeedSearcher::new(
+            &[1; 32],
+            &[4; 32],
+            MatchType::Prefix("zzzz".into()),
+            false,
+            start - 5,
+        )
+        .unwrap();
+        assert_eq!(searcher.current_seed_len(), Some(2));
+        searcher.search_batch(5);
+        assert_eq!(searcher.current_seed_len(), Some(3));
+        assert_eq!(searcher.attempts(), 5);
+
+        let mut end = ShortSeedSearcher::new(
+            &[1; 32],
+            &[4; 32],
+            MatchType::Prefix("zzzz".into()),
+            false,
+            u64::MAX,
+        )
+        .unwrap();
+        assert_eq!(end.search_batch(10), None);
+        assert!(end.is_exhausted());
+        assert_eq!(end.current_seed_len(), None);
+    }
+
+    #[test]
+    fn test_short_seed_estimate() {
+        let easy = short_seed_estimate(1.0 / 58.0);
+        assert_eq!(easy.likely_seed_len, Some(1));
+        assert_eq!(easy.warning, None);
+
+        // Seeds of up to 3 characters are far too few; 4 make a match likely.
+        let four = short_seed_estimate(58f64.powi(-4));
+        assert_eq!(four.likely_seed_len, Some(4));
+        assert_eq!(four.warning, None);
+
+        let hard = short_seed_estimate(58f64.powi(-7));
+        assert_eq!(hard.likely_seed_len, Some(7));
+        assert!(hard.warning.unwrap().contains("up to 4 characters"));
+        let impossible = short_seed_estimate(58f64.powi(-20));
+        assert_eq!(impossible.likely_seed_len, None);
+        assert!(impossible.warning.unwrap().contains("run out"));
+    }
+}
@@ -0,0 +1,290 @@
+//! Squads v4 vault grinding: the vanity address is the multisig's vault,
+//! two program derivations away from a create key the search generates.
+
+use alloc::string::{String, ToString};
+
+use serde::Serialize;
+
+use crate::derive::{encode_pubkey, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::keys::{encode_keypair, pubkey_from_secret, secret_from_counter};
+use crate::matcher::MatchType;
+use crate::pda::find_program_address;
+
+/// The Squads v4 multisig program on mainnet and devnet.
+pub const SQUADS_PROGRAM_ID: &str = "SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf";
+
+const SEED_PREFIX: &[u8] = b"multisig";
+const SEED_MULTISIG: &[u8] = b"multisig";
+const SEED_VAULT: &[u8] = b"vault";
+
+/// The multisig PDA for `create_key`, with its bump.
+pub fn multisig_address(
+    program_id: &[u8; PUBKEY_LEN],
+    create_key: &[u8; PUBKEY_LEN],
+) -> ([u8; PUBKEY_LEN], u8) {
+    find_program_address(&[SEED_PREFIX, SEED_MULTISIG, create_key], program_id)
+        .expect("no bump takes the multisig address off the curve")
+}
+
+/// The vault PDA at `vault_index` of `multisig`, with its bump.
+pub fn vault_address(
+    program_id: &[u8; PUBKEY_LEN],
+    multisig: &[u8; PUBKEY_LEN],
+    vault_index: u8,
+) -> ([u8; PUBKEY_LEN], u8) {
+    find_program_address(
+        &[SEED_PREFIX, multisig, SEED_VAULT, &[vault_index]],
+        program_id,
+    )
+    .expect("no bump takes the vault address off the curve")
+}
+
+/// A vault found by a [`SquadsSearcher`]. Creating the multisig needs the
+/// create key to sign, so its keypair is part of the result.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct SquadsMatch {
+    /// Base58 of the 64-byte keypair, as wallets import it.
+    pub create_key_secret: String,
+    pub create_key: String,
+    pub multisig: String,
+    pub multisig_bump: u8,
+    pub vault: String,
+    pub vault_bump: u8,
+    pub vault_index: u8,
+    pub attempts: u64,
+    pub counter: u64,
+}
+
+/// Walks counters like [`crate::Searcher`], but each counter becomes a
+/// create key (through [`secret_from_counter`]), and the address matched is
+/// the vault of the multisig that key would create. Each attempt costs a
+/// scalar multiplication and two PDA searches, so expect a far lower rate
+/// than seed grinding.
+#[derive(Clone)]
+pub struct SquadsSearcher {
+    program_id: [u8; PUBKEY_LEN],
+    vault_index: u8,
+    master_secret: [u8; 32],
+    match_type: MatchType,
+    case_insensitive: bool,
+    count: u64,
+    count_offset: u64,
+}
+
+impl SquadsSearcher {
+    /// Fails on an empty or unmatchable pattern, as [`crate::Searcher::new`]
+    /// does. `master_secret` seeds every create key: keep it as secret as
+    /// the keys themselves.
+    pub fn new(
+        program_id: &[u8; PUBKEY_LEN],
+        vault_index: u8,
+        master_secret: &[u8; 32],
+        match_type: MatchType,
+        case_insensitive: bool,
+        count_offset: u64,
+    ) -> Result<SquadsSearcher, VanityError> {
+        if match_type.is_empty() {
+            return Err(VanityError::EmptyPattern);
+        }
+        match_type.validate(case_insensitive)?;
+        Ok(SquadsSearcher {
+            program_id: *program_id,
+            vault_index,
+            master_secret: *master_secret,
+            match_type,
+            case_insensitive,
+            count: 0,
+            count_offset,
+        })
+    }
+
+    /// The derivation for one counter, matched or not.
+    pub fn derive(&self, counter: u64) -> SquadsMatch {
+        let secret = secret_from_counter(&self.master_secret, counter);
+        let create_key = pubkey_from_secret(&secret);
+        let (multisig, multisig_bump) = multisig_address(&self.program_id, &create_key);
+        let (vault, vault_bump) = vault_address(&self.program_id, &multisig, self.vault_index);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        SquadsMatch {
+            create_key_secret: encode_keypair(&secret, &create_key),
+            create_key: encode_pubkey(&create_key, &mut buf).to_string(),
+            multisig: encode_pubkey(&multisig, &mut buf).to_string(),
+            multisig_bump,
+            vault: encode_pubkey(&vault, &mut buf).to_string(),
+            vault_bump,
+            vault_index: self.vault_index,
+            attempts: self.count,
+            counter,
+        }
+    }
+
+    /// Tries up to `batch_size` more create keys, stopping at the first
+    /// match or the end of the counter space.
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<SquadsMatch> {
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for _ in 0..batch_size {
+            let counter = self.absolute_position()?;
+            let secret = secret_from_counter(&self.master_secret, counter);
+            let (multisig, _) = multisig_address(&self.program_id, &pubkey_from_secret(&secret));
+            let (vault, _) = vault_address(&self.program_id, &multisig, self.vault_index);
+            let address = encode_pubkey(&vault, &mut encoded_buf);
+            self.count += 1;
+
+            if self
+                .match_type
+                .matches(address.as_bytes(), self.case_insensitive)
+            {
+                // Only matches pay for the encoding of every field.
+                return Some(SquadsMatch {
+                    attempts: self.count,
+                    ..self.derive(counter)
+                });
+            }
+        }
+        None
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// The next counter to try, or `None` once the counter space is used up.
+    pub fn absolute_position(&self) -> Option<u64> {
+        self.count_offset.checked_add(self.count)
+    }
+
+    /// Whether the counter space is used up.
+    pub fn is_exhausted(&self) -> bool {
+        self.absolute_position().is_none()
+    }
+
+    pub fn vault_index(&self) -> u8 {
+        self.vault_index
+    }
+
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.match_type.probability(self.case_insensitive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::KEYPAIR_LEN;
+    use solana_pubkey::Pubkey;
+
+    fn program_id() -> [u8; PUBKEY_LEN] {
+        crate::Pubkey::from_base58(SQUADS_PROGRAM_ID)
+            .unwrap()
+            .to_bytes()
+    }
+
+    fn searcher(pattern: &str, vault_index: u8) -> SquadsSearcher {
+        let match_type = MatchType::Prefix(pattern.into());
+        SquadsSearcher::new(&program_id(), vault_index, &[5; 32], match_type, false, 0).unwrap()
+    }
+
+    #[test]
+    fn test_seed_layout_matches_the_reference_derivation() {
+        let program = Pubkey::new_from_array(program_id());
+        for (create_key, vault_index) in [([1u8; 32], 0u8), ([2; 32], 0), ([2; 32], 3)] {
+            let (multisig, multisig_bump) =
+                Pubkey::find_program_address(&[b"multisig", b"multisig", &create_key], &program);
+            let (vault, vault_bump) = Pubkey::find_program_address(
+                &[b"multisig", multisig.as_ref(), b"vault", &[vault_index]],
+                &program,
+            );
+            assert_eq!(
+                multisig_address(&program_id(), &create_key),
+                (multisig.to_bytes(), multisig_bump)
+            );
+            assert_eq!(
+                vault_address(&program_id(), &multisig.to_bytes(), vault_index),
+                (vault.to_bytes(), vault_bump)
+            );
+        }
+    }
+
+    #[test]
+    fn test_known_vault() {
+        let found = searcher("A", 0).derive(0);
+        assert_eq!(found.counter, 0);
+        // Pinned, so a change to the key or seed scheme can't go unnoticed.
+        assert_eq!(
+            found.create_key,
+            "7DLVs2A4RQJDwphjLoiDhMUQMFZQxPYABrTq21vZaiUx"
+        );
+        assert_eq!(
+            found.multisig,
+            "GgJA6NvNsWLGZsz8tz3nEqoJyiLT2pCzPtsD415KYTCR"
+        );
+        assert_eq!(found.multisig_bump, 253);
+        assert_eq!(found.vault, "7A3vCsygGMGBDUW7GCvbnJErnHFLaUd9SWS3dPpcNpL4");
+        assert_eq!(found.vault_bump, 255);
+
+        let program = Pubkey::new_from_array(program_id());
+        let create_key = crate::Pubkey::from_base58(&found.create_key)
+            .unwrap()
+            .to_bytes();
+        let (multisig, multisig_bump) =
+            Pubkey::find_program_address(&[b"multisig", b"multisig", &create_key], &program);
+        let (vault, vault_bump) = Pubkey::find_program_address(
+            &[b"multisig", multisig.as_ref(), b"vault", &[0]],
+            &program,
+        );
+        assert_eq!(found.multisig, multisig.to_string());
+        assert_eq!(found.multisig_bump, multisig_bump);
+        assert_eq!(found.vault, vault.to_string());
+        assert_eq!(found.vault_bump, vault_bump);
+
+        // The keypair holds the secret the create key came from.
+        let keypair = crate::decode_base58(&found.create_key_secret).unwrap();
+        assert_eq!(keypair.len(), KEYPAIR_LEN);
+        let secret: [u8; 32] = keypair[..32].try_into().unwrap();
+        assert_eq!(secret, secret_from_counter(&[5; 32], 0));
+        assert_eq!(pubkey_from_secret(&secret), create_key);
+        assert_eq!(keypair[32..], create_key);
+    }
+
+    #[test]
+    fn test_finds_a_matching_vault() {
+        let mut searcher = searcher("AB", 1);
+        let found = loop {
+            if let Some(found) = searcher.search_batch(500) {
+                break found;
+            }
+        };
+        assert!(found.vault.starts_with("AB"));
+        assert_eq!(found.vault_index, 1);
+        assert_eq!(found.counter, found.attempts - 1);
+        assert_eq!(
+            SquadsMatch {
+                attempts: found.attempts,
+                ..searcher.derive(found.counter)
+            },
+            found
+        );
+        // No earlier counter matches.
+        assert!((0..found.counter).all(|counter| !searcher.derive(counter).vault.starts_with("AB")));
+        // Another vault index is another address.
+        assert_ne!(
+            self::searcher("AB", 0).derive(found.counter).vault,
+            found.vault
+        );
+    }
+
+    #[test]
+    fn test_pattern_is_validated() {
+        let new = |prefix: Option<&str>| {
+            let match_type = MatchType::new(prefix.map(Into::into), None, false);
+            SquadsSearcher::new(&program_id(), 0, &[5; 32], match_type, false, 0).err()
+        };
+        assert_eq!(new(None), Some(VanityError::EmptyPattern));
+        assert_eq!(
+            new(Some("0x")).map(|e| e.kind()),
+            Some(crate::ErrorKind::InvalidPatternChar)
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! Fallback patterns: "ideally prefix SoLaNa, but after 50M attempts take
+//! SoLan, and after 150M take SoLa", for users who would rather settle
+//! than wait forever.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::derivation::{Candidate, CreateWithSeedDerivation, Derivation};
+use crate::derive::{encode_pubkey, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::matcher::AddressMatcher;
+use crate::patterns::{check_pattern_list, PatternEntry};
+use crate::seed::seed_to_string;
+
+/// One tier of a [`TieredSearcher`]: a pattern accepted once the search
+/// has made `after_attempts` attempts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct PatternTier {
+    /// An empty label is replaced by the entry's directives, as
+    /// [`crate::parse_pattern_list`] names unlabelled lines.
+    pub pattern: PatternEntry,
+    /// Attempts, counted from the searcher's start, before the tier is
+    /// accepted. Always 0 for the first tier.
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub after_attempts: u64,
+}
+
+/// A match found by a [`TieredSearcher`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct TieredMatch {
+    pub address: String,
+    pub seed: String,
+    pub attempts: u64,
+    pub counter: u64,
+    /// Best tier the address satisfies; every better tier is accepted
+    /// whenever a worse one is, so this is also the tier it was accepted
+    /// for.
+    pub tier: u32,
+    pub label: String,
+    /// Every tier the address satisfies, best first, including ones not
+    /// accepted yet, e.g. to show that a tier-0 hit would also have done
+    /// for tier 2.
+    pub satisfied: Vec<u32>,
+    /// Tiers accepted when the match was found.
+    pub active_tiers: u32,
+}
+
+/// Walks counters like [`crate::Searcher`], accepting an address that
+/// matches any tier accepted so far. Tiers are accepted strictly in order,
+/// so the ones being matched are always the first few: the loop only ever
+/// sees those, and switching happens between runs of counters, never per
+/// attempt.
+#[derive(Clone)]
+pub struct TieredSearcher {
+    derivation: CreateWithSeedDerivation,
+    tiers: Vec<PatternTier>,
+    matchers: Vec<AddressMatcher>,
+    /// Tiers accepted: `matchers[..active]`.
+    active: usize,
+    count: u64,
+    count_offset: u64,
+}
+
+impl TieredSearcher {
+    /// Fails with `InvalidOptions` unless there is at least one tier, the
+    /// first with an `after_attempts` of 0 and none earlier than the one
+    /// before it, or as [`check_pattern_list`] does for the patterns, with
+    /// the tier's 1-based index as the line.
+    pub fn new(
+        base_pubkey: &[u8; PUBKEY_LEN],
+        owner_pubkey: &[u8; PUBKEY_LEN],
+        mut tiers: Vec<PatternTier>,
+        count_offset: u64,
+    ) -> Result<TieredSearcher, VanityError> {
+        let Some(first) = tiers.first() else {
+            return Err(VanityError::InvalidOptions(
+                "at least one pattern tier is required".into(),
+            ));
+        };
+        if first.after_attempts != 0 {
+            return Err(VanityError::InvalidOptions(format!(
+                "the first tier is accepted from the start, so its after_attempts must be 0, \
+                 got {}",
+                first.after_attempts
+            )));
+        }
+        if let Some(index) = tiers
+            .windows(2)
+            .position(|pair| pair[1].after_attempts < pair[0].after_attempts)
+        {
+            return Err(VanityError::InvalidOptions(format!(
+                "tier {} is accepted after {} attempts, before tier {} at {}",
+                index + 1,
+                tiers[index + 1].after_attempts,
+                index,
+                tiers[index].after_attempts
+            )));
+        }
+        for tier in &mut tiers {
+            if tier.pattern.label.is_empty() {
+                tier.pattern.label = tier.pattern.directives();
+            }
+        }
+        let entries: Vec<PatternEntry> = tiers.iter().map(|tier| tier.pattern.clone()).collect();
+        check_pattern_list(&entries)?;
+        let matchers = entries
+            .iter()
+            .map(|entry| {
+                AddressMatcher::with_match_type(entry.match_type(), entry.case_insensitive)
+            })
+            .collect();
+        let mut searcher = TieredSearcher {
+            derivation: CreateWithSeedDerivation::new(base_pubkey, owner_pubkey),
+            tiers,
+            matchers,
+            active: 0,
+            count: 0,
+            count_offset,
+        };
+        searcher.accept_due_tiers();
+        Ok(searcher)
+    }
+
+    fn accept_due_tiers(&mut self) {
+        while self
+            .tiers
+            .get(self.active)
+            .is_some_and(|tier| tier.after_attempts <= self.count)
+        {
+            self.active += 1;
+        }
+    }
+
+    /// Tries up to `batch_size` more counters, stopping at the first match
+    /// or the end of the counter space. Tiers that come due mid-batch are
+    /// accepted from the attempt they come due on.
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<TieredMatch> {
+        let mut remaining = u64::from(batch_size);
+        while remaining > 0 && !self.is_exhausted() {
+            self.accept_due_tiers();
+            let run = match self.tiers.get(self.active) {
+                Some(next) => remaining.min(next.after_attempts - self.count),
+                None => remaining,
+            };
+            remaining -= run;
+            if let Some(found) = self.search_active(run) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Tries up to `run` counters against the accepted tiers, which don't
+    /// change meanwhile.
+    fn search_active(&mut self, run: u64) -> Option<TieredMatch> {
+        let mut candidate = Candidate::default();
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let active = &self.matchers[..self.active];
+        for _ in 0..run {
+            let counter = self.count_offset.checked_add(self.count)?;
+            self.derivation.candidate(counter, &mut candidate);
+            let address = encode_pubkey(&candidate.pubkey, &mut encoded_buf);
+            self.count += 1;
+            let Some(tier) = active.iter().position(|matcher| matcher.matches(address)) else {
+                continue;
+            };
+            let satisfied = (tier..self.matchers.len())
+                .filter(|&index| self.matchers[index].matches(address))
+                .map(|index| index as u32)
+                .collect();
+            return Some(TieredMatch {
+                address: address.to_string(),
+                seed: seed_to_string(&candidate.artifacts),
+                attempts: self.count,
+                counter,
+                tier: tier as u32,
+                label: self.tiers[tier].pattern.label.clone(),
+                satisfied,
+                active_tiers: self.active as u32,
+            });
+        }
+        None
+    }
+
+    /// The tiers, empty labels filled in.
+    pub fn tiers(&self) -> &[PatternTier] {
+        &self.tiers
+    }
+
+    /// Tiers accepted so far, at least 1.
+    pub fn active_tiers(&self) -> usize {
+        self.active
+    }
+
+    /// Attempt count at which the next tier is accepted, or `None` once
+    /// every tier is.
+    pub fn next_tier_at(&self) -> Option<u64> {
+        self.tiers.get(self.active).map(|tier| tier.after_attempts)
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// Counter the next attempt will use, or `None` once the counter space
+    /// is used up.
+    pub fn absolute_position(&self) -> Option<u64> {
+        self.count_offset.checked_add(self.count)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.absolute_position().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::{Deriver, MAX_ENCODED_LEN};
+    use crate::error::ErrorKind;
+    use crate::seed::generate_seed_from_counter;
+
+    fn tier(prefix: &str, after_attempts: u64) -> PatternTier {
+        PatternTier {
+            pattern: PatternEntry {
+                prefix: Some(prefix.into()),
+                ..PatternEntry::default()
+            },
+            after_attempts,
+        }
+    }
+
+    fn address(counter: u64) -> String {
+        let pubkey = Deriver::new(&[1; 32], &[4; 32]).derive(&generate_seed_from_counter(counter));
+        let mut buf = [0u8; MAX_ENCODED_LEN];
+        encode_pubkey(&pubkey, &mut buf).to_string()
+    }
+
+    #[test]
+    fn test_fallback_tier_waits_for_its_threshold() {
+        let first_a = (0..).find(|&c| address(c).starts_with('A')).unwrap();
+        let threshold = first_a + 50;
+        // Nothing but the fallback is realistically matched here.
+        let tiers = alloc::vec![tier("zzzzzzzz", 0), tier("A", threshold)];
+        let mut searcher = TieredSearcher::new(&[1; 32], &[4; 32], tiers, 0).unwrap();
+        assert_eq!(searcher.active_tiers(), 1);
+        assert_eq!(searcher.next_tier_at(), Some(threshold));
+
+        // first_a only matches the fallback, so it's passed over.
+        assert_eq!(searcher.search_batch(threshold as u32), None);
+        assert_eq!(searcher.attempts(), threshold);
+        let found = searcher.search_batch(10_000).unwrap();
+        assert!(found.counter >= threshold);
+        assert!(found.address.starts_with('A'));
+        assert_eq!(found.address, address(found.counter));
+        assert_eq!((found.tier, found.active_tiers), (1, 2));
+        assert_eq!(found.label, "prefix:A");
+        assert_eq!(found.satisfied, [1]);
+        assert_eq!(searcher.next_tier_at(), None);
+
+        // In one batch the tier comes due partway through.
+        let tiers = alloc::vec![tier("zzzzzzzz", 0), tier("A", threshold)];
+        let mut searcher = TieredSearcher::new(&[1; 32], &[4; 32], tiers, 0).unwrap();
+        assert_eq!(searcher.search_batch(1_000_000), Some(found));
+
+        // Accepted from the start, it takes first_a.
+        let tiers = alloc::vec![tier("zzzzzzzz", 0), tier("A", 0)];
+        let mut searcher = TieredSearcher::new(&[1; 32], &[4; 32], tiers, 0).unwrap();
+        assert_eq!(searcher.search_batch(1_000_000).unwrap().counter, first_a);
+    }
+
+    #[test]
+    fn test_matches_list_every_tier_satisfied() {
+        let tiers = alloc::vec![tier("A", 0), tier("zzzzzzzz", 10), tier("A", 20)];
+        let tiers = tiers
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut tier)| {
+                tier.pattern.label = format!("tier-{index}");
+                tier
+            })
+            .collect();
+        let mut searcher = TieredSearcher::new(&[1; 32], &[4; 32], tiers, 5).unwrap();
+        let found = searcher.search_batch(1_000_000).unwrap();
+        assert_eq!((found.tier, found.label.as_str()), (0, "tier-0"));
+        assert_eq!(found.satisfied, [0, 2]);
+        assert_eq!(found.attempts, found.counter - 5 + 1);
+        assert_eq!(searcher.attempts(), found.attempts);
+    }
+
+    #[test]
+    fn test_tiers_are_validated() {
+        let kind = |tiers: Vec<PatternTier>| {
+            TieredSearcher::new(&[1; 32], &[4; 32], tiers, 0)
+                .err()
+                .map(|error| error.kind())
+        };
+        assert_eq!(kind(Vec::new()), Some(ErrorKind::InvalidOptions));
+        assert_eq!(
+            kind(alloc::vec![tier("A", 5)]),
+            Some(ErrorKind::InvalidOptions)
+        );
+        assert_eq!(
+            kind(alloc::vec![tier("A", 0), tier("B", 10), tier("C", 9)]),
+            Some(ErrorKind::InvalidOptions)
+        );
+        assert_eq!(
+            kind(alloc::vec![tier("A", 0), tier("0", 10)]),
+            Some(ErrorKind::InvalidPatternList)
+        );
+        // Default labels have to be unique too.
+        assert_eq!(
+            kind(alloc::vec![tier("A", 0), tier("A", 10)]),
+            Some(ErrorKind::InvalidPatternList)
+        );
+        assert_eq!(kind(alloc::vec![tier("A", 0), tier("B", 0)]), None);
+    }
+}
@@ -0,0 +1,618 @@
+//! Token-2022 mint grinding: a mint keypair whose address matches a
+//! pattern, with the account size the mint's extensions need worked out
+//! the way the Token-2022 program lays the account out.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::derive::{encode_pubkey, PUBKEY_LEN};
+use crate::error::VanityError;
+use crate::keys::{encode_keypair, pubkey_from_secret, secret_from_counter};
+use crate::matcher::MatchType;
+use crate::pubkey::Pubkey;
+use crate::rent::rent_exempt_lamports;
+
+/// The Token-2022 program.
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+/// Size of a mint without extensions.
+pub const MINT_LEN: usize = 82;
+
+/// Size of a token account without extensions. A mint with extensions is
+/// padded to it, so the account type byte sits at the same offset in both.
+const BASE_ACCOUNT_LEN: usize = 165;
+
+/// The account type byte after the padding.
+const ACCOUNT_TYPE_LEN: usize = 1;
+
+/// Type and length, 2 bytes each, in front of every extension's data.
+const TLV_HEADER_LEN: usize = 4;
+
+/// Size of a multisig account. The program tells account kinds apart by
+/// size, so an extended mint that would come out this long grows by 2.
+const MULTISIG_LEN: usize = 355;
+
+/// The mint extensions a [`MintLayout`] can size, numbered as the
+/// program's `ExtensionType`. Bit `1 << n` of an extension bitset stands
+/// for the extension numbered `n`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum MintExtension {
+    TransferFeeConfig = 1,
+    MintCloseAuthority = 3,
+    ConfidentialTransferMint = 4,
+    DefaultAccountState = 6,
+    NonTransferable = 9,
+    InterestBearingConfig = 10,
+    PermanentDelegate = 12,
+    TransferHook = 14,
+    ConfidentialTransferFeeConfig = 16,
+    MetadataPointer = 18,
+    GroupPointer = 20,
+    TokenGroup = 21,
+    GroupMemberPointer = 22,
+    TokenGroupMember = 23,
+    ScaledUiAmount = 25,
+    Pausable = 26,
+}
+
+impl MintExtension {
+    pub const ALL: [MintExtension; 16] = [
+        MintExtension::TransferFeeConfig,
+        MintExtension::MintCloseAuthority,
+        MintExtension::ConfidentialTransferMint,
+        MintExtension::DefaultAccountState,
+        MintExtension::NonTransferable,
+        MintExtension::InterestBearingConfig,
+        MintExtension::PermanentDelegate,
+        MintExtension::TransferHook,
+        MintExtension::ConfidentialTransferFeeConfig,
+        MintExtension::MetadataPointer,
+        MintExtension::GroupPointer,
+        MintExtension::TokenGroup,
+        MintExtension::GroupMemberPointer,
+        MintExtension::TokenGroupMember,
+        MintExtension::ScaledUiAmount,
+        MintExtension::Pausable,
+    ];
+
+    pub const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// Bytes of the extension's data, without its TLV header.
+    pub const fn data_len(self) -> usize {
+        match self {
+            // Two authorities, the withheld amount and two fee schedules.
+            MintExtension::TransferFeeConfig => 108,
+            MintExtension::MintCloseAuthority | MintExtension::PermanentDelegate => 32,
+            MintExtension::ConfidentialTransferMint => 65,
+            MintExtension::DefaultAccountState => 1,
+            MintExtension::NonTransferable => 0,
+            MintExtension::InterestBearingConfig => 52,
+            MintExtension::TransferHook
+            | MintExtension::MetadataPointer
+            | MintExtension::GroupPointer
+            | MintExtension::GroupMemberPointer => 64,
+            MintExtension::ConfidentialTransferFeeConfig => 129,
+            MintExtension::TokenGroup => 80,
+            MintExtension::TokenGroupMember => 72,
+            MintExtension::ScaledUiAmount => 56,
+            MintExtension::Pausable => 33,
+        }
+    }
+
+    /// Whether the extension is written by its own instruction after
+    /// `initializeMint2`, which grows the account to fit it: the account is
+    /// created without room for it, but with lamports for it.
+    pub const fn is_added_later(self) -> bool {
+        matches!(
+            self,
+            MintExtension::TokenGroup | MintExtension::TokenGroupMember
+        )
+    }
+
+    /// The pointer that has to point at the mint itself for the extension
+    /// to be stored in it.
+    const fn pointer(self) -> Option<MintExtension> {
+        match self {
+            MintExtension::TokenGroup => Some(MintExtension::GroupPointer),
+            MintExtension::TokenGroupMember => Some(MintExtension::GroupMemberPointer),
+            _ => None,
+        }
+    }
+}
+
+/// One extension as listed for a UI building a bitset.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct MintExtensionInfo {
+    pub extension: MintExtension,
+    pub bit: u32,
+    pub data_len: u32,
+    pub added_later: bool,
+}
+
+/// Every [`MintExtension`], in `ExtensionType` order.
+pub fn mint_extensions() -> Vec<MintExtensionInfo> {
+    MintExtension::ALL
+        .into_iter()
+        .map(|extension| MintExtensionInfo {
+            extension,
+            bit: extension.bit(),
+            data_len: extension.data_len() as u32,
+            added_later: extension.is_added_later(),
+        })
+        .collect()
+}
+
+/// Bytes of a Token-2022 `TokenMetadata` stored in the mint: update
+/// authority and mint, then borsh strings (a 4-byte length each) and the
+/// additional key-value pairs.
+pub fn token_metadata_len(
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    additional_metadata: &[(String, String)],
+) -> usize {
+    let string = |s: &str| 4 + s.len();
+    2 * PUBKEY_LEN
+        + string(name)
+        + string(symbol)
+        + string(uri)
+        + 4
+        + additional_metadata
+            .iter()
+            .map(|(key, value)| string(key) + string(value))
+            .sum::<usize>()
+}
+
+/// The extension set of a mint, checked against the program's rules for
+/// which extensions go together.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MintLayout {
+    bits: u32,
+    token_metadata_len: Option<usize>,
+}
+
+impl MintLayout {
+    /// `bits` is a set of [`MintExtension::bit`]s and `token_metadata_len`
+    /// the [`token_metadata_len`] of metadata stored in the mint, if any.
+    /// Fails with `InvalidOptions` for a bit that is no mint extension, for
+    /// a combination `initializeMint2` or a later instruction would reject,
+    /// or for group data or metadata without its pointer.
+    pub fn new(bits: u32, token_metadata_len: Option<usize>) -> Result<MintLayout, VanityError> {
+        let known = MintExtension::ALL
+            .into_iter()
+            .fold(0, |bits, extension| bits | extension.bit());
+        if bits & !known != 0 {
+            return Err(VanityError::InvalidOptions(format!(
+                "extension bits {:#x} are not mint extensions",
+                bits & !known
+            )));
+        }
+        let layout = MintLayout {
+            bits,
+            token_metadata_len,
+        };
+        use MintExtension::*;
+        let has = |extension| layout.contains(extension);
+        let confidential_fees = has(ConfidentialTransferFeeConfig);
+        if confidential_fees != (has(TransferFeeConfig) && has(ConfidentialTransferMint)) {
+            return Err(VanityError::InvalidOptions(
+                "confidential transfers with transfer fees need all of transfer_fee_config, \
+                 confidential_transfer_mint and confidential_transfer_fee_config"
+                    .into(),
+            ));
+        }
+        if has(ScaledUiAmount) && has(InterestBearingConfig) {
+            return Err(VanityError::InvalidOptions(
+                "scaled_ui_amount and interest_bearing_config can't be combined".into(),
+            ));
+        }
+        if let Some(extension) = layout
+            .extensions()
+            .find(|extension| extension.pointer().is_some_and(|pointer| !has(pointer)))
+        {
+            return Err(VanityError::InvalidOptions(format!(
+                "{extension:?} is only stored in the mint with its {:?}",
+                extension.pointer().unwrap()
+            )));
+        }
+        if token_metadata_len.is_some() && !has(MetadataPointer) {
+            return Err(VanityError::InvalidOptions(
+                "token metadata is only stored in the mint with a metadata_pointer".into(),
+            ));
+        }
+        Ok(layout)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn contains(&self, extension: MintExtension) -> bool {
+        self.bits & extension.bit() != 0
+    }
+
+    pub fn extensions(&self) -> impl Iterator<Item = MintExtension> + '_ {
+        MintExtension::ALL
+            .into_iter()
+            .filter(|&extension| self.contains(extension))
+    }
+
+    pub fn token_metadata_len(&self) -> Option<usize> {
+        self.token_metadata_len
+    }
+
+    /// Bytes to create the account with: what `initializeMint2` expects,
+    /// i.e. room for every extension initialized before it.
+    pub fn space(&self) -> usize {
+        account_len(
+            self.extensions()
+                .filter(|extension| !extension.is_added_later())
+                .map(MintExtension::data_len),
+        )
+    }
+
+    /// Bytes the account ends up with once group data and metadata have
+    /// been added, in that order, each growing it by its TLV entry. The
+    /// account has to hold rent for this from the start.
+    pub fn rent_space(&self) -> usize {
+        self.extensions()
+            .filter(|extension| extension.is_added_later())
+            .map(MintExtension::data_len)
+            .chain(self.token_metadata_len)
+            .fold(self.space(), |len, data_len| {
+                adjust_for_multisig(len + TLV_HEADER_LEN + data_len)
+            })
+    }
+
+    pub fn rent_exempt_lamports(&self) -> u64 {
+        rent_exempt_lamports(self.rent_space())
+    }
+}
+
+/// The program's account length for extensions with these data lengths.
+fn account_len(data_lens: impl Iterator<Item = usize>) -> usize {
+    let mut data_lens = data_lens.peekable();
+    if data_lens.peek().is_none() {
+        return MINT_LEN;
+    }
+    let tlv_len: usize = data_lens.map(|len| TLV_HEADER_LEN + len).sum();
+    adjust_for_multisig(BASE_ACCOUNT_LEN + ACCOUNT_TYPE_LEN + tlv_len)
+}
+
+fn adjust_for_multisig(len: usize) -> usize {
+    if len == MULTISIG_LEN {
+        len + 2
+    } else {
+        len
+    }
+}
+
+/// A mint found by a [`Token2022MintSearcher`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Token2022Match {
+    /// Base58 of the mint's 64-byte keypair, as wallets import it. It signs
+    /// the `createAccount`.
+    pub mint_secret: String,
+    pub mint: String,
+    /// The extension bitset the sizes are for.
+    pub extensions: u32,
+    /// [`MintLayout::space`].
+    pub space: u64,
+    /// [`MintLayout::rent_space`].
+    pub rent_space: u64,
+    /// Rent exemption for `rent_space`.
+    pub rent_exempt_lamports: u64,
+    pub attempts: u64,
+    pub counter: u64,
+}
+
+/// `SystemProgram.createAccount` parameters, named as web3.js names them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAccountParams {
+    pub from_pubkey: String,
+    pub new_account_pubkey: String,
+    pub lamports: u64,
+    pub space: u64,
+    pub program_id: String,
+}
+
+/// `createInitializeMint2Instruction` parameters, named as
+/// `@solana/spl-token` names them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeMint2Params {
+    pub mint: String,
+    pub decimals: u8,
+    pub mint_authority: String,
+    /// Unset for a mint nobody can freeze accounts of.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub freeze_authority: Option<String>,
+    pub program_id: String,
+}
+
+/// The two instructions that create a mint. Each extension's own
+/// initialize instruction goes between them, and group data or metadata
+/// after them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct MintInstructionParams {
+    pub create_account: CreateAccountParams,
+    pub initialize_mint2: InitializeMint2Params,
+}
+
+impl Token2022Match {
+    /// Fails as [`Pubkey::from_base58`] does for a key that isn't a base58
+    /// pubkey.
+    pub fn instruction_params(
+        &self,
+        payer: &str,
+        mint_authority: &str,
+        freeze_authority: Option<&str>,
+        decimals: u8,
+    ) -> Result<MintInstructionParams, VanityError> {
+        let checked = |key: &str| Pubkey::from_base58(key).map(|_| key.to_string());
+        Ok(MintInstructionParams {
+            create_account: CreateAccountParams {
+                from_pubkey: checked(payer)?,
+                new_account_pubkey: self.mint.clone(),
+                lamports: self.rent_exempt_lamports,
+                space: self.space,
+                program_id: TOKEN_2022_PROGRAM_ID.into(),
+            },
+            initialize_mint2: InitializeMint2Params {
+                mint: self.mint.clone(),
+                decimals,
+                mint_authority: checked(mint_authority)?,
+                freeze_authority: freeze_authority.map(checked).transpose()?,
+                program_id: TOKEN_2022_PROGRAM_ID.into(),
+            },
+        })
+    }
+}
+
+/// Walks counters like [`crate::Searcher`], but each counter becomes a
+/// mint keypair (through [`secret_from_counter`]) and the mint address is
+/// matched; the layout only shapes the results.
+#[derive(Clone)]
+pub struct Token2022MintSearcher {
+    master_secret: [u8; 32],
+    match_type: MatchType,
+    case_insensitive: bool,
+    layout: MintLayout,
+    count: u64,
+    count_offset: u64,
+}
+
+impl Token2022MintSearcher {
+    /// Fails on an empty or unmatchable pattern, as [`crate::Searcher::new`]
+    /// does. `master_secret` seeds every mint key: keep it as secret as the
+    /// keys themselves.
+    pub fn new(
+        master_secret: &[u8; 32],
+        match_type: MatchType,
+        case_insensitive: bool,
+        layout: MintLayout,
+        count_offset: u64,
+    ) -> Result<Token2022MintSearcher, VanityError> {
+        if match_type.is_empty() {
+            return Err(VanityError::EmptyPattern);
+        }
+        match_type.validate(case_insensitive)?;
+        Ok(Token2022MintSearcher {
+            master_secret: *master_secret,
+            match_type,
+            case_insensitive,
+            layout,
+            count: 0,
+            count_offset,
+        })
+    }
+
+    /// The mint for one counter, matched or not.
+    pub fn derive(&self, counter: u64) -> Token2022Match {
+        let secret = secret_from_counter(&self.master_secret, counter);
+        let mint = pubkey_from_secret(&secret);
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        Token2022Match {
+            mint_secret: encode_keypair(&secret, &mint),
+            mint: encode_pubkey(&mint, &mut buf).to_string(),
+            extensions: self.layout.bits(),
+            space: self.layout.space() as u64,
+            rent_space: self.layout.rent_space() as u64,
+            rent_exempt_lamports: self.layout.rent_exempt_lamports(),
+            attempts: self.count,
+            counter,
+        }
+    }
+
+    /// Tries up to `batch_size` more mints, stopping at the first match or
+    /// the end of the counter space.
+    pub fn search_batch(&mut self, batch_size: u32) -> Option<Token2022Match> {
+        let mut encoded_buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        for _ in 0..batch_size {
+            let counter = self.absolute_position()?;
+            let mint = pubkey_from_secret(&secret_from_counter(&self.master_secret, counter));
+            let address = encode_pubkey(&mint, &mut encoded_buf);
+            self.count += 1;
+            if self
+                .match_type
+                .matches(address.as_bytes(), self.case_insensitive)
+            {
+                return Some(self.derive(counter));
+            }
+        }
+        None
+    }
+
+    pub fn layout(&self) -> &MintLayout {
+        &self.layout
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.count
+    }
+
+    /// The next counter to try, or `None` once the counter space is used up.
+    pub fn absolute_position(&self) -> Option<u64> {
+        self.count_offset.checked_add(self.count)
+    }
+
+    /// Whether the counter space is used up.
+    pub fn is_exhausted(&self) -> bool {
+        self.absolute_position().is_none()
+    }
+
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.match_type.probability(self.case_insensitive)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use MintExtension::*;
+
+    fn layout(extensions: &[MintExtension], metadata_len: Option<usize>) -> MintLayout {
+        let bits = extensions.iter().fold(0, |bits, e| bits | e.bit());
+        MintLayout::new(bits, metadata_len).unwrap()
+    }
+
+    #[test]
+    fn test_space_matches_the_program_layout() {
+        // What `getMintLen` in @solana/spl-token gives for each set.
+        for (extensions, space) in [
+            (&[][..], 82),
+            (&[MetadataPointer][..], 234),
+            (&[TransferFeeConfig][..], 278),
+            (&[MintCloseAuthority][..], 202),
+            (&[NonTransferable][..], 170),
+            (&[TransferFeeConfig, InterestBearingConfig][..], 334),
+            (
+                &[GroupPointer, GroupMemberPointer, MetadataPointer][..],
+                370,
+            ),
+            (
+                &[PermanentDelegate, TransferHook, DefaultAccountState][..],
+                275,
+            ),
+            // 355 bytes would pass for a multisig, so 2 more are added.
+            (
+                &[
+                    TransferFeeConfig,
+                    MintCloseAuthority,
+                    Pausable,
+                    NonTransferable,
+                ][..],
+                357,
+            ),
+        ] {
+            let layout = layout(extensions, None);
+            assert_eq!(layout.space(), space, "{extensions:?}");
+            assert_eq!(layout.rent_space(), space, "{extensions:?}");
+        }
+        assert_eq!(layout(&[], None).rent_exempt_lamports(), 1_461_600);
+        assert_eq!(
+            layout(&[MetadataPointer], None).rent_exempt_lamports(),
+            2_519_520
+        );
+    }
+
+    #[test]
+    fn test_data_added_later_only_counts_for_rent() {
+        let metadata = token_metadata_len("Vanity", "VNTY", "https://example.com/v.json", &[]);
+        assert_eq!(metadata, 64 + 10 + 8 + 30 + 4);
+        let with_metadata = layout(&[MetadataPointer], Some(metadata));
+        assert_eq!(with_metadata.space(), 234);
+        assert_eq!(with_metadata.rent_space(), 234 + 4 + metadata);
+
+        let pairs = [("twitter".to_string(), "@vanity".to_string())];
+        let with_pairs = token_metadata_len("Vanity", "VNTY", "https://example.com/v.json", &pairs);
+        assert_eq!(with_pairs, metadata + 11 + 11);
+
+        let group = layout(&[GroupPointer, TokenGroup], None);
+        assert_eq!((group.space(), group.rent_space()), (234, 234 + 84));
+        let member = layout(&[GroupMemberPointer, TokenGroupMember], None);
+        assert_eq!((member.space(), member.rent_space()), (234, 234 + 76));
+    }
+
+    #[test]
+    fn test_invalid_combinations_are_rejected() {
+        let kind = |bits, metadata_len| MintLayout::new(bits, metadata_len).unwrap_err().kind();
+        assert_eq!(kind(1 << 2, None), ErrorKind::InvalidOptions);
+        assert_eq!(kind(TokenGroup.bit(), None), ErrorKind::InvalidOptions);
+        assert_eq!(kind(0, Some(100)), ErrorKind::InvalidOptions);
+        assert_eq!(
+            kind(ScaledUiAmount.bit() | InterestBearingConfig.bit(), None),
+            ErrorKind::InvalidOptions
+        );
+        assert_eq!(
+            kind(
+                TransferFeeConfig.bit() | ConfidentialTransferMint.bit(),
+                None
+            ),
+            ErrorKind::InvalidOptions
+        );
+        let confidential_fees = TransferFeeConfig.bit()
+            | ConfidentialTransferMint.bit()
+            | ConfidentialTransferFeeConfig.bit();
+        assert_eq!(
+            MintLayout::new(confidential_fees, None).unwrap().space(),
+            166 + 112 + 69 + 133
+        );
+        assert_eq!(mint_extensions().len(), MintExtension::ALL.len());
+    }
+
+    #[test]
+    fn test_mint_secret_signs_for_the_mint() {
+        let layout = layout(&[MetadataPointer], None);
+        let mut searcher =
+            Token2022MintSearcher::new(&[8; 32], MatchType::Prefix("A".into()), false, layout, 0)
+                .unwrap();
+        let found = loop {
+            if let Some(found) = searcher.search_batch(100) {
+                break found;
+            }
+        };
+        assert!(found.mint.starts_with('A'));
+        assert_eq!(found.attempts, found.counter + 1);
+        assert_eq!(
+            (found.space, found.extensions),
+            (234, MetadataPointer.bit())
+        );
+
+        let keypair = crate::decode_base58(&found.mint_secret).unwrap();
+        let secret: [u8; 32] = keypair[..32].try_into().unwrap();
+        let mint = crate::Pubkey::from_base58(&found.mint).unwrap().to_bytes();
+        assert_eq!(pubkey_from_secret(&secret), mint);
+        assert_eq!(keypair[32..], mint);
+
+        let mut buf = [0u8; five8::BASE58_ENCODED_32_MAX_LEN];
+        let payer = encode_pubkey(&[1; 32], &mut buf).to_string();
+        let params = found.instruction_params(&payer, &payer, None, 6).unwrap();
+        assert_eq!(params.create_account.new_account_pubkey, found.mint);
+        assert_eq!(params.create_account.lamports, 2_519_520);
+        assert_eq!(params.create_account.space, 234);
+        assert_eq!(params.initialize_mint2.program_id, TOKEN_2022_PROGRAM_ID);
+        assert_eq!(
+            found
+                .instruction_params("not a key", &payer, None, 6)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidBase58
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! One derivation laid open, step by step, for debugging and for showing
+//! how an address comes about. Filled in by [`crate::Searcher::trace`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::alphabet::HEX;
+use crate::matcher::MatchCheck;
+
+/// Every intermediate value of one `createWithSeed` derivation.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct DerivationTrace {
+    /// The counter traced, as results report it, in decimal: counters span
+    /// all of u64, past what a JS number holds exactly.
+    pub counter: String,
+    /// The counter the seed was generated from: `counter` mixed with the
+    /// seed namespace, or `counter` itself without one.
+    pub seed_counter: String,
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub seed_namespace: Option<String>,
+    pub seed: String,
+    pub seed_hex: String,
+    /// `base || seed || owner`, the 80 bytes SHA-256 hashes.
+    pub hash_input_hex: String,
+    /// The SHA-256 digest, which is the address's pubkey.
+    pub digest_hex: String,
+    pub address: String,
+    /// Whether the pubkey passed the byte mask; unset without one.
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub byte_mask_passed: Option<bool>,
+    /// Whether a search would report this address.
+    pub matches: bool,
+    /// Each part of the pattern, as `AddressMatcher::explain` reports it.
+    pub checks: Vec<MatchCheck>,
+    pub timing: TraceTiming,
+}
+
+/// How long each step took, in milliseconds, as read from the clock passed
+/// to [`crate::Searcher::trace`]. A single step is far below most clocks'
+/// resolution, so expect zeros.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct TraceTiming {
+    /// Seed generation and hashing.
+    pub derive_ms: f64,
+    /// Base58 encoding.
+    pub encode_ms: f64,
+    /// Byte mask and pattern checks.
+    pub match_ms: f64,
+}
+
+/// Lowercase hex, two digits per byte.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    let digits = HEX.chars();
+    bytes
+        .iter()
+        .flat_map(|&byte| {
+            [
+                digits[usize::from(byte >> 4)],
+                digits[usize::from(byte & 15)],
+            ]
+        })
+        .map(char::from)
+        .collect()
+}
@@ -0,0 +1,474 @@
+//! Work units for grinding one pattern across machines without a server:
+//! counter ranges of a search handed out as JSON, the completion records
+//! that come back, and checks on those records before their coverage and
+//! matches are trusted.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::VanityError;
+use crate::pubkey::{KeyRole, Pubkey};
+use crate::ranges::{merge_ranges, WorkRange};
+use crate::search::Searcher;
+use crate::seed::{seed_to_string, SeedScheme};
+use crate::trace::to_hex;
+
+/// `version` of every unit and record, bumped if the format changes.
+pub const WORK_UNIT_VERSION: u32 = 1;
+
+/// The search a team shares: `createWithSeed` addresses for the keys, as
+/// base58, matched against the pattern.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct WorkConfig {
+    pub base: String,
+    pub owner: String,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub suffix: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub case_insensitive: bool,
+    /// [`SeedScheme`] version to generate seeds with. Units written before
+    /// it was recorded don't have it, and are version 1.
+    #[serde(default = "legacy_seed_scheme")]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub seed_scheme: u32,
+}
+
+fn legacy_seed_scheme() -> u32 {
+    SeedScheme::V1.version()
+}
+
+impl WorkConfig {
+    /// Fails as [`Searcher::new`] does, with `InvalidBase` or
+    /// `InvalidOwner` for keys that aren't base58 pubkeys, or with
+    /// `InvalidOptions` for an unknown seed scheme.
+    pub fn searcher(&self) -> Result<Searcher, VanityError> {
+        let mut searcher = Searcher::new(
+            Pubkey::from_base58_for_role(&self.base, KeyRole::Base)?.as_bytes(),
+            Pubkey::from_base58_for_role(&self.owner, KeyRole::Owner)?.as_bytes(),
+            self.prefix.clone(),
+            self.suffix.clone(),
+            self.case_insensitive,
+            0,
+        )?;
+        searcher.set_seed_scheme(SeedScheme::from_version(self.seed_scheme)?)?;
+        Ok(searcher)
+    }
+
+    /// The fingerprint resume tokens carry ([`Searcher::config_fingerprint`]),
+    /// as 16 hex digits.
+    pub fn hash(&self) -> Result<String, VanityError> {
+        Ok(format!("{:016x}", self.searcher()?.config_fingerprint()))
+    }
+}
+
+/// One range of counters to search, and who it was given to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct WorkUnit {
+    pub version: u32,
+    pub config_hash: String,
+    pub config: WorkConfig,
+    /// Position among the units issued together.
+    pub index: u32,
+    pub range: WorkRange,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub issued_to: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub issued_at_ms: f64,
+}
+
+/// A match a record reports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct WorkMatch {
+    pub address: String,
+    pub seed: String,
+    pub counter: u64,
+}
+
+/// What running a [`WorkUnit`] produced. `digest` is a SHA-256 of the rest
+/// of the record, so corruption and careless edits show, but anyone can
+/// recompute it: [`collate_results`] also re-derives every match, and a
+/// record can't be trusted to have searched its range beyond that.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct WorkRecord {
+    pub version: u32,
+    pub config_hash: String,
+    pub config: WorkConfig,
+    pub index: u32,
+    pub range: WorkRange,
+    #[serde(default)]
+    #[cfg_attr(feature = "tsify", tsify(optional))]
+    pub issued_to: Option<String>,
+    /// Every match in the range, in counter order.
+    pub matches: Vec<WorkMatch>,
+    pub attempts: u64,
+    pub digest: String,
+}
+
+impl WorkRecord {
+    fn compute_digest(&self) -> String {
+        let mut hasher = Sha256::new()
+            .chain_update(b"web-vanity work record")
+            .chain_update(self.version.to_le_bytes())
+            .chain_update(self.config_hash.as_bytes())
+            .chain_update(self.index.to_le_bytes())
+            .chain_update(self.range.offset.to_le_bytes())
+            .chain_update(self.range.length.to_le_bytes())
+            .chain_update(self.attempts.to_le_bytes());
+        for found in &self.matches {
+            hasher.update(found.counter.to_le_bytes());
+            hasher.update((found.address.len() as u32).to_le_bytes());
+            hasher.update(found.address.as_bytes());
+            hasher.update(found.seed.as_bytes());
+        }
+        to_hex(&hasher.finalize())
+    }
+
+    /// Why the record can't be trusted for `config_hash`, if it can't.
+    fn problem(&self, config_hash: &str) -> Option<String> {
+        if self.version != WORK_UNIT_VERSION {
+            return Some(format!("unsupported version {}", self.version));
+        }
+        if self.config_hash != config_hash
+            || self.config.hash().ok().as_deref() != Some(config_hash)
+        {
+            return Some("record is for a different search".into());
+        }
+        if self.digest != self.compute_digest() {
+            return Some("digest doesn't match the record".into());
+        }
+        if self.attempts != self.range.length {
+            return Some(format!(
+                "searched {} of the range's {} counters",
+                self.attempts, self.range.length
+            ));
+        }
+        let Ok(mut searcher) = self.config.searcher() else {
+            return Some("configuration is unusable".into());
+        };
+        for found in &self.matches {
+            let end = self.range.end().unwrap_or(u64::MAX);
+            if !(self.range.offset..end).contains(&found.counter) {
+                return Some(format!(
+                    "match at counter {} is outside the range",
+                    found.counter
+                ));
+            }
+            let rederived = searcher.search_range(found.counter, found.counter + 1);
+            let genuine = rederived.matches.iter().any(|derived| {
+                derived.address == found.address && derived.seed.as_slice() == found.seed.as_bytes()
+            });
+            if !genuine {
+                return Some(format!(
+                    "match {} at counter {} doesn't re-derive",
+                    found.address, found.counter
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// `count` consecutive units of `unit_size` counters from `start`. Fails
+/// with `InvalidOptions` for an empty unit or count, or units that would
+/// wrap past the end of the counter space, and as [`WorkConfig::searcher`]
+/// for a bad configuration.
+pub fn create_work_units(
+    config: &WorkConfig,
+    start: u64,
+    unit_size: u64,
+    count: u32,
+    issued_to: Option<&str>,
+    issued_at_ms: f64,
+) -> Result<Vec<WorkUnit>, VanityError> {
+    if unit_size == 0 || count == 0 {
+        return Err(VanityError::InvalidOptions(
+            "work units need a size and a count of at least 1".into(),
+        ));
+    }
+    let total = unit_size.checked_mul(count as u64);
+    if total.and_then(|total| start.checked_add(total)).is_none() {
+        return Err(VanityError::InvalidOptions(format!(
+            "{count} units of {unit_size} counters from {start} wrap past the end of the counter space"
+        )));
+    }
+    let config_hash = config.hash()?;
+    Ok((0..count)
+        .map(|index| WorkUnit {
+            version: WORK_UNIT_VERSION,
+            config_hash: config_hash.clone(),
+            config: config.clone(),
+            index,
+            range: WorkRange {
+                offset: start + index as u64 * unit_size,
+                length: unit_size,
+            },
+            issued_to: issued_to.map(String::from),
+            issued_at_ms,
+        })
+        .collect())
+}
+
+/// Searches exactly the unit's range and records every match in it. Fails
+/// with `InvalidWorkUnit` if the unit's version or hash doesn't fit its
+/// configuration.
+pub fn run_work_unit(unit: &WorkUnit) -> Result<WorkRecord, VanityError> {
+    if unit.version != WORK_UNIT_VERSION {
+        return Err(VanityError::InvalidWorkUnit(format!(
+            "unsupported version {}",
+            unit.version
+        )));
+    }
+    let mut searcher = unit.config.searcher()?;
+    if unit.config.hash()? != unit.config_hash {
+        return Err(VanityError::InvalidWorkUnit(
+            "config hash doesn't match the configuration".into(),
+        ));
+    }
+    let end = unit.range.end().ok_or_else(|| {
+        VanityError::InvalidWorkUnit("range wraps past the end of the counter space".into())
+    })?;
+    let outcome = searcher.search_range(unit.range.offset, end);
+    let mut record = WorkRecord {
+        version: WORK_UNIT_VERSION,
+        config_hash: unit.config_hash.clone(),
+        config: unit.config.clone(),
+        index: unit.index,
+        range: unit.range,
+        issued_to: unit.issued_to.clone(),
+        matches: outcome
+            .matches
+            .into_iter()
+            .map(|derived| WorkMatch {
+                address: derived.address,
+                seed: seed_to_string(&derived.seed),
+                counter: derived.counter,
+            })
+            .collect(),
+        attempts: outcome.attempts,
+        digest: String::new(),
+    };
+    record.digest = record.compute_digest();
+    Ok(record)
+}
+
+/// A record [`collate_results`] turned down.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct RejectedRecord {
+    /// Position in the records given.
+    pub record: u32,
+    pub reason: String,
+}
+
+/// What [`collate_results`] made of a set of records.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct CollatedWork {
+    /// The search every accepted record belongs to.
+    pub config_hash: String,
+    /// Positions of the records that passed every check.
+    pub accepted: Vec<u32>,
+    pub rejected: Vec<RejectedRecord>,
+    /// Union of the accepted ranges, sorted and disjoint.
+    pub coverage: Vec<WorkRange>,
+    /// Counters in `coverage`.
+    pub covered: u64,
+    /// Matches of the accepted records, by counter, each once.
+    pub matches: Vec<WorkMatch>,
+}
+
+/// Checks records against the search of the first one and merges the ones
+/// that pass. A record is rejected, with the reason, if its version, config
+/// hash or digest is off, if it didn't search its whole range, or if a
+/// match it reports lies outside its range or doesn't re-derive from its
+/// counter. Fails with `InvalidWorkUnit` if there are no records or the
+/// first one's configuration is unusable.
+pub fn collate_results(records: &[WorkRecord]) -> Result<CollatedWork, VanityError> {
+    let first = records
+        .first()
+        .ok_or_else(|| VanityError::InvalidWorkUnit("no records to collate".into()))?;
+    let config_hash = first
+        .config
+        .hash()
+        .map_err(|error| VanityError::InvalidWorkUnit(format!("first record: {error}")))?;
+
+    let mut collated = CollatedWork {
+        config_hash,
+        accepted: Vec::new(),
+        rejected: Vec::new(),
+        coverage: Vec::new(),
+        covered: 0,
+        matches: Vec::new(),
+    };
+    let mut ranges = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        match record.problem(&collated.config_hash) {
+            Some(reason) => collated.rejected.push(RejectedRecord {
+                record: index as u32,
+                reason,
+            }),
+            None => {
+                collated.accepted.push(index as u32);
+                ranges.push(record.range);
+                collated.matches.extend(record.matches.iter().cloned());
+            }
+        }
+    }
+    collated.coverage = merge_ranges(&ranges)?;
+    collated.covered = collated.coverage.iter().map(|range| range.length).sum();
+    collated.matches.sort_by_key(|found| found.counter);
+    collated.matches.dedup_by_key(|found| found.counter);
+    Ok(collated)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::ErrorKind;
+
+    fn config() -> WorkConfig {
+        WorkConfig {
+            base: "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi".into(),
+            owner: "Vote111111111111111111111111111111111111111".into(),
+            prefix: Some("A".into()),
+            suffix: None,
+            case_insensitive: false,
+            seed_scheme: crate::SEED_SCHEME_VERSION,
+        }
+    }
+
+    #[test]
+    fn test_units_run_and_collate() {
+        let units = create_work_units(&config(), 1_000, 500, 3, Some("alice"), 1.7e12).unwrap();
+        assert_eq!(units.len(), 3);
+        assert_eq!(
+            units[2].range,
+            WorkRange {
+                offset: 2_000,
+                length: 500
+            }
+        );
+        assert_eq!(units[0].config_hash, config().hash().unwrap());
+        assert_eq!(units[0].config_hash.len(), 16);
+
+        let records: Vec<_> = units
+            .iter()
+            .map(|unit| run_work_unit(unit).unwrap())
+            .collect();
+        assert!(records.iter().all(|record| record.attempts == 500));
+        let found: usize = records.iter().map(|record| record.matches.len()).sum();
+        assert!(found > 0);
+
+        let collated = collate_results(&records).unwrap();
+        assert_eq!(collated.accepted, [0, 1, 2]);
+        assert!(collated.rejected.is_empty());
+        assert_eq!(
+            collated.coverage,
+            [WorkRange {
+                offset: 1_000,
+                length: 1_500
+            }]
+        );
+        assert_eq!(collated.covered, 1_500);
+        assert_eq!(collated.matches.len(), found);
+        assert!(collated.matches.iter().all(|m| m.address.starts_with('A')));
+    }
+
+    #[test]
+    fn test_tampered_records_are_rejected() {
+        let units = create_work_units(&config(), 0, 400, 4, None, 0.0).unwrap();
+        let mut records: Vec<_> = units
+            .iter()
+            .map(|unit| run_work_unit(unit).unwrap())
+            .collect();
+        let genuine = records[0].matches[0].clone();
+
+        // A claimed match that doesn't re-derive, digest recomputed.
+        records[1].matches.push(WorkMatch {
+            counter: 450,
+            ..genuine.clone()
+        });
+        records[1].digest = records[1].compute_digest();
+        // An edit without recomputing the digest.
+        records[2].attempts = 10;
+        // Another search's record.
+        let mut other = config();
+        other.prefix = Some("B".into());
+        records[3] =
+            run_work_unit(&create_work_units(&other, 1_200, 400, 1, None, 0.0).unwrap()[0])
+                .unwrap();
+
+        let collated = collate_results(&records).unwrap();
+        assert_eq!(collated.accepted, [0]);
+        let reasons: Vec<_> = collated
+            .rejected
+            .iter()
+            .map(|r| (r.record, r.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                (
+                    1,
+                    &*format!("match {} at counter 450 doesn't re-derive", genuine.address)
+                ),
+                (2, "digest doesn't match the record"),
+                (3, "record is for a different search"),
+            ]
+        );
+        assert_eq!(collated.covered, 400);
+
+        let error = collate_results(&[]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidWorkUnit);
+    }
+
+    #[test]
+    fn test_bad_units() {
+        let error = create_work_units(&config(), u64::MAX - 10, 10, 2, None, 0.0).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidOptions);
+        assert_eq!(
+            create_work_units(&config(), 0, 0, 2, None, 0.0)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidOptions
+        );
+
+        let mut unit = create_work_units(&config(), 0, 10, 1, None, 0.0)
+            .unwrap()
+            .remove(0);
+        unit.config.case_insensitive = true;
+        let error = run_work_unit(&unit).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidWorkUnit);
+        assert_eq!(
+            error.to_string(),
+            "invalid work unit: config hash doesn't match the configuration"
+        );
+
+        let unknown = WorkConfig {
+            seed_scheme: 99,
+            ..config()
+        };
+        assert_eq!(
+            create_work_units(&unknown, 0, 10, 1, None, 0.0)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidOptions
+        );
+    }
+}
@@ -1,4 +1,7 @@
-import init, { VanitySearcher } from "../../rust-vanity/pkg/rust_vanity.js";
+import init, {
+  VanitySearcher,
+  self_test,
+} from "../../rust-vanity/pkg/rust_vanity.js";
 
 let wasmInitialized = false;
 let searcher: VanitySearcher | null = null;
@@ -7,6 +10,8 @@ let shouldStop = false;
 async function initWasm() {
   if (!wasmInitialized) {
     await init();
+    // Throws if this build derives addresses wrongly; never grind with it.
+    self_test();
     wasmInitialized = true;
   }
 }